@@ -0,0 +1,111 @@
+/*
+ * C API for embedding the Ruffle Flash Player emulator.
+ *
+ * Players render in software to an RGBA framebuffer. A player must only be
+ * used from the thread that created it.
+ *
+ * If the player panics, the function returns NULL, zero or nothing, and
+ * ruffle_last_error describes what went wrong. A player that panicked should
+ * be destroyed, as later calls on it fail as well.
+ */
+
+#ifndef RUFFLE_H
+#define RUFFLE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RUFFLE_CAPI_VERSION 1
+
+typedef struct RufflePlayer RufflePlayer;
+
+/* Describes the panic of the last call on this thread, or returns NULL if it
+ * didn't panic. The string stays valid until the next call to the library. */
+const char *ruffle_last_error(void);
+
+/* Returns the version of the library's API, to compare with RUFFLE_CAPI_VERSION. */
+uint32_t ruffle_capi_version(void);
+
+/* Creates a player for the SWF file in `data`, which is copied.
+ * Returns NULL if the movie can't be loaded. */
+RufflePlayer *ruffle_player_create(const uint8_t *data, size_t len);
+
+/* Destroys a player. Does nothing if `player` is NULL. */
+void ruffle_player_destroy(RufflePlayer *player);
+
+/* Advances the player by `dt` milliseconds. */
+void ruffle_player_tick(RufflePlayer *player, double dt);
+
+/* Returns how many milliseconds to wait before the next tick. */
+double ruffle_player_time_til_next_frame(RufflePlayer *player);
+
+/* Starts (nonzero) or pauses (zero) the movie. Players start paused. */
+void ruffle_player_set_playing(RufflePlayer *player, int playing);
+
+/* Pauses (nonzero) or resumes (zero) the player, suspending its audio. */
+void ruffle_player_set_paused(RufflePlayer *player, int paused);
+
+/* Runs a single frame, and leaves the player paused. */
+void ruffle_player_step_frame(RufflePlayer *player);
+
+/* Overrides the frame rate of the movie, or restores it if zero or less. */
+void ruffle_player_set_frame_rate(RufflePlayer *player, double frame_rate);
+
+/* Sets how fast time passes, such as 0.5 for slow motion. */
+void ruffle_player_set_time_scale(RufflePlayer *player, double time_scale);
+
+/* Seeds random numbers and fixes the clock, so that every run is the same. */
+void ruffle_player_enable_deterministic_mode(RufflePlayer *player, uint64_t seed);
+
+/* Plays an input script, given as text. Returns nonzero if it was valid. */
+int ruffle_player_play_input_script(RufflePlayer *player, const char *script);
+
+/* Starts recording the input given to the player. */
+void ruffle_player_start_input_recording(RufflePlayer *player);
+
+/* Stops recording input, and returns it as an input script to be freed with
+ * ruffle_string_free. Returns NULL if input wasn't being recorded. */
+char *ruffle_player_stop_input_recording(RufflePlayer *player);
+
+/* Frees a string returned by the library. Does nothing if `string` is NULL. */
+void ruffle_string_free(char *string);
+
+/* Writes the size of the movie's stage in pixels. */
+void ruffle_player_movie_size(RufflePlayer *player, uint32_t *width, uint32_t *height);
+
+/* Resizes the viewport and framebuffer, in pixels. */
+void ruffle_player_set_viewport(RufflePlayer *player, uint32_t width, uint32_t height);
+
+/* Returns nonzero if the stage changed since it was last rendered. */
+int ruffle_player_needs_render(RufflePlayer *player);
+
+/* Renders the stage and returns the RGBA framebuffer, writing its size.
+ * The framebuffer stays valid until the next render or until the player is
+ * destroyed. Returns NULL if rendering failed. */
+const uint8_t *ruffle_player_render(RufflePlayer *player, uint32_t *width, uint32_t *height);
+
+/* Mouse input, in pixels relative to the top left of the viewport. */
+void ruffle_player_mouse_move(RufflePlayer *player, double x, double y);
+void ruffle_player_mouse_down(RufflePlayer *player, double x, double y);
+void ruffle_player_mouse_up(RufflePlayer *player, double x, double y);
+void ruffle_player_mouse_leave(RufflePlayer *player);
+
+/* Scrolls the mouse wheel by `lines`. Positive values scroll up. */
+void ruffle_player_mouse_wheel(RufflePlayer *player, double lines);
+
+/* Keyboard input, using Flash key codes (as returned by Key.getCode()). */
+void ruffle_player_key_down(RufflePlayer *player, uint8_t key_code);
+void ruffle_player_key_up(RufflePlayer *player, uint8_t key_code);
+
+/* Types a Unicode character. */
+void ruffle_player_text_input(RufflePlayer *player, uint32_t codepoint);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RUFFLE_H */
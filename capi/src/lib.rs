@@ -0,0 +1,603 @@
+//! A C API for embedding Ruffle in applications that aren't written in Rust, such
+//! as emulator frontends and media centers.
+//!
+//! The player renders in software to an RGBA framebuffer owned by the player, which
+//! the host copies to the screen. See `include/ruffle.h` for the C declarations.
+//!
+//! None of the functions are thread-safe: a player must only be used from the
+//! thread that created it.
+//!
+//! A panic never unwinds into the host. The function that panicked returns its
+//! failure value instead, and `ruffle_last_error` describes the panic. Panics
+//! can only be caught when the library is built to unwind, which the workspace
+//! profiles turn off:
+//!
+//! ```text
+//! cargo build --release -p ruffle_capi --config 'profile.release.panic="unwind"'
+//! ```
+
+use ruffle_core::backend::render::BitmapFormat;
+use ruffle_core::events::{KeyCode, MouseWheelDelta};
+use ruffle_core::input_script::InputScript;
+use ruffle_core::{Player, PlayerBuilder, PlayerEvent};
+use ruffle_render_software::SoftwareRenderBackend;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_double, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+/// The version of the C API. It is increased whenever the API changes in a way
+/// that breaks existing hosts.
+pub const RUFFLE_CAPI_VERSION: u32 = 1;
+
+/// A player, handed to C as an opaque pointer.
+pub struct RufflePlayer {
+    player: Arc<Mutex<Player>>,
+
+    /// The last rendered frame, as RGBA pixels.
+    framebuffer: Vec<u8>,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+}
+
+thread_local! {
+    /// A description of the panic of the last call on this thread, if it panicked.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the body of an entry point, returning `failure` if it panics.
+///
+/// The player that panicked has its lock poisoned, so later calls on it fail too.
+fn guard<T>(failure: T, body: impl FnOnce() -> T) -> T {
+    LAST_ERROR.with(|error| *error.borrow_mut() = None);
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                (*message).to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "Unknown panic".to_string()
+            };
+            log::error!("Panic in the player: {}", message);
+            let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+            LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+            failure
+        }
+    }
+}
+
+/// Returns a description of the panic of the last call to the library on this
+/// thread, or null if it didn't panic. The string belongs to the library, and stays
+/// valid until the next call to it.
+#[no_mangle]
+pub extern "C" fn ruffle_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| match &*error.borrow() {
+        Some(message) => message.as_ptr(),
+        None => std::ptr::null(),
+    })
+}
+
+/// Returns the version of the C API, `RUFFLE_CAPI_VERSION`.
+#[no_mangle]
+pub extern "C" fn ruffle_capi_version() -> u32 {
+    guard(0, || RUFFLE_CAPI_VERSION)
+}
+
+/// Creates a player for the SWF file in the given buffer, which is copied.
+///
+/// The viewport starts at the size of the movie. Returns null if the movie can't
+/// be loaded.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_create(data: *const u8, len: usize) -> *mut RufflePlayer {
+    guard(std::ptr::null_mut(), || {
+        if data.is_null() {
+            return std::ptr::null_mut();
+        }
+        let data = std::slice::from_raw_parts(data, len).to_vec();
+
+        // The renderer is resized to the viewport when the player is built.
+        let player = PlayerBuilder::new()
+            .with_renderer(SoftwareRenderBackend::new(1, 1))
+            .with_movie_bytes(data)
+            .build();
+        let player = match player {
+            Ok(player) => player,
+            Err(e) => {
+                log::error!("Unable to load movie: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
+
+        Box::into_raw(Box::new(RufflePlayer {
+            player,
+            framebuffer: vec![],
+            framebuffer_width: 0,
+            framebuffer_height: 0,
+        }))
+    })
+}
+
+/// Destroys a player created by `ruffle_player_create`. Does nothing if `player`
+/// is null.
+///
+/// # Safety
+///
+/// `player` must be null or a player returned by `ruffle_player_create` that
+/// wasn't destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_destroy(player: *mut RufflePlayer) {
+    guard((), || {
+        if !player.is_null() {
+            drop(Box::from_raw(player));
+        }
+    })
+}
+
+/// Advances the player by `dt` milliseconds, running the frames that are due.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_tick(player: *mut RufflePlayer, dt: c_double) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().tick(dt);
+        }
+    })
+}
+
+/// Returns how many milliseconds the host can wait before the next call to
+/// `ruffle_player_tick`.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_time_til_next_frame(player: *mut RufflePlayer) -> c_double {
+    guard(0.0, || match player.as_mut() {
+        Some(player) => {
+            let time = player.player.lock().unwrap().time_til_next_frame();
+            time.as_secs_f64() * 1000.0
+        }
+        None => 0.0,
+    })
+}
+
+/// Starts (`playing` is nonzero) or pauses the movie. Players start paused.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_set_playing(player: *mut RufflePlayer, playing: c_int) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().set_is_playing(playing != 0);
+        }
+    })
+}
+
+/// Pauses (`paused` is nonzero) or resumes the player. A paused player keeps its
+/// state, but doesn't run frames, and its audio is suspended.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_set_paused(player: *mut RufflePlayer, paused: c_int) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().set_paused(paused != 0);
+        }
+    })
+}
+
+/// Runs a single frame of the movie, and leaves the player paused.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_step_frame(player: *mut RufflePlayer) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().step_frame();
+        }
+    })
+}
+
+/// Runs the movie at `frame_rate` frames per second instead of its own frame rate,
+/// or at its own frame rate again if `frame_rate` is zero or less.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_set_frame_rate(
+    player: *mut RufflePlayer,
+    frame_rate: c_double,
+) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            let frame_rate = if frame_rate > 0.0 {
+                Some(frame_rate)
+            } else {
+                None
+            };
+            player
+                .player
+                .lock()
+                .unwrap()
+                .set_frame_rate_override(frame_rate);
+        }
+    })
+}
+
+/// Sets how fast time passes in the player, such as 0.5 for slow motion or 2.0 to
+/// fast-forward.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_set_time_scale(
+    player: *mut RufflePlayer,
+    time_scale: c_double,
+) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().set_time_scale(time_scale);
+        }
+    })
+}
+
+/// Makes runs of the movie reproducible: random numbers are seeded with `seed`, and
+/// the clock advances by exactly one frame each frame.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_enable_deterministic_mode(
+    player: *mut RufflePlayer,
+    seed: u64,
+) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player
+                .player
+                .lock()
+                .unwrap()
+                .enable_deterministic_mode(seed);
+        }
+    })
+}
+
+/// Plays an input script, given as UTF-8 text, into the player. Input from the host
+/// is ignored until the script ends. Returns nonzero if the script was valid.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`, and `script`
+/// must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_play_input_script(
+    player: *mut RufflePlayer,
+    script: *const c_char,
+) -> c_int {
+    guard(0, || {
+        let player = match player.as_mut() {
+            Some(player) => player,
+            None => return 0,
+        };
+        if script.is_null() {
+            return 0;
+        }
+        let script = CStr::from_ptr(script).to_string_lossy();
+        match script.parse::<InputScript>() {
+            Ok(script) => {
+                player.player.lock().unwrap().play_input_script(script);
+                1
+            }
+            Err(e) => {
+                log::error!("{}", e);
+                0
+            }
+        }
+    })
+}
+
+/// Starts recording the input given to the player, discarding any previous recording.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_start_input_recording(player: *mut RufflePlayer) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player.player.lock().unwrap().start_input_recording();
+        }
+    })
+}
+
+/// Stops recording input, and returns the recording as an input script. The string
+/// must be freed with `ruffle_string_free`. Returns null if input wasn't being
+/// recorded.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_stop_input_recording(
+    player: *mut RufflePlayer,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let player = match player.as_mut() {
+            Some(player) => player,
+            None => return std::ptr::null_mut(),
+        };
+        let recording = player.player.lock().unwrap().stop_input_recording();
+        // Recordings of typed null characters can't be given to C.
+        match recording.and_then(|recording| CString::new(recording.to_string()).ok()) {
+            Some(script) => script.into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Frees a string returned by the library. Does nothing if `string` is null.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the library that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_string_free(string: *mut c_char) {
+    guard((), || {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    })
+}
+
+/// Returns the width and height of the movie's stage in pixels.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`, and `width`
+/// and `height` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_movie_size(
+    player: *mut RufflePlayer,
+    width: *mut u32,
+    height: *mut u32,
+) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            let player = player.player.lock().unwrap();
+            if let Some(width) = width.as_mut() {
+                *width = player.movie_width();
+            }
+            if let Some(height) = height.as_mut() {
+                *height = player.movie_height();
+            }
+        }
+    })
+}
+
+/// Resizes the viewport, and the framebuffer along with it, in pixels.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_set_viewport(
+    player: *mut RufflePlayer,
+    width: u32,
+    height: u32,
+) {
+    guard((), || {
+        if let Some(player) = player.as_mut() {
+            player
+                .player
+                .lock()
+                .unwrap()
+                .set_viewport_dimensions(width.max(1), height.max(1), 1.0);
+        }
+    })
+}
+
+/// Returns nonzero if the stage changed since it was last rendered.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_needs_render(player: *mut RufflePlayer) -> c_int {
+    guard(0, || match player.as_mut() {
+        Some(player) => player.player.lock().unwrap().needs_render() as c_int,
+        None => 0,
+    })
+}
+
+/// Renders the stage, and returns the framebuffer as RGBA pixels, row by row from
+/// the top, with no padding between rows. The size of the framebuffer is written
+/// to `width` and `height`.
+///
+/// The framebuffer belongs to the player, and stays valid until the next call to
+/// this function or `ruffle_player_destroy`. Returns null if rendering failed.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`, and `width`
+/// and `height` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_render(
+    player: *mut RufflePlayer,
+    width: *mut u32,
+    height: *mut u32,
+) -> *const u8 {
+    guard(std::ptr::null(), || {
+        let player = match player.as_mut() {
+            Some(player) => player,
+            None => return std::ptr::null(),
+        };
+        let bitmap = player.player.lock().unwrap().capture_frame();
+        let bitmap = match bitmap {
+            Some(bitmap) => bitmap,
+            None => return std::ptr::null(),
+        };
+        player.framebuffer = match bitmap.data {
+            BitmapFormat::Rgba(data) => data,
+            BitmapFormat::Rgb(data) => data
+                .chunks_exact(3)
+                .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+        };
+        player.framebuffer_width = bitmap.width;
+        player.framebuffer_height = bitmap.height;
+
+        if let Some(width) = width.as_mut() {
+            *width = player.framebuffer_width;
+        }
+        if let Some(height) = height.as_mut() {
+            *height = player.framebuffer_height;
+        }
+        player.framebuffer.as_ptr()
+    })
+}
+
+/// Moves the mouse to the given position in the viewport, in pixels.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_mouse_move(
+    player: *mut RufflePlayer,
+    x: c_double,
+    y: c_double,
+) {
+    guard((), || {
+        handle_event(player, PlayerEvent::MouseMove { x, y });
+    })
+}
+
+/// Presses the mouse button at the given position in the viewport, in pixels.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_mouse_down(
+    player: *mut RufflePlayer,
+    x: c_double,
+    y: c_double,
+) {
+    guard((), || {
+        handle_event(player, PlayerEvent::MouseDown { x, y });
+    })
+}
+
+/// Releases the mouse button at the given position in the viewport, in pixels.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_mouse_up(
+    player: *mut RufflePlayer,
+    x: c_double,
+    y: c_double,
+) {
+    guard((), || {
+        handle_event(player, PlayerEvent::MouseUp { x, y });
+    })
+}
+
+/// Tells the player that the mouse left the viewport.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_mouse_leave(player: *mut RufflePlayer) {
+    guard((), || {
+        handle_event(player, PlayerEvent::MouseLeft);
+    })
+}
+
+/// Scrolls the mouse wheel by the given number of lines. Positive values scroll up.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_mouse_wheel(player: *mut RufflePlayer, lines: c_double) {
+    guard((), || {
+        handle_event(
+            player,
+            PlayerEvent::MouseWheel {
+                delta: MouseWheelDelta::Lines(lines),
+            },
+        );
+    })
+}
+
+/// Presses the key with the given Flash key code. Unknown key codes are ignored.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_key_down(player: *mut RufflePlayer, key_code: u8) {
+    guard((), || {
+        if let Ok(key_code) = KeyCode::try_from(key_code) {
+            handle_event(player, PlayerEvent::KeyDown { key_code });
+        }
+    })
+}
+
+/// Releases the key with the given Flash key code. Unknown key codes are ignored.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_key_up(player: *mut RufflePlayer, key_code: u8) {
+    guard((), || {
+        if let Ok(key_code) = KeyCode::try_from(key_code) {
+            handle_event(player, PlayerEvent::KeyUp { key_code });
+        }
+    })
+}
+
+/// Types the given Unicode character, such as into a text field. Invalid
+/// characters are ignored.
+///
+/// # Safety
+///
+/// `player` must be a live player returned by `ruffle_player_create`.
+#[no_mangle]
+pub unsafe extern "C" fn ruffle_player_text_input(player: *mut RufflePlayer, codepoint: u32) {
+    guard((), || {
+        if let Some(codepoint) = std::char::from_u32(codepoint) {
+            handle_event(player, PlayerEvent::TextInput { codepoint });
+        }
+    })
+}
+
+unsafe fn handle_event(player: *mut RufflePlayer, event: PlayerEvent) {
+    if let Some(player) = player.as_mut() {
+        player.player.lock().unwrap().handle_event(event);
+    }
+}
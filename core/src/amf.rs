@@ -0,0 +1,388 @@
+//! Action Message Format (AMF) serialization.
+//!
+//! AMF is the binary format that Flash Player uses to persist `SharedObject`
+//! data, to pass `LocalConnection` messages between movies, and to talk to
+//! remoting servers. AMF0 is the format of AVM1; AMF3 is the more compact
+//! format of AVM2, which an AMF0 stream can switch to for a single value.
+//!
+//! Values are decoded into a `Graph` that is independent of either VM, so that
+//! the same codec serves both of them. Objects are stored in the graph and
+//! referenced by id, which lets shared objects and cycles survive a round
+//! trip: both encoders write an object that was already written as a
+//! reference to it, and both decoders register an object before reading its
+//! members.
+
+pub mod amf0;
+pub mod amf3;
+pub mod remoting;
+
+use thiserror::Error;
+
+/// The version of AMF to encode or decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmfVersion {
+    Amf0,
+    Amf3,
+}
+
+/// The id of an object in a `Graph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectId(usize);
+
+/// A value that can be serialized to AMF.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+
+    /// An integer in the range of AMF3 integers.
+    ///
+    /// AMF0 has no integer type and writes this as a number.
+    Integer(i32),
+    String(String),
+
+    /// A date, in milliseconds since the Unix epoch.
+    Date(f64),
+
+    /// The source of an XML document.
+    Xml(String),
+    Object(ObjectId),
+}
+
+/// An object that can be serialized to AMF.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Object {
+    /// An anonymous object, or an object of a registered class.
+    Object {
+        class_name: Option<String>,
+        properties: Vec<(String, Value)>,
+    },
+
+    /// An array, with the elements from index 0 up to its length, and any
+    /// other named elements.
+    Array {
+        dense: Vec<Value>,
+        associative: Vec<(String, Value)>,
+    },
+
+    /// A `ByteArray`, which only AMF3 can hold.
+    ByteArray(Vec<u8>),
+}
+
+impl Object {
+    /// An anonymous object with no properties.
+    pub fn anonymous() -> Self {
+        Object::Object {
+            class_name: None,
+            properties: vec![],
+        }
+    }
+}
+
+/// The objects of a serialized value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Graph {
+    objects: Vec<Object>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an object to the graph.
+    pub fn add(&mut self, object: Object) -> ObjectId {
+        self.objects.push(object);
+        ObjectId(self.objects.len() - 1)
+    }
+
+    pub fn get(&self, id: ObjectId) -> &Object {
+        &self.objects[id.0]
+    }
+
+    pub fn get_mut(&mut self, id: ObjectId) -> &mut Object {
+        &mut self.objects[id.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+/// An error while decoding AMF data.
+#[derive(Debug, Error, PartialEq)]
+pub enum AmfError {
+    #[error("Unexpected end of AMF data")]
+    UnexpectedEof,
+
+    #[error("Unknown AMF marker {0:#x}")]
+    UnknownMarker(u8),
+
+    #[error("Invalid AMF reference {0}")]
+    InvalidReference(usize),
+
+    #[error("Invalid UTF-8 in AMF string")]
+    InvalidUtf8,
+
+    #[error("Unsupported AMF type: {0}")]
+    Unsupported(&'static str),
+}
+
+/// Serialize a value, along with the objects it references.
+pub fn serialize(version: AmfVersion, graph: &Graph, value: &Value) -> Vec<u8> {
+    match version {
+        AmfVersion::Amf0 => {
+            let mut encoder = amf0::Encoder::new(graph);
+            encoder.write_value(value);
+            encoder.into_bytes()
+        }
+        AmfVersion::Amf3 => {
+            let mut encoder = amf3::Encoder::new(graph);
+            encoder.write_value(value);
+            encoder.into_bytes()
+        }
+    }
+}
+
+/// Deserialize a single value, adding the objects it references to `graph`.
+pub fn deserialize(version: AmfVersion, data: &[u8], graph: &mut Graph) -> Result<Value, AmfError> {
+    match version {
+        AmfVersion::Amf0 => amf0::Decoder::new(data, graph).read_value(),
+        AmfVersion::Amf3 => amf3::Decoder::new(data, graph).read_value(),
+    }
+}
+
+/// A cursor over the bytes of AMF data.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], AmfError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(AmfError::UnexpectedEof)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, AmfError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, AmfError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, AmfError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, AmfError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(f64::from_be_bytes(bytes))
+    }
+
+    fn read_utf8(&mut self, len: usize) -> Result<String, AmfError> {
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| AmfError::InvalidUtf8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialize a value and deserialize it back.
+    fn round_trip(version: AmfVersion, graph: &Graph, value: &Value) -> (Graph, Value) {
+        let bytes = serialize(version, graph, value);
+        let mut decoded_graph = Graph::new();
+        let decoded = deserialize(version, &bytes, &mut decoded_graph).unwrap();
+        (decoded_graph, decoded)
+    }
+
+    fn corpus() -> Vec<(Graph, Value)> {
+        let mut values = vec![];
+        for value in &[
+            Value::Undefined,
+            Value::Null,
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Number(0.5),
+            Value::Number(-1e300),
+            Value::Number(f64::INFINITY),
+            Value::String("".to_string()),
+            Value::String("Hello, 世界".to_string()),
+            Value::String("a".repeat(70000)),
+            Value::Date(1_595_000_000_000.0),
+            Value::Xml("<a b=\"c\"/>".to_string()),
+        ] {
+            values.push((Graph::new(), value.clone()));
+        }
+
+        // Objects are numbered in the order they are written, so that the
+        // decoded graph matches.
+        let graph = Graph {
+            objects: vec![
+                Object::Object {
+                    class_name: None,
+                    properties: vec![
+                        ("first".to_string(), Value::Object(ObjectId(1))),
+                        ("second".to_string(), Value::Object(ObjectId(1))),
+                        ("name".to_string(), Value::String("first".to_string())),
+                    ],
+                },
+                Object::Array {
+                    dense: vec![Value::Number(1.0), Value::String("two".to_string())],
+                    associative: vec![],
+                },
+            ],
+        };
+        values.push((graph, Value::Object(ObjectId(0))));
+
+        let mut graph = Graph::new();
+        let array = graph.add(Object::Array {
+            dense: vec![Value::Null, Value::Bool(true)],
+            associative: vec![("key".to_string(), Value::Number(3.0))],
+        });
+        values.push((graph, Value::Object(array)));
+
+        let point = |x, y| Object::Object {
+            class_name: Some("com.example.Point".to_string()),
+            properties: vec![
+                ("x".to_string(), Value::Number(x)),
+                ("y".to_string(), Value::Number(y)),
+            ],
+        };
+        let graph = Graph {
+            objects: vec![
+                Object::Array {
+                    dense: vec![Value::Object(ObjectId(1)), Value::Object(ObjectId(2))],
+                    associative: vec![],
+                },
+                point(1.5, -2.5),
+                point(0.0, 0.25),
+            ],
+        };
+        values.push((graph, Value::Object(ObjectId(0))));
+
+        // A cycle: the object holds itself.
+        let mut graph = Graph::new();
+        let cyclic = graph.add(Object::anonymous());
+        if let Object::Object { properties, .. } = graph.get_mut(cyclic) {
+            properties.push(("self".to_string(), Value::Object(cyclic)));
+        }
+        values.push((graph, Value::Object(cyclic)));
+
+        values
+    }
+
+    #[test]
+    fn round_trip_corpus() {
+        for version in &[AmfVersion::Amf0, AmfVersion::Amf3] {
+            for (graph, value) in corpus() {
+                let (decoded_graph, decoded) = round_trip(*version, &graph, &value);
+                assert_eq!(decoded_graph, graph, "{:?} of {:?}", version, value);
+                assert_eq!(decoded, value, "{:?}", version);
+            }
+        }
+    }
+
+    #[test]
+    fn integers() {
+        let graph = Graph::new();
+        for i in &[0, 1, 127, 128, 16383, 16384, 0x0fff_ffff, -1, -0x1000_0000] {
+            let (_, decoded) = round_trip(AmfVersion::Amf3, &graph, &Value::Integer(*i));
+            assert_eq!(decoded, Value::Integer(*i));
+        }
+
+        // Integers outside of the 29-bit range are written as numbers.
+        let (_, decoded) = round_trip(AmfVersion::Amf3, &graph, &Value::Integer(i32::MAX));
+        assert_eq!(decoded, Value::Number(f64::from(i32::MAX)));
+        let (_, decoded) = round_trip(AmfVersion::Amf0, &graph, &Value::Integer(5));
+        assert_eq!(decoded, Value::Number(5.0));
+    }
+
+    #[test]
+    fn byte_arrays() {
+        let graph = Graph {
+            objects: vec![
+                Object::Object {
+                    class_name: None,
+                    properties: vec![
+                        ("a".to_string(), Value::Object(ObjectId(1))),
+                        ("b".to_string(), Value::Object(ObjectId(1))),
+                    ],
+                },
+                Object::ByteArray(vec![0, 1, 2, 255]),
+            ],
+        };
+        let object = ObjectId(0);
+
+        // AMF0 switches to AMF3 for each byte array, so only AMF3 keeps the
+        // reference between them.
+        let (decoded_graph, _) = round_trip(AmfVersion::Amf3, &graph, &Value::Object(object));
+        assert_eq!(decoded_graph, graph);
+        let (decoded_graph, _) = round_trip(AmfVersion::Amf0, &graph, &Value::Object(object));
+        assert_eq!(decoded_graph.len(), 3);
+        assert_eq!(
+            decoded_graph.get(ObjectId(1)),
+            &Object::ByteArray(vec![0, 1, 2, 255])
+        );
+    }
+
+    #[test]
+    fn known_encodings() {
+        let graph = Graph::new();
+        assert_eq!(
+            serialize(AmfVersion::Amf0, &graph, &Value::String("hi".to_string())),
+            vec![0x02, 0x00, 0x02, b'h', b'i']
+        );
+        assert_eq!(
+            serialize(AmfVersion::Amf3, &graph, &Value::Integer(200)),
+            vec![0x04, 0x81, 0x48]
+        );
+        assert_eq!(
+            serialize(AmfVersion::Amf3, &graph, &Value::String("hi".to_string())),
+            vec![0x06, 0x05, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn malformed_data() {
+        let mut graph = Graph::new();
+        assert_eq!(
+            deserialize(AmfVersion::Amf0, &[0x00, 0x01], &mut graph),
+            Err(AmfError::UnexpectedEof)
+        );
+        assert_eq!(
+            deserialize(AmfVersion::Amf0, &[0x07, 0x00, 0x03], &mut graph),
+            Err(AmfError::InvalidReference(3))
+        );
+        assert_eq!(
+            deserialize(AmfVersion::Amf3, &[0x06, 0x02], &mut graph),
+            Err(AmfError::InvalidReference(1))
+        );
+        assert_eq!(
+            deserialize(AmfVersion::Amf3, &[0x20], &mut graph),
+            Err(AmfError::UnknownMarker(0x20))
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! AMF0 encoding and decoding
+
+use super::{amf3, AmfError, Graph, Object, ObjectId, Reader, Value};
+use std::collections::HashMap;
+
+const NUMBER: u8 = 0x00;
+const BOOLEAN: u8 = 0x01;
+const STRING: u8 = 0x02;
+const OBJECT: u8 = 0x03;
+const MOVIE_CLIP: u8 = 0x04;
+const NULL: u8 = 0x05;
+const UNDEFINED: u8 = 0x06;
+const REFERENCE: u8 = 0x07;
+const ECMA_ARRAY: u8 = 0x08;
+const OBJECT_END: u8 = 0x09;
+const STRICT_ARRAY: u8 = 0x0a;
+const DATE: u8 = 0x0b;
+const LONG_STRING: u8 = 0x0c;
+const UNSUPPORTED: u8 = 0x0d;
+const RECORDSET: u8 = 0x0e;
+const XML_DOCUMENT: u8 = 0x0f;
+const TYPED_OBJECT: u8 = 0x10;
+const AVMPLUS: u8 = 0x11;
+
+/// Writes values and the objects they reference as AMF0.
+pub struct Encoder<'a> {
+    graph: &'a Graph,
+    output: Vec<u8>,
+
+    /// The index of each object that was written, which later occurrences
+    /// refer to.
+    references: HashMap<ObjectId, u16>,
+    reference_count: usize,
+}
+
+impl<'a> Encoder<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            output: vec![],
+            references: HashMap::new(),
+            reference_count: 0,
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.output
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.output.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.output.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.output.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        self.output.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Write a string with a 16-bit length, without a type marker.
+    ///
+    /// Strings longer than that are cut off.
+    pub fn write_utf8(&mut self, value: &str) {
+        let mut len = value.len().min(usize::from(u16::MAX));
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.write_u16(len as u16);
+        self.output.extend_from_slice(&value.as_bytes()[..len]);
+    }
+
+    fn write_long_utf8(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.output.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn write_value(&mut self, value: &Value) {
+        match value {
+            Value::Undefined => self.write_u8(UNDEFINED),
+            Value::Null => self.write_u8(NULL),
+            Value::Bool(b) => {
+                self.write_u8(BOOLEAN);
+                self.write_u8(*b as u8);
+            }
+            Value::Number(n) => {
+                self.write_u8(NUMBER);
+                self.write_f64(*n);
+            }
+            Value::Integer(i) => {
+                self.write_u8(NUMBER);
+                self.write_f64(f64::from(*i));
+            }
+            Value::String(s) if s.len() <= usize::from(u16::MAX) => {
+                self.write_u8(STRING);
+                self.write_utf8(s);
+            }
+            Value::String(s) => {
+                self.write_u8(LONG_STRING);
+                self.write_long_utf8(s);
+            }
+            Value::Date(time) => {
+                self.write_u8(DATE);
+                self.write_f64(*time);
+                // The time zone is unused, and always written as UTC.
+                self.write_u16(0);
+            }
+            Value::Xml(xml) => {
+                self.write_u8(XML_DOCUMENT);
+                self.write_long_utf8(xml);
+            }
+            Value::Object(id) => self.write_object(*id),
+        }
+    }
+
+    fn write_object(&mut self, id: ObjectId) {
+        let graph = self.graph;
+        let object = graph.get(id);
+
+        if let Object::ByteArray(_) = object {
+            // Only AMF3 can hold byte arrays.
+            let mut encoder = amf3::Encoder::new(graph);
+            encoder.write_value(&Value::Object(id));
+            self.write_u8(AVMPLUS);
+            self.output.extend(encoder.into_bytes());
+            return;
+        }
+
+        if let Some(index) = self.references.get(&id) {
+            let index = *index;
+            self.write_u8(REFERENCE);
+            self.write_u16(index);
+            return;
+        }
+
+        // Later objects can't be referenced once the table is full, which
+        // only matters for cycles.
+        if self.reference_count <= usize::from(u16::MAX) {
+            self.references.insert(id, self.reference_count as u16);
+        }
+        self.reference_count += 1;
+
+        match object {
+            Object::Object {
+                class_name,
+                properties,
+            } => {
+                match class_name {
+                    Some(class_name) => {
+                        self.write_u8(TYPED_OBJECT);
+                        self.write_utf8(class_name);
+                    }
+                    None => self.write_u8(OBJECT),
+                }
+                self.write_properties(properties);
+            }
+            Object::Array { dense, associative } if associative.is_empty() => {
+                self.write_u8(STRICT_ARRAY);
+                self.write_u32(dense.len() as u32);
+                for value in dense {
+                    self.write_value(value);
+                }
+            }
+            Object::Array { dense, associative } => {
+                self.write_u8(ECMA_ARRAY);
+                self.write_u32(dense.len() as u32);
+                for (i, value) in dense.iter().enumerate() {
+                    self.write_utf8(&i.to_string());
+                    self.write_value(value);
+                }
+                self.write_properties(associative);
+            }
+            Object::ByteArray(_) => unreachable!(),
+        }
+    }
+
+    /// Write named values, followed by the end of the object.
+    fn write_properties(&mut self, properties: &[(String, Value)]) {
+        for (name, value) in properties {
+            self.write_utf8(name);
+            self.write_value(value);
+        }
+        self.write_u16(0);
+        self.write_u8(OBJECT_END);
+    }
+}
+
+/// Reads AMF0 values, adding the objects they reference to a graph.
+pub struct Decoder<'a, 'g> {
+    reader: Reader<'a>,
+    graph: &'g mut Graph,
+    references: Vec<ObjectId>,
+}
+
+impl<'a, 'g> Decoder<'a, 'g> {
+    pub fn new(data: &'a [u8], graph: &'g mut Graph) -> Self {
+        Self {
+            reader: Reader::new(data),
+            graph,
+            references: vec![],
+        }
+    }
+
+    /// Forget the objects that were read, so that later values can't refer
+    /// to them.
+    pub fn reset_references(&mut self) {
+        self.references.clear();
+    }
+
+    /// Whether all of the data was read.
+    pub fn is_empty(&self) -> bool {
+        self.reader.position >= self.reader.data.len()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, AmfError> {
+        self.reader.read_u8()
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, AmfError> {
+        self.reader.read_u16()
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, AmfError> {
+        self.reader.read_u32()
+    }
+
+    /// Read a string with a 16-bit length, without a type marker.
+    pub fn read_utf8(&mut self) -> Result<String, AmfError> {
+        let len = self.reader.read_u16()?;
+        self.reader.read_utf8(usize::from(len))
+    }
+
+    fn read_long_utf8(&mut self) -> Result<String, AmfError> {
+        let len = self.reader.read_u32()?;
+        self.reader.read_utf8(len as usize)
+    }
+
+    pub fn read_value(&mut self) -> Result<Value, AmfError> {
+        let marker = self.reader.read_u8()?;
+        self.read_value_with_marker(marker)
+    }
+
+    fn read_value_with_marker(&mut self, marker: u8) -> Result<Value, AmfError> {
+        Ok(match marker {
+            NUMBER => Value::Number(self.reader.read_f64()?),
+            BOOLEAN => Value::Bool(self.reader.read_u8()? != 0),
+            STRING => Value::String(self.read_utf8()?),
+            LONG_STRING => Value::String(self.read_long_utf8()?),
+            NULL => Value::Null,
+            UNDEFINED | UNSUPPORTED => Value::Undefined,
+            DATE => {
+                let time = self.reader.read_f64()?;
+                let _time_zone = self.reader.read_u16()?;
+                Value::Date(time)
+            }
+            XML_DOCUMENT => Value::Xml(self.read_long_utf8()?),
+            REFERENCE => {
+                let index = usize::from(self.reader.read_u16()?);
+                let id = self
+                    .references
+                    .get(index)
+                    .ok_or(AmfError::InvalidReference(index))?;
+                Value::Object(*id)
+            }
+            OBJECT => self.read_object(None)?,
+            TYPED_OBJECT => {
+                let class_name = self.read_utf8()?;
+                self.read_object(Some(class_name))?
+            }
+            STRICT_ARRAY => {
+                let id = self.add_object(Object::Array {
+                    dense: vec![],
+                    associative: vec![],
+                });
+                let len = self.reader.read_u32()?;
+                let mut values = vec![];
+                for _ in 0..len {
+                    values.push(self.read_value()?);
+                }
+                if let Object::Array { dense, .. } = self.graph.get_mut(id) {
+                    *dense = values;
+                }
+                Value::Object(id)
+            }
+            ECMA_ARRAY => {
+                let id = self.add_object(Object::Array {
+                    dense: vec![],
+                    associative: vec![],
+                });
+                let _len = self.reader.read_u32()?;
+
+                // Elements are written by name, so the dense part is made of
+                // the leading indices.
+                let mut values = vec![];
+                let mut properties = vec![];
+                for (name, value) in self.read_properties()? {
+                    if properties.is_empty() && name == values.len().to_string() {
+                        values.push(value);
+                    } else {
+                        properties.push((name, value));
+                    }
+                }
+                if let Object::Array { dense, associative } = self.graph.get_mut(id) {
+                    *dense = values;
+                    *associative = properties;
+                }
+                Value::Object(id)
+            }
+            AVMPLUS => {
+                let data = self.reader.data;
+                let mut decoder = amf3::Decoder::new(&data[self.reader.position..], self.graph);
+                let value = decoder.read_value()?;
+                self.reader.position += decoder.position();
+                value
+            }
+            MOVIE_CLIP => return Err(AmfError::Unsupported("movie clip")),
+            RECORDSET => return Err(AmfError::Unsupported("recordset")),
+            _ => return Err(AmfError::UnknownMarker(marker)),
+        })
+    }
+
+    /// Add an object to the graph, and register it for references before its
+    /// contents are read.
+    fn add_object(&mut self, object: Object) -> ObjectId {
+        let id = self.graph.add(object);
+        self.references.push(id);
+        id
+    }
+
+    fn read_object(&mut self, class_name: Option<String>) -> Result<Value, AmfError> {
+        let id = self.add_object(Object::anonymous());
+        let values = self.read_properties()?;
+        *self.graph.get_mut(id) = Object::Object {
+            class_name,
+            properties: values,
+        };
+        Ok(Value::Object(id))
+    }
+
+    /// Read named values up to the end of an object.
+    fn read_properties(&mut self) -> Result<Vec<(String, Value)>, AmfError> {
+        let mut properties = vec![];
+        loop {
+            let name = self.read_utf8()?;
+            let marker = self.reader.read_u8()?;
+            if name.is_empty() && marker == OBJECT_END {
+                return Ok(properties);
+            }
+            let value = self.read_value_with_marker(marker)?;
+            properties.push((name, value));
+        }
+    }
+}
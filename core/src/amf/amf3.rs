@@ -0,0 +1,437 @@
+//! AMF3 encoding and decoding
+
+use super::{AmfError, Graph, Object, ObjectId, Reader, Value};
+use std::collections::HashMap;
+
+const UNDEFINED: u8 = 0x00;
+const NULL: u8 = 0x01;
+const FALSE: u8 = 0x02;
+const TRUE: u8 = 0x03;
+const INTEGER: u8 = 0x04;
+const DOUBLE: u8 = 0x05;
+const STRING: u8 = 0x06;
+const XML_DOCUMENT: u8 = 0x07;
+const DATE: u8 = 0x08;
+const ARRAY: u8 = 0x09;
+const OBJECT: u8 = 0x0a;
+const XML: u8 = 0x0b;
+const BYTE_ARRAY: u8 = 0x0c;
+
+/// The range of integers that fit in 29 bits.
+const MIN_INTEGER: i32 = -0x1000_0000;
+const MAX_INTEGER: i32 = 0x0fff_ffff;
+
+/// The layout of a class, which is written once and then referenced.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Traits {
+    class_name: Option<String>,
+    sealed_names: Vec<String>,
+    dynamic: bool,
+}
+
+/// Writes values and the objects they reference as AMF3.
+pub struct Encoder<'a> {
+    graph: &'a Graph,
+    output: Vec<u8>,
+    strings: HashMap<String, u32>,
+
+    /// The index of each object that was written. Dates and XML also take up
+    /// an index, but are never referenced.
+    objects: HashMap<ObjectId, u32>,
+    object_count: u32,
+    traits: HashMap<Traits, u32>,
+}
+
+impl<'a> Encoder<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            output: vec![],
+            strings: HashMap::new(),
+            objects: HashMap::new(),
+            object_count: 0,
+            traits: HashMap::new(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.output
+    }
+
+    /// Write a variable-length 29-bit unsigned integer.
+    fn write_u29(&mut self, value: u32) {
+        let value = value & 0x1fff_ffff;
+        if value < 0x80 {
+            self.output.push(value as u8);
+        } else if value < 0x4000 {
+            self.output
+                .extend_from_slice(&[(value >> 7) as u8 | 0x80, value as u8 & 0x7f]);
+        } else if value < 0x20_0000 {
+            self.output.extend_from_slice(&[
+                (value >> 14) as u8 | 0x80,
+                (value >> 7) as u8 | 0x80,
+                value as u8 & 0x7f,
+            ]);
+        } else {
+            self.output.extend_from_slice(&[
+                (value >> 22) as u8 | 0x80,
+                (value >> 15) as u8 | 0x80,
+                (value >> 8) as u8 | 0x80,
+                value as u8,
+            ]);
+        }
+    }
+
+    /// Write a string without a type marker, or a reference to it if it was
+    /// already written.
+    fn write_string(&mut self, value: &str) {
+        if value.is_empty() {
+            // The empty string is never referenced.
+            self.write_u29(1);
+        } else if let Some(index) = self.strings.get(value) {
+            let index = *index;
+            self.write_u29(index << 1);
+        } else {
+            self.strings
+                .insert(value.to_string(), self.strings.len() as u32);
+            self.write_u29(((value.len() as u32) << 1) | 1);
+            self.output.extend_from_slice(value.as_bytes());
+        }
+    }
+
+    pub fn write_value(&mut self, value: &Value) {
+        match value {
+            Value::Undefined => self.output.push(UNDEFINED),
+            Value::Null => self.output.push(NULL),
+            Value::Bool(false) => self.output.push(FALSE),
+            Value::Bool(true) => self.output.push(TRUE),
+            Value::Integer(i) if (MIN_INTEGER..=MAX_INTEGER).contains(i) => {
+                self.output.push(INTEGER);
+                self.write_u29(*i as u32);
+            }
+            Value::Integer(i) => {
+                self.output.push(DOUBLE);
+                self.output.extend_from_slice(&f64::from(*i).to_be_bytes());
+            }
+            Value::Number(n) => {
+                self.output.push(DOUBLE);
+                self.output.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::String(s) => {
+                self.output.push(STRING);
+                self.write_string(s);
+            }
+            Value::Date(time) => {
+                self.output.push(DATE);
+                self.object_count += 1;
+                self.write_u29(1);
+                self.output.extend_from_slice(&time.to_be_bytes());
+            }
+            Value::Xml(xml) => {
+                self.output.push(XML);
+                self.object_count += 1;
+                self.write_u29(((xml.len() as u32) << 1) | 1);
+                self.output.extend_from_slice(xml.as_bytes());
+            }
+            Value::Object(id) => self.write_object(*id),
+        }
+    }
+
+    fn write_object(&mut self, id: ObjectId) {
+        let graph = self.graph;
+        let object = graph.get(id);
+        self.output.push(match object {
+            Object::Object { .. } => OBJECT,
+            Object::Array { .. } => ARRAY,
+            Object::ByteArray(_) => BYTE_ARRAY,
+        });
+
+        if let Some(index) = self.objects.get(&id) {
+            let index = *index;
+            self.write_u29(index << 1);
+            return;
+        }
+        self.objects.insert(id, self.object_count);
+        self.object_count += 1;
+
+        match object {
+            Object::Object {
+                class_name,
+                properties,
+            } => {
+                // Objects of a class are sealed, and anonymous objects are
+                // dynamic.
+                let traits = Traits {
+                    class_name: class_name.clone(),
+                    sealed_names: if class_name.is_some() {
+                        properties.iter().map(|(name, _)| name.clone()).collect()
+                    } else {
+                        vec![]
+                    },
+                    dynamic: class_name.is_none(),
+                };
+                self.write_traits(traits);
+
+                if class_name.is_some() {
+                    for (_, value) in properties {
+                        self.write_value(value);
+                    }
+                } else {
+                    for (name, value) in properties {
+                        self.write_string(name);
+                        self.write_value(value);
+                    }
+                    self.write_string("");
+                }
+            }
+            Object::Array { dense, associative } => {
+                self.write_u29(((dense.len() as u32) << 1) | 1);
+                for (name, value) in associative {
+                    self.write_string(name);
+                    self.write_value(value);
+                }
+                self.write_string("");
+                for value in dense {
+                    self.write_value(value);
+                }
+            }
+            Object::ByteArray(bytes) => {
+                self.write_u29(((bytes.len() as u32) << 1) | 1);
+                self.output.extend_from_slice(bytes);
+            }
+        }
+    }
+
+    fn write_traits(&mut self, traits: Traits) {
+        if let Some(index) = self.traits.get(&traits) {
+            let index = *index;
+            self.write_u29((index << 2) | 0b01);
+            return;
+        }
+
+        let mut flags = 0b011 | ((traits.sealed_names.len() as u32) << 4);
+        if traits.dynamic {
+            flags |= 0b1000;
+        }
+        self.write_u29(flags);
+        self.write_string(traits.class_name.as_deref().unwrap_or(""));
+        for name in &traits.sealed_names {
+            self.write_string(name);
+        }
+
+        let index = self.traits.len() as u32;
+        self.traits.insert(traits, index);
+    }
+}
+
+/// Reads AMF3 values, adding the objects they reference to a graph.
+pub struct Decoder<'a, 'g> {
+    reader: Reader<'a>,
+    graph: &'g mut Graph,
+    strings: Vec<String>,
+
+    /// The values that can be referenced: objects, dates and XML.
+    objects: Vec<Value>,
+    traits: Vec<Traits>,
+}
+
+impl<'a, 'g> Decoder<'a, 'g> {
+    pub fn new(data: &'a [u8], graph: &'g mut Graph) -> Self {
+        Self {
+            reader: Reader::new(data),
+            graph,
+            strings: vec![],
+            objects: vec![],
+            traits: vec![],
+        }
+    }
+
+    /// The number of bytes that were read.
+    pub fn position(&self) -> usize {
+        self.reader.position
+    }
+
+    /// Read a variable-length 29-bit unsigned integer.
+    fn read_u29(&mut self) -> Result<u32, AmfError> {
+        let mut value = 0;
+        for i in 0..4 {
+            let byte = u32::from(self.reader.read_u8()?);
+            if i == 3 {
+                return Ok((value << 8) | byte);
+            }
+            value = (value << 7) | (byte & 0x7f);
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Read a string without a type marker.
+    fn read_string(&mut self) -> Result<String, AmfError> {
+        let header = self.read_u29()?;
+        if header & 1 == 0 {
+            let index = (header >> 1) as usize;
+            return self
+                .strings
+                .get(index)
+                .cloned()
+                .ok_or(AmfError::InvalidReference(index));
+        }
+
+        let value = self.reader.read_utf8((header >> 1) as usize)?;
+        if !value.is_empty() {
+            self.strings.push(value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Read the header of an object type, returning either the referenced
+    /// value or the inline value of the header.
+    fn read_object_header(&mut self) -> Result<Result<u32, Value>, AmfError> {
+        let header = self.read_u29()?;
+        if header & 1 == 0 {
+            let index = (header >> 1) as usize;
+            return match self.objects.get(index) {
+                Some(value) => Ok(Err(value.clone())),
+                None => Err(AmfError::InvalidReference(index)),
+            };
+        }
+        Ok(Ok(header >> 1))
+    }
+
+    pub fn read_value(&mut self) -> Result<Value, AmfError> {
+        let marker = self.reader.read_u8()?;
+        Ok(match marker {
+            UNDEFINED => Value::Undefined,
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            INTEGER => {
+                let value = self.read_u29()?;
+                // Sign-extend from 29 bits.
+                Value::Integer(((value << 3) as i32) >> 3)
+            }
+            DOUBLE => Value::Number(self.reader.read_f64()?),
+            STRING => Value::String(self.read_string()?),
+            XML_DOCUMENT | XML => match self.read_object_header()? {
+                Ok(len) => {
+                    let value = Value::Xml(self.reader.read_utf8(len as usize)?);
+                    self.objects.push(value.clone());
+                    value
+                }
+                Err(value) => value,
+            },
+            DATE => match self.read_object_header()? {
+                Ok(_) => {
+                    let value = Value::Date(self.reader.read_f64()?);
+                    self.objects.push(value.clone());
+                    value
+                }
+                Err(value) => value,
+            },
+            BYTE_ARRAY => match self.read_object_header()? {
+                Ok(len) => {
+                    let bytes = self.reader.read_bytes(len as usize)?.to_vec();
+                    self.add_object(Object::ByteArray(bytes))
+                }
+                Err(value) => value,
+            },
+            ARRAY => match self.read_object_header()? {
+                Ok(len) => self.read_array(len)?,
+                Err(value) => value,
+            },
+            OBJECT => match self.read_object_header()? {
+                Ok(header) => self.read_object(header)?,
+                Err(value) => value,
+            },
+            _ => return Err(AmfError::UnknownMarker(marker)),
+        })
+    }
+
+    /// Add an object to the graph, and register it for references before its
+    /// contents are read.
+    fn add_object(&mut self, object: Object) -> Value {
+        let value = Value::Object(self.graph.add(object));
+        self.objects.push(value.clone());
+        value
+    }
+
+    fn read_array(&mut self, len: u32) -> Result<Value, AmfError> {
+        let value = self.add_object(Object::Array {
+            dense: vec![],
+            associative: vec![],
+        });
+
+        let mut properties = vec![];
+        loop {
+            let name = self.read_string()?;
+            if name.is_empty() {
+                break;
+            }
+            properties.push((name, self.read_value()?));
+        }
+
+        let mut values = vec![];
+        for _ in 0..len {
+            values.push(self.read_value()?);
+        }
+
+        if let Value::Object(id) = value {
+            *self.graph.get_mut(id) = Object::Array {
+                dense: values,
+                associative: properties,
+            };
+        }
+        Ok(value)
+    }
+
+    fn read_object(&mut self, header: u32) -> Result<Value, AmfError> {
+        let value = self.add_object(Object::anonymous());
+
+        let traits = if header & 1 == 0 {
+            let index = (header >> 1) as usize;
+            self.traits
+                .get(index)
+                .cloned()
+                .ok_or(AmfError::InvalidReference(index))?
+        } else if header & 0b10 != 0 {
+            return Err(AmfError::Unsupported("externalizable object"));
+        } else {
+            let class_name = self.read_string()?;
+            let mut sealed_names = vec![];
+            for _ in 0..header >> 3 {
+                sealed_names.push(self.read_string()?);
+            }
+            let traits = Traits {
+                class_name: Some(class_name).filter(|name| !name.is_empty()),
+                sealed_names,
+                dynamic: header & 0b100 != 0,
+            };
+            self.traits.push(traits.clone());
+            traits
+        };
+
+        let mut properties = vec![];
+        for name in &traits.sealed_names {
+            properties.push((name.clone(), self.read_value()?));
+        }
+        if traits.dynamic {
+            loop {
+                let name = self.read_string()?;
+                if name.is_empty() {
+                    break;
+                }
+                properties.push((name, self.read_value()?));
+            }
+        }
+
+        if let Value::Object(id) = value {
+            *self.graph.get_mut(id) = Object::Object {
+                class_name: traits.class_name,
+                properties,
+            };
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,193 @@
+//! Flash Remoting envelopes
+//!
+//! `NetConnection.call` sends an envelope of messages to a gateway over HTTP,
+//! and the gateway answers with an envelope holding one result or status
+//! message per call. Each header and message body is AMF0, with its own
+//! table of object references.
+
+use super::{amf0, AmfError, Graph, Value};
+
+/// The MIME type of remoting requests and responses.
+pub const CONTENT_TYPE: &str = "application/x-amf";
+
+/// A header of an envelope, which applies to all of its messages.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub name: String,
+    pub must_understand: bool,
+    pub value: Value,
+}
+
+/// A call to a service, or the response to one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    /// The service method for calls, or `<response URI>/onResult` and
+    /// `<response URI>/onStatus` for responses.
+    pub target_uri: String,
+
+    /// The URI that the response to a call is sent to.
+    pub response_uri: String,
+    pub value: Value,
+}
+
+/// The body of a remoting request or response.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Envelope {
+    /// The AMF version of the client, which is 0 for AVM1 movies.
+    pub version: u16,
+
+    /// The objects referenced by the headers and messages.
+    pub graph: Graph,
+    pub headers: Vec<Header>,
+    pub messages: Vec<Message>,
+}
+
+/// How a response to a call ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseKind {
+    Result,
+    Status,
+}
+
+impl Message {
+    /// Check whether this message is the response to a call with the given
+    /// response URI.
+    pub fn response_to(&self, response_uri: &str) -> Option<ResponseKind> {
+        if !self.target_uri.starts_with(response_uri) {
+            return None;
+        }
+        match &self.target_uri[response_uri.len()..] {
+            "/onResult" => Some(ResponseKind::Result),
+            "/onStatus" => Some(ResponseKind::Status),
+            _ => None,
+        }
+    }
+}
+
+impl Envelope {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = vec![];
+        output.extend_from_slice(&self.version.to_be_bytes());
+
+        output.extend_from_slice(&(self.headers.len() as u16).to_be_bytes());
+        for header in &self.headers {
+            write_utf8(&mut output, &header.name);
+            output.push(header.must_understand as u8);
+            self.write_value(&mut output, &header.value);
+        }
+
+        output.extend_from_slice(&(self.messages.len() as u16).to_be_bytes());
+        for message in &self.messages {
+            write_utf8(&mut output, &message.target_uri);
+            write_utf8(&mut output, &message.response_uri);
+            self.write_value(&mut output, &message.value);
+        }
+
+        output
+    }
+
+    /// Write a value with its length in front of it.
+    fn write_value(&self, output: &mut Vec<u8>, value: &Value) {
+        let mut encoder = amf0::Encoder::new(&self.graph);
+        encoder.write_value(value);
+        let bytes = encoder.into_bytes();
+        output.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        output.extend(bytes);
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, AmfError> {
+        let mut graph = Graph::new();
+        let mut decoder = amf0::Decoder::new(data, &mut graph);
+        let version = decoder.read_u16()?;
+
+        let mut headers = vec![];
+        for _ in 0..decoder.read_u16()? {
+            let name = decoder.read_utf8()?;
+            let must_understand = decoder.read_u8()? != 0;
+            // The length may be unknown, and values end on their own anyway.
+            let _len = decoder.read_u32()?;
+            decoder.reset_references();
+            let value = decoder.read_value()?;
+            headers.push(Header {
+                name,
+                must_understand,
+                value,
+            });
+        }
+
+        let mut messages = vec![];
+        for _ in 0..decoder.read_u16()? {
+            let target_uri = decoder.read_utf8()?;
+            let response_uri = decoder.read_utf8()?;
+            let _len = decoder.read_u32()?;
+            decoder.reset_references();
+            let value = decoder.read_value()?;
+            messages.push(Message {
+                target_uri,
+                response_uri,
+                value,
+            });
+        }
+
+        Ok(Self {
+            version,
+            graph,
+            headers,
+            messages,
+        })
+    }
+}
+
+fn write_utf8(output: &mut Vec<u8>, value: &str) {
+    output.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    output.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Object;
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut graph = Graph::new();
+        let args = graph.add(Object::Array {
+            dense: vec![Value::String("player".to_string()), Value::Number(1200.0)],
+            associative: vec![],
+        });
+        let envelope = Envelope {
+            version: 0,
+            graph,
+            headers: vec![Header {
+                name: "Credentials".to_string(),
+                must_understand: false,
+                value: Value::Null,
+            }],
+            messages: vec![Message {
+                target_uri: "Scores.submit".to_string(),
+                response_uri: "/1".to_string(),
+                value: Value::Object(args),
+            }],
+        };
+
+        assert_eq!(Envelope::decode(&envelope.encode()), Ok(envelope));
+    }
+
+    #[test]
+    fn responses() {
+        let message = |target_uri: &str| Message {
+            target_uri: target_uri.to_string(),
+            response_uri: "null".to_string(),
+            value: Value::Undefined,
+        };
+        assert_eq!(
+            message("/1/onResult").response_to("/1"),
+            Some(ResponseKind::Result)
+        );
+        assert_eq!(
+            message("/1/onStatus").response_to("/1"),
+            Some(ResponseKind::Status)
+        );
+        assert_eq!(message("/2/onResult").response_to("/1"), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Tracking of the sounds played by movies.
+//!
+//! The audio backend only knows about sound instances. The player keeps track
+//! of the display object and `Sound` object that started each one, so that the
+//! sound transforms of clips apply to the sounds they own, and so that
+//! `Sound.onSoundComplete` fires once a sound ends.
+
+use crate::avm1::SoundObject;
+use crate::backend::audio::{swf, AudioBackend, SoundHandle, SoundInstanceHandle, SoundTransform};
+use crate::display_object::{DisplayObject, TDisplayObject};
+use gc_arena::{Collect, MutationContext};
+
+/// A sound played by a movie.
+struct SoundInstance<'gc> {
+    /// The sound that is playing.
+    /// `None` for audio that isn't from the library, like streams and attached sources.
+    sound: Option<SoundHandle>,
+
+    instance: SoundInstanceHandle,
+
+    /// The display object that owns this sound. The sound transforms of it and
+    /// its parents apply to the sound.
+    display_object: Option<DisplayObject<'gc>>,
+
+    /// The AVM1 `Sound` object that started this sound.
+    avm1_object: Option<SoundObject<'gc>>,
+}
+
+unsafe impl<'gc> Collect for SoundInstance<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        self.display_object.trace(cc);
+        self.avm1_object.trace(cc);
+    }
+}
+
+/// The sounds played by every movie of the player.
+pub struct AudioManager<'gc> {
+    sounds: Vec<SoundInstance<'gc>>,
+
+    /// The transform applied to every sound, set by `Sound` objects without an owner.
+    global_transform: SoundTransform,
+}
+
+unsafe impl<'gc> Collect for AudioManager<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        self.sounds.trace(cc);
+    }
+}
+
+impl<'gc> AudioManager<'gc> {
+    pub fn new() -> Self {
+        Self {
+            sounds: Vec::new(),
+            global_transform: Default::default(),
+        }
+    }
+
+    /// Starts playing a sound from the library, owned by the given display object.
+    pub fn start_sound(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        sound: SoundHandle,
+        settings: &swf::SoundInfo,
+        display_object: Option<DisplayObject<'gc>>,
+        avm1_object: Option<SoundObject<'gc>>,
+    ) -> Option<SoundInstanceHandle> {
+        match audio.start_sound(sound, settings) {
+            Ok(instance) => {
+                self.add_sound(audio, Some(sound), instance, display_object, avm1_object);
+                Some(instance)
+            }
+            Err(e) => {
+                log::warn!("Unable to start sound: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Tracks a sound instance that was started directly on the audio backend,
+    /// such as a stream, and applies the transform of its owner to it.
+    pub fn add_instance(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        instance: SoundInstanceHandle,
+        display_object: Option<DisplayObject<'gc>>,
+    ) {
+        self.add_sound(audio, None, instance, display_object, None);
+    }
+
+    /// Stops tracking a sound instance without stopping it.
+    pub fn remove_instance(&mut self, instance: SoundInstanceHandle) {
+        self.sounds.retain(|sound| sound.instance != instance);
+    }
+
+    pub fn stop_sound(&mut self, audio: &mut dyn AudioBackend, instance: SoundInstanceHandle) {
+        audio.stop_sound(instance);
+        self.remove_instance(instance);
+    }
+
+    /// Stops every instance of a sound from the library.
+    pub fn stop_sounds_with_handle(&mut self, audio: &mut dyn AudioBackend, handle: SoundHandle) {
+        audio.stop_sounds_with_handle(handle);
+        self.sounds.retain(|sound| sound.sound != Some(handle));
+    }
+
+    /// Stops every sound owned by a display object, like `Sound.stop` on a `Sound`
+    /// created for a clip.
+    pub fn stop_sounds_with_display_object(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        display_object: DisplayObject<'gc>,
+    ) {
+        self.sounds.retain(|sound| match sound.display_object {
+            Some(owner) if DisplayObject::ptr_eq(owner, display_object) => {
+                audio.stop_sound(sound.instance);
+                false
+            }
+            _ => true,
+        });
+    }
+
+    pub fn stop_all_sounds(&mut self, audio: &mut dyn AudioBackend) {
+        audio.stop_all_sounds();
+        self.sounds.clear();
+    }
+
+    pub fn global_transform(&self) -> SoundTransform {
+        self.global_transform
+    }
+
+    pub fn set_global_transform(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        transform: SoundTransform,
+    ) {
+        self.global_transform = transform;
+        self.update_sound_transforms(audio);
+    }
+
+    /// The transform of a sound owned by the given display object, combining the
+    /// sound transforms of the object and all of its parents with the global transform.
+    pub fn transform_for(&self, display_object: Option<DisplayObject<'gc>>) -> SoundTransform {
+        let mut transform = SoundTransform::default();
+        let mut node = display_object;
+        while let Some(object) = node {
+            if let Some(clip) = object.as_movie_clip() {
+                transform = clip.sound_transform().concat(&transform);
+            }
+            node = object.parent();
+        }
+        self.global_transform.concat(&transform)
+    }
+
+    /// Applies the current sound transforms to every playing sound.
+    /// Called whenever the sound transform of a clip changes.
+    pub fn update_sound_transforms(&self, audio: &mut dyn AudioBackend) {
+        for sound in &self.sounds {
+            audio.set_sound_transform(sound.instance, self.transform_for(sound.display_object));
+        }
+    }
+
+    /// Forgets sounds that finished playing, and updates the positions of the
+    /// `Sound` objects that are playing them.
+    ///
+    /// Returns the `Sound` objects whose last started sound completed, which
+    /// should receive `onSoundComplete`.
+    pub fn update_sounds(
+        &mut self,
+        gc_context: MutationContext<'gc, '_>,
+        audio: &mut dyn AudioBackend,
+    ) -> Vec<SoundObject<'gc>> {
+        let mut completed = vec![];
+        self.sounds.retain(|sound| {
+            let is_playing = audio.is_sound_playing(sound.instance);
+            if let Some(object) = sound.avm1_object {
+                // Only the last sound started by a `Sound` object is reported by it.
+                if object.sound_instance() == Some(sound.instance) {
+                    if is_playing {
+                        if let Some(position) = audio.get_sound_position(sound.instance) {
+                            object.set_position(gc_context, position);
+                        }
+                    } else {
+                        // The position stays at the end of a sound once it completes.
+                        object.set_position(gc_context, object.duration());
+                        object.set_sound_instance(gc_context, None);
+                        completed.push(object);
+                    }
+                }
+            }
+            is_playing
+        });
+        completed
+    }
+
+    fn add_sound(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        sound: Option<SoundHandle>,
+        instance: SoundInstanceHandle,
+        display_object: Option<DisplayObject<'gc>>,
+        avm1_object: Option<SoundObject<'gc>>,
+    ) {
+        let transform = self.transform_for(display_object);
+        if transform != SoundTransform::default() {
+            audio.set_sound_transform(instance, transform);
+        }
+        self.sounds.push(SoundInstance {
+            sound,
+            instance,
+            display_object,
+            avm1_object,
+        });
+    }
+}
+
+impl<'gc> Default for AudioManager<'gc> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -11,14 +11,17 @@ use crate::tag_utils::SwfSlice;
 
 #[cfg(test)]
 #[macro_use]
-mod test_utils;
+pub(crate) mod test_utils;
 
 #[macro_use]
 pub mod listeners;
 
 pub mod activation;
+pub mod amf;
 pub mod color_transform_object;
 pub mod debug;
+pub mod debugger;
+pub mod disassembler;
 pub mod error;
 mod fscommand;
 pub mod function;
@@ -31,7 +34,8 @@ pub mod shared_object;
 mod sound_object;
 mod stage_object;
 mod super_object;
-mod value;
+pub mod text_snapshot_object;
+pub(crate) mod value;
 mod value_object;
 pub mod xml_attributes_object;
 pub mod xml_idmap_object;
@@ -93,6 +97,12 @@ pub struct Avm1<'gc> {
     /// The maximum amount of functions that can be called before a `Error::FunctionRecursionLimit`
     /// is raised. This defaults to 256 but can be changed per movie.
     max_recursion_depth: u16,
+
+    /// Whether each action is logged as it runs, along with the stack.
+    trace_actions: bool,
+
+    /// The attached debugger, if any.
+    debugger: Option<debugger::Debugger>,
 }
 
 unsafe impl<'gc> gc_arena::Collect for Avm1<'gc> {
@@ -131,6 +141,8 @@ impl<'gc> Avm1<'gc> {
             ],
             halted: false,
             max_recursion_depth: 255,
+            trace_actions: false,
+            debugger: None,
         }
     }
 
@@ -390,6 +402,17 @@ impl<'gc> Avm1<'gc> {
     pub fn set_max_recursion_depth(&mut self, max_recursion_depth: u16) {
         self.max_recursion_depth = max_recursion_depth
     }
+
+    /// Logs each action as it runs, disassembled, with the values on the stack before
+    /// it. They are logged at the info level with the `avm1_actions` target.
+    pub fn set_trace_actions(&mut self, trace_actions: bool) {
+        self.trace_actions = trace_actions;
+    }
+
+    /// Attaches a debugger, or detaches it with `None`.
+    pub fn set_debugger(&mut self, debugger: Option<debugger::Debugger>) {
+        self.debugger = debugger;
+    }
 }
 
 pub fn root_error_handler<'gc>(
@@ -401,7 +424,7 @@ pub fn root_error_handler<'gc>(
         let string = error
             .coerce_to_string(activation, context)
             .unwrap_or_else(|_| Cow::Borrowed("undefined"));
-        log::info!(target: "avm_trace", "{}", string);
+        context.log.avm_warning(&string);
     } else {
         log::error!("{}", error);
     }
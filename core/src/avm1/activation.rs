@@ -1,3 +1,5 @@
+use crate::avm1::debug::describe_value;
+use crate::avm1::disassembler::format_action;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Avm1Function, ExecutionReason, FunctionObject};
 use crate::avm1::object::{Object, TObject};
@@ -5,12 +7,14 @@ use crate::avm1::property::Attribute;
 use crate::avm1::scope::Scope;
 use crate::avm1::value::f64_to_wrapping_u32;
 use crate::avm1::{
-    fscommand, globals, scope, skip_actions, start_drag, value_object, Avm1, ScriptObject, Value,
+    debugger, fscommand, globals, scope, skip_actions, start_drag, value_object, Avm1,
+    ScriptObject, Value,
 };
 use crate::backend::navigator::{NavigationMethod, RequestOptions};
 use crate::context::UpdateContext;
 use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
 use crate::tag_utils::SwfSlice;
+use crate::unimplemented::FeatureKind;
 use enumset::EnumSet;
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
 use rand::Rng;
@@ -163,6 +167,15 @@ impl<'a> ActivationIdentifier<'a> {
     pub fn depth(&self) -> u16 {
         self.depth
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The identifier of the activation that started this one, if any.
+    pub fn parent(&self) -> Option<&'a ActivationIdentifier<'a>> {
+        self.parent
+    }
 }
 
 unsafe impl<'gc> gc_arena::Collect for ActivationIdentifier<'gc> {
@@ -311,6 +324,36 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         }
     }
 
+    /// Construct a stack frame with no code, which resolves variables and paths as
+    /// code on the timeline of `base_clip` does.
+    ///
+    /// This is used by embedders to read and write the variables of a movie.
+    pub fn from_timeline(
+        avm: &'a mut Avm1<'gc>,
+        id: ActivationIdentifier<'a>,
+        swf_version: u8,
+        mc: MutationContext<'gc, '_>,
+        base_clip: DisplayObject<'gc>,
+        clip_obj: Object<'gc>,
+    ) -> Self {
+        let global_scope = GcCell::allocate(mc, Scope::from_global_object(avm.globals));
+        let child_scope = GcCell::allocate(
+            mc,
+            Scope::new(global_scope, scope::ScopeClass::Target, clip_obj),
+        );
+        let constant_pool = avm.constant_pool;
+        Self::from_action(
+            avm,
+            id,
+            swf_version,
+            child_scope,
+            constant_pool,
+            base_clip,
+            clip_obj,
+            None,
+        )
+    }
+
     /// Add a stack frame that executes code in timeline scope
     pub fn run_child_frame_for_action<S: Into<Cow<'static, str>>>(
         &mut self,
@@ -415,12 +458,20 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
+        let offset = data.start + reader.pos();
         if reader.pos() >= (data.end - data.start) {
             //Executing beyond the end of a function constitutes an implicit return.
             Ok(FrameControl::Return(ReturnType::Implicit))
         } else if let Some(action) = reader.read_action()? {
             avm_debug!("({}) Action: {:?}", self.id.depth(), action);
 
+            if self.avm.trace_actions {
+                self.trace_action(&action, offset, data.start + reader.pos());
+            }
+            if self.avm.debugger.is_some() {
+                debugger::before_action(self, context, data, offset, &action);
+            }
+
             match action {
                 Action::Add => self.action_add(context),
                 Action::Add2 => self.action_add_2(context),
@@ -560,10 +611,17 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
 
     fn unknown_op(
         &mut self,
-        _context: &mut UpdateContext,
+        context: &mut UpdateContext,
         action: swf::avm1::types::Action,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
         log::error!("Unknown AVM1 opcode: {:?}", action);
+        let features = &mut context.unimplemented_features;
+        match action {
+            Action::Unknown { opcode, .. } => {
+                features.record(FeatureKind::Action, &format!("0x{:02X}", opcode))
+            }
+            action => features.record_variant(FeatureKind::Action, &action),
+        }
         Ok(FrameControl::Continue)
     }
 
@@ -1198,7 +1256,7 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         &mut self,
         context: &mut UpdateContext,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        let time = context.navigator.time_since_launch().as_millis() as u32;
+        let time = context.global_time as u32;
         self.avm.push(time);
         Ok(FrameControl::Continue)
     }
@@ -1233,6 +1291,7 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
                         context.player.clone().unwrap(),
                         level,
                         fetch,
+                        url.to_string(),
                         None,
                     );
                     context.navigator.spawn_future(process);
@@ -1248,7 +1307,7 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         }
 
         if let Some(fscommand) = fscommand::parse(url) {
-            fscommand::handle(fscommand, self, context)?;
+            fscommand::handle(fscommand, target, self, context)?;
         } else {
             context
                 .navigator
@@ -1272,7 +1331,8 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         let url = url_val.coerce_to_string(self, context)?;
 
         if let Some(fscommand) = fscommand::parse(&url) {
-            fscommand::handle(fscommand, self, context)?;
+            let args = target.coerce_to_string(self, context)?;
+            fscommand::handle(fscommand, &args, self, context)?;
             return Ok(FrameControl::Continue);
         }
 
@@ -1323,6 +1383,7 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
                     context.player.clone().unwrap(),
                     clip_target,
                     fetch,
+                    url.to_string(),
                     None,
                 );
                 context.navigator.spawn_future(process);
@@ -1973,7 +2034,11 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         } else {
             log::warn!("SetTarget failed: {} not found", target);
             // TODO: Emulate AVM1 trace error message.
-            log::info!(target: "avm_trace", "Target not found: Target=\"{}\" Base=\"{}\"", target, base_clip.path());
+            context.log.avm_warning(&format!(
+                "Target not found: Target=\"{}\" Base=\"{}\"",
+                target,
+                base_clip.path()
+            ));
 
             // When SetTarget has an invalid target, subsequent GetVariables act
             // as if they are targeting root, but subsequent Play/Stop/etc.
@@ -2095,7 +2160,7 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         &mut self,
         context: &mut UpdateContext,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        context.audio.stop_all_sounds();
+        context.audio_manager.stop_all_sounds(context.audio);
         Ok(FrameControl::Continue)
     }
 
@@ -2225,6 +2290,9 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         let _clip = self.avm.pop().coerce_to_object(self, context);
         self.avm.push(Value::Undefined);
         log::warn!("Unimplemented action: TargetPath");
+        context
+            .unimplemented_features
+            .record(FeatureKind::Action, "TargetPath");
         Ok(FrameControl::Continue)
     }
 
@@ -2254,6 +2322,19 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         Ok(FrameControl::Continue)
     }
 
+    /// Logs an action that is about to run, with the values on the stack.
+    fn trace_action(&self, action: &Action, offset: usize, next_offset: usize) {
+        let stack: Vec<String> = self.avm.stack.iter().map(describe_value).collect();
+        log::info!(
+            target: "avm1_actions",
+            "({}) {:6}: {}    stack: [{}]",
+            self.id.depth(),
+            offset,
+            format_action(action, next_offset, &self.constant_pool.read()[..]),
+            stack.join(", ")
+        );
+    }
+
     fn action_to_string(
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -2276,7 +2357,11 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         } else {
             val.coerce_to_string(self, context)?
         };
-        log::info!(target: "avm_trace", "{}", out);
+        context.log.avm_trace(&out);
+        if self.avm.debugger.is_some() {
+            let out = out.into_owned();
+            debugger::after_trace(self, context, &out);
+        }
         Ok(FrameControl::Continue)
     }
 
@@ -2292,12 +2377,15 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
     fn action_wait_for_frame(
         &mut self,
         _context: &mut UpdateContext,
-        _frame: u16,
+        frame: u16,
         num_actions_to_skip: u8,
         r: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        // TODO(Herschel): Always true for now.
-        let loaded = true;
+        // The frame is 0-based.
+        let loaded = self
+            .target_clip_or_root()
+            .as_movie_clip()
+            .map_or(true, |clip| clip.frames_loaded() > frame);
         if !loaded {
             // Note that the offset is given in # of actions, NOT in bytes.
             // Read the actions and toss them away.
@@ -2312,9 +2400,21 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
         num_actions_to_skip: u8,
         r: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        // TODO(Herschel): Always true for now.
-        let _frame_num = self.avm.pop().coerce_to_f64(self, context)? as u16;
-        let loaded = true;
+        let frame = self.avm.pop();
+        let loaded = if let Some(clip) = self.target_clip_or_root().as_movie_clip() {
+            // The frame is either a 1-based frame number or a frame label.
+            let frame_num = match frame {
+                Value::String(label) => clip.frame_label_to_number(&label),
+                frame => Some(frame.coerce_to_f64(self, context)? as u16),
+            };
+            // Labels are only known once their frame has loaded, so an unknown label
+            // waits for the whole movie.
+            frame_num.map_or(clip.frames_loaded() >= clip.total_frames(), |frame_num| {
+                clip.frames_loaded() >= frame_num
+            })
+        } else {
+            true
+        };
         if !loaded {
             // Note that the offset is given in # of actions, NOT in bytes.
             // Read the actions and toss them away.
@@ -2348,7 +2448,9 @@ impl<'a, 'gc: 'a> Activation<'a, 'gc> {
             // Undefined/null with is ignored.
             Value::Undefined | Value::Null => {
                 // Mimic Flash's error output.
-                log::info!(target: "avm_trace", "Error: A 'with' action failed because the specified object did not exist.\n");
+                context.log.avm_warning(
+                    "Error: A 'with' action failed because the specified object did not exist.\n",
+                );
                 Ok(FrameControl::Continue)
             }
 
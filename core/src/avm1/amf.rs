@@ -0,0 +1,181 @@
+//! AMF serialization of AVM1 values
+
+use crate::amf::{self, Graph, ObjectId};
+use crate::avm1::activation::Activation;
+use crate::avm1::{Object, ObjectPtr, ScriptObject, TObject, Value};
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use std::collections::HashMap;
+
+/// Serialize a value and the objects it references into `graph`.
+///
+/// Functions and display objects can't be serialized; they are left out of
+/// objects and arrays, and are serialized as `undefined` on their own.
+pub fn serialize<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    value: Value<'gc>,
+    graph: &mut Graph,
+) -> amf::Value {
+    Serializer::default()
+        .serialize(activation, context, value, graph)
+        .unwrap_or(amf::Value::Undefined)
+}
+
+/// Tracks the objects that were already serialized, so that each of them is
+/// only serialized once.
+#[derive(Default)]
+struct Serializer {
+    objects: HashMap<*const ObjectPtr, ObjectId>,
+}
+
+impl Serializer {
+    fn serialize<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        value: Value<'gc>,
+        graph: &mut Graph,
+    ) -> Option<amf::Value> {
+        Some(match value {
+            Value::Undefined => amf::Value::Undefined,
+            Value::Null => amf::Value::Null,
+            Value::Bool(b) => amf::Value::Bool(b),
+            Value::Number(n) => amf::Value::Number(n),
+            Value::String(s) => amf::Value::String(s),
+            Value::Object(object) => {
+                amf::Value::Object(self.serialize_object(activation, context, object, graph)?)
+            }
+        })
+    }
+
+    fn serialize_object<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        graph: &mut Graph,
+    ) -> Option<ObjectId> {
+        if let Some(id) = self.objects.get(&object.as_ptr()) {
+            return Some(*id);
+        }
+        if object.as_executable().is_some() || object.as_display_object().is_some() {
+            return None;
+        }
+
+        let array_proto = activation.avm.prototypes.array;
+        let is_array = object
+            .is_instance_of(activation, context, object, array_proto)
+            .unwrap_or_default();
+
+        let id = graph.add(amf::Object::anonymous());
+        self.objects.insert(object.as_ptr(), id);
+
+        let length = if is_array { object.length() } else { 0 };
+        let mut dense = Vec::with_capacity(length);
+        for i in 0..length {
+            let element = object.array_element(i);
+            dense.push(
+                self.serialize(activation, context, element, graph)
+                    .unwrap_or(amf::Value::Undefined),
+            );
+        }
+
+        let mut properties = vec![];
+        for key in object.get_keys(activation) {
+            if key.parse::<usize>().map(|i| i < length).unwrap_or(false) {
+                continue;
+            }
+            if let Ok(value) = object.get(&key, activation, context) {
+                if let Some(value) = self.serialize(activation, context, value, graph) {
+                    properties.push((key, value));
+                }
+            }
+        }
+
+        *graph.get_mut(id) = if is_array {
+            amf::Object::Array {
+                dense,
+                associative: properties,
+            }
+        } else {
+            amf::Object::Object {
+                class_name: None,
+                properties,
+            }
+        };
+
+        Some(id)
+    }
+}
+
+/// Deserialize a value, creating the objects it references.
+///
+/// TODO: Objects of registered classes are deserialized as plain objects, and
+/// byte arrays are deserialized as `undefined`.
+pub fn deserialize<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    graph: &Graph,
+    value: &amf::Value,
+) -> Value<'gc> {
+    deserialize_value(activation, context, graph, value, &mut HashMap::new())
+}
+
+fn deserialize_value<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    graph: &Graph,
+    value: &amf::Value,
+    objects: &mut HashMap<ObjectId, Object<'gc>>,
+) -> Value<'gc> {
+    match value {
+        amf::Value::Undefined => Value::Undefined,
+        amf::Value::Null => Value::Null,
+        amf::Value::Bool(b) => Value::Bool(*b),
+        amf::Value::Number(n) | amf::Value::Date(n) => Value::Number(*n),
+        amf::Value::Integer(i) => Value::Number(f64::from(*i)),
+        amf::Value::String(s) | amf::Value::Xml(s) => Value::String(s.clone()),
+        amf::Value::Object(id) => {
+            if let Some(object) = objects.get(id) {
+                return (*object).into();
+            }
+
+            match graph.get(*id) {
+                amf::Object::Object { properties, .. } => {
+                    let object: Object<'gc> = ScriptObject::object(
+                        context.gc_context,
+                        Some(activation.avm.prototypes.object),
+                    )
+                    .into();
+                    objects.insert(*id, object);
+
+                    for (name, value) in properties {
+                        let value = deserialize_value(activation, context, graph, value, objects);
+                        object.define_value(context.gc_context, name, value, EnumSet::empty());
+                    }
+                    object.into()
+                }
+                amf::Object::Array { dense, associative } => {
+                    let array: Object<'gc> = ScriptObject::array(
+                        context.gc_context,
+                        Some(activation.avm.prototypes.array),
+                    )
+                    .into();
+                    objects.insert(*id, array);
+
+                    for (i, value) in dense.iter().enumerate() {
+                        let value = deserialize_value(activation, context, graph, value, objects);
+                        array.set_array_element(i, value, context.gc_context);
+                    }
+                    for (name, value) in associative {
+                        let value = deserialize_value(activation, context, graph, value, objects);
+                        array.define_value(context.gc_context, name, value, EnumSet::empty());
+                    }
+                    array.into()
+                }
+                amf::Object::ByteArray(_) => Value::Undefined,
+            }
+        }
+    }
+}
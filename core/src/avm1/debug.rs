@@ -193,6 +193,22 @@ impl<'a> VariableDumper<'a> {
     }
 }
 
+/// A short description of a value, without the properties of objects.
+pub(crate) fn describe_value(value: &Value<'_>) -> String {
+    match value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => {
+            let mut dumper = VariableDumper::new("");
+            dumper.print_string(value);
+            dumper.output().to_string()
+        }
+        Value::Object(_) => format!("[{}]", value.type_of()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
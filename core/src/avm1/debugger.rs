@@ -0,0 +1,476 @@
+//! An interactive debugger for AVM1 code, driven by a debugger UI.
+//!
+//! The UI exchanges JSON messages with the player through a `DebuggerConnection`.
+//! It sends commands, which are objects with a `command` field:
+//!
+//! * `{"command": "setBreakpoint", "offset": 1234}` pauses before the action at that
+//!   offset in the movie's SWF data, counted from the end of its header. Replies with
+//!   `{"event": "breakpointSet", "id": 1}`.
+//! * `{"command": "setBreakpoint", "clip": "_level0.menu", "frame": 5}` pauses before
+//!   the script of a frame of a clip. `clip` defaults to `_level0`, and frames are
+//!   numbered from 1.
+//! * `{"command": "removeBreakpoint", "id": 1}`, which replies with `breakpointRemoved`.
+//! * `{"command": "pauseOnTrace", "enabled": true}` pauses after each `trace`.
+//! * `{"command": "pause"}` pauses before the next action.
+//!
+//! While paused, it can also send:
+//!
+//! * `continue`, `stepInto`, `stepOver` and `stepOut`, which resume the player.
+//! * `callstack`, which replies with the names of the running functions and scripts,
+//!   innermost first.
+//! * `scope`, which replies with the variables of each scope, innermost first.
+//! * `{"command": "inspect", "name": "_root.score"}`, which replies with a dump of
+//!   the value of a variable or path.
+//!
+//! The player sends `paused` when it pauses, with the `reason`, the `offset` and
+//! `action` it paused at, and the `clip` the code belongs to; `resumed` when it
+//! resumes; `trace` with the `message` of each trace; and `error` for bad commands.
+//!
+//! While paused, the player waits for commands without returning, so the movie
+//! doesn't render or respond until it resumes.
+
+use crate::avm1::activation::Activation;
+use crate::avm1::debug::{describe_value, VariableDumper};
+use crate::avm1::TObject;
+use crate::context::UpdateContext;
+use crate::display_object::TDisplayObject;
+use crate::tag_utils::SwfSlice;
+use json::JsonValue;
+use swf::avm1::types::Action;
+
+/// Carries the messages of the debugger to and from its UI.
+pub trait DebuggerConnection {
+    /// Sends a message to the UI, if one is attached.
+    fn send(&mut self, message: &str);
+
+    /// Returns a message that the UI sent, without waiting for one.
+    fn poll(&mut self) -> Option<String>;
+
+    /// Waits for the next message from the UI. Returns `None` if no UI is attached,
+    /// which resumes the player.
+    fn wait(&mut self) -> Option<String>;
+}
+
+/// A place where the player pauses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    /// Before the action at this offset in the movie's SWF data.
+    Offset(usize),
+
+    /// Before the script of a frame of the clip at this path.
+    Frame { clip: String, frame: u16 },
+}
+
+/// When to pause next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    /// Only at breakpoints.
+    Run,
+
+    /// Before the next action, as asked by the UI.
+    Pause,
+
+    /// Before the next action, after stepping.
+    Into,
+
+    /// Before the next action at or above this call depth.
+    Over(u16),
+
+    /// Before the next action above this call depth.
+    Out(u16),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    SetBreakpoint(Breakpoint),
+    RemoveBreakpoint(u32),
+    PauseOnTrace(bool),
+    Pause,
+    Continue,
+    StepInto,
+    StepOver,
+    StepOut,
+    Callstack,
+    Scope,
+    Inspect(String),
+}
+
+impl Command {
+    fn parse(message: &str) -> Result<Self, String> {
+        let message = json::parse(message).map_err(|e| format!("Invalid message: {}", e))?;
+        let command = match message["command"].as_str() {
+            Some(command) => command,
+            None => return Err("Message has no command".to_string()),
+        };
+        let command = match command {
+            "setBreakpoint" => {
+                if let Some(offset) = message["offset"].as_usize() {
+                    Command::SetBreakpoint(Breakpoint::Offset(offset))
+                } else if let Some(frame) = message["frame"].as_u16() {
+                    let clip = message["clip"].as_str().unwrap_or("_level0").to_string();
+                    Command::SetBreakpoint(Breakpoint::Frame { clip, frame })
+                } else {
+                    return Err("Breakpoints need an offset or a frame".to_string());
+                }
+            }
+            "removeBreakpoint" => match message["id"].as_u32() {
+                Some(id) => Command::RemoveBreakpoint(id),
+                None => return Err("removeBreakpoint needs an id".to_string()),
+            },
+            "pauseOnTrace" => Command::PauseOnTrace(message["enabled"].as_bool().unwrap_or(true)),
+            "pause" => Command::Pause,
+            "continue" => Command::Continue,
+            "stepInto" => Command::StepInto,
+            "stepOver" => Command::StepOver,
+            "stepOut" => Command::StepOut,
+            "callstack" => Command::Callstack,
+            "scope" => Command::Scope,
+            "inspect" => match message["name"].as_str() {
+                Some(name) => Command::Inspect(name.to_string()),
+                None => return Err("inspect needs a name".to_string()),
+            },
+            _ => return Err(format!("Unknown command {}", command)),
+        };
+        Ok(command)
+    }
+}
+
+/// An attached debugger, with its breakpoints.
+pub struct Debugger {
+    connection: Box<dyn DebuggerConnection>,
+    breakpoints: Vec<(u32, Breakpoint)>,
+    next_breakpoint_id: u32,
+    pause_on_trace: bool,
+    step: Step,
+
+    /// The offset of the action being run.
+    offset: usize,
+}
+
+impl Debugger {
+    pub fn new(connection: Box<dyn DebuggerConnection>) -> Self {
+        Self {
+            connection,
+            breakpoints: vec![],
+            next_breakpoint_id: 1,
+            pause_on_trace: false,
+            step: Step::Run,
+            offset: 0,
+        }
+    }
+
+    fn send(&mut self, event: JsonValue) {
+        self.connection.send(&event.dump());
+    }
+
+    fn send_error(&mut self, message: &str) {
+        let mut error = event("error");
+        error["message"] = message.into();
+        self.send(error);
+    }
+
+    /// Runs the commands that the UI sent while the player was running.
+    fn poll_commands(&mut self) {
+        while let Some(message) = self.connection.poll() {
+            match Command::parse(&message) {
+                Ok(command) => self.run_command(command),
+                Err(e) => self.send_error(&e),
+            }
+        }
+    }
+
+    /// Runs a command that doesn't need the player to be paused.
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::SetBreakpoint(breakpoint) => {
+                let id = self.next_breakpoint_id;
+                self.next_breakpoint_id += 1;
+                self.breakpoints.push((id, breakpoint));
+                let mut reply = event("breakpointSet");
+                reply["id"] = id.into();
+                self.send(reply);
+            }
+            Command::RemoveBreakpoint(id) => {
+                let len = self.breakpoints.len();
+                self.breakpoints.retain(|(other, _)| *other != id);
+                if self.breakpoints.len() < len {
+                    let mut reply = event("breakpointRemoved");
+                    reply["id"] = id.into();
+                    self.send(reply);
+                } else {
+                    self.send_error(&format!("There is no breakpoint {}", id));
+                }
+            }
+            Command::PauseOnTrace(enabled) => self.pause_on_trace = enabled,
+            Command::Pause => self.step = Step::Pause,
+            _ => self.send_error("The player isn't paused"),
+        }
+    }
+
+    /// Whether a breakpoint is set on the action at the given offset.
+    fn has_breakpoint(
+        &self,
+        activation: &Activation<'_, '_>,
+        code: &SwfSlice,
+        offset: usize,
+    ) -> bool {
+        for (_, breakpoint) in &self.breakpoints {
+            match breakpoint {
+                Breakpoint::Offset(breakpoint_offset) if *breakpoint_offset == offset => {
+                    return true
+                }
+                Breakpoint::Frame { clip, frame }
+                    if offset == code.start && is_frame_script(code) =>
+                {
+                    let base_clip = activation.base_clip();
+                    let current_frame = base_clip.as_movie_clip().map(|mc| mc.current_frame());
+                    if current_frame == Some(*frame) && base_clip.path() == *clip {
+                        return true;
+                    }
+                }
+                _ => (),
+            }
+        }
+        false
+    }
+
+    /// Tells the UI that the player paused, and answers its commands until it resumes
+    /// the player.
+    fn pause<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reason: &str,
+        action: &str,
+        message: Option<&str>,
+    ) {
+        self.step = Step::Run;
+        let depth = activation.id.depth();
+        let mut paused = event("paused");
+        paused["reason"] = reason.into();
+        paused["offset"] = self.offset.into();
+        paused["action"] = action.into();
+        paused["clip"] = activation.base_clip().path().into();
+        paused["depth"] = depth.into();
+        if let Some(message) = message {
+            paused["message"] = message.into();
+        }
+        self.send(paused);
+
+        loop {
+            let message = match self.connection.wait() {
+                Some(message) => message,
+                None => {
+                    // Without a UI, nothing could resume the player at a later pause.
+                    self.breakpoints.clear();
+                    self.pause_on_trace = false;
+                    return;
+                }
+            };
+            let command = match Command::parse(&message) {
+                Ok(command) => command,
+                Err(e) => {
+                    self.send_error(&e);
+                    continue;
+                }
+            };
+            match command {
+                Command::Continue => break,
+                Command::StepInto => {
+                    self.step = Step::Into;
+                    break;
+                }
+                Command::StepOver => {
+                    self.step = Step::Over(depth);
+                    break;
+                }
+                Command::StepOut => {
+                    self.step = Step::Out(depth);
+                    break;
+                }
+                Command::Pause => (),
+                Command::Callstack => self.send_callstack(activation),
+                Command::Scope => self.send_scope(activation, context),
+                Command::Inspect(name) => self.send_value(activation, context, &name),
+                command => self.run_command(command),
+            }
+        }
+
+        self.send(event("resumed"));
+    }
+
+    fn send_callstack(&mut self, activation: &Activation<'_, '_>) {
+        let mut frames = JsonValue::new_array();
+        let mut id = Some(&activation.id);
+        while let Some(current) = id {
+            let _ = frames.push(current.name());
+            id = current.parent();
+        }
+        let mut reply = event("callstack");
+        reply["frames"] = frames;
+        self.send(reply);
+    }
+
+    fn send_scope<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) {
+        let mut scopes = JsonValue::new_array();
+        let mut scope = Some(activation.scope_cell());
+        while let Some(cell) = scope {
+            let (class, locals, parent) = {
+                let scope = cell.read();
+                (scope.class(), scope.locals_cell(), scope.parent_cell())
+            };
+            let mut variables = JsonValue::new_object();
+            for key in locals.get_keys(activation) {
+                let value = match locals.get(&key, activation, context) {
+                    Ok(value) => describe_value(&value),
+                    Err(e) => format!("Error: {}", e),
+                };
+                variables[key.as_str()] = value.into();
+            }
+            let mut entry = JsonValue::new_object();
+            entry["class"] = format!("{:?}", class).into();
+            entry["variables"] = variables;
+            let _ = scopes.push(entry);
+            scope = parent;
+        }
+        let mut reply = event("scope");
+        reply["scopes"] = scopes;
+        self.send(reply);
+    }
+
+    fn send_value<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        name: &str,
+    ) {
+        let value = match activation.get_variable(context, name) {
+            Ok(value) => VariableDumper::dump(&value, "  ", activation, context),
+            Err(e) => format!("Error: {}", e),
+        };
+        let mut reply = event("value");
+        reply["name"] = name.into();
+        reply["value"] = value.into();
+        self.send(reply);
+    }
+}
+
+/// Checks whether to pause before an action, and if so, pauses until the UI resumes
+/// the player.
+pub(crate) fn before_action<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    code: &SwfSlice,
+    offset: usize,
+    action: &Action,
+) {
+    // The debugger is taken out while it's in use, so that code run to inspect
+    // values doesn't pause again.
+    let mut debugger = match activation.avm.debugger.take() {
+        Some(debugger) => debugger,
+        None => return,
+    };
+    debugger.offset = offset;
+    debugger.poll_commands();
+
+    let depth = activation.id.depth();
+    let reason = match debugger.step {
+        Step::Pause => Some("pause"),
+        Step::Into => Some("step"),
+        Step::Over(step_depth) if depth <= step_depth => Some("step"),
+        Step::Out(step_depth) if depth < step_depth => Some("step"),
+        _ if debugger.has_breakpoint(activation, code, offset) => Some("breakpoint"),
+        _ => None,
+    };
+    if let Some(reason) = reason {
+        debugger.pause(activation, context, reason, &format!("{:?}", action), None);
+    }
+
+    activation.avm.debugger = Some(debugger);
+}
+
+/// Sends the output of a `trace` to the UI, and pauses if it asked to.
+pub(crate) fn after_trace<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    message: &str,
+) {
+    let mut debugger = match activation.avm.debugger.take() {
+        Some(debugger) => debugger,
+        None => return,
+    };
+
+    let mut output = event("trace");
+    output["message"] = message.into();
+    debugger.send(output);
+    if debugger.pause_on_trace {
+        debugger.pause(activation, context, "trace", "Trace", Some(message));
+    }
+
+    activation.avm.debugger = Some(debugger);
+}
+
+fn event(name: &str) -> JsonValue {
+    let mut event = JsonValue::new_object();
+    event["event"] = name.into();
+    event
+}
+
+/// Whether the code is the body of a `DoAction` tag, as frame scripts are, rather
+/// than a clip event or a function.
+fn is_frame_script(code: &SwfSlice) -> bool {
+    const DO_ACTION: u16 = 12;
+    let data = code.movie.data();
+    let len = code.end - code.start;
+    let tag_code_and_length = |at: usize| {
+        data.get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+
+    // Tags shorter than 63 bytes have their length in the tag code.
+    if len < 0x3f
+        && code.start >= 2
+        && tag_code_and_length(code.start - 2) == Some((DO_ACTION << 6) | len as u16)
+    {
+        return true;
+    }
+    code.start >= 6
+        && tag_code_and_length(code.start - 6) == Some((DO_ACTION << 6) | 0x3f)
+        && data.get(code.start - 4..code.start) == Some(&(len as u32).to_le_bytes()[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_breakpoints() {
+        assert_eq!(
+            Command::parse(r#"{"command": "setBreakpoint", "offset": 120}"#),
+            Ok(Command::SetBreakpoint(Breakpoint::Offset(120)))
+        );
+        assert_eq!(
+            Command::parse(r#"{"command": "setBreakpoint", "frame": 3}"#),
+            Ok(Command::SetBreakpoint(Breakpoint::Frame {
+                clip: "_level0".to_string(),
+                frame: 3
+            }))
+        );
+        assert_eq!(
+            Command::parse(r#"{"command": "removeBreakpoint", "id": 2}"#),
+            Ok(Command::RemoveBreakpoint(2))
+        );
+        assert!(Command::parse(r#"{"command": "setBreakpoint"}"#).is_err());
+    }
+
+    #[test]
+    fn parse_bad_messages() {
+        assert!(Command::parse("stepInto").is_err());
+        assert!(Command::parse(r#"{"name": "x"}"#).is_err());
+        assert!(Command::parse(r#"{"command": "jump"}"#).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Disassembles AVM1 bytecode to text, for diagnosing how scripts run.
+
+use std::fmt::Write;
+use swf::avm1::read::Reader;
+use swf::avm1::types::{Action, CatchVar, Value};
+
+/// Disassembles a block of actions, such as the body of a `DoAction` tag.
+///
+/// Each action is on its own line, after its offset from the start of the block. The
+/// bodies of functions, `with` and `try` blocks follow the action that defines them,
+/// indented.
+pub fn disassemble(code: &[u8], swf_version: u8) -> String {
+    let mut output = String::new();
+    let mut constant_pool = vec![];
+    disassemble_block(code, code, 0, swf_version, &mut constant_pool, &mut output);
+    output
+}
+
+fn disassemble_block<'a>(
+    code: &'a [u8],
+    block: &'a [u8],
+    depth: usize,
+    swf_version: u8,
+    constant_pool: &mut Vec<&'a str>,
+    output: &mut String,
+) {
+    let start = block.as_ptr() as usize - code.as_ptr() as usize;
+    let indent = "    ".repeat(depth);
+    let mut reader = Reader::new(block, swf_version);
+    while reader.pos() < block.len() {
+        let offset = start + reader.pos();
+        let action = match reader.read_action() {
+            Ok(Some(action)) => action,
+            Ok(None) => {
+                let _ = writeln!(output, "{:6}: {}End", offset, indent);
+                break;
+            }
+            Err(e) => {
+                let _ = writeln!(output, "{:6}: {}<invalid action: {}>", offset, indent, e);
+                break;
+            }
+        };
+        let next_offset = start + reader.pos();
+        let _ = writeln!(
+            output,
+            "{:6}: {}{}",
+            offset,
+            indent,
+            format_action(&action, next_offset, &constant_pool[..])
+        );
+
+        let mut disassemble_body = |body: &'a [u8], constant_pool: &mut Vec<&'a str>| {
+            disassemble_block(code, body, depth + 1, swf_version, constant_pool, output)
+        };
+        match action {
+            Action::ConstantPool(pool) => *constant_pool = pool,
+            Action::DefineFunction { actions, .. } => disassemble_body(actions, constant_pool),
+            Action::DefineFunction2(function) => disassemble_body(function.actions, constant_pool),
+            Action::With { actions } => disassemble_body(actions, constant_pool),
+            Action::Try(try_block) => {
+                disassemble_body(try_block.try_actions, constant_pool);
+                if let Some((_, catch_actions)) = try_block.catch {
+                    disassemble_body(catch_actions, constant_pool);
+                }
+                if let Some(finally_actions) = try_block.finally {
+                    disassemble_body(finally_actions, constant_pool);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Formats an action as text, with the targets of jumps as offsets, and values from
+/// the constant pool next to their indices.
+pub fn format_action<S: AsRef<str>>(
+    action: &Action,
+    next_offset: usize,
+    constant_pool: &[S],
+) -> String {
+    let jump_target = |offset: i16| next_offset as isize + isize::from(offset);
+    match action {
+        Action::Push(values) => {
+            let values: Vec<String> = values
+                .iter()
+                .map(|value| format_value(value, constant_pool))
+                .collect();
+            format!("Push {}", values.join(", "))
+        }
+        Action::ConstantPool(pool) => format!("ConstantPool {:?}", pool),
+        Action::If { offset } => format!("If -> {}", jump_target(*offset)),
+        Action::Jump { offset } => format!("Jump -> {}", jump_target(*offset)),
+        Action::DefineFunction { name, params, .. } => {
+            format!("DefineFunction {:?} ({})", name, params.join(", "))
+        }
+        Action::DefineFunction2(function) => {
+            let params: Vec<String> = function
+                .params
+                .iter()
+                .map(|param| match param.register_index {
+                    Some(register) => format!("{} (r:{})", param.name, register),
+                    None => param.name.to_string(),
+                })
+                .collect();
+            format!(
+                "DefineFunction2 {:?} ({}), {} registers",
+                function.name,
+                params.join(", "),
+                function.register_count
+            )
+        }
+        Action::With { .. } => "With".to_string(),
+        Action::Try(try_block) => match &try_block.catch {
+            Some((CatchVar::Var(name), _)) => format!("Try, catch into {}", name),
+            Some((CatchVar::Register(register), _)) => format!("Try, catch into r:{}", register),
+            None => "Try".to_string(),
+        },
+        action => format!("{:?}", action),
+    }
+}
+
+fn format_value<S: AsRef<str>>(value: &Value, constant_pool: &[S]) -> String {
+    match value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Int(value) => value.to_string(),
+        Value::Float(value) => format!("{}f", value),
+        Value::Double(value) => format!("{}d", value),
+        Value::Str(value) => format!("{:?}", value),
+        Value::Register(register) => format!("r:{}", register),
+        Value::ConstantPool(index) => match constant_pool.get(usize::from(*index)) {
+            Some(value) => format!("c:{} {:?}", index, value.as_ref()),
+            None => format!("c:{}", index),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassemble_push_and_trace() {
+        // ConstantPool "hello"; Push c:0; Trace; End
+        let code = [
+            0x88, 0x08, 0x00, 0x01, 0x00, b'h', b'e', b'l', b'l', b'o', 0x00, 0x96, 0x02, 0x00,
+            0x08, 0x00, 0x26, 0x00,
+        ];
+        assert_eq!(
+            disassemble(&code, 6),
+            "     0: ConstantPool [\"hello\"]\n    11: Push c:0 \"hello\"\n    16: Trace\n    17: End\n"
+        );
+    }
+
+    #[test]
+    fn disassemble_jump_targets() {
+        // Jump over a Play to a Stop.
+        let code = [0x99, 0x02, 0x00, 0x01, 0x00, 0x06, 0x07, 0x00];
+        assert_eq!(
+            disassemble(&code, 6),
+            "     0: Jump -> 6\n     5: Play\n     6: Stop\n     7: End\n"
+        );
+    }
+}
@@ -2,7 +2,9 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::globals::stage;
 use crate::avm1::UpdateContext;
+use crate::stage::StageDisplayState;
 /// Parse an FSCommand URL.
 pub fn parse(url: &str) -> Option<&str> {
     log::info!("Checking {}", url);
@@ -13,13 +15,34 @@ pub fn parse(url: &str) -> Option<&str> {
     }
 }
 
-/// TODO: FSCommand URL handling
+/// Handle an FSCommand with the given arguments.
+/// Commands that only apply to the standalone projector are ignored.
 pub fn handle<'gc>(
     fscommand: &str,
+    args: &str,
     _activation: &mut Activation,
-    _ac: &mut UpdateContext,
+    ac: &mut UpdateContext,
 ) -> Result<(), Error<'gc>> {
-    log::warn!("Unhandled FSCommand: {}", fscommand);
+    match fscommand.to_lowercase().as_str() {
+        "trapallkeys" => {
+            // Send every key to the movie, including the ones used for shortcuts.
+            ac.input
+                .set_trap_all_keys(args.eq_ignore_ascii_case("true"));
+        }
+        "fullscreen" => {
+            let display_state = if args.eq_ignore_ascii_case("true") {
+                StageDisplayState::FullScreen
+            } else {
+                StageDisplayState::Normal
+            };
+            stage::change_display_state(ac, display_state);
+        }
+        _ => {
+            if !ac.navigator.fs_command(fscommand, args) {
+                log::warn!("Unhandled FSCommand: {}", fscommand);
+            }
+        }
+    }
 
     //This should be an error.
     Ok(())
@@ -8,11 +8,13 @@ use crate::avm1::super_object::SuperObject;
 use crate::avm1::value::Value;
 use crate::avm1::{Object, ObjectPtr, ScriptObject, TObject, UpdateContext};
 use crate::display_object::{DisplayObject, TDisplayObject};
+use crate::host::{call_host_function, HostFunction};
 use crate::tag_utils::SwfSlice;
 use enumset::EnumSet;
 use gc_arena::{Collect, CollectionContext, Gc, GcCell, MutationContext};
 use std::borrow::Cow;
 use std::fmt;
+use std::rc::Rc;
 use swf::avm1::types::FunctionParam;
 
 /// Represents a function defined in Ruffle's code.
@@ -200,6 +202,9 @@ pub enum Executable<'gc> {
     /// ActionScript data defined by a previous `DefineFunction` or
     /// `DefineFunction2` action.
     Action(Gc<'gc, Avm1Function<'gc>>),
+
+    /// A function provided by the application embedding Ruffle.
+    Host(Rc<HostFunction>),
 }
 
 unsafe impl<'gc> Collect for Executable<'gc> {
@@ -207,6 +212,7 @@ unsafe impl<'gc> Collect for Executable<'gc> {
         match self {
             Self::Native(_) => {}
             Self::Action(af) => af.trace(cc),
+            Self::Host(_) => {}
         }
     }
 }
@@ -219,6 +225,10 @@ impl fmt::Debug for Executable<'_> {
                 .field(&format!("{:p}", nf))
                 .finish(),
             Executable::Action(af) => f.debug_tuple("Executable::Action").field(&af).finish(),
+            Executable::Host(hf) => f
+                .debug_tuple("Executable::Host")
+                .field(&format!("{:p}", &**hf))
+                .finish(),
         }
     }
 }
@@ -243,6 +253,7 @@ impl<'gc> Executable<'gc> {
     ) -> Result<Value<'gc>, Error<'gc>> {
         match self {
             Executable::Native(nf) => nf(activation, ac, this, args),
+            Executable::Host(hf) => call_host_function(hf.as_ref(), activation, ac, args),
             Executable::Action(af) => {
                 let child_scope = GcCell::allocate(
                     ac.gc_context,
@@ -10,27 +10,37 @@ use gc_arena::MutationContext;
 use rand::Rng;
 use std::f64;
 
+pub(crate) mod accessibility;
 mod array;
 pub(crate) mod boolean;
 pub(crate) mod button;
 mod color;
 mod color_transform;
+pub(crate) mod context_menu;
+mod context_menu_item;
 pub(crate) mod display_object;
 pub(crate) mod error;
 mod function;
 mod key;
+mod load_vars;
+mod local_connection;
 mod math;
 mod matrix;
 pub(crate) mod mouse;
 pub(crate) mod movie_clip;
 mod movie_clip_loader;
+mod net_connection;
+#[cfg(feature = "rtmp")]
+mod net_stream;
 pub(crate) mod number;
 mod object;
 mod point;
+mod print_job;
 mod rectangle;
+mod selection;
 pub(crate) mod shared_object;
 mod sound;
-mod stage;
+pub(crate) mod stage;
 pub(crate) mod string;
 pub(crate) mod system;
 pub(crate) mod system_capabilities;
@@ -38,6 +48,7 @@ pub(crate) mod system_ime;
 pub(crate) mod system_security;
 pub(crate) mod text_field;
 mod text_format;
+mod text_snapshot;
 mod xml;
 
 #[allow(non_snake_case, unused_must_use)] //can't use errors yet
@@ -51,7 +62,14 @@ pub fn getURL<'a, 'gc>(
     if let Some(url_val) = args.get(0) {
         let url = url_val.coerce_to_string(activation, context)?;
         if let Some(fscommand) = fscommand::parse(&url) {
-            fscommand::handle(fscommand, activation, context);
+            let fscommand_args = if let Some(fscommand_args) = args.get(1) {
+                fscommand_args
+                    .coerce_to_string(activation, context)?
+                    .to_string()
+            } else {
+                String::new()
+            };
+            fscommand::handle(fscommand, &fscommand_args, activation, context)?;
             return Ok(Value::Undefined);
         }
 
@@ -152,6 +170,9 @@ pub struct SystemPrototypes<'gc> {
     pub rectangle_constructor: Object<'gc>,
     pub shared_object: Object<'gc>,
     pub color_transform: Object<'gc>,
+    pub text_snapshot: Object<'gc>,
+    pub context_menu: Object<'gc>,
+    pub context_menu_item: Object<'gc>,
 }
 
 unsafe impl<'gc> gc_arena::Collect for SystemPrototypes<'gc> {
@@ -174,6 +195,9 @@ unsafe impl<'gc> gc_arena::Collect for SystemPrototypes<'gc> {
         self.rectangle.trace(cc);
         self.rectangle_constructor.trace(cc);
         self.shared_object.trace(cc);
+        self.text_snapshot.trace(cc);
+        self.context_menu.trace(cc);
+        self.context_menu_item.trace(cc);
     }
 }
 
@@ -200,6 +224,8 @@ pub fn create_globals<'gc>(
         text_field::create_proto(gc_context, object_proto, function_proto);
     let text_format_proto: Object<'gc> =
         text_format::create_proto(gc_context, object_proto, function_proto);
+    let text_snapshot_proto: Object<'gc> =
+        text_snapshot::create_proto(gc_context, object_proto, function_proto);
 
     let array_proto: Object<'gc> = array::create_proto(gc_context, object_proto, function_proto);
 
@@ -222,6 +248,21 @@ pub fn create_globals<'gc>(
         rectangle::create_proto(gc_context, object_proto, function_proto);
     let color_transform_proto: Object<'gc> =
         color_transform::create_proto(gc_context, object_proto, function_proto);
+    let print_job_proto: Object<'gc> =
+        print_job::create_proto(gc_context, object_proto, function_proto);
+    let load_vars_proto: Object<'gc> =
+        load_vars::create_proto(gc_context, object_proto, function_proto);
+    let local_connection_proto: Object<'gc> =
+        local_connection::create_proto(gc_context, object_proto, function_proto);
+    let net_connection_proto: Object<'gc> =
+        net_connection::create_proto(gc_context, object_proto, function_proto);
+    #[cfg(feature = "rtmp")]
+    let net_stream_proto: Object<'gc> =
+        net_stream::create_proto(gc_context, object_proto, function_proto);
+    let context_menu_proto: Object<'gc> =
+        context_menu::create_proto(gc_context, object_proto, function_proto);
+    let context_menu_item_proto: Object<'gc> =
+        context_menu_item::create_proto(gc_context, object_proto, function_proto);
 
     //TODO: These need to be constructors and should also set `.prototype` on each one
     let object = object::create_object_object(gc_context, object_proto, function_proto);
@@ -280,6 +321,55 @@ pub fn create_globals<'gc>(
         Some(function_proto),
         Some(text_format_proto),
     );
+    let print_job = FunctionObject::function(
+        gc_context,
+        Executable::Native(print_job::constructor),
+        Some(function_proto),
+        Some(print_job_proto),
+    );
+    let local_connection = FunctionObject::function(
+        gc_context,
+        Executable::Native(local_connection::constructor),
+        Some(function_proto),
+        Some(local_connection_proto),
+    );
+    let load_vars = FunctionObject::function(
+        gc_context,
+        Executable::Native(load_vars::constructor),
+        Some(function_proto),
+        Some(load_vars_proto),
+    );
+    let net_connection = FunctionObject::function(
+        gc_context,
+        Executable::Native(net_connection::constructor),
+        Some(function_proto),
+        Some(net_connection_proto),
+    );
+    #[cfg(feature = "rtmp")]
+    let net_stream = FunctionObject::function(
+        gc_context,
+        Executable::Native(net_stream::constructor),
+        Some(function_proto),
+        Some(net_stream_proto),
+    );
+    let text_snapshot = FunctionObject::function(
+        gc_context,
+        Executable::Native(text_snapshot::constructor),
+        Some(function_proto),
+        Some(text_snapshot_proto),
+    );
+    let context_menu = FunctionObject::function(
+        gc_context,
+        Executable::Native(context_menu::constructor),
+        Some(function_proto),
+        Some(context_menu_proto),
+    );
+    let context_menu_item = FunctionObject::function(
+        gc_context,
+        Executable::Native(context_menu_item::constructor),
+        Some(function_proto),
+        Some(context_menu_item_proto),
+    );
     let array = array::create_array_object(gc_context, Some(array_proto), Some(function_proto));
     let xmlnode = FunctionObject::function(
         gc_context,
@@ -330,6 +420,18 @@ pub fn create_globals<'gc>(
     globals.define_value(gc_context, "Array", array.into(), EnumSet::empty());
     globals.define_value(gc_context, "Button", button.into(), EnumSet::empty());
     globals.define_value(gc_context, "Color", color.into(), EnumSet::empty());
+    globals.define_value(
+        gc_context,
+        "ContextMenu",
+        context_menu.into(),
+        EnumSet::empty(),
+    );
+    globals.define_value(
+        gc_context,
+        "ContextMenuItem",
+        context_menu_item.into(),
+        EnumSet::empty(),
+    );
     globals.define_value(gc_context, "Error", error.into(), EnumSet::empty());
     globals.define_value(gc_context, "Object", object.into(), EnumSet::empty());
     globals.define_value(gc_context, "Function", function.into(), EnumSet::empty());
@@ -348,6 +450,28 @@ pub fn create_globals<'gc>(
         text_format.into(),
         EnumSet::empty(),
     );
+    globals.define_value(gc_context, "PrintJob", print_job.into(), EnumSet::empty());
+    globals.define_value(
+        gc_context,
+        "LocalConnection",
+        local_connection.into(),
+        EnumSet::empty(),
+    );
+    globals.define_value(gc_context, "LoadVars", load_vars.into(), EnumSet::empty());
+    globals.define_value(
+        gc_context,
+        "NetConnection",
+        net_connection.into(),
+        EnumSet::empty(),
+    );
+    #[cfg(feature = "rtmp")]
+    globals.define_value(gc_context, "NetStream", net_stream.into(), EnumSet::empty());
+    globals.define_value(
+        gc_context,
+        "TextSnapshot",
+        text_snapshot.into(),
+        EnumSet::empty(),
+    );
     globals.define_value(gc_context, "XMLNode", xmlnode.into(), EnumSet::empty());
     globals.define_value(gc_context, "XML", xml.into(), EnumSet::empty());
     globals.define_value(gc_context, "String", string.into(), EnumSet::empty());
@@ -409,6 +533,17 @@ pub fn create_globals<'gc>(
         )),
         EnumSet::empty(),
     );
+    globals.define_value(
+        gc_context,
+        "Selection",
+        Value::Object(selection::create_selection_object(
+            gc_context,
+            Some(object_proto),
+            Some(function_proto),
+            &listeners.selection,
+        )),
+        EnumSet::empty(),
+    );
     globals.define_value(
         gc_context,
         "Key",
@@ -419,6 +554,16 @@ pub fn create_globals<'gc>(
         )),
         EnumSet::empty(),
     );
+    globals.define_value(
+        gc_context,
+        "Accessibility",
+        Value::Object(accessibility::create_accessibility_object(
+            gc_context,
+            Some(object_proto),
+            Some(function_proto),
+        )),
+        EnumSet::empty(),
+    );
     globals.define_value(
         gc_context,
         "Stage",
@@ -427,6 +572,7 @@ pub fn create_globals<'gc>(
             Some(object_proto),
             Some(array_proto),
             Some(function_proto),
+            &listeners.stage,
         )),
         EnumSet::empty(),
     );
@@ -493,6 +639,9 @@ pub fn create_globals<'gc>(
             rectangle_constructor: rectangle,
             shared_object: shared_object_proto,
             color_transform: color_transform_proto,
+            text_snapshot: text_snapshot_proto,
+            context_menu: context_menu_proto,
+            context_menu_item: context_menu_item_proto,
         },
         globals.into(),
         listeners,
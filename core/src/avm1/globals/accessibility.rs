@@ -0,0 +1,228 @@
+//! Accessibility object
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::backend::accessibility::{AccessibilityNode, AccessibilityRole};
+use crate::display_object::{DisplayObject, TDisplayObject};
+use gc_arena::MutationContext;
+
+pub fn create_accessibility_object<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Option<Object<'gc>>,
+    fn_proto: Option<Object<'gc>>,
+) -> Object<'gc> {
+    let mut accessibility = ScriptObject::object(gc_context, proto);
+
+    accessibility.force_set_function(
+        "isActive",
+        is_active,
+        gc_context,
+        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+        fn_proto,
+    );
+
+    accessibility.force_set_function(
+        "sendEvent",
+        send_event,
+        gc_context,
+        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+        fn_proto,
+    );
+
+    accessibility.force_set_function(
+        "updateProperties",
+        update_properties,
+        gc_context,
+        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+        fn_proto,
+    );
+
+    accessibility.into()
+}
+
+fn is_active<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(context.accessibility.is_active().into())
+}
+
+fn send_event<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Backends find out about changes by comparing trees, so any event just refreshes the tree.
+    update_tree(activation, context)?;
+    Ok(Value::Undefined)
+}
+
+fn update_properties<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    update_tree(activation, context)?;
+    Ok(Value::Undefined)
+}
+
+/// Builds the accessibility tree of every level and sends it to the accessibility backend.
+///
+/// Nothing is built when no assistive technology is running.
+pub fn update_tree<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Result<(), Error<'gc>> {
+    if !context.accessibility.is_active() {
+        return Ok(());
+    }
+
+    let levels: Vec<_> = context.levels.values().copied().collect();
+    let mut nodes = vec![];
+    for level in levels {
+        build_nodes(activation, context, level, &mut nodes)?;
+    }
+    context.accessibility.update_tree(nodes);
+    Ok(())
+}
+
+/// The properties set on the `_accProps` object of a display object.
+#[derive(Default)]
+struct AccessibilityProperties {
+    /// Hides the object and its children from assistive technologies.
+    silent: bool,
+
+    /// Hides the children of the object from assistive technologies.
+    force_simple: bool,
+
+    name: String,
+    description: String,
+    shortcut: String,
+}
+
+impl AccessibilityProperties {
+    fn from_object<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+    ) -> Result<Self, Error<'gc>> {
+        let props = match object.get("_accProps", activation, context)? {
+            Value::Object(props) => props,
+            _ => return Ok(Self::default()),
+        };
+
+        let swf_version = activation.current_swf_version();
+        let mut get_string = |name: &str| -> Result<String, Error<'gc>> {
+            match props.get(name, activation, context)? {
+                Value::Undefined | Value::Null => Ok(String::new()),
+                value => Ok(value.coerce_to_string(activation, context)?.into_owned()),
+            }
+        };
+        let name = get_string("name")?;
+        let description = get_string("description")?;
+        let shortcut = get_string("shortcut")?;
+
+        Ok(Self {
+            silent: props
+                .get("silent", activation, context)?
+                .as_bool(swf_version),
+            force_simple: props
+                .get("forceSimple", activation, context)?
+                .as_bool(swf_version),
+            name,
+            description,
+            shortcut,
+        })
+    }
+}
+
+/// Appends the accessibility nodes of a display object and its descendants.
+fn build_nodes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    display_object: DisplayObject<'gc>,
+    nodes: &mut Vec<AccessibilityNode>,
+) -> Result<(), Error<'gc>> {
+    if !display_object.visible() {
+        return Ok(());
+    }
+
+    let props = match display_object.object() {
+        Value::Object(object) => AccessibilityProperties::from_object(activation, context, object)?,
+        _ => AccessibilityProperties::default(),
+    };
+    if props.silent {
+        return Ok(());
+    }
+
+    let mut name = props.name;
+    let mut value = None;
+    let role = if let Some(text) = display_object.as_edit_text() {
+        if text.is_editable() {
+            value = Some(if text.is_password() {
+                "*".repeat(text.text_length())
+            } else {
+                text.text()
+            });
+            AccessibilityRole::InputText
+        } else {
+            if name.is_empty() {
+                name = text.text();
+            }
+            AccessibilityRole::StaticText
+        }
+    } else if let Some(text) = display_object.as_text() {
+        if name.is_empty() {
+            for c in text.chars(context) {
+                if c.new_line && !name.is_empty() {
+                    name.push('\n');
+                }
+                name.push(c.character);
+            }
+        }
+        AccessibilityRole::StaticText
+    } else if display_object.as_button().is_some() {
+        AccessibilityRole::Button
+    } else if let Some(clip) = display_object.as_movie_clip() {
+        if clip.is_button_mode(activation.avm, context) {
+            AccessibilityRole::Button
+        } else {
+            AccessibilityRole::Group
+        }
+    } else {
+        // Shapes and bitmaps have nothing to tell assistive technologies.
+        return Ok(());
+    };
+
+    let mut children = vec![];
+    if role == AccessibilityRole::Group && !props.force_simple {
+        if let Some(clip) = display_object.as_movie_clip() {
+            for child in clip.children_by_depth() {
+                build_nodes(activation, context, child, &mut children)?;
+            }
+        }
+    }
+
+    // Unnamed movie clips are only used for layout, so their children take their place.
+    if role == AccessibilityRole::Group && name.is_empty() {
+        nodes.extend(children);
+        return Ok(());
+    }
+
+    nodes.push(AccessibilityNode {
+        role,
+        name,
+        description: props.description,
+        shortcut: props.shortcut,
+        value,
+        bounds: display_object.world_bounds(),
+        children,
+    });
+    Ok(())
+}
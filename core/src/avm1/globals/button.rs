@@ -14,6 +14,8 @@ pub fn create_proto<'gc>(
     let object = ScriptObject::object(gc_context, Some(proto));
 
     display_object::define_display_object_proto(gc_context, object, fn_proto);
+    display_object::define_button_mode_properties(gc_context, object);
+    display_object::define_cache_as_bitmap_property(gc_context, object);
 
     object.into()
 }
@@ -0,0 +1,125 @@
+//! `ContextMenu` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use gc_arena::MutationContext;
+
+/// The built-in items of the player's menu that can be hidden with `builtInItems`.
+pub const BUILT_IN_ITEMS: &[&str] = &[
+    "save",
+    "zoom",
+    "quality",
+    "play",
+    "loop",
+    "rewind",
+    "forward_back",
+    "print",
+];
+
+/// Implements `ContextMenu`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let callback = args.get(0).cloned().unwrap_or(Value::Undefined);
+    this.set("onSelect", callback, activation, context)?;
+
+    let built_in_items =
+        ScriptObject::object(context.gc_context, Some(activation.avm.prototypes.object));
+    for item in BUILT_IN_ITEMS {
+        built_in_items.set(item, true.into(), activation, context)?;
+    }
+    this.set("builtInItems", built_in_items.into(), activation, context)?;
+
+    let custom_items =
+        ScriptObject::array(context.gc_context, Some(activation.avm.prototypes.array));
+    this.set("customItems", custom_items.into(), activation, context)?;
+
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "copy",
+        copy,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "hideBuiltInItems",
+        hide_built_in_items,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn copy<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let copy: Object<'gc> = ScriptObject::object(
+        context.gc_context,
+        Some(activation.avm.prototypes.context_menu),
+    )
+    .into();
+    let callback = this.get("onSelect", activation, context)?;
+    constructor(activation, context, copy, &[callback])?;
+
+    let built_in_items = this.get("builtInItems", activation, context)?;
+    let copied_built_in_items = copy.get("builtInItems", activation, context)?;
+    if let (Value::Object(built_in_items), Value::Object(copied_built_in_items)) =
+        (built_in_items, copied_built_in_items)
+    {
+        for item in BUILT_IN_ITEMS {
+            let value = built_in_items.get(item, activation, context)?;
+            copied_built_in_items.set(item, value, activation, context)?;
+        }
+    }
+
+    let custom_items = this.get("customItems", activation, context)?;
+    let copied_custom_items = copy.get("customItems", activation, context)?;
+    if let (Value::Object(custom_items), Value::Object(copied_custom_items)) =
+        (custom_items, copied_custom_items)
+    {
+        for i in 0..custom_items.length() {
+            let item = match custom_items.array_element(i) {
+                Value::Object(item) => item.call_method("copy", &[], activation, context)?,
+                item => item,
+            };
+            copied_custom_items.set_array_element(i, item, context.gc_context);
+        }
+    }
+
+    Ok(copy.into())
+}
+
+fn hide_built_in_items<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Value::Object(built_in_items) = this.get("builtInItems", activation, context)? {
+        for item in BUILT_IN_ITEMS {
+            built_in_items.set(item, false.into(), activation, context)?;
+        }
+    }
+    Ok(Value::Undefined)
+}
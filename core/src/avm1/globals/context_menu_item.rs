@@ -0,0 +1,87 @@
+//! `ContextMenuItem` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use gc_arena::MutationContext;
+
+/// Implements `ContextMenuItem`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let caption = args
+        .get(0)
+        .cloned()
+        .unwrap_or(Value::Undefined)
+        .coerce_to_string(activation, context)?
+        .to_string();
+    let callback = args.get(1).cloned().unwrap_or(Value::Undefined);
+    let separator_before = args
+        .get(2)
+        .map_or(false, |v| v.as_bool(activation.current_swf_version()));
+    let enabled = args
+        .get(3)
+        .map_or(true, |v| v.as_bool(activation.current_swf_version()));
+    let visible = args
+        .get(4)
+        .map_or(true, |v| v.as_bool(activation.current_swf_version()));
+
+    this.set("caption", caption.into(), activation, context)?;
+    this.set("onSelect", callback, activation, context)?;
+    this.set(
+        "separatorBefore",
+        separator_before.into(),
+        activation,
+        context,
+    )?;
+    this.set("enabled", enabled.into(), activation, context)?;
+    this.set("visible", visible.into(), activation, context)?;
+
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "copy",
+        copy,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn copy<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let copy: Object<'gc> = ScriptObject::object(
+        context.gc_context,
+        Some(activation.avm.prototypes.context_menu_item),
+    )
+    .into();
+    for name in &[
+        "caption",
+        "onSelect",
+        "separatorBefore",
+        "enabled",
+        "visible",
+    ] {
+        let value = this.get(name, activation, context)?;
+        copy.set(name, value, activation, context)?;
+    }
+    Ok(copy.into())
+}
@@ -6,6 +6,7 @@ use crate::avm1::function::Executable;
 use crate::avm1::property::Attribute::*;
 use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
 use crate::display_object::{DisplayObject, TDisplayObject};
+use crate::prelude::Depth;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 
@@ -19,6 +20,20 @@ pub const AVM_DEPTH_BIAS: i32 = 16384;
 /// What is the derivation of this number...?
 pub const AVM_MAX_DEPTH: i32 = 2_130_706_428;
 
+/// Whether a depth, offset by `AVM_DEPTH_BIAS`, is one that scripts can move clips to.
+///
+/// Depths are split in two ranges: the timeline depths (-16384 to -1 in ActionScript)
+/// hold the clips placed in the Flash IDE, and the dynamic depths (from 0) hold the
+/// clips created by scripts.
+pub fn is_avm_depth(depth: Depth) -> bool {
+    (0..=AVM_MAX_DEPTH).contains(&depth)
+}
+
+/// Whether a depth, offset by `AVM_DEPTH_BIAS`, is in the dynamic depth range.
+pub fn is_dynamic_depth(depth: Depth) -> bool {
+    (AVM_DEPTH_BIAS..=AVM_MAX_DEPTH).contains(&depth)
+}
+
 macro_rules! with_display_object {
     ( $gc_context: ident, $object:ident, $fn_proto: expr, $($name:expr => $fn:expr),* ) => {{
         $(
@@ -78,6 +93,58 @@ pub fn define_display_object_proto<'gc>(
     );
 }
 
+/// Add the properties that control how buttons and button-mode movie clips react to the mouse.
+pub fn define_button_mode_properties<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    object: ScriptObject<'gc>,
+) {
+    object.define_value(gc_context, "enabled", true.into(), DontEnum.into());
+    object.define_value(gc_context, "trackAsMenu", false.into(), DontEnum.into());
+    object.define_value(gc_context, "useHandCursor", true.into(), DontEnum.into());
+}
+
+/// Add the `cacheAsBitmap` property shared by buttons and movie clips.
+pub fn define_cache_as_bitmap_property<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    object: ScriptObject<'gc>,
+) {
+    object.add_property(
+        gc_context,
+        "cacheAsBitmap",
+        Executable::Native(get_cache_as_bitmap),
+        Some(Executable::Native(set_cache_as_bitmap)),
+        DontDelete | DontEnum,
+    );
+}
+
+pub fn get_cache_as_bitmap<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this
+        .as_display_object()
+        .map(|display_object| display_object.is_bitmap_cached().into())
+        .unwrap_or(Value::Undefined))
+}
+
+pub fn set_cache_as_bitmap<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        let value = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .as_bool(activation.current_swf_version());
+        display_object.set_bitmap_cached(context.gc_context, value);
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn get_parent<'gc>(
     activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
@@ -0,0 +1,271 @@
+//! `LoadVars` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::backend::navigator::{NavigationMethod, RequestOptions};
+use gc_arena::MutationContext;
+use std::collections::HashMap;
+use url::form_urlencoded;
+
+/// Implements `LoadVars`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "load",
+        load,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "sendAndLoad",
+        send_and_load,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "decode",
+        decode,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "toString",
+        to_string,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "onData",
+        on_data,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// Loads variables from a URL into this object.
+fn load<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let url = match args.get(0) {
+        Some(url) => url.coerce_to_string(activation, context)?.to_string(),
+        None => return Ok(false.into()),
+    };
+
+    spawn_load(activation, context, this, &url, RequestOptions::get())?;
+
+    Ok(true.into())
+}
+
+/// Sends the variables of this object to a URL, and opens the response in a window.
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let url = match args.get(0) {
+        Some(url) => url.coerce_to_string(activation, context)?.to_string(),
+        None => return Ok(false.into()),
+    };
+    let window = match args.get(1) {
+        Some(window) => Some(window.coerce_to_string(activation, context)?.to_string()),
+        None => None,
+    };
+    let method = method_arg(activation, context, args.get(2))?;
+    let vars = form_values(activation, context, this)?;
+
+    context
+        .navigator
+        .navigate_to_url(url, window, Some((method, vars)));
+
+    Ok(true.into())
+}
+
+/// Sends the variables of this object to a URL, and loads the response into
+/// the target object.
+fn send_and_load<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let url = match args.get(0) {
+        Some(url) => url.coerce_to_string(activation, context)?.to_string(),
+        None => return Ok(false.into()),
+    };
+    let target = match args.get(1) {
+        Some(Value::Object(target)) => *target,
+        _ => return Ok(false.into()),
+    };
+    let method = method_arg(activation, context, args.get(2))?;
+    let vars = form_values(activation, context, this)?;
+    let query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(vars.iter())
+        .finish();
+
+    let (url, options) = match method {
+        NavigationMethod::GET if url.contains('?') => {
+            (format!("{}&{}", url, query), RequestOptions::get())
+        }
+        NavigationMethod::GET => (format!("{}?{}", url, query), RequestOptions::get()),
+        NavigationMethod::POST => (
+            url,
+            RequestOptions::post(Some((
+                query.into_bytes(),
+                "application/x-www-form-urlencoded".to_string(),
+            ))),
+        ),
+    };
+
+    spawn_load(activation, context, target, &url, options)?;
+
+    Ok(true.into())
+}
+
+/// Adds the variables in a URL-encoded string to this object.
+fn decode<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(src) = args.get(0) {
+        let src = src.coerce_to_string(activation, context)?;
+        for (name, value) in form_urlencoded::parse(src.as_bytes()) {
+            this.set(&name, value.into_owned().into(), activation, context)?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Returns the variables of this object as a URL-encoded string.
+fn to_string<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let vars = form_values(activation, context, this)?;
+    let query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(vars.iter())
+        .finish();
+
+    Ok(query.into())
+}
+
+/// Receives the raw response of a load, or `undefined` if the load failed.
+///
+/// Scripts can replace this to parse the response themselves.
+fn on_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    match args.get(0) {
+        None | Some(Value::Undefined) => {
+            this.call_method("onLoad", &[false.into()], activation, context)?;
+        }
+        Some(src) => {
+            this.call_method("decode", &[src.clone()], activation, context)?;
+            this.set("loaded", true.into(), activation, context)?;
+            this.call_method("onLoad", &[true.into()], activation, context)?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn spawn_load<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    target: Object<'gc>,
+    url: &str,
+    options: RequestOptions,
+) -> Result<(), Error<'gc>> {
+    target.set("loaded", false.into(), activation, context)?;
+
+    let fetch = context.navigator.fetch(url, options);
+    let process = context.load_manager.load_vars_into_object(
+        context.player.clone().unwrap(),
+        target,
+        activation.target_clip_or_root(),
+        fetch,
+    );
+    context.navigator.spawn_future(process);
+
+    Ok(())
+}
+
+/// The method of a request, which is `POST` unless `GET` is given.
+fn method_arg<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    method: Option<&Value<'gc>>,
+) -> Result<NavigationMethod, Error<'gc>> {
+    let method = match method {
+        Some(method) => method.coerce_to_string(activation, context)?,
+        None => return Ok(NavigationMethod::POST),
+    };
+    Ok(NavigationMethod::from_method_str(&method).unwrap_or(NavigationMethod::POST))
+}
+
+/// The enumerable properties of an object, as strings.
+fn form_values<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    object: Object<'gc>,
+) -> Result<HashMap<String, String>, Error<'gc>> {
+    let mut vars = HashMap::new();
+    for name in object.get_keys(activation) {
+        let value = object.get(&name, activation, context)?;
+        if let Value::Object(value) = value {
+            // Methods that were added to the object aren't sent.
+            if value.as_executable().is_some() {
+                continue;
+            }
+        }
+        let value = value.coerce_to_string(activation, context)?.to_string();
+        vars.insert(name, value);
+    }
+    Ok(vars)
+}
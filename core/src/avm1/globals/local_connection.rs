@@ -0,0 +1,126 @@
+//! `LocalConnection` impl
+
+use crate::amf::Graph;
+use crate::avm1::activation::Activation;
+use crate::avm1::amf;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, UpdateContext, Value};
+use crate::local_connection::LocalConnectionReceiver;
+use gc_arena::MutationContext;
+
+/// Implements `LocalConnection`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "domain",
+        domain,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = match args.get(0) {
+        Some(Value::String(name)) => name.clone(),
+        Some(value @ Value::Object(_)) => value.coerce_to_string(activation, context)?.to_string(),
+        _ => return Ok(false.into()),
+    };
+
+    Ok(context
+        .local_connections
+        .connect(&name, LocalConnectionReceiver::Avm1(this))
+        .into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if args.len() < 2 {
+        return Ok(false.into());
+    }
+
+    let name = args[0].coerce_to_string(activation, context)?.to_string();
+    let method_name = args[1].coerce_to_string(activation, context)?.to_string();
+    let mut graph = Graph::new();
+    let message_args: Vec<_> = args[2..]
+        .iter()
+        .map(|arg| amf::serialize(activation, context, arg.clone(), &mut graph))
+        .collect();
+
+    Ok(context
+        .local_connections
+        .send(&name, &method_name, &graph, &message_args)
+        .into())
+}
+
+fn close<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    context
+        .local_connections
+        .close(LocalConnectionReceiver::Avm1(this));
+
+    Ok(Value::Undefined)
+}
+
+fn domain<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok("localhost".into())
+}
@@ -2,15 +2,20 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::function::Executable;
 use crate::avm1::globals::display_object::{self, AVM_DEPTH_BIAS, AVM_MAX_DEPTH};
 use crate::avm1::globals::matrix::gradient_object_to_matrix;
+use crate::avm1::globals::rectangle::construct_new_rectangle;
 use crate::avm1::property::Attribute::*;
+use crate::avm1::text_snapshot_object::TextSnapshotObject;
 use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::backend::audio::AudioSource;
 use crate::backend::navigator::NavigationMethod;
 use crate::display_object::{DisplayObject, EditText, MovieClip, TDisplayObject};
 use crate::prelude::*;
 use crate::shape_utils::DrawCommand;
 use crate::tag_utils::SwfSlice;
+use crate::unimplemented::FeatureKind;
 use gc_arena::MutationContext;
 use swf::{
     FillStyle, Gradient, GradientInterpolation, GradientRecord, GradientSpread, LineCapStyle,
@@ -63,7 +68,7 @@ pub fn hit_test<'gc>(
             .map(|v| v.as_bool(activation.current_swf_version()))
             .unwrap_or(false);
         if shape {
-            log::warn!("Ignoring shape hittest and using bounding box instead. Shape based hit detection is not yet implemented. See https://github.com/ruffle-rs/ruffle/issues/177");
+            log::warn!("Ignoring shape hittest and using bounding boxes of children instead. Shape based hit detection is not yet implemented. See https://github.com/ruffle-rs/ruffle/issues/177");
         }
         if x.is_finite() && y.is_finite() {
             // The docs say the point is in "Stage coordinates", but actually they are in root coordinates.
@@ -71,7 +76,12 @@ pub fn hit_test<'gc>(
             let point = movie_clip
                 .root()
                 .local_to_global((Twips::from_pixels(x), Twips::from_pixels(y)));
-            return Ok(movie_clip.hit_test(point).into());
+            let is_hit = if shape {
+                movie_clip.hit_test(point)
+            } else {
+                movie_clip.world_bounds().contains(point)
+            };
+            return Ok(is_hit.into());
         }
     } else if args.len() == 1 {
         let other = args
@@ -98,11 +108,14 @@ pub fn create_proto<'gc>(
     let mut object = ScriptObject::object(gc_context, Some(proto));
 
     display_object::define_display_object_proto(gc_context, object, fn_proto);
+    display_object::define_button_mode_properties(gc_context, object);
+    display_object::define_cache_as_bitmap_property(gc_context, object);
 
     with_movie_clip!(
         gc_context,
         object,
         Some(fn_proto),
+        "attachAudio" => attach_audio,
         "attachMovie" => attach_movie,
         "createEmptyMovieClip" => create_empty_movie_clip,
         "createTextField" => create_text_field,
@@ -110,8 +123,10 @@ pub fn create_proto<'gc>(
         "getBounds" => get_bounds,
         "getBytesLoaded" => get_bytes_loaded,
         "getBytesTotal" => get_bytes_total,
+        "getInstanceAtDepth" => get_instance_at_depth,
         "getNextHighestDepth" => get_next_highest_depth,
         "getRect" => get_rect,
+        "getTextSnapshot" => get_text_snapshot,
         "globalToLocal" => global_to_local,
         "gotoAndPlay" => goto_and_play,
         "gotoAndStop" => goto_and_stop,
@@ -123,6 +138,7 @@ pub fn create_proto<'gc>(
         "play" => play,
         "prevFrame" => prev_frame,
         "removeMovieClip" => remove_movie_clip,
+        "setMask" => set_mask,
         "startDrag" => start_drag,
         "stop" => stop,
         "stopDrag" => stop_drag,
@@ -131,17 +147,126 @@ pub fn create_proto<'gc>(
         "unloadMovie" => unload_movie,
         "beginFill" => begin_fill,
         "beginGradientFill" => begin_gradient_fill,
+        "beginBitmapFill" => begin_bitmap_fill,
         "moveTo" => move_to,
         "lineTo" => line_to,
         "curveTo" => curve_to,
         "endFill" => end_fill,
         "lineStyle" => line_style,
+        "lineGradientStyle" => line_gradient_style,
         "clear" => clear
     );
 
+    object.add_property(
+        gc_context,
+        "_lockroot",
+        Executable::Native(get_lock_root),
+        Some(Executable::Native(set_lock_root)),
+        DontDelete | DontEnum,
+    );
+
+    object.add_property(
+        gc_context,
+        "scale9Grid",
+        Executable::Native(get_scale9_grid),
+        Some(Executable::Native(set_scale9_grid)),
+        DontDelete | DontEnum,
+    );
+
     object.into()
 }
 
+fn get_lock_root<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this
+        .as_display_object()
+        .and_then(|o| o.as_movie_clip())
+        .map(|mc| mc.lock_root().into())
+        .unwrap_or(Value::Undefined))
+}
+
+fn set_lock_root<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(movie_clip) = this.as_display_object().and_then(|o| o.as_movie_clip()) {
+        let value = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .as_bool(activation.current_swf_version());
+        movie_clip.set_lock_root(context.gc_context, value);
+    }
+    Ok(Value::Undefined)
+}
+
+fn get_scale9_grid<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let movie_clip = this.as_display_object().and_then(|o| o.as_movie_clip());
+    if let Some(grid) = movie_clip.and_then(|mc| mc.scaling_grid()) {
+        let args = [
+            grid.x_min.to_pixels().into(),
+            grid.y_min.to_pixels().into(),
+            grid.width().to_pixels().into(),
+            grid.height().to_pixels().into(),
+        ];
+        return Ok(construct_new_rectangle(&args, activation, context)?.into());
+    }
+    Ok(Value::Undefined)
+}
+
+fn set_scale9_grid<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let movie_clip = if let Some(mc) = this.as_display_object().and_then(|o| o.as_movie_clip()) {
+        mc
+    } else {
+        return Ok(Value::Undefined);
+    };
+    let grid = match args.get(0) {
+        Some(Value::Object(rect)) => {
+            let x = rect
+                .get("x", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let y = rect
+                .get("y", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let width = rect
+                .get("width", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let height = rect
+                .get("height", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            if !(x.is_finite() && y.is_finite() && width.is_finite() && height.is_finite()) {
+                log::warn!("MovieClip.scale9Grid: Invalid rectangle");
+                return Ok(Value::Undefined);
+            }
+            Some(BoundingBox {
+                x_min: Twips::from_pixels(x),
+                y_min: Twips::from_pixels(y),
+                x_max: Twips::from_pixels(x + width),
+                y_max: Twips::from_pixels(y + height),
+                valid: true,
+            })
+        }
+        _ => None,
+    };
+    movie_clip.set_scaling_grid(context.gc_context, grid);
+    Ok(Value::Undefined)
+}
+
 fn line_style<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc>,
@@ -179,10 +304,10 @@ fn line_style<'gc>(
             .and_then(|v| v.coerce_to_string(activation, context).ok())
             .as_deref()
         {
-            Some("normal") => (true, true),
+            Some("none") => (false, false),
             Some("vertical") => (true, false),
             Some("horizontal") => (false, true),
-            _ => (false, false),
+            _ => (true, true),
         };
         let cap_style = match args
             .get(5)
@@ -268,89 +393,147 @@ fn begin_gradient_fill<'gc>(
     context: &mut UpdateContext<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let (Some(method), Some(colors), Some(alphas), Some(ratios), Some(matrix)) = (
-        args.get(0),
-        args.get(1),
-        args.get(2),
-        args.get(3),
-        args.get(4),
-    ) {
-        let method = method.coerce_to_string(activation, context)?;
-        let colors = colors.coerce_to_object(activation, context).array();
-        let alphas = alphas.coerce_to_object(activation, context).array();
-        let ratios = ratios.coerce_to_object(activation, context).array();
-        let matrix_object = matrix.coerce_to_object(activation, context);
-        if colors.len() != alphas.len() || colors.len() != ratios.len() {
-            log::warn!(
-                "beginGradientFill() received different sized arrays for colors, alphas and ratios"
-            );
-            return Ok(Value::Undefined);
+    if args.len() >= 5 {
+        if let Some(style) = gradient_style("beginGradientFill", activation, context, args)? {
+            movie_clip.set_fill_style(context, Some(style));
         }
-        let mut records = Vec::with_capacity(colors.len());
-        for i in 0..colors.len() {
-            let ratio = ratios[i]
-                .coerce_to_f64(activation, context)?
-                .min(255.0)
-                .max(0.0);
-            let rgb = colors[i].coerce_to_u32(activation, context)?;
-            let alpha = alphas[i]
-                .coerce_to_f64(activation, context)?
-                .min(100.0)
-                .max(0.0);
-            records.push(GradientRecord {
-                ratio: ratio as u8,
-                color: Color::from_rgb(rgb, (alpha / 100.0 * 255.0) as u8),
-            });
-        }
-        let matrix = gradient_object_to_matrix(matrix_object, activation, context)?;
-        let spread = match args
-            .get(5)
-            .and_then(|v| v.coerce_to_string(activation, context).ok())
-            .as_deref()
-        {
-            Some("reflect") => GradientSpread::Reflect,
-            Some("repeat") => GradientSpread::Repeat,
-            _ => GradientSpread::Pad,
-        };
-        let interpolation = match args
-            .get(6)
-            .and_then(|v| v.coerce_to_string(activation, context).ok())
-            .as_deref()
-        {
-            Some("linearRGB") => GradientInterpolation::LinearRGB,
-            _ => GradientInterpolation::RGB,
-        };
-
-        let gradient = Gradient {
-            matrix,
-            spread,
-            interpolation,
-            records,
-        };
-        let style = match method.as_ref() {
-            "linear" => FillStyle::LinearGradient(gradient),
-            "radial" => {
-                if let Some(focal_point) = args.get(7) {
-                    FillStyle::FocalGradient {
-                        gradient,
-                        focal_point: focal_point.coerce_to_f64(activation, context)? as f32,
-                    }
-                } else {
-                    FillStyle::RadialGradient(gradient)
-                }
-            }
-            other => {
-                log::warn!("beginGradientFill() received invalid fill type {:?}", other);
-                return Ok(Value::Undefined);
-            }
-        };
-        movie_clip.set_fill_style(context, Some(style));
     } else {
         movie_clip.set_fill_style(context, None);
     }
     Ok(Value::Undefined)
 }
 
+fn begin_bitmap_fill<'gc>(
+    _movie_clip: MovieClip<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Bitmap fills take a `BitmapData`, which is not yet implemented.
+    log::warn!("MovieClip.beginBitmapFill: unimplemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "MovieClip.beginBitmapFill");
+    Ok(Value::Undefined)
+}
+
+fn line_gradient_style<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if args.len() >= 5 {
+        if let Some(style) = gradient_style("lineGradientStyle", activation, context, args)? {
+            movie_clip.set_line_fill_style(context, Some(style));
+        }
+    } else {
+        movie_clip.set_line_fill_style(context, None);
+    }
+    Ok(Value::Undefined)
+}
+
+/// Creates a gradient fill style from the arguments shared by `beginGradientFill`
+/// and `lineGradientStyle`.
+///
+/// Returns `None` and logs a warning if the arguments don't describe a valid gradient.
+fn gradient_style<'gc>(
+    method_name: &str,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Option<FillStyle>, Error<'gc>> {
+    let method = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    let colors = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation, context)
+        .array();
+    let alphas = args
+        .get(2)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation, context)
+        .array();
+    let ratios = args
+        .get(3)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation, context)
+        .array();
+    let matrix_object = args
+        .get(4)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation, context);
+    if colors.len() != alphas.len() || colors.len() != ratios.len() {
+        log::warn!(
+            "{}() received different sized arrays for colors, alphas and ratios",
+            method_name
+        );
+        return Ok(None);
+    }
+    let mut records = Vec::with_capacity(colors.len());
+    for i in 0..colors.len() {
+        let ratio = ratios[i]
+            .coerce_to_f64(activation, context)?
+            .min(255.0)
+            .max(0.0);
+        let rgb = colors[i].coerce_to_u32(activation, context)?;
+        let alpha = alphas[i]
+            .coerce_to_f64(activation, context)?
+            .min(100.0)
+            .max(0.0);
+        records.push(GradientRecord {
+            ratio: ratio as u8,
+            color: Color::from_rgb(rgb, (alpha / 100.0 * 255.0) as u8),
+        });
+    }
+    let matrix = gradient_object_to_matrix(matrix_object, activation, context)?;
+    let spread = match args
+        .get(5)
+        .and_then(|v| v.coerce_to_string(activation, context).ok())
+        .as_deref()
+    {
+        Some("reflect") => GradientSpread::Reflect,
+        Some("repeat") => GradientSpread::Repeat,
+        _ => GradientSpread::Pad,
+    };
+    let interpolation = match args
+        .get(6)
+        .and_then(|v| v.coerce_to_string(activation, context).ok())
+        .as_deref()
+    {
+        Some("linearRGB") => GradientInterpolation::LinearRGB,
+        _ => GradientInterpolation::RGB,
+    };
+
+    let gradient = Gradient {
+        matrix,
+        spread,
+        interpolation,
+        records,
+    };
+    let style = match method.as_ref() {
+        "linear" => FillStyle::LinearGradient(gradient),
+        "radial" => {
+            if let Some(focal_point) = args.get(7) {
+                FillStyle::FocalGradient {
+                    gradient,
+                    focal_point: focal_point.coerce_to_f64(activation, context)? as f32,
+                }
+            } else {
+                FillStyle::RadialGradient(gradient)
+            }
+        }
+        other => {
+            log::warn!("{}() received invalid fill type {:?}", method_name, other);
+            return Ok(None);
+        }
+    };
+    Ok(Some(style))
+}
+
 fn move_to<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc>,
@@ -437,14 +620,56 @@ fn clear<'gc>(
     Ok(Value::Undefined)
 }
 
+fn attach_audio<'gc>(
+    movie_clip: MovieClip<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    match args.get(0).unwrap_or(&Value::Undefined) {
+        // Passing `false` detaches the current audio source.
+        Value::Undefined | Value::Null | Value::Bool(false) => {
+            movie_clip.attach_audio(context, None);
+        }
+        Value::Object(object) => match audio_source(context, *object) {
+            Some(source) => movie_clip.attach_audio(context, Some(source)),
+            None => log::warn!("MovieClip.attachAudio: Unsupported audio source"),
+        },
+        _ => log::warn!("MovieClip.attachAudio: Unsupported audio source"),
+    }
+    Ok(Value::Undefined)
+}
+
+/// Makes a source that plays the audio of an object passed to `attachAudio`.
+///
+/// TODO: Route audio from `Microphone` objects once they exist.
+#[cfg(feature = "rtmp")]
+fn audio_source<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    object: Object<'gc>,
+) -> Option<AudioSource> {
+    context.rtmp_connections.stream_audio_source(object)
+}
+
+/// Makes a source that plays the audio of an object passed to `attachAudio`.
+///
+/// `NetStream` objects only have audio with the `rtmp` feature.
+#[cfg(not(feature = "rtmp"))]
+fn audio_source<'gc>(
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _object: Object<'gc>,
+) -> Option<AudioSource> {
+    None
+}
+
 fn attach_movie<'gc>(
     mut movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let (export_name, new_instance_name, depth) = match &args[0..3] {
-        [export_name, new_instance_name, depth] => (
+    let (export_name, new_instance_name, depth) = match args {
+        [export_name, new_instance_name, depth, ..] => (
             export_name.coerce_to_string(activation, context)?,
             new_instance_name.coerce_to_string(activation, context)?,
             depth
@@ -497,15 +722,15 @@ fn create_empty_movie_clip<'gc>(
     context: &mut UpdateContext<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let (new_instance_name, depth) = match &args[0..2] {
-        [new_instance_name, depth] => (
+    let (new_instance_name, depth) = match args {
+        [new_instance_name, depth, ..] => (
             new_instance_name.coerce_to_string(activation, context)?,
             depth
                 .coerce_to_i32(activation, context)?
                 .wrapping_add(AVM_DEPTH_BIAS),
         ),
         _ => {
-            log::error!("MovieClip.attachMovie: Too few parameters");
+            log::error!("MovieClip.createEmptyMovieClip: Too few parameters");
             return Ok(Value::Undefined);
         }
     };
@@ -598,15 +823,15 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
     args: &[Value<'gc>],
     depth_bias: i32,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let (new_instance_name, depth) = match &args[0..2] {
-        [new_instance_name, depth] => (
+    let (new_instance_name, depth) = match args {
+        [new_instance_name, depth, ..] => (
             new_instance_name.coerce_to_string(activation, context)?,
             depth
                 .coerce_to_i32(activation, context)?
                 .wrapping_add(depth_bias),
         ),
         _ => {
-            log::error!("MovieClip.attachMovie: Too few parameters");
+            log::error!("MovieClip.duplicateMovieClip: Too few parameters");
             return Ok(Value::Undefined);
         }
     };
@@ -625,12 +850,19 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
         return Ok(Value::Undefined);
     }
 
-    if let Ok(mut new_clip) = context
-        .library
-        .library_for_movie(movie_clip.movie().unwrap())
-        .ok_or_else(|| "Movie is missing!".into())
-        .and_then(|l| l.instantiate_by_id(movie_clip.id(), context.gc_context))
-    {
+    let movie = movie_clip.movie().unwrap();
+    let new_clip = if movie_clip.id() == 0 {
+        // Clips created by `createEmptyMovieClip` have no library symbol.
+        Ok(MovieClip::new(SwfSlice::empty(movie), context.gc_context).into())
+    } else {
+        context
+            .library
+            .library_for_movie(movie)
+            .ok_or_else(|| "Movie is missing!".into())
+            .and_then(|l| l.instantiate_by_id(movie_clip.id(), context.gc_context))
+    };
+
+    if let Ok(mut new_clip) = new_clip {
         // Set name and attach to parent.
         new_clip.set_name(context.gc_context, &new_instance_name);
         parent.add_child_from_avm(context, new_clip, depth);
@@ -638,8 +870,12 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
         // Copy display properties from previous clip to new clip.
         new_clip.set_matrix(context.gc_context, &*movie_clip.matrix());
         new_clip.set_color_transform(context.gc_context, &*movie_clip.color_transform());
-        // TODO: Any other properties we should copy...?
-        // Definitely not ScriptObject properties.
+        new_clip.set_visible(context.gc_context, movie_clip.visible());
+        if let Some(new_clip) = new_clip.as_movie_clip() {
+            // Clip events and drawing API content are duplicated, but not ScriptObject properties.
+            new_clip.set_clip_actions(context.gc_context, movie_clip.clip_actions().to_vec());
+            new_clip.copy_drawing(context.gc_context, movie_clip);
+        }
 
         let init_object = init_object.map(|v| v.coerce_to_object(activation, context));
         new_clip.post_instantiation(activation.avm, context, new_clip, init_object, true);
@@ -656,23 +892,21 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
 }
 
 fn get_bytes_loaded<'gc>(
-    _movie_clip: MovieClip<'gc>,
+    movie_clip: MovieClip<'gc>,
     _activation: &mut Activation<'_, 'gc>,
     _context: &mut UpdateContext<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // TODO find a correct value
-    Ok(1.0.into())
+    Ok((movie_clip.bytes_loaded() as f64).into())
 }
 
 fn get_bytes_total<'gc>(
-    _movie_clip: MovieClip<'gc>,
+    movie_clip: MovieClip<'gc>,
     _activation: &mut Activation<'_, 'gc>,
     _context: &mut UpdateContext<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // TODO find a correct value
-    Ok(1.0.into())
+    Ok((movie_clip.bytes_total() as f64).into())
 }
 
 fn get_next_highest_depth<'gc>(
@@ -682,26 +916,62 @@ fn get_next_highest_depth<'gc>(
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 7 {
-        let depth = std::cmp::max(
-            movie_clip
-                .highest_depth()
-                .unwrap_or(0)
-                .wrapping_sub(AVM_DEPTH_BIAS - 1),
-            0,
-        );
+        // The next depth is always a dynamic depth, even if only timeline depths are used.
+        let depth = movie_clip
+            .highest_depth()
+            .unwrap_or(0)
+            .clamp(AVM_DEPTH_BIAS - 1, AVM_MAX_DEPTH)
+            - (AVM_DEPTH_BIAS - 1);
         Ok(depth.into())
     } else {
         Ok(Value::Undefined)
     }
 }
 
+fn get_instance_at_depth<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if activation.current_swf_version() >= 7 {
+        let depth = if let Some(depth) = args.get(0) {
+            depth
+                .coerce_to_i32(activation, context)?
+                .wrapping_add(AVM_DEPTH_BIAS)
+        } else {
+            return Ok(Value::Undefined);
+        };
+        Ok(movie_clip
+            .child_by_depth(depth)
+            .map(|child| child.object())
+            .unwrap_or(Value::Undefined))
+    } else {
+        Ok(Value::Undefined)
+    }
+}
+
+fn get_text_snapshot<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let snapshot = TextSnapshotObject::empty_snapshot(
+        context.gc_context,
+        Some(activation.avm.prototypes.text_snapshot),
+    );
+    snapshot.set_texts(context.gc_context, movie_clip.static_texts());
+    Ok(Object::from(snapshot).into())
+}
+
 fn goto_and_play<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    goto_frame(movie_clip, activation, context, args, false, 0)
+    goto_scene_frame(movie_clip, activation, context, args, false)
 }
 
 fn goto_and_stop<'gc>(
@@ -710,7 +980,46 @@ fn goto_and_stop<'gc>(
     context: &mut UpdateContext<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    goto_frame(movie_clip, activation, context, args, true, 0)
+    goto_scene_frame(movie_clip, activation, context, args, true)
+}
+
+/// Handles both the `goto(frame)` and `goto(scene, frame)` forms of
+/// `gotoAndPlay` and `gotoAndStop`.
+fn goto_scene_frame<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+    stop: bool,
+) -> Result<Value<'gc>, Error<'gc>> {
+    if args.len() < 2 {
+        return goto_frame(movie_clip, activation, context, args, stop, 0);
+    }
+
+    let scene = args[0].coerce_to_string(activation, context)?;
+    if let Some(start) = movie_clip.scene_label_to_number(&scene) {
+        // Frame numbers are relative to the scene, even when given as strings,
+        // but labels are unique across the timeline.
+        let frame = match &args[1] {
+            Value::Number(n) => Value::Number(*n),
+            frame => frame.coerce_to_string(activation, context)?.into(),
+        };
+        let scene_offset = match &frame {
+            Value::String(label) if movie_clip.frame_label_to_number(label).is_some() => 0,
+            _ => start - 1,
+        };
+        goto_frame(
+            movie_clip,
+            activation,
+            context,
+            &[frame],
+            stop,
+            scene_offset,
+        )
+    } else {
+        log::warn!("MovieClip.goto: Scene '{}' not found", scene);
+        Ok(Value::Undefined)
+    }
 }
 
 pub fn goto_frame<'gc>(
@@ -749,6 +1058,18 @@ pub fn goto_frame<'gc>(
             if let Some(mut frame) = movie_clip.frame_label_to_number(&frame_label) {
                 frame = frame.wrapping_add(scene_offset);
                 movie_clip.goto_frame(activation.avm, context, frame, stop);
+            } else if let Ok(n) = frame_label.trim().parse::<f64>() {
+                // Strings that aren't a label are treated as frame numbers.
+                if n.fract() == 0.0 {
+                    return goto_frame(
+                        movie_clip,
+                        activation,
+                        context,
+                        &[n.into()],
+                        stop,
+                        scene_offset,
+                    );
+                }
             }
         }
     }
@@ -814,10 +1135,45 @@ pub fn remove_movie_clip_with_bias<'gc>(
         };
 
         parent.remove_child_from_avm(context, movie_clip.into());
+        context.load_manager.cancel_loads_into(movie_clip.into());
     }
     Ok(Value::Undefined)
 }
 
+fn set_mask<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let mask = match args.get(0).cloned().unwrap_or(Value::Undefined) {
+        Value::Undefined | Value::Null => None,
+        value => {
+            let mask =
+                activation.resolve_target_display_object(context, movie_clip.into(), value)?;
+            if mask.is_none() {
+                log::warn!("MovieClip.setMask: Invalid mask");
+                return Ok(false.into());
+            }
+            mask
+        }
+    };
+
+    let mut maskee: DisplayObject<'gc> = movie_clip.into();
+    if let Some(mut old_mask) = maskee.masker() {
+        old_mask.set_maskee(context.gc_context, None);
+    }
+    if let Some(mut mask) = mask {
+        // A mask can only mask one object at a time.
+        if let Some(mut old_maskee) = mask.maskee() {
+            old_maskee.set_masker(context.gc_context, None);
+        }
+        mask.set_maskee(context.gc_context, Some(maskee));
+    }
+    maskee.set_masker(context.gc_context, mask);
+    Ok(true.into())
+}
+
 fn start_drag<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc>,
@@ -881,13 +1237,18 @@ fn swap_depths<'gc>(
     };
 
     if let Some(depth) = depth {
-        if depth < 0 || depth > AVM_MAX_DEPTH {
+        if !display_object::is_avm_depth(depth) {
             // Depth out of range; no action.
             return Ok(Value::Undefined);
         }
 
         if depth != movie_clip.depth() {
             parent.swap_child_to_depth(context, movie_clip.into(), depth);
+            // Clips moved to the dynamic depth range are out of the timeline's control,
+            // but the timeline can still remove clips moved to one of its own depths.
+            if display_object::is_dynamic_depth(depth) {
+                movie_clip.set_placed_by_script(context.gc_context, true);
+            }
         }
     }
 
@@ -1049,6 +1410,7 @@ fn load_movie<'gc>(
         context.player.clone().unwrap(),
         DisplayObject::MovieClip(target),
         fetch,
+        url.to_string(),
         None,
     );
 
@@ -1086,6 +1448,7 @@ fn unload_movie<'gc>(
     context: &mut UpdateContext<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    context.load_manager.cancel_loads_into(target.into());
     target.unload(context);
     target.replace_with_movie(context.gc_context, None);
 
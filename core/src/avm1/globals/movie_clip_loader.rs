@@ -135,6 +135,7 @@ pub fn load_clip<'gc>(
                 context.player.clone().unwrap(),
                 DisplayObject::MovieClip(movieclip),
                 fetch,
+                url.to_string(),
                 Some(this),
             );
 
@@ -160,6 +161,7 @@ pub fn unload_clip<'gc>(
             .as_display_object()
             .and_then(|dobj| dobj.as_movie_clip())
         {
+            context.load_manager.cancel_loads_into(movieclip.into());
             movieclip.unload(context);
             movieclip.replace_with_movie(context.gc_context, None);
 
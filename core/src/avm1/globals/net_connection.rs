@@ -0,0 +1,266 @@
+//! `NetConnection` impl
+
+use crate::amf::remoting::{Envelope, Message, CONTENT_TYPE};
+use crate::amf::{self, Graph};
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::backend::navigator::RequestOptions;
+use crate::unimplemented::FeatureKind;
+use gc_arena::MutationContext;
+
+/// The response URI of every call.
+///
+/// Each call is sent in its own request, so there is only ever one response
+/// to tell apart.
+const RESPONSE_URI: &str = "/1";
+
+/// Implements `NetConnection`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.define_value(
+        context.gc_context,
+        "isConnected",
+        false.into(),
+        DontEnum.into(),
+    );
+
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "call",
+        call,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "addHeader",
+        add_header,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// Implements `NetConnection.connect`
+///
+/// Connections to remoting gateways aren't opened until a call is made, so
+/// this only remembers the gateway URL. Connections to media servers are
+/// opened with RTMP, and `onStatus` is told whether they succeeded.
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let uri = match args.get(0) {
+        None | Some(Value::Undefined) | Some(Value::Null) => {
+            // A null connection is used to play local video.
+            this.define_value(context.gc_context, "uri", "null".into(), DontEnum.into());
+            this.define_value(
+                context.gc_context,
+                "isConnected",
+                true.into(),
+                DontEnum.into(),
+            );
+            return Ok(true.into());
+        }
+        Some(uri) => uri.coerce_to_string(activation, context)?.to_string(),
+    };
+
+    this.define_value(
+        context.gc_context,
+        "uri",
+        uri.clone().into(),
+        DontEnum.into(),
+    );
+    this.define_value(
+        context.gc_context,
+        "isConnected",
+        false.into(),
+        DontEnum.into(),
+    );
+
+    #[cfg(not(feature = "rtmp"))]
+    if uri.starts_with("rtmp") {
+        log::warn!("NetConnection.connect: RTMP connections are not supported");
+        return Ok(false.into());
+    }
+
+    #[cfg(feature = "rtmp")]
+    if uri.starts_with("rtmp") {
+        let mut graph = Graph::new();
+        let mut connect_args = vec![];
+        for arg in args.get(1..).unwrap_or(&[]) {
+            connect_args.push(crate::avm1::amf::serialize(
+                activation,
+                context,
+                arg.clone(),
+                &mut graph,
+            ));
+        }
+        let flash_version = context.system.get_version_string(activation);
+        let connected = context.rtmp_connections.connect(
+            context.rtmp,
+            context.rng,
+            this,
+            &uri,
+            &flash_version,
+            &graph,
+            &connect_args,
+        );
+        return Ok(connected.into());
+    }
+
+    Ok(true.into())
+}
+
+/// Implements `NetConnection.call`
+///
+/// The remaining arguments are passed to the service method, and the result
+/// is given to the `onResult` method of the responder. Over RTMP, the method
+/// is called on the application of the media server instead.
+fn call<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let uri = match this.get("uri", activation, context)? {
+        Value::String(uri) if uri != "null" => uri,
+        _ => return Ok(false.into()),
+    };
+    #[cfg(feature = "rtmp")]
+    let is_rtmp = context.rtmp_connections.is_rtmp(this);
+    #[cfg(not(feature = "rtmp"))]
+    let is_rtmp = false;
+    if uri.starts_with("rtmp") && !is_rtmp {
+        return Ok(false.into());
+    }
+    let command = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?
+        .to_string();
+    let responder = match args.get(1) {
+        Some(Value::Object(responder)) => Some(*responder),
+        _ => None,
+    };
+
+    let mut graph = Graph::new();
+    let mut call_args = vec![];
+    for arg in args.get(2..).unwrap_or(&[]) {
+        call_args.push(crate::avm1::amf::serialize(
+            activation,
+            context,
+            arg.clone(),
+            &mut graph,
+        ));
+    }
+
+    #[cfg(feature = "rtmp")]
+    if is_rtmp {
+        context
+            .rtmp_connections
+            .call(this, &command, responder, &graph, &call_args);
+        return Ok(Value::Undefined);
+    }
+
+    let call_args = graph.add(amf::Object::Array {
+        dense: call_args,
+        associative: vec![],
+    });
+    let envelope = Envelope {
+        version: 0,
+        graph,
+        headers: vec![],
+        messages: vec![Message {
+            target_uri: command,
+            response_uri: RESPONSE_URI.to_string(),
+            value: amf::Value::Object(call_args),
+        }],
+    };
+
+    let fetch = context.navigator.fetch(
+        &uri,
+        RequestOptions::post(Some((envelope.encode(), CONTENT_TYPE.to_string()))),
+    );
+    let process = context.load_manager.load_remoting_call(
+        context.player.clone().unwrap(),
+        this,
+        responder,
+        RESPONSE_URI.to_string(),
+        activation.target_clip_or_root(),
+        fetch,
+    );
+    context.navigator.spawn_future(process);
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `NetConnection.close`
+///
+/// RTMP connections tell `onStatus` that they closed on the next frame.
+fn close<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    #[cfg(feature = "rtmp")]
+    context.rtmp_connections.close(this);
+    this.define_value(
+        context.gc_context,
+        "isConnected",
+        false.into(),
+        DontEnum.into(),
+    );
+
+    Ok(Value::Undefined)
+}
+
+fn add_header<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    log::warn!("NetConnection.addHeader() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "NetConnection.addHeader");
+    Ok(Value::Undefined)
+}
@@ -0,0 +1,230 @@
+//! `NetStream` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::function::Executable;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::rtmp::StreamCommand;
+use gc_arena::MutationContext;
+
+/// Implements `NetStream`
+///
+/// Streams are only created on RTMP connections; streams of other
+/// connections can't play anything.
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.define_value(
+        context.gc_context,
+        "bufferTime",
+        0.1.into(),
+        DontEnum.into(),
+    );
+
+    if let Some(Value::Object(connection)) = args.get(0) {
+        if !context.rtmp_connections.create_stream(*connection, this) {
+            log::warn!("NetStream: Only streams of RTMP connections are supported");
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "play",
+        play,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "pause",
+        pause,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "seek",
+        seek,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "setBufferTime",
+        set_buffer_time,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.add_property(
+        gc_context,
+        "time",
+        Executable::Native(time),
+        None,
+        DontDelete | ReadOnly | DontEnum,
+    );
+
+    object.add_property(
+        gc_context,
+        "bytesLoaded",
+        Executable::Native(bytes_loaded),
+        None,
+        DontDelete | ReadOnly | DontEnum,
+    );
+
+    object.into()
+}
+
+/// Implements `NetStream.play`
+///
+/// The stream starts at the given time in seconds. By default, a live stream
+/// is played if there is one, and a recorded one otherwise.
+fn play<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?
+        .to_string();
+    let start = match args.get(1) {
+        Some(start) => start.coerce_to_f64(activation, context)?,
+        None => -2.0,
+    };
+
+    // The buffer time has to be known before recorded streams are sent.
+    let buffer_time = this
+        .get("bufferTime", activation, context)?
+        .coerce_to_f64(activation, context)?;
+    context
+        .rtmp_connections
+        .stream_command(this, StreamCommand::SetBufferTime(buffer_time));
+    if !context
+        .rtmp_connections
+        .stream_command(this, StreamCommand::Play { name, start })
+    {
+        log::warn!("NetStream.play: Only streams of RTMP connections can be played");
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `NetStream.pause`
+///
+/// Without an argument, this toggles between pausing and resuming.
+fn pause<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let paused = match args.get(0) {
+        Some(Value::Bool(paused)) => Some(*paused),
+        _ => None,
+    };
+    context
+        .rtmp_connections
+        .stream_command(this, StreamCommand::Pause(paused));
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `NetStream.seek`
+fn seek<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let time = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation, context)?;
+    context
+        .rtmp_connections
+        .stream_command(this, StreamCommand::Seek(time.max(0.0)));
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `NetStream.setBufferTime`
+fn set_buffer_time<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let time = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation, context)?;
+    this.define_value(
+        context.gc_context,
+        "bufferTime",
+        time.into(),
+        DontEnum.into(),
+    );
+    context
+        .rtmp_connections
+        .stream_command(this, StreamCommand::SetBufferTime(time));
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `NetStream.close`
+fn close<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    context.rtmp_connections.close_stream(this);
+
+    Ok(Value::Undefined)
+}
+
+fn time<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(context.rtmp_connections.stream_time(this).into())
+}
+
+fn bytes_loaded<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok((context.rtmp_connections.stream_bytes_loaded(this) as f64).into())
+}
@@ -5,6 +5,7 @@ use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::property::Attribute::{self, *};
 use crate::avm1::{Object, TObject, UpdateContext, Value};
 use crate::character::Character;
+use crate::display_object::TDisplayObject;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 use std::borrow::Cow;
@@ -144,9 +145,15 @@ pub fn register_class<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(class_name) = args.get(0).cloned() {
         let class_name = class_name.coerce_to_string(activation, context)?;
+        // Symbols are registered in the library of the movie that defines the calling code,
+        // which may be a loaded movie rather than the root SWF.
+        let movie = activation
+            .base_clip()
+            .movie()
+            .unwrap_or_else(|| context.swf.clone());
         if let Some(Character::MovieClip(movie_clip)) = context
             .library
-            .library_for_movie_mut(context.swf.clone())
+            .library_for_movie_mut(movie)
             .get_character_by_export_name(&class_name)
         {
             if let Some(constructor) = args.get(1) {
@@ -0,0 +1,212 @@
+//! `PrintJob` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::context::RenderContext;
+use crate::prelude::*;
+use crate::transform::{Transform, TransformStack};
+use gc_arena::MutationContext;
+
+/// The scale at which pages are rendered, relative to the 72 points per inch
+/// that Flash maps pixels to. This prints at 288 DPI.
+const PRINT_SCALE: f32 = 4.0;
+
+/// Implements `PrintJob`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "start",
+        start,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "addPage",
+        add_page,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn start<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(settings) = context.printer.start_job() {
+        this.set(
+            "paperWidth",
+            settings.paper_width.into(),
+            activation,
+            context,
+        )?;
+        this.set(
+            "paperHeight",
+            settings.paper_height.into(),
+            activation,
+            context,
+        )?;
+        this.set("pageWidth", settings.page_width.into(), activation, context)?;
+        this.set(
+            "pageHeight",
+            settings.page_height.into(),
+            activation,
+            context,
+        )?;
+        this.set(
+            "orientation",
+            settings.orientation.as_str().into(),
+            activation,
+            context,
+        )?;
+        Ok(true.into())
+    } else {
+        Ok(false.into())
+    }
+}
+
+fn add_page<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // The page size is only set once the job has been started.
+    let page_size = (
+        this.get("pageWidth", activation, context)?,
+        this.get("pageHeight", activation, context)?,
+    );
+    let (page_width, page_height) = match page_size {
+        (Value::Number(width), Value::Number(height)) if width > 0.0 && height > 0.0 => {
+            (width, height)
+        }
+        _ => return Ok(false.into()),
+    };
+
+    let target = match args.get(0) {
+        Some(Value::Number(level)) => context.levels.get(&(*level as u32)).copied(),
+        Some(Value::Object(o)) if o.as_display_object().is_some() => o.as_display_object(),
+        Some(val) => {
+            let path = val.coerce_to_string(activation, context)?;
+            let start = activation.target_clip_or_root();
+            activation.resolve_target_display_object(context, start, path.into())?
+        }
+        None => None,
+    };
+    let target = match target {
+        Some(target) => target,
+        None => return Ok(false.into()),
+    };
+
+    // The area of the target to print, in its own coordinate space.
+    let print_area = match args.get(1) {
+        Some(Value::Object(area)) => {
+            let mut bounds = BoundingBox::default();
+            let x_min = area
+                .get("xMin", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let y_min = area
+                .get("yMin", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let x_max = area
+                .get("xMax", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            let y_max = area
+                .get("yMax", activation, context)?
+                .coerce_to_f64(activation, context)?;
+            bounds.encompass(Twips::from_pixels(x_min), Twips::from_pixels(y_min));
+            bounds.encompass(Twips::from_pixels(x_max), Twips::from_pixels(y_max));
+            bounds
+        }
+        _ => target.bounds(),
+    };
+
+    match args.get(3) {
+        None | Some(Value::Undefined) => (),
+        Some(_) => log::warn!("PrintJob.addPage: frameNum is unimplemented"),
+    }
+
+    let width = (page_width as f32 * PRINT_SCALE) as u32;
+    let height = (page_height as f32 * PRINT_SCALE) as u32;
+    let white = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    };
+    if !context.renderer.begin_offscreen_frame(width, height, white) {
+        log::warn!("PrintJob.addPage: Renderer is unable to render pages");
+        return Ok(false.into());
+    }
+
+    // Print the target at its own scale, with the print area at the top-left of the page.
+    let mut to_local_matrix = *target.matrix();
+    to_local_matrix.invert();
+    let mut transform_stack = TransformStack::new();
+    transform_stack.push(&Transform {
+        matrix: Matrix::scale(PRINT_SCALE, PRINT_SCALE)
+            * Matrix::translate(
+                Twips::new(0) - print_area.x_min,
+                Twips::new(0) - print_area.y_min,
+            )
+            * to_local_matrix,
+        ..Default::default()
+    });
+    let mut render_context = RenderContext {
+        renderer: &mut *context.renderer,
+        library: &*context.library,
+        transform_stack: &mut transform_stack,
+        view_bounds: print_area.transform(&target.local_to_global_matrix()),
+        clip_depth_stack: vec![],
+        scale9_grid: None,
+        profiler: &mut *context.profiler,
+    };
+    target.render(&mut render_context);
+
+    if let Some(page) = context.renderer.end_offscreen_frame() {
+        context.printer.add_page(page);
+        Ok(true.into())
+    } else {
+        Ok(false.into())
+    }
+}
+
+fn send<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    context.printer.send_job();
+    Ok(Value::Undefined)
+}
@@ -52,6 +52,17 @@ fn constructor<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn construct_new_rectangle<'gc>(
+    args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Result<Object<'gc>, Error<'gc>> {
+    let proto = context.system_prototypes.rectangle;
+    let object = proto.new(activation, context, proto, &args)?;
+    let _ = constructor(activation, context, object, &args)?;
+    Ok(object)
+}
+
 fn to_string<'gc>(
     activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
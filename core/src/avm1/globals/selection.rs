@@ -0,0 +1,99 @@
+//! `Selection` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::listeners::Listeners;
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::display_object::TDisplayObject;
+use crate::focus_tracker;
+use gc_arena::MutationContext;
+
+/// Returns the target path of the focused object, or `null` if nothing has focus.
+pub fn get_focus<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    match *context.focus {
+        Some(focus) if !focus.removed() => Ok(focus.path().into()),
+        _ => Ok(Value::Null),
+    }
+}
+
+/// Focuses the given object or target path. `null` removes focus.
+/// Returns whether focus was set.
+pub fn set_focus<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let new_focus = match args.get(0).cloned().unwrap_or(Value::Undefined) {
+        Value::Undefined | Value::Null => None,
+        target => {
+            let start = activation.target_clip_or_root();
+            match activation.resolve_target_display_object(context, start, target)? {
+                Some(object) => Some(object),
+                None => return Ok(false.into()),
+            }
+        }
+    };
+    focus_tracker::set_focus(context, new_focus);
+    Ok(true.into())
+}
+
+/// Returns the position of the cursor in the focused text field, or -1 if no text
+/// field has focus. Selections aren't supported yet, so this is used for the
+/// beginning, end and caret of the selection.
+pub fn get_caret_index<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    match context.focus.and_then(|focus| focus.as_edit_text()) {
+        Some(text_field) => Ok(text_field.caret_index().into()),
+        None => Ok((-1).into()),
+    }
+}
+
+pub fn create_selection_object<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Option<Object<'gc>>,
+    fn_proto: Option<Object<'gc>>,
+    listener: &Listeners<'gc>,
+) -> Object<'gc> {
+    let mut selection = ScriptObject::object(gc_context, proto);
+
+    register_listener!(gc_context, selection, listener, fn_proto, selection);
+
+    selection.force_set_function(
+        "getFocus",
+        get_focus,
+        gc_context,
+        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+        fn_proto,
+    );
+
+    selection.force_set_function(
+        "setFocus",
+        set_focus,
+        gc_context,
+        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+        fn_proto,
+    );
+
+    for name in &["getBeginIndex", "getEndIndex", "getCaretIndex"] {
+        selection.force_set_function(
+            name,
+            get_caret_index,
+            gc_context,
+            Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
+            fn_proto,
+        );
+    }
+
+    selection.into()
+}
@@ -1,71 +1,70 @@
+use crate::amf::{self, AmfVersion, Graph};
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, FunctionObject};
-use crate::avm1::{Object, TObject, Value};
+use crate::avm1::{self, Object, TObject, Value};
 use crate::context::UpdateContext;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 
 use crate::avm1::shared_object::SharedObject;
+use crate::unimplemented::FeatureKind;
 
 use json::JsonValue;
 
 pub fn delete_all<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.deleteAll() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.deleteAll");
     Ok(Value::Undefined)
 }
 
 pub fn get_disk_usage<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.getDiskUsage() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.getDiskUsage");
     Ok(Value::Undefined)
 }
 
-/// Serialize an Object and any children to a JSON object
-/// It would be best if this was implemented via serde but due to avm and context it can't
-/// Undefined fields aren't serialized
-fn recursive_serialize<'gc>(
+/// Serialize the data of a shared object to AMF0.
+fn serialize_data<'gc>(
     activation: &mut Activation<'_, 'gc>,
     action_context: &mut UpdateContext<'_, 'gc, '_>,
-    obj: Object<'gc>,
-    json_obj: &mut JsonValue,
-) {
-    for k in &obj.get_keys(activation) {
-        if let Ok(elem) = obj.get(k, activation, action_context) {
-            match elem {
-                Value::Undefined => {}
-                Value::Null => json_obj[k] = JsonValue::Null,
-                Value::Bool(b) => json_obj[k] = b.into(),
-                Value::Number(f) => json_obj[k] = f.into(),
-                Value::String(s) => json_obj[k] = s.into(),
-                Value::Object(o) => {
-                    // Don't attempt to serialize functions
-                    let function = activation.avm.prototypes.function;
-                    if !o
-                        .is_instance_of(activation, action_context, o, function)
-                        .unwrap_or_default()
-                    {
-                        let mut sub_data_json = JsonValue::new_object();
-                        recursive_serialize(activation, action_context, o, &mut sub_data_json);
-                        json_obj[k] = sub_data_json;
-                    }
-                }
-            }
-        }
+    data: Object<'gc>,
+) -> Vec<u8> {
+    let mut graph = Graph::new();
+    let value = avm1::amf::serialize(activation, action_context, data.into(), &mut graph);
+    amf::serialize(AmfVersion::Amf0, &graph, &value)
+}
+
+/// Deserialize the data of a shared object from AMF0.
+fn deserialize_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
+    saved: &[u8],
+) -> Option<Object<'gc>> {
+    let mut graph = Graph::new();
+    let value = amf::deserialize(AmfVersion::Amf0, saved, &mut graph).ok()?;
+    match avm1::amf::deserialize(activation, action_context, &graph, &value) {
+        Value::Object(data) => Some(data),
+        _ => None,
     }
 }
 
 /// Deserialize an Object and any children from a JSON object
-/// It would be best if this was implemented via serde but due to avm and context it can't
+/// Shared objects used to be saved as JSON, and this is kept to load them
 /// Undefined fields aren't deserialized
 fn recursive_deserialize<'gc>(
     json_obj: JsonValue,
@@ -144,13 +143,26 @@ pub fn get_local<'gc>(
         .coerce_to_string(activation, action_context)?
         .to_string();
 
+    // Shared objects with a local path are stored under that path, so that the
+    // movies sharing the path can share the object.
+    let local_path = match args.get(1) {
+        None | Some(Value::Undefined) | Some(Value::Null) => String::new(),
+        Some(local_path) => local_path
+            .coerce_to_string(activation, action_context)?
+            .to_string(),
+    };
+    let name = match local_path.trim_matches('/') {
+        "" => name,
+        local_path => format!("{}/{}", local_path, name),
+    };
+
     //Check if this is referencing an existing shared object
     if let Some(so) = action_context.shared_objects.get(&name) {
         return Ok(Value::Object(*so));
     }
 
-    if args.len() > 1 {
-        log::warn!("SharedObject.getLocal() doesn't support localPath or secure yet");
+    if args.len() > 2 {
+        log::warn!("SharedObject.getLocal() doesn't support secure yet");
     }
 
     // Data property only should exist when created with getLocal/Remote
@@ -162,17 +174,27 @@ pub fn get_local<'gc>(
     let obj_so = this.as_shared_object().unwrap();
     obj_so.set_name(action_context.gc_context, name.to_string());
 
-    // Create the data object
-    let data_proto = activation.avm.prototypes.object;
-    let data = data_proto.new(activation, action_context, so, &[])?;
-    let _ = crate::avm1::globals::object::constructor(activation, action_context, data, &[])?;
-
     // Load the data object from storage if it existed prior
-    if let Some(saved) = action_context.storage.get_string(&name) {
-        if let Ok(json_data) = json::parse(&saved) {
-            recursive_deserialize(json_data, activation, data, action_context);
+    let saved = action_context.storage.get_bytes(&name);
+    let data = match saved.and_then(|saved| deserialize_data(activation, action_context, &saved)) {
+        Some(data) => data,
+        None => {
+            // Create the data object
+            let data_proto = activation.avm.prototypes.object;
+            let data = data_proto.new(activation, action_context, so, &[])?;
+            let _ =
+                crate::avm1::globals::object::constructor(activation, action_context, data, &[])?;
+
+            // Data saved by older versions is stored as JSON
+            if let Some(saved) = action_context.storage.get_string(&name) {
+                if let Ok(json_data) = json::parse(&saved) {
+                    recursive_deserialize(json_data, activation, data, action_context);
+                }
+            }
+
+            data
         }
-    }
+    };
 
     this.define_value(
         action_context.gc_context,
@@ -188,41 +210,53 @@ pub fn get_local<'gc>(
 
 pub fn get_remote<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.getRemote() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.getRemote");
     Ok(Value::Undefined)
 }
 
 pub fn get_max_size<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.getMaxSize() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.getMaxSize");
     Ok(Value::Undefined)
 }
 
 pub fn add_listener<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.addListener() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.addListener");
     Ok(Value::Undefined)
 }
 
 pub fn remove_listener<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.removeListener() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.removeListener");
     Ok(Value::Undefined)
 }
 
@@ -322,21 +356,27 @@ pub fn clear<'gc>(
 
 pub fn close<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.close() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.close");
     Ok(Value::Undefined)
 }
 
 pub fn connect<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.connect() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.connect");
     Ok(Value::Undefined)
 }
 
@@ -350,65 +390,78 @@ pub fn flush<'gc>(
         .get("data", activation, action_context)?
         .coerce_to_object(activation, action_context);
 
-    let mut data_json = JsonValue::new_object();
-    recursive_serialize(activation, action_context, data, &mut data_json);
+    let bytes = serialize_data(activation, action_context, data);
 
     let this_obj = this.as_shared_object().unwrap();
     let name = this_obj.get_name();
 
-    Ok(action_context
-        .storage
-        .put_string(&name, data_json.dump())
-        .into())
+    Ok(action_context.storage.put_bytes(&name, &bytes).into())
 }
 
 pub fn get_size<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("SharedObject.getSize() not implemented");
-    Ok(Value::Undefined)
+    let data = this
+        .get("data", activation, action_context)?
+        .coerce_to_object(activation, action_context);
+
+    Ok(serialize_data(activation, action_context, data)
+        .len()
+        .into())
 }
 
 pub fn send<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.send() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.send");
     Ok(Value::Undefined)
 }
 
 pub fn set_fps<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.setFps() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.setFps");
     Ok(Value::Undefined)
 }
 
 pub fn on_status<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.onStatus() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.onStatus");
     Ok(Value::Undefined)
 }
 
 pub fn on_sync<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("SharedObject.onSync() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "SharedObject.onSync");
     Ok(Value::Undefined)
 }
 
@@ -1,13 +1,15 @@
 //! AVM1 Sound object
-//! TODO: Sound position, transform, loadSound
+//! TODO: loadSound
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::Executable;
 use crate::avm1::property::Attribute::*;
-use crate::avm1::{Object, SoundObject, TObject, UpdateContext, Value};
+use crate::avm1::{Object, ScriptObject, SoundObject, TObject, UpdateContext, Value};
+use crate::backend::audio::SoundTransform;
 use crate::character::Character;
 use crate::display_object::TDisplayObject;
+use crate::unimplemented::FeatureKind;
 use gc_arena::MutationContext;
 
 /// Implements `Sound`
@@ -86,7 +88,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "get_transform",
+        "getTransform",
         get_transform,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -94,7 +96,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "get_volume",
+        "getVolume",
         get_volume,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -102,7 +104,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "load_sound",
+        "loadSound",
         load_sound,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -118,7 +120,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "set_pan",
+        "setPan",
         set_pan,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -126,7 +128,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "set_transform",
+        "setTransform",
         set_transform,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -134,7 +136,7 @@ pub fn create_proto<'gc>(
     );
 
     object.as_script_object().unwrap().force_set_function(
-        "set_volume",
+        "setVolume",
         set_volume,
         gc_context,
         DontDelete | ReadOnly | DontEnum,
@@ -219,12 +221,15 @@ fn duration<'gc>(
 
 fn get_bytes_loaded<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 6 {
         log::warn!("Sound.getBytesLoaded: Unimplemented");
+        context
+            .unimplemented_features
+            .record(FeatureKind::Builtin, "Sound.getBytesLoaded");
         Ok(1.into())
     } else {
         Ok(Value::Undefined)
@@ -233,12 +238,15 @@ fn get_bytes_loaded<'gc>(
 
 fn get_bytes_total<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 6 {
         log::warn!("Sound.getBytesTotal: Unimplemented");
+        context
+            .unimplemented_features
+            .record(FeatureKind::Builtin, "Sound.getBytesTotal");
         Ok(1.into())
     } else {
         Ok(Value::Undefined)
@@ -247,72 +255,98 @@ fn get_bytes_total<'gc>(
 
 fn get_pan<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.getPan: Unimplemented");
-    Ok(0.into())
+    if let Some(sound) = this.as_sound_object() {
+        return Ok(sound_transform(context, sound).pan().into());
+    } else {
+        log::warn!("Sound.getPan: this is not a Sound");
+    }
+    Ok(Value::Undefined)
 }
 
 fn get_transform<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.getTransform: Unimplemented");
+    if let Some(sound) = this.as_sound_object() {
+        let transform = sound_transform(context, sound);
+        let out = ScriptObject::object(context.gc_context, Some(activation.avm.prototypes.object));
+        out.set("ll", transform.left_to_left.into(), activation, context)?;
+        out.set("lr", transform.left_to_right.into(), activation, context)?;
+        out.set("rl", transform.right_to_left.into(), activation, context)?;
+        out.set("rr", transform.right_to_right.into(), activation, context)?;
+        return Ok(out.into());
+    } else {
+        log::warn!("Sound.getTransform: this is not a Sound");
+    }
     Ok(Value::Undefined)
 }
 
 fn get_volume<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.getVolume: Unimplemented");
-    Ok(100.into())
+    if let Some(sound) = this.as_sound_object() {
+        return Ok(sound_transform(context, sound).volume.into());
+    } else {
+        log::warn!("Sound.getVolume: this is not a Sound");
+    }
+    Ok(Value::Undefined)
 }
 
 fn id3<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 6 {
         log::warn!("Sound.id3: Unimplemented");
+        context
+            .unimplemented_features
+            .record(FeatureKind::Builtin, "Sound.id3");
     }
     Ok(Value::Undefined)
 }
 
 fn load_sound<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 6 {
         log::warn!("Sound.loadSound: Unimplemented");
+        context
+            .unimplemented_features
+            .record(FeatureKind::Builtin, "Sound.loadSound");
     }
     Ok(Value::Undefined)
 }
 
 fn position<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.current_swf_version() >= 6 {
         if let Some(sound_object) = this.as_sound_object() {
-            // TODO: The position is "sticky"; even if the sound is no longer playing, it should return
-            // the previous valid position.
-            // Needs some audio backend work for this.
+            // The position is "sticky"; once the sound stops playing, the last
+            // valid position is returned.
             if sound_object.sound().is_some() {
-                if let Some(_sound_instance) = sound_object.sound_instance() {
-                    log::warn!("Sound.position: Unimplemented");
+                if let Some(position) = sound_object
+                    .sound_instance()
+                    .and_then(|instance| context.audio.get_sound_position(instance))
+                {
+                    sound_object.set_position(context.gc_context, position);
                 }
                 return Ok(sound_object.position().into());
             }
@@ -324,32 +358,105 @@ fn position<'gc>(
 }
 
 fn set_pan<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.setPan: Unimplemented");
+    let pan = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation, context)?;
+    if let Some(sound) = this.as_sound_object() {
+        let mut transform = sound_transform(context, sound);
+        transform.set_pan(pan);
+        set_sound_transform(context, sound, transform);
+    } else {
+        log::warn!("Sound.setPan: this is not a Sound");
+    }
     Ok(Value::Undefined)
 }
 
 fn set_transform<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.setTransform: Unimplemented");
+    fn set_channel<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        transform: Object<'gc>,
+        property: &str,
+        out: &mut i32,
+    ) -> Result<(), Error<'gc>> {
+        // Only the properties that are set on the object itself are changed.
+        if transform.has_own_property(activation, context, property) {
+            *out = transform
+                .get(property, activation, context)?
+                .coerce_to_i32(activation, context)?;
+        }
+        Ok(())
+    }
+
+    if let Some(sound) = this.as_sound_object() {
+        let object = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation, context);
+        let mut transform = sound_transform(context, sound);
+        set_channel(
+            activation,
+            context,
+            object,
+            "ll",
+            &mut transform.left_to_left,
+        )?;
+        set_channel(
+            activation,
+            context,
+            object,
+            "lr",
+            &mut transform.left_to_right,
+        )?;
+        set_channel(
+            activation,
+            context,
+            object,
+            "rl",
+            &mut transform.right_to_left,
+        )?;
+        set_channel(
+            activation,
+            context,
+            object,
+            "rr",
+            &mut transform.right_to_right,
+        )?;
+        set_sound_transform(context, sound, transform);
+    } else {
+        log::warn!("Sound.setTransform: this is not a Sound");
+    }
     Ok(Value::Undefined)
 }
 
 fn set_volume<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Sound.setVolume: Unimplemented");
+    let volume = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation, context)?;
+    if let Some(sound) = this.as_sound_object() {
+        let mut transform = sound_transform(context, sound);
+        transform.volume = volume;
+        set_sound_transform(context, sound, transform);
+    } else {
+        log::warn!("Sound.setVolume: this is not a Sound");
+    }
     Ok(Value::Undefined)
 }
 
@@ -377,7 +484,8 @@ fn start<'gc>(
     use swf::{SoundEvent, SoundInfo};
     if let Some(sound_object) = this.as_sound_object() {
         if let Some(sound) = sound_object.sound() {
-            let sound_instance = context.audio.start_sound(
+            let sound_instance = context.audio_manager.start_sound(
+                context.audio,
                 sound,
                 &SoundInfo {
                     event: SoundEvent::Start,
@@ -390,9 +498,12 @@ fn start<'gc>(
                     num_loops: loops,
                     envelope: None,
                 },
+                sound_object.owner(),
+                Some(sound_object),
             );
-            if let Ok(sound_instance) = sound_instance {
+            if let Some(sound_instance) = sound_instance {
                 sound_object.set_sound_instance(context.gc_context, Some(sound_instance));
+                sound_object.set_position(context.gc_context, (start_offset * 1000.0) as u32);
             }
         } else {
             log::warn!("Sound.start: No sound is attached");
@@ -425,7 +536,9 @@ fn stop<'gc>(
                     .get_character_by_export_name(&name)
                 {
                     // Stop all sounds with the given name.
-                    context.audio.stop_sounds_with_handle(*sound);
+                    context
+                        .audio_manager
+                        .stop_sounds_with_handle(context.audio, *sound);
                 } else {
                     log::warn!("Sound.stop: Sound '{}' not found", name);
                 }
@@ -435,15 +548,14 @@ fn stop<'gc>(
                     name
                 )
             }
-        } else if let Some(_owner) = sound.owner() {
+        } else if let Some(owner) = sound.owner() {
             // Usage 2: Stop all sound running within a given clip.
-            // TODO: We just stop the last played sound for now.
-            if let Some(sound_instance) = sound.sound_instance() {
-                context.audio.stop_sound(sound_instance);
-            }
+            context
+                .audio_manager
+                .stop_sounds_with_display_object(context.audio, owner);
         } else {
             // Usage 3: If there is no owner and no name, this call acts like `stopAllSounds()`.
-            context.audio.stop_all_sounds();
+            context.audio_manager.stop_all_sounds(context.audio);
         }
     } else {
         log::warn!("Sound.stop: this is not a Sound");
@@ -451,3 +563,28 @@ fn stop<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// The sound transform controlled by a `Sound` object: the one of the clip that
+/// owns it, or the global transform if it has no owner.
+fn sound_transform<'gc>(
+    context: &UpdateContext<'_, 'gc, '_>,
+    sound: SoundObject<'gc>,
+) -> SoundTransform {
+    match sound.owner().and_then(|owner| owner.as_movie_clip()) {
+        Some(clip) => clip.sound_transform(),
+        None => context.audio_manager.global_transform(),
+    }
+}
+
+fn set_sound_transform<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    sound: SoundObject<'gc>,
+    transform: SoundTransform,
+) {
+    match sound.owner().and_then(|owner| owner.as_movie_clip()) {
+        Some(clip) => clip.set_sound_transform(context, transform),
+        None => context
+            .audio_manager
+            .set_global_transform(context.audio, transform),
+    }
+}
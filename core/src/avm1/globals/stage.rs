@@ -1,11 +1,15 @@
 //! Stage object
 //!
-//! TODO: This is a very rough stub with not much implementation.
+//! TODO: This is a rough stub; only the size, scale mode, alignment and display state
+//! are implemented.
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::Executable;
+use crate::avm1::listeners::{Listeners, SystemListener};
 use crate::avm1::property::Attribute;
 use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::context::ActionType;
+use crate::stage::{StageAlign, StageDisplayState, StageScaleMode};
 use gc_arena::MutationContext;
 
 pub fn create_stage_object<'gc>(
@@ -13,16 +17,11 @@ pub fn create_stage_object<'gc>(
     proto: Option<Object<'gc>>,
     _array_proto: Option<Object<'gc>>,
     fn_proto: Option<Object<'gc>>,
+    listener: &Listeners<'gc>,
 ) -> Object<'gc> {
     let mut stage = ScriptObject::object(gc_context, proto);
 
-    stage.force_set_function(
-        "addListener",
-        add_listener,
-        gc_context,
-        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
-        fn_proto,
-    );
+    register_listener!(gc_context, stage, listener, fn_proto, stage);
 
     stage.add_property(
         gc_context,
@@ -40,12 +39,12 @@ pub fn create_stage_object<'gc>(
         Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
     );
 
-    stage.force_set_function(
-        "removeListener",
-        remove_listener,
+    stage.add_property(
         gc_context,
-        Attribute::DontEnum | Attribute::DontDelete | Attribute::ReadOnly,
-        fn_proto,
+        "displayState",
+        Executable::Native(display_state),
+        Some(Executable::Native(set_display_state)),
+        Attribute::DontEnum | Attribute::DontDelete,
     );
 
     stage.add_property(
@@ -75,92 +74,136 @@ pub fn create_stage_object<'gc>(
     stage.into()
 }
 
-fn add_listener<'gc>(
+fn align<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.addListener: unimplemented");
-    Ok(Value::Undefined)
+    Ok(context.stage_align.to_string().into())
 }
 
-fn align<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+fn set_align<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.align: unimplemented");
-    Ok("".into())
+    let align = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    *context.stage_align = StageAlign::from(&*align);
+    Ok(Value::Undefined)
 }
 
-fn set_align<'gc>(
+fn display_state<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.align: unimplemented");
-    Ok(Value::Undefined)
+    Ok(context.stage_display_state.to_string().into())
 }
 
-fn height<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
+fn set_display_state<'gc>(
+    activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(context.stage_size.1.to_pixels().into())
+    let display_state = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    // Unknown values are ignored.
+    if let Ok(display_state) = display_state.parse() {
+        change_display_state(context, display_state);
+    }
+    Ok(Value::Undefined)
 }
 
-fn remove_listener<'gc>(
+/// Asks the frontend to enter or leave fullscreen, and tells the `Stage` listeners
+/// with `onFullScreen` if it did.
+pub fn change_display_state(
+    context: &mut UpdateContext<'_, '_, '_>,
+    display_state: StageDisplayState,
+) {
+    if *context.stage_display_state == display_state {
+        return;
+    }
+    let is_full_screen = display_state == StageDisplayState::FullScreen;
+    if !context.input.set_full_screen(is_full_screen) {
+        log::warn!("Unable to change the display state to {}", display_state);
+        return;
+    }
+    *context.stage_display_state = display_state;
+    context.action_queue.queue_actions(
+        *context.levels.get(&0).expect("root level"),
+        ActionType::NotifyListeners {
+            listener: SystemListener::Stage,
+            method: "onFullScreen",
+            args: vec![is_full_screen.into()],
+        },
+        false,
+    );
+}
+
+fn height<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.removeListener: unimplemented");
-    Ok("".into())
+    Ok(context.stage_size.1.to_pixels().into())
 }
 
 fn scale_mode<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.scaleMode: unimplemented");
-    Ok("noScale".into())
+    Ok(context.stage_scale_mode.to_string().into())
 }
 
 fn set_scale_mode<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.scaleMode: unimplemented");
+    // Unknown values fall back to `showAll`.
+    let scale_mode: StageScaleMode = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?
+        .parse()
+        .unwrap_or_default();
+    *context.stage_scale_mode = scale_mode;
     Ok(Value::Undefined)
 }
 
 fn show_menu<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.showMenu: unimplemented");
-    Ok(true.into())
+    Ok((*context.show_menu).into())
 }
 
 fn set_show_menu<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Stage.showMenu: unimplemented");
+    let show_menu = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .as_bool(context.swf.version());
+    *context.show_menu = show_menu;
     Ok(Value::Undefined)
 }
 
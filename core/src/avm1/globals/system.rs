@@ -4,6 +4,7 @@ use crate::avm1::function::Executable;
 use crate::avm1::object::Object;
 use crate::avm1::{ScriptObject, TObject, Value};
 use crate::context::UpdateContext;
+use crate::unimplemented::FeatureKind;
 use core::fmt;
 use enumset::{EnumSet, EnumSetType};
 use gc_arena::MutationContext;
@@ -436,6 +437,9 @@ pub fn show_settings<'gc>(
     let panel = SettingsPanel::try_from(panel_pos as u8).unwrap_or(SettingsPanel::Privacy);
 
     log::warn!("System.showSettings({:?}) not not implemented", panel);
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.showSettings");
     Ok(Value::Undefined)
 }
 
@@ -493,11 +497,14 @@ pub fn get_exact_settings<'gc>(
 
 pub fn on_status<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _action_context: &mut UpdateContext<'_, 'gc, '_>,
+    action_context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("System.onStatus() not implemented");
+    action_context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.onStatus");
     Ok(Value::Undefined)
 }
 
@@ -4,47 +4,60 @@ use crate::avm1::function::Executable;
 use crate::avm1::object::Object;
 use crate::avm1::{ScriptObject, TObject, Value};
 use crate::context::UpdateContext;
+use crate::unimplemented::FeatureKind;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 use std::convert::Into;
 
 fn allow_domain<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("System.security.allowDomain() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.allowDomain");
     Ok(Value::Undefined)
 }
 
 fn allow_insecure_domain<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("System.security.allowInsecureDomain() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.allowInsecureDomain");
     Ok(Value::Undefined)
 }
 
 fn load_policy_file<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("System.security.allowInsecureDomain() not implemented");
+    log::warn!("System.security.loadPolicyFile() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.loadPolicyFile");
     Ok(Value::Undefined)
 }
 
 fn escape_domain<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("System.security.escapeDomain() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.escapeDomain");
     Ok(Value::Undefined)
 }
 
@@ -59,21 +72,27 @@ fn get_sandbox_type<'gc>(
 
 fn get_choose_local_swf_path<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     log::warn!("System.security.chooseLocalSwfPath() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.chooseLocalSwfPath");
     Ok(Value::Undefined)
 }
 
 fn policy_file_resolver<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("System.security.chooseLocalSwfPath() not implemented");
+    log::warn!("System.security.PolicyFileResolver() not implemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "System.security.PolicyFileResolver");
     Ok(Value::Undefined)
 }
 
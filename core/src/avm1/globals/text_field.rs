@@ -416,6 +416,94 @@ pub fn set_auto_size<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn scroll<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(etext) = this
+        .as_display_object()
+        .and_then(|dobj| dobj.as_edit_text())
+    {
+        return Ok((etext.scroll() as f64).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_scroll<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(etext) = this
+        .as_display_object()
+        .and_then(|dobj| dobj.as_edit_text())
+    {
+        if let Some(value) = args.get(0) {
+            let scroll = value.coerce_to_f64(activation, context)?;
+            if scroll.is_finite() {
+                etext.set_scroll(context.gc_context, scroll.max(1.0) as usize);
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn maxscroll<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(etext) = this
+        .as_display_object()
+        .and_then(|dobj| dobj.as_edit_text())
+    {
+        return Ok((etext.maxscroll() as f64).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn mouse_wheel_enabled<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(etext) = this
+        .as_display_object()
+        .and_then(|dobj| dobj.as_edit_text())
+    {
+        return Ok(etext.is_mouse_wheel_enabled().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_mouse_wheel_enabled<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(etext) = this
+        .as_display_object()
+        .and_then(|dobj| dobj.as_edit_text())
+    {
+        if let Some(value) = args.get(0) {
+            let is_enabled = value.as_bool(activation.current_swf_version());
+            etext.set_mouse_wheel_enabled(context.gc_context, is_enabled);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn create_proto<'gc>(
     gc_context: MutationContext<'gc, '_>,
     proto: Object<'gc>,
@@ -524,6 +612,27 @@ pub fn attach_virtual_properties<'gc>(gc_context: MutationContext<'gc, '_>, obje
         Some(Executable::Native(set_embed_fonts)),
         ReadOnly.into(),
     );
+    object.add_property(
+        gc_context,
+        "scroll",
+        Executable::Native(scroll),
+        Some(Executable::Native(set_scroll)),
+        ReadOnly.into(),
+    );
+    object.add_property(
+        gc_context,
+        "maxscroll",
+        Executable::Native(maxscroll),
+        None,
+        ReadOnly.into(),
+    );
+    object.add_property(
+        gc_context,
+        "mouseWheelEnabled",
+        Executable::Native(mouse_wheel_enabled),
+        Some(Executable::Native(set_mouse_wheel_enabled)),
+        ReadOnly.into(),
+    );
 }
 
 fn get_new_text_format<'gc>(
@@ -0,0 +1,355 @@
+//! `TextSnapshot` impl
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute::*;
+use crate::avm1::{Object, ScriptObject, TObject, UpdateContext, Value};
+use crate::display_object::{StaticTextChar, Text};
+use crate::unimplemented::FeatureKind;
+use gc_arena::MutationContext;
+
+/// Implements `TextSnapshot`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "getCount",
+        get_count,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "getText",
+        get_text,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "findText",
+        find_text,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "getSelected",
+        get_selected,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "getSelectedText",
+        get_selected_text,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "setSelected",
+        set_selected,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "setSelectColor",
+        set_select_color,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "hitTestTextNearPos",
+        hit_test_text_near_pos,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "getTextRunInfo",
+        get_text_run_info,
+        gc_context,
+        DontDelete | ReadOnly | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// Returns every character of the snapshot, along with the text it belongs to
+/// and its index within that text.
+fn snapshot_chars<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+) -> Vec<(Text<'gc>, usize, StaticTextChar)> {
+    let texts = this
+        .as_text_snapshot_object()
+        .map(|snapshot| snapshot.texts())
+        .unwrap_or_default();
+    texts
+        .into_iter()
+        .flat_map(|text| {
+            text.chars(context)
+                .into_iter()
+                .enumerate()
+                .map(move |(i, c)| (text, i, c))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Builds the string for a range of snapshot characters.
+fn range_to_string(
+    chars: &[(Text<'_>, usize, StaticTextChar)],
+    include_line_endings: bool,
+) -> String {
+    let mut string = String::with_capacity(chars.len());
+    for (i, (_, _, c)) in chars.iter().enumerate() {
+        if include_line_endings && c.new_line && i > 0 {
+            string.push('\n');
+        }
+        string.push(c.character);
+    }
+    string
+}
+
+/// Coerces the `start` and `end` arguments to a range of character indices.
+fn char_range<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+    len: usize,
+) -> Result<std::ops::Range<usize>, Error<'gc>> {
+    let start = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation, context)?
+        .max(0) as usize;
+    let end = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation, context)?
+        .max(0) as usize;
+    let start = start.min(len);
+    Ok(start..end.min(len).max(start))
+}
+
+fn get_count<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(snapshot_chars(context, this).len().into())
+}
+
+fn get_text<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let chars = snapshot_chars(context, this);
+    let range = char_range(activation, context, args, chars.len())?;
+    let include_line_endings = args
+        .get(2)
+        .map(|v| v.as_bool(activation.current_swf_version()))
+        .unwrap_or(false);
+    Ok(range_to_string(&chars[range], include_line_endings).into())
+}
+
+fn find_text<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let chars = snapshot_chars(context, this);
+    let start = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation, context)?
+        .max(0) as usize;
+    let needle: Vec<char> = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?
+        .chars()
+        .collect();
+    let case_sensitive = args
+        .get(2)
+        .map(|v| v.as_bool(activation.current_swf_version()))
+        .unwrap_or(false);
+
+    let matches = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+    if !needle.is_empty() && start < chars.len() {
+        let found = chars[start..].windows(needle.len()).position(|window| {
+            window
+                .iter()
+                .zip(&needle)
+                .all(|((_, _, c), n)| matches(c.character, *n))
+        });
+        if let Some(index) = found {
+            return Ok((start + index).into());
+        }
+    }
+    Ok((-1).into())
+}
+
+fn get_selected<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let chars = snapshot_chars(context, this);
+    let range = char_range(activation, context, args, chars.len())?;
+    Ok(chars[range]
+        .iter()
+        .any(|(text, i, _)| text.is_glyph_selected(*i))
+        .into())
+}
+
+fn get_selected_text<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let chars: Vec<_> = snapshot_chars(context, this)
+        .into_iter()
+        .filter(|(text, i, _)| text.is_glyph_selected(*i))
+        .collect();
+    let include_line_endings = args
+        .get(0)
+        .map(|v| v.as_bool(activation.current_swf_version()))
+        .unwrap_or(false);
+    Ok(range_to_string(&chars, include_line_endings).into())
+}
+
+fn set_selected<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let chars = snapshot_chars(context, this);
+    let range = char_range(activation, context, args, chars.len())?;
+    let selected = args
+        .get(2)
+        .map(|v| v.as_bool(activation.current_swf_version()))
+        .unwrap_or(true);
+    for (text, i, _) in &chars[range] {
+        text.set_glyph_selected(context.gc_context, *i, selected);
+    }
+    Ok(Value::Undefined)
+}
+
+fn set_select_color<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let rgb = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation, context)? as i32;
+    let color = swf::Color {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+        a: 255,
+    };
+    if let Some(snapshot) = this.as_text_snapshot_object() {
+        for text in snapshot.texts() {
+            text.set_select_color(context.gc_context, color.clone());
+        }
+    }
+    Ok(Value::Undefined)
+}
+
+fn hit_test_text_near_pos<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let x = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation, context)?;
+    let y = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation, context)?;
+    let close_dist = match args.get(2) {
+        Some(v) => v.coerce_to_f64(activation, context)?,
+        None => 0.0,
+    };
+    if x.is_nan() || y.is_nan() {
+        return Ok((-1).into());
+    }
+
+    // Find the glyph whose bounds are closest to the point.
+    let mut nearest = None;
+    for (i, (_, _, c)) in snapshot_chars(context, this).iter().enumerate() {
+        let bounds = &c.bounds;
+        let dx = (bounds.x_min.to_pixels() - x)
+            .max(x - bounds.x_max.to_pixels())
+            .max(0.0);
+        let dy = (bounds.y_min.to_pixels() - y)
+            .max(y - bounds.y_max.to_pixels())
+            .max(0.0);
+        let dist = dx.hypot(dy);
+        if dist <= close_dist && nearest.map(|(_, d)| dist < d).unwrap_or(true) {
+            nearest = Some((i, dist));
+        }
+    }
+    Ok(nearest
+        .map(|(i, _)| Value::from(i))
+        .unwrap_or_else(|| (-1).into()))
+}
+
+fn get_text_run_info<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    log::warn!("TextSnapshot.getTextRunInfo: unimplemented");
+    context
+        .unimplemented_features
+        .record(FeatureKind::Builtin, "TextSnapshot.getTextRunInfo");
+    Ok(Value::Undefined)
+}
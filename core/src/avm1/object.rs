@@ -9,6 +9,7 @@ use crate::avm1::value_object::ValueObject;
 
 use crate::avm1::activation::Activation;
 use crate::avm1::color_transform_object::ColorTransformObject;
+use crate::avm1::text_snapshot_object::TextSnapshotObject;
 use crate::avm1::xml_attributes_object::XMLAttributesObject;
 use crate::avm1::xml_idmap_object::XMLIDMapObject;
 use crate::avm1::xml_object::XMLObject;
@@ -38,6 +39,7 @@ use std::fmt::Debug;
         FunctionObject(FunctionObject<'gc>),
         SharedObject(SharedObject<'gc>),
         ColorTransformObject(ColorTransformObject<'gc>),
+        TextSnapshotObject(TextSnapshotObject<'gc>),
     }
 )]
 pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy {
@@ -403,6 +405,11 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         None
     }
 
+    /// Get the underlying `TextSnapshotObject`, if it exists
+    fn as_text_snapshot_object(&self) -> Option<TextSnapshotObject<'gc>> {
+        None
+    }
+
     fn as_ptr(&self) -> *const ObjectPtr;
 
     /// Check if this object is in the prototype chain of the specified test object.
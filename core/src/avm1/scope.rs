@@ -202,6 +202,11 @@ impl<'gc> Scope<'gc> {
         }
     }
 
+    /// Returns what kind of scope this is.
+    pub fn class(&self) -> ScopeClass {
+        self.class
+    }
+
     /// Returns a reference to the current local scope object.
     pub fn locals(&self) -> &Object<'gc> {
         &self.values
@@ -9,6 +9,7 @@ use crate::avm1::{Object, ObjectPtr, ScriptObject, TDisplayObject, TObject, Valu
 use crate::context::UpdateContext;
 use crate::display_object::{DisplayObject, EditText, MovieClip};
 use crate::property_map::PropertyMap;
+use crate::stage::StageQuality;
 use enumset::EnumSet;
 use gc_arena::{Collect, GcCell, MutationContext};
 use std::borrow::Cow;
@@ -864,97 +865,125 @@ fn set_name<'gc>(
 fn drop_target<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
+    this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _droptarget");
-    Ok("".into())
+    let drop_target = this.as_movie_clip().and_then(|clip| clip.drop_target());
+    match drop_target {
+        // Root clips have an empty slash path, but are returned as `/`.
+        Some(target) if target.parent().is_none() => Ok("/".into()),
+        Some(target) => Ok(target.slash_path().into()),
+        None => Ok("".into()),
+    }
 }
 
 fn url<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
+    this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _url");
-    Ok("".into())
+    Ok(this
+        .movie()
+        .and_then(|movie| movie.url().map(|url| url.to_string()))
+        .unwrap_or_default()
+        .into())
 }
 
 fn high_quality<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _highquality");
-    Ok(1.into())
+    let quality = match *context.stage_quality {
+        StageQuality::Best => 2,
+        StageQuality::High => 1,
+        _ => 0,
+    };
+    Ok(quality.into())
 }
 
 fn set_high_quality<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    log::warn!("Unimplemented property _highquality");
+    let quality = match val.coerce_to_f64(activation, context)? as i32 {
+        2 => StageQuality::Best,
+        1 => StageQuality::High,
+        _ => StageQuality::Low,
+    };
+    set_stage_quality(context, quality);
     Ok(())
 }
 
 fn focus_rect<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
+    this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _focusrect");
-    Ok(Value::Null)
+    Ok(this.focus_rect().map_or(Value::Null, Value::Bool))
 }
 
 fn set_focus_rect<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: DisplayObject<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    log::warn!("Unimplemented property _focusrect");
+    let focus_rect = match val {
+        Value::Undefined | Value::Null => None,
+        val => Some(val.as_bool(activation.current_swf_version())),
+    };
+    this.set_focus_rect(context.gc_context, focus_rect);
     Ok(())
 }
 
 fn sound_buf_time<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _soundbuftime");
-    Ok(5.into())
+    Ok((*context.sound_buffer_time).into())
 }
 
 fn set_sound_buf_time<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    log::warn!("Unimplemented property _soundbuftime");
+    // The buffer time is global, and can't be negative.
+    *context.sound_buffer_time = val.coerce_to_i32(activation, context)?.max(0);
     Ok(())
 }
 
 fn quality<'gc>(
     _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    log::warn!("Unimplemented property _quality");
-    Ok("HIGH".into())
+    Ok(context.stage_quality.to_string().into())
 }
 
 fn set_quality<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _context: &mut UpdateContext<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    log::warn!("Unimplemented property _quality");
+    // Invalid quality names are ignored.
+    if let Ok(quality) = val.coerce_to_string(activation, context)?.parse() {
+        set_stage_quality(context, quality);
+    }
     Ok(())
 }
 
+/// Changes the global rendering quality, set by the `_quality` and `_highquality` properties.
+fn set_stage_quality(context: &mut UpdateContext<'_, '_, '_>, quality: StageQuality) {
+    *context.stage_quality = quality;
+    context.renderer.set_quality(quality);
+}
+
 fn x_mouse<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     context: &mut UpdateContext<'_, 'gc, '_>,
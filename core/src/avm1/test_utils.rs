@@ -1,18 +1,32 @@
+use crate::audio_manager::AudioManager;
 use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::error::Error;
 use crate::avm1::globals::system::SystemProperties;
 use crate::avm1::{Avm1, Object, UpdateContext};
+use crate::avm2::Avm2;
+use crate::backend::accessibility::NullAccessibilityBackend;
 use crate::backend::audio::NullAudioBackend;
+use crate::backend::font::NullFontBackend;
 use crate::backend::input::NullInputBackend;
+use crate::backend::log::NullLogBackend;
 use crate::backend::navigator::NullNavigatorBackend;
+use crate::backend::printer::NullPrinterBackend;
 use crate::backend::render::NullRenderer;
+#[cfg(feature = "rtmp")]
+use crate::backend::rtmp::NullRtmpBackend;
 use crate::backend::storage::MemoryStorageBackend;
 use crate::context::ActionQueue;
 use crate::display_object::{MovieClip, TDisplayObject};
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::LocalConnections;
 use crate::prelude::*;
+use crate::profiler::Profiler;
+#[cfg(feature = "rtmp")]
+use crate::rtmp::RtmpConnections;
+use crate::stage::{StageAlign, StageDisplayState, StageQuality, StageScaleMode};
 use crate::tag_utils::{SwfMovie, SwfSlice};
+use crate::unimplemented::UnimplementedFeatures;
 use gc_arena::{rootless_arena, MutationContext};
 use rand::{rngs::SmallRng, SeedableRng};
 use std::collections::{BTreeMap, HashMap};
@@ -62,17 +76,38 @@ where
             navigator: &mut NullNavigatorBackend::new(),
             renderer: &mut NullRenderer::new(),
             system_prototypes: avm.prototypes().clone(),
+            avm2: &mut Avm2::new(gc_context),
             mouse_hovered_object: None,
             mouse_position: &(Twips::new(0), Twips::new(0)),
             drag_object: &mut None,
+            focus: &mut None,
             stage_size: (Twips::from_pixels(550.0), Twips::from_pixels(400.0)),
+            stage_scale_mode: &mut StageScaleMode::default(),
+            stage_align: &mut StageAlign::default(),
+            stage_quality: &mut StageQuality::default(),
+            stage_display_state: &mut StageDisplayState::default(),
+            show_menu: &mut true,
+            sound_buffer_time: &mut 5,
             player: None,
             load_manager: &mut LoadManager::new(),
             system: &mut SystemProperties::default(),
             instance_counter: &mut 0,
             storage: &mut MemoryStorageBackend::default(),
+            font: &mut NullFontBackend::new(),
+            printer: &mut NullPrinterBackend::new(),
+            accessibility: &mut NullAccessibilityBackend::new(),
+            #[cfg(feature = "rtmp")]
+            rtmp: &mut NullRtmpBackend::new(),
+            log: &mut NullLogBackend::new(),
             shared_objects: &mut HashMap::new(),
             unbound_text_fields: &mut Vec::new(),
+            unloaded_clips: &mut Vec::new(),
+            local_connections: &mut LocalConnections::new(),
+            #[cfg(feature = "rtmp")]
+            rtmp_connections: &mut RtmpConnections::new(),
+            audio_manager: &mut AudioManager::new(),
+            profiler: &mut Profiler::new(),
+            unimplemented_features: &mut UnimplementedFeatures::new(),
         };
         root.post_instantiation(&mut avm, &mut context, root, None, false);
         root.set_name(context.gc_context, "");
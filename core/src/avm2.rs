@@ -0,0 +1,229 @@
+//! ActionScript Virtual Machine 2 (AS3) support
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::install_instance_slots;
+use crate::avm2::globals::flash::events::{event, eventdispatcher};
+use crate::avm2::globals::SystemPrototypes;
+use crate::avm2::names::{Multiname, Namespace, QName};
+use crate::avm2::script::{Script, TranslationUnit};
+use crate::avm2::stage_object::StageObject;
+use crate::context::UpdateContext;
+use crate::display_object::{DisplayObject, TDisplayObject};
+use gc_arena::{Collect, MutationContext};
+use std::collections::HashMap;
+use std::io::Cursor;
+use swf::avm2::read::Reader;
+
+macro_rules! avm_debug {
+    ($($arg:tt)*) => (
+        #[cfg(feature = "avm_debug")]
+        log::debug!($($arg)*)
+    )
+}
+
+pub mod activation;
+pub mod amf;
+pub mod class;
+pub mod function;
+pub mod globals;
+pub mod names;
+pub mod object;
+pub mod property;
+pub mod scope;
+pub mod script;
+pub mod script_object;
+pub mod stage_object;
+pub mod value;
+
+pub use crate::avm2::object::{Object, TObject};
+pub use crate::avm2::value::Value;
+
+/// Boxed error alias.
+///
+/// As AVM2 is a far stricter VM than AVM1, this may eventually be replaced
+/// with a proper Avm2Error enum.
+pub type Error = Box<dyn std::error::Error>;
+
+/// The state of an AVM2 interpreter.
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct Avm2<'gc> {
+    /// The global object holding the builtins.
+    globals: Object<'gc>,
+
+    /// System prototypes.
+    system_prototypes: SystemPrototypes<'gc>,
+
+    /// The scripts that define each global name loaded from ABC files.
+    definitions: HashMap<QName, Script<'gc>>,
+
+    /// The object representing the stage, created when it's first accessed.
+    stage: Option<Object<'gc>>,
+}
+
+impl<'gc> Avm2<'gc> {
+    /// Construct a new AVM interpreter.
+    pub fn new(mc: MutationContext<'gc, '_>) -> Self {
+        let (globals, system_prototypes) =
+            globals::construct_global_scope(mc).expect("Builtins should be valid");
+
+        Self {
+            globals,
+            system_prototypes,
+            definitions: HashMap::new(),
+            stage: None,
+        }
+    }
+
+    /// Return the current set of system prototypes.
+    pub fn prototypes(&self) -> &SystemPrototypes<'gc> {
+        &self.system_prototypes
+    }
+
+    /// The global object holding the builtins.
+    pub fn globals(&self) -> Object<'gc> {
+        self.globals
+    }
+
+    /// The object representing the stage.
+    pub fn stage(&mut self, mc: MutationContext<'gc, '_>) -> Object<'gc> {
+        let stage_proto = self.system_prototypes.stage;
+        *self.stage.get_or_insert_with(|| stage_proto.derive(mc))
+    }
+
+    /// Find the object that defines a global name, which is either the
+    /// global object of a loaded script or the builtins.
+    ///
+    /// The defining script is initialized if it hasn't been yet.
+    pub fn find_definition(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        multiname: &Multiname,
+    ) -> Result<Option<Object<'gc>>, Error> {
+        let script = multiname.local_name().and_then(|local_name| {
+            multiname.namespace_set().find_map(|ns| {
+                if ns.is_any() {
+                    context
+                        .avm2
+                        .definitions
+                        .iter()
+                        .find(|(name, _)| name.local_name() == local_name)
+                        .map(|(_, script)| *script)
+                } else {
+                    context
+                        .avm2
+                        .definitions
+                        .get(&QName::new(ns.clone(), local_name))
+                        .copied()
+                }
+            })
+        });
+
+        if let Some(script) = script {
+            return Ok(Some(script.globals(context)?));
+        }
+
+        let globals = context.avm2.globals;
+        if globals.resolve_multiname(multiname).is_some() {
+            return Ok(Some(globals));
+        }
+
+        Ok(None)
+    }
+
+    /// Load the ABC file of a `DoABC` tag, and run its entry point.
+    pub fn load_abc(abc: &[u8], context: &mut UpdateContext<'_, 'gc, '_>) -> Result<(), Error> {
+        let abc_file = Reader::new(Cursor::new(abc)).read()?;
+        let translation_unit = TranslationUnit::from_abc(abc_file);
+        let num_scripts = translation_unit.abc().scripts.len();
+        let object_proto = context.avm2.prototypes().object;
+
+        let mut entrypoint = None;
+        for i in 0..num_scripts {
+            let script = Script::from_abc_index(
+                context.gc_context,
+                translation_unit.clone(),
+                i as u32,
+                object_proto,
+            )?;
+
+            for name in script.trait_names()? {
+                context.avm2.definitions.insert(name, script);
+            }
+
+            entrypoint = Some(script);
+        }
+
+        if let Some(entrypoint) = entrypoint {
+            entrypoint.globals(context)?;
+        }
+
+        Ok(())
+    }
+
+    /// Construct the AVM2 object of a display object, as an instance of the
+    /// class with the given fully-qualified name.
+    ///
+    /// The class constructor runs with the new object as `this`, once the
+    /// object has been bound to the display object.
+    pub fn construct_display_object(
+        mut display_object: DisplayObject<'gc>,
+        class_name: &str,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Object<'gc>, Error> {
+        let (package, local_name) = match class_name.rfind('.') {
+            Some(index) => (&class_name[..index], &class_name[index + 1..]),
+            None => ("", class_name),
+        };
+        let class_qname = QName::new(Namespace::package(package), local_name);
+
+        let mut activation = Activation::from_nothing();
+        let definition = Self::find_definition(context, &class_qname.clone().into())?
+            .ok_or_else(|| format!("Class {} does not exist", class_name))?;
+        let class = definition
+            .get_property(definition, &class_qname, &mut activation, context)?
+            .as_object()?;
+        let prototype = class
+            .get_property(
+                class,
+                &QName::dynamic_name("prototype"),
+                &mut activation,
+                context,
+            )?
+            .as_object()?;
+
+        let object: Object<'gc> =
+            StageObject::for_display_object(context.gc_context, display_object, prototype).into();
+        display_object.set_object2(context.gc_context, object);
+        install_instance_slots(object, class, context)?;
+        class.call(Some(object), &[], &mut activation, context)?;
+
+        Ok(object)
+    }
+
+    /// Call a function, such as a frame script or an event listener, outside
+    /// of any other AVM2 code.
+    pub fn run_stack_frame_for_callable(
+        callable: Object<'gc>,
+        receiver: Option<Object<'gc>>,
+        args: &[Value<'gc>],
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        callable.call(receiver, args, &mut Activation::from_nothing(), context)?;
+
+        Ok(())
+    }
+
+    /// Dispatch a new event of the given type to an object.
+    ///
+    /// Returns `false` if a listener cancelled the event.
+    pub fn dispatch_event(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        target: Object<'gc>,
+        event_type: &str,
+    ) -> Result<bool, Error> {
+        let mut activation = Activation::from_nothing();
+        let event = event::create_event(&mut activation, context, event_type)?;
+
+        eventdispatcher::dispatch_event(&mut activation, context, target, event)
+    }
+}
@@ -0,0 +1,1569 @@
+//! Activation frames
+
+use crate::avm2::class::{install_trait, new_class};
+use crate::avm2::function::FunctionObject;
+use crate::avm2::names::{Multiname, Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::scope::Scope;
+use crate::avm2::script::{BytecodeMethod, TranslationUnit};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::{Avm2, Error};
+use crate::context::UpdateContext;
+use crate::unimplemented::FeatureKind;
+use gc_arena::GcCell;
+use std::convert::TryFrom;
+use swf::avm2::types::{Index, Multiname as AbcMultiname, Op};
+
+/// The most branches back to an earlier instruction that a single activation
+/// may take before it's assumed to be stuck in an infinite loop.
+const MAX_BACKWARD_BRANCHES: u32 = 10_000_000;
+
+/// How execution of a method should continue after an instruction.
+enum FrameControl<'gc> {
+    /// Continue with the next instruction.
+    Continue,
+
+    /// Jump to an offset relative to the end of the current instruction.
+    Branch(i32),
+
+    /// Jump to an offset relative to the start of the current instruction,
+    /// as done by `lookupswitch`.
+    Switch(i32),
+
+    /// Return from the method with a value.
+    Return(Value<'gc>),
+}
+
+/// Represents a single activation of a given AVM2 function or script.
+pub struct Activation<'gc> {
+    /// The method being executed, or `None` for activations that only exist
+    /// to call into native code.
+    method: Option<BytecodeMethod>,
+
+    /// The local registers, which start with `this` and the arguments.
+    local_registers: Vec<Value<'gc>>,
+
+    /// The current scope chain, which starts with the scope the method was
+    /// defined in.
+    scope: Option<GcCell<'gc, Scope<'gc>>>,
+
+    /// The objects pushed onto the scope chain by this activation, which
+    /// `getscopeobject` indexes into.
+    local_scopes: Vec<Object<'gc>>,
+
+    /// The superclass of the class the method was defined on, used by
+    /// `super` operations.
+    superclass: Option<Object<'gc>>,
+
+    /// The operand stack.
+    stack: Vec<Value<'gc>>,
+}
+
+impl<'gc> Activation<'gc> {
+    /// Construct an activation that does not represent any particular scope.
+    ///
+    /// This exists primarily for non-AVM2 related manipulations of the
+    /// interpreter environment that require an activation, such as calling
+    /// builtins or coercing values.
+    pub fn from_nothing() -> Self {
+        Self {
+            method: None,
+            local_registers: vec![],
+            scope: None,
+            local_scopes: vec![],
+            superclass: None,
+            stack: vec![],
+        }
+    }
+
+    /// Construct an activation for running a method with the given
+    /// arguments.
+    pub fn from_method(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        method: BytecodeMethod,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+        this: Option<Object<'gc>>,
+        arguments: &[Value<'gc>],
+        superclass: Option<Object<'gc>>,
+    ) -> Result<Self, Error> {
+        let body = method
+            .body()
+            .ok_or("Cannot execute a method without a body")?;
+        let abc_method = method.method();
+        let num_params = abc_method.params.len();
+        let num_locals = (body.num_locals as usize).max(num_params + 2);
+
+        let mut local_registers = vec![Value::Undefined; num_locals];
+        local_registers[0] = this.map(Value::Object).unwrap_or(Value::Null);
+
+        for (i, param) in abc_method.params.iter().enumerate() {
+            local_registers[i + 1] = match (arguments.get(i), &param.default_value) {
+                (Some(argument), _) => argument.clone(),
+                (None, Some(default)) => {
+                    Value::from_abc_default_value(method.translation_unit(), default)?
+                }
+                (None, None) => Value::Undefined,
+            };
+        }
+
+        if abc_method.needs_rest || abc_method.needs_arguments_object {
+            let values = if abc_method.needs_rest {
+                arguments.get(num_params..).unwrap_or(&[])
+            } else {
+                arguments
+            };
+            let array_proto = context.avm2.prototypes().array;
+            local_registers[num_params + 1] =
+                ScriptObject::array(context.gc_context, array_proto, values).into();
+        }
+
+        Ok(Self {
+            method: Some(method),
+            local_registers,
+            scope,
+            local_scopes: vec![],
+            superclass,
+            stack: vec![],
+        })
+    }
+
+    /// Push a value onto the operand stack.
+    pub fn push(&mut self, value: impl Into<Value<'gc>>) {
+        let value = value.into();
+        avm_debug!("Stack push {}: {:?}", self.stack.len(), value);
+        self.stack.push(value);
+    }
+
+    /// Retrieve the top-most value on the operand stack.
+    #[allow(clippy::let_and_return)]
+    pub fn pop(&mut self) -> Value<'gc> {
+        let value = self.stack.pop().unwrap_or_else(|| {
+            log::warn!("Avm2::pop: Stack underflow");
+            Value::Undefined
+        });
+
+        avm_debug!("Stack pop {}: {:?}", self.stack.len(), value);
+
+        value
+    }
+
+    /// Pop the arguments of a call, which were pushed in order.
+    fn pop_args(&mut self, num_args: u32) -> Vec<Value<'gc>> {
+        let mut args = vec![Value::Undefined; num_args as usize];
+        for arg in args.iter_mut().rev() {
+            *arg = self.pop();
+        }
+
+        args
+    }
+
+    fn register(&self, index: u32) -> Result<Value<'gc>, Error> {
+        self.local_registers
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format!("Out of bounds register read: {}", index).into())
+    }
+
+    fn set_register(&mut self, index: u32, value: Value<'gc>) -> Result<(), Error> {
+        match self.local_registers.get_mut(index as usize) {
+            Some(register) => {
+                *register = value;
+                Ok(())
+            }
+            None => Err(format!("Out of bounds register write: {}", index).into()),
+        }
+    }
+
+    /// The global object of the current scope chain.
+    fn global_object(&self, context: &UpdateContext<'_, 'gc, '_>) -> Object<'gc> {
+        self.scope
+            .map(|scope| *Scope::global_scope(scope).read().locals())
+            .unwrap_or_else(|| context.avm2.globals())
+    }
+
+    /// Run the method of this activation until it returns.
+    pub fn run_actions(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let method = self
+            .method
+            .clone()
+            .ok_or("Cannot run an activation without a method")?;
+        let code_len = method
+            .body()
+            .ok_or("Cannot execute a method without a body")?
+            .code
+            .len();
+        let decoded = method.decoded_body()?;
+        let ops = &decoded.ops;
+        let offsets = &decoded.offsets;
+
+        let mut backward_branches = 0;
+        let mut position = 0;
+        while let Some(op) = ops.get(position) {
+            avm_debug!("Opcode: {:?}", op);
+
+            let op_offset = offsets[position];
+            let next_offset = offsets.get(position + 1).copied().unwrap_or(code_len);
+            position += 1;
+
+            let target = match self.do_op(context, &method, op)? {
+                FrameControl::Continue => continue,
+                FrameControl::Branch(offset) => next_offset as i64 + i64::from(offset),
+                FrameControl::Switch(offset) => op_offset as i64 + i64::from(offset),
+                FrameControl::Return(value) => return Ok(value),
+            };
+
+            if target <= op_offset as i64 {
+                backward_branches += 1;
+                if backward_branches > MAX_BACKWARD_BRANCHES {
+                    return Err(format!(
+                        "{} backward branches were taken in one method call. This is probably an infinite loop.",
+                        MAX_BACKWARD_BRANCHES
+                    )
+                    .into());
+                }
+            }
+
+            position = if target == code_len as i64 {
+                ops.len()
+            } else {
+                offsets
+                    .binary_search(&(target as usize))
+                    .ok()
+                    .filter(|_| target >= 0)
+                    .ok_or_else(|| format!("Jump to invalid offset {}", target))?
+            };
+        }
+
+        Ok(Value::Undefined)
+    }
+
+    /// Run a single instruction.
+    fn do_op(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        method: &BytecodeMethod,
+        op: &Op,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let translation_unit = method.translation_unit();
+
+        match op {
+            Op::PushByte { value } => self.op_push(i32::from(*value as i8)),
+            Op::PushShort { value } => self.op_push(i32::from(*value as i16)),
+            Op::PushInt { value } => self.op_push(translation_unit.pool_int(value.0)?),
+            Op::PushUint { value } => self.op_push(translation_unit.pool_uint(value.0)?),
+            Op::PushDouble { value } => self.op_push(translation_unit.pool_double(value.0)?),
+            Op::PushString { value } => self.op_push(translation_unit.pool_string(value.0)?),
+            Op::PushNamespace { value } => self.op_push(Namespace::from_abc_namespace(
+                translation_unit,
+                value.clone(),
+            )?),
+            Op::PushTrue => self.op_push(true),
+            Op::PushFalse => self.op_push(false),
+            Op::PushNull => self.op_push(Value::Null),
+            Op::PushUndefined => self.op_push(Value::Undefined),
+            Op::PushNaN => self.op_push(f64::NAN),
+            Op::Pop => self.op_pop(),
+            Op::Dup => self.op_dup(),
+            Op::Swap => self.op_swap(),
+            Op::GetLocal { index } => self.op_get_local(*index),
+            Op::SetLocal { index } => self.op_set_local(*index),
+            Op::Kill { index } => self.op_kill(*index),
+            Op::IncLocal { index } => self.op_inc_local(context, *index, 1.0),
+            Op::DecLocal { index } => self.op_inc_local(context, *index, -1.0),
+            Op::IncLocalI { index } => self.op_inc_local_i(context, *index, 1),
+            Op::DecLocalI { index } => self.op_inc_local_i(context, *index, -1),
+            Op::PushScope => self.op_push_scope(context),
+            Op::PushWith => self.op_push_with(context),
+            Op::PopScope => self.op_pop_scope(),
+            Op::GetScopeObject { index } => self.op_get_scope_object(*index),
+            Op::GetGlobalScope => self.op_get_global_scope(context),
+            Op::NewActivation => self.op_new_activation(context, method),
+            Op::GetProperty { index } => self.op_get_property(context, translation_unit, index),
+            Op::SetProperty { index } => self.op_set_property(context, translation_unit, index),
+            Op::InitProperty { index } => self.op_init_property(context, translation_unit, index),
+            Op::DeleteProperty { index } => {
+                self.op_delete_property(context, translation_unit, index)
+            }
+            Op::GetSlot { index } => self.op_get_slot(*index),
+            Op::SetSlot { index } => self.op_set_slot(context, *index),
+            Op::GetGlobalSlot { index } => self.op_get_global_slot(context, *index),
+            Op::SetGlobalSlot { index } => self.op_set_global_slot(context, *index),
+            Op::FindProperty { index } => {
+                self.op_find_property(context, translation_unit, index, false)
+            }
+            Op::FindPropStrict { index } => {
+                self.op_find_property(context, translation_unit, index, true)
+            }
+            Op::GetLex { index } => self.op_get_lex(context, translation_unit, index),
+            Op::GetSuper { index } => self.op_get_super(context, translation_unit, index),
+            Op::SetSuper { index } => self.op_set_super(context, translation_unit, index),
+            Op::In => self.op_in(context),
+            Op::Call { num_args } => self.op_call(context, *num_args),
+            Op::CallProperty { index, num_args } => {
+                self.op_call_property(context, translation_unit, index, *num_args, true, true)
+            }
+            Op::CallPropLex { index, num_args } => {
+                self.op_call_property(context, translation_unit, index, *num_args, false, true)
+            }
+            Op::CallPropVoid { index, num_args } => {
+                self.op_call_property(context, translation_unit, index, *num_args, true, false)
+            }
+            Op::CallSuper { index, num_args } => {
+                self.op_call_super(context, translation_unit, index, *num_args, true)
+            }
+            Op::CallSuperVoid { index, num_args } => {
+                self.op_call_super(context, translation_unit, index, *num_args, false)
+            }
+            Op::Construct { num_args } => self.op_construct(context, *num_args),
+            Op::ConstructProp { index, num_args } => {
+                self.op_construct_prop(context, translation_unit, index, *num_args)
+            }
+            Op::ConstructSuper { num_args } => self.op_construct_super(context, *num_args),
+            Op::NewObject { num_args } => self.op_new_object(context, *num_args),
+            Op::NewArray { num_args } => self.op_new_array(context, *num_args),
+            Op::NewFunction { index } => self.op_new_function(context, translation_unit, index.0),
+            Op::NewClass { index } => self.op_new_class(context, translation_unit, index.0),
+            Op::Add => self.op_add(context),
+            Op::AddI => self.op_binary_int(context, i32::wrapping_add),
+            Op::Subtract => self.op_binary_number(context, |a, b| a - b),
+            Op::SubtractI => self.op_binary_int(context, i32::wrapping_sub),
+            Op::Multiply => self.op_binary_number(context, |a, b| a * b),
+            Op::MultiplyI => self.op_binary_int(context, i32::wrapping_mul),
+            Op::Divide => self.op_binary_number(context, |a, b| a / b),
+            Op::Modulo => self.op_binary_number(context, |a, b| a % b),
+            Op::Negate => self.op_unary_number(context, |a| -a),
+            Op::NegateI => self.op_unary_int(context, i32::wrapping_neg),
+            Op::Increment => self.op_unary_number(context, |a| a + 1.0),
+            Op::IncrementI => self.op_unary_int(context, |a| a.wrapping_add(1)),
+            Op::Decrement => self.op_unary_number(context, |a| a - 1.0),
+            Op::DecrementI => self.op_unary_int(context, |a| a.wrapping_sub(1)),
+            Op::BitAnd => self.op_binary_int(context, |a, b| a & b),
+            Op::BitOr => self.op_binary_int(context, |a, b| a | b),
+            Op::BitXor => self.op_binary_int(context, |a, b| a ^ b),
+            Op::BitNot => self.op_unary_int(context, |a| !a),
+            Op::LShift => self.op_lshift(context),
+            Op::RShift => self.op_rshift(context),
+            Op::URShift => self.op_urshift(context),
+            Op::Not => self.op_not(),
+            Op::Equals => self.op_equals(context),
+            Op::StrictEquals => self.op_strict_equals(),
+            Op::LessThan => self.op_compare(context, Comparison::LessThan),
+            Op::LessEquals => self.op_compare(context, Comparison::LessEquals),
+            Op::GreaterThan => self.op_compare(context, Comparison::GreaterThan),
+            Op::GreaterEquals => self.op_compare(context, Comparison::GreaterEquals),
+            Op::Jump { offset } => Ok(FrameControl::Branch(*offset)),
+            Op::IfTrue { offset } => self.op_if_true(*offset, true),
+            Op::IfFalse { offset } => self.op_if_true(*offset, false),
+            Op::IfEq { offset } => self.op_if_eq(context, *offset, true),
+            Op::IfNe { offset } => self.op_if_eq(context, *offset, false),
+            Op::IfStrictEq { offset } => self.op_if_strict_eq(*offset, true),
+            Op::IfStrictNe { offset } => self.op_if_strict_eq(*offset, false),
+            Op::IfLt { offset } => self.op_if_compare(context, *offset, Comparison::LessThan, true),
+            Op::IfLe { offset } => {
+                self.op_if_compare(context, *offset, Comparison::LessEquals, true)
+            }
+            Op::IfGt { offset } => {
+                self.op_if_compare(context, *offset, Comparison::GreaterThan, true)
+            }
+            Op::IfGe { offset } => {
+                self.op_if_compare(context, *offset, Comparison::GreaterEquals, true)
+            }
+            Op::IfNlt { offset } => {
+                self.op_if_compare(context, *offset, Comparison::LessThan, false)
+            }
+            Op::IfNle { offset } => {
+                self.op_if_compare(context, *offset, Comparison::LessEquals, false)
+            }
+            Op::IfNgt { offset } => {
+                self.op_if_compare(context, *offset, Comparison::GreaterThan, false)
+            }
+            Op::IfNge { offset } => {
+                self.op_if_compare(context, *offset, Comparison::GreaterEquals, false)
+            }
+            Op::LookupSwitch {
+                default_offset,
+                case_offsets,
+            } => self.op_lookup_switch(context, *default_offset, case_offsets),
+            Op::ConvertB => self.op_convert_b(),
+            Op::ConvertD => self.op_convert_d(context),
+            Op::ConvertI => self.op_convert_i(context),
+            Op::ConvertU => self.op_convert_u(context),
+            Op::ConvertS => self.op_convert_s(context),
+            Op::ConvertO => self.op_convert_o(),
+            Op::CoerceA => Ok(FrameControl::Continue),
+            Op::CoerceS => self.op_coerce_s(context),
+            Op::Coerce { index } => self.op_coerce(context, translation_unit, index),
+            Op::AsType { type_name } => self.op_as_type(context, translation_unit, type_name),
+            Op::AsTypeLate => self.op_as_type_late(context),
+            Op::IsType { index } => self.op_is_type(context, translation_unit, index),
+            Op::IsTypeLate => self.op_is_type_late(context),
+            Op::InstanceOf => self.op_instance_of(context),
+            Op::TypeOf => self.op_type_of(),
+            Op::ReturnValue => self.op_return_value(),
+            Op::ReturnVoid => Ok(FrameControl::Return(Value::Undefined)),
+            Op::Throw => self.op_throw(context),
+            Op::HasNext => self.op_has_next(context),
+            Op::HasNext2 {
+                object_register,
+                index_register,
+            } => self.op_has_next_2(*object_register, *index_register),
+            Op::NextName => self.op_next_name(context),
+            Op::NextValue => self.op_next_value(context),
+            Op::Nop
+            | Op::Label
+            | Op::Debug { .. }
+            | Op::DebugFile { .. }
+            | Op::DebugLine { .. } => Ok(FrameControl::Continue),
+            // TODO: Exceptions, XML, `callmethod` and `callstatic`.
+            _ => {
+                context
+                    .unimplemented_features
+                    .record_variant(FeatureKind::Avm2Opcode, op);
+                Err(format!("Unimplemented AVM2 opcode {:?}", op).into())
+            }
+        }
+    }
+
+    fn op_push(&mut self, value: impl Into<Value<'gc>>) -> Result<FrameControl<'gc>, Error> {
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_pop(&mut self) -> Result<FrameControl<'gc>, Error> {
+        self.pop();
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_dup(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.stack.last().cloned().unwrap_or(Value::Undefined);
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_swap(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let a = self.pop();
+        let b = self.pop();
+        self.push(a);
+        self.push(b);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_get_local(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
+        let value = self.register(index)?;
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_set_local(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        self.set_register(index, value)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_kill(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
+        self.set_register(index, Value::Undefined)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_inc_local(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: u32,
+        amount: f64,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.register(index)?.coerce_to_number(self, context)?;
+        self.set_register(index, (value + amount).into())?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_inc_local_i(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: u32,
+        amount: i32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.register(index)?.coerce_to_i32(self, context)?;
+        self.set_register(index, value.wrapping_add(amount).into())?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_push_scope(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let object = self.pop().as_object()?;
+        self.scope = Some(Scope::push_scope(self.scope, object, context.gc_context));
+        self.local_scopes.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_push_with(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let object = self.pop().as_object()?;
+        self.scope = Some(Scope::push_with(self.scope, object, context.gc_context));
+        self.local_scopes.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_pop_scope(&mut self) -> Result<FrameControl<'gc>, Error> {
+        if self.local_scopes.pop().is_none() {
+            return Err("Scope stack underflow".into());
+        }
+
+        self.scope = self.scope.and_then(|scope| scope.read().parent());
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_get_scope_object(&mut self, index: u8) -> Result<FrameControl<'gc>, Error> {
+        let object = *self
+            .local_scopes
+            .get(index as usize)
+            .ok_or_else(|| format!("Scope object {} out of bounds", index))?;
+        self.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_get_global_scope(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let globals = self.global_object(context);
+        self.push(globals);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_new_activation(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        method: &BytecodeMethod,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let activation = ScriptObject::bare_object(context.gc_context);
+        if let Some(body) = method.body() {
+            for abc_trait in &body.traits {
+                install_trait(
+                    activation,
+                    method.translation_unit(),
+                    abc_trait,
+                    self.scope,
+                    None,
+                    context,
+                )?;
+            }
+        }
+
+        self.push(activation);
+        Ok(FrameControl::Continue)
+    }
+
+    /// Pop an object off the stack to access a property of.
+    ///
+    /// TODO: Properties of primitive values are not supported yet.
+    fn pop_object(&mut self) -> Result<Object<'gc>, Error> {
+        self.pop().as_object()
+    }
+
+    fn op_get_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+
+        let value = match object.resolve_multiname(&multiname) {
+            Some(name) => object.get_property(object, &name, self, context)?,
+            None => Value::Undefined,
+        };
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_set_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+
+        let name = resolve_for_set(object, &multiname)?;
+        object.set_property(object, &name, value, self, context)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_init_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+
+        let name = resolve_for_set(object, &multiname)?;
+        object.init_property(object, &name, value, self, context)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_delete_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+
+        let deleted = match object.resolve_multiname(&multiname) {
+            Some(name) => object.delete_property(context.gc_context, &name),
+            // Deleting a property that doesn't exist succeeds.
+            None => true,
+        };
+
+        self.push(deleted);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_get_slot(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
+        let object = self.pop_object()?;
+        let value = object.get_slot(index)?;
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_set_slot(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        let object = self.pop_object()?;
+        object.set_slot(index, value, context.gc_context)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_get_global_slot(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.global_object(context).get_slot(index)?;
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_set_global_slot(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        self.global_object(context)
+            .set_slot(index, value, context.gc_context)?;
+        Ok(FrameControl::Continue)
+    }
+
+    /// Find the object on the scope chain, or the script that defines it,
+    /// that holds a property.
+    fn find_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        multiname: &Multiname,
+    ) -> Result<Option<Object<'gc>>, Error> {
+        if let Some(scope) = self.scope {
+            if let Some(object) = scope.read().find(multiname) {
+                return Ok(Some(object));
+            }
+        }
+
+        Avm2::find_definition(context, multiname)
+    }
+
+    fn op_find_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+        strict: bool,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = match self.find_property(context, &multiname)? {
+            Some(object) => object,
+            None if strict => return Err(property_not_found(&multiname)),
+            None => self.global_object(context),
+        };
+
+        self.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    /// Find a property on the scope chain and retrieve its value.
+    fn get_lexical(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        multiname: &Multiname,
+    ) -> Result<Value<'gc>, Error> {
+        let object = self
+            .find_property(context, multiname)?
+            .ok_or_else(|| property_not_found(multiname))?;
+        let name = object
+            .resolve_multiname(multiname)
+            .ok_or_else(|| property_not_found(multiname))?;
+
+        object.get_property(object, &name, self, context)
+    }
+
+    fn op_get_lex(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname = Multiname::from_abc_multiname_static(translation_unit, index.clone())?;
+        let value = self.get_lexical(context, &multiname)?;
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    /// The prototype of the superclass, which `super` properties are looked
+    /// up on.
+    fn super_prototype(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Object<'gc>, Error> {
+        let superclass = self
+            .superclass
+            .ok_or("Attempted to use super outside of a subclass")?;
+
+        superclass
+            .get_property(superclass, &QName::dynamic_name("prototype"), self, context)?
+            .as_object()
+    }
+
+    fn op_get_super(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+        let super_proto = self.super_prototype(context)?;
+
+        let value = match super_proto.resolve_multiname(&multiname) {
+            Some(name) => super_proto.get_property(object, &name, self, context)?,
+            None => Value::Undefined,
+        };
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_set_super(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let object = self.pop_object()?;
+        let super_proto = self.super_prototype(context)?;
+
+        let name = resolve_for_set(super_proto, &multiname)?;
+        super_proto.set_property(object, &name, value, self, context)?;
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_in(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let object = self.pop_object()?;
+        let name = self.pop().coerce_to_string(self, context)?;
+
+        self.push(object.resolve_any(&name).is_some());
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_call(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let receiver = match self.pop() {
+            Value::Object(receiver) => Some(receiver),
+            _ => None,
+        };
+        let function = self.pop_object()?;
+
+        let value = function.call(receiver, &args, self, context)?;
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_call_property(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+        num_args: u32,
+        pass_receiver: bool,
+        push_result: bool,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let receiver = self.pop_object()?;
+
+        let name = receiver
+            .resolve_multiname(&multiname)
+            .ok_or_else(|| property_not_found(&multiname))?;
+        let function = receiver
+            .get_property(receiver, &name, self, context)?
+            .as_object()?;
+        let value = function.call(
+            if pass_receiver { Some(receiver) } else { None },
+            &args,
+            self,
+            context,
+        )?;
+
+        if push_result {
+            self.push(value);
+        }
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_call_super(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+        num_args: u32,
+        push_result: bool,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let receiver = self.pop_object()?;
+        let super_proto = self.super_prototype(context)?;
+
+        let name = super_proto
+            .resolve_multiname(&multiname)
+            .ok_or_else(|| property_not_found(&multiname))?;
+        let function = super_proto
+            .get_property(receiver, &name, self, context)?
+            .as_object()?;
+        let value = function.call(Some(receiver), &args, self, context)?;
+
+        if push_result {
+            self.push(value);
+        }
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_construct(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let constructor = self.pop_object()?;
+
+        let object = constructor.construct(self, context, &args)?;
+        self.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_construct_prop(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let multiname =
+            Multiname::from_abc_multiname(translation_unit, index.clone(), self, context)?;
+        let source = self.pop_object()?;
+
+        let name = source
+            .resolve_multiname(&multiname)
+            .ok_or_else(|| property_not_found(&multiname))?;
+        let constructor = source
+            .get_property(source, &name, self, context)?
+            .as_object()?;
+
+        let object = constructor.construct(self, context, &args)?;
+        self.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_construct_super(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let receiver = self.pop_object()?;
+
+        // Classes without a superclass implicitly extend `Object`, whose
+        // constructor does nothing.
+        if let Some(superclass) = self.superclass {
+            superclass.call(Some(receiver), &args, self, context)?;
+        }
+
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_new_object(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let mut properties = Vec::with_capacity(num_args as usize);
+        for _ in 0..num_args {
+            let value = self.pop();
+            let name = self.pop().coerce_to_string(self, context)?;
+            properties.push((name, value));
+        }
+
+        let object = ScriptObject::object(context.gc_context, context.avm2.prototypes().object);
+        for (name, value) in properties.into_iter().rev() {
+            object.set_property(object, &QName::dynamic_name(&name), value, self, context)?;
+        }
+
+        self.push(object);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_new_array(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        num_args: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let args = self.pop_args(num_args);
+        let array_proto = context.avm2.prototypes().array;
+
+        self.push(ScriptObject::array(context.gc_context, array_proto, &args));
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_new_function(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let method = translation_unit.load_method(index)?;
+        let function = FunctionObject::from_method(
+            context.gc_context,
+            method,
+            self.scope,
+            context.avm2.prototypes().function,
+            None,
+        );
+
+        self.push(function);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_new_class(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let superclass = match self.pop() {
+            Value::Object(superclass) => Some(superclass),
+            Value::Null | Value::Undefined => None,
+            value => return Err(format!("Cannot extend {:?}", value).into()),
+        };
+
+        let class = new_class(
+            self,
+            context,
+            translation_unit,
+            index,
+            superclass,
+            self.scope,
+        )?;
+        self.push(class);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_add(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop().coerce_to_primitive(self, context)?;
+        let value1 = self.pop().coerce_to_primitive(self, context)?;
+
+        match (&value1, &value2) {
+            (Value::String(_), _) | (_, Value::String(_)) => {
+                let mut string = value1.coerce_to_string(self, context)?;
+                string.push_str(&value2.coerce_to_string(self, context)?);
+                self.push(string);
+            }
+            _ => {
+                let sum = value1.coerce_to_number(self, context)?
+                    + value2.coerce_to_number(self, context)?;
+                self.push(sum);
+            }
+        }
+
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_binary_number(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        operation: fn(f64, f64) -> f64,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop().coerce_to_number(self, context)?;
+        let value1 = self.pop().coerce_to_number(self, context)?;
+
+        self.push(operation(value1, value2));
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_binary_int(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        operation: fn(i32, i32) -> i32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop().coerce_to_i32(self, context)?;
+        let value1 = self.pop().coerce_to_i32(self, context)?;
+
+        self.push(operation(value1, value2));
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_unary_number(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        operation: fn(f64) -> f64,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_number(self, context)?;
+
+        self.push(operation(value));
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_unary_int(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        operation: fn(i32) -> i32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_i32(self, context)?;
+
+        self.push(operation(value));
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_lshift(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let shift = self.pop().coerce_to_u32(self, context)? & 0x1F;
+        let value = self.pop().coerce_to_i32(self, context)?;
+
+        self.push(value << shift);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_rshift(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let shift = self.pop().coerce_to_u32(self, context)? & 0x1F;
+        let value = self.pop().coerce_to_i32(self, context)?;
+
+        self.push(value >> shift);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_urshift(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let shift = self.pop().coerce_to_u32(self, context)? & 0x1F;
+        let value = self.pop().coerce_to_u32(self, context)?;
+
+        self.push(value >> shift);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_not(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_boolean();
+
+        self.push(!value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_equals(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop();
+        let value1 = self.pop();
+
+        let result = value1.abstract_eq(&value2, self, context)?;
+        self.push(result);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_strict_equals(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop();
+        let value1 = self.pop();
+
+        self.push(value1.strict_eq(&value2));
+        Ok(FrameControl::Continue)
+    }
+
+    /// Pop two values and compare them.
+    fn pop_compare(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        comparison: Comparison,
+    ) -> Result<bool, Error> {
+        let value2 = self.pop();
+        let value1 = self.pop();
+
+        // Every comparison is false if either value is `NaN`.
+        Ok(match comparison {
+            Comparison::LessThan => value1.abstract_lt(&value2, self, context)? == Some(true),
+            Comparison::LessEquals => value2.abstract_lt(&value1, self, context)? == Some(false),
+            Comparison::GreaterThan => value2.abstract_lt(&value1, self, context)? == Some(true),
+            Comparison::GreaterEquals => value1.abstract_lt(&value2, self, context)? == Some(false),
+        })
+    }
+
+    fn op_compare(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        comparison: Comparison,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let result = self.pop_compare(context, comparison)?;
+
+        self.push(result);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_if_true(&mut self, offset: i32, expected: bool) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_boolean();
+
+        Ok(branch_if(value == expected, offset))
+    }
+
+    fn op_if_eq(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        offset: i32,
+        expected: bool,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop();
+        let value1 = self.pop();
+
+        let result = value1.abstract_eq(&value2, self, context)?;
+        Ok(branch_if(result == expected, offset))
+    }
+
+    fn op_if_strict_eq(&mut self, offset: i32, expected: bool) -> Result<FrameControl<'gc>, Error> {
+        let value2 = self.pop();
+        let value1 = self.pop();
+
+        Ok(branch_if(value1.strict_eq(&value2) == expected, offset))
+    }
+
+    fn op_if_compare(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        offset: i32,
+        comparison: Comparison,
+        expected: bool,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let result = self.pop_compare(context, comparison)?;
+
+        Ok(branch_if(result == expected, offset))
+    }
+
+    fn op_lookup_switch(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        default_offset: i32,
+        case_offsets: &[i32],
+    ) -> Result<FrameControl<'gc>, Error> {
+        let index = self.pop().coerce_to_i32(self, context)?;
+
+        let offset = usize::try_from(index)
+            .ok()
+            .and_then(|index| case_offsets.get(index))
+            .copied()
+            .unwrap_or(default_offset);
+        Ok(FrameControl::Switch(offset))
+    }
+
+    fn op_convert_b(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_boolean();
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_convert_d(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_number(self, context)?;
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_convert_i(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_i32(self, context)?;
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_convert_u(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_u32(self, context)?;
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_convert_s(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_string(self, context)?;
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_convert_o(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+        if let Value::Undefined | Value::Null = value {
+            return Err(format!("Cannot convert {:?} to an object", value).into());
+        }
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_coerce_s(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = match self.pop() {
+            Value::Undefined | Value::Null => Value::Null,
+            value => value.coerce_to_string(self, context)?.into(),
+        };
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_coerce(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+
+        // TODO: Values aren't checked against classes yet, only converted to
+        // primitive types.
+        let type_name = QName::from_abc_multiname(translation_unit, index.clone()).ok();
+        let value = match type_name {
+            Some(type_name) if type_name.namespace().is_public() => match type_name.local_name() {
+                "Boolean" => value.coerce_to_boolean().into(),
+                "Number" => value.coerce_to_number(self, context)?.into(),
+                "int" => value.coerce_to_i32(self, context)?.into(),
+                "uint" => value.coerce_to_u32(self, context)?.into(),
+                "String" => match value {
+                    Value::Undefined | Value::Null => Value::Null,
+                    value => value.coerce_to_string(self, context)?.into(),
+                },
+                _ => value,
+            },
+            _ => value,
+        };
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+
+    /// Check if a value is an instance of a class.
+    ///
+    /// TODO: Primitive values are never considered instances of a class.
+    fn is_instance_of(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        value: &Value<'gc>,
+        class: Object<'gc>,
+    ) -> Result<bool, Error> {
+        let object = match value {
+            Value::Object(object) => *object,
+            _ => return Ok(false),
+        };
+
+        let prototype = class
+            .get_property(class, &QName::dynamic_name("prototype"), self, context)?
+            .as_object()?;
+        Ok(prototype.is_prototype_of(object))
+    }
+
+    fn op_as_type(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname = Multiname::from_abc_multiname_static(translation_unit, index.clone())?;
+        let class = self.get_lexical(context, &multiname)?.as_object()?;
+        let value = self.pop();
+
+        if self.is_instance_of(context, &value, class)? {
+            self.push(value);
+        } else {
+            self.push(Value::Null);
+        }
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_as_type_late(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let class = self.pop_object()?;
+        let value = self.pop();
+
+        if self.is_instance_of(context, &value, class)? {
+            self.push(value);
+        } else {
+            self.push(Value::Null);
+        }
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_is_type(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        translation_unit: &TranslationUnit,
+        index: &Index<AbcMultiname>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let multiname = Multiname::from_abc_multiname_static(translation_unit, index.clone())?;
+        let class = self.get_lexical(context, &multiname)?.as_object()?;
+        let value = self.pop();
+
+        let result = self.is_instance_of(context, &value, class)?;
+        self.push(result);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_is_type_late(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let class = self.pop_object()?;
+        let value = self.pop();
+
+        let result = self.is_instance_of(context, &value, class)?;
+        self.push(result);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_instance_of(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let class = self.pop_object()?;
+        let value = self.pop();
+
+        let result = self.is_instance_of(context, &value, class)?;
+        self.push(result);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_type_of(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+
+        self.push(value.type_of());
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_return_value(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop();
+
+        Ok(FrameControl::Return(value))
+    }
+
+    fn op_throw(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let value = self.pop().coerce_to_string(self, context)?;
+
+        // TODO: Exceptions can't be caught yet, so they abort execution.
+        Err(format!("Uncaught exception: {}", value).into())
+    }
+
+    fn op_has_next(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let index = self.pop().coerce_to_u32(self, context)?;
+        let object = self.pop();
+
+        let next_index = match object {
+            Value::Object(object) if object.get_enumerant_name(index + 1).is_some() => index + 1,
+            _ => 0,
+        };
+
+        self.push(next_index);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_has_next_2(
+        &mut self,
+        object_register: u32,
+        index_register: u32,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let mut object = match self.register(object_register)? {
+            Value::Object(object) => Some(object),
+            _ => None,
+        };
+        let mut index = match self.register(index_register)? {
+            Value::Number(index) => index as u32,
+            _ => 0,
+        };
+
+        // Once an object runs out of properties, enumeration continues with
+        // its prototype.
+        while let Some(this_object) = object {
+            if this_object.get_enumerant_name(index + 1).is_some() {
+                index += 1;
+                break;
+            }
+
+            object = this_object.proto();
+            index = 0;
+        }
+
+        self.set_register(
+            object_register,
+            object.map(Value::Object).unwrap_or(Value::Null),
+        )?;
+        self.set_register(index_register, index.into())?;
+        self.push(object.is_some());
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_next_name(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let index = self.pop().coerce_to_u32(self, context)?;
+        let object = self.pop_object()?;
+
+        let name = object
+            .get_enumerant_name(index)
+            .map(|name| Value::from(name.local_name()))
+            .unwrap_or(Value::Undefined);
+
+        self.push(name);
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_next_value(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let index = self.pop().coerce_to_u32(self, context)?;
+        let object = self.pop_object()?;
+
+        let value = match object.get_enumerant_name(index) {
+            Some(name) => object.get_property(object, &name, self, context)?,
+            None => Value::Undefined,
+        };
+
+        self.push(value);
+        Ok(FrameControl::Continue)
+    }
+}
+
+/// The relational comparisons done by comparison opcodes.
+#[derive(Clone, Copy)]
+enum Comparison {
+    LessThan,
+    LessEquals,
+    GreaterThan,
+    GreaterEquals,
+}
+
+fn branch_if<'gc>(condition: bool, offset: i32) -> FrameControl<'gc> {
+    if condition {
+        FrameControl::Branch(offset)
+    } else {
+        FrameControl::Continue
+    }
+}
+
+fn property_not_found(multiname: &Multiname) -> Error {
+    format!(
+        "Property {} not found",
+        multiname.local_name().unwrap_or("*")
+    )
+    .into()
+}
+
+/// Resolve the name of a property to set, which is created in the public
+/// namespace if it doesn't exist yet.
+fn resolve_for_set(object: Object<'_>, multiname: &Multiname) -> Result<QName, Error> {
+    if let Some(name) = object.resolve_multiname(multiname) {
+        return Ok(name);
+    }
+
+    let local_name = multiname
+        .local_name()
+        .ok_or("Cannot set a property without a name")?;
+    let ns = multiname
+        .namespace_set()
+        .find(|ns| ns.is_public())
+        .or_else(|| multiname.namespace_set().find(|ns| !ns.is_any()))
+        .cloned()
+        .unwrap_or_else(Namespace::public_namespace);
+
+    Ok(QName::new(ns, local_name))
+}
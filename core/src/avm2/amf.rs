@@ -0,0 +1,207 @@
+//! AMF serialization of AVM2 values
+
+use crate::amf::{self, Graph, ObjectId};
+use crate::avm2::activation::Activation;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use std::collections::HashMap;
+
+/// Serialize a value and the objects it references into `graph`.
+///
+/// Functions and display objects can't be serialized; they are left out of
+/// objects and arrays, and are serialized as `undefined` on their own.
+pub fn serialize<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    value: &Value<'gc>,
+    graph: &mut Graph,
+) -> Result<amf::Value, Error> {
+    Ok(Serializer::default()
+        .serialize(activation, context, value, graph)?
+        .unwrap_or(amf::Value::Undefined))
+}
+
+/// Tracks the objects that were already serialized, so that each of them is
+/// only serialized once.
+#[derive(Default)]
+struct Serializer {
+    objects: HashMap<*const ObjectPtr, ObjectId>,
+}
+
+impl Serializer {
+    fn serialize<'gc>(
+        &mut self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        value: &Value<'gc>,
+        graph: &mut Graph,
+    ) -> Result<Option<amf::Value>, Error> {
+        Ok(Some(match value {
+            Value::Undefined | Value::Namespace(_) => amf::Value::Undefined,
+            Value::Null => amf::Value::Null,
+            Value::Bool(b) => amf::Value::Bool(*b),
+            Value::Number(n) => amf::Value::Number(*n),
+            Value::String(s) => amf::Value::String(s.clone()),
+            Value::Object(object) => {
+                match self.serialize_object(activation, context, *object, graph)? {
+                    Some(id) => amf::Value::Object(id),
+                    None => return Ok(None),
+                }
+            }
+        }))
+    }
+
+    fn serialize_object<'gc>(
+        &mut self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        graph: &mut Graph,
+    ) -> Result<Option<ObjectId>, Error> {
+        if let Some(id) = self.objects.get(&object.as_ptr()) {
+            return Ok(Some(*id));
+        }
+        if object.as_executable().is_some() || object.as_display_object().is_some() {
+            return Ok(None);
+        }
+
+        let is_array = context.avm2.prototypes().array.is_prototype_of(object);
+        let id = graph.add(amf::Object::anonymous());
+        self.objects.insert(object.as_ptr(), id);
+
+        let length = if is_array {
+            object
+                .get_property(object, &QName::dynamic_name("length"), activation, context)?
+                .coerce_to_u32(activation, context)? as usize
+        } else {
+            0
+        };
+        let mut dense = Vec::with_capacity(length);
+        for i in 0..length {
+            let element = object.get_property(
+                object,
+                &QName::dynamic_name(&i.to_string()),
+                activation,
+                context,
+            )?;
+            dense.push(
+                self.serialize(activation, context, &element, graph)?
+                    .unwrap_or(amf::Value::Undefined),
+            );
+        }
+
+        let mut properties = vec![];
+        let mut index = 0;
+        while let Some(name) = object.get_enumerant_name(index) {
+            index += 1;
+            let key = name.local_name().to_string();
+            if key.parse::<usize>().map(|i| i < length).unwrap_or(false) {
+                continue;
+            }
+
+            let value = object.get_property(object, &name, activation, context)?;
+            if let Some(value) = self.serialize(activation, context, &value, graph)? {
+                properties.push((key, value));
+            }
+        }
+
+        *graph.get_mut(id) = if is_array {
+            amf::Object::Array {
+                dense,
+                associative: properties,
+            }
+        } else {
+            amf::Object::Object {
+                class_name: None,
+                properties,
+            }
+        };
+
+        Ok(Some(id))
+    }
+}
+
+/// Deserialize a value, creating the objects it references.
+///
+/// TODO: Objects of registered classes are deserialized as plain objects, and
+/// byte arrays are deserialized as `undefined`.
+pub fn deserialize<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    graph: &Graph,
+    value: &amf::Value,
+) -> Result<Value<'gc>, Error> {
+    deserialize_value(activation, context, graph, value, &mut HashMap::new())
+}
+
+fn deserialize_value<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    graph: &Graph,
+    value: &amf::Value,
+    objects: &mut HashMap<ObjectId, Object<'gc>>,
+) -> Result<Value<'gc>, Error> {
+    Ok(match value {
+        amf::Value::Undefined => Value::Undefined,
+        amf::Value::Null => Value::Null,
+        amf::Value::Bool(b) => Value::Bool(*b),
+        amf::Value::Number(n) | amf::Value::Date(n) => Value::Number(*n),
+        amf::Value::Integer(i) => Value::Number(f64::from(*i)),
+        amf::Value::String(s) | amf::Value::Xml(s) => Value::String(s.clone()),
+        amf::Value::Object(id) => {
+            if let Some(object) = objects.get(id) {
+                return Ok((*object).into());
+            }
+
+            let (object, properties) = match graph.get(*id) {
+                amf::Object::Object { properties, .. } => {
+                    let proto = context.avm2.prototypes().object;
+                    let object = ScriptObject::object(context.gc_context, proto);
+                    objects.insert(*id, object);
+                    (object, properties)
+                }
+                amf::Object::Array { dense, associative } => {
+                    let proto = context.avm2.prototypes().array;
+                    let array = ScriptObject::array(context.gc_context, proto, &[]);
+                    objects.insert(*id, array);
+
+                    for (i, value) in dense.iter().enumerate() {
+                        let value = deserialize_value(activation, context, graph, value, objects)?;
+                        array.set_property(
+                            array,
+                            &QName::dynamic_name(&i.to_string()),
+                            value,
+                            activation,
+                            context,
+                        )?;
+                    }
+                    array.set_property(
+                        array,
+                        &QName::dynamic_name("length"),
+                        dense.len().into(),
+                        activation,
+                        context,
+                    )?;
+                    (array, associative)
+                }
+                amf::Object::ByteArray(_) => return Ok(Value::Undefined),
+            };
+
+            for (name, value) in properties {
+                let value = deserialize_value(activation, context, graph, value, objects)?;
+                object.set_property(
+                    object,
+                    &QName::dynamic_name(name),
+                    value,
+                    activation,
+                    context,
+                )?;
+            }
+            object.into()
+        }
+    })
+}
@@ -0,0 +1,267 @@
+//! AVM2 classes and traits
+
+use crate::avm2::activation::Activation;
+use crate::avm2::function::FunctionObject;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::scope::Scope;
+use crate::avm2::script::TranslationUnit;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::{Collect, GcCell};
+use swf::avm2::types::{
+    DefaultValue as AbcDefaultValue, Index, Multiname as AbcMultiname, Trait as AbcTrait,
+    TraitKind as AbcTraitKind,
+};
+
+/// The data of a class defined in an ABC file.
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+pub struct Class<'gc> {
+    /// The name of the class.
+    name: QName,
+
+    /// The class this class extends, if any.
+    superclass: Option<Object<'gc>>,
+
+    /// The ABC file the class was defined in.
+    translation_unit: TranslationUnit,
+
+    /// The index of the instance and class information of the class in its
+    /// ABC file.
+    class_index: u32,
+}
+
+impl<'gc> Class<'gc> {
+    pub fn name(&self) -> &QName {
+        &self.name
+    }
+
+    pub fn superclass(&self) -> Option<Object<'gc>> {
+        self.superclass
+    }
+}
+
+/// Create a class from its definition in an ABC file, as done by the
+/// `newclass` opcode.
+///
+/// Instance methods are installed on the prototype, class traits are
+/// installed on the class itself, and then the class initializer is run.
+pub fn new_class<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    translation_unit: &TranslationUnit,
+    class_index: u32,
+    superclass: Option<Object<'gc>>,
+    scope: Option<GcCell<'gc, Scope<'gc>>>,
+) -> Result<Object<'gc>, Error> {
+    let abc = translation_unit.abc();
+    let abc_instance = abc
+        .instances
+        .get(class_index as usize)
+        .ok_or_else(|| format!("Unknown class {}", class_index))?;
+    let abc_class = abc
+        .classes
+        .get(class_index as usize)
+        .ok_or_else(|| format!("Unknown class {}", class_index))?;
+
+    let base_proto = match superclass {
+        Some(superclass) => superclass
+            .get_property(
+                superclass,
+                &QName::dynamic_name("prototype"),
+                activation,
+                context,
+            )?
+            .as_object()?,
+        None => context.avm2.prototypes().object,
+    };
+    let prototype = base_proto.derive(context.gc_context);
+
+    // TODO: Interfaces and the protected namespace of the class are ignored.
+    let class = Class {
+        name: QName::from_abc_multiname(translation_unit, abc_instance.name.clone())?,
+        superclass,
+        translation_unit: translation_unit.clone(),
+        class_index,
+    };
+    let initializer = translation_unit.load_method(abc_instance.init_method.0)?;
+    let (class_object, class_scope) = FunctionObject::from_class(
+        context.gc_context,
+        class,
+        initializer,
+        scope,
+        prototype,
+        context.avm2.prototypes().function,
+    )?;
+
+    // Slots are installed on each instance when it is constructed, and
+    // everything else is shared through the prototype.
+    for abc_trait in &abc_instance.traits {
+        if !is_slot_trait(abc_trait) {
+            install_trait(
+                prototype,
+                translation_unit,
+                abc_trait,
+                Some(class_scope),
+                superclass,
+                context,
+            )?;
+        }
+    }
+
+    for abc_trait in &abc_class.traits {
+        install_trait(
+            class_object,
+            translation_unit,
+            abc_trait,
+            Some(class_scope),
+            None,
+            context,
+        )?;
+    }
+
+    let class_initializer = translation_unit.load_method(abc_class.init_method.0)?;
+    let mut class_init_activation = Activation::from_method(
+        context,
+        class_initializer,
+        Some(class_scope),
+        Some(class_object),
+        &[],
+        None,
+    )?;
+    class_init_activation.run_actions(context)?;
+
+    Ok(class_object)
+}
+
+/// Install the slots declared by a class and its superclasses on a new
+/// instance of it.
+pub fn install_instance_slots<'gc>(
+    instance: Object<'gc>,
+    class_object: Object<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Result<(), Error> {
+    let class = match class_object.as_class() {
+        Some(class) => class.read().clone(),
+        None => return Ok(()),
+    };
+
+    if let Some(superclass) = class.superclass {
+        install_instance_slots(instance, superclass, context)?;
+    }
+
+    let abc_instance = &class.translation_unit.abc().instances[class.class_index as usize];
+    for abc_trait in &abc_instance.traits {
+        if is_slot_trait(abc_trait) {
+            install_trait(
+                instance,
+                &class.translation_unit,
+                abc_trait,
+                None,
+                None,
+                context,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_slot_trait(abc_trait: &AbcTrait) -> bool {
+    matches!(
+        abc_trait.kind,
+        AbcTraitKind::Slot { .. } | AbcTraitKind::Const { .. }
+    )
+}
+
+/// Install a trait from an ABC file on an object.
+///
+/// Methods close over `scope`, and use `superclass` for `super` operations.
+pub fn install_trait<'gc>(
+    object: Object<'gc>,
+    translation_unit: &TranslationUnit,
+    abc_trait: &AbcTrait,
+    scope: Option<GcCell<'gc, Scope<'gc>>>,
+    superclass: Option<Object<'gc>>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Result<(), Error> {
+    let mc = context.gc_context;
+    let fn_proto = context.avm2.prototypes().function;
+    let name = QName::from_abc_multiname(translation_unit, abc_trait.name.clone())?;
+
+    match &abc_trait.kind {
+        AbcTraitKind::Slot {
+            slot_id,
+            type_name,
+            value,
+        } => {
+            let value = slot_default_value(translation_unit, type_name, value)?;
+            object.install_slot(mc, name, *slot_id, value);
+        }
+        AbcTraitKind::Const {
+            slot_id,
+            type_name,
+            value,
+        } => {
+            let value = slot_default_value(translation_unit, type_name, value)?;
+            object.install_const(mc, name, *slot_id, value);
+        }
+        AbcTraitKind::Method { method, .. } => {
+            let method = translation_unit.load_method(method.0)?;
+            let function = FunctionObject::from_method(mc, method, scope, fn_proto, superclass);
+            object.install_method(mc, name, function);
+        }
+        AbcTraitKind::Getter { method, .. } => {
+            let method = translation_unit.load_method(method.0)?;
+            let function = FunctionObject::from_method(mc, method, scope, fn_proto, superclass);
+            object.install_getter(mc, name, function)?;
+        }
+        AbcTraitKind::Setter { method, .. } => {
+            let method = translation_unit.load_method(method.0)?;
+            let function = FunctionObject::from_method(mc, method, scope, fn_proto, superclass);
+            object.install_setter(mc, name, function)?;
+        }
+        AbcTraitKind::Class { slot_id, .. } => {
+            // The class itself is created by the `newclass` opcode in the
+            // script initializer, which stores it in this slot.
+            object.install_slot(mc, name, *slot_id, Value::Undefined);
+        }
+        AbcTraitKind::Function { slot_id, function } => {
+            let method = translation_unit.load_method(function.0)?;
+            let function = FunctionObject::from_method(mc, method, scope, fn_proto, None);
+            object.install_const(mc, name, *slot_id, function.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The initial value of a slot, which is its declared default value, or
+/// otherwise depends on its type.
+fn slot_default_value<'gc>(
+    translation_unit: &TranslationUnit,
+    type_name: &Index<AbcMultiname>,
+    value: &Option<AbcDefaultValue>,
+) -> Result<Value<'gc>, Error> {
+    if let Some(value) = value {
+        return Value::from_abc_default_value(translation_unit, value);
+    }
+
+    if type_name.0 == 0 {
+        return Ok(Value::Undefined);
+    }
+
+    let type_name = match QName::from_abc_multiname(translation_unit, type_name.clone()) {
+        Ok(type_name) if type_name.namespace().is_public() => type_name,
+        _ => return Ok(Value::Null),
+    };
+
+    Ok(match type_name.local_name() {
+        "Number" => f64::NAN.into(),
+        "int" | "uint" => Value::Number(0.0),
+        "Boolean" => false.into(),
+        _ => Value::Null,
+    })
+}
@@ -0,0 +1,467 @@
+//! AVM2 executables.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::{install_instance_slots, Class};
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::scope::Scope;
+use crate::avm2::script::BytecodeMethod;
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::{Collect, CollectionContext, GcCell, MutationContext};
+use std::fmt;
+
+/// Represents a function defined in Ruffle's code.
+///
+/// Parameters are as follows:
+///
+///  * The current activation
+///  * The action context
+///  * The current `this` object, if any
+///  * The arguments this function was called with
+pub type NativeMethod<'gc> = fn(
+    &mut Activation<'gc>,
+    &mut UpdateContext<'_, 'gc, '_>,
+    Option<Object<'gc>>,
+    &[Value<'gc>],
+) -> Result<Value<'gc>, Error>;
+
+/// Represents code that can be executed by some means.
+#[derive(Clone)]
+pub enum Executable<'gc> {
+    /// Code defined in Ruffle's binary, along with the receiver it was bound
+    /// to, if any.
+    Native(NativeMethod<'gc>, Option<Object<'gc>>),
+
+    /// Code defined in a loaded ABC file.
+    Action {
+        /// The method to run.
+        method: BytecodeMethod,
+
+        /// The scope the method was defined in.
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+
+        /// The receiver the method was bound to, if any.
+        receiver: Option<Object<'gc>>,
+
+        /// The superclass of the class the method was defined on, used by
+        /// `super` operations.
+        superclass: Option<Object<'gc>>,
+    },
+}
+
+unsafe impl<'gc> Collect for Executable<'gc> {
+    fn trace(&self, cc: CollectionContext) {
+        match self {
+            Self::Native(_, receiver) => receiver.trace(cc),
+            Self::Action {
+                scope,
+                receiver,
+                superclass,
+                ..
+            } => {
+                scope.trace(cc);
+                receiver.trace(cc);
+                superclass.trace(cc);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Executable<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Native(nf, receiver) => fmt
+                .debug_tuple("Executable::Native")
+                .field(&format!("{:p}", nf))
+                .field(receiver)
+                .finish(),
+            Self::Action {
+                method,
+                scope,
+                receiver,
+                superclass,
+            } => fmt
+                .debug_struct("Executable::Action")
+                .field("method", method)
+                .field("scope", scope)
+                .field("receiver", receiver)
+                .field("superclass", superclass)
+                .finish(),
+        }
+    }
+}
+
+impl<'gc> Executable<'gc> {
+    /// Bind the executable to a receiver, unless it already has one.
+    pub fn bind(&self, new_receiver: Object<'gc>) -> Self {
+        match self {
+            Self::Native(nf, receiver) => Self::Native(*nf, receiver.or(Some(new_receiver))),
+            Self::Action {
+                method,
+                scope,
+                receiver,
+                superclass,
+            } => Self::Action {
+                method: method.clone(),
+                scope: *scope,
+                receiver: receiver.or(Some(new_receiver)),
+                superclass: *superclass,
+            },
+        }
+    }
+
+    /// Execute the code, using `unbound_receiver` as `this` unless the
+    /// executable has been bound.
+    pub fn exec(
+        &self,
+        unbound_receiver: Option<Object<'gc>>,
+        arguments: &[Value<'gc>],
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        match self {
+            Self::Native(nf, receiver) => nf(
+                activation,
+                context,
+                receiver.or(unbound_receiver),
+                arguments,
+            ),
+            Self::Action {
+                method,
+                scope,
+                receiver,
+                superclass,
+            } => {
+                let mut activation = Activation::from_method(
+                    context,
+                    method.clone(),
+                    *scope,
+                    receiver.or(unbound_receiver),
+                    arguments,
+                    *superclass,
+                )?;
+                activation.run_actions(context)
+            }
+        }
+    }
+}
+
+/// An Object which can be called to execute its function code.
+#[derive(Collect, Debug, Clone, Copy)]
+#[collect(no_drop)]
+pub struct FunctionObject<'gc> {
+    /// The underlying script object.
+    ///
+    /// This is used to hold properties such as `prototype`.
+    base: ScriptObject<'gc>,
+
+    data: GcCell<'gc, FunctionObjectData<'gc>>,
+}
+
+#[derive(Collect, Debug, Clone)]
+#[collect(no_drop)]
+struct FunctionObjectData<'gc> {
+    /// Executable code.
+    exec: Option<Executable<'gc>>,
+
+    /// The class this function constructs, if it is a class constructor.
+    class: Option<GcCell<'gc, Class<'gc>>>,
+}
+
+impl<'gc> FunctionObject<'gc> {
+    fn allocate(
+        mc: MutationContext<'gc, '_>,
+        exec: Option<Executable<'gc>>,
+        class: Option<GcCell<'gc, Class<'gc>>>,
+        fn_proto: Object<'gc>,
+    ) -> Self {
+        FunctionObject {
+            base: ScriptObject::base(mc, Some(fn_proto)),
+            data: GcCell::allocate(mc, FunctionObjectData { exec, class }),
+        }
+    }
+
+    /// Construct a function from an executable.
+    pub fn from_executable(
+        mc: MutationContext<'gc, '_>,
+        exec: Executable<'gc>,
+        fn_proto: Object<'gc>,
+    ) -> Object<'gc> {
+        Self::allocate(mc, Some(exec), None, fn_proto).into()
+    }
+
+    /// Construct a function from an ABC method and the scope it closes over.
+    pub fn from_method(
+        mc: MutationContext<'gc, '_>,
+        method: BytecodeMethod,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+        fn_proto: Object<'gc>,
+        superclass: Option<Object<'gc>>,
+    ) -> Object<'gc> {
+        let exec = Executable::Action {
+            method,
+            scope,
+            receiver: None,
+            superclass,
+        };
+
+        Self::from_executable(mc, exec, fn_proto)
+    }
+
+    /// Construct a builtin function from a native method.
+    pub fn from_builtin(
+        mc: MutationContext<'gc, '_>,
+        nf: NativeMethod<'gc>,
+        fn_proto: Object<'gc>,
+    ) -> Object<'gc> {
+        Self::from_executable(mc, Executable::Native(nf, None), fn_proto)
+    }
+
+    /// Construct a class constructor, linking it to its prototype.
+    ///
+    /// Returns the class scope along with the constructor, which contains the
+    /// class itself and should be used for the methods of the class.
+    pub fn from_class(
+        mc: MutationContext<'gc, '_>,
+        class: Class<'gc>,
+        initializer: BytecodeMethod,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+        prototype: Object<'gc>,
+        fn_proto: Object<'gc>,
+    ) -> Result<(Object<'gc>, GcCell<'gc, Scope<'gc>>), Error> {
+        let superclass = class.superclass();
+        let function = Self::allocate(mc, None, Some(GcCell::allocate(mc, class)), fn_proto);
+        let constructor: Object<'gc> = function.into();
+
+        // The initializer can only close over the class scope once the
+        // class exists.
+        let class_scope = Scope::push_scope(scope, constructor, mc);
+        function.data.write(mc).exec = Some(Executable::Action {
+            method: initializer,
+            scope: Some(class_scope),
+            receiver: None,
+            superclass,
+        });
+
+        link_prototype(mc, constructor, prototype)?;
+
+        Ok((constructor, class_scope))
+    }
+
+    /// Construct a builtin class constructor from a native method.
+    pub fn from_builtin_constructor(
+        mc: MutationContext<'gc, '_>,
+        constructor: NativeMethod<'gc>,
+        prototype: Object<'gc>,
+        fn_proto: Object<'gc>,
+    ) -> Result<Object<'gc>, Error> {
+        let constructor: Object<'gc> = Self::allocate(
+            mc,
+            Some(Executable::Native(constructor, None)),
+            None,
+            fn_proto,
+        )
+        .into();
+
+        link_prototype(mc, constructor, prototype)?;
+
+        Ok(constructor)
+    }
+}
+
+/// Set the `prototype` of a constructor and the `constructor` of its
+/// prototype.
+fn link_prototype<'gc>(
+    mc: MutationContext<'gc, '_>,
+    constructor: Object<'gc>,
+    prototype: Object<'gc>,
+) -> Result<(), Error> {
+    constructor.install_dynamic_property(mc, QName::dynamic_name("prototype"), prototype.into())?;
+    prototype.install_dynamic_property(mc, QName::dynamic_name("constructor"), constructor.into())
+}
+
+/// Bind a method to the object it was retrieved from.
+///
+/// Objects that aren't functions are returned as-is.
+pub fn bind_method<'gc>(
+    function: Object<'gc>,
+    receiver: Object<'gc>,
+    mc: MutationContext<'gc, '_>,
+) -> Object<'gc> {
+    match (function.as_executable(), function.proto()) {
+        (Some(exec), Some(fn_proto)) => {
+            FunctionObject::from_executable(mc, exec.bind(receiver), fn_proto)
+        }
+        _ => function,
+    }
+}
+
+impl<'gc> TObject<'gc> for FunctionObject<'gc> {
+    fn get_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        self.base
+            .get_property_local(receiver, name, activation, context)
+    }
+
+    fn set_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base
+            .set_property_local(receiver, name, value, activation, context)
+    }
+
+    fn init_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base
+            .init_property_local(receiver, name, value, activation, context)
+    }
+
+    fn get_slot(&self, id: u32) -> Result<Value<'gc>, Error> {
+        self.base.get_slot(id)
+    }
+
+    fn set_slot(
+        &self,
+        id: u32,
+        value: Value<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base.set_slot(id, value, mc)
+    }
+
+    fn resolve_any(&self, local_name: &str) -> Option<QName> {
+        self.base.resolve_any(local_name)
+    }
+
+    fn has_own_property(&self, name: &QName) -> bool {
+        self.base.has_own_property(name)
+    }
+
+    fn has_own_virtual_setter(&self, name: &QName) -> bool {
+        self.base.has_own_virtual_setter(name)
+    }
+
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName) -> bool {
+        self.base.delete_property(gc_context, name)
+    }
+
+    fn proto(&self) -> Option<Object<'gc>> {
+        self.base.proto()
+    }
+
+    fn get_enumerant_name(&self, index: u32) -> Option<QName> {
+        self.base.get_enumerant_name(index)
+    }
+
+    fn install_method(&self, mc: MutationContext<'gc, '_>, name: QName, function: Object<'gc>) {
+        self.base.install_method(mc, name, function)
+    }
+
+    fn install_getter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_getter(mc, name, function)
+    }
+
+    fn install_setter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_setter(mc, name, function)
+    }
+
+    fn install_dynamic_property(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        value: Value<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_dynamic_property(mc, name, value)
+    }
+
+    fn install_slot(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.base.install_slot(mc, name, id, value)
+    }
+
+    fn install_const(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.base.install_const(mc, name, id, value)
+    }
+
+    fn call(
+        &self,
+        receiver: Option<Object<'gc>>,
+        arguments: &[Value<'gc>],
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let exec = self.data.read().exec.clone();
+        match exec {
+            Some(exec) => exec.exec(receiver, arguments, activation, context),
+            None => Err("Function has no code to execute".into()),
+        }
+    }
+
+    fn construct(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        arguments: &[Value<'gc>],
+    ) -> Result<Object<'gc>, Error> {
+        let this: Object<'gc> = (*self).into();
+        let prototype = match this.get_property(
+            this,
+            &QName::dynamic_name("prototype"),
+            activation,
+            context,
+        )? {
+            Value::Object(prototype) => prototype,
+            _ => context.avm2.prototypes().object,
+        };
+
+        let instance = prototype.derive(context.gc_context);
+        install_instance_slots(instance, this, context)?;
+        self.call(Some(instance), arguments, activation, context)?;
+
+        Ok(instance)
+    }
+
+    fn as_executable(&self) -> Option<Executable<'gc>> {
+        self.data.read().exec.clone()
+    }
+
+    fn as_class(&self) -> Option<GcCell<'gc, Class<'gc>>> {
+        self.data.read().class
+    }
+
+    fn to_string(&self) -> Result<Value<'gc>, Error> {
+        Ok("function Function() {}".into())
+    }
+
+    fn as_ptr(&self) -> *const ObjectPtr {
+        self.base.as_ptr() as *const ObjectPtr
+    }
+}
@@ -0,0 +1,417 @@
+//! Global scope built-ins
+
+use crate::avm2::activation::Activation;
+use crate::avm2::function::{FunctionObject, NativeMethod};
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::stage_object::StageObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::{Collect, MutationContext};
+
+pub mod flash;
+mod function;
+mod object;
+
+/// This structure represents all system builtins' prototypes.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+pub struct SystemPrototypes<'gc> {
+    pub object: Object<'gc>,
+    pub function: Object<'gc>,
+    pub array: Object<'gc>,
+    pub event_dispatcher: Object<'gc>,
+    pub event: Object<'gc>,
+    pub display_object: Object<'gc>,
+    pub interactive_object: Object<'gc>,
+    pub display_object_container: Object<'gc>,
+    pub sprite: Object<'gc>,
+    pub movie_clip: Object<'gc>,
+    pub stage: Object<'gc>,
+}
+
+/// Implements `trace`
+fn trace<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let mut message = Vec::with_capacity(args.len());
+    for arg in args {
+        message.push(arg.coerce_to_string(activation, context)?);
+    }
+
+    context.log.avm_trace(&message.join(" "));
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Array`
+fn array<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let this = match this {
+        Some(this) => this,
+        None => {
+            let array_proto = context.avm2.prototypes().array;
+            return Ok(ScriptObject::array(context.gc_context, array_proto, args).into());
+        }
+    };
+
+    // A single numeric argument is the length of the array.
+    let length = match args {
+        [Value::Number(length)] => *length as usize,
+        _ => {
+            for (i, arg) in args.iter().enumerate() {
+                this.set_property(
+                    this,
+                    &QName::dynamic_name(&i.to_string()),
+                    arg.clone(),
+                    activation,
+                    context,
+                )?;
+            }
+            args.len()
+        }
+    };
+
+    this.install_dynamic_property(
+        context.gc_context,
+        QName::dynamic_name("length"),
+        length.into(),
+    )?;
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Boolean`
+fn boolean<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(args
+        .get(0)
+        .map(|arg| arg.coerce_to_boolean())
+        .unwrap_or(false)
+        .into())
+}
+
+/// Implements `Number`
+fn number<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    match args.get(0) {
+        Some(arg) => Ok(arg.coerce_to_number(activation, context)?.into()),
+        None => Ok(Value::Number(0.0)),
+    }
+}
+
+/// Implements `int`
+fn int<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    match args.get(0) {
+        Some(arg) => Ok(arg.coerce_to_i32(activation, context)?.into()),
+        None => Ok(Value::Number(0.0)),
+    }
+}
+
+/// Implements `uint`
+fn uint<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    match args.get(0) {
+        Some(arg) => Ok(arg.coerce_to_u32(activation, context)?.into()),
+        None => Ok(Value::Number(0.0)),
+    }
+}
+
+/// Implements `String`
+fn string<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    match args.get(0) {
+        Some(arg) => Ok(arg.coerce_to_string(activation, context)?.into()),
+        None => Ok("".into()),
+    }
+}
+
+/// Add a builtin function to the global scope.
+fn function<'gc>(
+    mc: MutationContext<'gc, '_>,
+    globals: Object<'gc>,
+    name: &str,
+    nf: NativeMethod<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    globals.install_dynamic_property(
+        mc,
+        QName::dynamic_name(name),
+        FunctionObject::from_builtin(mc, nf, fn_proto).into(),
+    )
+}
+
+/// Add a builtin class to the global scope, in the given package.
+fn class<'gc>(
+    mc: MutationContext<'gc, '_>,
+    globals: Object<'gc>,
+    package: &str,
+    name: &str,
+    constructor: NativeMethod<'gc>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<Object<'gc>, Error> {
+    let class = FunctionObject::from_builtin_constructor(mc, constructor, proto, fn_proto)?;
+    globals.install_dynamic_property(
+        mc,
+        QName::new(Namespace::package(package), name),
+        class.into(),
+    )?;
+
+    Ok(class)
+}
+
+/// Add a builtin method to a prototype.
+pub fn method<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    name: &str,
+    nf: NativeMethod<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    proto.install_dynamic_property(
+        mc,
+        QName::dynamic_name(name),
+        FunctionObject::from_builtin(mc, nf, fn_proto).into(),
+    )
+}
+
+/// Add a builtin property with a getter, and a setter unless it is
+/// read-only, to a prototype.
+pub fn accessor<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    name: &str,
+    getter: NativeMethod<'gc>,
+    setter: Option<NativeMethod<'gc>>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    proto.install_getter(
+        mc,
+        QName::dynamic_name(name),
+        FunctionObject::from_builtin(mc, getter, fn_proto),
+    )?;
+
+    if let Some(setter) = setter {
+        proto.install_setter(
+            mc,
+            QName::dynamic_name(name),
+            FunctionObject::from_builtin(mc, setter, fn_proto),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Construct a new global scope.
+///
+/// This function returns both the global scope object, as well as all builtin
+/// prototypes that other parts of the VM will need to use.
+pub fn construct_global_scope<'gc>(
+    mc: MutationContext<'gc, '_>,
+) -> Result<(Object<'gc>, SystemPrototypes<'gc>), Error> {
+    let object_proto = ScriptObject::bare_object(mc);
+    let fn_proto = ScriptObject::object(mc, object_proto);
+    let array_proto = ScriptObject::object(mc, object_proto);
+
+    object::fill_proto(mc, object_proto, fn_proto)?;
+    function::fill_proto(mc, fn_proto)?;
+
+    let globals = ScriptObject::object(mc, object_proto);
+
+    class(
+        mc,
+        globals,
+        "",
+        "Object",
+        object::constructor,
+        object_proto,
+        fn_proto,
+    )?;
+    class(
+        mc,
+        globals,
+        "",
+        "Function",
+        function::constructor,
+        fn_proto,
+        fn_proto,
+    )?;
+    class(mc, globals, "", "Array", array, array_proto, fn_proto)?;
+
+    // TODO: The primitive types are only usable as conversion functions.
+    function(mc, globals, "Boolean", boolean, fn_proto)?;
+    function(mc, globals, "Number", number, fn_proto)?;
+    function(mc, globals, "int", int, fn_proto)?;
+    function(mc, globals, "uint", uint, fn_proto)?;
+    function(mc, globals, "String", string, fn_proto)?;
+
+    function(mc, globals, "trace", trace, fn_proto)?;
+
+    // package `flash.events`
+    let event_dispatcher_proto =
+        flash::events::eventdispatcher::create_proto(mc, object_proto, fn_proto)?;
+    class(
+        mc,
+        globals,
+        "flash.events",
+        "EventDispatcher",
+        flash::events::eventdispatcher::constructor,
+        event_dispatcher_proto,
+        fn_proto,
+    )?;
+
+    let event_proto = flash::events::event::create_proto(mc, object_proto, fn_proto)?;
+    let event = class(
+        mc,
+        globals,
+        "flash.events",
+        "Event",
+        flash::events::event::constructor,
+        event_proto,
+        fn_proto,
+    )?;
+    flash::events::event::install_constants(mc, event);
+
+    // package `flash.net`
+    let local_connection_proto =
+        flash::net::localconnection::create_proto(mc, event_dispatcher_proto, fn_proto)?;
+    class(
+        mc,
+        globals,
+        "flash.net",
+        "LocalConnection",
+        flash::net::localconnection::constructor,
+        local_connection_proto,
+        fn_proto,
+    )?;
+
+    // package `flash.display`
+    // Display object prototypes are stage objects, so that their instances
+    // are too.
+    let display_object_proto: Object<'gc> =
+        StageObject::bare(mc, Some(event_dispatcher_proto)).into();
+    flash::display::displayobject::fill_proto(mc, display_object_proto, fn_proto)?;
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "DisplayObject",
+        flash::display::displayobject::constructor,
+        display_object_proto,
+        fn_proto,
+    )?;
+
+    let interactive_object_proto = display_object_proto.derive(mc);
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "InteractiveObject",
+        flash::display::interactiveobject::constructor,
+        interactive_object_proto,
+        fn_proto,
+    )?;
+
+    let display_object_container_proto = interactive_object_proto.derive(mc);
+    flash::display::displayobjectcontainer::fill_proto(
+        mc,
+        display_object_container_proto,
+        fn_proto,
+    )?;
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "DisplayObjectContainer",
+        flash::display::displayobjectcontainer::constructor,
+        display_object_container_proto,
+        fn_proto,
+    )?;
+
+    let sprite_proto = display_object_container_proto.derive(mc);
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "Sprite",
+        flash::display::sprite::constructor,
+        sprite_proto,
+        fn_proto,
+    )?;
+
+    let movie_clip_proto = sprite_proto.derive(mc);
+    flash::display::movieclip::fill_proto(mc, movie_clip_proto, fn_proto)?;
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "MovieClip",
+        flash::display::movieclip::constructor,
+        movie_clip_proto,
+        fn_proto,
+    )?;
+
+    let stage_proto = display_object_container_proto.derive(mc);
+    flash::display::stage::fill_proto(mc, stage_proto, fn_proto)?;
+    class(
+        mc,
+        globals,
+        "flash.display",
+        "Stage",
+        flash::display::stage::constructor,
+        stage_proto,
+        fn_proto,
+    )?;
+
+    globals.install_dynamic_property(mc, QName::dynamic_name("NaN"), f64::NAN.into())?;
+    globals.install_dynamic_property(mc, QName::dynamic_name("Infinity"), f64::INFINITY.into())?;
+    globals.install_dynamic_property(mc, QName::dynamic_name("undefined"), Value::Undefined)?;
+
+    Ok((
+        globals,
+        SystemPrototypes {
+            object: object_proto,
+            function: fn_proto,
+            array: array_proto,
+            event_dispatcher: event_dispatcher_proto,
+            event: event_proto,
+            display_object: display_object_proto,
+            interactive_object: interactive_object_proto,
+            display_object_container: display_object_container_proto,
+            sprite: sprite_proto,
+            movie_clip: movie_clip_proto,
+            stage: stage_proto,
+        },
+    ))
+}
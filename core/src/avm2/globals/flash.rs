@@ -0,0 +1,5 @@
+//! `flash` namespace
+
+pub mod display;
+pub mod events;
+pub mod net;
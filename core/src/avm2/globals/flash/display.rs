@@ -0,0 +1,8 @@
+//! `flash.display` namespace
+
+pub mod displayobject;
+pub mod displayobjectcontainer;
+pub mod interactiveobject;
+pub mod movieclip;
+pub mod sprite;
+pub mod stage;
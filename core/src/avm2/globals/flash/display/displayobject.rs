@@ -0,0 +1,245 @@
+//! `flash.display.DisplayObject` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::accessor;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::display_object::{DisplayObject, TDisplayObject};
+use gc_arena::MutationContext;
+
+/// Implements `flash.display.DisplayObject`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Generates a getter and setter pair for a numeric display object property.
+macro_rules! number_property {
+    ($getter:ident, $setter:ident, |$dobj:ident, $mc:ident| $get:expr, |$sdobj:ident, $smc:ident, $value:ident| $set:expr) => {
+        fn $getter<'gc>(
+            _activation: &mut Activation<'gc>,
+            context: &mut UpdateContext<'_, 'gc, '_>,
+            this: Option<Object<'gc>>,
+            _args: &[Value<'gc>],
+        ) -> Result<Value<'gc>, Error> {
+            if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+                #[allow(unused_mut)]
+                let mut $dobj = dobj;
+                let $mc = context.gc_context;
+                return Ok($get.into());
+            }
+
+            Ok(Value::Undefined)
+        }
+
+        fn $setter<'gc>(
+            activation: &mut Activation<'gc>,
+            context: &mut UpdateContext<'_, 'gc, '_>,
+            this: Option<Object<'gc>>,
+            args: &[Value<'gc>],
+        ) -> Result<Value<'gc>, Error> {
+            if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+                #[allow(unused_mut)]
+                let mut $sdobj = dobj;
+                let $value = args
+                    .get(0)
+                    .unwrap_or(&Value::Undefined)
+                    .coerce_to_number(activation, context)?;
+                let $smc = context.gc_context;
+                $set;
+                $sdobj.set_transformed_by_script($smc, true);
+            }
+
+            Ok(Value::Undefined)
+        }
+    };
+}
+
+number_property!(x, set_x, |dobj, _mc| dobj.x(), |dobj, mc, value| dobj
+    .set_x(mc, value));
+number_property!(y, set_y, |dobj, _mc| dobj.y(), |dobj, mc, value| dobj
+    .set_y(mc, value));
+number_property!(
+    rotation,
+    set_rotation,
+    |dobj, mc| dobj.rotation(mc).to_degrees(),
+    |dobj, mc, value| dobj.set_rotation(mc, value.to_radians())
+);
+number_property!(
+    scale_x,
+    set_scale_x,
+    |dobj, mc| dobj.scale_x(mc),
+    |dobj, mc, value| dobj.set_scale_x(mc, value)
+);
+number_property!(
+    scale_y,
+    set_scale_y,
+    |dobj, mc| dobj.scale_y(mc),
+    |dobj, mc, value| dobj.set_scale_y(mc, value)
+);
+number_property!(
+    alpha,
+    set_alpha,
+    |dobj, _mc| dobj.alpha(),
+    |dobj, mc, value| dobj.set_alpha(mc, value)
+);
+number_property!(
+    width,
+    set_width,
+    |dobj, _mc| dobj.width(),
+    |dobj, mc, value| dobj.set_width(mc, value)
+);
+number_property!(
+    height,
+    set_height,
+    |dobj, _mc| dobj.height(),
+    |dobj, mc, value| dobj.set_height(mc, value)
+);
+
+/// Implements `DisplayObject.visible`'s getter
+fn visible<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        return Ok(dobj.visible().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObject.visible`'s setter
+fn set_visible<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut dobj) = this.and_then(|this| this.as_display_object()) {
+        let visible = args.get(0).unwrap_or(&false.into()).coerce_to_boolean();
+        dobj.set_visible(context.gc_context, visible);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObject.name`'s getter
+fn name<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        return Ok(dobj.name().to_string().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObject.name`'s setter
+fn set_name<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut dobj) = this.and_then(|this| this.as_display_object()) {
+        let name = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation, context)?;
+        dobj.set_name(context.gc_context, &name);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObject.parent`'s getter
+fn parent<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(this
+        .and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.parent())
+        .map(|parent| parent.object2())
+        .unwrap_or(Value::Null))
+}
+
+/// Implements `DisplayObject.root`'s getter
+fn root<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(this
+        .and_then(|this| this.as_display_object())
+        .map(|dobj| dobj.root().object2())
+        .unwrap_or(Value::Null))
+}
+
+/// Implements `DisplayObject.stage`'s getter
+fn stage<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    // Objects are on the stage when their root is one of the levels.
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let root = dobj.root();
+        if context
+            .levels
+            .values()
+            .any(|level| DisplayObject::ptr_eq(*level, root))
+        {
+            return Ok(context.avm2.stage(context.gc_context).into());
+        }
+    }
+
+    Ok(Value::Null)
+}
+
+/// Fill `DisplayObject.prototype` with its properties.
+///
+/// The prototype is a stage object, so it is allocated by the caller.
+pub fn fill_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    accessor(mc, proto, "x", x, Some(set_x), fn_proto)?;
+    accessor(mc, proto, "y", y, Some(set_y), fn_proto)?;
+    accessor(
+        mc,
+        proto,
+        "rotation",
+        rotation,
+        Some(set_rotation),
+        fn_proto,
+    )?;
+    accessor(mc, proto, "scaleX", scale_x, Some(set_scale_x), fn_proto)?;
+    accessor(mc, proto, "scaleY", scale_y, Some(set_scale_y), fn_proto)?;
+    accessor(mc, proto, "alpha", alpha, Some(set_alpha), fn_proto)?;
+    accessor(mc, proto, "width", width, Some(set_width), fn_proto)?;
+    accessor(mc, proto, "height", height, Some(set_height), fn_proto)?;
+    accessor(mc, proto, "visible", visible, Some(set_visible), fn_proto)?;
+    accessor(mc, proto, "name", name, Some(set_name), fn_proto)?;
+    accessor(mc, proto, "parent", parent, None, fn_proto)?;
+    accessor(mc, proto, "root", root, None, fn_proto)?;
+    accessor(mc, proto, "stage", stage, None, fn_proto)?;
+
+    Ok(())
+}
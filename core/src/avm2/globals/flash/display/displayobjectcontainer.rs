@@ -0,0 +1,184 @@
+//! `flash.display.DisplayObjectContainer` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::{accessor, method};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
+use gc_arena::MutationContext;
+
+/// Implements `flash.display.DisplayObjectContainer`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Get the container that an AVM2 object represents.
+fn container<'gc>(this: Option<Object<'gc>>) -> Option<MovieClip<'gc>> {
+    this.and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_movie_clip())
+}
+
+/// Get the display object that a display object argument represents.
+fn child_arg<'gc>(args: &[Value<'gc>]) -> Result<DisplayObject<'gc>, Error> {
+    args.get(0)
+        .unwrap_or(&Value::Undefined)
+        .as_object()?
+        .as_display_object()
+        .ok_or_else(|| "Argument must be a DisplayObject".into())
+}
+
+/// Implements `DisplayObjectContainer.numChildren`'s getter
+fn num_children<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(container) = container(this) {
+        return Ok(container.children_by_depth().len().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObjectContainer.getChildAt`
+fn get_child_at<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(container) = container(this) {
+        let index = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation, context)?;
+        let children = container.children_by_depth();
+
+        return match children.get(index as usize) {
+            Some(child) if index >= 0 => Ok(child.object2()),
+            _ => Err(format!("RangeError: Child index {} is out of range", index).into()),
+        };
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObjectContainer.getChildByName`
+fn get_child_by_name<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(container) = container(this) {
+        let name = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation, context)?;
+
+        return Ok(container
+            .get_child_by_name(&name, true)
+            .map(|child| child.object2())
+            .unwrap_or(Value::Null));
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObjectContainer.addChild`
+fn add_child<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut container) = container(this) {
+        let child = child_arg(args)?;
+
+        if let Some(mut old_parent) = child.parent().and_then(|p| p.as_movie_clip()) {
+            old_parent.remove_child_from_avm(context, child);
+        }
+
+        let depth = container.highest_depth().map(|d| d + 1).unwrap_or(0);
+        container.add_child_from_avm(context, child, depth);
+
+        return Ok(child.object2());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObjectContainer.removeChild`
+fn remove_child<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut container) = container(this) {
+        let mut child = child_arg(args)?;
+
+        match child.parent() {
+            Some(parent) if DisplayObject::ptr_eq(parent, container.into()) => {}
+            _ => return Err("ArgumentError: The child is not a child of the caller".into()),
+        }
+
+        container.remove_child_from_avm(context, child);
+        child.set_parent(context.gc_context, None);
+
+        return Ok(child.object2());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `DisplayObjectContainer.contains`
+fn contains<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(container) = container(this) {
+        let container: DisplayObject<'gc> = container.into();
+        let mut ancestor = Some(child_arg(args)?);
+
+        while let Some(node) = ancestor {
+            if DisplayObject::ptr_eq(node, container) {
+                return Ok(true.into());
+            }
+
+            ancestor = node.parent();
+        }
+
+        return Ok(false.into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Fill `DisplayObjectContainer.prototype` with its methods.
+///
+/// The prototype is a stage object, so it is allocated by the caller.
+pub fn fill_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    accessor(mc, proto, "numChildren", num_children, None, fn_proto)?;
+    method(mc, proto, "getChildAt", get_child_at, fn_proto)?;
+    method(mc, proto, "getChildByName", get_child_by_name, fn_proto)?;
+    method(mc, proto, "addChild", add_child, fn_proto)?;
+    method(mc, proto, "removeChild", remove_child, fn_proto)?;
+    method(mc, proto, "contains", contains, fn_proto)?;
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! `flash.display.InteractiveObject` builtin
+
+use crate::avm2::activation::Activation;
+use crate::avm2::object::Object;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+
+/// Implements `flash.display.InteractiveObject`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
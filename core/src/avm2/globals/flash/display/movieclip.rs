@@ -0,0 +1,243 @@
+//! `flash.display.MovieClip` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::flash::display::sprite;
+use crate::avm2::globals::{accessor, method};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::{ActionType, UpdateContext};
+use crate::display_object::{MovieClip, TDisplayObject};
+use gc_arena::MutationContext;
+
+/// Implements `flash.display.MovieClip`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    sprite::constructor(activation, context, this, args)
+}
+
+/// Get the movie clip that an AVM2 object represents.
+fn movie_clip<'gc>(this: Option<Object<'gc>>) -> Option<MovieClip<'gc>> {
+    this.and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_movie_clip())
+}
+
+/// Queue a goto on a clip.
+///
+/// Gotos run once the current script is done, as they can run AVM1 code on
+/// the target timeline.
+fn queue_goto<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    clip: MovieClip<'gc>,
+    frame: u16,
+    stop: bool,
+) {
+    context
+        .action_queue
+        .queue_actions(clip.into(), ActionType::GotoAvm2 { frame, stop }, false);
+}
+
+/// Implements `MovieClip.play`
+fn play<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        clip.play(context);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `MovieClip.stop`
+fn stop<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        clip.stop(context);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Shared implementation of `gotoAndPlay` and `gotoAndStop`.
+fn goto_frame<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+    stop: bool,
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        let frame = match args.get(0).unwrap_or(&Value::Undefined) {
+            Value::String(label) => clip.frame_label_to_number(label),
+            value => {
+                let frame = value.coerce_to_number(activation, context)?;
+                if frame.is_finite() && frame >= 1.0 {
+                    Some(frame.min(f64::from(u16::MAX)) as u16)
+                } else {
+                    None
+                }
+            }
+        };
+
+        match frame {
+            Some(frame) => queue_goto(context, clip, frame, stop),
+            None => {
+                return Err(format!(
+                    "ArgumentError: Frame {:?} not found",
+                    args.get(0).unwrap_or(&Value::Undefined)
+                )
+                .into())
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `MovieClip.gotoAndPlay`
+fn goto_and_play<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    goto_frame(activation, context, this, args, false)
+}
+
+/// Implements `MovieClip.gotoAndStop`
+fn goto_and_stop<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    goto_frame(activation, context, this, args, true)
+}
+
+/// Implements `MovieClip.nextFrame`
+fn next_frame<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        if clip.current_frame() < clip.total_frames() {
+            queue_goto(context, clip, clip.current_frame() + 1, true);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `MovieClip.prevFrame`
+fn prev_frame<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        if clip.current_frame() > 1 {
+            queue_goto(context, clip, clip.current_frame() - 1, true);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `MovieClip.currentFrame`'s getter
+fn current_frame<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(movie_clip(this)
+        .map(|clip| u32::from(clip.current_frame()).into())
+        .unwrap_or(Value::Undefined))
+}
+
+/// Implements `MovieClip.totalFrames`'s getter
+fn total_frames<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(movie_clip(this)
+        .map(|clip| u32::from(clip.total_frames()).into())
+        .unwrap_or(Value::Undefined))
+}
+
+/// Implements `MovieClip.framesLoaded`'s getter
+fn frames_loaded<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(movie_clip(this)
+        .map(|clip| u32::from(clip.frames_loaded()).into())
+        .unwrap_or(Value::Undefined))
+}
+
+/// Implements `MovieClip.addFrameScript`
+///
+/// This takes any number of pairs of 0-based frame numbers and functions.
+/// A null function removes the frame script of that frame.
+fn add_frame_script<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(clip) = movie_clip(this) {
+        for pair in args.chunks_exact(2) {
+            let frame = pair[0].coerce_to_u32(activation, context)?;
+            let callable = match &pair[1] {
+                Value::Object(callable) => Some(*callable),
+                _ => None,
+            };
+
+            if frame < u32::from(u16::MAX) {
+                clip.register_frame_script(frame as u16 + 1, callable, context);
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Fill `MovieClip.prototype` with its methods.
+///
+/// The prototype is a stage object, so it is allocated by the caller.
+pub fn fill_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    method(mc, proto, "play", play, fn_proto)?;
+    method(mc, proto, "stop", stop, fn_proto)?;
+    method(mc, proto, "gotoAndPlay", goto_and_play, fn_proto)?;
+    method(mc, proto, "gotoAndStop", goto_and_stop, fn_proto)?;
+    method(mc, proto, "nextFrame", next_frame, fn_proto)?;
+    method(mc, proto, "prevFrame", prev_frame, fn_proto)?;
+    method(mc, proto, "addFrameScript", add_frame_script, fn_proto)?;
+    accessor(mc, proto, "currentFrame", current_frame, None, fn_proto)?;
+    accessor(mc, proto, "totalFrames", total_frames, None, fn_proto)?;
+    accessor(mc, proto, "framesLoaded", frames_loaded, None, fn_proto)?;
+
+    Ok(())
+}
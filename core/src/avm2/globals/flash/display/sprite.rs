@@ -0,0 +1,33 @@
+//! `flash.display.Sprite` builtin
+
+use crate::avm2::activation::Activation;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::display_object::{MovieClip, TDisplayObject};
+use crate::tag_utils::SwfSlice;
+
+/// Implements `flash.display.Sprite`
+///
+/// Objects constructed from AS3 don't have a display object yet, so an empty
+/// clip is created for them. Timeline objects are bound to theirs before the
+/// constructor runs.
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if this.as_display_object().is_none() {
+            let mut new_clip =
+                MovieClip::new(SwfSlice::empty(context.swf.clone()), context.gc_context);
+
+            new_clip.set_object2(context.gc_context, this);
+            this.init_display_object(context.gc_context, new_clip.into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
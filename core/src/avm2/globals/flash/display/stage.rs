@@ -0,0 +1,86 @@
+//! `flash.display.Stage` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::accessor;
+use crate::avm2::object::Object;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::stage::StageQuality;
+use gc_arena::MutationContext;
+
+/// Implements `flash.display.Stage`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Err("The Stage class cannot be constructed".into())
+}
+
+/// Implements `Stage.stageWidth`'s getter
+fn stage_width<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(context.stage_size.0.to_pixels().into())
+}
+
+/// Implements `Stage.stageHeight`'s getter
+fn stage_height<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(context.stage_size.1.to_pixels().into())
+}
+
+/// Implements `Stage.quality`'s getter
+///
+/// Flash Player returns quality names in uppercase, even though the
+/// `StageQuality` constants are lowercase.
+fn quality<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(context.stage_quality.to_string().into())
+}
+
+/// Implements `Stage.quality`'s setter
+fn set_quality<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    let quality: StageQuality = name.parse().map_err(|_| {
+        "ArgumentError: Error #2008: Parameter quality must be one of the accepted values."
+    })?;
+    *context.stage_quality = quality;
+    context.renderer.set_quality(quality);
+
+    Ok(Value::Undefined)
+}
+
+/// Fill `Stage.prototype` with its properties.
+pub fn fill_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    accessor(mc, proto, "stageWidth", stage_width, None, fn_proto)?;
+    accessor(mc, proto, "stageHeight", stage_height, None, fn_proto)?;
+    accessor(mc, proto, "quality", quality, Some(set_quality), fn_proto)?;
+
+    Ok(())
+}
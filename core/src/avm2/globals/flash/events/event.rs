@@ -0,0 +1,191 @@
+//! `flash.events.Event` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::method;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::MutationContext;
+
+/// The event types that are constants of the `Event` class.
+const EVENT_TYPES: &[(&str, &str)] = &[
+    ("ACTIVATE", "activate"),
+    ("ADDED", "added"),
+    ("ADDED_TO_STAGE", "addedToStage"),
+    ("COMPLETE", "complete"),
+    ("DEACTIVATE", "deactivate"),
+    ("ENTER_FRAME", "enterFrame"),
+    ("EXIT_FRAME", "exitFrame"),
+    ("FRAME_CONSTRUCTED", "frameConstructed"),
+    ("INIT", "init"),
+    ("REMOVED", "removed"),
+    ("REMOVED_FROM_STAGE", "removedFromStage"),
+    ("RENDER", "render"),
+    ("RESIZE", "resize"),
+];
+
+/// The name of the hidden property that records whether an event was
+/// cancelled.
+fn default_prevented_name() -> QName {
+    QName::new(
+        Namespace::Private("flash.events:Event".to_string()),
+        "defaultPrevented",
+    )
+}
+
+/// Implements `flash.events.Event`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event_type = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation, context)?;
+        let bubbles = args.get(1).unwrap_or(&false.into()).coerce_to_boolean();
+        let cancelable = args.get(2).unwrap_or(&false.into()).coerce_to_boolean();
+        let mc = context.gc_context;
+
+        this.install_dynamic_property(mc, QName::dynamic_name("type"), event_type.into())?;
+        this.install_dynamic_property(mc, QName::dynamic_name("bubbles"), bubbles.into())?;
+        this.install_dynamic_property(mc, QName::dynamic_name("cancelable"), cancelable.into())?;
+        this.install_dynamic_property(mc, QName::dynamic_name("target"), Value::Null)?;
+        this.install_dynamic_property(mc, QName::dynamic_name("currentTarget"), Value::Null)?;
+        this.install_dynamic_property(mc, default_prevented_name(), false.into())?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Event.prototype.toString`
+fn to_string<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event_type = this
+            .get_property(this, &QName::dynamic_name("type"), activation, context)?
+            .coerce_to_string(activation, context)?;
+        let bubbles = this
+            .get_property(this, &QName::dynamic_name("bubbles"), activation, context)?
+            .coerce_to_boolean();
+        let cancelable = this
+            .get_property(
+                this,
+                &QName::dynamic_name("cancelable"),
+                activation,
+                context,
+            )?
+            .coerce_to_boolean();
+
+        return Ok(format!(
+            "[Event type=\"{}\" bubbles={} cancelable={}]",
+            event_type, bubbles, cancelable
+        )
+        .into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Event.prototype.preventDefault`
+fn prevent_default<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let cancelable = this
+            .get_property(
+                this,
+                &QName::dynamic_name("cancelable"),
+                activation,
+                context,
+            )?
+            .coerce_to_boolean();
+        if cancelable {
+            this.install_dynamic_property(
+                context.gc_context,
+                default_prevented_name(),
+                true.into(),
+            )?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Event.prototype.isDefaultPrevented`
+fn is_default_prevented<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        return Ok(is_cancelled(activation, context, this)?.into());
+    }
+
+    Ok(false.into())
+}
+
+/// Whether a listener has cancelled an event.
+pub fn is_cancelled<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    event: Object<'gc>,
+) -> Result<bool, Error> {
+    Ok(event
+        .get_property(event, &default_prevented_name(), activation, context)?
+        .coerce_to_boolean())
+}
+
+/// Construct `Event.prototype`.
+pub fn create_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    super_proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<Object<'gc>, Error> {
+    let proto = ScriptObject::object(mc, super_proto);
+
+    method(mc, proto, "toString", to_string, fn_proto)?;
+    method(mc, proto, "preventDefault", prevent_default, fn_proto)?;
+    method(
+        mc,
+        proto,
+        "isDefaultPrevented",
+        is_default_prevented,
+        fn_proto,
+    )?;
+
+    Ok(proto)
+}
+
+/// Install the event type constants on the `Event` class.
+pub fn install_constants<'gc>(mc: MutationContext<'gc, '_>, class: Object<'gc>) {
+    for (name, event_type) in EVENT_TYPES {
+        class.install_const(mc, QName::dynamic_name(name), 0, (*event_type).into());
+    }
+}
+
+/// Create a new event of the given type, which neither bubbles nor can be
+/// cancelled.
+pub fn create_event<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    event_type: &str,
+) -> Result<Object<'gc>, Error> {
+    let event = ScriptObject::object(context.gc_context, context.avm2.prototypes().event);
+    constructor(activation, context, Some(event), &[event_type.into()])?;
+
+    Ok(event)
+}
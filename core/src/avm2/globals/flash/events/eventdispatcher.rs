@@ -0,0 +1,224 @@
+//! `flash.events.EventDispatcher` builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::flash::events::event;
+use crate::avm2::globals::method;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::MutationContext;
+
+/// The name of the hidden property holding the listeners for an event type.
+fn listeners_name(event_type: &str) -> QName {
+    QName::new(
+        Namespace::Private("flash.events:EventDispatcher".to_string()),
+        event_type,
+    )
+}
+
+/// Get the listeners registered on an object for an event type, in the order
+/// they were added.
+fn listeners<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    event_type: &str,
+) -> Result<Vec<Object<'gc>>, Error> {
+    let name = listeners_name(event_type);
+    if !this.has_own_property(&name) {
+        return Ok(vec![]);
+    }
+
+    let list = this
+        .get_property_local(this, &name, activation, context)?
+        .as_object()?;
+    let length = list
+        .get_property(list, &QName::dynamic_name("length"), activation, context)?
+        .coerce_to_u32(activation, context)?;
+
+    let mut listeners = Vec::with_capacity(length as usize);
+    for i in 0..length {
+        let listener = list.get_property(
+            list,
+            &QName::dynamic_name(&i.to_string()),
+            activation,
+            context,
+        )?;
+        if let Value::Object(listener) = listener {
+            listeners.push(listener);
+        }
+    }
+
+    Ok(listeners)
+}
+
+/// Replace the listeners registered on an object for an event type.
+fn set_listeners<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Object<'gc>,
+    event_type: &str,
+    listeners: &[Object<'gc>],
+) -> Result<(), Error> {
+    let values: Vec<Value<'gc>> = listeners.iter().map(|l| (*l).into()).collect();
+    let list = ScriptObject::array(context.gc_context, context.avm2.prototypes().array, &values);
+
+    this.install_dynamic_property(context.gc_context, listeners_name(event_type), list.into())
+}
+
+/// Coerce the event type argument of a listener method.
+fn event_type<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<String, Error> {
+    args.get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)
+}
+
+/// Implements `flash.events.EventDispatcher`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `EventDispatcher.prototype.addEventListener`
+fn add_event_listener<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event_type = event_type(activation, context, args)?;
+        let listener = args.get(1).unwrap_or(&Value::Undefined).as_object()?;
+
+        let mut listeners = listeners(activation, context, this, &event_type)?;
+        if !listeners.iter().any(|l| Object::ptr_eq(*l, listener)) {
+            listeners.push(listener);
+            set_listeners(context, this, &event_type, &listeners)?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `EventDispatcher.prototype.removeEventListener`
+fn remove_event_listener<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event_type = event_type(activation, context, args)?;
+        let listener = args.get(1).unwrap_or(&Value::Undefined).as_object()?;
+
+        let mut listeners = listeners(activation, context, this, &event_type)?;
+        listeners.retain(|l| !Object::ptr_eq(*l, listener));
+        set_listeners(context, this, &event_type, &listeners)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `EventDispatcher.prototype.hasEventListener`
+fn has_event_listener<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event_type = event_type(activation, context, args)?;
+
+        return Ok((!listeners(activation, context, this, &event_type)?.is_empty()).into());
+    }
+
+    Ok(false.into())
+}
+
+/// Implements `EventDispatcher.prototype.dispatchEvent`
+fn dispatch_event_method<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let event = args.get(0).unwrap_or(&Value::Undefined).as_object()?;
+
+        return Ok(dispatch_event(activation, context, this, event)?.into());
+    }
+
+    Ok(false.into())
+}
+
+/// Dispatch an event to the listeners of its type on a target.
+///
+/// Returns `false` if a listener cancelled the event.
+///
+/// TODO: Events don't bubble through the display list yet; only the
+/// listeners on the target itself are called.
+pub fn dispatch_event<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    target: Object<'gc>,
+    event: Object<'gc>,
+) -> Result<bool, Error> {
+    let event_type = event
+        .get_property(event, &QName::dynamic_name("type"), activation, context)?
+        .coerce_to_string(activation, context)?;
+
+    if let Value::Null =
+        event.get_property(event, &QName::dynamic_name("target"), activation, context)?
+    {
+        event.install_dynamic_property(
+            context.gc_context,
+            QName::dynamic_name("target"),
+            target.into(),
+        )?;
+    }
+    event.install_dynamic_property(
+        context.gc_context,
+        QName::dynamic_name("currentTarget"),
+        target.into(),
+    )?;
+
+    for listener in listeners(activation, context, target, &event_type)? {
+        listener.call(Some(target), &[event.into()], activation, context)?;
+    }
+
+    Ok(!event::is_cancelled(activation, context, event)?)
+}
+
+/// Construct `EventDispatcher.prototype`.
+pub fn create_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    super_proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<Object<'gc>, Error> {
+    let proto = ScriptObject::object(mc, super_proto);
+
+    method(mc, proto, "addEventListener", add_event_listener, fn_proto)?;
+    method(
+        mc,
+        proto,
+        "removeEventListener",
+        remove_event_listener,
+        fn_proto,
+    )?;
+    method(mc, proto, "hasEventListener", has_event_listener, fn_proto)?;
+    // Without bubbling, only the target's own listeners can be triggered.
+    method(mc, proto, "willTrigger", has_event_listener, fn_proto)?;
+    method(mc, proto, "dispatchEvent", dispatch_event_method, fn_proto)?;
+
+    Ok(proto)
+}
@@ -0,0 +1,3 @@
+//! `flash.net` namespace
+
+pub mod localconnection;
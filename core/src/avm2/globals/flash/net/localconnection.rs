@@ -0,0 +1,150 @@
+//! `flash.net.LocalConnection` builtin and prototype
+
+use crate::amf::Graph;
+use crate::avm2::activation::Activation;
+use crate::avm2::amf;
+use crate::avm2::globals::{accessor, method};
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::local_connection::LocalConnectionReceiver;
+use gc_arena::MutationContext;
+
+/// Implements `flash.net.LocalConnection`
+///
+/// Messages are delivered to the `client` of the connection, which is the
+/// connection itself unless it is replaced.
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        this.install_dynamic_property(
+            context.gc_context,
+            QName::dynamic_name("client"),
+            this.into(),
+        )?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.connect`
+fn connect<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let name = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation, context)?;
+
+        if !context
+            .local_connections
+            .connect(&name, LocalConnectionReceiver::Avm2(this))
+        {
+            return Err(format!("ArgumentError: Connection {} is already in use", name).into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.send`
+fn send<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    let method_name = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+    let mut graph = Graph::new();
+    let mut message_args = vec![];
+    for arg in args.get(2..).unwrap_or(&[]) {
+        message_args.push(amf::serialize(activation, context, arg, &mut graph)?);
+    }
+
+    if !context
+        .local_connections
+        .send(&name, &method_name, &graph, &message_args)
+    {
+        return Err(format!("ArgumentError: Method {} can't be sent", method_name).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.close`
+fn close<'gc>(
+    _activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let receiver = LocalConnectionReceiver::Avm2(this);
+        if !context.local_connections.is_connected(receiver) {
+            return Err("ArgumentError: The connection is not open".into());
+        }
+
+        context.local_connections.close(receiver);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.allowDomain` and `allowInsecureDomain`
+///
+/// Every movie is considered to be on the same domain, so there is nothing
+/// to allow.
+fn allow_domain<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.domain`'s getter
+fn domain<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok("localhost".into())
+}
+
+/// Construct `LocalConnection.prototype`.
+pub fn create_proto<'gc>(
+    mc: MutationContext<'gc, '_>,
+    super_proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<Object<'gc>, Error> {
+    let proto = ScriptObject::object(mc, super_proto);
+
+    method(mc, proto, "connect", connect, fn_proto)?;
+    method(mc, proto, "send", send, fn_proto)?;
+    method(mc, proto, "close", close, fn_proto)?;
+    method(mc, proto, "allowDomain", allow_domain, fn_proto)?;
+    method(mc, proto, "allowInsecureDomain", allow_domain, fn_proto)?;
+    accessor(mc, proto, "domain", domain, None, fn_proto)?;
+
+    Ok(proto)
+}
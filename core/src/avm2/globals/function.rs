@@ -0,0 +1,93 @@
+//! Function builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::function::FunctionObject;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::MutationContext;
+
+/// Implements `Function`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `Function.prototype.call`
+fn call<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    func: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let func = func.ok_or("Function.prototype.call called without a function")?;
+    let this = match args.get(0) {
+        Some(Value::Object(this)) => Some(*this),
+        _ => None,
+    };
+    let args = args.get(1..).unwrap_or(&[]);
+
+    func.call(this, args, activation, context)
+}
+
+/// Implements `Function.prototype.apply`
+fn apply<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    func: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let func = func.ok_or("Function.prototype.apply called without a function")?;
+    let this = match args.get(0) {
+        Some(Value::Object(this)) => Some(*this),
+        _ => None,
+    };
+
+    let mut child_args = Vec::new();
+    if let Some(Value::Object(args_object)) = args.get(1) {
+        let length = args_object
+            .get_property(
+                *args_object,
+                &QName::dynamic_name("length"),
+                activation,
+                context,
+            )?
+            .coerce_to_u32(activation, context)?;
+
+        for i in 0..length {
+            child_args.push(args_object.get_property(
+                *args_object,
+                &QName::dynamic_name(&i.to_string()),
+                activation,
+                context,
+            )?);
+        }
+    }
+
+    func.call(this, &child_args, activation, context)
+}
+
+/// Fill `Function.prototype` with its methods.
+pub fn fill_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    fn_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("call"),
+        FunctionObject::from_builtin(gc_context, call, fn_proto).into(),
+    )?;
+    fn_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("apply"),
+        FunctionObject::from_builtin(gc_context, apply, fn_proto).into(),
+    )?;
+
+    Ok(())
+}
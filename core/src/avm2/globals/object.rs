@@ -0,0 +1,142 @@
+//! Object builtin and prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::function::FunctionObject;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::MutationContext;
+
+/// Implements `Object`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `Object.prototype.toString`
+fn to_string<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    this.map(|this| this.to_string())
+        .unwrap_or(Ok(Value::Undefined))
+}
+
+/// Implements `Object.prototype.valueOf`
+fn value_of<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    this.map(|this| this.value_of())
+        .unwrap_or(Ok(Value::Undefined))
+}
+
+/// Implements `Object.prototype.hasOwnProperty`
+fn has_own_property<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let this = match this {
+        Some(this) => this,
+        None => return Ok(false.into()),
+    };
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation, context)?;
+
+    Ok(this.has_own_property(&QName::dynamic_name(&name)).into())
+}
+
+/// Implements `Object.prototype.isPrototypeOf`
+fn is_prototype_of<'gc>(
+    _activation: &mut Activation<'gc>,
+    _context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(match (this, args.get(0)) {
+        (Some(this), Some(Value::Object(other))) => this.is_prototype_of(*other).into(),
+        _ => false.into(),
+    })
+}
+
+/// Implements `Object.prototype.propertyIsEnumerable`
+fn property_is_enumerable<'gc>(
+    activation: &mut Activation<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let this = match this {
+        Some(this) => this,
+        None => return Ok(false.into()),
+    };
+    let name = QName::dynamic_name(
+        &args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation, context)?,
+    );
+
+    let mut index = 1;
+    while let Some(enumerant) = this.get_enumerant_name(index) {
+        if enumerant == name {
+            return Ok(true.into());
+        }
+
+        index += 1;
+    }
+
+    Ok(false.into())
+}
+
+/// Fill `Object.prototype` with its methods.
+///
+/// The prototype has to be allocated before `Function.prototype`, which its
+/// methods need, so it is created empty and filled in afterwards.
+pub fn fill_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    object_proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Result<(), Error> {
+    object_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("toString"),
+        FunctionObject::from_builtin(gc_context, to_string, fn_proto).into(),
+    )?;
+    object_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("valueOf"),
+        FunctionObject::from_builtin(gc_context, value_of, fn_proto).into(),
+    )?;
+    object_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("hasOwnProperty"),
+        FunctionObject::from_builtin(gc_context, has_own_property, fn_proto).into(),
+    )?;
+    object_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("isPrototypeOf"),
+        FunctionObject::from_builtin(gc_context, is_prototype_of, fn_proto).into(),
+    )?;
+    object_proto.install_dynamic_property(
+        gc_context,
+        QName::dynamic_name("propertyIsEnumerable"),
+        FunctionObject::from_builtin(gc_context, property_is_enumerable, fn_proto).into(),
+    )?;
+
+    Ok(())
+}
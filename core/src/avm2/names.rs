@@ -0,0 +1,288 @@
+//! AVM2 names & namespacing
+
+use crate::avm2::activation::Activation;
+use crate::avm2::script::TranslationUnit;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::Collect;
+use swf::avm2::types::{
+    Index, Multiname as AbcMultiname, Namespace as AbcNamespace, NamespaceSet as AbcNamespaceSet,
+};
+
+/// Represents the name of a namespace.
+#[derive(Clone, Collect, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[collect(require_static)]
+pub enum Namespace {
+    Namespace(String),
+    Package(String),
+    PackageInternal(String),
+    Protected(String),
+    Explicit(String),
+    StaticProtected(String),
+    Private(String),
+
+    /// The wildcard namespace, which matches every other namespace.
+    Any,
+}
+
+impl Namespace {
+    /// Read a namespace declaration from the ABC constant pool and copy it to
+    /// a namespace value.
+    pub fn from_abc_namespace(
+        translation_unit: &TranslationUnit,
+        namespace_index: Index<AbcNamespace>,
+    ) -> Result<Self, Error> {
+        if namespace_index.0 == 0 {
+            return Ok(Self::Any);
+        }
+
+        let abc_namespace = translation_unit
+            .abc()
+            .constant_pool
+            .namespaces
+            .get(namespace_index.0 as usize - 1)
+            .ok_or_else(|| format!("Unknown namespace constant {}", namespace_index.0))?;
+
+        Ok(match abc_namespace {
+            AbcNamespace::Namespace(idx) => Self::Namespace(translation_unit.pool_string(idx.0)?),
+            AbcNamespace::Package(idx) => Self::Package(translation_unit.pool_string(idx.0)?),
+            AbcNamespace::PackageInternal(idx) => {
+                Self::PackageInternal(translation_unit.pool_string(idx.0)?)
+            }
+            AbcNamespace::Protected(idx) => Self::Protected(translation_unit.pool_string(idx.0)?),
+            AbcNamespace::Explicit(idx) => Self::Explicit(translation_unit.pool_string(idx.0)?),
+            AbcNamespace::StaticProtected(idx) => {
+                Self::StaticProtected(translation_unit.pool_string(idx.0)?)
+            }
+            AbcNamespace::Private(idx) => Self::Private(translation_unit.pool_string(idx.0)?),
+        })
+    }
+
+    /// The public namespace, which holds dynamic properties and global builtins.
+    pub fn public_namespace() -> Self {
+        Namespace::Package("".to_string())
+    }
+
+    /// A public namespace for the given package.
+    pub fn package(package_name: &str) -> Self {
+        Namespace::Package(package_name.to_string())
+    }
+
+    pub fn is_public(&self) -> bool {
+        *self == Self::public_namespace()
+    }
+
+    pub fn is_any(&self) -> bool {
+        *self == Self::Any
+    }
+}
+
+/// A `QName`, likely "qualified name", consists of a namespace and name string.
+///
+/// A property cannot be retrieved or set without first being resolved into a
+/// `QName`. All other forms of names and multinames are either versions of
+/// `QName` with unspecified parameters, or multiple names to be checked in
+/// order.
+#[derive(Clone, Collect, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[collect(require_static)]
+pub struct QName {
+    ns: Namespace,
+    name: String,
+}
+
+impl QName {
+    pub fn new(ns: Namespace, name: &str) -> Self {
+        Self {
+            ns,
+            name: name.to_string(),
+        }
+    }
+
+    /// The name of a dynamic property, which lives in the public namespace.
+    pub fn dynamic_name(local_part: &str) -> Self {
+        Self::new(Namespace::public_namespace(), local_part)
+    }
+
+    /// Pull a `QName` from the multiname pool.
+    ///
+    /// This function returns an Err if the multiname does not exist or is not
+    /// a `QName`.
+    pub fn from_abc_multiname(
+        translation_unit: &TranslationUnit,
+        multiname_index: Index<AbcMultiname>,
+    ) -> Result<Self, Error> {
+        let abc_multiname = translation_unit.pool_multiname(multiname_index.0)?;
+
+        Ok(match abc_multiname {
+            AbcMultiname::QName { namespace, name } | AbcMultiname::QNameA { namespace, name } => {
+                Self {
+                    ns: Namespace::from_abc_namespace(translation_unit, namespace.clone())?,
+                    name: translation_unit.pool_string(name.0)?,
+                }
+            }
+            _ => return Err("Attempted to pull QName from non-QName multiname".into()),
+        })
+    }
+
+    pub fn local_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn namespace(&self) -> &Namespace {
+        &self.ns
+    }
+}
+
+/// A `Multiname` consists of a name which could be resolved in one or more
+/// potential namespaces.
+///
+/// All unresolved names are of type `Multiname`, and they are resolved into
+/// a `QName` by looking up each namespace in order.
+#[derive(Clone, Debug)]
+pub struct Multiname {
+    /// The list of namespaces that satisfy this multiname.
+    ns: Vec<Namespace>,
+
+    /// The local name that satisfies this multiname. If `None`, then this
+    /// multiname is satisfied by any name in the namespace.
+    name: Option<String>,
+}
+
+impl Multiname {
+    /// Read a namespace set from the ABC constant pool.
+    fn abc_namespace_set(
+        translation_unit: &TranslationUnit,
+        namespace_set_index: Index<AbcNamespaceSet>,
+    ) -> Result<Vec<Namespace>, Error> {
+        if namespace_set_index.0 == 0 {
+            return Err("Multiname namespace set must not be null".into());
+        }
+
+        let ns_set = translation_unit
+            .abc()
+            .constant_pool
+            .namespace_sets
+            .get(namespace_set_index.0 as usize - 1)
+            .ok_or_else(|| format!("Unknown namespace set constant {}", namespace_set_index.0))?;
+
+        ns_set
+            .iter()
+            .map(|ns| Namespace::from_abc_namespace(translation_unit, ns.clone()))
+            .collect()
+    }
+
+    /// Read a name from the string pool, where the null string matches any name.
+    fn abc_name(translation_unit: &TranslationUnit, name: u32) -> Result<Option<String>, Error> {
+        if name == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(translation_unit.pool_string(name)?))
+        }
+    }
+
+    /// Read a multiname from the ABC constant pool, popping any runtime
+    /// components of the name off the operand stack.
+    pub fn from_abc_multiname<'gc>(
+        translation_unit: &TranslationUnit,
+        multiname_index: Index<AbcMultiname>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Self, Error> {
+        if multiname_index.0 == 0 {
+            return Ok(Self::any());
+        }
+
+        let abc_multiname = translation_unit.pool_multiname(multiname_index.0)?;
+
+        Ok(match abc_multiname {
+            AbcMultiname::RTQName { name } | AbcMultiname::RTQNameA { name } => {
+                let ns = activation.pop().as_namespace()?;
+                Self {
+                    ns: vec![ns],
+                    name: Self::abc_name(translation_unit, name.0)?,
+                }
+            }
+            AbcMultiname::RTQNameL | AbcMultiname::RTQNameLA => {
+                let name = activation.pop().coerce_to_string(activation, context)?;
+                let ns = activation.pop().as_namespace()?;
+                Self {
+                    ns: vec![ns],
+                    name: Some(name),
+                }
+            }
+            AbcMultiname::MultinameL { namespace_set }
+            | AbcMultiname::MultinameLA { namespace_set } => {
+                let name = activation.pop().coerce_to_string(activation, context)?;
+                Self {
+                    ns: Self::abc_namespace_set(translation_unit, namespace_set.clone())?,
+                    name: Some(name),
+                }
+            }
+            _ => Self::from_abc_multiname_static(translation_unit, multiname_index)?,
+        })
+    }
+
+    /// Read a static multiname from the ABC constant pool.
+    ///
+    /// This function prohibits the use of runtime-qualified and late-bound
+    /// names. Runtime multinames will instead result in an error.
+    pub fn from_abc_multiname_static(
+        translation_unit: &TranslationUnit,
+        multiname_index: Index<AbcMultiname>,
+    ) -> Result<Self, Error> {
+        if multiname_index.0 == 0 {
+            return Ok(Self::any());
+        }
+
+        let abc_multiname = translation_unit.pool_multiname(multiname_index.0)?;
+
+        Ok(match abc_multiname {
+            AbcMultiname::QName { namespace, name } | AbcMultiname::QNameA { namespace, name } => {
+                Self {
+                    ns: vec![Namespace::from_abc_namespace(
+                        translation_unit,
+                        namespace.clone(),
+                    )?],
+                    name: Self::abc_name(translation_unit, name.0)?,
+                }
+            }
+            AbcMultiname::Multiname {
+                namespace_set,
+                name,
+            }
+            | AbcMultiname::MultinameA {
+                namespace_set,
+                name,
+            } => Self {
+                ns: Self::abc_namespace_set(translation_unit, namespace_set.clone())?,
+                name: Self::abc_name(translation_unit, name.0)?,
+            },
+            _ => return Err(format!("Multiname {} is not static", multiname_index.0).into()),
+        })
+    }
+
+    /// Indicates the any type (any name in any namespace).
+    pub fn any() -> Self {
+        Self {
+            ns: vec![Namespace::Any],
+            name: None,
+        }
+    }
+
+    pub fn namespace_set(&self) -> impl Iterator<Item = &Namespace> {
+        self.ns.iter()
+    }
+
+    pub fn local_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl From<QName> for Multiname {
+    fn from(name: QName) -> Self {
+        Self {
+            ns: vec![name.ns],
+            name: Some(name.name),
+        }
+    }
+}
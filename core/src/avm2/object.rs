@@ -0,0 +1,336 @@
+//! AVM2 objects.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::Class;
+use crate::avm2::function::{Executable, FunctionObject};
+use crate::avm2::names::{Multiname, QName};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::stage_object::StageObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::display_object::DisplayObject;
+use gc_arena::{Collect, GcCell, MutationContext};
+use ruffle_macros::enum_trait_object;
+use std::fmt::Debug;
+
+/// Represents an object that can be directly interacted with by the AVM2
+/// runtime.
+#[enum_trait_object(
+    #[derive(Clone, Collect, Debug, Copy)]
+    #[collect(no_drop)]
+    pub enum Object<'gc> {
+        ScriptObject(ScriptObject<'gc>),
+        FunctionObject(FunctionObject<'gc>),
+        StageObject(StageObject<'gc>),
+    }
+)]
+pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy {
+    /// Retrieve a property by its QName, without taking prototype lookups
+    /// into account.
+    ///
+    /// The `receiver` is the object the property was originally looked up on,
+    /// which getters and methods are bound to.
+    fn get_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error>;
+
+    /// Retrieve a property by its QName, searching the prototype chain if
+    /// the object doesn't have it.
+    fn get_property(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        if self.has_own_property(name) {
+            return self.get_property_local(receiver, name, activation, context);
+        }
+
+        if let Some(proto) = self.proto() {
+            return proto.get_property(receiver, name, activation, context);
+        }
+
+        Ok(Value::Undefined)
+    }
+
+    /// Set a property on this specific object.
+    fn set_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error>;
+
+    /// Set a property by its QName.
+    ///
+    /// Setters defined on the prototype chain are called instead of creating
+    /// a new property on this object.
+    fn set_property(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        if !self.has_own_property(name) {
+            let mut proto = self.proto();
+            while let Some(this_proto) = proto {
+                if this_proto.has_own_virtual_setter(name) {
+                    return this_proto
+                        .set_property_local(receiver, name, value, activation, context);
+                }
+
+                if this_proto.has_own_property(name) {
+                    break;
+                }
+
+                proto = this_proto.proto();
+            }
+        }
+
+        self.set_property_local(receiver, name, value, activation, context)
+    }
+
+    /// Initialize a property on this specific object.
+    ///
+    /// This differs from `set_property_local` in that it may write `const`
+    /// slots.
+    fn init_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error>;
+
+    /// Initialize a property by its QName.
+    fn init_property(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        if !self.has_own_property(name) {
+            let mut proto = self.proto();
+            while let Some(this_proto) = proto {
+                if this_proto.has_own_virtual_setter(name) {
+                    return this_proto
+                        .init_property_local(receiver, name, value, activation, context);
+                }
+
+                if this_proto.has_own_property(name) {
+                    break;
+                }
+
+                proto = this_proto.proto();
+            }
+        }
+
+        self.init_property_local(receiver, name, value, activation, context)
+    }
+
+    /// Retrieve a slot by its index.
+    fn get_slot(&self, id: u32) -> Result<Value<'gc>, Error>;
+
+    /// Set a slot by its index.
+    fn set_slot(
+        &self,
+        id: u32,
+        value: Value<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error>;
+
+    /// Resolve a multiname into a single QName, if any of the namespaces
+    /// match a property on this object or its prototype chain.
+    fn resolve_multiname(&self, multiname: &Multiname) -> Option<QName> {
+        let local_name = multiname.local_name()?;
+
+        for ns in multiname.namespace_set() {
+            if ns.is_any() {
+                if let Some(name) = self.resolve_any(local_name) {
+                    return Some(name);
+                }
+            } else {
+                let qname = QName::new(ns.clone(), local_name);
+                if self.has_property(&qname) {
+                    return Some(qname);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Given a local name, find the namespace it resides in on this object or
+    /// its prototype chain, if any.
+    fn resolve_any(&self, local_name: &str) -> Option<QName>;
+
+    /// Indicates whether or not a property exists on an object.
+    fn has_property(&self, name: &QName) -> bool {
+        if self.has_own_property(name) {
+            return true;
+        }
+
+        if let Some(proto) = self.proto() {
+            return proto.has_property(name);
+        }
+
+        false
+    }
+
+    /// Indicates whether or not a property exists on an object and is not
+    /// part of the prototype chain.
+    fn has_own_property(&self, name: &QName) -> bool;
+
+    /// Check if a particular object has a setter of the given name.
+    fn has_own_virtual_setter(&self, name: &QName) -> bool;
+
+    /// Delete a named property from the object.
+    ///
+    /// Returns false if the property cannot be deleted.
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName) -> bool;
+
+    /// Retrieve the `__proto__` of a given object.
+    fn proto(&self) -> Option<Object<'gc>>;
+
+    /// Retrieve the name of an enumerable property, given its one-based
+    /// enumeration index.
+    ///
+    /// Index 0 is never a valid property, so that `hasnext2` can use it to
+    /// signal the end of enumeration.
+    fn get_enumerant_name(&self, index: u32) -> Option<QName>;
+
+    /// Install a method (or any other non-slot value) on an object.
+    fn install_method(&self, mc: MutationContext<'gc, '_>, name: QName, function: Object<'gc>);
+
+    /// Install a getter method on an object property.
+    fn install_getter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error>;
+
+    /// Install a setter method on an object property.
+    fn install_setter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error>;
+
+    /// Install a dynamic or built-in value property on an object.
+    fn install_dynamic_property(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        value: Value<'gc>,
+    ) -> Result<(), Error>;
+
+    /// Install a slot on an object property.
+    ///
+    /// A slot id of 0 assigns the next free slot.
+    fn install_slot(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>);
+
+    /// Install a const on an object property.
+    fn install_const(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>);
+
+    /// Call the object as a function.
+    ///
+    /// `receiver` is the `this` value, which bound methods ignore.
+    fn call(
+        &self,
+        _receiver: Option<Object<'gc>>,
+        _arguments: &[Value<'gc>],
+        _activation: &mut Activation<'gc>,
+        _context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        Err("Object is not callable".into())
+    }
+
+    /// Construct a new instance, using this object as the class.
+    fn construct(
+        &self,
+        _activation: &mut Activation<'gc>,
+        _context: &mut UpdateContext<'_, 'gc, '_>,
+        _arguments: &[Value<'gc>],
+    ) -> Result<Object<'gc>, Error> {
+        Err("Object is not constructable".into())
+    }
+
+    /// Create a new object that uses this object as its prototype.
+    ///
+    /// Host objects return an object of their own kind, so that instances of
+    /// classes that extend them, such as `flash.display.Sprite`, can still be
+    /// used as host objects.
+    fn derive(&self, mc: MutationContext<'gc, '_>) -> Object<'gc> {
+        ScriptObject::object(mc, (*self).into())
+    }
+
+    /// Get the display object this object represents, if any.
+    fn as_display_object(&self) -> Option<DisplayObject<'gc>> {
+        None
+    }
+
+    /// Associate a display object with this object, if it can represent
+    /// one.
+    fn init_display_object(&self, _mc: MutationContext<'gc, '_>, _obj: DisplayObject<'gc>) {}
+
+    /// Get the executable code of the object, if it is a function.
+    fn as_executable(&self) -> Option<Executable<'gc>> {
+        None
+    }
+
+    /// Get the class data of the object, if it is a class.
+    fn as_class(&self) -> Option<GcCell<'gc, Class<'gc>>> {
+        None
+    }
+
+    /// Implements the `toString` method of `Object.prototype`.
+    fn to_string(&self) -> Result<Value<'gc>, Error> {
+        Ok("[object Object]".into())
+    }
+
+    /// Implements the `valueOf` method of `Object.prototype`.
+    fn value_of(&self) -> Result<Value<'gc>, Error> {
+        Ok(Value::Object((*self).into()))
+    }
+
+    /// Get a raw pointer value for this object.
+    fn as_ptr(&self) -> *const ObjectPtr;
+
+    /// Check if this object is in the prototype chain of the specified test object.
+    fn is_prototype_of(&self, other: Object<'gc>) -> bool {
+        let mut proto = other.proto();
+
+        while let Some(proto_ob) = proto {
+            if self.as_ptr() == proto_ob.as_ptr() {
+                return true;
+            }
+
+            proto = proto_ob.proto();
+        }
+
+        false
+    }
+}
+
+pub enum ObjectPtr {}
+
+impl<'gc> Object<'gc> {
+    pub fn ptr_eq(a: Object<'gc>, b: Object<'gc>) -> bool {
+        a.as_ptr() == b.as_ptr()
+    }
+}
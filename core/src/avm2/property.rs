@@ -0,0 +1,81 @@
+//! Property data structures
+
+use crate::avm2::object::Object;
+use crate::avm2::Value;
+use enumset::{EnumSet, EnumSetType};
+use gc_arena::Collect;
+
+/// Attributes of properties in the AVM runtime.
+#[derive(EnumSetType, Debug)]
+pub enum Attribute {
+    DontEnum,
+    DontDelete,
+    ReadOnly,
+}
+
+/// A property of an object.
+#[derive(Clone, Debug)]
+pub enum Property<'gc> {
+    /// A property with a getter and/or a setter, defined by a getter or
+    /// setter trait.
+    Virtual {
+        get: Option<Object<'gc>>,
+        set: Option<Object<'gc>>,
+    },
+
+    /// A method defined by a method trait.
+    ///
+    /// Retrieving a method binds it to the object it was retrieved from.
+    Method { function: Object<'gc> },
+
+    /// A property holding a value, such as a dynamic property.
+    Stored {
+        value: Value<'gc>,
+        attributes: EnumSet<Attribute>,
+    },
+
+    /// A property whose value is held in a slot of the object.
+    Slot { slot_id: u32 },
+}
+
+unsafe impl<'gc> Collect for Property<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        match self {
+            Property::Virtual { get, set } => {
+                get.trace(cc);
+                set.trace(cc);
+            }
+            Property::Method { function } => function.trace(cc),
+            Property::Stored { value, .. } => value.trace(cc),
+            Property::Slot { .. } => {}
+        }
+    }
+}
+
+impl<'gc> Property<'gc> {
+    pub fn new_stored(value: impl Into<Value<'gc>>) -> Self {
+        Property::Stored {
+            value: value.into(),
+            attributes: EnumSet::empty(),
+        }
+    }
+
+    /// Whether the property may be removed with `delete`.
+    pub fn can_delete(&self) -> bool {
+        match self {
+            Property::Stored { attributes, .. } => !attributes.contains(Attribute::DontDelete),
+            _ => false,
+        }
+    }
+}
+
+/// The value held by a slot, along with whether it may be overwritten.
+#[derive(Clone, Debug, Collect)]
+#[collect(no_drop)]
+pub struct Slot<'gc> {
+    pub value: Value<'gc>,
+
+    /// Whether the slot was defined by a `const` trait, which may only be set
+    /// while the object is being initialized.
+    pub is_const: bool,
+}
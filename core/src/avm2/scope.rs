@@ -0,0 +1,103 @@
+//! Represents AVM2 scope chain resolution.
+
+use crate::avm2::names::Multiname;
+use crate::avm2::object::{Object, TObject};
+use gc_arena::{Collect, GcCell, MutationContext};
+
+/// Indicates what kind of scope a scope is.
+#[derive(Copy, Clone, Debug, PartialEq, Collect)]
+#[collect(require_static)]
+pub enum ScopeClass {
+    /// Scope represents global or closure scope.
+    GlobalOrClosure,
+
+    /// Scope represents an object added to the scope chain with `with`.
+    /// It is not inherited when closures are defined. Furthermore, a `with`
+    /// scope gains the ability to be searched for dynamic properties.
+    WithScope,
+}
+
+/// Represents a scope chain for an AVM2 activation.
+#[derive(Debug, Collect)]
+#[collect(no_drop)]
+pub struct Scope<'gc> {
+    parent: Option<GcCell<'gc, Scope<'gc>>>,
+    class: ScopeClass,
+    values: Object<'gc>,
+}
+
+impl<'gc> Scope<'gc> {
+    /// Push a scope onto the stack, producing a new scope chain that's one
+    /// item longer.
+    pub fn push_scope(
+        scope_stack: Option<GcCell<'gc, Scope<'gc>>>,
+        object: Object<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> GcCell<'gc, Self> {
+        GcCell::allocate(
+            mc,
+            Self {
+                parent: scope_stack,
+                class: ScopeClass::GlobalOrClosure,
+                values: object,
+            },
+        )
+    }
+
+    /// Construct a with scope to be used as the scope during a with block.
+    ///
+    /// A with block adds an object to the top of the scope chain, so unqualified
+    /// references will try to resolve on that object first.
+    pub fn push_with(
+        scope_stack: Option<GcCell<'gc, Scope<'gc>>>,
+        with_object: Object<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> GcCell<'gc, Self> {
+        GcCell::allocate(
+            mc,
+            Scope {
+                parent: scope_stack,
+                class: ScopeClass::WithScope,
+                values: with_object,
+            },
+        )
+    }
+
+    /// Returns a reference to the current local scope object.
+    pub fn locals(&self) -> &Object<'gc> {
+        &self.values
+    }
+
+    /// Returns a reference to the parent scope object.
+    pub fn parent(&self) -> Option<GcCell<'gc, Scope<'gc>>> {
+        self.parent
+    }
+
+    /// Returns the bottom-most scope of the chain, which holds the global
+    /// object of the script.
+    pub fn global_scope(scope: GcCell<'gc, Self>) -> GcCell<'gc, Self> {
+        let mut scope = scope;
+        loop {
+            let parent = scope.read().parent;
+            match parent {
+                Some(parent) => scope = parent,
+                None => return scope,
+            }
+        }
+    }
+
+    /// Find an object that contains a given property in the scope stack.
+    ///
+    /// This function yields `None` if no such scope exists.
+    pub fn find(&self, name: &Multiname) -> Option<Object<'gc>> {
+        if self.locals().resolve_multiname(name).is_some() {
+            return Some(*self.locals());
+        }
+
+        if let Some(scope) = self.parent() {
+            return scope.read().find(name);
+        }
+
+        None
+    }
+}
@@ -0,0 +1,327 @@
+//! Whole script representation
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::install_trait;
+use crate::avm2::names::QName;
+use crate::avm2::object::Object;
+use crate::avm2::scope::Scope;
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::rc::Rc;
+use swf::avm2::read::Reader;
+use swf::avm2::types::{
+    AbcFile, Method as AbcMethod, MethodBody as AbcMethodBody, Multiname as AbcMultiname, Op,
+};
+use swf::read::SwfRead;
+
+/// A loaded ABC file, which the methods, classes and scripts defined in it
+/// refer back to.
+#[derive(Clone, Collect, Debug)]
+#[collect(require_static)]
+pub struct TranslationUnit(Rc<TranslationUnitData>);
+
+#[derive(Debug)]
+struct TranslationUnitData {
+    abc: AbcFile,
+
+    /// The index of the body of each method that has one.
+    method_bodies: HashMap<u32, u32>,
+
+    /// The decoded instructions of each method body, filled in the first time
+    /// the method runs.
+    decoded_bodies: Vec<RefCell<Option<Rc<DecodedBody>>>>,
+}
+
+/// The instructions of a method body, decoded once and shared by every call.
+#[derive(Debug)]
+pub struct DecodedBody {
+    /// The instructions, in order.
+    pub ops: Vec<Op>,
+
+    /// The byte offset of each instruction in the body, which branches refer to.
+    pub offsets: Vec<usize>,
+}
+
+impl DecodedBody {
+    fn decode(body: &AbcMethodBody) -> Result<Self, Error> {
+        let mut ops = vec![];
+        let mut offsets = vec![];
+        let mut reader = Reader::new(Cursor::new(&body.code[..]));
+        loop {
+            let offset = reader.get_inner().position() as usize;
+            if offset >= body.code.len() {
+                break;
+            }
+
+            match reader.read_op()? {
+                Some(op) => {
+                    ops.push(op);
+                    offsets.push(offset);
+                }
+                None => break,
+            }
+        }
+
+        Ok(Self { ops, offsets })
+    }
+}
+
+impl TranslationUnit {
+    pub fn from_abc(abc: AbcFile) -> Self {
+        let method_bodies = abc
+            .method_bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| (body.method.0, i as u32))
+            .collect();
+        let decoded_bodies = abc
+            .method_bodies
+            .iter()
+            .map(|_| RefCell::new(None))
+            .collect();
+
+        Self(Rc::new(TranslationUnitData {
+            abc,
+            method_bodies,
+            decoded_bodies,
+        }))
+    }
+
+    pub fn abc(&self) -> &AbcFile {
+        &self.0.abc
+    }
+
+    /// Look up a method and its body.
+    pub fn load_method(&self, method_index: u32) -> Result<BytecodeMethod, Error> {
+        if method_index as usize >= self.abc().methods.len() {
+            return Err(format!("Unknown method {}", method_index).into());
+        }
+
+        Ok(BytecodeMethod {
+            translation_unit: self.clone(),
+            method_index,
+            body_index: self.0.method_bodies.get(&method_index).copied(),
+        })
+    }
+
+    /// Retrieve a string from the constant pool, where index 0 is the empty string.
+    pub fn pool_string(&self, index: u32) -> Result<String, Error> {
+        if index == 0 {
+            return Ok(String::new());
+        }
+
+        self.abc()
+            .constant_pool
+            .strings
+            .get(index as usize - 1)
+            .cloned()
+            .ok_or_else(|| format!("Unknown string constant {}", index).into())
+    }
+
+    pub fn pool_int(&self, index: u32) -> Result<i32, Error> {
+        if index == 0 {
+            return Ok(0);
+        }
+
+        self.abc()
+            .constant_pool
+            .ints
+            .get(index as usize - 1)
+            .copied()
+            .ok_or_else(|| format!("Unknown int constant {}", index).into())
+    }
+
+    pub fn pool_uint(&self, index: u32) -> Result<u32, Error> {
+        if index == 0 {
+            return Ok(0);
+        }
+
+        self.abc()
+            .constant_pool
+            .uints
+            .get(index as usize - 1)
+            .copied()
+            .ok_or_else(|| format!("Unknown uint constant {}", index).into())
+    }
+
+    pub fn pool_double(&self, index: u32) -> Result<f64, Error> {
+        if index == 0 {
+            return Ok(f64::NAN);
+        }
+
+        self.abc()
+            .constant_pool
+            .doubles
+            .get(index as usize - 1)
+            .copied()
+            .ok_or_else(|| format!("Unknown double constant {}", index).into())
+    }
+
+    pub fn pool_multiname(&self, index: u32) -> Result<&AbcMultiname, Error> {
+        if index == 0 {
+            return Err("Multiname index must not be null".into());
+        }
+
+        self.abc()
+            .constant_pool
+            .multinames
+            .get(index as usize - 1)
+            .ok_or_else(|| format!("Unknown multiname constant {}", index).into())
+    }
+}
+
+/// A method defined in an ABC file, along with its body if it has one.
+#[derive(Clone, Collect, Debug)]
+#[collect(require_static)]
+pub struct BytecodeMethod {
+    translation_unit: TranslationUnit,
+    method_index: u32,
+    body_index: Option<u32>,
+}
+
+impl BytecodeMethod {
+    pub fn translation_unit(&self) -> &TranslationUnit {
+        &self.translation_unit
+    }
+
+    pub fn method(&self) -> &AbcMethod {
+        &self.translation_unit.abc().methods[self.method_index as usize]
+    }
+
+    /// The body of the method, which native and interface methods lack.
+    pub fn body(&self) -> Option<&AbcMethodBody> {
+        self.body_index
+            .map(|index| &self.translation_unit.abc().method_bodies[index as usize])
+    }
+
+    /// The decoded instructions of the method body, which are decoded the
+    /// first time they're needed.
+    pub fn decoded_body(&self) -> Result<Rc<DecodedBody>, Error> {
+        let index = self
+            .body_index
+            .ok_or("Cannot execute a method without a body")? as usize;
+        let cache = &self.translation_unit.0.decoded_bodies[index];
+        if let Some(decoded) = &*cache.borrow() {
+            return Ok(decoded.clone());
+        }
+
+        let decoded = Rc::new(DecodedBody::decode(
+            &self.translation_unit.abc().method_bodies[index],
+        )?);
+        *cache.borrow_mut() = Some(decoded.clone());
+        Ok(decoded)
+    }
+}
+
+/// A script in an ABC file, which defines global traits.
+///
+/// Scripts are initialized lazily, the first time that one of their traits is
+/// looked up, except for the last script of a file, which is its entry point.
+#[derive(Clone, Copy, Collect, Debug)]
+#[collect(no_drop)]
+pub struct Script<'gc>(GcCell<'gc, ScriptData<'gc>>);
+
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+struct ScriptData<'gc> {
+    /// The global object of the script, which holds its traits.
+    globals: Object<'gc>,
+
+    /// The ABC file the script was defined in, or `None` for builtins.
+    translation_unit: Option<TranslationUnit>,
+
+    /// The index of the script in its ABC file.
+    script_index: u32,
+
+    /// Whether the traits of the script have been installed and its
+    /// initializer has run.
+    initialized: bool,
+}
+
+impl<'gc> Script<'gc> {
+    /// Prepare a script from an ABC file, without running it yet.
+    pub fn from_abc_index(
+        mc: MutationContext<'gc, '_>,
+        translation_unit: TranslationUnit,
+        script_index: u32,
+        object_proto: Object<'gc>,
+    ) -> Result<Self, Error> {
+        if script_index as usize >= translation_unit.abc().scripts.len() {
+            return Err(format!("Unknown script {}", script_index).into());
+        }
+
+        Ok(Self(GcCell::allocate(
+            mc,
+            ScriptData {
+                globals: ScriptObject::object(mc, object_proto),
+                translation_unit: Some(translation_unit),
+                script_index,
+                initialized: false,
+            },
+        )))
+    }
+
+    /// The names of the traits defined by this script.
+    pub fn trait_names(self) -> Result<Vec<QName>, Error> {
+        let read = self.0.read();
+        match &read.translation_unit {
+            Some(translation_unit) => translation_unit.abc().scripts[read.script_index as usize]
+                .traits
+                .iter()
+                .map(|abc_trait| {
+                    QName::from_abc_multiname(translation_unit, abc_trait.name.clone())
+                })
+                .collect(),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Return the global object of this script, initializing the script
+    /// first if that hasn't happened yet.
+    pub fn globals(self, context: &mut UpdateContext<'_, 'gc, '_>) -> Result<Object<'gc>, Error> {
+        let (globals, translation_unit, script_index) = {
+            let read = self.0.read();
+            if read.initialized {
+                return Ok(read.globals);
+            }
+
+            (
+                read.globals,
+                read.translation_unit.clone(),
+                read.script_index,
+            )
+        };
+
+        // Mark the script first, so that traits referring back to it don't
+        // initialize it again.
+        self.0.write(context.gc_context).initialized = true;
+
+        if let Some(translation_unit) = translation_unit {
+            let script = &translation_unit.abc().scripts[script_index as usize];
+            let scope = Scope::push_scope(None, globals, context.gc_context);
+
+            for abc_trait in &script.traits {
+                install_trait(
+                    globals,
+                    &translation_unit,
+                    abc_trait,
+                    Some(scope),
+                    None,
+                    context,
+                )?;
+            }
+
+            let init = translation_unit.load_method(script.init_method.0)?;
+            let mut activation =
+                Activation::from_method(context, init, Some(scope), Some(globals), &[], None)?;
+            activation.run_actions(context)?;
+        }
+
+        Ok(globals)
+    }
+}
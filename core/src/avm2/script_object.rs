@@ -0,0 +1,438 @@
+//! Default AVM2 object impl
+
+use crate::avm2::activation::Activation;
+use crate::avm2::function::bind_method;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::property::{Attribute, Property, Slot};
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::collections::HashMap;
+
+/// Default implementation of `avm2::Object`.
+#[derive(Clone, Collect, Debug, Copy)]
+#[collect(no_drop)]
+pub struct ScriptObject<'gc>(GcCell<'gc, ScriptObjectData<'gc>>);
+
+/// Base data common to all `TObject` implementations.
+///
+/// Host implementations of `TObject` should embed a `ScriptObject` and
+/// forward any trait method implementations it does not overwrite to it.
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+pub struct ScriptObjectData<'gc> {
+    /// Properties stored on this object.
+    values: HashMap<QName, Property<'gc>>,
+
+    /// Slots stored on this object. Slot ids start at 1.
+    slots: Vec<Slot<'gc>>,
+
+    /// Implicit prototype of this script object.
+    proto: Option<Object<'gc>>,
+
+    /// Enumerable property names, in the order they were created.
+    enumerants: Vec<QName>,
+}
+
+impl<'gc> TObject<'gc> for ScriptObject<'gc> {
+    fn get_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let property = self.0.read().values.get(name).cloned();
+        ScriptObjectData::get_property(*self, property, receiver, activation, context)
+    }
+
+    fn set_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        let property = self.0.read().values.get(name).cloned();
+        if let Some(Property::Virtual { set, .. }) = property {
+            return match set {
+                Some(set) => set
+                    .call(Some(receiver), &[value], activation, context)
+                    .map(|_| ()),
+                None => Err(format!("Property {} is read-only", name.local_name()).into()),
+            };
+        }
+
+        self.0
+            .write(context.gc_context)
+            .set_property_local(name, value, false)
+    }
+
+    fn init_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        let property = self.0.read().values.get(name).cloned();
+        if let Some(Property::Virtual { set, .. }) = property {
+            return match set {
+                Some(set) => set
+                    .call(Some(receiver), &[value], activation, context)
+                    .map(|_| ()),
+                None => Err(format!("Property {} is read-only", name.local_name()).into()),
+            };
+        }
+
+        self.0
+            .write(context.gc_context)
+            .set_property_local(name, value, true)
+    }
+
+    fn get_slot(&self, id: u32) -> Result<Value<'gc>, Error> {
+        self.0.read().get_slot(id)
+    }
+
+    fn set_slot(
+        &self,
+        id: u32,
+        value: Value<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error> {
+        self.0.write(mc).set_slot(id, value, false)
+    }
+
+    fn resolve_any(&self, local_name: &str) -> Option<QName> {
+        self.0.read().resolve_any(local_name)
+    }
+
+    fn has_own_property(&self, name: &QName) -> bool {
+        self.0.read().has_own_property(name)
+    }
+
+    fn has_own_virtual_setter(&self, name: &QName) -> bool {
+        self.0.read().has_own_virtual_setter(name)
+    }
+
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName) -> bool {
+        self.0.write(gc_context).delete_property(name)
+    }
+
+    fn proto(&self) -> Option<Object<'gc>> {
+        self.0.read().proto
+    }
+
+    fn get_enumerant_name(&self, index: u32) -> Option<QName> {
+        self.0.read().get_enumerant_name(index)
+    }
+
+    fn install_method(&self, mc: MutationContext<'gc, '_>, name: QName, function: Object<'gc>) {
+        self.0.write(mc).install_method(name, function)
+    }
+
+    fn install_getter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.0.write(mc).install_getter(name, function)
+    }
+
+    fn install_setter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.0.write(mc).install_setter(name, function)
+    }
+
+    fn install_dynamic_property(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        value: Value<'gc>,
+    ) -> Result<(), Error> {
+        self.0.write(mc).install_dynamic_property(name, value)
+    }
+
+    fn install_slot(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.0.write(mc).install_slot(name, id, value, false)
+    }
+
+    fn install_const(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.0.write(mc).install_slot(name, id, value, true)
+    }
+
+    fn as_ptr(&self) -> *const ObjectPtr {
+        self.0.as_ptr() as *const ObjectPtr
+    }
+}
+
+impl<'gc> ScriptObject<'gc> {
+    /// Construct the script object that another object type embeds.
+    pub fn base(mc: MutationContext<'gc, '_>, proto: Option<Object<'gc>>) -> Self {
+        ScriptObject(GcCell::allocate(mc, ScriptObjectData::base_new(proto)))
+    }
+
+    /// Construct a bare object with no prototype.
+    pub fn bare_object(mc: MutationContext<'gc, '_>) -> Object<'gc> {
+        Self::base(mc, None).into()
+    }
+
+    /// Construct an object with a prototype.
+    pub fn object(mc: MutationContext<'gc, '_>, proto: Object<'gc>) -> Object<'gc> {
+        Self::base(mc, Some(proto)).into()
+    }
+
+    /// Construct an array-like object holding the given values as indexed
+    /// properties, along with a `length`.
+    pub fn array(
+        mc: MutationContext<'gc, '_>,
+        array_proto: Object<'gc>,
+        values: &[Value<'gc>],
+    ) -> Object<'gc> {
+        let mut data = ScriptObjectData::base_new(Some(array_proto));
+        for (i, value) in values.iter().enumerate() {
+            data.values.insert(
+                QName::dynamic_name(&i.to_string()),
+                Property::new_stored(value.clone()),
+            );
+            data.enumerants.push(QName::dynamic_name(&i.to_string()));
+        }
+        data.values.insert(
+            QName::dynamic_name("length"),
+            Property::Stored {
+                value: values.len().into(),
+                attributes: Attribute::DontEnum | Attribute::DontDelete,
+            },
+        );
+
+        ScriptObject(GcCell::allocate(mc, data)).into()
+    }
+}
+
+impl<'gc> ScriptObjectData<'gc> {
+    pub fn base_new(proto: Option<Object<'gc>>) -> Self {
+        ScriptObjectData {
+            values: HashMap::new(),
+            slots: Vec::new(),
+            proto,
+            enumerants: Vec::new(),
+        }
+    }
+
+    /// Read the value of a property that was cloned out of an object.
+    ///
+    /// The property has to be cloned first, as getters may access the object
+    /// again.
+    pub fn get_property(
+        this: impl TObject<'gc>,
+        property: Option<Property<'gc>>,
+        receiver: Object<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        match property {
+            Some(Property::Stored { value, .. }) => Ok(value),
+            Some(Property::Slot { slot_id }) => this.get_slot(slot_id),
+            Some(Property::Method { function }) => {
+                Ok(bind_method(function, receiver, context.gc_context).into())
+            }
+            Some(Property::Virtual { get: Some(get), .. }) => {
+                get.call(Some(receiver), &[], activation, context)
+            }
+            Some(Property::Virtual { get: None, .. }) | None => Ok(Value::Undefined),
+        }
+    }
+
+    /// Set a property that isn't virtual, creating it if it doesn't exist.
+    pub fn set_property_local(
+        &mut self,
+        name: &QName,
+        value: Value<'gc>,
+        is_init: bool,
+    ) -> Result<(), Error> {
+        match self.values.get_mut(name) {
+            Some(Property::Slot { slot_id }) => {
+                let slot_id = *slot_id;
+                self.set_slot(slot_id, value, is_init)
+            }
+            Some(Property::Method { .. }) => {
+                Err(format!("Cannot assign to method {}", name.local_name()).into())
+            }
+            Some(Property::Stored {
+                value: stored,
+                attributes,
+            }) => {
+                if !attributes.contains(Attribute::ReadOnly) {
+                    *stored = value;
+                }
+                Ok(())
+            }
+            Some(Property::Virtual { .. }) => {
+                Err(format!("Property {} is read-only", name.local_name()).into())
+            }
+            None => {
+                self.values
+                    .insert(name.clone(), Property::new_stored(value));
+                self.enumerants.push(name.clone());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get_slot(&self, id: u32) -> Result<Value<'gc>, Error> {
+        id.checked_sub(1)
+            .and_then(|index| self.slots.get(index as usize))
+            .map(|slot| slot.value.clone())
+            .ok_or_else(|| format!("Slot index {} out of bounds", id).into())
+    }
+
+    pub fn set_slot(&mut self, id: u32, value: Value<'gc>, is_init: bool) -> Result<(), Error> {
+        let slot = id
+            .checked_sub(1)
+            .and_then(|index| self.slots.get_mut(index as usize))
+            .ok_or_else(|| format!("Slot index {} out of bounds", id))?;
+
+        if slot.is_const && !is_init {
+            return Err(format!("Cannot overwrite const slot {}", id).into());
+        }
+
+        slot.value = value;
+        Ok(())
+    }
+
+    pub fn resolve_any(&self, local_name: &str) -> Option<QName> {
+        for key in self.values.keys() {
+            if key.local_name() == local_name {
+                return Some(key.clone());
+            }
+        }
+
+        self.proto.and_then(|proto| proto.resolve_any(local_name))
+    }
+
+    pub fn has_own_property(&self, name: &QName) -> bool {
+        self.values.contains_key(name)
+    }
+
+    pub fn has_own_virtual_setter(&self, name: &QName) -> bool {
+        matches!(
+            self.values.get(name),
+            Some(Property::Virtual { set: Some(_), .. })
+        )
+    }
+
+    pub fn delete_property(&mut self, name: &QName) -> bool {
+        let can_delete = self
+            .values
+            .get(name)
+            .map(|property| property.can_delete())
+            .unwrap_or(false);
+
+        if can_delete {
+            self.values.remove(name);
+            self.enumerants.retain(|enumerant| enumerant != name);
+        }
+
+        can_delete
+    }
+
+    pub fn get_enumerant_name(&self, index: u32) -> Option<QName> {
+        let index = index.checked_sub(1)?;
+        self.enumerants.get(index as usize).cloned()
+    }
+
+    pub fn install_method(&mut self, name: QName, function: Object<'gc>) {
+        self.values.insert(name, Property::Method { function });
+    }
+
+    pub fn install_getter(&mut self, name: QName, function: Object<'gc>) -> Result<(), Error> {
+        match self.values.get_mut(&name) {
+            Some(Property::Virtual { get, .. }) => *get = Some(function),
+            None => {
+                self.values.insert(
+                    name,
+                    Property::Virtual {
+                        get: Some(function),
+                        set: None,
+                    },
+                );
+            }
+            _ => {
+                return Err(
+                    format!("Cannot install getter over property {}", name.local_name()).into(),
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn install_setter(&mut self, name: QName, function: Object<'gc>) -> Result<(), Error> {
+        match self.values.get_mut(&name) {
+            Some(Property::Virtual { set, .. }) => *set = Some(function),
+            None => {
+                self.values.insert(
+                    name,
+                    Property::Virtual {
+                        get: None,
+                        set: Some(function),
+                    },
+                );
+            }
+            _ => {
+                return Err(
+                    format!("Cannot install setter over property {}", name.local_name()).into(),
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn install_dynamic_property(
+        &mut self,
+        name: QName,
+        value: Value<'gc>,
+    ) -> Result<(), Error> {
+        self.values.insert(
+            name,
+            Property::Stored {
+                value,
+                attributes: EnumSet::from(Attribute::DontEnum),
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn install_slot(&mut self, name: QName, id: u32, value: Value<'gc>, is_const: bool) {
+        let id = if id == 0 {
+            self.slots.len() as u32 + 1
+        } else {
+            id
+        };
+
+        if self.slots.len() < id as usize {
+            self.slots.resize(
+                id as usize,
+                Slot {
+                    value: Value::Undefined,
+                    is_const: false,
+                },
+            );
+        }
+
+        self.slots[id as usize - 1] = Slot { value, is_const };
+        self.values.insert(name, Property::Slot { slot_id: id });
+    }
+}
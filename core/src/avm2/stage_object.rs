@@ -0,0 +1,179 @@
+//! AVM2 object type to represent objects on the stage.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::names::QName;
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::script_object::ScriptObject;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use crate::display_object::DisplayObject;
+use gc_arena::{Collect, GcCell, MutationContext};
+
+/// An object that represents a display node, which is an instance of one of
+/// the `flash.display` classes.
+///
+/// The prototypes of those classes are stage objects without a display node,
+/// so that objects derived from them are stage objects too.
+#[derive(Clone, Collect, Debug, Copy)]
+#[collect(no_drop)]
+pub struct StageObject<'gc> {
+    /// The underlying script object.
+    ///
+    /// This holds the properties of the object and its prototype chain.
+    base: ScriptObject<'gc>,
+
+    /// The display node this object represents, once it has one.
+    display_object: GcCell<'gc, Option<DisplayObject<'gc>>>,
+}
+
+impl<'gc> StageObject<'gc> {
+    /// Create a stage object for a given display node.
+    pub fn for_display_object(
+        mc: MutationContext<'gc, '_>,
+        display_object: DisplayObject<'gc>,
+        proto: Object<'gc>,
+    ) -> Self {
+        Self {
+            base: ScriptObject::base(mc, Some(proto)),
+            display_object: GcCell::allocate(mc, Some(display_object)),
+        }
+    }
+
+    /// Create a stage object that doesn't represent a display node yet.
+    pub fn bare(mc: MutationContext<'gc, '_>, proto: Option<Object<'gc>>) -> Self {
+        Self {
+            base: ScriptObject::base(mc, proto),
+            display_object: GcCell::allocate(mc, None),
+        }
+    }
+}
+
+impl<'gc> TObject<'gc> for StageObject<'gc> {
+    fn get_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        self.base
+            .get_property_local(receiver, name, activation, context)
+    }
+
+    fn set_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base
+            .set_property_local(receiver, name, value, activation, context)
+    }
+
+    fn init_property_local(
+        &self,
+        receiver: Object<'gc>,
+        name: &QName,
+        value: Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base
+            .init_property_local(receiver, name, value, activation, context)
+    }
+
+    fn get_slot(&self, id: u32) -> Result<Value<'gc>, Error> {
+        self.base.get_slot(id)
+    }
+
+    fn set_slot(
+        &self,
+        id: u32,
+        value: Value<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error> {
+        self.base.set_slot(id, value, mc)
+    }
+
+    fn resolve_any(&self, local_name: &str) -> Option<QName> {
+        self.base.resolve_any(local_name)
+    }
+
+    fn has_own_property(&self, name: &QName) -> bool {
+        self.base.has_own_property(name)
+    }
+
+    fn has_own_virtual_setter(&self, name: &QName) -> bool {
+        self.base.has_own_virtual_setter(name)
+    }
+
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName) -> bool {
+        self.base.delete_property(gc_context, name)
+    }
+
+    fn proto(&self) -> Option<Object<'gc>> {
+        self.base.proto()
+    }
+
+    fn get_enumerant_name(&self, index: u32) -> Option<QName> {
+        self.base.get_enumerant_name(index)
+    }
+
+    fn install_method(&self, mc: MutationContext<'gc, '_>, name: QName, function: Object<'gc>) {
+        self.base.install_method(mc, name, function)
+    }
+
+    fn install_getter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_getter(mc, name, function)
+    }
+
+    fn install_setter(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        function: Object<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_setter(mc, name, function)
+    }
+
+    fn install_dynamic_property(
+        &self,
+        mc: MutationContext<'gc, '_>,
+        name: QName,
+        value: Value<'gc>,
+    ) -> Result<(), Error> {
+        self.base.install_dynamic_property(mc, name, value)
+    }
+
+    fn install_slot(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.base.install_slot(mc, name, id, value)
+    }
+
+    fn install_const(&self, mc: MutationContext<'gc, '_>, name: QName, id: u32, value: Value<'gc>) {
+        self.base.install_const(mc, name, id, value)
+    }
+
+    fn derive(&self, mc: MutationContext<'gc, '_>) -> Object<'gc> {
+        Self::bare(mc, Some((*self).into())).into()
+    }
+
+    fn as_display_object(&self) -> Option<DisplayObject<'gc>> {
+        *self.display_object.read()
+    }
+
+    fn init_display_object(&self, mc: MutationContext<'gc, '_>, obj: DisplayObject<'gc>) {
+        *self.display_object.write(mc) = Some(obj);
+    }
+
+    fn as_ptr(&self) -> *const ObjectPtr {
+        self.base.as_ptr() as *const ObjectPtr
+    }
+}
@@ -0,0 +1,365 @@
+//! AVM2 values
+
+use crate::avm1::value::{f64_to_string, f64_to_wrapping_i32, f64_to_wrapping_u32};
+use crate::avm2::activation::Activation;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::script::TranslationUnit;
+use crate::avm2::Error;
+use crate::context::UpdateContext;
+use gc_arena::Collect;
+use swf::avm2::types::DefaultValue as AbcDefaultValue;
+
+/// An AVM2 value.
+///
+/// TODO: AVM2 also needs XML values.
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+pub enum Value<'gc> {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Namespace(Namespace),
+    Object(Object<'gc>),
+}
+
+impl<'gc> From<String> for Value<'gc> {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+impl<'gc> From<&str> for Value<'gc> {
+    fn from(string: &str) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl<'gc> From<bool> for Value<'gc> {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl<'gc, T> From<T> for Value<'gc>
+where
+    Object<'gc>: From<T>,
+{
+    fn from(value: T) -> Self {
+        Value::Object(Object::from(value))
+    }
+}
+
+impl<'gc> From<f64> for Value<'gc> {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl<'gc> From<f32> for Value<'gc> {
+    fn from(value: f32) -> Self {
+        Value::Number(f64::from(value))
+    }
+}
+
+impl<'gc> From<i32> for Value<'gc> {
+    fn from(value: i32) -> Self {
+        Value::Number(f64::from(value))
+    }
+}
+
+impl<'gc> From<u32> for Value<'gc> {
+    fn from(value: u32) -> Self {
+        Value::Number(f64::from(value))
+    }
+}
+
+impl<'gc> From<usize> for Value<'gc> {
+    fn from(value: usize) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl<'gc> From<Namespace> for Value<'gc> {
+    fn from(value: Namespace) -> Self {
+        Value::Namespace(value)
+    }
+}
+
+impl PartialEq for Value<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Undefined, Value::Undefined) => true,
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Namespace(a), Value::Namespace(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => Object::ptr_eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+impl<'gc> Value<'gc> {
+    /// Read a default value of a parameter or slot from the ABC constant pool.
+    pub fn from_abc_default_value(
+        translation_unit: &TranslationUnit,
+        default: &AbcDefaultValue,
+    ) -> Result<Self, Error> {
+        Ok(match default {
+            AbcDefaultValue::Int(i) => translation_unit.pool_int(i.0)?.into(),
+            AbcDefaultValue::Uint(u) => translation_unit.pool_uint(u.0)?.into(),
+            AbcDefaultValue::Double(d) => translation_unit.pool_double(d.0)?.into(),
+            AbcDefaultValue::String(s) => translation_unit.pool_string(s.0)?.into(),
+            AbcDefaultValue::True => true.into(),
+            AbcDefaultValue::False => false.into(),
+            AbcDefaultValue::Null => Value::Null,
+            AbcDefaultValue::Undefined => Value::Undefined,
+            AbcDefaultValue::Namespace(ns)
+            | AbcDefaultValue::Package(ns)
+            | AbcDefaultValue::PackageInternal(ns)
+            | AbcDefaultValue::Protected(ns)
+            | AbcDefaultValue::Explicit(ns)
+            | AbcDefaultValue::StaticProtected(ns)
+            | AbcDefaultValue::Private(ns) => {
+                Namespace::from_abc_namespace(translation_unit, ns.clone())?.into()
+            }
+        })
+    }
+
+    pub fn as_object(&self) -> Result<Object<'gc>, Error> {
+        match self {
+            Value::Object(object) => Ok(*object),
+            Value::Undefined => Err("Cannot access properties of undefined".into()),
+            Value::Null => Err("Cannot access properties of null".into()),
+            _ => Err(format!("Expected Object, found {:?}", self).into()),
+        }
+    }
+
+    pub fn as_namespace(&self) -> Result<Namespace, Error> {
+        match self {
+            Value::Namespace(ns) => Ok(ns.clone()),
+            _ => Err(format!("Expected Namespace, found {:?}", self).into()),
+        }
+    }
+
+    /// ECMA-262 3rd edition s. 9.2 ToBoolean
+    pub fn coerce_to_boolean(&self) -> bool {
+        match self {
+            Value::Undefined | Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => !n.is_nan() && *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::Namespace(_) | Value::Object(_) => true,
+        }
+    }
+
+    /// ECMA-262 3rd edition s. 9.1 ToPrimitive
+    ///
+    /// Objects are converted by calling `valueOf`, or `toString` if that
+    /// doesn't result in a primitive value.
+    pub fn coerce_to_primitive(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let object = match self {
+            Value::Object(object) => *object,
+            value => return Ok(value.clone()),
+        };
+
+        for method_name in &["valueOf", "toString"] {
+            let method = object.get_property(
+                object,
+                &QName::dynamic_name(method_name),
+                activation,
+                context,
+            )?;
+            if let Value::Object(method) = method {
+                let value = method.call(Some(object), &[], activation, context)?;
+                if !matches!(value, Value::Object(_)) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        Err("Object could not be converted to a primitive value".into())
+    }
+
+    /// ECMA-262 3rd edition s. 9.3 ToNumber
+    pub fn coerce_to_number(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<f64, Error> {
+        Ok(match self {
+            Value::Undefined => f64::NAN,
+            Value::Null => 0.0,
+            Value::Bool(true) => 1.0,
+            Value::Bool(false) => 0.0,
+            Value::Number(n) => *n,
+            Value::String(s) => string_to_number(s),
+            Value::Namespace(_) => f64::NAN,
+            Value::Object(_) => self
+                .coerce_to_primitive(activation, context)?
+                .coerce_to_number(activation, context)?,
+        })
+    }
+
+    /// ECMA-262 3rd edition s. 9.5 ToInt32
+    pub fn coerce_to_i32(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<i32, Error> {
+        Ok(f64_to_wrapping_i32(
+            self.coerce_to_number(activation, context)?,
+        ))
+    }
+
+    /// ECMA-262 3rd edition s. 9.6 ToUint32
+    pub fn coerce_to_u32(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<u32, Error> {
+        Ok(f64_to_wrapping_u32(
+            self.coerce_to_number(activation, context)?,
+        ))
+    }
+
+    /// ECMA-262 3rd edition s. 9.8 ToString
+    pub fn coerce_to_string(
+        &self,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<String, Error> {
+        Ok(match self {
+            Value::Undefined => "undefined".to_string(),
+            Value::Null => "null".to_string(),
+            Value::Bool(true) => "true".to_string(),
+            Value::Bool(false) => "false".to_string(),
+            Value::Number(n) => f64_to_string(*n).into_owned(),
+            Value::String(s) => s.clone(),
+            Value::Namespace(ns) => namespace_uri(ns).to_string(),
+            Value::Object(_) => self
+                .coerce_to_primitive(activation, context)?
+                .coerce_to_string(activation, context)?,
+        })
+    }
+
+    /// The result of the `typeof` operator.
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            Value::Undefined => "undefined",
+            Value::Null => "object",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Namespace(_) => "object",
+            Value::Object(object) if object.as_executable().is_some() => "function",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// ECMA-262 3rd edition s. 11.9.6 Strict equality comparison algorithm
+    pub fn strict_eq(&self, other: &Value<'gc>) -> bool {
+        self == other
+    }
+
+    /// ECMA-262 3rd edition s. 11.9.3 Abstract equality comparison algorithm
+    pub fn abstract_eq(
+        &self,
+        other: &Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<bool, Error> {
+        Ok(match (self, other) {
+            (Value::Undefined, Value::Null) | (Value::Null, Value::Undefined) => true,
+            (Value::Number(_), Value::String(_)) | (Value::String(_), Value::Number(_)) => {
+                self.coerce_to_number(activation, context)?
+                    == other.coerce_to_number(activation, context)?
+            }
+            (Value::Bool(_), _) => Value::Number(self.coerce_to_number(activation, context)?)
+                .abstract_eq(other, activation, context)?,
+            (_, Value::Bool(_)) => self.abstract_eq(
+                &Value::Number(other.coerce_to_number(activation, context)?),
+                activation,
+                context,
+            )?,
+            (Value::Number(_), Value::Object(_)) | (Value::String(_), Value::Object(_)) => {
+                let other = other.coerce_to_primitive(activation, context)?;
+                self.abstract_eq(&other, activation, context)?
+            }
+            (Value::Object(_), Value::Number(_)) | (Value::Object(_), Value::String(_)) => {
+                let this = self.coerce_to_primitive(activation, context)?;
+                this.abstract_eq(other, activation, context)?
+            }
+            _ => self == other,
+        })
+    }
+
+    /// ECMA-262 3rd edition s. 11.8.5 Abstract relational comparison algorithm
+    ///
+    /// Returns `None` if either value is `NaN`, in which case every comparison
+    /// is false.
+    pub fn abstract_lt(
+        &self,
+        other: &Value<'gc>,
+        activation: &mut Activation<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<Option<bool>, Error> {
+        let prim_self = self.coerce_to_primitive(activation, context)?;
+        let prim_other = other.coerce_to_primitive(activation, context)?;
+
+        if let (Value::String(a), Value::String(b)) = (&prim_self, &prim_other) {
+            return Ok(Some(a < b));
+        }
+
+        let num_self = prim_self.coerce_to_number(activation, context)?;
+        let num_other = prim_other.coerce_to_number(activation, context)?;
+
+        if num_self.is_nan() || num_other.is_nan() {
+            return Ok(None);
+        }
+
+        Ok(Some(num_self < num_other))
+    }
+}
+
+/// ECMA-262 3rd edition s. 9.3.1 ToNumber applied to the String type
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    if s.is_empty() {
+        return 0.0;
+    }
+
+    if s.starts_with("0x") || s.starts_with("0X") {
+        return u64::from_str_radix(&s[2..], 16)
+            .map(|n| n as f64)
+            .unwrap_or(f64::NAN);
+    }
+
+    match s {
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        _ => s.parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// The URI of a namespace, which is how namespaces are converted to strings.
+fn namespace_uri(ns: &Namespace) -> &str {
+    match ns {
+        Namespace::Namespace(uri)
+        | Namespace::Package(uri)
+        | Namespace::PackageInternal(uri)
+        | Namespace::Protected(uri)
+        | Namespace::Explicit(uri)
+        | Namespace::StaticProtected(uri)
+        | Namespace::Private(uri) => uri,
+        Namespace::Any => "*",
+    }
+}
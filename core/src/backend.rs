@@ -1,5 +1,11 @@
+pub mod accessibility;
 pub mod audio;
+pub mod font;
 pub mod input;
+pub mod log;
 pub mod navigator;
+pub mod printer;
 pub mod render;
+#[cfg(feature = "rtmp")]
+pub mod rtmp;
 pub mod storage;
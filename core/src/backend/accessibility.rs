@@ -0,0 +1,71 @@
+//! Exposing the display list to screen readers and other assistive technologies.
+
+use crate::prelude::*;
+
+/// The role of an element in the accessibility tree.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    /// A movie clip, which groups the elements of its children.
+    Group,
+
+    /// A button, or a movie clip that acts as a button.
+    Button,
+
+    /// Text that can't be edited by the user.
+    StaticText,
+
+    /// An input text field.
+    InputText,
+}
+
+/// An element of the accessibility tree, describing a display object.
+///
+/// The name, description and shortcut come from the `_accProps` object of the
+/// display object, if it has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    pub name: String,
+    pub description: String,
+    pub shortcut: String,
+
+    /// The current value of the element, such as the contents of a text field.
+    pub value: Option<String>,
+
+    /// The bounds of the element on the stage.
+    pub bounds: BoundingBox,
+
+    pub children: Vec<AccessibilityNode>,
+}
+
+/// A backend that exposes the accessibility tree to the platform accessibility APIs.
+pub trait AccessibilityBackend {
+    /// Returns whether a screen reader or other assistive technology is running.
+    ///
+    /// The accessibility tree is only built while this is true.
+    fn is_active(&self) -> bool;
+
+    /// Replaces the accessibility tree with the elements of the movie.
+    ///
+    /// This is called after every frame while the backend is active, so
+    /// backends should only update the platform when the tree has changed.
+    fn update_tree(&mut self, nodes: Vec<AccessibilityNode>);
+}
+
+/// Accessibility backend for platforms without assistive technologies.
+#[derive(Default)]
+pub struct NullAccessibilityBackend;
+
+impl NullAccessibilityBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl AccessibilityBackend for NullAccessibilityBackend {
+    fn is_active(&self) -> bool {
+        false
+    }
+
+    fn update_tree(&mut self, _nodes: Vec<AccessibilityNode>) {}
+}
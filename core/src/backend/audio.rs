@@ -1,6 +1,7 @@
 use generational_arena::{Arena, Index};
 
 pub mod decoders;
+pub mod mixer;
 pub mod swf {
     pub use swf::{
         read, AudioCompression, CharacterId, Sound, SoundEnvelope, SoundEnvelopePoint, SoundEvent,
@@ -14,6 +15,107 @@ pub type SoundInstanceHandle = Index;
 
 type Error = Box<dyn std::error::Error>;
 
+/// A source of audio that is generated at runtime rather than defined in the SWF,
+/// such as the audio track of a `NetStream` or the input of a `Microphone`.
+///
+/// Sources are attached to a movie clip with `MovieClip.attachAudio`.
+pub type AudioSource = Box<dyn Send + decoders::Decoder>;
+
+/// The volume and panning applied to a playing sound.
+///
+/// The channel values are the percentage of an input channel that is mixed
+/// into an output channel, mirroring the `Sound.setTransform` object in AVM1.
+/// `volume` is a percentage applied on top of them, set by `Sound.setVolume`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoundTransform {
+    pub volume: i32,
+    pub left_to_left: i32,
+    pub left_to_right: i32,
+    pub right_to_left: i32,
+    pub right_to_right: i32,
+}
+
+impl SoundTransform {
+    /// Applies this transform to a stereo sample frame.
+    pub fn apply(&self, [left, right]: [i16; 2]) -> [i16; 2] {
+        let mix = |l: i32, r: i32| {
+            let sample = (i32::from(left) * l + i32::from(right) * r) / 100 * self.volume / 100;
+            sample.max(i32::from(i16::MIN)).min(i32::from(i16::MAX)) as i16
+        };
+        [
+            mix(self.left_to_left, self.right_to_left),
+            mix(self.left_to_right, self.right_to_right),
+        ]
+    }
+
+    /// Returns the transform that applies `inner` first, followed by this transform.
+    ///
+    /// Sounds are affected by the transform of the clip that owns them and of
+    /// every one of its parents, as well as the global transform.
+    pub fn concat(&self, inner: &SoundTransform) -> SoundTransform {
+        let mul = |a: i32, b: i32, c: i32, d: i32| (a * b + c * d) / 100;
+        SoundTransform {
+            volume: self.volume * inner.volume / 100,
+            left_to_left: mul(
+                self.left_to_left,
+                inner.left_to_left,
+                self.right_to_left,
+                inner.left_to_right,
+            ),
+            left_to_right: mul(
+                self.left_to_right,
+                inner.left_to_left,
+                self.right_to_right,
+                inner.left_to_right,
+            ),
+            right_to_left: mul(
+                self.left_to_left,
+                inner.right_to_left,
+                self.right_to_left,
+                inner.right_to_right,
+            ),
+            right_to_right: mul(
+                self.left_to_right,
+                inner.right_to_left,
+                self.right_to_right,
+                inner.right_to_right,
+            ),
+        }
+    }
+
+    /// The balance between the left and right channels, from -100 (left) to 100 (right),
+    /// as returned by `Sound.getPan`.
+    pub fn pan(&self) -> i32 {
+        if self.left_to_left != 100 {
+            100 - self.left_to_left
+        } else {
+            self.right_to_right - 100
+        }
+    }
+
+    /// Sets the channels to play each input channel in its own speaker,
+    /// attenuating one side, like `Sound.setPan`.
+    pub fn set_pan(&mut self, pan: i32) {
+        let pan = pan.max(-100).min(100);
+        self.left_to_left = 100 - pan.max(0);
+        self.left_to_right = 0;
+        self.right_to_left = 0;
+        self.right_to_right = 100 + pan.min(0);
+    }
+}
+
+impl Default for SoundTransform {
+    fn default() -> Self {
+        Self {
+            volume: 100,
+            left_to_left: 100,
+            left_to_right: 0,
+            right_to_left: 0,
+            right_to_right: 100,
+        }
+    }
+}
+
 pub trait AudioBackend {
     fn prime_audio(&mut self) {}
     fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error>;
@@ -49,6 +151,18 @@ pub trait AudioBackend {
         handle: &swf::SoundStreamHead,
     ) -> Result<AudioStreamHandle, Error>;
 
+    /// Starts playing audio from a dynamic source, such as a `NetStream`.
+    ///
+    /// The returned instance is stopped with `stop_sound`. Backends that do not
+    /// support dynamic sources return an error, and the source is dropped.
+    fn start_source(&mut self, _source: AudioSource) -> Result<SoundInstanceHandle, Error> {
+        Err("Dynamic audio sources are not supported by this audio backend".into())
+    }
+
+    /// Sets the volume and panning of a playing sound instance.
+    /// No-op if the sound is not playing.
+    fn set_sound_transform(&mut self, _sound: SoundInstanceHandle, _transform: SoundTransform) {}
+
     /// Stops a playing sound instance.
     /// No-op if the sound is not playing.
     fn stop_sound(&mut self, sound: SoundInstanceHandle);
@@ -73,12 +187,46 @@ pub trait AudioBackend {
     /// Returns `None` if sound is not registered.
     fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32>;
 
+    /// Returns whether a sound instance is still playing.
+    ///
+    /// Sounds that finish on their own are reported as no longer playing, which
+    /// the player uses to fire `Sound.onSoundComplete`.
+    fn is_sound_playing(&self, sound: SoundInstanceHandle) -> bool;
+
+    /// Get the playback position of a sound instance in milliseconds.
+    ///
+    /// For event sounds, this is the position within the sound, used by
+    /// `Sound.position`. Returns `None` if the sound is not playing, or if the
+    /// backend is unable to report positions.
+    fn get_sound_position(&self, _sound: SoundInstanceHandle) -> Option<u32> {
+        None
+    }
+
+    /// Get the playback position of a stream sound in milliseconds,
+    /// measured from the frame where the stream started.
+    ///
+    /// The player uses this to keep the timeline in sync with streaming audio.
+    /// Returns `None` if the stream is not playing, or if the backend is unable
+    /// to report positions, in which case the timeline runs freely.
+    fn get_stream_position(&self, _stream: AudioStreamHandle) -> Option<u32> {
+        None
+    }
+
     // TODO: Eventually remove this/move it to library.
     fn is_loading_complete(&self) -> bool {
         true
     }
     fn tick(&mut self) {}
 
+    /// Sets the volume of the final mix of all sounds, from 0.0 to 1.0.
+    ///
+    /// This is the player's master volume, and is applied on top of sound transforms.
+    fn set_volume(&mut self, _volume: f32) {}
+
+    /// Suspends or resumes all audio output. Paused sounds keep their position,
+    /// and continue from it when resumed.
+    fn set_paused(&mut self, _paused: bool) {}
+
     /// Inform the audio backend of the current stage frame rate.
     ///
     /// This is only necessary if your particular audio backend needs to know
@@ -136,6 +284,10 @@ impl AudioBackend for NullAudioBackend {
         false
     }
 
+    fn is_sound_playing(&self, _sound: SoundInstanceHandle) -> bool {
+        false
+    }
+
     fn get_sound_duration(&self, _sound: SoundHandle) -> Option<u32> {
         None
     }
@@ -1,16 +1,18 @@
 //! Audio decoders.
 
 mod adpcm;
+mod flv;
 mod mp3;
 mod pcm;
 
 pub use adpcm::AdpcmDecoder;
+pub use flv::FlvAudioStream;
 pub use mp3::Mp3Decoder;
 pub use pcm::PcmDecoder;
 
 use crate::tag_utils::SwfSlice;
 use std::io::{Cursor, Read};
-use swf::{AudioCompression, SoundFormat, TagCode};
+use swf::{AudioCompression, SoundFormat, SoundStreamHead, TagCode};
 
 type Error = Box<dyn std::error::Error>;
 
@@ -88,11 +90,22 @@ struct StandardStreamDecoder {
 impl StandardStreamDecoder {
     /// Constructs a new `StandardStreamDecoder.
     /// `swf_data` should be the tag data of the MovieClip that contains the stream.
-    fn new(format: &SoundFormat, swf_data: SwfSlice) -> Result<Self, Error> {
+    fn new(stream_info: &SoundStreamHead, swf_data: SwfSlice) -> Result<Self, Error> {
+        let format = &stream_info.stream_format;
         // Create a tag reader to get the audio data from SoundStreamBlock tags.
         let tag_reader = StreamTagReader::new(format.compression, swf_data);
         // Wrap the tag reader in the decoder.
-        let decoder = make_decoder(format, tag_reader)?;
+        let mut decoder = make_decoder(format, tag_reader)?;
+
+        // MP3 streams specify the number of samples of encoder delay at the start of the stream.
+        // Skip these so that the audio lines up with the timeline.
+        // SWF19 p.185
+        if format.compression == AudioCompression::Mp3 && stream_info.latency_seek > 0 {
+            for _ in 0..stream_info.latency_seek {
+                decoder.next();
+            }
+        }
+
         Ok(Self { decoder })
     }
 }
@@ -181,13 +194,14 @@ impl Iterator for AdpcmStreamDecoder {
 /// Makes a `StreamDecoder` for the given stream. `swf_data` should be the MovieClip's tag data.
 /// Generally this will return a `StandardStreamDecoder`, except for ADPCM streams.
 pub fn make_stream_decoder(
-    format: &swf::SoundFormat,
+    stream_info: &SoundStreamHead,
     swf_data: SwfSlice,
 ) -> Result<Box<dyn Decoder + Send>, Error> {
+    let format = &stream_info.stream_format;
     let decoder: Box<dyn Decoder + Send> = if format.compression == AudioCompression::Adpcm {
         Box::new(AdpcmStreamDecoder::new(format, swf_data))
     } else {
-        Box::new(StandardStreamDecoder::new(format, swf_data)?)
+        Box::new(StandardStreamDecoder::new(stream_info, swf_data)?)
     };
     Ok(decoder)
 }
@@ -2,36 +2,101 @@ use super::{Decoder, SeekableDecoder};
 use bitstream_io::{BigEndian, BitReader};
 use std::io::{Cursor, Read};
 
+/// Decoder for the SWF variant of IMA ADPCM.
+///
+/// The stream begins with a 2-bit header giving the size of each ADPCM code.
+/// The remaining data is split into packets of 4096 sample frames. Each packet
+/// starts with the uncompressed initial sample and step index for each channel,
+/// followed by 4095 ADPCM codes per channel. The final packet may be shorter.
+/// None of this data is byte-aligned.
+/// SWF19 pp.190-191
 pub struct AdpcmDecoder<R: Read> {
     inner: BitReader<R, BigEndian>,
     sample_rate: u16,
     is_stereo: bool,
     bits_per_sample: usize,
+
+    /// The index of the next sample frame within the current packet.
     sample_num: u16,
-    left_sample: i32,
-    left_step_index: i16,
-    left_step: i32,
-    right_sample: i32,
-    right_step_index: i16,
-    right_step: i32,
+    left: AdpcmChannel,
+    right: AdpcmChannel,
+}
+
+/// The decoding state for a single channel of an ADPCM stream.
+#[derive(Debug, Default, Copy, Clone)]
+struct AdpcmChannel {
+    sample: i32,
+    step_index: i16,
 }
 
+impl AdpcmChannel {
+    /// Reads the uncompressed initial sample and step index at the start of a packet.
+    fn read_header<R: Read>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<(), std::io::Error> {
+        self.sample = reader.read_signed(16)?;
+        self.step_index = reader.read::<u16>(6)? as i16;
+        if self.step_index >= STEP_TABLE.len() as i16 {
+            self.step_index = STEP_TABLE.len() as i16 - 1;
+        }
+        Ok(())
+    }
+
+    /// Applies a single ADPCM code to the current sample.
+    fn decode(&mut self, data: i32, bits_per_sample: usize) {
+        let step = STEP_TABLE[self.step_index as usize];
+
+        // (data + 0.5) * step / 2^(bits_per_sample - 2)
+        // Data is sign-magnitude, NOT two's complement.
+        let sign_mask = 1 << (bits_per_sample - 1);
+        let magnitude = data & !sign_mask;
+        let delta = (2 * magnitude + 1) * step / sign_mask;
+
+        if (data & sign_mask) != 0 {
+            self.sample -= delta;
+        } else {
+            self.sample += delta;
+        }
+        if self.sample < i32::from(i16::MIN) {
+            self.sample = i32::from(i16::MIN);
+        } else if self.sample > i32::from(i16::MAX) {
+            self.sample = i32::from(i16::MAX);
+        }
+
+        self.step_index += INDEX_TABLE[bits_per_sample - 2][magnitude as usize];
+        if self.step_index < 0 {
+            self.step_index = 0;
+        } else if self.step_index >= STEP_TABLE.len() as i16 {
+            self.step_index = STEP_TABLE.len() as i16 - 1;
+        }
+    }
+}
+
+const INDEX_TABLE: [&[i16]; 4] = [
+    &[-1, 2],
+    &[-1, -1, 2, 4],
+    &[-1, -1, -1, -1, 2, 4, 6, 8],
+    &[-1, -1, -1, -1, -1, -1, -1, -1, 1, 2, 4, 6, 8, 10, 13, 16],
+];
+
+const STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
 impl<R: Read> AdpcmDecoder<R> {
-    const INDEX_TABLE: [&'static [i16]; 4] = [
-        &[-1, 2],
-        &[-1, -1, 2, 4],
-        &[-1, -1, -1, -1, 2, 4, 6, 8],
-        &[-1, -1, -1, -1, -1, -1, -1, -1, 1, 2, 4, 6, 8, 10, 13, 16],
-    ];
-
-    const STEP_TABLE: [i32; 89] = [
-        7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60,
-        66, 73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371,
-        408, 449, 494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878,
-        2066, 2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845,
-        8630, 9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086,
-        29794, 32767,
-    ];
+    /// The number of sample frames in each ADPCM packet, including the initial sample.
+    const SAMPLES_PER_PACKET: u16 = 4096;
+
+    /// The size of the per-channel packet header in bits (16-bit sample + 6-bit step index).
+    const PACKET_HEADER_BITS: u64 = 22;
+
+    /// The size of the stream header containing the ADPCM code size.
+    const STREAM_HEADER_BITS: u64 = 2;
 
     pub fn new(inner: R, is_stereo: bool, sample_rate: u16) -> Self {
         let mut reader = BitReader::new(inner);
@@ -41,99 +106,47 @@ impl<R: Read> AdpcmDecoder<R> {
         }) as usize
             + 2;
 
-        let left_sample = 0;
-        let left_step_index = 0;
-        let left_step = 0;
-        let right_sample = 0;
-        let right_step_index = 0;
-        let right_step = 0;
         Self {
             inner: reader,
             sample_rate,
             is_stereo,
             bits_per_sample,
             sample_num: 0,
-            left_sample,
-            left_step,
-            left_step_index,
-            right_sample,
-            right_step,
-            right_step_index,
+            left: Default::default(),
+            right: Default::default(),
+        }
+    }
+
+    /// The size of a full ADPCM packet in bits, across all channels.
+    fn packet_len_bits(&self) -> u64 {
+        let channel_bits = Self::PACKET_HEADER_BITS
+            + u64::from(Self::SAMPLES_PER_PACKET - 1) * self.bits_per_sample as u64;
+        if self.is_stereo {
+            channel_bits * 2
+        } else {
+            channel_bits
         }
     }
 
     pub fn next_sample(&mut self) -> Result<(), std::io::Error> {
         if self.sample_num == 0 {
-            // The initial sample values are NOT byte-aligned.
-            self.left_sample = self.inner.read_signed(16)?;
-            self.left_step_index = self.inner.read::<u16>(6)? as i16;
-            self.left_step = Self::STEP_TABLE[self.left_step_index as usize];
+            // Each packet begins with the uncompressed initial sample,
+            // which is also the first sample frame of the packet.
+            self.left.read_header(&mut self.inner)?;
             if self.is_stereo {
-                self.right_sample = self.inner.read_signed(16)?;
-                self.right_step_index = self.inner.read::<u16>(6)? as i16;
-                self.right_step = Self::STEP_TABLE[self.right_step_index as usize];
+                self.right.read_header(&mut self.inner)?;
             }
-        }
-
-        self.sample_num = (self.sample_num + 1) % 4095;
-
-        let data: i32 = self.inner.read::<u32>(self.bits_per_sample as u32)? as i32;
-        self.left_step = Self::STEP_TABLE[self.left_step_index as usize];
-
-        // (data + 0.5) * step / 2^(bits_per_sample - 2)
-        // Data is sign-magnitude, NOT two's complement.
-        // TODO(Herschel): Other implementations use some bit-tricks for this.
-        let sign_mask = 1 << (self.bits_per_sample - 1);
-        let magnitude = data & !sign_mask;
-        let delta = (2 * magnitude + 1) * self.left_step / sign_mask;
-
-        if (data & sign_mask) != 0 {
-            self.left_sample -= delta;
         } else {
-            self.left_sample += delta;
-        }
-        if self.left_sample < -32767 {
-            self.left_sample = -32767;
-        } else if self.left_sample > 32767 {
-            self.left_sample = 32767;
-        }
-
-        let i = magnitude as usize;
-        self.left_step_index += Self::INDEX_TABLE[self.bits_per_sample - 2][i];
-        if self.left_step_index < 0 {
-            self.left_step_index = 0;
-        } else if self.left_step_index >= Self::STEP_TABLE.len() as i16 {
-            self.left_step_index = Self::STEP_TABLE.len() as i16 - 1;
-        }
-
-        if self.is_stereo {
             let data = self.inner.read::<u32>(self.bits_per_sample as u32)? as i32;
-            self.right_step = Self::STEP_TABLE[self.right_step_index as usize];
-
-            let sign_mask = 1 << (self.bits_per_sample - 1);
-            let magnitude = data & !sign_mask;
-            let delta = (2 * magnitude + 1) * self.right_step / sign_mask;
-
-            if (data & sign_mask) != 0 {
-                self.right_sample -= delta;
-            } else {
-                self.right_sample += delta;
-            }
-            if self.right_sample < -32767 {
-                self.right_sample = -32767;
-            } else if self.right_sample > 32767 {
-                self.right_sample = 32767;
-            }
-
-            let i = magnitude as usize;
-            self.right_step_index += Self::INDEX_TABLE[self.bits_per_sample - 2][i];
-            if self.right_step_index < 0 {
-                self.right_step_index = 0;
-            } else if self.right_step_index >= Self::STEP_TABLE.len() as i16 {
-                self.right_step_index = Self::STEP_TABLE.len() as i16 - 1;
+            self.left.decode(data, self.bits_per_sample);
+            if self.is_stereo {
+                let data = self.inner.read::<u32>(self.bits_per_sample as u32)? as i32;
+                self.right.decode(data, self.bits_per_sample);
             }
         }
 
+        self.sample_num = (self.sample_num + 1) % Self::SAMPLES_PER_PACKET;
+
         Ok(())
     }
 }
@@ -143,9 +156,9 @@ impl<R: Read> Iterator for AdpcmDecoder<R> {
     fn next(&mut self) -> Option<Self::Item> {
         self.next_sample().ok()?;
         if self.is_stereo {
-            Some([self.left_sample as i16, self.right_sample as i16])
+            Some([self.left.sample as i16, self.right.sample as i16])
         } else {
-            Some([self.left_sample as i16, self.left_sample as i16])
+            Some([self.left.sample as i16, self.left.sample as i16])
         }
     }
 }
@@ -166,15 +179,165 @@ impl<R: std::io::Read> Decoder for AdpcmDecoder<R> {
     }
 }
 
+impl<R: AsRef<[u8]> + Default> AdpcmDecoder<Cursor<R>> {
+    /// Moves the underlying reader to the given bit offset from the start of the stream,
+    /// and resets the decoder to expect a packet header.
+    fn seek_to_bit(&mut self, bit_pos: u64) -> Result<(), std::io::Error> {
+        // `BitReader` can't seek, so unwrap the cursor and rebuild the reader around it.
+        let bit_stream = std::mem::replace(&mut self.inner, BitReader::new(Default::default()));
+        let mut cursor = bit_stream.into_reader();
+        cursor.set_position(bit_pos / 8);
+        self.inner = BitReader::new(cursor);
+        self.inner.skip((bit_pos % 8) as u32)?;
+        self.sample_num = 0;
+        self.left = Default::default();
+        self.right = Default::default();
+        Ok(())
+    }
+}
+
 impl<R: AsRef<[u8]> + Default> SeekableDecoder for AdpcmDecoder<Cursor<R>> {
     #[inline]
     fn reset(&mut self) {
-        // TODO: This is funky.
-        // I want to reset the `BitStream` and `Cursor` to their initial positions,
-        // but have to work around the borrowing rules of Rust.
-        let bit_stream = std::mem::replace(&mut self.inner, BitReader::new(Default::default()));
-        let mut cursor = bit_stream.into_reader();
-        cursor.set_position(0);
-        *self = AdpcmDecoder::new(cursor, self.is_stereo, self.sample_rate());
+        self.seek_to_sample_frame(0);
+    }
+
+    fn seek_to_sample_frame(&mut self, frame: u32) {
+        // Packets have a fixed size, so we can jump directly to the packet containing this
+        // frame and resync using its header. Only the remainder needs to be decoded.
+        let packet = u64::from(frame / u32::from(Self::SAMPLES_PER_PACKET));
+        let offset = frame % u32::from(Self::SAMPLES_PER_PACKET);
+        let bit_pos = Self::STREAM_HEADER_BITS + packet * self.packet_len_bits();
+        if let Err(e) = self.seek_to_bit(bit_pos) {
+            // Seeking past the end of the stream; the decoder is now exhausted.
+            log::warn!("ADPCM seek out of range: {}", e);
+            return;
+        }
+        for _ in 0..offset {
+            if self.next_sample().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitstream_io::BitWriter;
+
+    /// Encodes a mono ADPCM stream from the given packets.
+    /// Each packet is (initial sample, initial step index, codes).
+    fn encode(bits_per_sample: u32, packets: &[(i16, u8, Vec<u32>)]) -> Vec<u8> {
+        let mut data = vec![];
+        let mut writer = BitWriter::<_, BigEndian>::new(&mut data);
+        writer.write(2, bits_per_sample - 2).unwrap();
+        for (sample, step_index, codes) in packets {
+            writer.write_signed(16, *sample).unwrap();
+            writer.write(6, *step_index).unwrap();
+            for code in codes {
+                writer.write(bits_per_sample, *code).unwrap();
+            }
+        }
+        writer.byte_align().unwrap();
+        data
+    }
+
+    /// A simple deterministic sequence of ADPCM codes.
+    fn codes(bits_per_sample: u32, len: usize) -> Vec<u32> {
+        let mut state: u32 = 12345;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) % (1 << bits_per_sample)
+            })
+            .collect()
+    }
+
+    /// Returns the first `len` samples of the left channel.
+    /// Trailing padding bits may decode as extra samples, so these are ignored.
+    fn samples<R: Read>(decoder: AdpcmDecoder<R>, len: usize) -> Vec<i16> {
+        decoder.map(|[l, _]| l).take(len).collect()
+    }
+
+    #[test]
+    fn decode_2bit() {
+        let data = encode(2, &[(0, 0, vec![1, 1, 3, 2])]);
+        let decoder = AdpcmDecoder::new(Cursor::new(data), false, 44100);
+        // The initial sample is output as the first sample frame.
+        // Subsequent samples are decoded by hand from the SWF19 algorithm.
+        assert_eq!(samples(decoder, 5), vec![0, 10, 23, 7, 1]);
+    }
+
+    #[test]
+    fn decode_4bit() {
+        let data = encode(4, &[(1000, 10, vec![0b0111, 0b1111, 0b0000])]);
+        let decoder = AdpcmDecoder::new(Cursor::new(data), false, 44100);
+        assert_eq!(samples(decoder, 4), vec![1000, 1035, 959, 970]);
+    }
+
+    #[test]
+    fn decode_clamps_samples() {
+        let data = encode(5, &[(32760, 63, vec![0b01111])]);
+        let decoder = AdpcmDecoder::new(Cursor::new(data), false, 44100);
+        assert_eq!(samples(decoder, 2), vec![32760, 32767]);
+
+        let data = encode(5, &[(-32760, 63, vec![0b11111])]);
+        let decoder = AdpcmDecoder::new(Cursor::new(data), false, 44100);
+        assert_eq!(samples(decoder, 2), vec![-32760, -32768]);
+    }
+
+    #[test]
+    fn decode_stereo() {
+        let mut data = vec![];
+        let mut writer = BitWriter::<_, BigEndian>::new(&mut data);
+        writer.write(2, 0u8).unwrap();
+        writer.write_signed(16, 0i16).unwrap();
+        writer.write(6, 0u8).unwrap();
+        writer.write_signed(16, 100i16).unwrap();
+        writer.write(6, 0u8).unwrap();
+        writer.write(2, 1u8).unwrap();
+        writer.write(2, 3u8).unwrap();
+        writer.byte_align().unwrap();
+
+        let decoder = AdpcmDecoder::new(Cursor::new(data), true, 44100);
+        assert_eq!(
+            decoder.take(2).collect::<Vec<_>>(),
+            vec![[0, 100], [10, 90]]
+        );
+    }
+
+    #[test]
+    fn seek_matches_linear_decode() {
+        for bits_per_sample in 2..=5 {
+            let packets = vec![
+                (100, 5, codes(bits_per_sample, 4095)),
+                (-2000, 40, codes(bits_per_sample, 4095)),
+                (3000, 20, codes(bits_per_sample, 1000)),
+            ];
+            let data = encode(bits_per_sample, &packets);
+            let num_samples = 4096 + 4096 + 1001;
+            let decoder = AdpcmDecoder::new(Cursor::new(data.clone()), false, 44100);
+            let expected = samples(decoder, num_samples);
+            assert_eq!(expected.len(), num_samples);
+
+            let mut decoder = AdpcmDecoder::new(Cursor::new(data), false, 44100);
+            for &frame in &[0, 1, 4095, 4096, 4097, 8191, 8192, 9000, 9192] {
+                decoder.seek_to_sample_frame(frame);
+                assert_eq!(
+                    decoder.next().map(|[l, _]| l),
+                    Some(expected[frame as usize]),
+                    "{}-bit ADPCM seek to frame {}",
+                    bits_per_sample,
+                    frame
+                );
+            }
+
+            decoder.seek_to_sample_frame(20000);
+            assert_eq!(decoder.next(), None);
+
+            decoder.reset();
+            assert_eq!(samples(decoder, num_samples), expected);
+        }
     }
 }
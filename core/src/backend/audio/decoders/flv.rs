@@ -0,0 +1,161 @@
+use super::{make_decoder, Decoder};
+use crate::backend::audio::AudioSource;
+use gc_arena::Collect;
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex, Weak};
+
+type Error = Box<dyn std::error::Error>;
+
+/// The sample rate that the audio of every packet is converted to.
+const SAMPLE_RATE: u16 = 44100;
+
+/// The audio track of a stream that arrives in FLV audio packets as it plays,
+/// such as the audio of a `NetStream`.
+///
+/// Each source made with `source` plays the audio that arrives after it was made.
+/// Sources play silence while they wait for more audio, and end once the stream
+/// is closed or dropped and they have played everything that arrived.
+#[derive(Collect, Default)]
+#[collect(require_static)]
+pub struct FlvAudioStream {
+    sources: Vec<Weak<Mutex<QueuedAudio>>>,
+}
+
+/// The audio waiting to be played by a source.
+#[derive(Default)]
+struct QueuedAudio {
+    frames: VecDeque<[i16; 2]>,
+    is_closed: bool,
+}
+
+impl FlvAudioStream {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Makes a source that plays the audio of this stream from now on.
+    pub fn source(&mut self) -> AudioSource {
+        let queue = Arc::new(Mutex::new(QueuedAudio::default()));
+        self.sources.push(Arc::downgrade(&queue));
+        Box::new(FlvAudioSource(queue))
+    }
+
+    /// Decodes the body of an FLV audio tag, which is a sound format byte followed
+    /// by the audio data, and queues it for every source.
+    pub fn push_packet(&mut self, packet: &[u8]) -> Result<(), Error> {
+        // The mixer drops sources once they're stopped, such as when their clip
+        // attaches another source.
+        self.sources.retain(|source| source.strong_count() > 0);
+        if self.sources.is_empty() {
+            return Ok(());
+        }
+
+        let mut reader = swf::read::Reader::new(packet, 0);
+        let format = reader.read_sound_format()?;
+        let mut decoder = make_decoder(&format, Cursor::new(*reader.get_ref()))?;
+        let frames: Vec<_> = decoder.by_ref().collect();
+        let sample_rate = usize::from(decoder.sample_rate().max(1));
+
+        // Convert the audio to the sample rate of the sources by repeating or
+        // skipping frames.
+        let len = frames.len() * usize::from(SAMPLE_RATE) / sample_rate;
+        let frames = (0..len).map(|i| frames[i * sample_rate / usize::from(SAMPLE_RATE)]);
+        let sources: Vec<_> = self.sources.iter().filter_map(Weak::upgrade).collect();
+        for frame in frames {
+            for source in &sources {
+                source.lock().unwrap().frames.push_back(frame);
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the sources once they've played the audio that has already arrived.
+    pub fn close(&mut self) {
+        for source in self.sources.drain(..).filter_map(|source| source.upgrade()) {
+            source.lock().unwrap().is_closed = true;
+        }
+    }
+}
+
+impl Drop for FlvAudioStream {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Plays the audio that an `FlvAudioStream` queues for it.
+struct FlvAudioSource(Arc<Mutex<QueuedAudio>>);
+
+impl Iterator for FlvAudioSource {
+    type Item = [i16; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut queue = self.0.lock().unwrap();
+        match queue.frames.pop_front() {
+            Some(frame) => Some(frame),
+            // Keep playing silence until more audio arrives.
+            None if !queue.is_closed => Some([0, 0]),
+            None => None,
+        }
+    }
+}
+
+impl Decoder for FlvAudioSource {
+    fn num_channels(&self) -> u8 {
+        2
+    }
+
+    fn sample_rate(&self) -> u16 {
+        SAMPLE_RATE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A packet of 16-bit mono PCM audio at 22kHz.
+    fn pcm_packet(samples: &[i16]) -> Vec<u8> {
+        let mut packet = vec![0b0011_1010];
+        for sample in samples {
+            packet.extend_from_slice(&sample.to_le_bytes());
+        }
+        packet
+    }
+
+    #[test]
+    fn play_packets() {
+        let mut stream = FlvAudioStream::new();
+        stream.push_packet(&pcm_packet(&[100])).unwrap();
+
+        let mut source = stream.source();
+        assert_eq!(source.next(), Some([0, 0]));
+        stream.push_packet(&pcm_packet(&[1000, 2000])).unwrap();
+        stream.close();
+        let frames: Vec<_> = source.collect();
+        assert_eq!(
+            frames,
+            [[1000, 1000], [1000, 1000], [2000, 2000], [2000, 2000]]
+        );
+    }
+
+    #[test]
+    fn dropped_stream_ends_sources() {
+        let mut stream = FlvAudioStream::new();
+        let mut source = stream.source();
+        stream.push_packet(&pcm_packet(&[1000])).unwrap();
+        drop(stream);
+        assert_eq!(source.next(), Some([1000, 1000]));
+        assert_eq!(source.next(), Some([1000, 1000]));
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn stopped_sources_are_forgotten() {
+        let mut stream = FlvAudioStream::new();
+        drop(stream.source());
+        stream.push_packet(&pcm_packet(&[1000])).unwrap();
+        assert!(stream.sources.is_empty());
+    }
+}
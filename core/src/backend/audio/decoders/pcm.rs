@@ -79,7 +79,8 @@ impl<R: AsRef<[u8]>> SeekableDecoder for PcmDecoder<Cursor<R>> {
 
     #[inline]
     fn seek_to_sample_frame(&mut self, frame: u32) {
-        let pos = u64::from(frame) * u64::from(self.num_channels()) * 2;
+        let bytes_per_sample = if self.is_16_bit { 2 } else { 1 };
+        let pos = u64::from(frame) * u64::from(self.num_channels()) * bytes_per_sample;
         self.inner.set_position(pos);
     }
 }
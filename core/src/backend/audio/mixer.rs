@@ -0,0 +1,931 @@
+//! Software mixing of playing sounds.
+//!
+//! Backends that can only play a plain stream of samples own an `AudioMixer`
+//! and pull mixed audio out of it as their output device needs it. The mixer
+//! keeps track of every playing sound, so these backends get positions,
+//! looping, envelopes and sound transforms for free.
+
+use super::decoders::{self, AdpcmDecoder, Mp3Decoder, PcmDecoder, SeekableDecoder};
+use super::{
+    swf, AudioSource, AudioStreamHandle, SoundHandle, SoundInstanceHandle, SoundTransform,
+};
+use crate::tag_utils::SwfSlice;
+use generational_arena::Arena;
+use std::io::Cursor;
+use std::sync::Arc;
+use swf::AudioCompression;
+
+type Error = Box<dyn std::error::Error>;
+
+/// A stream of stereo sample frames at the output sample rate.
+type Signal = Box<dyn Send + Iterator<Item = [i16; 2]>>;
+
+/// Mixes all playing sounds into stereo sample frames at a fixed sample rate.
+pub struct AudioMixer {
+    sounds: Arena<Sound>,
+    sound_instances: Arena<SoundInstance>,
+
+    /// The sample rate of the mixed output, in Hz.
+    output_sample_rate: u32,
+
+    /// The volume that the final mix is scaled by, from 0.0 to 1.0.
+    volume: f32,
+
+    /// Whether the output is silenced, with every sound held at its position.
+    is_paused: bool,
+
+    /// The number of sample frames of output that have been mixed, including the
+    /// silence mixed while paused.
+    output_frames: u64,
+}
+
+/// Contains the data and metadata for a sound in an SWF file.
+/// A `Sound` is defined by the `DefineSound` SWF tags.
+struct Sound {
+    format: swf::SoundFormat,
+    data: Arc<Vec<u8>>,
+
+    /// Number of samples in this audio.
+    /// This does not include the skip_sample_frames.
+    num_sample_frames: u32,
+
+    /// Number of samples to skip encoder delay.
+    skip_sample_frames: u16,
+}
+
+/// An actively playing instance of a sound.
+/// This sound can be either an event sound (`StartSound`), a stream sound
+/// (`SoundStreamBlock`), or a dynamic audio source.
+struct SoundInstance {
+    /// The handle the sound definition inside `sounds`.
+    /// `None` if this is a stream sound or a dynamic source.
+    handle: Option<SoundHandle>,
+
+    /// The audio stream, resampled to the output sample rate.
+    signal: Signal,
+
+    /// Flag indicating whether this sound is still playing.
+    /// If this flag is false, the sound will be cleaned up after the next mix.
+    active: bool,
+
+    /// The number of sample frames of this sound that have been mixed into the output.
+    output_frames: u64,
+
+    /// The frame of the output where this sound started playing.
+    start_frame: u64,
+
+    /// The position in the sound where playback started, in milliseconds.
+    start_position: u32,
+
+    /// The length of one loop of the sound in milliseconds, or `None` if it doesn't loop.
+    loop_duration: Option<u32>,
+
+    /// The volume and panning applied to this sound as it is mixed.
+    transform: SoundTransform,
+}
+
+impl AudioMixer {
+    pub fn new(output_sample_rate: u32) -> Self {
+        Self {
+            sounds: Arena::new(),
+            sound_instances: Arena::new(),
+            output_sample_rate,
+            volume: 1.0,
+            is_paused: false,
+            output_frames: 0,
+        }
+    }
+
+    pub fn output_sample_rate(&self) -> u32 {
+        self.output_sample_rate
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    /// The number of sample frames of output that have been mixed.
+    pub fn output_frames(&self) -> u64 {
+        self.output_frames
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    pub fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error> {
+        // Slice off latency seek for MP3 data.
+        let (skip_sample_frames, data) = if swf_sound.format.compression == AudioCompression::Mp3 {
+            if swf_sound.data.len() < 2 {
+                return Err("MP3 sound is missing its seek samples".into());
+            }
+            let skip_sample_frames =
+                u16::from(swf_sound.data[0]) | (u16::from(swf_sound.data[1]) << 8);
+            (skip_sample_frames, &swf_sound.data[2..])
+        } else {
+            (0, &swf_sound.data[..])
+        };
+
+        let sound = Sound {
+            format: swf_sound.format.clone(),
+            data: Arc::new(data.to_vec()),
+            num_sample_frames: swf_sound.num_samples,
+            skip_sample_frames,
+        };
+        Ok(self.sounds.insert(sound))
+    }
+
+    pub fn start_sound(
+        &mut self,
+        sound_handle: SoundHandle,
+        settings: &swf::SoundInfo,
+    ) -> Result<SoundInstanceHandle, Error> {
+        let sound = self.sounds.get(sound_handle).ok_or("Unknown sound")?;
+        let data = Cursor::new(VecAsRef(Arc::clone(&sound.data)));
+        let sample_rate = sound.format.sample_rate;
+
+        // `in_sample` and `out_sample` are always in 44.1kHz samples.
+        let start_position = (u64::from(settings.in_sample.unwrap_or(0)) * 1000 / 44100) as u32;
+        let end_position = match settings.out_sample {
+            Some(out_sample) => (u64::from(out_sample) * 1000 / 44100) as u32,
+            None => {
+                (u64::from(sound.num_sample_frames) * 1000 / u64::from(sample_rate.max(1))) as u32
+            }
+        };
+        let loop_duration = if settings.num_loops > 1 && end_position > start_position {
+            Some(end_position - start_position)
+        } else {
+            None
+        };
+
+        let signal: Signal = if sound.skip_sample_frames == 0
+            && settings.in_sample.is_none()
+            && settings.out_sample.is_none()
+            && settings.num_loops <= 1
+            && settings.envelope.is_none()
+        {
+            // For simple event sounds, just decode the data as is.
+            let decoder = decoders::make_decoder(&sound.format, data)?;
+            Box::new(self.make_resampler(sample_rate, decoder))
+        } else {
+            // For event sounds with envelopes/other properties, wrap it in `EventSoundSignal`.
+            let decoder = make_seekable_decoder(&sound.format, data)?;
+            let signal = EventSoundSignal::new_with_settings(
+                decoder,
+                settings,
+                sound.num_sample_frames,
+                sound.skip_sample_frames,
+            );
+            Box::new(self.make_resampler(sample_rate, signal))
+        };
+
+        Ok(self.sound_instances.insert(SoundInstance {
+            handle: Some(sound_handle),
+            signal,
+            active: true,
+            output_frames: 0,
+            start_frame: self.output_frames,
+            start_position,
+            loop_duration,
+            transform: Default::default(),
+        }))
+    }
+
+    pub fn start_stream(
+        &mut self,
+        clip_data: SwfSlice,
+        stream_info: &swf::SoundStreamHead,
+    ) -> Result<AudioStreamHandle, Error> {
+        // The audio data for stream sounds is distributed among the frames of a
+        // movie clip. The stream tag reader will parse through the SWF and
+        // feed the decoder audio data on the fly.
+        let decoder = decoders::make_stream_decoder(stream_info, clip_data)?;
+        let signal = self.make_resampler(stream_info.stream_format.sample_rate, decoder);
+        Ok(self.insert_signal(Box::new(signal)))
+    }
+
+    pub fn start_source(&mut self, source: AudioSource) -> Result<SoundInstanceHandle, Error> {
+        let sample_rate = source.sample_rate();
+        let signal = self.make_resampler(sample_rate, source);
+        Ok(self.insert_signal(Box::new(signal)))
+    }
+
+    pub fn set_sound_transform(&mut self, sound: SoundInstanceHandle, transform: SoundTransform) {
+        if let Some(instance) = self.sound_instances.get_mut(sound) {
+            instance.transform = transform;
+        }
+    }
+
+    pub fn stop_sound(&mut self, sound: SoundInstanceHandle) {
+        self.sound_instances.remove(sound);
+    }
+
+    pub fn stop_all_sounds(&mut self) {
+        self.sound_instances.clear();
+    }
+
+    pub fn stop_sounds_with_handle(&mut self, handle: SoundHandle) {
+        let handle = Some(handle);
+        self.sound_instances
+            .retain(|_, instance| instance.handle != handle);
+    }
+
+    pub fn is_sound_playing(&self, sound: SoundInstanceHandle) -> bool {
+        self.sound_instances
+            .get(sound)
+            .map_or(false, |instance| instance.active)
+    }
+
+    pub fn is_sound_playing_with_handle(&self, handle: SoundHandle) -> bool {
+        let handle = Some(handle);
+        self.sound_instances
+            .iter()
+            .any(|(_, instance)| instance.handle == handle && instance.active)
+    }
+
+    pub fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32> {
+        let sound = self.sounds.get(sound)?;
+        // AS duration does not subtract skip_sample_frames.
+        let num_sample_frames = u64::from(sound.num_sample_frames);
+        let ms = num_sample_frames * 1000 / u64::from(sound.format.sample_rate.max(1));
+        Some(ms as u32)
+    }
+
+    /// Returns the playback position of a sound instance in milliseconds.
+    ///
+    /// For event sounds, this is the position within the sound, which wraps
+    /// around as the sound loops. For streams and dynamic sources, this is the
+    /// time since playback started.
+    pub fn get_sound_position(&self, sound: SoundInstanceHandle) -> Option<u32> {
+        let instance = self.sound_instances.get(sound)?;
+        let elapsed = instance.output_frames * 1000 / u64::from(self.output_sample_rate.max(1));
+        let elapsed = match instance.loop_duration {
+            Some(loop_duration) => elapsed % u64::from(loop_duration),
+            None => elapsed,
+        };
+        Some(instance.start_position + elapsed as u32)
+    }
+
+    /// Returns the frame of the output where a sound instance started playing.
+    pub fn get_sound_start_frame(&self, sound: SoundInstanceHandle) -> Option<u64> {
+        self.sound_instances
+            .get(sound)
+            .map(|instance| instance.start_frame)
+    }
+
+    /// Fills `output` with the next sample frames of all playing sounds mixed together.
+    /// Sounds that finish playing are removed. While paused, the output is silent.
+    pub fn mix(&mut self, output: &mut [[i16; 2]]) {
+        self.output_frames += output.len() as u64;
+        if self.is_paused {
+            for output_frame in output.iter_mut() {
+                *output_frame = [0, 0];
+            }
+            return;
+        }
+
+        for output_frame in output.iter_mut() {
+            let mut mixed = [0i32; 2];
+            for (_, sound) in self.sound_instances.iter_mut() {
+                if !sound.active {
+                    continue;
+                }
+                if let Some(frame) = sound.signal.next() {
+                    let [left, right] = sound.transform.apply(frame);
+                    mixed[0] += i32::from(left);
+                    mixed[1] += i32::from(right);
+                    sound.output_frames += 1;
+                } else {
+                    sound.active = false;
+                }
+            }
+
+            let volume = self.volume;
+            let scale = |sample: i32| {
+                let sample = (sample as f32 * volume) as i32;
+                sample.max(i32::from(i16::MIN)).min(i32::from(i16::MAX)) as i16
+            };
+            *output_frame = [scale(mixed[0]), scale(mixed[1])];
+        }
+
+        // Remove all dead sounds.
+        self.sound_instances.retain(|_, sound| sound.active);
+    }
+
+    fn insert_signal(&mut self, signal: Signal) -> SoundInstanceHandle {
+        self.sound_instances.insert(SoundInstance {
+            handle: None,
+            signal,
+            active: true,
+            output_frames: 0,
+            start_frame: self.output_frames,
+            start_position: 0,
+            loop_duration: None,
+            transform: Default::default(),
+        })
+    }
+
+    /// Resamples a stream to the output sample rate.
+    fn make_resampler<I: Iterator<Item = [i16; 2]>>(
+        &self,
+        sample_rate: u16,
+        source: I,
+    ) -> Resampler<I> {
+        Resampler::new(source, sample_rate.into(), self.output_sample_rate)
+    }
+}
+
+/// Implements the methods of `AudioBackend` that play sounds by forwarding them
+/// to an `AudioMixer`, for backends that only have to feed mixed audio to a device.
+///
+/// The argument is the name of a field of type `Arc<Mutex<AudioMixer>>`.
+///
+/// `get_stream_position` is left to the backend, as the mix runs ahead of what
+/// the device is playing by however much audio the device buffers.
+#[macro_export]
+macro_rules! impl_audio_mixer_backend {
+    ($mixer:ident) => {
+        fn register_sound(
+            &mut self,
+            swf_sound: &$crate::backend::audio::swf::Sound,
+        ) -> Result<$crate::backend::audio::SoundHandle, Box<dyn std::error::Error>> {
+            self.$mixer.lock().unwrap().register_sound(swf_sound)
+        }
+
+        fn start_sound(
+            &mut self,
+            sound: $crate::backend::audio::SoundHandle,
+            settings: &$crate::backend::audio::swf::SoundInfo,
+        ) -> Result<$crate::backend::audio::SoundInstanceHandle, Box<dyn std::error::Error>> {
+            self.$mixer.lock().unwrap().start_sound(sound, settings)
+        }
+
+        fn start_stream(
+            &mut self,
+            _clip_id: $crate::backend::audio::swf::CharacterId,
+            _clip_frame: u16,
+            clip_data: $crate::tag_utils::SwfSlice,
+            stream_info: &$crate::backend::audio::swf::SoundStreamHead,
+        ) -> Result<$crate::backend::audio::AudioStreamHandle, Box<dyn std::error::Error>> {
+            self.$mixer
+                .lock()
+                .unwrap()
+                .start_stream(clip_data, stream_info)
+        }
+
+        fn start_source(
+            &mut self,
+            source: $crate::backend::audio::AudioSource,
+        ) -> Result<$crate::backend::audio::SoundInstanceHandle, Box<dyn std::error::Error>> {
+            self.$mixer.lock().unwrap().start_source(source)
+        }
+
+        fn set_sound_transform(
+            &mut self,
+            sound: $crate::backend::audio::SoundInstanceHandle,
+            transform: $crate::backend::audio::SoundTransform,
+        ) {
+            self.$mixer
+                .lock()
+                .unwrap()
+                .set_sound_transform(sound, transform)
+        }
+
+        fn stop_sound(&mut self, sound: $crate::backend::audio::SoundInstanceHandle) {
+            self.$mixer.lock().unwrap().stop_sound(sound)
+        }
+
+        fn stop_stream(&mut self, stream: $crate::backend::audio::AudioStreamHandle) {
+            self.$mixer.lock().unwrap().stop_sound(stream)
+        }
+
+        fn stop_all_sounds(&mut self) {
+            self.$mixer.lock().unwrap().stop_all_sounds()
+        }
+
+        fn stop_sounds_with_handle(&mut self, handle: $crate::backend::audio::SoundHandle) {
+            self.$mixer.lock().unwrap().stop_sounds_with_handle(handle)
+        }
+
+        fn is_sound_playing(&self, sound: $crate::backend::audio::SoundInstanceHandle) -> bool {
+            self.$mixer.lock().unwrap().is_sound_playing(sound)
+        }
+
+        fn is_sound_playing_with_handle(
+            &mut self,
+            handle: $crate::backend::audio::SoundHandle,
+        ) -> bool {
+            self.$mixer
+                .lock()
+                .unwrap()
+                .is_sound_playing_with_handle(handle)
+        }
+
+        fn get_sound_duration(&self, sound: $crate::backend::audio::SoundHandle) -> Option<u32> {
+            self.$mixer.lock().unwrap().get_sound_duration(sound)
+        }
+
+        fn get_sound_position(
+            &self,
+            sound: $crate::backend::audio::SoundInstanceHandle,
+        ) -> Option<u32> {
+            self.$mixer.lock().unwrap().get_sound_position(sound)
+        }
+
+        fn set_volume(&mut self, volume: f32) {
+            self.$mixer.lock().unwrap().set_volume(volume)
+        }
+
+        fn set_paused(&mut self, paused: bool) {
+            self.$mixer.lock().unwrap().set_paused(paused)
+        }
+    };
+}
+
+/// Instantiate a seekable decoder for the compression that the sound data uses.
+fn make_seekable_decoder(
+    format: &swf::SoundFormat,
+    data: Cursor<VecAsRef>,
+) -> Result<Box<dyn Send + SeekableDecoder>, Error> {
+    let decoder: Box<dyn Send + SeekableDecoder> = match format.compression {
+        AudioCompression::Uncompressed | AudioCompression::UncompressedUnknownEndian => Box::new(
+            PcmDecoder::new(data, format.is_stereo, format.sample_rate, format.is_16_bit),
+        ),
+        AudioCompression::Adpcm => Box::new(AdpcmDecoder::new(
+            data,
+            format.is_stereo,
+            format.sample_rate,
+        )),
+        AudioCompression::Mp3 => Box::new(Mp3Decoder::new(
+            if format.is_stereo { 2 } else { 1 },
+            format.sample_rate.into(),
+            data,
+        )),
+        _ => {
+            let msg = format!(
+                "make_seekable_decoder: Unhandled audio compression {:?}",
+                format.compression
+            );
+            log::error!("{}", msg);
+            return Err(msg.into());
+        }
+    };
+    Ok(decoder)
+}
+
+/// A dummy wrapper struct to implement `AsRef<[u8]>` for `Arc<Vec<u8>`.
+/// Not having this trait causes problems when trying to use `Cursor<Vec<u8>>`.
+struct VecAsRef(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for VecAsRef {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Default for VecAsRef {
+    fn default() -> Self {
+        VecAsRef(Arc::new(vec![]))
+    }
+}
+
+/// Converts a stream of sample frames to another sample rate by linear interpolation.
+struct Resampler<I> {
+    source: I,
+
+    /// How far to advance through the source for each output frame.
+    step: f64,
+
+    /// The position between `prev` and `next` of the next output frame, from 0 to 1.
+    position: f64,
+
+    prev: Option<[i16; 2]>,
+    next: Option<[i16; 2]>,
+}
+
+impl<I: Iterator<Item = [i16; 2]>> Resampler<I> {
+    fn new(mut source: I, source_sample_rate: u32, output_sample_rate: u32) -> Self {
+        let prev = source.next();
+        let next = source.next();
+        Self {
+            source,
+            step: f64::from(source_sample_rate) / f64::from(output_sample_rate.max(1)),
+            position: 0.0,
+            prev,
+            next,
+        }
+    }
+}
+
+impl<I: Iterator<Item = [i16; 2]>> Iterator for Resampler<I> {
+    type Item = [i16; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev = self.prev?;
+        // The last frame of the source is held until the source is exhausted.
+        let next = self.next.unwrap_or(prev);
+        let lerp = |a: i16, b: i16| {
+            (f64::from(a) + (f64::from(b) - f64::from(a)) * self.position).round() as i16
+        };
+        let frame = [lerp(prev[0], next[0]), lerp(prev[1], next[1])];
+
+        self.position += self.step;
+        while self.position >= 1.0 && self.prev.is_some() {
+            self.position -= 1.0;
+            self.prev = self.next;
+            self.next = self.source.next();
+        }
+        Some(frame)
+    }
+}
+
+/// A signal for event sound instances using sound settings (looping, start/end point, envelope).
+///
+/// The decoder only seeks once, to the in point. The sample frames of the first loop
+/// are kept as they are decoded, and later loops play them back, so loops join up
+/// exactly and the sound isn't decoded again.
+struct EventSoundSignal {
+    decoder: Box<dyn SeekableDecoder + Send>,
+    envelope: Option<Envelope>,
+
+    /// The number of times left to play the sound after the current loop.
+    loops_remaining: u16,
+
+    /// The number of sample frames between the in and out points.
+    loop_length: u32,
+
+    /// The position of the next sample frame within the loop.
+    position: u32,
+
+    /// The decoded sample frames of the loop. Empty if the sound doesn't loop.
+    loop_buffer: Vec<[i16; 2]>,
+
+    /// Whether the current loop plays back from `loop_buffer`.
+    is_replaying: bool,
+
+    is_exhausted: bool,
+}
+
+impl EventSoundSignal {
+    fn new_with_settings(
+        mut decoder: Box<dyn SeekableDecoder + Send>,
+        settings: &swf::SoundInfo,
+        num_sample_frames: u32,
+        skip_sample_frames: u16,
+    ) -> Self {
+        // The in and out points are always in 44.1kHz samples, whatever the sample
+        // rate of the sound is.
+        let sample_rate = u64::from(decoder.sample_rate());
+        let to_sample_frame = |sample: u32| (u64::from(sample) * sample_rate / 44100) as u32;
+        let start_sample_frame = settings.in_sample.map_or(0, to_sample_frame);
+        let end_sample_frame = settings
+            .out_sample
+            .map_or(num_sample_frames, to_sample_frame)
+            .min(num_sample_frames);
+
+        // MP3 encoders add silence to the start of the sound, which isn't counted
+        // in its length.
+        decoder.seek_to_sample_frame(start_sample_frame + u32::from(skip_sample_frames));
+
+        let envelope = settings
+            .envelope
+            .clone()
+            .map(|envelope| Envelope::new(envelope, decoder.sample_rate()));
+        let loops_remaining = settings.num_loops.max(1) - 1;
+        let loop_length = end_sample_frame.saturating_sub(start_sample_frame);
+        let loop_buffer = if loops_remaining > 0 {
+            Vec::with_capacity(loop_length as usize)
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            decoder,
+            envelope,
+            loops_remaining,
+            loop_length,
+            position: 0,
+            loop_buffer,
+            is_replaying: false,
+            is_exhausted: false,
+        }
+    }
+
+    /// Starts the next loop from the in point, or ends the sound after the last loop.
+    fn next_loop(&mut self) {
+        if self.loops_remaining > 0 {
+            self.loops_remaining -= 1;
+            self.position = 0;
+            self.is_replaying = true;
+        } else {
+            self.is_exhausted = true;
+        }
+    }
+}
+
+impl Iterator for EventSoundSignal {
+    type Item = [i16; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Loop the sound if necessary, and get the next frame.
+        let frame = loop {
+            if self.is_exhausted {
+                return None;
+            }
+            if self.position >= self.loop_length {
+                self.next_loop();
+                continue;
+            }
+
+            let frame = if self.is_replaying {
+                self.loop_buffer.get(self.position as usize).copied()
+            } else {
+                let frame = self.decoder.next();
+                if let (Some(frame), true) = (frame, self.loops_remaining > 0) {
+                    self.loop_buffer.push(frame);
+                }
+                frame
+            };
+
+            if let Some(frame) = frame {
+                self.position += 1;
+                break frame;
+            }
+            // The sound data ended before the out point.
+            self.loop_length = self.position;
+        };
+
+        if let Some(envelope) = &mut self.envelope {
+            let [left_volume, right_volume] = envelope.next_volume();
+            Some([
+                (f32::from(frame[0]) * left_volume) as i16,
+                (f32::from(frame[1]) * right_volume) as i16,
+            ])
+        } else {
+            Some(frame)
+        }
+    }
+}
+
+/// The sound envelope of an event sound.
+/// The sound gets multiplied by the envelope for volume/panning effects.
+struct Envelope {
+    /// Iterator through the envelope points specified in the SWF file.
+    points: std::vec::IntoIter<swf::SoundEnvelopePoint>,
+
+    /// The starting envelope point.
+    prev_point: swf::SoundEnvelopePoint,
+
+    /// The ending envelope point.
+    next_point: swf::SoundEnvelopePoint,
+
+    /// The current position in the sound, in 44.1kHz samples.
+    cur_sample: u32,
+
+    /// The number of 44.1kHz samples in each sample frame of the sound.
+    step: u32,
+}
+
+impl Envelope {
+    fn new(envelope: swf::SoundEnvelope, sample_rate: u16) -> Self {
+        let mut points = envelope.into_iter();
+        let first_point = points.next().unwrap_or_else(|| swf::SoundEnvelopePoint {
+            sample: 0,
+            left_volume: 1.0,
+            right_volume: 1.0,
+        });
+        Self {
+            // The initial volume is the first point's volume.
+            prev_point: swf::SoundEnvelopePoint {
+                sample: 0,
+                left_volume: first_point.left_volume,
+                right_volume: first_point.right_volume,
+            },
+            next_point: first_point,
+            cur_sample: 0,
+            step: (44100 / u32::from(sample_rate.max(1))).max(1),
+            points,
+        }
+    }
+
+    /// Returns the volume of the left and right channels for the next sample frame.
+    fn next_volume(&mut self) -> [f32; 2] {
+        // Calculate interpolated volume.
+        let out = if self.prev_point.sample < self.next_point.sample {
+            let a = f64::from(self.cur_sample - self.prev_point.sample);
+            let b = f64::from(self.next_point.sample - self.prev_point.sample);
+            let lerp = (a / b) as f32;
+            let prev = &self.prev_point;
+            let next = &self.next_point;
+            [
+                prev.left_volume + (next.left_volume - prev.left_volume) * lerp,
+                prev.right_volume + (next.right_volume - prev.right_volume) * lerp,
+            ]
+        } else {
+            [self.next_point.left_volume, self.next_point.right_volume]
+        };
+
+        // Update envelope endpoints.
+        self.cur_sample = self.cur_sample.saturating_add(self.step);
+        while self.cur_sample > self.next_point.sample {
+            self.prev_point = self.next_point.clone();
+            self.next_point = self
+                .points
+                .next()
+                .unwrap_or_else(|| swf::SoundEnvelopePoint {
+                    sample: std::u32::MAX,
+                    left_volume: self.prev_point.left_volume,
+                    right_volume: self.prev_point.right_volume,
+                });
+
+            if self.prev_point.sample > self.next_point.sample {
+                self.next_point.sample = self.prev_point.sample;
+                log::error!("Invalid sound envelope; sample indices are out of order");
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mono 16-bit PCM sound of the given samples at 11025Hz.
+    fn pcm_sound(samples: &[i16]) -> swf::Sound {
+        swf::Sound {
+            id: 1,
+            format: swf::SoundFormat {
+                compression: AudioCompression::Uncompressed,
+                sample_rate: 11025,
+                is_16_bit: true,
+                is_stereo: false,
+            },
+            num_samples: samples.len() as u32,
+            data: samples
+                .iter()
+                .flat_map(|s| s.to_le_bytes().to_vec())
+                .collect(),
+        }
+    }
+
+    fn sound_info(num_loops: u16) -> swf::SoundInfo {
+        swf::SoundInfo {
+            event: swf::SoundEvent::Event,
+            in_sample: None,
+            out_sample: None,
+            num_loops,
+            envelope: None,
+        }
+    }
+
+    #[test]
+    fn mix_sounds() {
+        let mut mixer = AudioMixer::new(11025);
+        let sound = mixer
+            .register_sound(&pcm_sound(&[1000, 2000, 3000]))
+            .unwrap();
+        let first = mixer.start_sound(sound, &sound_info(1)).unwrap();
+        let second = mixer.start_sound(sound, &sound_info(1)).unwrap();
+        mixer.set_sound_transform(
+            second,
+            SoundTransform {
+                volume: 50,
+                ..Default::default()
+            },
+        );
+
+        let mut output = [[0; 2]; 2];
+        mixer.mix(&mut output);
+        assert_eq!(output, [[1500, 1500], [3000, 3000]]);
+        assert!(mixer.is_sound_playing(first));
+        assert_eq!(mixer.get_sound_position(first), Some(0));
+
+        mixer.mix(&mut output);
+        assert_eq!(output, [[4500, 4500], [0, 0]]);
+        assert!(!mixer.is_sound_playing(first));
+        assert!(!mixer.is_sound_playing_with_handle(sound));
+    }
+
+    #[test]
+    fn master_volume() {
+        let mut mixer = AudioMixer::new(11025);
+        let sound = mixer.register_sound(&pcm_sound(&[1000, 1000])).unwrap();
+        mixer.start_sound(sound, &sound_info(1)).unwrap();
+
+        mixer.set_volume(0.5);
+        let mut output = [[0; 2]; 1];
+        mixer.mix(&mut output);
+        assert_eq!(output, [[500, 500]]);
+
+        mixer.set_volume(0.0);
+        mixer.mix(&mut output);
+        assert_eq!(output, [[0, 0]]);
+    }
+
+    #[test]
+    fn pause() {
+        let mut mixer = AudioMixer::new(11025);
+        let sound = mixer.register_sound(&pcm_sound(&[1000, 2000])).unwrap();
+        let instance = mixer.start_sound(sound, &sound_info(1)).unwrap();
+
+        mixer.set_paused(true);
+        let mut output = [[0; 2]; 1];
+        mixer.mix(&mut output);
+        assert_eq!(output, [[0, 0]]);
+        assert_eq!(mixer.get_sound_position(instance), Some(0));
+
+        mixer.set_paused(false);
+        mixer.mix(&mut output);
+        assert_eq!(output, [[1000, 1000]]);
+    }
+
+    #[test]
+    fn source_transform() {
+        let mut stream = decoders::FlvAudioStream::new();
+        let source = stream.source();
+        // 16-bit mono PCM at 44kHz.
+        stream.push_packet(&[0b0011_1110, 0xe8, 0x03]).unwrap();
+
+        let mut mixer = AudioMixer::new(44100);
+        let instance = mixer.start_source(source).unwrap();
+        mixer.set_sound_transform(
+            instance,
+            SoundTransform {
+                volume: 50,
+                ..Default::default()
+            },
+        );
+
+        let mut output = [[0; 2]; 2];
+        mixer.mix(&mut output);
+        assert_eq!(output, [[500, 500], [0, 0]]);
+        assert!(mixer.is_sound_playing(instance));
+
+        // The source ends once the stream is closed and its audio has played.
+        stream.close();
+        mixer.mix(&mut [[0; 2]; 4]);
+        assert!(!mixer.is_sound_playing(instance));
+    }
+
+    #[test]
+    fn start_frame() {
+        let mut mixer = AudioMixer::new(11025);
+        let sound = mixer.register_sound(&pcm_sound(&[1000, 2000])).unwrap();
+        let first = mixer.start_sound(sound, &sound_info(1)).unwrap();
+
+        // Silence mixed while paused counts towards the output too.
+        mixer.set_paused(true);
+        mixer.mix(&mut [[0; 2]; 3]);
+        mixer.set_paused(false);
+        let second = mixer.start_sound(sound, &sound_info(1)).unwrap();
+        mixer.mix(&mut [[0; 2]; 1]);
+        assert_eq!(mixer.output_frames(), 4);
+        assert_eq!(mixer.get_sound_start_frame(first), Some(0));
+        assert_eq!(mixer.get_sound_start_frame(second), Some(3));
+    }
+
+    #[test]
+    fn loop_in_out_points() {
+        let mut mixer = AudioMixer::new(11025);
+        let sound = mixer
+            .register_sound(&pcm_sound(&[100, 200, 300, 400, 500]))
+            .unwrap();
+        // In and out points are in 44.1kHz samples, so 4 per sample of this sound.
+        let settings = swf::SoundInfo {
+            in_sample: Some(4),
+            out_sample: Some(12),
+            ..sound_info(3)
+        };
+        mixer.start_sound(sound, &settings).unwrap();
+
+        let mut output = [[0; 2]; 7];
+        mixer.mix(&mut output);
+        let left: Vec<i16> = output.iter().map(|frame| frame[0]).collect();
+        assert_eq!(left, [200, 300, 200, 300, 200, 300, 0]);
+    }
+
+    #[test]
+    fn loop_position() {
+        let mut mixer = AudioMixer::new(1000);
+        let sound = mixer.register_sound(&pcm_sound(&[0; 11025])).unwrap();
+        assert_eq!(mixer.get_sound_duration(sound), Some(1000));
+
+        let instance = mixer.start_sound(sound, &sound_info(3)).unwrap();
+        let mut output = vec![[0; 2]; 1500];
+        mixer.mix(&mut output);
+        assert_eq!(mixer.get_sound_position(instance), Some(500));
+        mixer.mix(&mut vec![[0; 2]; 2000]);
+        assert!(!mixer.is_sound_playing(instance));
+    }
+}
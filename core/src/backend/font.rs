@@ -0,0 +1,232 @@
+//! Access to fonts installed on the host system.
+
+use swf::{ShapeRecord, StyleChangeData, Twips};
+
+/// The size of the EM square used by `DefineFont3` glyphs.
+/// Fonts loaded by a `FontBackend` are converted to this scale.
+pub const EM_SQUARE_SIZE: f32 = 20480.0;
+
+/// A backend that locates and loads fonts installed on the user's system.
+///
+/// Text fields that use device fonts (or embedded fonts without glyphs) are
+/// rendered with a system font returned by this backend. Loaded fonts are
+/// returned as `DefineFont3` definitions so that they can be registered with
+/// the renderer like any embedded font.
+pub trait FontBackend {
+    /// Returns the names of all font families installed on the system.
+    fn font_names(&self) -> Vec<String>;
+
+    /// Loads an installed font family with the given style.
+    ///
+    /// Returns `None` if the family is not installed or could not be loaded.
+    fn load_font(&mut self, name: &str, is_bold: bool, is_italic: bool) -> Option<swf::Font>;
+
+    /// Loads the OpenType/CFF font data embedded in a `DefineFont4` tag.
+    ///
+    /// Returns `None` if the font has no data or the backend is unable to parse it.
+    fn load_font_data(&mut self, _font: &swf::Font4) -> Option<swf::Font> {
+        None
+    }
+}
+
+/// Font backend that finds no system fonts.
+///
+/// Device text will be rendered using the built-in Noto Sans font.
+#[derive(Default)]
+pub struct NullFontBackend;
+
+impl NullFontBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl FontBackend for NullFontBackend {
+    fn font_names(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn load_font(&mut self, _name: &str, _is_bold: bool, _is_italic: bool) -> Option<swf::Font> {
+        None
+    }
+}
+
+/// Returns the system font families to try for one of Flash's generic device
+/// font names (`_sans`, `_serif` or `_typewriter`), in order of preference.
+///
+/// Returns `None` if `name` is not a generic font name.
+pub fn device_font_families(name: &str) -> Option<&'static [&'static str]> {
+    match name {
+        "_sans" => Some(&[
+            "Arial",
+            "Helvetica",
+            "Liberation Sans",
+            "DejaVu Sans",
+            "Noto Sans",
+        ]),
+        "_serif" => Some(&[
+            "Times New Roman",
+            "Times",
+            "Liberation Serif",
+            "DejaVu Serif",
+            "Noto Serif",
+        ]),
+        "_typewriter" => Some(&[
+            "Courier New",
+            "Courier",
+            "Liberation Mono",
+            "DejaVu Sans Mono",
+            "Noto Mono",
+        ]),
+        _ => None,
+    }
+}
+
+/// Builds the shape records of a `DefineFont3` glyph from a font outline.
+///
+/// Font outlines use a Y-up coordinate system in font units, while SWF glyphs
+/// are Y-down in a 20480 unit EM square, with the baseline at 0.
+/// Cubic curves are approximated by a single quadratic curve.
+pub struct GlyphBuilder {
+    scale: f32,
+    records: Vec<ShapeRecord>,
+    cursor: (i32, i32),
+    contour_start: (i32, i32),
+}
+
+impl GlyphBuilder {
+    pub fn new(units_per_em: f32) -> Self {
+        Self {
+            scale: EM_SQUARE_SIZE / units_per_em,
+            records: vec![],
+            cursor: (0, 0),
+            contour_start: (0, 0),
+        }
+    }
+
+    /// Converts a point in font units to EM square units.
+    fn point(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            (x * self.scale).round() as i32,
+            (-y * self.scale).round() as i32,
+        )
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        // The first contour selects the glyph's single fill style.
+        let fill_style_1 = if self.records.is_empty() {
+            Some(1)
+        } else {
+            None
+        };
+        self.records.push(ShapeRecord::StyleChange(StyleChangeData {
+            move_to: Some((Twips::new(to.0), Twips::new(to.1))),
+            fill_style_0: None,
+            fill_style_1,
+            line_style: None,
+            new_styles: None,
+        }));
+        self.cursor = to;
+        self.contour_start = to;
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.line_to_point(to);
+    }
+
+    fn line_to_point(&mut self, to: (i32, i32)) {
+        if to != self.cursor {
+            self.records.push(ShapeRecord::StraightEdge {
+                delta_x: Twips::new(to.0 - self.cursor.0),
+                delta_y: Twips::new(to.1 - self.cursor.1),
+            });
+            self.cursor = to;
+        }
+    }
+
+    pub fn quadratic_curve_to(&mut self, control_x: f32, control_y: f32, x: f32, y: f32) {
+        let control = self.point(control_x, control_y);
+        let to = self.point(x, y);
+        self.records.push(ShapeRecord::CurvedEdge {
+            control_delta_x: Twips::new(control.0 - self.cursor.0),
+            control_delta_y: Twips::new(control.1 - self.cursor.1),
+            anchor_delta_x: Twips::new(to.0 - control.0),
+            anchor_delta_y: Twips::new(to.1 - control.1),
+        });
+        self.cursor = to;
+    }
+
+    pub fn cubic_curve_to(&mut self, control_1: (f32, f32), control_2: (f32, f32), x: f32, y: f32) {
+        // Approximate the cubic with the quadratic whose control point is the
+        // midpoint of the two control points extrapolated from each end.
+        let from = (
+            self.cursor.0 as f32 / self.scale,
+            -self.cursor.1 as f32 / self.scale,
+        );
+        let control_x = (3.0 * (control_1.0 + control_2.0) - from.0 - x) / 4.0;
+        let control_y = (3.0 * (control_1.1 + control_2.1) - from.1 - y) / 4.0;
+        self.quadratic_curve_to(control_x, control_y, x, y);
+    }
+
+    /// Closes the current contour with a straight edge back to its start.
+    pub fn close(&mut self) {
+        let start = self.contour_start;
+        self.line_to_point(start);
+    }
+
+    pub fn into_shape_records(self) -> Vec<ShapeRecord> {
+        self.records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_font_aliases() {
+        assert_eq!(device_font_families("_sans").unwrap()[0], "Arial");
+        assert_eq!(
+            device_font_families("_typewriter").unwrap()[0],
+            "Courier New"
+        );
+        assert_eq!(device_font_families("Arial"), None);
+    }
+
+    #[test]
+    fn glyph_builder_flips_and_scales() {
+        let mut builder = GlyphBuilder::new(1024.0);
+        builder.move_to(0.0, 0.0);
+        builder.line_to(512.0, 1024.0);
+        builder.quadratic_curve_to(1024.0, 1024.0, 1024.0, 0.0);
+        builder.close();
+        assert_eq!(
+            builder.into_shape_records(),
+            vec![
+                ShapeRecord::StyleChange(StyleChangeData {
+                    move_to: Some((Twips::new(0), Twips::new(0))),
+                    fill_style_0: None,
+                    fill_style_1: Some(1),
+                    line_style: None,
+                    new_styles: None,
+                }),
+                ShapeRecord::StraightEdge {
+                    delta_x: Twips::new(10240),
+                    delta_y: Twips::new(-20480),
+                },
+                ShapeRecord::CurvedEdge {
+                    control_delta_x: Twips::new(10240),
+                    control_delta_y: Twips::new(0),
+                    anchor_delta_x: Twips::new(0),
+                    anchor_delta_y: Twips::new(20480),
+                },
+                ShapeRecord::StraightEdge {
+                    delta_x: Twips::new(-20480),
+                    delta_y: Twips::new(0),
+                },
+            ]
+        );
+    }
+}
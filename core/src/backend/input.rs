@@ -15,8 +15,27 @@ pub trait InputBackend: Downcast {
     /// Changes the mouse cursor image.
     fn set_mouse_cursor(&mut self, cursor: MouseCursor);
 
-    /// Set the clipboard to the given content
+    /// Set the clipboard to the given content.
+    ///
+    /// Some platforms only allow this while handling user input, in which case
+    /// the clipboard may be left unchanged.
     fn set_clipboard_content(&mut self, content: String);
+
+    /// Sets whether every key press should go to the movie, as requested by
+    /// `fscommand("trapallkeys", "true")`. Otherwise, frontends may keep some keys
+    /// for their own shortcuts.
+    fn set_trap_all_keys(&mut self, _trap_all_keys: bool) {}
+
+    /// Called when a text field gains or loses keyboard focus. Frontends should start
+    /// sending `PlayerEvent::Ime` events for input method compositions while enabled.
+    fn set_ime_enabled(&mut self, _enabled: bool) {}
+
+    /// Called when the movie asks to enter or leave fullscreen, with `Stage.displayState`
+    /// or `fscommand("fullscreen")`. Returns `false` if the frontend can't, in which
+    /// case the display state doesn't change.
+    fn set_full_screen(&mut self, _full_screen: bool) -> bool {
+        false
+    }
 }
 impl_downcast!(InputBackend);
 
@@ -0,0 +1,54 @@
+//! Output of ActionScript, such as `trace()`.
+//!
+//! Messages for the author of the movie go through the `LogBackend`, apart from
+//! the log of the player itself, so that frontends can show them in a console or
+//! tests can compare them against the output of Flash Player.
+
+use downcast_rs::Downcast;
+
+pub trait LogBackend: Downcast {
+    /// Outputs a message from `trace()`.
+    fn avm_trace(&mut self, message: &str);
+
+    /// Outputs an error that Flash Player shows to the author of a movie, such as an
+    /// uncaught exception or a `tellTarget` to a clip that doesn't exist.
+    fn avm_warning(&mut self, message: &str);
+}
+impl_downcast!(LogBackend);
+
+/// Log backend that discards all output.
+#[derive(Default)]
+pub struct NullLogBackend;
+
+impl NullLogBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LogBackend for NullLogBackend {
+    fn avm_trace(&mut self, _message: &str) {}
+
+    fn avm_warning(&mut self, _message: &str) {}
+}
+
+/// Log backend that writes output to the `log` crate under the `avm_trace` target,
+/// with `trace()` at the `Info` level and errors at the `Warn` level.
+#[derive(Default)]
+pub struct LogCrateBackend;
+
+impl LogCrateBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LogBackend for LogCrateBackend {
+    fn avm_trace(&mut self, message: &str) {
+        log::info!(target: "avm_trace", "{}", message);
+    }
+
+    fn avm_warning(&mut self, message: &str) {
+        log::warn!(target: "avm_trace", "{}", message);
+    }
+}
@@ -1,19 +1,21 @@
 //! Browser-related platform functions
 
 use crate::loader::Error;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::ptr::null;
+use std::rc::Rc;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use std::time::Duration;
 use swf::avm1::types::SendVarsMethod;
 
 /// Enumerates all possible navigation methods.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum NavigationMethod {
     /// Indicates that navigation should generate a GET request.
     GET,
@@ -41,6 +43,63 @@ impl NavigationMethod {
     }
 }
 
+/// The window that `getURL` opens a page in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WindowTarget {
+    /// The window or frame containing the movie, given as `_self` or no window at all.
+    Current,
+
+    /// A new window, given as `_blank`.
+    Blank,
+
+    /// The parent frame of the movie, given as `_parent`.
+    Parent,
+
+    /// The top-level window, given as `_top`.
+    Top,
+
+    /// The window or frame with the given name, which is created if it doesn't exist.
+    Named(String),
+}
+
+impl WindowTarget {
+    /// Parse the window given to `getURL`. Special names are case-insensitive.
+    pub fn from_window_spec(window: Option<&str>) -> Self {
+        let window = match window {
+            Some(window) if !window.is_empty() => window,
+            _ => return Self::Current,
+        };
+        match window.to_ascii_lowercase().as_str() {
+            "_self" => Self::Current,
+            "_blank" => Self::Blank,
+            "_parent" => Self::Parent,
+            "_top" => Self::Top,
+            _ => Self::Named(window.to_string()),
+        }
+    }
+
+    /// The name of this target, as used by the `target` attribute in HTML.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Current => "_self",
+            Self::Blank => "_blank",
+            Self::Parent => "_parent",
+            Self::Top => "_top",
+            Self::Named(name) => name,
+        }
+    }
+
+    /// Whether opening a page in this target may create a new window.
+    ///
+    /// Browsers block new windows that aren't opened in response to user input.
+    pub fn may_open_window(&self) -> bool {
+        match self {
+            Self::Blank | Self::Named(_) => true,
+            Self::Current | Self::Parent | Self::Top => false,
+        }
+    }
+}
+
 /// Represents request options to be sent as part of a fetch.
 pub struct RequestOptions {
     /// The HTTP method to be used to make the request.
@@ -51,6 +110,9 @@ pub struct RequestOptions {
     ///
     /// The body consists of data and a mime type.
     body: Option<(Vec<u8>, String)>,
+
+    /// Extra HTTP headers to send with the request, as name and value pairs.
+    headers: Vec<(String, String)>,
 }
 
 impl RequestOptions {
@@ -59,6 +121,7 @@ impl RequestOptions {
         Self {
             method: NavigationMethod::GET,
             body: None,
+            headers: Vec::new(),
         }
     }
 
@@ -67,9 +130,16 @@ impl RequestOptions {
         Self {
             method: NavigationMethod::POST,
             body,
+            headers: Vec::new(),
         }
     }
 
+    /// Adds an HTTP header to send with this request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
     /// Retrieve the navigation method for this request.
     pub fn method(&self) -> NavigationMethod {
         self.method
@@ -79,6 +149,176 @@ impl RequestOptions {
     pub fn body(&self) -> &Option<(Vec<u8>, String)> {
         &self.body
     }
+
+    /// Retrieve the extra HTTP headers of this request.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+}
+
+/// Rewrites the URLs of loads before they are fetched.
+///
+/// Many movies load assets from servers that no longer exist. Frontends can use
+/// this to point those loads at a mirror or an archived copy instead. Rules are
+/// tried in the order they were added, and the first one that matches wins.
+#[derive(Default)]
+pub struct UrlRewriter {
+    rules: Vec<UrlRewriteRule>,
+}
+
+enum UrlRewriteRule {
+    /// Replaces the start of a URL.
+    Prefix { from: String, to: String },
+
+    /// A function that returns the new URL, or `None` to leave the URL alone.
+    Callback(Box<dyn Fn(&str) -> Option<String>>),
+}
+
+impl UrlRewriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule that replaces `from` with `to` in any URL starting with `from`.
+    pub fn add_prefix(&mut self, from: impl Into<String>, to: impl Into<String>) {
+        self.rules.push(UrlRewriteRule::Prefix {
+            from: from.into(),
+            to: to.into(),
+        });
+    }
+
+    /// Adds a rule that calls the given function, which returns the new URL or
+    /// `None` to leave the URL to the following rules.
+    pub fn add_callback(&mut self, callback: impl Fn(&str) -> Option<String> + 'static) {
+        self.rules
+            .push(UrlRewriteRule::Callback(Box::new(callback)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the URL to fetch in place of the given URL.
+    pub fn rewrite(&self, url: &str) -> String {
+        for rule in &self.rules {
+            let rewritten = match rule {
+                UrlRewriteRule::Prefix { from, to } if url.starts_with(from.as_str()) => {
+                    Some(format!("{}{}", to, &url[from.len()..]))
+                }
+                UrlRewriteRule::Prefix { .. } => None,
+                UrlRewriteRule::Callback(callback) => callback(url),
+            };
+            if let Some(rewritten) = rewritten {
+                log::info!("Rewrote URL {} to {}", url, rewritten);
+                return rewritten;
+            }
+        }
+        url.to_string()
+    }
+}
+
+/// A request made by a fetch, as reported to a `NetworkObserver`.
+#[derive(Clone, Debug)]
+pub struct RequestInfo {
+    /// A number identifying this request, which is also given to its response.
+    pub id: u64,
+
+    /// The URL being fetched, after any rewriting.
+    pub url: String,
+
+    pub method: NavigationMethod,
+
+    /// The size of the request body in bytes.
+    pub body_size: usize,
+}
+
+/// The outcome of a fetch, as reported to a `NetworkObserver`.
+#[derive(Clone, Debug)]
+pub struct ResponseInfo {
+    /// The ID of the request this is the response to.
+    pub id: u64,
+
+    pub url: String,
+
+    /// The HTTP status code, if the response came from an HTTP server.
+    pub status: Option<u16>,
+
+    /// The size of the response body in bytes.
+    pub size: usize,
+
+    /// Why the fetch failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Watches the requests made by a navigator backend, such as for a network
+/// log in a debugger.
+pub trait NetworkObserver {
+    /// Called when a fetch starts.
+    fn request_sent(&self, request: &RequestInfo);
+
+    /// Called when a fetch completes or fails.
+    fn response_received(&self, response: &ResponseInfo);
+}
+
+/// The observers of a navigator backend.
+///
+/// This can be cloned into the futures of fetches, to report their responses.
+#[derive(Clone, Default)]
+pub struct NetworkObservers {
+    observers: Rc<RefCell<Vec<Rc<dyn NetworkObserver>>>>,
+    next_id: Rc<Cell<u64>>,
+}
+
+impl NetworkObservers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, observer: Rc<dyn NetworkObserver>) {
+        self.observers.borrow_mut().push(observer);
+    }
+
+    /// Reports a request to the observers, and returns its info to pass to
+    /// `response` once it completes.
+    pub fn request(&self, url: &str, options: &RequestOptions) -> RequestInfo {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+        let request = RequestInfo {
+            id,
+            url: url.to_string(),
+            method: options.method(),
+            body_size: options.body().as_ref().map_or(0, |(data, _)| data.len()),
+        };
+        for observer in self.observers() {
+            observer.request_sent(&request);
+        }
+        request
+    }
+
+    /// Reports the result of a request to the observers.
+    pub fn response(
+        &self,
+        request: &RequestInfo,
+        status: Option<u16>,
+        result: &Result<Vec<u8>, Error>,
+    ) {
+        let response = ResponseInfo {
+            id: request.id,
+            url: request.url.clone(),
+            status,
+            size: result.as_ref().map_or(0, |data| data.len()),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        for observer in self.observers() {
+            observer.response_received(&response);
+        }
+    }
+
+    /// A copy of the observer list, so that observers can add observers while
+    /// they are being notified.
+    fn observers(&self) -> Vec<Rc<dyn NetworkObserver>> {
+        self.observers.borrow().clone()
+    }
 }
 
 /// Type alias for pinned, boxed, and owned futures that output a falliable
@@ -119,8 +359,9 @@ pub trait NavigatorBackend {
     /// Fetch data at a given URL and return it some time in the future.
     fn fetch(&self, url: &str, request_options: RequestOptions) -> OwnedFuture<Vec<u8>, Error>;
 
-    /// Get the amount of time since the SWF was launched.
-    /// Used by the `getTimer` ActionScript call.
+    /// Get the amount of real time since the SWF was launched.
+    /// Used to time out loads. `getTimer` uses the player's own clock instead,
+    /// which stops while the player is paused.
     fn time_since_launch(&mut self) -> Duration;
 
     /// Arrange for a future to be run at some point in the... well, future.
@@ -132,6 +373,24 @@ pub trait NavigatorBackend {
     /// TODO: For some reason, `wasm_bindgen_futures` wants unpinnable futures.
     /// This seems highly limiting.
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>);
+
+    /// Tells the backend whether the player is handling user input, such as a click.
+    ///
+    /// Browsers block new windows that aren't opened in response to user input,
+    /// so backends that open windows may behave differently outside of it.
+    fn set_handling_user_input(&mut self, _is_handling_user_input: bool) {}
+
+    /// Reports every fetch made by this backend to the given observer.
+    ///
+    /// Backends that can't report their fetches ignore the observer.
+    fn add_network_observer(&mut self, _observer: Rc<dyn NetworkObserver>) {}
+
+    /// Passes an FSCommand that the player doesn't handle itself to the page or
+    /// application hosting the movie, as Flash Player passed them to the page's
+    /// `DoFSCommand` handler. Returns whether the host handled it.
+    fn fs_command(&mut self, _command: &str, _args: &str) -> bool {
+        false
+    }
 }
 
 /// A null implementation of an event loop that only supports blocking.
@@ -304,3 +563,55 @@ impl NavigatorBackend for NullNavigatorBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_rewriter_uses_first_matching_rule() {
+        let mut rewriter = UrlRewriter::new();
+        rewriter.add_prefix(
+            "http://dead.example.com/",
+            "https://mirror.example.org/dead/",
+        );
+        rewriter.add_callback(|url| {
+            if url.ends_with(".swf") {
+                Some("local.swf".to_string())
+            } else {
+                None
+            }
+        });
+
+        assert_eq!(
+            rewriter.rewrite("http://dead.example.com/game.swf"),
+            "https://mirror.example.org/dead/game.swf"
+        );
+        assert_eq!(
+            rewriter.rewrite("http://other.example.com/game.swf"),
+            "local.swf"
+        );
+        assert_eq!(rewriter.rewrite("data.txt"), "data.txt");
+    }
+
+    #[test]
+    fn window_target_from_window_spec() {
+        assert_eq!(WindowTarget::from_window_spec(None), WindowTarget::Current);
+        assert_eq!(
+            WindowTarget::from_window_spec(Some("")),
+            WindowTarget::Current
+        );
+        assert_eq!(
+            WindowTarget::from_window_spec(Some("_BLANK")),
+            WindowTarget::Blank
+        );
+        assert_eq!(
+            WindowTarget::from_window_spec(Some("_parent")),
+            WindowTarget::Parent
+        );
+        assert_eq!(
+            WindowTarget::from_window_spec(Some("Help")),
+            WindowTarget::Named("Help".to_string())
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Printing of movie clips by `PrintJob`.
+
+use crate::backend::render::Bitmap;
+
+/// The orientation of the paper in a print job.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PrintOrientation {
+    Portrait,
+    Landscape,
+}
+
+impl PrintOrientation {
+    /// The name of the orientation as exposed by `PrintJob.orientation`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PrintOrientation::Portrait => "portrait",
+            PrintOrientation::Landscape => "landscape",
+        }
+    }
+}
+
+/// The paper settings chosen when starting a print job.
+///
+/// All sizes are in points (1/72 of an inch), which Flash treats as pixels.
+#[derive(Debug, Clone)]
+pub struct PrintJobSettings {
+    pub paper_width: u32,
+    pub paper_height: u32,
+    pub page_width: u32,
+    pub page_height: u32,
+    pub orientation: PrintOrientation,
+}
+
+impl Default for PrintJobSettings {
+    /// US Letter paper with half-inch margins.
+    fn default() -> Self {
+        Self {
+            paper_width: 612,
+            paper_height: 792,
+            page_width: 576,
+            page_height: 756,
+            orientation: PrintOrientation::Portrait,
+        }
+    }
+}
+
+/// A backend that sends pages to a printer.
+///
+/// Pages are rendered by the core at print resolution and handed to the backend
+/// as bitmaps, so backends only need to deliver them to the user.
+pub trait PrinterBackend {
+    /// Starts a new print job, usually by asking the user to confirm it in a print dialog.
+    ///
+    /// Returns the paper settings of the job, or `None` if the job was cancelled.
+    fn start_job(&mut self) -> Option<PrintJobSettings>;
+
+    /// Adds a page to the current print job.
+    fn add_page(&mut self, page: Bitmap);
+
+    /// Sends the pages of the current print job to the printer.
+    fn send_job(&mut self);
+}
+
+/// Printer backend that cancels every print job.
+#[derive(Default)]
+pub struct NullPrinterBackend;
+
+impl NullPrinterBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PrinterBackend for NullPrinterBackend {
+    fn start_job(&mut self) -> Option<PrintJobSettings> {
+        None
+    }
+
+    fn add_page(&mut self, _page: Bitmap) {}
+
+    fn send_job(&mut self) {}
+}
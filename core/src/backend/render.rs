@@ -1,4 +1,5 @@
 use crate::shape_utils::DistilledShape;
+use crate::stage::StageQuality;
 pub use crate::{transform::Transform, Color};
 use downcast_rs::Downcast;
 use std::io::Read;
@@ -32,13 +33,102 @@ pub trait RenderBackend: Downcast {
     ) -> Result<BitmapInfo, Error>;
 
     fn begin_frame(&mut self, clear: Color);
-    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform);
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool);
     fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform);
     fn end_frame(&mut self);
     fn draw_letterbox(&mut self, letterbox: Letterbox);
     fn push_mask(&mut self);
     fn activate_mask(&mut self);
     fn pop_mask(&mut self);
+
+    /// Changes the rendering quality, as set by `_quality` and `Stage.quality`.
+    /// This chooses the amount of anti-aliasing, and whether bitmaps are smoothed.
+    /// Backends that don't support multiple quality levels can ignore this.
+    fn set_quality(&mut self, _quality: StageQuality) {}
+
+    /// Starts rendering into an offscreen image of the given size, such as a printed page.
+    /// Returns `false` if the backend is unable to render offscreen.
+    fn begin_offscreen_frame(&mut self, _width: u32, _height: u32, _clear: Color) -> bool {
+        false
+    }
+
+    /// Finishes an offscreen frame started by `begin_offscreen_frame` and returns
+    /// the rendered image.
+    fn end_offscreen_frame(&mut self) -> Option<Bitmap> {
+        None
+    }
+
+    /// Reads back the last frame rendered to the screen as an RGBA image, such as
+    /// for screenshots. Returns `None` if the backend is unable to read back frames.
+    fn capture_frame(&mut self) -> Option<Bitmap> {
+        None
+    }
+
+    /// Returns the last frame rendered to the screen as an SVG document, for backends
+    /// that keep shapes as vectors. Returns `None` for backends that rasterize them.
+    fn export_svg(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Starts rendering into a new transparent offscreen surface the size of the
+    /// current one. Surfaces nest, and each one has its own masks.
+    /// Returns `false` if the backend has no offscreen surfaces, in which case
+    /// rendering continues on the current surface and `pop_and_composite` must not be
+    /// called.
+    fn push_render_target(&mut self) -> bool {
+        false
+    }
+
+    /// Finishes the surface started by the last `push_render_target`, and draws it
+    /// onto the surface below it through the given composite. The masks active on the
+    /// surface below apply.
+    fn pop_and_composite(&mut self, _composite: &Composite) {}
+
+    /// Finishes the surface started by the last `push_render_target` like
+    /// `pop_and_composite`, and keeps it so that `composite_cached` can draw it again
+    /// in later frames. Returns `None` if the backend can't keep surfaces.
+    fn pop_and_cache(&mut self, composite: &Composite) -> Option<CacheHandle> {
+        self.pop_and_composite(composite);
+        None
+    }
+
+    /// Draws a surface kept by `pop_and_cache` onto the current surface through the
+    /// given composite, moved by `dx` and `dy` pixels.
+    ///
+    /// Kept surfaces that aren't drawn in a frame are released at the end of it.
+    /// Returns `false` if the surface has been released, in which case nothing is drawn.
+    fn composite_cached(
+        &mut self,
+        _handle: CacheHandle,
+        _dx: i32,
+        _dy: i32,
+        _composite: &Composite,
+    ) -> bool {
+        false
+    }
+
+    /// Registers a video stream whose frames are `width` by `height` pixels.
+    /// The returned bitmap shows the last frame uploaded with `upload_video_frame`,
+    /// and is drawn with `render_bitmap`. It starts out transparent.
+    fn register_video_stream(
+        &mut self,
+        _id: swf::CharacterId,
+        _width: u32,
+        _height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        Err("Video is not supported by this render backend".into())
+    }
+
+    /// Replaces the image of a video stream with a decoded frame of the same size.
+    /// Backends that can convert YUV frames to RGB on the GPU should do so; others can
+    /// use `DecodedFrame::to_rgba`.
+    fn upload_video_frame(
+        &mut self,
+        _handle: BitmapHandle,
+        _frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        Err("Video is not supported by this render backend".into())
+    }
 }
 impl_downcast!(RenderBackend);
 
@@ -50,6 +140,87 @@ pub struct ShapeHandle(pub usize);
 #[derive(Copy, Clone, Debug)]
 pub struct BitmapHandle(pub usize);
 
+/// An offscreen surface kept by `RenderBackend::pop_and_cache`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CacheHandle(pub usize);
+
+/// How an offscreen surface is drawn onto the surface below it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Composite {
+    pub blend_mode: swf::BlendMode,
+
+    /// An effect applied to the surface before it is blended.
+    pub shader: Option<CompositeShader>,
+}
+
+impl Default for Composite {
+    fn default() -> Self {
+        Self {
+            blend_mode: swf::BlendMode::Normal,
+            shader: None,
+        }
+    }
+}
+
+/// An effect applied to a whole offscreen surface, such as a filter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompositeShader {
+    /// Transforms the unmultiplied color of each pixel with a 4x5 matrix in row-major
+    /// order, as `ColorMatrixFilter` does. The last column is added, in the range of 0 to 1.
+    ColorMatrix([f32; 20]),
+
+    /// Blurs the surface with box blurs, as `BlurFilter` does.
+    /// The blur sizes are in pixels.
+    Blur {
+        blur_x: f32,
+        blur_y: f32,
+        passes: u32,
+    },
+}
+
+/// A decoded frame of video, as uploaded by `upload_video_frame`.
+#[derive(Copy, Clone, Debug)]
+pub enum DecodedFrame<'a> {
+    /// Pre-multiplied RGBA, 4 bytes per pixel.
+    Rgba(&'a [u8]),
+
+    /// Planar YUV 4:2:0 in the limited range of BT.601, as decoded from H.263 and VP6.
+    /// The chroma planes are half the width and height of the frame, rounded up.
+    Yuv420 {
+        y: &'a [u8],
+        u: &'a [u8],
+        v: &'a [u8],
+    },
+}
+
+impl DecodedFrame<'_> {
+    /// Converts the frame to pre-multiplied RGBA on the CPU.
+    pub fn to_rgba(self, width: u32, height: u32) -> Vec<u8> {
+        let (y_plane, u_plane, v_plane) = match self {
+            DecodedFrame::Rgba(data) => return data.to_vec(),
+            DecodedFrame::Yuv420 { y, u, v } => (y, u, v),
+        };
+
+        let (width, height) = (width as usize, height as usize);
+        let chroma_width = (width + 1) / 2;
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for row in 0..height {
+            for col in 0..width {
+                let chroma = (row / 2) * chroma_width + col / 2;
+                let y = (f32::from(y_plane[row * width + col]) - 16.0) * (255.0 / 219.0);
+                let u = (f32::from(u_plane[chroma]) - 128.0) * (255.0 / 224.0);
+                let v = (f32::from(v_plane[chroma]) - 128.0) * (255.0 / 224.0);
+                let to_u8 = |c: f32| c.round().max(0.0).min(255.0) as u8;
+                rgba.push(to_u8(y + 1.402 * v));
+                rgba.push(to_u8(y - 0.344_136 * u - 0.714_136 * v));
+                rgba.push(to_u8(y + 1.772 * u));
+                rgba.push(255);
+            }
+        }
+        rgba
+    }
+}
+
 /// Info returned by the `register_bitmap` methods.
 #[derive(Copy, Clone, Debug)]
 pub struct BitmapInfo {
@@ -58,11 +229,13 @@ pub struct BitmapInfo {
     pub height: u16,
 }
 
+/// The black bars drawn in the parts of the viewport outside of the stage.
+/// The margins are in device pixels, and differ on each side unless the stage is centered.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Letterbox {
     None,
-    Letterbox(f32),
-    Pillarbox(f32),
+    Letterbox { top: f32, bottom: f32 },
+    Pillarbox { left: f32, right: f32 },
 }
 
 pub struct NullRenderer;
@@ -135,12 +308,27 @@ impl RenderBackend for NullRenderer {
     }
     fn begin_frame(&mut self, _clear: Color) {}
     fn end_frame(&mut self) {}
-    fn render_bitmap(&mut self, _bitmap: BitmapHandle, _transform: &Transform) {}
+    fn render_bitmap(&mut self, _bitmap: BitmapHandle, _transform: &Transform, _smoothing: bool) {}
     fn render_shape(&mut self, _shape: ShapeHandle, _transform: &Transform) {}
     fn draw_letterbox(&mut self, _letterbox: Letterbox) {}
     fn push_mask(&mut self) {}
     fn activate_mask(&mut self) {}
     fn pop_mask(&mut self) {}
+    fn register_video_stream(
+        &mut self,
+        _id: swf::CharacterId,
+        _width: u32,
+        _height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        Ok(BitmapHandle(0))
+    }
+    fn upload_video_frame(
+        &mut self,
+        _handle: BitmapHandle,
+        _frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 /// The format of image data in a DefineBitsJpeg2/3 tag.
@@ -176,7 +364,8 @@ pub fn determine_jpeg_tag_format(data: &[u8]) -> JpegTagFormat {
         [0xff, 0xd8, ..] => JpegTagFormat::Jpeg,
         [0xff, 0xd9, 0xff, 0xd8, ..] => JpegTagFormat::Jpeg, // erroneous header in SWF
         [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, ..] => JpegTagFormat::Png,
-        [0x47, 0x49, 0x46, 0x38, 0x39, 0x61, ..] => JpegTagFormat::Gif,
+        [0x47, 0x49, 0x46, 0x38, 0x39, 0x61, ..] => JpegTagFormat::Gif, // GIF89a
+        [0x47, 0x49, 0x46, 0x38, 0x37, 0x61, ..] => JpegTagFormat::Gif, // GIF87a
         _ => JpegTagFormat::Unknown,
     }
 }
@@ -234,10 +423,10 @@ pub fn glue_tables_to_jpeg<'a>(
 pub fn remove_invalid_jpeg_data(mut data: &[u8]) -> std::borrow::Cow<[u8]> {
     // TODO: Might be better to return an Box<Iterator<Item=u8>> instead of a Cow here,
     // where the spliced iter is a data[..n].chain(data[n+4..])?
-    if data[..4] == [0xFF, 0xD9, 0xFF, 0xD8] {
+    if data.starts_with(&[0xFF, 0xD9, 0xFF, 0xD8]) {
         data = &data[4..];
     }
-    if let Some(pos) = (0..data.len() - 4).find(|&n| data[n..n + 4] == [0xFF, 0xD9, 0xFF, 0xD8]) {
+    if let Some(pos) = data.windows(4).position(|w| w == [0xFF, 0xD9, 0xFF, 0xD8]) {
         let mut out_data = Vec::with_capacity(data.len() - 4);
         out_data.extend_from_slice(&data[..pos]);
         out_data.extend_from_slice(&data[pos + 4..]);
@@ -259,6 +448,11 @@ pub fn decode_jpeg(
     decoder.read_info()?;
     let metadata = decoder.info().ok_or("Unable to get image info")?;
     let decoded_data = decoder.decode()?;
+    let decoded_data = match metadata.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => decoded_data,
+        jpeg_decoder::PixelFormat::L8 => decoded_data.iter().flat_map(|&l| vec![l; 3]).collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => return Err("Unsupported CMYK JPEG".into()),
+    };
 
     // Decompress the alpha data (DEFLATE compression).
     if let Some(alpha_data) = alpha_data {
@@ -271,22 +465,21 @@ pub fn decode_jpeg(
 
         if alpha_data.len() == decoded_data.len() / 3 {
             let mut rgba = Vec::with_capacity((decoded_data.len() / 3) * 4);
-            let mut i = 0;
-            let mut a = 0;
-            while i < decoded_data.len() {
-                rgba.push(decoded_data[i]);
-                rgba.push(decoded_data[i + 1]);
-                rgba.push(decoded_data[i + 2]);
-                rgba.push(alpha_data[a]);
-                i += 3;
-                a += 1;
+            for (rgb, &a) in decoded_data.chunks_exact(3).zip(alpha_data.iter()) {
+                // The color should already be pre-multiplied, but some SWFs contain colors
+                // brighter than their alpha. Flash clamps these so that transparent
+                // pixels stay invisible.
+                rgba.push(rgb[0].min(a));
+                rgba.push(rgb[1].min(a));
+                rgba.push(rgb[2].min(a));
+                rgba.push(a);
             }
             return Ok(Bitmap {
                 width: metadata.width.into(),
                 height: metadata.height.into(),
                 data: BitmapFormat::Rgba(rgba),
             });
-        } else {
+        } else if !alpha_data.is_empty() {
             // Size isn't correct; fallback to RGB?
             log::error!("Size mismatch in DefineBitsJPEG3 alpha data");
         }
@@ -312,125 +505,103 @@ pub fn decode_define_bits_lossless(
     swf_tag: &swf::DefineBitsLossless,
 ) -> Result<Bitmap, Box<dyn std::error::Error>> {
     // Decompress the image data (DEFLATE compression).
-    let mut decoded_data = {
+    let decoded_data = {
         let mut data = vec![];
         let mut decoder = libflate::zlib::Decoder::new(&swf_tag.data[..])?;
         decoder.read_to_end(&mut data)?;
         data
     };
 
-    // Swizzle/de-palettize the bitmap.
-    let out_data = match (swf_tag.version, swf_tag.format) {
-        (1, swf::BitmapFormat::Rgb15) => {
-            let mut out_data: Vec<u8> = Vec::with_capacity(decoded_data.len() * 2);
-            let mut i = 0;
-            while i < decoded_data.len() {
-                let compressed: u16 = ((decoded_data[i] as u16) << 8) | decoded_data[i + 1] as u16;
-                out_data.push(rgb5_component(compressed, 10));
-                out_data.push(rgb5_component(compressed, 5));
-                out_data.push(rgb5_component(compressed, 0));
-                out_data.push(0xff);
-                i += 2;
-            }
-            out_data
+    let width = usize::from(swf_tag.width);
+    let height = usize::from(swf_tag.height);
+    let has_alpha = swf_tag.version == 2;
+
+    // Each row of pixel data is padded to a 32-bit boundary.
+    let (palette_len, bytes_per_pixel) = match swf_tag.format {
+        swf::BitmapFormat::ColorMap8 => {
+            let color_len = if has_alpha { 4 } else { 3 };
+            ((usize::from(swf_tag.num_colors) + 1) * color_len, 1)
         }
-        (1, swf::BitmapFormat::Rgb32) => {
-            let mut i = 0;
-            while i < decoded_data.len() {
-                decoded_data[i] = decoded_data[i + 1];
-                decoded_data[i + 1] = decoded_data[i + 2];
-                decoded_data[i + 2] = decoded_data[i + 3];
-                decoded_data[i + 3] = 0xff;
-                i += 4;
-            }
-            decoded_data
+        swf::BitmapFormat::Rgb15 if !has_alpha => (0, 2),
+        swf::BitmapFormat::Rgb32 => (0, 4),
+        _ => {
+            return Err(format!(
+                "Unsupported DefineBitsLossless{} format {:?}",
+                swf_tag.version, swf_tag.format
+            )
+            .into())
         }
-        (2, swf::BitmapFormat::Rgb32) => {
-            let mut i = 0;
-            while i < decoded_data.len() {
-                let alpha = decoded_data[i];
-                decoded_data[i] = decoded_data[i + 1];
-                decoded_data[i + 1] = decoded_data[i + 2];
-                decoded_data[i + 2] = decoded_data[i + 3];
-                decoded_data[i + 3] = alpha;
-                i += 4;
+    };
+    let row_len = (width * bytes_per_pixel + 0b11) & !0b11;
+    if decoded_data.len() < palette_len + row_len * height {
+        return Err("Truncated DefineBitsLossless data".into());
+    }
+    let (palette_data, pixel_data) = decoded_data.split_at(palette_len);
+
+    // Swizzle/de-palettize the bitmap.
+    let mut out_data: Vec<u8> = Vec::with_capacity(width * height * 4);
+    let rows = pixel_data.chunks(row_len).take(height);
+    match swf_tag.format {
+        swf::BitmapFormat::Rgb15 => {
+            for row in rows {
+                for pixel in row[..width * 2].chunks_exact(2) {
+                    let compressed = u16::from_be_bytes([pixel[0], pixel[1]]);
+                    out_data.push(rgb5_component(compressed, 10));
+                    out_data.push(rgb5_component(compressed, 5));
+                    out_data.push(rgb5_component(compressed, 0));
+                    out_data.push(0xff);
+                }
             }
-            decoded_data
         }
-        (1, swf::BitmapFormat::ColorMap8) => {
-            let mut i = 0;
-            let padded_width = (swf_tag.width + 0b11) & !0b11;
-
-            let mut palette = Vec::with_capacity(swf_tag.num_colors as usize + 1);
-            for _ in 0..=swf_tag.num_colors {
-                palette.push(Color {
-                    r: decoded_data[i],
-                    g: decoded_data[i + 1],
-                    b: decoded_data[i + 2],
-                    a: 255,
-                });
-                i += 3;
-            }
-            let mut out_data = vec![];
-            for _ in 0..swf_tag.height {
-                for _ in 0..swf_tag.width {
-                    let entry = decoded_data[i] as usize;
-                    if entry < palette.len() {
-                        let color = &palette[entry];
-                        out_data.push(color.r);
-                        out_data.push(color.g);
-                        out_data.push(color.b);
-                        out_data.push(color.a);
-                    } else {
-                        out_data.push(0);
-                        out_data.push(0);
-                        out_data.push(0);
-                        out_data.push(255);
-                    }
-                    i += 1;
+        swf::BitmapFormat::Rgb32 => {
+            for row in rows {
+                for pixel in row[..width * 4].chunks_exact(4) {
+                    // Pixels are stored as ARGB; the alpha byte is reserved in DefineBitsLossless.
+                    out_data.extend_from_slice(&pixel[1..4]);
+                    out_data.push(if has_alpha { pixel[0] } else { 0xff });
                 }
-                i += (padded_width - swf_tag.width) as usize;
             }
-            out_data
         }
-        (2, swf::BitmapFormat::ColorMap8) => {
-            let mut i = 0;
-            let padded_width = (swf_tag.width + 0b11) & !0b11;
-
-            let mut palette = Vec::with_capacity(swf_tag.num_colors as usize + 1);
-            for _ in 0..=swf_tag.num_colors {
-                palette.push(Color {
-                    r: decoded_data[i],
-                    g: decoded_data[i + 1],
-                    b: decoded_data[i + 2],
-                    a: decoded_data[i + 3],
-                });
-                i += 4;
-            }
-            let mut out_data = vec![];
-            for _ in 0..swf_tag.height {
-                for _ in 0..swf_tag.width {
-                    let entry = decoded_data[i] as usize;
-                    if entry < palette.len() {
-                        let color = &palette[entry];
-                        out_data.push(color.r);
-                        out_data.push(color.g);
-                        out_data.push(color.b);
-                        out_data.push(color.a);
-                    } else {
-                        out_data.push(0);
-                        out_data.push(0);
-                        out_data.push(0);
-                        out_data.push(0);
-                    }
-                    i += 1;
+        swf::BitmapFormat::ColorMap8 => {
+            let palette: Vec<Color> = if has_alpha {
+                palette_data
+                    .chunks_exact(4)
+                    .map(|c| Color {
+                        r: c[0],
+                        g: c[1],
+                        b: c[2],
+                        a: c[3],
+                    })
+                    .collect()
+            } else {
+                palette_data
+                    .chunks_exact(3)
+                    .map(|c| Color {
+                        r: c[0],
+                        g: c[1],
+                        b: c[2],
+                        a: 255,
+                    })
+                    .collect()
+            };
+            // Out of range entries are black, and transparent if the bitmap has alpha.
+            let missing = Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: if has_alpha { 0 } else { 255 },
+            };
+            for row in rows {
+                for &entry in &row[..width] {
+                    let color = palette.get(usize::from(entry)).unwrap_or(&missing);
+                    out_data.push(color.r);
+                    out_data.push(color.g);
+                    out_data.push(color.b);
+                    out_data.push(color.a);
                 }
-                i += (padded_width - swf_tag.width) as usize;
             }
-            out_data
         }
-        _ => unimplemented!("{:?} {:?}", swf_tag.version, swf_tag.format),
-    };
+    }
 
     Ok(Bitmap {
         width: swf_tag.width.into(),
@@ -439,35 +610,47 @@ pub fn decode_define_bits_lossless(
     })
 }
 
-/// Decodes the bitmap data in DefineBitsLossless tag into RGBA.
-/// DefineBitsLossless is Zlib encoded pixel data (similar to PNG), possibly
-/// palletized.
+/// Decodes PNG data from a DefineBitsJPEG2/3 tag.
+/// The data is returned with pre-multiplied alpha.
 pub fn decode_png(data: &[u8]) -> Result<Bitmap, Error> {
     use png::{ColorType, Transformations};
 
     let mut decoder = png::Decoder::new(data);
-    // EXPAND expands palettized types to RGB.
-    decoder.set_transformations(Transformations::EXPAND);
+    // EXPAND expands palettized types to RGB and transparency chunks to an alpha channel.
+    // STRIP_16 reduces 16-bit channels to 8 bits.
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
     let (info, mut reader) = decoder.read_info()?;
 
     let mut data = vec![0; info.buffer_size()];
     reader.next_frame(&mut data)?;
 
+    let data = match info.color_type {
+        ColorType::RGBA => {
+            premultiply_alpha_rgba(&mut data);
+            BitmapFormat::Rgba(data)
+        }
+        ColorType::RGB => BitmapFormat::Rgb(data),
+        ColorType::GrayscaleAlpha => {
+            let mut rgba: Vec<u8> = data
+                .chunks_exact(2)
+                .flat_map(|la| vec![la[0], la[0], la[0], la[1]])
+                .collect();
+            premultiply_alpha_rgba(&mut rgba);
+            BitmapFormat::Rgba(rgba)
+        }
+        ColorType::Grayscale => BitmapFormat::Rgb(data.iter().flat_map(|&l| vec![l; 3]).collect()),
+        ColorType::Indexed => return Err("Unexpected indexed PNG after expansion".into()),
+    };
+
     Ok(Bitmap {
         width: info.width,
         height: info.height,
-        data: if info.color_type == ColorType::RGBA {
-            BitmapFormat::Rgba(data)
-        } else {
-            // EXPAND expands other types to RGB.
-            BitmapFormat::Rgb(data)
-        },
+        data,
     })
 }
 
-/// Decodes the bitmap data in DefineBitsLossless tag into RGBA.
-/// DefineBitsLossless is Zlib encoded pixel data (similar to PNG), possibly
-/// palletized.
+/// Decodes the first frame of GIF data from a DefineBitsJPEG2/3 tag.
+/// The data is returned with pre-multiplied alpha.
 pub fn decode_gif(data: &[u8]) -> Result<Bitmap, Error> {
     use gif::SetParameter;
 
@@ -475,11 +658,23 @@ pub fn decode_gif(data: &[u8]) -> Result<Bitmap, Error> {
     decoder.set(gif::ColorOutput::RGBA);
     let mut reader = decoder.read_info()?;
     let frame = reader.read_next_frame()?.ok_or("No frames in GIF")?;
+    let mut data = frame.buffer.to_vec();
+    premultiply_alpha_rgba(&mut data);
 
     Ok(Bitmap {
         width: frame.width.into(),
         height: frame.height.into(),
-        data: BitmapFormat::Rgba(frame.buffer.to_vec()),
+        data: BitmapFormat::Rgba(data),
+    })
+}
+
+/// Converts standard RGBA to RGBA premultiplied alpha, as used by bitmaps in SWFs.
+pub fn premultiply_alpha_rgba(rgba: &mut [u8]) {
+    rgba.chunks_exact_mut(4).for_each(|rgba| {
+        let a = u16::from(rgba[3]);
+        rgba[0] = ((u16::from(rgba[0]) * a + 127) / 255) as u8;
+        rgba[1] = ((u16::from(rgba[1]) * a + 127) / 255) as u8;
+        rgba[2] = ((u16::from(rgba[2]) * a + 127) / 255) as u8;
     })
 }
 
@@ -512,3 +707,122 @@ pub fn srgb_to_linear(color: [f32; 4]) -> [f32; 4] {
         color[3],
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn lossless(version: u8, format: swf::BitmapFormat, width: u16, data: &[u8]) -> Bitmap {
+        let mut encoder = libflate::zlib::Encoder::new(vec![]).unwrap();
+        encoder.write_all(data).unwrap();
+        let data = encoder.finish().into_result().unwrap();
+        let swf_tag = swf::DefineBitsLossless {
+            version,
+            id: 1,
+            format,
+            width,
+            height: 2,
+            num_colors: 1,
+            data,
+        };
+        decode_define_bits_lossless(&swf_tag).unwrap()
+    }
+
+    fn rgba(bitmap: Bitmap) -> Vec<u8> {
+        match bitmap.data {
+            BitmapFormat::Rgba(data) => data,
+            BitmapFormat::Rgb(_) => panic!("Expected RGBA bitmap"),
+        }
+    }
+
+    #[test]
+    fn yuv420_to_rgba() {
+        // The pixels of a 2x2 frame share one chroma sample.
+        let frame = DecodedFrame::Yuv420 {
+            y: &[235, 16, 126, 81],
+            u: &[128],
+            v: &[128],
+        };
+        assert_eq!(
+            frame.to_rgba(2, 2),
+            vec![255, 255, 255, 255, 0, 0, 0, 255, 128, 128, 128, 255, 76, 76, 76, 255]
+        );
+
+        let red = DecodedFrame::Yuv420 {
+            y: &[81],
+            u: &[90],
+            v: &[240],
+        };
+        assert_eq!(red.to_rgba(1, 1), vec![254, 0, 0, 255]);
+    }
+
+    #[test]
+    fn lossless_rgb15_skips_row_padding() {
+        let data = [0x7C, 0x00, 0, 0, 0x00, 0x1F, 0, 0];
+        let bitmap = lossless(1, swf::BitmapFormat::Rgb15, 1, &data);
+        assert_eq!(rgba(bitmap), vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn lossless_colormap_with_alpha() {
+        // Two palette entries followed by 2 rows of 1 pixel, each padded to 4 bytes.
+        let data = [10, 20, 30, 40, 1, 2, 3, 4, 1, 0, 0, 0, 5, 0, 0, 0];
+        let bitmap = lossless(2, swf::BitmapFormat::ColorMap8, 1, &data);
+        assert_eq!(rgba(bitmap), vec![1, 2, 3, 4, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn lossless_truncated_data() {
+        let mut encoder = libflate::zlib::Encoder::new(vec![]).unwrap();
+        encoder.write_all(&[0; 4]).unwrap();
+        let swf_tag = swf::DefineBitsLossless {
+            version: 2,
+            id: 1,
+            format: swf::BitmapFormat::Rgb32,
+            width: 2,
+            height: 2,
+            num_colors: 0,
+            data: encoder.finish().into_result().unwrap(),
+        };
+        assert!(decode_define_bits_lossless(&swf_tag).is_err());
+    }
+
+    #[test]
+    fn sniff_jpeg_tag_format() {
+        assert_eq!(
+            determine_jpeg_tag_format(&[0xFF, 0xD8, 0xFF]),
+            JpegTagFormat::Jpeg
+        );
+        assert_eq!(
+            determine_jpeg_tag_format(b"\x89PNG\r\n\x1a\n"),
+            JpegTagFormat::Png
+        );
+        assert_eq!(determine_jpeg_tag_format(b"GIF87a"), JpegTagFormat::Gif);
+        assert_eq!(determine_jpeg_tag_format(b"GIF89a"), JpegTagFormat::Gif);
+        assert_eq!(determine_jpeg_tag_format(b"BM"), JpegTagFormat::Unknown);
+    }
+
+    #[test]
+    fn png_in_jpeg_tag_is_premultiplied() {
+        let mut data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut data, 1, 1);
+            encoder.set_color(png::ColorType::GrayscaleAlpha);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[200, 128]).unwrap();
+        }
+        let bitmap = decode_define_bits_jpeg(&data, None).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (1, 1));
+        assert_eq!(rgba(bitmap), vec![100, 100, 100, 128]);
+    }
+
+    #[test]
+    fn remove_erroneous_jpeg_header() {
+        assert_eq!(
+            &remove_invalid_jpeg_data(&[0xFF, 0xD9, 0xFF, 0xD8, 0xFF, 0xD8])[..],
+            &[0xFF, 0xD8]
+        );
+        assert_eq!(&remove_invalid_jpeg_data(&[0xFF, 0xD8])[..], &[0xFF, 0xD8]);
+    }
+}
@@ -0,0 +1,55 @@
+//! Connections to RTMP media servers.
+//!
+//! The RTMP protocol itself is implemented by the core; backends only move
+//! bytes between the player and the server. Frontends that can't open
+//! sockets, like the web, use the `NullRtmpBackend`.
+
+type Error = Box<dyn std::error::Error>;
+
+pub trait RtmpBackend {
+    /// Opens a connection to a media server.
+    ///
+    /// Tunneled connections (`rtmpt://` URLs) send the RTMP stream over HTTP
+    /// requests instead of a raw socket. Opening a connection must not block;
+    /// bytes sent before the connection is established are sent once it is.
+    fn connect(
+        &mut self,
+        host: &str,
+        port: u16,
+        tunneled: bool,
+    ) -> Result<Box<dyn RtmpTransport>, Error>;
+}
+
+/// An open connection to a media server.
+///
+/// The connection is closed when the transport is dropped.
+pub trait RtmpTransport {
+    /// Sends bytes to the server.
+    fn send(&mut self, data: &[u8]) -> Result<(), Error>;
+
+    /// Takes the bytes received since the last call, without blocking.
+    ///
+    /// Returns an error once the connection has been closed or has failed.
+    fn receive(&mut self) -> Result<Vec<u8>, Error>;
+}
+
+/// RTMP backend that can't connect to any server.
+#[derive(Default)]
+pub struct NullRtmpBackend;
+
+impl NullRtmpBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl RtmpBackend for NullRtmpBackend {
+    fn connect(
+        &mut self,
+        _host: &str,
+        _port: u16,
+        _tunneled: bool,
+    ) -> Result<Box<dyn RtmpTransport>, Error> {
+        Err("RTMP connections are not supported".into())
+    }
+}
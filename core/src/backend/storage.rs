@@ -11,9 +11,43 @@ pub trait StorageBackend: Downcast {
     }
 
     fn remove_key(&mut self, name: &str);
+
+    /// Get the names of all saved data, in order.
+    ///
+    /// Backends that can't list their data return nothing.
+    fn get_keys(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Get binary data that was saved with `put_bytes`.
+    ///
+    /// By default, the data is stored as a hexadecimal string.
+    fn get_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        self.get_string(name).and_then(|s| decode_hex(&s))
+    }
+
+    /// Save binary data.
+    fn put_bytes(&mut self, name: &str, value: &[u8]) -> bool {
+        self.put_string(name, encode_hex(value))
+    }
 }
 impl_downcast!(StorageBackend);
 
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode binary data saved as a hexadecimal string by the default `put_bytes`.
+pub fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 pub struct MemoryStorageBackend {
     pub map: HashMap<String, String>,
 }
@@ -39,4 +73,39 @@ impl StorageBackend for MemoryStorageBackend {
     fn remove_key(&mut self, name: &str) {
         self.map.remove(name);
     }
+
+    fn get_keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self.map.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip_through_strings() {
+        let mut storage = MemoryStorageBackend::default();
+        assert!(storage.put_bytes("test", &[0x00, 0x11, 0xab, 0xff]));
+        assert_eq!(storage.get_string("test").unwrap(), "0011abff");
+        assert_eq!(
+            storage.get_bytes("test").unwrap(),
+            vec![0x00, 0x11, 0xab, 0xff]
+        );
+
+        storage.put_string("test", "{\"a\":1}".to_string());
+        assert_eq!(storage.get_bytes("test"), None);
+    }
+
+    #[test]
+    fn keys_are_sorted() {
+        let mut storage = MemoryStorageBackend::default();
+        storage.put_string("b", "2".to_string());
+        storage.put_bytes("a/c", &[1]);
+        assert_eq!(storage.get_keys(), vec!["a/c".to_string(), "b".to_string()]);
+        storage.remove_key("b");
+        assert_eq!(storage.get_keys(), vec!["a/c".to_string()]);
+    }
 }
@@ -1,5 +1,7 @@
 use crate::backend::audio::SoundHandle;
-use crate::display_object::{Bitmap, Button, EditText, Graphic, MorphShape, MovieClip, Text};
+use crate::display_object::{
+    Bitmap, Button, EditText, Graphic, MorphShape, MovieClip, Text, Video,
+};
 use crate::font::Font;
 
 #[derive(Clone)]
@@ -12,6 +14,7 @@ pub enum Character<'gc> {
     Font(Font<'gc>),
     MorphShape(MorphShape<'gc>),
     Text(Text<'gc>),
+    Video(Video<'gc>),
     Sound(SoundHandle),
 }
 
@@ -27,6 +30,7 @@ unsafe impl<'gc> gc_arena::Collect for Character<'gc> {
             Character::Font(c) => c.trace(cc),
             Character::MorphShape(c) => c.trace(cc),
             Character::Text(c) => c.trace(cc),
+            Character::Video(c) => c.trace(cc),
             Character::Sound(c) => c.trace(cc),
         }
     }
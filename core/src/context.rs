@@ -1,19 +1,34 @@
 //! Contexts and helper types passed between functions.
+use crate::audio_manager::AudioManager;
 use crate::avm1;
 
 use crate::avm1::globals::system::SystemProperties;
 use crate::avm1::listeners::SystemListener;
 use crate::avm1::{Object, Value};
+use crate::avm2::{Avm2, Object as Avm2Object, Value as Avm2Value};
+use crate::backend::accessibility::AccessibilityBackend;
+use crate::backend::font::FontBackend;
 use crate::backend::input::InputBackend;
+use crate::backend::log::LogBackend;
+use crate::backend::printer::PrinterBackend;
+#[cfg(feature = "rtmp")]
+use crate::backend::rtmp::RtmpBackend;
 use crate::backend::storage::StorageBackend;
 use crate::backend::{audio::AudioBackend, navigator::NavigatorBackend, render::RenderBackend};
 use crate::display_object::EditText;
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::LocalConnections;
 use crate::player::Player;
 use crate::prelude::*;
+use crate::profiler::Profiler;
+#[cfg(feature = "rtmp")]
+use crate::rtmp::RtmpConnections;
+use crate::shape_utils::Scale9Grid;
+use crate::stage::{StageAlign, StageDisplayState, StageQuality, StageScaleMode};
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::transform::TransformStack;
+use crate::unimplemented::UnimplementedFeatures;
 use core::fmt;
 use gc_arena::{Collect, MutationContext};
 use rand::rngs::SmallRng;
@@ -36,7 +51,7 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The mutation context to allocate and mutate `GcCell` types.
     pub gc_context: MutationContext<'gc, 'gc_context>,
 
-    /// The time elapsed since this SWF started executing.
+    /// The time elapsed since this SWF started executing, in milliseconds.
     /// Used by AVM1 `GetTime` action and `getTimer` function.
     pub global_time: u64,
 
@@ -69,6 +84,22 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The storage backend, used for storing persistent state
     pub storage: &'a mut dyn StorageBackend,
 
+    /// The font backend, used to render device fonts with fonts installed on the system.
+    pub font: &'a mut dyn FontBackend,
+
+    /// The printer backend, used by `PrintJob` to print pages.
+    pub printer: &'a mut dyn PrinterBackend,
+
+    /// The accessibility backend, used to expose the movie to screen readers.
+    pub accessibility: &'a mut dyn AccessibilityBackend,
+
+    /// The RTMP backend, used by `NetConnection` to connect to media servers.
+    #[cfg(feature = "rtmp")]
+    pub rtmp: &'a mut dyn RtmpBackend,
+
+    /// The log backend, used by `trace()` and for errors shown to the author of the movie.
+    pub log: &'a mut dyn LogBackend,
+
     /// The RNG, used by the AVM `RandomNumber` opcode,  `Math.random(),` and `random()`.
     pub rng: &'a mut SmallRng,
 
@@ -79,6 +110,9 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// new built-in objects.
     pub system_prototypes: avm1::SystemPrototypes<'gc>,
 
+    /// The AVM2 interpreter, used by movies with `DoABC` tags.
+    pub avm2: &'a mut Avm2<'gc>,
+
     /// The display object that the mouse is currently hovering over.
     pub mouse_hovered_object: Option<DisplayObject<'gc>>,
 
@@ -88,9 +122,30 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The object being dragged via a `startDrag` action.
     pub drag_object: &'a mut Option<crate::player::DragObject<'gc>>,
 
+    /// The object that has keyboard focus. Typed text goes to a focused text field.
+    pub focus: &'a mut Option<DisplayObject<'gc>>,
+
     /// The dimensions of the stage.
     pub stage_size: (Twips, Twips),
 
+    /// How the movie is scaled to fit the viewport. Changed by `Stage.scaleMode`.
+    pub stage_scale_mode: &'a mut StageScaleMode,
+
+    /// Where the movie is placed in the viewport. Changed by `Stage.align`.
+    pub stage_align: &'a mut StageAlign,
+
+    /// The rendering quality of the stage. Changed by the `_quality` property.
+    pub stage_quality: &'a mut StageQuality,
+
+    /// Whether the stage fills the screen. Changed by `Stage.displayState`.
+    pub stage_display_state: &'a mut StageDisplayState,
+
+    /// Whether the full context menu is shown. Changed by `Stage.showMenu`.
+    pub show_menu: &'a mut bool,
+
+    /// The number of seconds of streaming sound to buffer. Changed by the `_soundbuftime` property.
+    pub sound_buffer_time: &'a mut i32,
+
     /// Weak reference to the player.
     ///
     /// Recipients of an update context may upgrade the reference to ensure
@@ -114,6 +169,26 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
 
     /// Text fields with unbound variable bindings.
     pub unbound_text_fields: &'a mut Vec<EditText<'gc>>,
+
+    /// Clips removed from the display list whose scripts can still run until the
+    /// end of the next frame.
+    pub unloaded_clips: &'a mut Vec<DisplayObject<'gc>>,
+
+    /// The connections opened by `LocalConnection` objects of both VMs.
+    pub local_connections: &'a mut LocalConnections<'gc>,
+
+    /// The connections of `NetConnection` objects to media servers.
+    #[cfg(feature = "rtmp")]
+    pub rtmp_connections: &'a mut RtmpConnections<'gc>,
+
+    /// The sounds played by movies, along with the objects that own them.
+    pub audio_manager: &'a mut AudioManager<'gc>,
+
+    /// Records how long each subsystem takes per frame, when profiling is on.
+    pub profiler: &'a mut Profiler,
+
+    /// The features the movie used that aren't implemented.
+    pub unimplemented_features: &'a mut UnimplementedFeatures,
 }
 
 /// A queued ActionScript call.
@@ -178,6 +253,16 @@ impl<'gc> ActionQueue<'gc> {
         }
     }
 
+    /// Drops the queued actions of a clip that is being removed, except for its
+    /// unload handlers.
+    pub fn cancel_actions(&mut self, clip: DisplayObject<'gc>) {
+        let keep = |actions: &QueuedActions<'gc>| {
+            actions.is_unload || !DisplayObject::ptr_eq(actions.clip, clip)
+        };
+        self.change_prototype_queue.retain(keep);
+        self.action_queue.retain(keep);
+    }
+
     /// Sorts and drains the actions from the queue.
     pub fn pop_action(&mut self) -> Option<QueuedActions<'gc>> {
         if !self.change_prototype_queue.is_empty() {
@@ -218,6 +303,12 @@ pub struct RenderContext<'a, 'gc> {
 
     /// The stack of clip depths, used in masking.
     pub clip_depth_stack: Vec<Depth>,
+
+    /// The 9-slice scaling grid of the movie clip whose children are being rendered, if any.
+    pub scale9_grid: Option<Scale9Grid>,
+
+    /// Records the time spent tessellating shapes while rendering, when profiling is on.
+    pub profiler: &'a mut Profiler,
 }
 
 /// The type of action being run.
@@ -245,6 +336,20 @@ pub enum ActionType<'gc> {
         method: &'static str,
         args: Vec<Value<'gc>>,
     },
+
+    /// Construct the AVM2 object of a timeline clip, once the classes of its
+    /// movie have been loaded.
+    ConstructAvm2,
+
+    /// An AVM2 callable, e.g. a frame script.
+    CallAvm2 {
+        callable: Avm2Object<'gc>,
+        receiver: Option<Avm2Object<'gc>>,
+        args: Vec<Avm2Value<'gc>>,
+    },
+
+    /// A goto requested by AVM2 code, which runs once that code is done.
+    GotoAvm2 { frame: u16, stop: bool },
 }
 
 impl fmt::Debug for ActionType<'_> {
@@ -278,6 +383,22 @@ impl fmt::Debug for ActionType<'_> {
                 .field("method", method)
                 .field("args", args)
                 .finish(),
+            ActionType::ConstructAvm2 => f.debug_struct("ActionType::ConstructAvm2").finish(),
+            ActionType::CallAvm2 {
+                callable,
+                receiver,
+                args,
+            } => f
+                .debug_struct("ActionType::CallAvm2")
+                .field("callable", callable)
+                .field("receiver", receiver)
+                .field("args", args)
+                .finish(),
+            ActionType::GotoAvm2 { frame, stop } => f
+                .debug_struct("ActionType::GotoAvm2")
+                .field("frame", frame)
+                .field("stop", stop)
+                .finish(),
         }
     }
 }
@@ -296,6 +417,15 @@ unsafe impl<'gc> Collect for ActionType<'gc> {
             ActionType::NotifyListeners { args, .. } => {
                 args.trace(cc);
             }
+            ActionType::CallAvm2 {
+                callable,
+                receiver,
+                args,
+            } => {
+                callable.trace(cc);
+                receiver.trace(cc);
+                args.trace(cc);
+            }
             _ => {}
         }
     }
@@ -0,0 +1,275 @@
+//! The menu shown when the movie is right-clicked.
+//!
+//! Movies customize the menu with AVM1 `ContextMenu` objects, assigned to the `menu`
+//! property of a clip, button or text field. Frontends show the items built here,
+//! along with items of their own, and run the chosen one with
+//! `Player::run_context_menu_callback`.
+
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::error::Error;
+use crate::avm1::{Avm1, Object, TObject, Value};
+use crate::context::UpdateContext;
+use crate::display_object::{DisplayObject, TDisplayObject};
+
+/// An item of the context menu, for frontends to show.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextMenuItem {
+    pub caption: String,
+    pub enabled: bool,
+
+    /// Whether a separator is shown above the item.
+    pub separator_before: bool,
+
+    /// Whether the item shows a check mark, such as "Play" while the movie plays.
+    pub checked: bool,
+}
+
+/// What choosing an item of the context menu does.
+pub(crate) enum ContextMenuCallback<'gc> {
+    Play,
+    Loop,
+    Rewind,
+    Forward,
+    Back,
+
+    /// A custom item added by the movie, whose `onSelect` handler is called with the
+    /// object that was right-clicked.
+    Avm1 {
+        item: Object<'gc>,
+        target: Value<'gc>,
+        callback: Option<Object<'gc>>,
+    },
+}
+
+/// Builds the context menu for the object under the mouse: the custom items of the
+/// nearest `menu` of it or its parents, followed by the built-in items of the player
+/// that the menu doesn't hide.
+///
+/// If `call_on_select` is set, the menu's `onSelect` handler is called first, as it is
+/// when the menu is opened, so that the movie can change its items.
+pub(crate) fn build_context_menu<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    call_on_select: bool,
+    show_built_in_items: bool,
+    is_looping: bool,
+) -> Result<Vec<(ContextMenuItem, ContextMenuCallback<'gc>)>, Error<'gc>> {
+    let root = match context.levels.get(&0) {
+        Some(root) => *root,
+        None => return Ok(vec![]),
+    };
+    let mut activation = Activation::from_nothing(
+        avm,
+        ActivationIdentifier::root("[Context Menu]"),
+        context.swf.version(),
+        avm.global_object_cell(),
+        context.gc_context,
+        root,
+    );
+    let swf_version = activation.current_swf_version();
+    let target = context.mouse_hovered_object.unwrap_or(root);
+    let menu = find_menu(target, &mut activation, context)?;
+
+    let mut items = vec![];
+    let mut built_in_items = None;
+    if let Some(menu) = menu {
+        if call_on_select {
+            if let Value::Object(callback) = menu.get("onSelect", &mut activation, context)? {
+                callback.call(
+                    "onSelect",
+                    &mut activation,
+                    context,
+                    menu,
+                    None,
+                    &[target.object(), menu.into()],
+                )?;
+            }
+        }
+
+        if let Value::Object(custom_items) = menu.get("customItems", &mut activation, context)? {
+            for i in 0..custom_items.length() {
+                let item = match custom_items.array_element(i) {
+                    Value::Object(item) => item,
+                    _ => continue,
+                };
+                if !item
+                    .get("visible", &mut activation, context)?
+                    .as_bool(swf_version)
+                {
+                    continue;
+                }
+                let caption = item
+                    .get("caption", &mut activation, context)?
+                    .coerce_to_string(&mut activation, context)?
+                    .to_string();
+                let enabled = item
+                    .get("enabled", &mut activation, context)?
+                    .as_bool(swf_version);
+                let separator_before = item
+                    .get("separatorBefore", &mut activation, context)?
+                    .as_bool(swf_version);
+                let callback = match item.get("onSelect", &mut activation, context)? {
+                    Value::Object(callback) => Some(callback),
+                    _ => None,
+                };
+                items.push((
+                    ContextMenuItem {
+                        caption,
+                        enabled,
+                        separator_before,
+                        checked: false,
+                    },
+                    ContextMenuCallback::Avm1 {
+                        item,
+                        target: target.object(),
+                        callback,
+                    },
+                ));
+            }
+        }
+
+        if let Value::Object(object) = menu.get("builtInItems", &mut activation, context)? {
+            built_in_items = Some(object);
+        }
+    }
+
+    // The built-in items are only shown for movies with more than one frame.
+    let root_clip = match root.as_movie_clip() {
+        Some(clip) if show_built_in_items && clip.total_frames() > 1 => clip,
+        _ => return Ok(items),
+    };
+    let mut is_shown = |name: &str| -> Result<bool, Error<'gc>> {
+        match built_in_items {
+            Some(built_in_items) => {
+                // Items are shown unless they are set to false.
+                match built_in_items.get(name, &mut activation, context)? {
+                    Value::Undefined => Ok(true),
+                    value => Ok(value.as_bool(swf_version)),
+                }
+            }
+            None => Ok(true),
+        }
+    };
+    let current_frame = root_clip.current_frame();
+    let total_frames = root_clip.total_frames();
+    let mut built_in = vec![];
+    if is_shown("play")? {
+        built_in.push(("Play", true, root_clip.playing(), ContextMenuCallback::Play));
+    }
+    if is_shown("loop")? {
+        built_in.push(("Loop", true, is_looping, ContextMenuCallback::Loop));
+    }
+    if is_shown("rewind")? {
+        built_in.push((
+            "Rewind",
+            current_frame > 1,
+            false,
+            ContextMenuCallback::Rewind,
+        ));
+    }
+    if is_shown("forward_back")? {
+        built_in.push((
+            "Forward",
+            current_frame < total_frames,
+            false,
+            ContextMenuCallback::Forward,
+        ));
+        built_in.push(("Back", current_frame > 1, false, ContextMenuCallback::Back));
+    }
+    for (i, (caption, enabled, checked, callback)) in built_in.into_iter().enumerate() {
+        items.push((
+            ContextMenuItem {
+                caption: caption.to_string(),
+                enabled,
+                separator_before: i == 0 && !items.is_empty(),
+                checked,
+            },
+            callback,
+        ));
+    }
+
+    Ok(items)
+}
+
+/// Finds the `menu` of the given object, or of its nearest parent that has one.
+fn find_menu<'gc>(
+    target: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Result<Option<Object<'gc>>, Error<'gc>> {
+    let mut owner = Some(target);
+    while let Some(display_object) = owner {
+        if let Value::Object(object) = display_object.object() {
+            if let Value::Object(menu) = object.get("menu", activation, context)? {
+                return Ok(Some(menu));
+            }
+        }
+        owner = display_object.parent();
+    }
+    Ok(None)
+}
+
+/// Runs the chosen item of the context menu. `Loop` is handled by the player, as
+/// looping is one of its settings.
+pub(crate) fn run_context_menu_callback<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    callback: ContextMenuCallback<'gc>,
+) -> Result<(), Error<'gc>> {
+    let root = match context.levels.get(&0) {
+        Some(root) => *root,
+        None => return Ok(()),
+    };
+    let root_clip = root.as_movie_clip();
+    match callback {
+        ContextMenuCallback::Play => {
+            if let Some(root_clip) = root_clip {
+                if root_clip.playing() {
+                    root_clip.stop(context);
+                } else {
+                    root_clip.play(context);
+                }
+            }
+        }
+        ContextMenuCallback::Rewind => {
+            if let Some(root_clip) = root_clip {
+                root_clip.goto_frame(avm, context, 1, true);
+            }
+        }
+        ContextMenuCallback::Forward => {
+            if let Some(root_clip) = root_clip {
+                root_clip.next_frame(avm, context);
+            }
+        }
+        ContextMenuCallback::Back => {
+            if let Some(root_clip) = root_clip {
+                root_clip.prev_frame(avm, context);
+            }
+        }
+        ContextMenuCallback::Loop => (),
+        ContextMenuCallback::Avm1 {
+            item,
+            target,
+            callback: Some(callback),
+        } => {
+            let mut activation = Activation::from_nothing(
+                avm,
+                ActivationIdentifier::root("[Context Menu Callback]"),
+                context.swf.version(),
+                avm.global_object_cell(),
+                context.gc_context,
+                root,
+            );
+            callback.call(
+                "onSelect",
+                &mut activation,
+                context,
+                item,
+                None,
+                &[target, item.into()],
+            )?;
+        }
+        ContextMenuCallback::Avm1 { callback: None, .. } => (),
+    }
+    Ok(())
+}
@@ -1,16 +1,21 @@
 use crate::avm1::{Avm1, Object, TObject, Value};
+use crate::avm2::{Object as Avm2Object, Value as Avm2Value};
+use crate::backend::render::{CacheHandle, Composite};
 use crate::context::{RenderContext, UpdateContext};
 use crate::player::NEWEST_PLAYER_VERSION;
 use crate::prelude::*;
 use crate::tag_utils::SwfMovie;
 use crate::transform::Transform;
 use enumset::{EnumSet, EnumSetType};
+use fnv::FnvHasher;
 use gc_arena::{Collect, MutationContext};
 use ruffle_macros::enum_trait_object;
-use std::cell::{Ref, RefMut};
+use std::cell::{Ref, RefCell, RefMut};
 use std::cmp::min;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use swf::BlendMode;
 
 mod bitmap;
 mod button;
@@ -19,8 +24,9 @@ mod graphic;
 mod morph_shape;
 mod movie_clip;
 mod text;
+mod video;
 
-use crate::avm1::activation::Activation;
+use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::events::{ClipEvent, ClipEventResult};
 pub use bitmap::Bitmap;
 pub use button::Button;
@@ -28,7 +34,8 @@ pub use edit_text::{AutoSizeMode, EditText};
 pub use graphic::Graphic;
 pub use morph_shape::{MorphShape, MorphShapeStatic};
 pub use movie_clip::MovieClip;
-pub use text::Text;
+pub use text::{StaticTextChar, Text};
+pub use video::Video;
 
 #[derive(Clone, Debug)]
 pub struct DisplayObjectBase<'gc> {
@@ -57,6 +64,26 @@ pub struct DisplayObjectBase<'gc> {
     /// The next sibling of this display object in order of execution.
     next_sibling: Option<DisplayObject<'gc>>,
 
+    /// The display object masking this object, set by `MovieClip.setMask`.
+    masker: Option<DisplayObject<'gc>>,
+
+    /// The display object masked by this object, set by `MovieClip.setMask`.
+    maskee: Option<DisplayObject<'gc>>,
+
+    /// How this object is blended with the objects below it.
+    blend_mode: BlendMode,
+
+    /// Whether a focus rectangle is drawn around this object when it has focus
+    /// (`_focusrect` property). `None` uses the setting of the root movie.
+    focus_rect: Option<bool>,
+
+    /// Increased when the content of this object changes in a way that its transform and
+    /// children don't show, such as by drawing into it.
+    content_version: u32,
+
+    /// The surface this object is drawn from while it is cached as a bitmap.
+    bitmap_cache: BitmapCache,
+
     /// Bit flags for various display object properites.
     flags: EnumSet<DisplayObjectFlags>,
 }
@@ -77,6 +104,12 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             first_child: None,
             prev_sibling: None,
             next_sibling: None,
+            masker: None,
+            maskee: None,
+            blend_mode: BlendMode::Normal,
+            focus_rect: None,
+            content_version: 0,
+            bitmap_cache: Default::default(),
             flags: DisplayObjectFlags::Visible.into(),
         }
     }
@@ -89,6 +122,8 @@ unsafe impl<'gc> Collect for DisplayObjectBase<'gc> {
         self.first_child.trace(cc);
         self.prev_sibling.trace(cc);
         self.next_sibling.trace(cc);
+        self.masker.trace(cc);
+        self.maskee.trace(cc);
     }
 }
 
@@ -285,6 +320,18 @@ impl<'gc> DisplayObjectBase<'gc> {
     ) {
         self.parent = parent;
     }
+    fn masker(&self) -> Option<DisplayObject<'gc>> {
+        self.masker
+    }
+    fn set_masker(&mut self, _context: MutationContext<'gc, '_>, node: Option<DisplayObject<'gc>>) {
+        self.masker = node;
+    }
+    fn maskee(&self) -> Option<DisplayObject<'gc>> {
+        self.maskee
+    }
+    fn set_maskee(&mut self, _context: MutationContext<'gc, '_>, node: Option<DisplayObject<'gc>>) {
+        self.maskee = node;
+    }
     fn first_child(&self) -> Option<DisplayObject<'gc>> {
         self.first_child
     }
@@ -350,6 +397,60 @@ impl<'gc> DisplayObjectBase<'gc> {
         }
     }
 
+    fn placed_by_script(&self) -> bool {
+        self.flags.contains(DisplayObjectFlags::PlacedByScript)
+    }
+
+    fn is_bitmap_cached(&self) -> bool {
+        self.flags.contains(DisplayObjectFlags::CacheAsBitmap)
+    }
+
+    fn set_bitmap_cached(&mut self, value: bool) {
+        if value {
+            self.flags.insert(DisplayObjectFlags::CacheAsBitmap);
+        } else {
+            self.flags.remove(DisplayObjectFlags::CacheAsBitmap);
+        }
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, value: BlendMode) {
+        self.blend_mode = value;
+    }
+
+    fn focus_rect(&self) -> Option<bool> {
+        self.focus_rect
+    }
+
+    fn set_focus_rect(&mut self, value: Option<bool>) {
+        self.focus_rect = value;
+    }
+
+    fn content_version(&self) -> u32 {
+        self.content_version
+    }
+
+    /// Records that the content of this object changed in a way that its transform and
+    /// children don't show, so that cached bitmaps of it and its ancestors are redrawn.
+    fn invalidate_content(&mut self) {
+        self.content_version = self.content_version.wrapping_add(1);
+    }
+
+    fn bitmap_cache(&self) -> &BitmapCache {
+        &self.bitmap_cache
+    }
+
+    fn set_placed_by_script(&mut self, value: bool) {
+        if value {
+            self.flags.insert(DisplayObjectFlags::PlacedByScript);
+        } else {
+            self.flags.remove(DisplayObjectFlags::PlacedByScript);
+        }
+    }
+
     fn swf_version(&self) -> u8 {
         self.parent
             .map(|p| p.swf_version())
@@ -372,6 +473,7 @@ impl<'gc> DisplayObjectBase<'gc> {
         MorphShape(MorphShape<'gc>),
         MovieClip(MovieClip<'gc>),
         Text(Text<'gc>),
+        Video(Video<'gc>),
     }
 )]
 pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>> {
@@ -633,6 +735,28 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
     fn set_clip_depth(&mut self, context: MutationContext<'gc, '_>, depth: Depth);
     fn parent(&self) -> Option<DisplayObject<'gc>>;
     fn set_parent(&mut self, context: MutationContext<'gc, '_>, parent: Option<DisplayObject<'gc>>);
+
+    /// The display object masking this object, set by `MovieClip.setMask`.
+    fn masker(&self) -> Option<DisplayObject<'gc>>;
+    fn set_masker(&mut self, context: MutationContext<'gc, '_>, node: Option<DisplayObject<'gc>>);
+
+    /// The display object masked by this object, set by `MovieClip.setMask`.
+    /// An object with a maskee is only rendered as that object's mask.
+    fn maskee(&self) -> Option<DisplayObject<'gc>>;
+    fn set_maskee(&mut self, context: MutationContext<'gc, '_>, node: Option<DisplayObject<'gc>>);
+
+    /// Unlinks this object from the objects masking it and masked by it, so that
+    /// neither stays masked or hidden once this object is removed.
+    fn clear_masks(&mut self, context: MutationContext<'gc, '_>) {
+        if let Some(mut masker) = self.masker() {
+            masker.set_maskee(context, None);
+            self.set_masker(context, None);
+        }
+        if let Some(mut maskee) = self.maskee() {
+            maskee.set_masker(context, None);
+            self.set_maskee(context, None);
+        }
+    }
     fn first_child(&self) -> Option<DisplayObject<'gc>>;
     fn set_first_child(
         &mut self,
@@ -718,6 +842,54 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
     /// When this flag is set, changes from SWF `PlaceObject` tags are ignored.
     fn set_transformed_by_script(&self, context: MutationContext<'gc, '_>, value: bool);
 
+    /// Whether this display object was placed or moved to its depth by ActionScript,
+    /// such as by `attachMovie` or `swapDepths`.
+    /// When this flag is set, SWF `RemoveObject` tags will not remove this object.
+    fn placed_by_script(&self) -> bool;
+
+    /// Sets whether this display object was placed or moved to its depth by ActionScript.
+    fn set_placed_by_script(&self, context: MutationContext<'gc, '_>, value: bool);
+
+    /// Whether this display object is cached as a bitmap.
+    /// Set by the `cacheAsBitmap` ActionScript property or a `PlaceObject3` tag.
+    ///
+    /// Cached objects are drawn into an offscreen surface, which is drawn again instead of
+    /// the object until it changes; see `render_cached`.
+    fn is_bitmap_cached(&self) -> bool;
+
+    /// Sets whether this display object is cached as a bitmap.
+    fn set_bitmap_cached(&self, context: MutationContext<'gc, '_>, value: bool);
+
+    /// Increased when the content of this display object changes in a way that its
+    /// transform and children don't show, such as by drawing into it.
+    fn content_version(&self) -> u32;
+
+    /// The surface this display object is drawn from while it is cached as a bitmap.
+    fn bitmap_cache(&self) -> Ref<BitmapCache>;
+
+    /// Hashes everything that affects how the content and children of this display object
+    /// look, other than its own transform. A cached bitmap of the object is drawn again
+    /// when this changes.
+    fn hash_content(&self, hasher: &mut FnvHasher) {
+        hasher.write_u32(self.content_version());
+        for child in self.children() {
+            hash_appearance(child, hasher);
+        }
+    }
+
+    /// How this display object is blended with the objects below it.
+    /// Set by a `PlaceObject3` tag.
+    fn blend_mode(&self) -> BlendMode;
+
+    /// Sets how this display object is blended with the objects below it.
+    fn set_blend_mode(&self, context: MutationContext<'gc, '_>, value: BlendMode);
+
+    /// Whether a focus rectangle is drawn around this object when it has focus.
+    /// `None` if the `_focusrect` property wasn't set on this object.
+    fn focus_rect(&self) -> Option<bool>;
+
+    fn set_focus_rect(&self, context: MutationContext<'gc, '_>, value: Option<bool>);
+
     /// Executes and propagates the given clip event.
     /// Events execute inside-out; the deepest child will react first, followed by its parent, and
     /// so forth.
@@ -730,6 +902,13 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
         ClipEventResult::NotHandled
     }
 
+    /// Broadcasts the `enterFrame` event to this object and its children.
+    ///
+    /// This is the first phase of a frame and runs for every object on the display
+    /// list before any frame scripts run in `run_frame`.
+    fn enter_frame(&mut self, _avm: &mut Avm1<'gc>, _context: &mut UpdateContext<'_, 'gc, '_>) {}
+
+    /// Advances this object to its next frame, running its timeline tags and actions.
     fn run_frame(&mut self, _avm: &mut Avm1<'gc>, _context: &mut UpdateContext<'_, 'gc, '_>) {}
     fn render(&self, _context: &mut RenderContext<'_, 'gc>) {}
 
@@ -746,9 +925,13 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
             }
         }
 
+        self.clear_masks(context.gc_context);
         self.set_removed(context.gc_context, true);
     }
 
+    fn as_bitmap(&self) -> Option<Bitmap<'gc>> {
+        None
+    }
     fn as_button(&self) -> Option<Button<'gc>> {
         None
     }
@@ -761,6 +944,12 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
     fn as_morph_shape(&self) -> Option<MorphShape<'gc>> {
         None
     }
+    fn as_text(&self) -> Option<Text<'gc>> {
+        None
+    }
+    fn as_video(&self) -> Option<Video<'gc>> {
+        None
+    }
     fn apply_place_object(
         &mut self,
         gc_context: MutationContext<'gc, '_>,
@@ -783,10 +972,28 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
             if let Some(ratio) = place_object.ratio {
                 if let Some(mut morph_shape) = self.as_morph_shape() {
                     morph_shape.set_ratio(gc_context, ratio);
+                } else if let Some(video) = self.as_video() {
+                    video.set_frame(gc_context, ratio);
                 }
             }
-            // Clip events only apply to movie clips.
-            if let Some(clip) = self.as_movie_clip() {
+            if let Some(is_bitmap_cached) = place_object.is_bitmap_cached {
+                self.set_bitmap_cached(gc_context, is_bitmap_cached);
+                // Bitmaps placed with PlaceObject3 are only smoothed when cached as a bitmap,
+                // so that pixel art stays crisp.
+                if let Some(bitmap) = self.as_bitmap() {
+                    bitmap.set_smoothing(gc_context, is_bitmap_cached);
+                }
+            }
+            if place_object.version >= 3 {
+                self.set_blend_mode(gc_context, place_object.blend_mode);
+            }
+            // TODO: Others will go here eventually.
+        }
+
+        // Clip events only apply to movie clips.
+        // They are only given when the clip is placed, so a move must not clear them.
+        if let Some(clip) = self.as_movie_clip() {
+            if !place_object.clip_actions.is_empty() {
                 // Convert from `swf::ClipAction` to Ruffle's `ClipAction`.
                 use crate::display_object::movie_clip::ClipAction;
                 clip.set_clip_actions(
@@ -800,7 +1007,6 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
                         .collect(),
                 );
             }
-            // TODO: Others will go here eventually.
         }
     }
 
@@ -827,6 +1033,13 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
         Value::Undefined // todo: impl for every type and delete this fallback
     }
 
+    /// The AVM2 object that represents this display object, if it has one.
+    fn object2(&self) -> Avm2Value<'gc> {
+        Avm2Value::Undefined // todo: impl for every type and delete this fallback
+    }
+
+    fn set_object2(&mut self, _mc: MutationContext<'gc, '_>, _to: Avm2Object<'gc>) {}
+
     /// Tests if a given stage position point intersects with the world bounds of this object.
     fn hit_test(&self, _pos: (Twips, Twips)) -> bool {
         false
@@ -880,9 +1093,18 @@ pub trait TDisplayObject<'gc>: 'gc + Collect + Debug + Into<DisplayObject<'gc>>
     /// object has not been post-instantiated, or that a top-level display
     /// object does not implement `object`.
     fn root(&self) -> DisplayObject<'gc> {
+        if let Some(clip) = self.as_movie_clip().filter(|clip| clip.lock_root()) {
+            return clip.into();
+        }
+
         let mut parent = self.parent();
 
         while let Some(p) = parent {
+            // A clip with `_lockroot` set is the root of everything inside it.
+            if let Some(clip) = p.as_movie_clip().filter(|clip| clip.lock_root()) {
+                return clip.into();
+            }
+
             let grandparent = p.parent();
 
             if grandparent.is_none() {
@@ -1033,6 +1255,26 @@ macro_rules! impl_display_object_sansbounds {
         ) {
             self.0.write(context).$field.set_parent(context, parent)
         }
+        fn masker(&self) -> Option<DisplayObject<'gc>> {
+            self.0.read().$field.masker()
+        }
+        fn set_masker(
+            &mut self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            node: Option<DisplayObject<'gc>>,
+        ) {
+            self.0.write(context).$field.set_masker(context, node);
+        }
+        fn maskee(&self) -> Option<DisplayObject<'gc>> {
+            self.0.read().$field.maskee()
+        }
+        fn set_maskee(
+            &mut self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            node: Option<DisplayObject<'gc>>,
+        ) {
+            self.0.write(context).$field.set_maskee(context, node);
+        }
         fn first_child(&self) -> Option<DisplayObject<'gc>> {
             self.0.read().$field.first_child()
         }
@@ -1088,6 +1330,40 @@ macro_rules! impl_display_object_sansbounds {
                 .$field
                 .set_transformed_by_script(value)
         }
+        fn placed_by_script(&self) -> bool {
+            self.0.read().$field.placed_by_script()
+        }
+        fn is_bitmap_cached(&self) -> bool {
+            self.0.read().$field.is_bitmap_cached()
+        }
+        fn set_bitmap_cached(&self, context: gc_arena::MutationContext<'gc, '_>, value: bool) {
+            self.0.write(context).$field.set_bitmap_cached(value)
+        }
+        fn content_version(&self) -> u32 {
+            self.0.read().$field.content_version()
+        }
+        fn bitmap_cache(&self) -> std::cell::Ref<crate::display_object::BitmapCache> {
+            std::cell::Ref::map(self.0.read(), |o| o.$field.bitmap_cache())
+        }
+        fn blend_mode(&self) -> swf::BlendMode {
+            self.0.read().$field.blend_mode()
+        }
+        fn set_blend_mode(
+            &self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            value: swf::BlendMode,
+        ) {
+            self.0.write(context).$field.set_blend_mode(value)
+        }
+        fn focus_rect(&self) -> Option<bool> {
+            self.0.read().$field.focus_rect()
+        }
+        fn set_focus_rect(&self, context: gc_arena::MutationContext<'gc, '_>, value: Option<bool>) {
+            self.0.write(context).$field.set_focus_rect(value)
+        }
+        fn set_placed_by_script(&self, context: gc_arena::MutationContext<'gc, '_>, value: bool) {
+            self.0.write(context).$field.set_placed_by_script(value)
+        }
         fn swf_version(&self) -> u8 {
             self.0.read().$field.swf_version()
         }
@@ -1148,7 +1424,7 @@ pub fn render_children<'gc>(
         // Check if we need to pop off a mask.
         // This must be a while loop because multiple masks can be popped
         // at the same dpeth.
-        while clip_depth > 0 && depth >= clip_depth {
+        while clip_depth > 0 && depth > clip_depth {
             context.renderer.pop_mask();
             clip_depth = clip_depth_stack.pop().unwrap();
         }
@@ -1159,9 +1435,20 @@ pub fn render_children<'gc>(
             context.renderer.push_mask();
             child.render(context);
             context.renderer.activate_mask();
+        } else if child.maskee().is_some() {
+            // Script masks are only rendered when rendering the object they mask.
         } else if child.visible() {
-            // Normal child.
-            child.render(context);
+            if let Some(masker) = child.masker() {
+                // Normal child masked by `setMask`.
+                context.renderer.push_mask();
+                render_script_mask(context, child, masker);
+                context.renderer.activate_mask();
+                render_blended(context, child);
+                context.renderer.pop_mask();
+            } else {
+                // Normal child.
+                render_blended(context, child);
+            }
         }
     }
 
@@ -1171,6 +1458,232 @@ pub fn render_children<'gc>(
     }
 }
 
+/// Renders a display object with its blend mode.
+///
+/// Objects with a blend mode other than `Normal` are rendered into an offscreen render target,
+/// which is then blended with the objects below it. Backends without offscreen render targets
+/// render them normally.
+fn render_blended<'gc>(context: &mut RenderContext<'_, 'gc>, child: DisplayObject<'gc>) {
+    let composite = Composite {
+        blend_mode: child.blend_mode(),
+        shader: None,
+    };
+    if child.is_bitmap_cached() {
+        render_cached(context, child, &composite);
+    } else if composite.blend_mode != BlendMode::Normal && context.renderer.push_render_target() {
+        child.render(context);
+        context.renderer.pop_and_composite(&composite);
+    } else {
+        child.render(context);
+    }
+}
+
+/// Renders a display object that is cached as a bitmap.
+///
+/// The object is rendered into an offscreen render target that the backend keeps, and later
+/// frames draw that surface again for as long as the object looks the same. Changes to its
+/// content or children, or scaling, rotating or color transforming it, render it again.
+/// Moving it only moves the surface, in whole pixels, unless part of the object was or
+/// would be outside the viewport.
+fn render_cached<'gc>(
+    context: &mut RenderContext<'_, 'gc>,
+    child: DisplayObject<'gc>,
+    composite: &Composite,
+) {
+    let transform = {
+        let parent = context.transform_stack.transform();
+        Transform {
+            matrix: parent.matrix * *child.matrix(),
+            color_transform: parent.color_transform * *child.color_transform(),
+        }
+    };
+    let mut hasher = FnvHasher::default();
+    child.hash_content(&mut hasher);
+    let content = hasher.finish();
+    let bounds = child.world_bounds();
+    let view = &context.view_bounds;
+    let is_on_screen = bounds.x_min >= view.x_min
+        && bounds.y_min >= view.y_min
+        && bounds.x_max <= view.x_max
+        && bounds.y_max <= view.y_max;
+
+    let cache = child.bitmap_cache();
+    if let Some((handle, dx, dy)) = cache.offset(&transform, content, is_on_screen) {
+        if context.renderer.composite_cached(handle, dx, dy, composite) {
+            return;
+        }
+    }
+
+    if context.renderer.push_render_target() {
+        child.render(context);
+        let surface = context
+            .renderer
+            .pop_and_cache(composite)
+            .map(|handle| CachedSurface {
+                handle,
+                transform,
+                content,
+                is_on_screen,
+            });
+        cache.0.replace(surface);
+    } else {
+        child.render(context);
+    }
+}
+
+/// Hashes everything that affects how a display object looks, for `hash_content`.
+fn hash_appearance(object: DisplayObject<'_>, hasher: &mut FnvHasher) {
+    object.as_ptr().hash(hasher);
+    object.depth().hash(hasher);
+    object.clip_depth().hash(hasher);
+    object.visible().hash(hasher);
+    object.is_bitmap_cached().hash(hasher);
+    std::mem::discriminant(&object.blend_mode()).hash(hasher);
+    object.masker().map(|masker| masker.as_ptr()).hash(hasher);
+    hash_transform(&object.transform(), hasher);
+    object.hash_content(hasher);
+}
+
+fn hash_transform(transform: &Transform, hasher: &mut FnvHasher) {
+    let matrix = &transform.matrix;
+    for value in &[matrix.a, matrix.b, matrix.c, matrix.d] {
+        value.to_bits().hash(hasher);
+    }
+    matrix.tx.get().hash(hasher);
+    matrix.ty.get().hash(hasher);
+    let color = &transform.color_transform;
+    for value in &[
+        color.r_mult,
+        color.g_mult,
+        color.b_mult,
+        color.a_mult,
+        color.r_add,
+        color.g_add,
+        color.b_add,
+        color.a_add,
+    ] {
+        value.to_bits().hash(hasher);
+    }
+}
+
+/// The surface a display object that is cached as a bitmap is drawn from.
+#[derive(Debug, Default)]
+pub struct BitmapCache(RefCell<Option<CachedSurface>>);
+
+impl Clone for BitmapCache {
+    /// Copies of a display object draw their own surface.
+    fn clone(&self) -> Self {
+        Default::default()
+    }
+}
+
+impl BitmapCache {
+    /// The surface to draw for an object with the given world transform and content hash,
+    /// and how many pixels to move it by. `None` if the object must be rendered again.
+    fn offset(
+        &self,
+        transform: &Transform,
+        content: u64,
+        is_on_screen: bool,
+    ) -> Option<(CacheHandle, i32, i32)> {
+        let surface = self.0.borrow();
+        let surface = surface.as_ref()?;
+        let (old, new) = (&surface.transform.matrix, &transform.matrix);
+        if surface.content != content
+            || surface.transform.color_transform != transform.color_transform
+            || (old.a, old.b, old.c, old.d) != (new.a, new.b, new.c, new.d)
+        {
+            return None;
+        }
+
+        let dx = (new.tx - old.tx).to_pixels().round() as i32;
+        let dy = (new.ty - old.ty).to_pixels().round() as i32;
+        if (dx != 0 || dy != 0) && !(surface.is_on_screen && is_on_screen) {
+            return None;
+        }
+        Some((surface.handle, dx, dy))
+    }
+}
+
+#[derive(Debug)]
+struct CachedSurface {
+    handle: CacheHandle,
+
+    /// The world transform the surface was drawn with.
+    transform: Transform,
+
+    /// The `hash_content` of the object when the surface was drawn.
+    content: u64,
+
+    /// Whether all of the object was within the viewport, so that the surface can be moved.
+    is_on_screen: bool,
+}
+
+/// Renders a mask set by `MovieClip.setMask`.
+///
+/// The mask can be anywhere on the display list, so it is rendered with its own world
+/// transform rather than relative to the object it masks.
+fn render_script_mask<'gc>(
+    context: &mut RenderContext<'_, 'gc>,
+    maskee: DisplayObject<'gc>,
+    masker: DisplayObject<'gc>,
+) {
+    let mut to_world = maskee
+        .parent()
+        .map(|parent| parent.local_to_global_matrix())
+        .unwrap_or_default();
+    to_world.invert();
+    let masker_parent = masker
+        .parent()
+        .map(|parent| parent.local_to_global_matrix())
+        .unwrap_or_default();
+    context.transform_stack.push(&Transform {
+        matrix: to_world * masker_parent,
+        color_transform: Default::default(),
+    });
+    masker.render(context);
+    context.transform_stack.pop();
+}
+
+/// Reads a property of a display object's AVM1 object, such as the `enabled` or
+/// `useHandCursor` properties that control how it reacts to the mouse.
+///
+/// Returns `None` if the property is undefined.
+pub fn get_avm1_property<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    display_object: DisplayObject<'gc>,
+    name: &str,
+) -> Option<Value<'gc>> {
+    let mut activation = Activation::from_nothing(
+        avm,
+        ActivationIdentifier::root("[Mouse Property]"),
+        context.swf.version(),
+        avm.global_object_cell(),
+        context.gc_context,
+        *context.levels.get(&0).unwrap(),
+    );
+    let object = display_object
+        .object()
+        .coerce_to_object(&mut activation, context);
+    match object.get(name, &mut activation, context) {
+        Ok(Value::Undefined) | Err(_) => None,
+        Ok(value) => Some(value),
+    }
+}
+
+/// Whether a button or button-mode movie clip reacts to the mouse, as set by its `enabled` property.
+pub fn is_enabled<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    display_object: DisplayObject<'gc>,
+) -> bool {
+    let swf_version = context.swf.version();
+    get_avm1_property(avm, context, display_object, "enabled")
+        .map(|value| value.as_bool(swf_version))
+        .unwrap_or(true)
+}
+
 impl<'gc> DisplayObject<'gc> {
     pub fn ptr_eq(a: DisplayObject<'gc>, b: DisplayObject<'gc>) -> bool {
         a.as_ptr() == b.as_ptr()
@@ -1194,6 +1707,13 @@ enum DisplayObjectFlags {
     /// Whether this object has been transformed by ActionScript.
     /// When this flag is set, changes from SWF `PlaceObject` tags are ignored.
     TransformedByScript,
+
+    /// Whether this object was placed or moved to its depth by ActionScript.
+    /// When this flag is set, SWF `RemoveObject` tags will not remove this object.
+    PlacedByScript,
+
+    /// Whether this object is cached as a bitmap (`cacheAsBitmap` property).
+    CacheAsBitmap,
 }
 
 pub struct ChildIter<'gc> {
@@ -5,7 +5,7 @@ use crate::backend::render::BitmapHandle;
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
-use gc_arena::{Collect, Gc, GcCell};
+use gc_arena::{Collect, Gc, GcCell, MutationContext};
 
 /// A Bitmap display object is a raw bitamp on the stage.
 /// This can only be instanitated on the display list in SWFv9 AVM2 files.
@@ -22,6 +22,9 @@ pub struct Bitmap<'gc>(GcCell<'gc, BitmapData<'gc>>);
 pub struct BitmapData<'gc> {
     base: DisplayObjectBase<'gc>,
     static_data: Gc<'gc, BitmapStatic>,
+
+    /// Whether the bitmap is drawn with bilinear filtering when scaled or rotated.
+    smoothing: bool,
 }
 
 impl<'gc> Bitmap<'gc> {
@@ -45,6 +48,7 @@ impl<'gc> Bitmap<'gc> {
                         height,
                     },
                 ),
+                smoothing: true,
             },
         ))
     }
@@ -61,6 +65,16 @@ impl<'gc> Bitmap<'gc> {
     pub fn height(self) -> u16 {
         self.0.read().static_data.height
     }
+
+    pub fn smoothing(self) -> bool {
+        self.0.read().smoothing
+    }
+
+    pub fn set_smoothing(self, gc_context: MutationContext<'gc, '_>, smoothing: bool) {
+        let mut bitmap = self.0.write(gc_context);
+        bitmap.smoothing = smoothing;
+        bitmap.base.invalidate_content();
+    }
 }
 
 impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
@@ -70,6 +84,10 @@ impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
         self.0.read().static_data.id
     }
 
+    fn as_bitmap(&self) -> Option<Self> {
+        Some(*self)
+    }
+
     fn self_bounds(&self) -> BoundingBox {
         BoundingBox {
             x_min: Twips::new(0),
@@ -95,6 +113,7 @@ impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
         context.renderer.render_bitmap(
             self.0.read().static_data.bitmap_handle,
             context.transform_stack.transform(),
+            self.smoothing(),
         );
 
         context.transform_stack.pop();
@@ -1,12 +1,14 @@
 use crate::avm1::{Avm1, Object, StageObject, Value};
 use crate::context::{ActionType, RenderContext, UpdateContext};
-use crate::display_object::{DisplayObjectBase, TDisplayObject};
+use crate::display_object::{hash_appearance, is_enabled, DisplayObjectBase, TDisplayObject};
 use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult};
 use crate::prelude::*;
 use crate::tag_utils::{SwfMovie, SwfSlice};
+use fnv::FnvHasher;
 use gc_arena::{Collect, GcCell, MutationContext};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::hash::Hasher;
 use std::sync::Arc;
 
 #[derive(Clone, Debug, Collect, Copy)]
@@ -86,6 +88,14 @@ impl<'gc> Button<'gc> {
         static_data.over_to_up_sound = sounds.over_to_up_sound;
     }
 
+    /// Whether this button tracks the mouse as a menu item, rather than as a push button.
+    ///
+    /// Menu buttons react to the mouse being dragged onto them while it is held down,
+    /// and stop tracking the mouse when it is dragged off.
+    pub fn is_tracked_as_menu(self) -> bool {
+        self.0.read().tracking == ButtonTracking::Menu
+    }
+
     /// Handles the ancient DefineButtonCxform SWF tag.
     /// Set the color transform for all children of each state.
     pub fn set_colors(
@@ -137,12 +147,26 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
         }
     }
 
+    fn enter_frame(&mut self, avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let children: Vec<_> = self.0.read().children.values().copied().collect();
+        for mut child in children {
+            child.enter_frame(avm, context);
+        }
+    }
+
     fn run_frame(&mut self, avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
         self.0
             .write(context.gc_context)
             .run_frame((*self).into(), avm, context)
     }
 
+    fn hash_content(&self, hasher: &mut FnvHasher) {
+        hasher.write_u32(self.content_version());
+        for child in self.0.read().children.values() {
+            hash_appearance(*child, hasher);
+        }
+    }
+
     fn render(&self, context: &mut RenderContext<'_, 'gc>) {
         context.transform_stack.push(&*self.transform());
 
@@ -168,13 +192,13 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
 
     fn mouse_pick(
         &self,
-        _avm: &mut Avm1<'gc>,
-        _context: &mut UpdateContext<'_, 'gc, '_>,
+        avm: &mut Avm1<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
         self_node: DisplayObject<'gc>,
         point: (Twips, Twips),
     ) -> Option<DisplayObject<'gc>> {
         // The button is hovered if the mouse is over any child nodes.
-        if self.hit_test(point) {
+        if self.hit_test(point) && is_enabled(avm, context, self_node) {
             Some(self_node)
         } else {
             None
@@ -312,42 +336,74 @@ impl<'gc> ButtonData<'gc> {
         let mut handled = ClipEventResult::NotHandled;
 
         // Translate the clip event to a button event, based on how the button state changes.
+        // Buttons that are dragged off while pressed display their over state; push buttons
+        // keep tracking the mouse until it is released, while menu buttons return to idle.
+        let static_data = self.static_data;
+        let static_data = static_data.read();
         let cur_state = self.state;
-        let new_state = match event {
-            ClipEvent::RollOut => ButtonState::Up,
-            ClipEvent::RollOver => ButtonState::Over,
-            ClipEvent::Press => ButtonState::Down,
-            ClipEvent::Release => ButtonState::Over,
-            ClipEvent::KeyPress { key_code } => {
+        let (new_state, condition, sound) = match (self.tracking, cur_state, event) {
+            (_, ButtonState::Up, ClipEvent::RollOver) => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::IdleToOverUp),
+                static_data.up_to_over_sound.as_ref(),
+            ),
+            (_, ButtonState::Over, ClipEvent::RollOut) => (
+                ButtonState::Up,
+                Some(swf::ButtonActionCondition::OverUpToIdle),
+                static_data.over_to_up_sound.as_ref(),
+            ),
+            (_, ButtonState::Over, ClipEvent::Press) => (
+                ButtonState::Down,
+                Some(swf::ButtonActionCondition::OverUpToOverDown),
+                static_data.over_to_down_sound.as_ref(),
+            ),
+            (_, ButtonState::Down, ClipEvent::Release) => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::OverDownToOverUp),
+                static_data.down_to_over_sound.as_ref(),
+            ),
+            (ButtonTracking::Push, ButtonState::Down, ClipEvent::DragOut) => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::OverDownToOutDown),
+                None,
+            ),
+            (ButtonTracking::Push, ButtonState::Over, ClipEvent::DragOver) => (
+                ButtonState::Down,
+                Some(swf::ButtonActionCondition::OutDownToOverDown),
+                None,
+            ),
+            (ButtonTracking::Push, ButtonState::Over, ClipEvent::ReleaseOutside) => (
+                ButtonState::Up,
+                Some(swf::ButtonActionCondition::OutDownToIdle),
+                static_data.over_to_up_sound.as_ref(),
+            ),
+            (ButtonTracking::Menu, ButtonState::Down, ClipEvent::DragOut) => (
+                ButtonState::Up,
+                Some(swf::ButtonActionCondition::OverDownToIdle),
+                None,
+            ),
+            (ButtonTracking::Menu, ButtonState::Up, ClipEvent::DragOver) => (
+                ButtonState::Down,
+                Some(swf::ButtonActionCondition::IdleToOverDown),
+                None,
+            ),
+            (_, _, ClipEvent::KeyPress { key_code }) => {
                 handled = self.run_actions(
                     context,
                     swf::ButtonActionCondition::KeyPress,
                     Some(key_code),
                 );
-                cur_state
+                (cur_state, None, None)
             }
+            (_, _, event) if event.is_button_event() => (cur_state, None, None),
             _ => return ClipEventResult::NotHandled,
         };
 
-        match (cur_state, new_state) {
-            (ButtonState::Up, ButtonState::Over) => {
-                self.run_actions(context, swf::ButtonActionCondition::IdleToOverUp, None);
-                self.play_sound(context, self.static_data.read().up_to_over_sound.as_ref());
-            }
-            (ButtonState::Over, ButtonState::Up) => {
-                self.run_actions(context, swf::ButtonActionCondition::OverUpToIdle, None);
-                self.play_sound(context, self.static_data.read().over_to_up_sound.as_ref());
-            }
-            (ButtonState::Over, ButtonState::Down) => {
-                self.run_actions(context, swf::ButtonActionCondition::OverUpToOverDown, None);
-                self.play_sound(context, self.static_data.read().over_to_down_sound.as_ref());
-            }
-            (ButtonState::Down, ButtonState::Over) => {
-                self.run_actions(context, swf::ButtonActionCondition::OverDownToOverUp, None);
-                self.play_sound(context, self.static_data.read().down_to_over_sound.as_ref());
-            }
-            _ => (),
-        };
+        if let Some(condition) = condition {
+            self.run_actions(context, condition, None);
+        }
+        self.play_sound(context, sound);
+        drop(static_data);
 
         // Queue ActionScript-defined event handlers after the SWF defined ones.
         // (e.g., clip.onRelease = foo).
@@ -365,7 +421,9 @@ impl<'gc> ButtonData<'gc> {
             }
         }
 
-        self.set_state(self_display_object, avm, context, new_state);
+        if new_state != cur_state {
+            self.set_state(self_display_object, avm, context, new_state);
+        }
 
         handled
     }
@@ -381,7 +439,15 @@ impl<'gc> ButtonData<'gc> {
                 .library_for_movie_mut(self.movie())
                 .get_sound(*id)
             {
-                let _ = context.audio.start_sound(sound_handle, sound_info);
+                // This button is locked while it handles events, so its sounds are
+                // owned by its parent instead.
+                context.audio_manager.start_sound(
+                    context.audio,
+                    sound_handle,
+                    sound_info,
+                    self.base.parent(),
+                    None,
+                );
             }
         }
     }
@@ -5,6 +5,7 @@ use crate::avm1::{Avm1, Object, StageObject, TObject, Value};
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::drawing::Drawing;
+use crate::events::KeyCode;
 use crate::font::{round_down_to_pixel, Glyph};
 use crate::html::{BoxBounds, FormatSpans, LayoutBox, TextFormat};
 use crate::prelude::*;
@@ -112,6 +113,22 @@ pub struct EditTextData<'gc> {
 
     /// Whether this text field is firing is variable binding (to prevent infinite loops).
     firing_variable_binding: bool,
+
+    /// The line shown at the top of the text field, starting from 1.
+    scroll: usize,
+
+    /// Whether the text field scrolls when the mouse wheel is used over it.
+    is_mouse_wheel_enabled: bool,
+
+    /// The position in the text where typed text is inserted, in bytes.
+    cursor: usize,
+
+    /// The text being composed with an input method editor, shown at the cursor
+    /// until it is committed.
+    composition: Option<String>,
+
+    /// The text spans that were laid out while composing, with the composition inserted.
+    composition_spans: Option<FormatSpans>,
 }
 
 impl<'gc> EditText<'gc> {
@@ -193,6 +210,11 @@ impl<'gc> EditText<'gc> {
                 variable,
                 bound_stage_object: None,
                 firing_variable_binding: false,
+                scroll: 1,
+                is_mouse_wheel_enabled: true,
+                cursor: 0,
+                composition: None,
+                composition_spans: None,
             },
         ));
 
@@ -383,6 +405,16 @@ impl<'gc> EditText<'gc> {
         self.relayout(context);
     }
 
+    /// Whether this is an input text field that the user can type into.
+    pub fn is_editable(self) -> bool {
+        !self.0.read().static_data.text.is_read_only
+    }
+
+    /// Whether the contents of this text field are hidden as a password.
+    pub fn is_password(self) -> bool {
+        self.0.read().static_data.text.is_password
+    }
+
     pub fn is_multiline(self) -> bool {
         self.0.read().is_multiline
     }
@@ -454,6 +486,93 @@ impl<'gc> EditText<'gc> {
         self.relayout(context);
     }
 
+    /// The position of the cursor, kept within the text.
+    fn cursor(self) -> usize {
+        let edit_text = self.0.read();
+        let text = edit_text.text_spans.text();
+        let mut cursor = edit_text.cursor.min(text.len());
+        while !text.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        cursor
+    }
+
+    /// The position of the cursor in characters, as reported by `Selection.getCaretIndex`.
+    pub fn caret_index(self) -> usize {
+        let cursor = self.cursor();
+        self.0.read().text_spans.text()[..cursor].chars().count()
+    }
+
+    /// Moves the cursor to the end of the text.
+    pub fn move_cursor_to_end(self, gc_context: MutationContext<'gc, '_>) {
+        let mut edit_text = self.0.write(gc_context);
+        edit_text.cursor = edit_text.text_spans.text().len();
+    }
+
+    /// Types text at the cursor, as if entered by the user.
+    pub fn type_text(self, text: &str, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let cursor = self.cursor();
+        self.replace_text(cursor, cursor, text, context);
+        self.0.write(context.gc_context).cursor = cursor + text.len();
+    }
+
+    /// Handles an editing key pressed while this text field has focus.
+    /// Returns whether the text changed.
+    pub fn handle_key_down(
+        self,
+        key_code: KeyCode,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> bool {
+        // Keys pressed while composing belong to the input method editor.
+        if self.0.read().composition.is_some() {
+            return false;
+        }
+
+        let cursor = self.cursor();
+        let text = self.text();
+        let previous_char = text[..cursor].char_indices().next_back().map(|(i, _)| i);
+        let next_char = text[cursor..].chars().next().map(|c| cursor + c.len_utf8());
+        let new_cursor = match key_code {
+            KeyCode::Backspace => {
+                if let Some(start) = previous_char {
+                    self.replace_text(start, cursor, "", context);
+                    self.0.write(context.gc_context).cursor = start;
+                    return true;
+                }
+                return false;
+            }
+            KeyCode::Delete => {
+                if let Some(end) = next_char {
+                    self.replace_text(cursor, end, "", context);
+                    return true;
+                }
+                return false;
+            }
+            KeyCode::Return if self.is_multiline() => {
+                self.type_text("\r", context);
+                return true;
+            }
+            KeyCode::Left => previous_char.unwrap_or(cursor),
+            KeyCode::Right => next_char.unwrap_or(cursor),
+            KeyCode::Home => 0,
+            KeyCode::End => text.len(),
+            _ => return false,
+        };
+        self.0.write(context.gc_context).cursor = new_cursor;
+        false
+    }
+
+    /// Sets the text being composed with an input method editor.
+    /// `None` ends the composition without changing the text.
+    pub fn set_composition(
+        self,
+        composition: Option<String>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) {
+        self.0.write(context.gc_context).composition = composition;
+        self.relayout(context);
+    }
+
     /// Construct a base text transform for a particular `EditText` span.
     ///
     /// This `text_transform` is separate from and relative to the base
@@ -541,6 +660,7 @@ impl<'gc> EditText<'gc> {
         let mut write = self.0.write(context);
 
         write.drawing.clear();
+        write.base.invalidate_content();
 
         if write.has_border {
             let bounds = write.bounds.clone();
@@ -583,14 +703,32 @@ impl<'gc> EditText<'gc> {
     /// have already been calculated and applied to HTML trees lowered into the
     /// text-span representation.
     fn relayout(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let cursor = self.cursor();
         let mut edit_text = self.0.write(context.gc_context);
+        edit_text.base.invalidate_content();
         let autosize = edit_text.autosize;
         let is_word_wrap = edit_text.is_word_wrap;
         let movie = edit_text.static_data.swf.clone();
         let width = edit_text.bounds.width() - Twips::from_pixels(Self::INTERNAL_PADDING * 2.0);
 
+        // Text being composed is shown underlined at the cursor.
+        let composition_spans = edit_text
+            .composition
+            .as_ref()
+            .filter(|composition| !composition.is_empty())
+            .map(|composition| {
+                let mut spans = edit_text.text_spans.clone();
+                spans.replace_text(cursor, cursor, composition, None);
+                let underline = TextFormat {
+                    underline: Some(true),
+                    ..Default::default()
+                };
+                spans.set_text_format(cursor, cursor + composition.len(), &underline);
+                spans
+            });
+
         let (new_layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
-            &edit_text.text_spans,
+            composition_spans.as_ref().unwrap_or(&edit_text.text_spans),
             context,
             movie,
             width,
@@ -600,6 +738,7 @@ impl<'gc> EditText<'gc> {
 
         edit_text.layout = new_layout;
         edit_text.intrinsic_bounds = intrinsic_bounds;
+        edit_text.composition_spans = composition_spans;
 
         match autosize {
             AutoSizeMode::None => {}
@@ -641,6 +780,56 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// The top of each line of laid-out text, from the first line to the last.
+    fn line_tops(self) -> Vec<Twips> {
+        let mut line_tops: Vec<Twips> = self
+            .0
+            .read()
+            .layout
+            .iter()
+            .map(|layout_box| layout_box.bounds().offset_y())
+            .collect();
+        line_tops.sort();
+        line_tops.dedup();
+        line_tops
+    }
+
+    /// The height of the area of the text field that shows text.
+    fn visible_text_height(self) -> Twips {
+        self.0.read().bounds.height() - Twips::from_pixels(Self::INTERNAL_PADDING * 2.0)
+    }
+
+    /// The line shown at the top of the text field, starting from 1.
+    pub fn scroll(self) -> usize {
+        self.0.read().scroll.min(self.maxscroll())
+    }
+
+    pub fn set_scroll(self, gc_context: MutationContext<'gc, '_>, scroll: usize) {
+        let scroll = scroll.max(1).min(self.maxscroll());
+        let mut edit_text = self.0.write(gc_context);
+        edit_text.scroll = scroll;
+        edit_text.base.invalidate_content();
+    }
+
+    /// The largest value of `scroll`, which is the first line that shows the end of the text.
+    pub fn maxscroll(self) -> usize {
+        let line_tops = self.line_tops();
+        let text_bottom = self.0.read().intrinsic_bounds.extent_y();
+        let visible_text_height = self.visible_text_height();
+        line_tops
+            .iter()
+            .position(|&top| text_bottom - top <= visible_text_height)
+            .map_or(line_tops.len().max(1), |line| line + 1)
+    }
+
+    pub fn is_mouse_wheel_enabled(self) -> bool {
+        self.0.read().is_mouse_wheel_enabled
+    }
+
+    pub fn set_mouse_wheel_enabled(self, gc_context: MutationContext<'gc, '_>, is_enabled: bool) {
+        self.0.write(gc_context).is_mouse_wheel_enabled = is_enabled;
+    }
+
     /// Measure the width and height of the `EditText`'s current text load.
     ///
     /// The returned tuple should be interpreted as width, then height.
@@ -664,9 +853,11 @@ impl<'gc> EditText<'gc> {
         // We're cheating a bit and not actually rendering text using the OS/web.
         // Instead, we embed an SWF version of Noto Sans to use as the "device font", and render
         // it the same as any other SWF outline text.
-        if let Some((text, _tf, font, params, color)) =
-            lbox.as_renderable_text(edit_text.text_spans.text())
-        {
+        let text_spans = edit_text
+            .composition_spans
+            .as_ref()
+            .unwrap_or(&edit_text.text_spans);
+        if let Some((text, _tf, font, params, color)) = lbox.as_renderable_text(text_spans.text()) {
             let baseline_adjustmnet =
                 font.get_baseline_for_height(params.height()) - params.height();
             font.evaluate(
@@ -999,13 +1190,32 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
             ..Default::default()
         });
 
+        // Only the lines from `scroll` to the bottom of the text field are shown.
+        let line_tops = self.line_tops();
+        let scroll_top = line_tops
+            .get(self.scroll() - 1)
+            .copied()
+            .unwrap_or_default();
+        let scroll_bottom = scroll_top + self.visible_text_height();
+        context.transform_stack.push(&Transform {
+            matrix: Matrix {
+                ty: Twips::zero() - scroll_top,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
         for layout_box in self.0.read().layout.iter() {
-            self.render_layout_box(context, layout_box);
+            let top = layout_box.bounds().offset_y();
+            if top == scroll_top || (top > scroll_top && top < scroll_bottom) {
+                self.render_layout_box(context, layout_box);
+            }
         }
 
         context.transform_stack.pop();
         context.transform_stack.pop();
         context.transform_stack.pop();
+        context.transform_stack.pop();
     }
 
     fn allow_as_mask(&self) -> bool {
@@ -1030,6 +1240,7 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
                 .retain(|&text_field| !DisplayObject::ptr_eq(text_field.into(), (*self).into()));
         }
 
+        self.clear_masks(context.gc_context);
         self.set_removed(context.gc_context, true);
     }
 }
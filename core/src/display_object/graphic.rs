@@ -1,9 +1,12 @@
 use crate::avm1::Avm1;
-use crate::backend::render::ShapeHandle;
+use crate::backend::render::{RenderBackend, ShapeHandle};
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
+use crate::profiler::Subsystem;
+use crate::shape_utils::{matrix_scale, transform_paths, DistilledShape, Scale9Grid};
 use gc_arena::{Collect, GcCell};
+use std::cell::RefCell;
 
 #[derive(Clone, Debug, Collect, Copy)]
 #[collect(no_drop)]
@@ -13,23 +16,97 @@ pub struct Graphic<'gc>(GcCell<'gc, GraphicData<'gc>>);
 pub struct GraphicData<'gc> {
     base: DisplayObjectBase<'gc>,
     static_data: gc_arena::Gc<'gc, GraphicStatic>,
+
+    /// The shape distorted by the parent clip's 9-slice grid, along with the grid and
+    /// matrix it was built for.
+    scale9_shape: RefCell<Option<(Scale9Grid, Matrix, ShapeHandle)>>,
+
+    /// The shape with its strokes tessellated for the scale it was last rendered at,
+    /// along with that scale.
+    scaled_shape: RefCell<Option<((f32, f32), ShapeHandle)>>,
 }
 
 impl<'gc> Graphic<'gc> {
     pub fn from_swf_tag(context: &mut UpdateContext<'_, 'gc, '_>, swf_shape: &swf::Shape) -> Self {
+        let shape = DistilledShape::from(swf_shape);
+        let has_scale_dependent_strokes = shape.has_scale_dependent_strokes();
+        let span = context.profiler.start();
+        let render_handle = context.renderer.register_shape(shape);
+        context.profiler.record(Subsystem::Tessellation, span);
         let static_data = GraphicStatic {
             id: swf_shape.id,
-            render_handle: context.renderer.register_shape(swf_shape.into()),
+            render_handle,
             bounds: swf_shape.shape_bounds.clone().into(),
+            shape: swf_shape.clone(),
+            has_scale_dependent_strokes,
         };
         Graphic(GcCell::allocate(
             context.gc_context,
             GraphicData {
                 base: Default::default(),
                 static_data: gc_arena::Gc::allocate(context.gc_context, static_data),
+                scale9_shape: RefCell::new(None),
+                scaled_shape: RefCell::new(None),
             },
         ))
     }
+
+    /// Returns the shape to render inside a clip with the given 9-slice grid.
+    ///
+    /// The grid is in the parent clip's coordinate space, so the shape is transformed
+    /// into that space, distorted, and transformed back. The result is cached until the
+    /// grid or this graphic's matrix change.
+    fn scale9_shape(&self, renderer: &mut dyn RenderBackend, grid: &Scale9Grid) -> ShapeHandle {
+        let read = self.0.read();
+        let matrix = *self.matrix();
+        let mut cache = read.scale9_shape.borrow_mut();
+        if let Some((cached_grid, cached_matrix, handle)) = &*cache {
+            if cached_grid == grid && *cached_matrix == matrix {
+                return *handle;
+            }
+        }
+
+        let mut inverse = matrix;
+        inverse.invert();
+        let mut shape = DistilledShape::from(&read.static_data.shape);
+        transform_paths(&mut shape.paths, |point| {
+            inverse * grid.map_point(matrix * point)
+        });
+
+        let handle = if let Some((_, _, handle)) = &*cache {
+            renderer.replace_shape(shape, *handle);
+            *handle
+        } else {
+            renderer.register_shape(shape)
+        };
+        *cache = Some((grid.clone(), matrix, handle));
+        handle
+    }
+
+    /// Returns the shape to render at the given scale on screen.
+    ///
+    /// The strokes of a shape that don't scale with it, including hairlines, are
+    /// tessellated for that scale. The result is cached until the scale changes.
+    fn scaled_shape(&self, renderer: &mut dyn RenderBackend, scale: (f32, f32)) -> ShapeHandle {
+        let read = self.0.read();
+        let mut cache = read.scaled_shape.borrow_mut();
+        if let Some((cached_scale, handle)) = &*cache {
+            if *cached_scale == scale {
+                return *handle;
+            }
+        }
+
+        let mut shape = DistilledShape::from(&read.static_data.shape);
+        shape.scale = scale;
+        let handle = if let Some((_, handle)) = &*cache {
+            renderer.replace_shape(shape, *handle);
+            *handle
+        } else {
+            renderer.register_shape(shape)
+        };
+        *cache = Some((scale, handle));
+        handle
+    }
 }
 
 impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
@@ -66,10 +143,19 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
 
         context.transform_stack.push(&*self.transform());
 
-        context.renderer.render_shape(
-            self.0.read().static_data.render_handle,
-            context.transform_stack.transform(),
-        );
+        let span = context.profiler.start();
+        let render_handle = if let Some(grid) = &context.scale9_grid {
+            self.scale9_shape(context.renderer, grid)
+        } else if self.0.read().static_data.has_scale_dependent_strokes {
+            let scale = matrix_scale(&context.transform_stack.transform().matrix);
+            self.scaled_shape(context.renderer, scale)
+        } else {
+            self.0.read().static_data.render_handle
+        };
+        context.profiler.record(Subsystem::Tessellation, span);
+        context
+            .renderer
+            .render_shape(render_handle, context.transform_stack.transform());
 
         context.transform_stack.pop();
     }
@@ -88,6 +174,8 @@ struct GraphicStatic {
     id: CharacterId,
     render_handle: ShapeHandle,
     bounds: BoundingBox,
+    shape: swf::Shape,
+    has_scale_dependent_strokes: bool,
 }
 
 unsafe impl<'gc> gc_arena::Collect for GraphicStatic {
@@ -3,7 +3,9 @@ use crate::backend::render::{RenderBackend, ShapeHandle};
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
+use crate::profiler::Subsystem;
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
+use std::cell::RefCell;
 use swf::Twips;
 
 #[derive(Clone, Debug, Collect, Copy)]
@@ -36,8 +38,15 @@ impl<'gc> MorphShape<'gc> {
         self.0.read().ratio
     }
 
+    /// Registers the shape at the given ratio with the renderer ahead of rendering it.
+    pub fn register_ratio(self, renderer: &mut dyn RenderBackend, ratio: u16) {
+        self.0.read().static_data.register_ratio(renderer, ratio);
+    }
+
     pub fn set_ratio(&mut self, gc_context: MutationContext<'gc, '_>, ratio: u16) {
-        self.0.write(gc_context).ratio = ratio;
+        let mut morph_shape = self.0.write(gc_context);
+        morph_shape.ratio = ratio;
+        morph_shape.base.invalidate_content();
     }
 }
 
@@ -59,24 +68,22 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
     fn render(&self, context: &mut RenderContext) {
         context.transform_stack.push(&*self.transform());
 
-        if let Some(frame) = self.0.read().static_data.frames.get(&self.ratio()) {
-            context
-                .renderer
-                .render_shape(frame.shape, context.transform_stack.transform());
-        } else {
-            log::warn!("Missing ratio for morph shape");
-        }
+        let span = context.profiler.start();
+        let shape = self
+            .0
+            .read()
+            .static_data
+            .get_shape(context.renderer, self.ratio());
+        context.profiler.record(Subsystem::Tessellation, span);
+        context
+            .renderer
+            .render_shape(shape, context.transform_stack.transform());
 
         context.transform_stack.pop();
     }
 
     fn self_bounds(&self) -> BoundingBox {
-        // TODO: Use the bounds of the current ratio.
-        if let Some(frame) = self.0.read().static_data.frames.get(&self.ratio()) {
-            frame.bounds.clone()
-        } else {
-            BoundingBox::default()
-        }
+        self.0.read().static_data.get_bounds(self.ratio())
     }
 }
 
@@ -88,28 +95,26 @@ unsafe impl<'gc> gc_arena::Collect for MorphShapeData<'gc> {
     }
 }
 
-/// A precalculated intermediate frame for a morph shape.
-struct Frame {
-    shape: ShapeHandle,
-    bounds: BoundingBox,
-}
-
 /// Static data shared between all instances of a morph shape.
 #[allow(dead_code)]
 pub struct MorphShapeStatic {
     id: CharacterId,
     start: swf::MorphShape,
     end: swf::MorphShape,
-    frames: fnv::FnvHashMap<u16, Frame>,
+
+    /// The interpolated shapes that have been registered with the renderer, keyed by ratio.
+    /// Ratios seen during preload are registered up front; any others are registered
+    /// the first time they are rendered.
+    frames: RefCell<fnv::FnvHashMap<u16, ShapeHandle>>,
 }
 
 impl MorphShapeStatic {
     pub fn from_swf_tag(renderer: &mut dyn RenderBackend, swf_tag: &swf::DefineMorphShape) -> Self {
-        let mut morph_shape = Self {
+        let morph_shape = Self {
             id: swf_tag.id,
             start: swf_tag.start.clone(),
             end: swf_tag.end.clone(),
-            frames: fnv::FnvHashMap::default(),
+            frames: RefCell::new(fnv::FnvHashMap::default()),
         };
         // Pre-register the start and end states.
         morph_shape.register_ratio(renderer, 0);
@@ -117,61 +122,44 @@ impl MorphShapeStatic {
         morph_shape
     }
 
-    pub fn register_ratio(&mut self, renderer: &mut dyn RenderBackend, ratio: u16) {
-        if self.frames.contains_key(&ratio) {
+    /// Returns the shape for the given ratio, registering it with the renderer if necessary.
+    pub fn get_shape(&self, renderer: &mut dyn RenderBackend, ratio: u16) -> ShapeHandle {
+        self.register_ratio(renderer, ratio);
+        self.frames.borrow()[&ratio]
+    }
+
+    /// Returns the bounds of the shape at the given ratio.
+    pub fn get_bounds(&self, ratio: u16) -> BoundingBox {
+        let ratio = Self::ratio_to_f32(ratio);
+        lerp_rectangle(&self.start.shape_bounds, &self.end.shape_bounds, ratio).into()
+    }
+
+    /// The position of a ratio between the start shape (0.0) and end shape (1.0).
+    fn ratio_to_f32(ratio: u16) -> f32 {
+        f32::from(ratio) / 65535.0
+    }
+
+    pub fn register_ratio(&self, renderer: &mut dyn RenderBackend, ratio: u16) {
+        if self.frames.borrow().contains_key(&ratio) {
             // Already registered.
             return;
         }
 
-        // Interpolate MorphShapes into a Shape.
-        use swf::{FillStyle, Gradient, LineStyle, ShapeRecord, ShapeStyles};
-        // Start shape is ratio 65535, end shape is ratio 0.
-        let b = f32::from(ratio) / 65535.0;
-        let a = 1.0 - b;
+        let shape = self.interpolate(Self::ratio_to_f32(ratio));
+        let handle = renderer.register_shape((&shape).into());
+        self.frames.borrow_mut().insert(ratio, handle);
+    }
+
+    /// Interpolates the start and end states into a shape.
+    /// Ratio 0.0 is the start shape, and ratio 1.0 is the end shape.
+    fn interpolate(&self, ratio: f32) -> swf::Shape {
+        use swf::{FillStyle, LineStyle, ShapeRecord, ShapeStyles, StyleChangeData};
         let fill_styles: Vec<FillStyle> = self
             .start
             .fill_styles
             .iter()
             .zip(self.end.fill_styles.iter())
-            .map(|(start, end)| match (start, end) {
-                (FillStyle::Color(start), FillStyle::Color(end)) => FillStyle::Color(Color {
-                    r: (a * f32::from(start.r) + b * f32::from(end.r)) as u8,
-                    g: (a * f32::from(start.g) + b * f32::from(end.g)) as u8,
-                    b: (a * f32::from(start.b) + b * f32::from(end.b)) as u8,
-                    a: (a * f32::from(start.a) + b * f32::from(end.a)) as u8,
-                }),
-                (FillStyle::LinearGradient(start), FillStyle::LinearGradient(end)) => {
-                    let records: Vec<swf::GradientRecord> = start
-                        .records
-                        .iter()
-                        .zip(end.records.iter())
-                        .map(|(start, end)| swf::GradientRecord {
-                            ratio: (f32::from(start.ratio) * a + f32::from(end.ratio) * b) as u8,
-                            color: Color {
-                                r: (a * f32::from(start.color.r) + b * f32::from(end.color.r))
-                                    as u8,
-                                g: (a * f32::from(start.color.g) + b * f32::from(end.color.g))
-                                    as u8,
-                                b: (a * f32::from(start.color.b) + b * f32::from(end.color.b))
-                                    as u8,
-                                a: (a * f32::from(start.color.a) + b * f32::from(end.color.a))
-                                    as u8,
-                            },
-                        })
-                        .collect();
-
-                    FillStyle::LinearGradient(Gradient {
-                        matrix: start.matrix,
-                        spread: start.spread,
-                        interpolation: start.interpolation,
-                        records,
-                    })
-                }
-                _ => {
-                    log::info!("Unhandled morph shape combination: {:?} {:?}", start, end);
-                    start.clone()
-                }
-            })
+            .map(|(start, end)| lerp_fill(start, end, ratio))
             .collect();
         let line_styles: Vec<LineStyle> = self
             .start
@@ -179,19 +167,15 @@ impl MorphShapeStatic {
             .iter()
             .zip(self.end.line_styles.iter())
             .map(|(start, end)| LineStyle {
-                width: Twips::new(
-                    ((start.width.get() as f32) * a + (end.width.get() as f32) * b) as i32,
-                ),
-                color: Color {
-                    r: (a * f32::from(start.color.r) + b * f32::from(end.color.r)) as u8,
-                    g: (a * f32::from(start.color.g) + b * f32::from(end.color.g)) as u8,
-                    b: (a * f32::from(start.color.b) + b * f32::from(end.color.b)) as u8,
-                    a: (a * f32::from(start.color.a) + b * f32::from(end.color.a)) as u8,
-                },
+                width: lerp_twips(start.width, end.width, ratio),
+                color: lerp_color(&start.color, &end.color, ratio),
                 start_cap: start.start_cap,
                 end_cap: start.end_cap,
                 join_style: start.join_style,
-                fill_style: None,
+                fill_style: match (&start.fill_style, &end.fill_style) {
+                    (Some(start), Some(end)) => Some(lerp_fill(start, end, ratio)),
+                    _ => None,
+                },
                 allow_scale_x: start.allow_scale_x,
                 allow_scale_y: start.allow_scale_y,
                 is_pixel_hinted: start.is_pixel_hinted,
@@ -208,7 +192,6 @@ impl MorphShapeStatic {
         let mut start_y = Twips::new(0);
         let mut end_x = Twips::new(0);
         let mut end_y = Twips::new(0);
-        // TODO: Feels like this could be cleaned up a bit.
         // We step through both the start records and end records, interpolating edges pairwise.
         // Fill style/line style changes should only appear in the start records.
         // However, StyleChangeRecord move_to can appear it both start and end records,
@@ -216,25 +199,15 @@ impl MorphShapeStatic {
         // in case one side is missing a move_to; it will implicitly use the last pen position.
         while let (Some(s), Some(e)) = (start, end) {
             match (s, e) {
-                (ShapeRecord::StyleChange(start_change), ShapeRecord::StyleChange(end_change)) => {
+                (ShapeRecord::StyleChange(start_change), ShapeRecord::StyleChange(_)) => {
                     let mut style_change = start_change.clone();
-                    if let Some((s_x, s_y)) = start_change.move_to {
-                        if let Some((e_x, e_y)) = end_change.move_to {
-                            start_x = s_x;
-                            start_y = s_y;
-                            end_x = e_x;
-                            end_y = e_y;
-                            style_change.move_to = Some((
-                                Twips::new(
-                                    (start_x.get() as f32 * a + end_x.get() as f32 * b) as i32,
-                                ),
-                                Twips::new(
-                                    (start_y.get() as f32 * a + end_y.get() as f32 * b) as i32,
-                                ),
-                            ));
-                        } else {
-                            panic!("Expected move_to for morph shape")
-                        }
+                    Self::update_pos(&mut start_x, &mut start_y, s);
+                    Self::update_pos(&mut end_x, &mut end_y, e);
+                    if style_change.move_to.is_some() {
+                        style_change.move_to = Some((
+                            lerp_twips(start_x, end_x, ratio),
+                            lerp_twips(start_y, end_y, ratio),
+                        ));
                     }
                     shape.push(ShapeRecord::StyleChange(style_change));
                     start = start_iter.next();
@@ -242,35 +215,35 @@ impl MorphShapeStatic {
                 }
                 (ShapeRecord::StyleChange(start_change), _) => {
                     let mut style_change = start_change.clone();
-                    if let Some((s_x, s_y)) = start_change.move_to {
-                        start_x = s_x;
-                        start_y = s_y;
+                    Self::update_pos(&mut start_x, &mut start_y, s);
+                    if style_change.move_to.is_some() {
                         style_change.move_to = Some((
-                            Twips::new((start_x.get() as f32 * a + end_x.get() as f32 * b) as i32),
-                            Twips::new((start_y.get() as f32 * a + end_y.get() as f32 * b) as i32),
+                            lerp_twips(start_x, end_x, ratio),
+                            lerp_twips(start_y, end_y, ratio),
                         ));
                     }
                     shape.push(ShapeRecord::StyleChange(style_change));
-                    Self::update_pos(&mut start_x, &mut start_y, s);
                     start = start_iter.next();
                 }
                 (_, ShapeRecord::StyleChange(end_change)) => {
-                    let mut style_change = end_change.clone();
-                    if let Some((e_x, e_y)) = end_change.move_to {
-                        end_x = e_x;
-                        end_y = e_y;
-                        style_change.move_to = Some((
-                            Twips::new((start_x.get() as f32 * a + end_x.get() as f32 * b) as i32),
-                            Twips::new((start_y.get() as f32 * a + end_y.get() as f32 * b) as i32),
-                        ));
+                    // Only the start records carry styles, so only the move is applied.
+                    Self::update_pos(&mut end_x, &mut end_y, e);
+                    if end_change.move_to.is_some() {
+                        shape.push(ShapeRecord::StyleChange(StyleChangeData {
+                            move_to: Some((
+                                lerp_twips(start_x, end_x, ratio),
+                                lerp_twips(start_y, end_y, ratio),
+                            )),
+                            fill_style_0: None,
+                            fill_style_1: None,
+                            line_style: None,
+                            new_styles: None,
+                        }));
                     }
-                    shape.push(ShapeRecord::StyleChange(style_change));
-                    Self::update_pos(&mut end_x, &mut end_y, s);
                     end = end_iter.next();
-                    continue;
                 }
                 _ => {
-                    shape.push(Self::interpolate_edges(s, e, a));
+                    shape.push(Self::interpolate_edges(s, e, ratio));
                     Self::update_pos(&mut start_x, &mut start_y, s);
                     Self::update_pos(&mut end_x, &mut end_y, e);
                     start = start_iter.next();
@@ -285,23 +258,17 @@ impl MorphShapeStatic {
         };
 
         let bounds = crate::shape_utils::calculate_shape_bounds(&shape[..]);
-        let shape = swf::Shape {
+        swf::Shape {
             version: 4,
             id: 0,
             shape_bounds: bounds.clone(),
-            edge_bounds: bounds.clone(),
+            edge_bounds: bounds,
             has_fill_winding_rule: false,
             has_non_scaling_strokes: false,
             has_scaling_strokes: true,
             styles,
             shape,
-        };
-
-        let frame = Frame {
-            shape: renderer.register_shape((&shape).into()),
-            bounds: bounds.into(),
-        };
-        self.frames.insert(ratio, frame);
+        }
     }
 
     fn update_pos(x: &mut Twips, y: &mut Twips, record: &swf::ShapeRecord) {
@@ -329,13 +296,43 @@ impl MorphShapeStatic {
         }
     }
 
+    /// Converts an edge into the deltas of a quadratic curve.
+    /// Straight edges become curves with the control point at their midpoint,
+    /// so that straight edges can be interpolated with curved edges.
+    fn edge_to_curve(record: &swf::ShapeRecord) -> (Twips, Twips, Twips, Twips) {
+        use swf::ShapeRecord;
+        match *record {
+            ShapeRecord::StraightEdge { delta_x, delta_y } => {
+                let control_dx = delta_x / 2;
+                let control_dy = delta_y / 2;
+                (
+                    control_dx,
+                    control_dy,
+                    delta_x - control_dx,
+                    delta_y - control_dy,
+                )
+            }
+            ShapeRecord::CurvedEdge {
+                control_delta_x,
+                control_delta_y,
+                anchor_delta_x,
+                anchor_delta_y,
+            } => (
+                control_delta_x,
+                control_delta_y,
+                anchor_delta_x,
+                anchor_delta_y,
+            ),
+            ShapeRecord::StyleChange(_) => unreachable!("{:?}", record),
+        }
+    }
+
     fn interpolate_edges(
         start: &swf::ShapeRecord,
         end: &swf::ShapeRecord,
-        a: f32,
+        ratio: f32,
     ) -> swf::ShapeRecord {
         use swf::ShapeRecord;
-        let b = 1.0 - a;
         match (start, end) {
             (
                 ShapeRecord::StraightEdge {
@@ -347,102 +344,19 @@ impl MorphShapeStatic {
                     delta_y: end_dy,
                 },
             ) => ShapeRecord::StraightEdge {
-                delta_x: Twips::new((start_dx.get() as f32 * a + end_dx.get() as f32 * b) as i32),
-                delta_y: Twips::new((start_dy.get() as f32 * a + end_dy.get() as f32 * b) as i32),
+                delta_x: lerp_twips(*start_dx, *end_dx, ratio),
+                delta_y: lerp_twips(*start_dy, *end_dy, ratio),
             },
-
-            (
+            _ => {
+                let (start_cdx, start_cdy, start_adx, start_ady) = Self::edge_to_curve(start);
+                let (end_cdx, end_cdy, end_adx, end_ady) = Self::edge_to_curve(end);
                 ShapeRecord::CurvedEdge {
-                    control_delta_x: start_cdx,
-                    control_delta_y: start_cdy,
-                    anchor_delta_x: start_adx,
-                    anchor_delta_y: start_ady,
-                },
-                ShapeRecord::CurvedEdge {
-                    control_delta_x: end_cdx,
-                    control_delta_y: end_cdy,
-                    anchor_delta_x: end_adx,
-                    anchor_delta_y: end_ady,
-                },
-            ) => ShapeRecord::CurvedEdge {
-                control_delta_x: Twips::new(
-                    (start_cdx.get() as f32 * a + end_cdx.get() as f32 * b) as i32,
-                ),
-                control_delta_y: Twips::new(
-                    (start_cdy.get() as f32 * a + end_cdy.get() as f32 * b) as i32,
-                ),
-                anchor_delta_x: Twips::new(
-                    (start_adx.get() as f32 * a + end_adx.get() as f32 * b) as i32,
-                ),
-                anchor_delta_y: Twips::new(
-                    (start_ady.get() as f32 * a + end_ady.get() as f32 * b) as i32,
-                ),
-            },
-
-            (
-                ShapeRecord::StraightEdge {
-                    delta_x: start_dx,
-                    delta_y: start_dy,
-                },
-                ShapeRecord::CurvedEdge {
-                    control_delta_x: end_cdx,
-                    control_delta_y: end_cdy,
-                    anchor_delta_x: end_adx,
-                    anchor_delta_y: end_ady,
-                },
-            ) => {
-                let start_cdx = *start_dx / 2;
-                let start_cdy = *start_dy / 2;
-                let start_adx = start_cdx;
-                let start_ady = start_cdy;
-                ShapeRecord::CurvedEdge {
-                    control_delta_x: Twips::new(
-                        (start_cdx.get() as f32 * a + end_cdx.get() as f32 * b) as i32,
-                    ),
-                    control_delta_y: Twips::new(
-                        (start_cdy.get() as f32 * a + end_cdy.get() as f32 * b) as i32,
-                    ),
-                    anchor_delta_x: Twips::new(
-                        (start_adx.get() as f32 * a + end_adx.get() as f32 * b) as i32,
-                    ),
-                    anchor_delta_y: Twips::new(
-                        (start_ady.get() as f32 * a + end_ady.get() as f32 * b) as i32,
-                    ),
+                    control_delta_x: lerp_twips(start_cdx, end_cdx, ratio),
+                    control_delta_y: lerp_twips(start_cdy, end_cdy, ratio),
+                    anchor_delta_x: lerp_twips(start_adx, end_adx, ratio),
+                    anchor_delta_y: lerp_twips(start_ady, end_ady, ratio),
                 }
             }
-
-            (
-                ShapeRecord::CurvedEdge {
-                    control_delta_x: start_cdx,
-                    control_delta_y: start_cdy,
-                    anchor_delta_x: start_adx,
-                    anchor_delta_y: start_ady,
-                },
-                ShapeRecord::StraightEdge {
-                    delta_x: end_dx,
-                    delta_y: end_dy,
-                },
-            ) => {
-                let end_cdx = *end_dx / 2;
-                let end_cdy = *end_dy / 2;
-                let end_adx = end_cdx;
-                let end_ady = end_cdy;
-                ShapeRecord::CurvedEdge {
-                    control_delta_x: Twips::new(
-                        (start_cdx.get() as f32 * a + end_cdx.get() as f32 * b) as i32,
-                    ),
-                    control_delta_y: Twips::new(
-                        (start_cdy.get() as f32 * a + end_cdy.get() as f32 * b) as i32,
-                    ),
-                    anchor_delta_x: Twips::new(
-                        (start_adx.get() as f32 * a + end_adx.get() as f32 * b) as i32,
-                    ),
-                    anchor_delta_y: Twips::new(
-                        (start_ady.get() as f32 * a + end_ady.get() as f32 * b) as i32,
-                    ),
-                }
-            }
-            _ => unreachable!("{:?} {:?}", start, end),
         }
     }
 }
@@ -453,3 +367,158 @@ unsafe impl<'gc> gc_arena::Collect for MorphShapeStatic {
         false
     }
 }
+
+fn lerp_f32(start: f32, end: f32, ratio: f32) -> f32 {
+    start + (end - start) * ratio
+}
+
+fn lerp_twips(start: Twips, end: Twips, ratio: f32) -> Twips {
+    Twips::new(lerp_f32(start.get() as f32, end.get() as f32, ratio).round() as i32)
+}
+
+fn lerp_u8(start: u8, end: u8, ratio: f32) -> u8 {
+    lerp_f32(f32::from(start), f32::from(end), ratio).round() as u8
+}
+
+fn lerp_color(start: &Color, end: &Color, ratio: f32) -> Color {
+    Color {
+        r: lerp_u8(start.r, end.r, ratio),
+        g: lerp_u8(start.g, end.g, ratio),
+        b: lerp_u8(start.b, end.b, ratio),
+        a: lerp_u8(start.a, end.a, ratio),
+    }
+}
+
+fn lerp_rectangle(start: &swf::Rectangle, end: &swf::Rectangle, ratio: f32) -> swf::Rectangle {
+    swf::Rectangle {
+        x_min: lerp_twips(start.x_min, end.x_min, ratio),
+        x_max: lerp_twips(start.x_max, end.x_max, ratio),
+        y_min: lerp_twips(start.y_min, end.y_min, ratio),
+        y_max: lerp_twips(start.y_max, end.y_max, ratio),
+    }
+}
+
+fn lerp_matrix(start: &swf::Matrix, end: &swf::Matrix, ratio: f32) -> swf::Matrix {
+    swf::Matrix {
+        a: lerp_f32(start.a, end.a, ratio),
+        b: lerp_f32(start.b, end.b, ratio),
+        c: lerp_f32(start.c, end.c, ratio),
+        d: lerp_f32(start.d, end.d, ratio),
+        tx: lerp_twips(start.tx, end.tx, ratio),
+        ty: lerp_twips(start.ty, end.ty, ratio),
+    }
+}
+
+fn lerp_gradient(start: &swf::Gradient, end: &swf::Gradient, ratio: f32) -> swf::Gradient {
+    // Morph gradients always have the same number of records in the start and end states.
+    let records = start
+        .records
+        .iter()
+        .zip(end.records.iter())
+        .map(|(start, end)| swf::GradientRecord {
+            ratio: lerp_u8(start.ratio, end.ratio, ratio),
+            color: lerp_color(&start.color, &end.color, ratio),
+        })
+        .collect();
+
+    swf::Gradient {
+        matrix: lerp_matrix(&start.matrix, &end.matrix, ratio),
+        spread: start.spread,
+        interpolation: start.interpolation,
+        records,
+    }
+}
+
+fn lerp_fill(start: &swf::FillStyle, end: &swf::FillStyle, ratio: f32) -> swf::FillStyle {
+    use swf::FillStyle;
+    match (start, end) {
+        (FillStyle::Color(start), FillStyle::Color(end)) => {
+            FillStyle::Color(lerp_color(start, end, ratio))
+        }
+        (FillStyle::LinearGradient(start), FillStyle::LinearGradient(end)) => {
+            FillStyle::LinearGradient(lerp_gradient(start, end, ratio))
+        }
+        (FillStyle::RadialGradient(start), FillStyle::RadialGradient(end)) => {
+            FillStyle::RadialGradient(lerp_gradient(start, end, ratio))
+        }
+        (
+            FillStyle::FocalGradient {
+                gradient: start,
+                focal_point: start_focal_point,
+            },
+            FillStyle::FocalGradient {
+                gradient: end,
+                focal_point: end_focal_point,
+            },
+        ) => FillStyle::FocalGradient {
+            gradient: lerp_gradient(start, end, ratio),
+            focal_point: lerp_f32(*start_focal_point, *end_focal_point, ratio),
+        },
+        (
+            FillStyle::Bitmap {
+                id,
+                matrix: start,
+                is_smoothed,
+                is_repeating,
+            },
+            FillStyle::Bitmap { matrix: end, .. },
+        ) => FillStyle::Bitmap {
+            id: *id,
+            matrix: lerp_matrix(start, end, ratio),
+            is_smoothed: *is_smoothed,
+            is_repeating: *is_repeating,
+        },
+        _ => {
+            log::info!("Unhandled morph shape combination: {:?} {:?}", start, end);
+            start.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{FillStyle, GradientInterpolation, GradientRecord, GradientSpread, ShapeRecord};
+
+    #[test]
+    fn lerp_radial_gradient() {
+        let gradient = |x, color| swf::Gradient {
+            matrix: swf::Matrix {
+                tx: Twips::new(x),
+                ..swf::Matrix::identity()
+            },
+            spread: GradientSpread::Pad,
+            interpolation: GradientInterpolation::RGB,
+            records: vec![GradientRecord { ratio: 0, color }],
+        };
+        let start = FillStyle::RadialGradient(gradient(0, Color::from_rgb(0x000000, 0)));
+        let end = FillStyle::RadialGradient(gradient(200, Color::from_rgb(0xFF00FF, 255)));
+        assert_eq!(
+            lerp_fill(&start, &end, 0.5),
+            FillStyle::RadialGradient(gradient(100, Color::from_rgb(0x800080, 128)))
+        );
+    }
+
+    #[test]
+    fn interpolate_straight_and_curved_edges() {
+        let start = ShapeRecord::StraightEdge {
+            delta_x: Twips::new(100),
+            delta_y: Twips::new(0),
+        };
+        let end = ShapeRecord::CurvedEdge {
+            control_delta_x: Twips::new(50),
+            control_delta_y: Twips::new(100),
+            anchor_delta_x: Twips::new(50),
+            anchor_delta_y: Twips::new(-100),
+        };
+        assert_eq!(
+            MorphShapeStatic::interpolate_edges(&start, &end, 0.5),
+            ShapeRecord::CurvedEdge {
+                control_delta_x: Twips::new(50),
+                control_delta_y: Twips::new(50),
+                anchor_delta_x: Twips::new(50),
+                anchor_delta_y: Twips::new(-50),
+            }
+        );
+    }
+}
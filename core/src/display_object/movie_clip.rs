@@ -1,19 +1,24 @@
 //! `MovieClip` display object and support code.
 use crate::avm1::{Avm1, Object, StageObject, TObject, Value};
-use crate::backend::audio::AudioStreamHandle;
+use crate::backend::audio::{AudioSource, AudioStreamHandle, SoundInstanceHandle, SoundTransform};
+use crate::backend::navigator::RequestOptions;
 
 use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm2::{Avm2, Object as Avm2Object, Value as Avm2Value};
 use crate::character::Character;
 use crate::context::{ActionType, RenderContext, UpdateContext};
 use crate::display_object::{
-    Bitmap, Button, DisplayObjectBase, EditText, Graphic, MorphShapeStatic, TDisplayObject, Text,
+    get_avm1_property, is_enabled, Bitmap, Button, DisplayObjectBase, EditText, Graphic,
+    MorphShape, MorphShapeStatic, TDisplayObject, Text, Video,
 };
 use crate::drawing::Drawing;
 use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult};
 use crate::font::Font;
 use crate::prelude::*;
-use crate::shape_utils::DrawCommand;
-use crate::tag_utils::{self, DecodeResult, SwfMovie, SwfSlice, SwfStream};
+use crate::profiler::Subsystem;
+use crate::shape_utils::{DrawCommand, Scale9Grid};
+use crate::tag_utils::{self, AvmType, DecodeResult, SwfMovie, SwfSlice, SwfStream};
+use crate::unimplemented::FeatureKind;
 use enumset::{EnumSet, EnumSetType};
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
 use smallvec::SmallVec;
@@ -41,7 +46,18 @@ pub struct MovieClipData<'gc> {
     static_data: Gc<'gc, MovieClipStatic>,
     tag_stream_pos: u64,
     current_frame: FrameNumber,
-    audio_stream: Option<AudioStreamHandle>,
+
+    /// How much of this clip's tag stream has been preloaded.
+    preload_progress: PreloadProgress<'gc>,
+
+    /// The stream sound playing on this clip's timeline, and the frame it started on.
+    audio_stream: Option<(AudioStreamHandle, FrameNumber)>,
+
+    /// The dynamic audio source attached with `attachAudio`, if any.
+    attached_audio: Option<SoundInstanceHandle>,
+
+    /// The volume and panning applied to audio attached to this clip.
+    sound_transform: SoundTransform,
     children: BTreeMap<Depth, DisplayObject<'gc>>,
     object: Option<Object<'gc>>,
     clip_actions: Vec<ClipAction>,
@@ -49,6 +65,18 @@ pub struct MovieClipData<'gc> {
     flags: EnumSet<MovieClipFlags>,
     avm1_constructor: Option<Object<'gc>>,
     drawing: Drawing,
+
+    /// The 9-slice scaling grid of this clip, set by `DefineScalingGrid` or `scale9Grid`.
+    scaling_grid: Option<BoundingBox>,
+
+    /// The display object that this clip was last dragged over, returned by `_droptarget`.
+    drop_target: Option<DisplayObject<'gc>>,
+
+    /// The AVM2 object of this clip, for clips in AS3 movies.
+    avm2_object: Option<Avm2Object<'gc>>,
+
+    /// The AS3 frame scripts registered with `addFrameScript`.
+    frame_scripts: Vec<Avm2FrameScript<'gc>>,
 }
 
 impl<'gc> MovieClip<'gc> {
@@ -61,7 +89,14 @@ impl<'gc> MovieClip<'gc> {
                 static_data: Gc::allocate(gc_context, MovieClipStatic::empty(swf)),
                 tag_stream_pos: 0,
                 current_frame: 0,
+                // Empty clips have no tags to preload.
+                preload_progress: PreloadProgress {
+                    complete: true,
+                    ..Default::default()
+                },
                 audio_stream: None,
+                attached_audio: None,
+                sound_transform: Default::default(),
                 children: BTreeMap::new(),
                 object: None,
                 clip_actions: Vec::new(),
@@ -69,6 +104,10 @@ impl<'gc> MovieClip<'gc> {
                 flags: EnumSet::empty(),
                 avm1_constructor: None,
                 drawing: Drawing::new(),
+                scaling_grid: None,
+                drop_target: None,
+                avm2_object: None,
+                frame_scripts: Vec::new(),
             },
         ))
     }
@@ -91,11 +130,15 @@ impl<'gc> MovieClip<'gc> {
                         total_frames: num_frames,
                         audio_stream_info: None,
                         frame_labels: HashMap::new(),
+                        scenes: Vec::new(),
                     },
                 ),
                 tag_stream_pos: 0,
                 current_frame: 0,
+                preload_progress: Default::default(),
                 audio_stream: None,
+                attached_audio: None,
+                sound_transform: Default::default(),
                 children: BTreeMap::new(),
                 object: None,
                 clip_actions: Vec::new(),
@@ -103,6 +146,10 @@ impl<'gc> MovieClip<'gc> {
                 flags: MovieClipFlags::Playing.into(),
                 avm1_constructor: None,
                 drawing: Drawing::new(),
+                scaling_grid: None,
+                drop_target: None,
+                avm2_object: None,
+                frame_scripts: Vec::new(),
             },
         ))
     }
@@ -132,230 +179,353 @@ impl<'gc> MovieClip<'gc> {
             .replace_with_movie(gc_context, movie)
     }
 
+    /// Continue the tag stream of this root clip with a newer version of its
+    /// movie, which has more of its data downloaded.
+    pub fn append_movie_data(self, gc_context: MutationContext<'gc, '_>, movie: Arc<SwfMovie>) {
+        let mut mc = self.0.write(gc_context);
+        let mut static_data = (&*mc.static_data).clone();
+        static_data.swf = movie.into();
+        mc.static_data = Gc::allocate(gc_context, static_data);
+    }
+
+    /// Preloads the definition tags of every frame that hasn't been preloaded yet.
     pub fn preload(
         self,
         avm: &mut Avm1<'gc>,
         context: &mut UpdateContext<'_, 'gc, '_>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
     ) {
+        while !self.preload_frame(avm, context, morph_shapes) {}
+    }
+
+    /// Preloads the next frame of a movie that may still be downloading.
+    ///
+    /// The morph shapes it defines are kept with the clip's preload progress until
+    /// their frame has been completely preloaded, since later tags of the frame can
+    /// still place them at new ratios. They are registered in the library afterward.
+    /// Returns `true` once every frame has been preloaded.
+    pub fn preload_streaming_frame(
+        self,
+        avm: &mut Avm1<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> bool {
+        let mut morph_shapes = std::mem::take(
+            &mut self
+                .0
+                .write(context.gc_context)
+                .preload_progress
+                .morph_shapes,
+        );
+        let frames_loaded = self.frames_loaded();
+        let complete = self.preload_frame(avm, context, &mut morph_shapes);
+        if complete || self.frames_loaded() > frames_loaded {
+            let library = context.library.library_for_movie_mut(self.movie().unwrap());
+            for (id, morph_shape) in morph_shapes {
+                library.register_character(id, Character::MorphShape(morph_shape));
+            }
+        } else {
+            self.0
+                .write(context.gc_context)
+                .preload_progress
+                .morph_shapes = morph_shapes;
+        }
+        complete
+    }
+
+    /// Preloads the definition tags of the next frame that hasn't been preloaded yet.
+    ///
+    /// Frames can only be played once they have been preloaded, so this allows playback to
+    /// start before the rest of a large movie has been processed.
+    /// Returns `true` once every frame has been preloaded.
+    pub fn preload_frame(
+        self,
+        avm: &mut Avm1<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
+    ) -> bool {
         use swf::TagCode;
+        if self.0.read().preload_progress.complete {
+            return true;
+        }
+
         // TODO: Re-creating static data because preload step occurs after construction.
         // Should be able to hoist this up somewhere, or use MaybeUninit.
         let mut static_data = (&*self.0.read().static_data).clone();
         let data = self.0.read().static_data.swf.clone();
-        let mut reader = data.read_from(self.0.read().tag_stream_pos);
-        let mut cur_frame = 1;
-        let mut ids = fnv::FnvHashMap::default();
+        let mut progress = std::mem::take(&mut self.0.write(context.gc_context).preload_progress);
+        let mut reader = data.read_from(progress.next_tag_pos);
+        let mut cur_frame = progress.frames_loaded + 1;
+        let mut is_end = false;
+        let ids = &mut progress.ids;
         let version = reader.version();
-        let tag_callback = |reader: &mut SwfStream<&[u8]>, tag_code, tag_len| {
-            let data = *reader.get_inner().get_ref();
-            let tag_pos = reader.get_inner().position() as usize;
-            let tag_slice = &data[tag_pos..tag_pos + tag_len];
-            let reader = &mut SwfStream::new(std::io::Cursor::new(tag_slice), version);
-            match tag_code {
-                TagCode::FileAttributes => {
-                    let attributes = reader.read_file_attributes()?;
-                    if attributes.is_action_script_3 {
-                        log::warn!("This SWF contains ActionScript 3 which is not yet supported by Ruffle. The movie may not work as intended.");
+        let avm_type = data.movie.avm_type();
+        let tag_callback =
+            |reader: &mut SwfStream<&[u8]>, tag_code, tag_len| {
+                let data = *reader.get_inner().get_ref();
+                let tag_pos = reader.get_inner().position() as usize;
+                let tag_slice = &data[tag_pos..tag_pos + tag_len];
+                let reader = &mut SwfStream::new(std::io::Cursor::new(tag_slice), version);
+                match tag_code {
+                    TagCode::End => {
+                        is_end = true;
+                        Ok(())
                     }
-                    Ok(())
-                }
-                TagCode::DefineBits => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits(context, reader, tag_len),
-                TagCode::DefineBitsJpeg2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits_jpeg_2(context, reader, tag_len),
-                TagCode::DefineBitsJpeg3 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits_jpeg_3(context, reader, tag_len),
-                TagCode::DefineBitsJpeg4 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits_jpeg_4(context, reader, tag_len),
-                TagCode::DefineBitsLossless => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits_lossless(context, reader, 1),
-                TagCode::DefineBitsLossless2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_bits_lossless(context, reader, 2),
-                TagCode::DefineButton => self
-                    .0
-                    .write(context.gc_context)
-                    .define_button_1(context, reader),
-                TagCode::DefineButton2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_button_2(context, reader),
-                TagCode::DefineButtonCxform => self
-                    .0
-                    .write(context.gc_context)
-                    .define_button_cxform(context, reader, tag_len),
-                TagCode::DefineButtonSound => self
-                    .0
-                    .write(context.gc_context)
-                    .define_button_sound(context, reader),
-                TagCode::DefineEditText => self
-                    .0
-                    .write(context.gc_context)
-                    .define_edit_text(context, reader),
-                TagCode::DefineFont => self
-                    .0
-                    .write(context.gc_context)
-                    .define_font_1(context, reader),
-                TagCode::DefineFont2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_font_2(context, reader),
-                TagCode::DefineFont3 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_font_3(context, reader),
-                TagCode::DefineFont4 => unimplemented!(),
-                TagCode::DefineMorphShape => self.0.write(context.gc_context).define_morph_shape(
-                    context,
-                    reader,
-                    morph_shapes,
-                    1,
-                ),
-                TagCode::DefineMorphShape2 => self.0.write(context.gc_context).define_morph_shape(
-                    context,
-                    reader,
-                    morph_shapes,
-                    2,
-                ),
-                TagCode::DefineShape => self
-                    .0
-                    .write(context.gc_context)
-                    .define_shape(context, reader, 1),
-                TagCode::DefineShape2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_shape(context, reader, 2),
-                TagCode::DefineShape3 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_shape(context, reader, 3),
-                TagCode::DefineShape4 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_shape(context, reader, 4),
-                TagCode::DefineSound => self
-                    .0
-                    .write(context.gc_context)
-                    .define_sound(context, reader),
-                TagCode::DefineSprite => self.0.write(context.gc_context).define_sprite(
-                    avm,
-                    context,
-                    reader,
-                    tag_len,
-                    morph_shapes,
-                ),
-                TagCode::DefineText => self
-                    .0
-                    .write(context.gc_context)
-                    .define_text(context, reader, 1),
-                TagCode::DefineText2 => self
-                    .0
-                    .write(context.gc_context)
-                    .define_text(context, reader, 2),
-                TagCode::DoInitAction => self.do_init_action(avm, context, reader, tag_len),
-                TagCode::ExportAssets => self
-                    .0
-                    .write(context.gc_context)
-                    .export_assets(context, reader),
-                TagCode::FrameLabel => self.0.write(context.gc_context).frame_label(
-                    context,
-                    reader,
-                    tag_len,
-                    cur_frame,
-                    &mut static_data,
-                ),
-                TagCode::JpegTables => self
-                    .0
-                    .write(context.gc_context)
-                    .jpeg_tables(context, reader, tag_len),
-                TagCode::PlaceObject => self.0.write(context.gc_context).preload_place_object(
-                    context,
-                    reader,
-                    tag_len,
-                    &mut ids,
-                    morph_shapes,
-                    1,
-                ),
-                TagCode::PlaceObject2 => self.0.write(context.gc_context).preload_place_object(
-                    context,
-                    reader,
-                    tag_len,
-                    &mut ids,
-                    morph_shapes,
-                    2,
-                ),
-                TagCode::PlaceObject3 => self.0.write(context.gc_context).preload_place_object(
-                    context,
-                    reader,
-                    tag_len,
-                    &mut ids,
-                    morph_shapes,
-                    3,
-                ),
-                TagCode::PlaceObject4 => self.0.write(context.gc_context).preload_place_object(
-                    context,
-                    reader,
-                    tag_len,
-                    &mut ids,
-                    morph_shapes,
-                    4,
-                ),
-                TagCode::RemoveObject => self
-                    .0
-                    .write(context.gc_context)
-                    .preload_remove_object(context, reader, &mut ids, 1),
-                TagCode::RemoveObject2 => self
-                    .0
-                    .write(context.gc_context)
-                    .preload_remove_object(context, reader, &mut ids, 2),
-                TagCode::ShowFrame => self.0.write(context.gc_context).preload_show_frame(
-                    context,
-                    reader,
-                    &mut cur_frame,
-                ),
-                TagCode::ScriptLimits => {
-                    self.0.write(context.gc_context).script_limits(reader, avm)
-                }
-                TagCode::SoundStreamHead => self
-                    .0
-                    .write(context.gc_context)
-                    .preload_sound_stream_head(context, reader, cur_frame, &mut static_data, 1),
-                TagCode::SoundStreamHead2 => self
-                    .0
-                    .write(context.gc_context)
-                    .preload_sound_stream_head(context, reader, cur_frame, &mut static_data, 2),
-                TagCode::SoundStreamBlock => {
-                    self.0.write(context.gc_context).preload_sound_stream_block(
+                    TagCode::DefineBits => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits(context, reader, tag_len),
+                    TagCode::DefineBitsJpeg2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits_jpeg_2(context, reader, tag_len),
+                    TagCode::DefineBitsJpeg3 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits_jpeg_3(context, reader, tag_len),
+                    TagCode::DefineBitsJpeg4 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits_jpeg_4(context, reader, tag_len),
+                    TagCode::DefineBitsLossless => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits_lossless(context, reader, 1),
+                    TagCode::DefineBitsLossless2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_bits_lossless(context, reader, 2),
+                    TagCode::DefineButton => self
+                        .0
+                        .write(context.gc_context)
+                        .define_button_1(context, reader),
+                    TagCode::DefineButton2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_button_2(context, reader),
+                    TagCode::DefineButtonCxform => self
+                        .0
+                        .write(context.gc_context)
+                        .define_button_cxform(context, reader, tag_len),
+                    TagCode::DefineButtonSound => self
+                        .0
+                        .write(context.gc_context)
+                        .define_button_sound(context, reader),
+                    TagCode::DefineEditText => self
+                        .0
+                        .write(context.gc_context)
+                        .define_edit_text(context, reader),
+                    TagCode::DefineFont => self
+                        .0
+                        .write(context.gc_context)
+                        .define_font_1(context, reader),
+                    TagCode::DefineFont2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_font_2(context, reader),
+                    TagCode::DefineFont3 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_font_3(context, reader),
+                    TagCode::DefineFont4 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_font_4(context, reader),
+                    TagCode::DefineMorphShape => self
+                        .0
+                        .write(context.gc_context)
+                        .define_morph_shape(context, reader, morph_shapes, 1),
+                    TagCode::DefineMorphShape2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_morph_shape(context, reader, morph_shapes, 2),
+                    TagCode::DefineShape => self
+                        .0
+                        .write(context.gc_context)
+                        .define_shape(context, reader, 1),
+                    TagCode::DefineShape2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_shape(context, reader, 2),
+                    TagCode::DefineShape3 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_shape(context, reader, 3),
+                    TagCode::DefineShape4 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_shape(context, reader, 4),
+                    TagCode::DefineSound => self
+                        .0
+                        .write(context.gc_context)
+                        .define_sound(context, reader),
+                    TagCode::DefineSceneAndFrameLabelData => self
+                        .0
+                        .write(context.gc_context)
+                        .scene_and_frame_labels(reader, &mut static_data),
+                    TagCode::DefineScalingGrid => self
+                        .0
+                        .write(context.gc_context)
+                        .define_scaling_grid(context, reader),
+                    TagCode::DefineSprite => self.0.write(context.gc_context).define_sprite(
+                        avm,
                         context,
                         reader,
+                        tag_len,
+                        morph_shapes,
+                    ),
+                    TagCode::DefineText => self
+                        .0
+                        .write(context.gc_context)
+                        .define_text(context, reader, 1),
+                    TagCode::DefineText2 => self
+                        .0
+                        .write(context.gc_context)
+                        .define_text(context, reader, 2),
+                    TagCode::DefineVideoStream => self
+                        .0
+                        .write(context.gc_context)
+                        .define_video_stream(context, reader),
+                    // Each movie only runs the code of its own VM.
+                    TagCode::DoInitAction if avm_type == AvmType::Avm1 => {
+                        self.do_init_action(avm, context, reader, tag_len)
+                    }
+                    TagCode::DoAbc if avm_type == AvmType::Avm2 => self.do_abc(context, reader),
+                    TagCode::ExportAssets => self
+                        .0
+                        .write(context.gc_context)
+                        .export_assets(context, reader),
+                    TagCode::SymbolClass if avm_type == AvmType::Avm2 => self
+                        .0
+                        .write(context.gc_context)
+                        .symbol_class(context, reader),
+                    TagCode::ImportAssets => self
+                        .0
+                        .write(context.gc_context)
+                        .import_assets(context, reader, 1),
+                    TagCode::ImportAssets2 => self
+                        .0
+                        .write(context.gc_context)
+                        .import_assets(context, reader, 2),
+                    TagCode::FrameLabel => self.0.write(context.gc_context).frame_label(
+                        context,
+                        reader,
+                        tag_len,
                         cur_frame,
                         &mut static_data,
+                    ),
+                    TagCode::JpegTables => self
+                        .0
+                        .write(context.gc_context)
+                        .jpeg_tables(context, reader, tag_len),
+                    TagCode::PlaceObject => self.0.write(context.gc_context).preload_place_object(
+                        context,
+                        reader,
                         tag_len,
-                    )
+                        ids,
+                        morph_shapes,
+                        1,
+                    ),
+                    TagCode::PlaceObject2 => self.0.write(context.gc_context).preload_place_object(
+                        context,
+                        reader,
+                        tag_len,
+                        ids,
+                        morph_shapes,
+                        2,
+                    ),
+                    TagCode::PlaceObject3 => self.0.write(context.gc_context).preload_place_object(
+                        context,
+                        reader,
+                        tag_len,
+                        ids,
+                        morph_shapes,
+                        3,
+                    ),
+                    TagCode::PlaceObject4 => self.0.write(context.gc_context).preload_place_object(
+                        context,
+                        reader,
+                        tag_len,
+                        ids,
+                        morph_shapes,
+                        4,
+                    ),
+                    TagCode::RemoveObject => self
+                        .0
+                        .write(context.gc_context)
+                        .preload_remove_object(context, reader, ids, 1),
+                    TagCode::RemoveObject2 => self
+                        .0
+                        .write(context.gc_context)
+                        .preload_remove_object(context, reader, ids, 2),
+                    TagCode::VideoFrame => self
+                        .0
+                        .write(context.gc_context)
+                        .preload_video_frame(context, reader),
+                    TagCode::ShowFrame => self.0.write(context.gc_context).preload_show_frame(
+                        context,
+                        reader,
+                        &mut cur_frame,
+                    ),
+                    TagCode::ScriptLimits => {
+                        self.0.write(context.gc_context).script_limits(reader, avm)
+                    }
+                    TagCode::SoundStreamHead => self
+                        .0
+                        .write(context.gc_context)
+                        .preload_sound_stream_head(context, reader, cur_frame, &mut static_data, 1),
+                    TagCode::SoundStreamHead2 => self
+                        .0
+                        .write(context.gc_context)
+                        .preload_sound_stream_head(context, reader, cur_frame, &mut static_data, 2),
+                    TagCode::SoundStreamBlock => {
+                        self.0.write(context.gc_context).preload_sound_stream_block(
+                            context,
+                            reader,
+                            cur_frame,
+                            &mut static_data,
+                            tag_len,
+                        )
+                    }
+                    TagCode::CsmTextSettings
+                    | TagCode::DefineBinaryData
+                    | TagCode::DefineFontAlignZones
+                    | TagCode::DefineFontInfo
+                    | TagCode::DefineFontInfo2
+                    | TagCode::DefineFontName
+                    | TagCode::SetTabIndex
+                    | TagCode::StartSound2 => {
+                        context
+                            .unimplemented_features
+                            .record(FeatureKind::Tag, &format!("{:?}", tag_code));
+                        Ok(())
+                    }
+                    _ => Ok(()),
                 }
-                _ => Ok(()),
-            }
-        };
-        let _ = tag_utils::decode_tags(&mut reader, tag_callback, TagCode::End);
-        self.0.write(context.gc_context).static_data =
-            Gc::allocate(context.gc_context, static_data);
+            };
+        let result = tag_utils::decode_tags(&mut reader, tag_callback, TagCode::ShowFrame);
+        progress.next_tag_pos = reader.get_ref().position();
+        progress.frames_loaded = cur_frame - 1;
+        // Stop at the `End` tag, the end of the data, or any corrupt tag header.
+        // Movies that are still downloading wait for more data instead.
+        let is_end_of_data = progress.next_tag_pos >= self.0.read().tag_stream_len() as u64;
+        progress.complete = is_end
+            || if is_end_of_data {
+                !data.movie.is_loading()
+            } else {
+                result.is_err()
+            };
+        let complete = progress.complete;
+        let mut mc = self.0.write(context.gc_context);
+        mc.preload_progress = progress;
+        mc.static_data = Gc::allocate(context.gc_context, static_data);
+        drop(mc);
 
         // Finalize audio stream.
-        if self.0.read().static_data.audio_stream_info.is_some() {
+        if complete && self.0.read().static_data.audio_stream_info.is_some() {
             context.audio.preload_sound_stream_end(self.0.read().id());
         }
+
+        complete
     }
 
     #[inline]
@@ -396,11 +566,119 @@ impl<'gc> MovieClip<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn do_abc(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&[u8]>,
+    ) -> DecodeResult {
+        // TODO: Lazily initialized ABC files should only run once one of
+        // their definitions is used.
+        let _flags = reader.read_u32()?;
+        let name = reader.read_c_string()?;
+        let position = reader.get_ref().position() as usize;
+        let abc = &reader.get_ref().get_ref()[position..];
+
+        if let Err(e) = Avm2::load_abc(abc, context) {
+            log::warn!("Error loading ABC file {}: {}", name, e);
+        }
+
+        Ok(())
+    }
+
+    /// Construct the AVM2 object of this clip, as an instance of the class
+    /// linked to it by `SymbolClass`, or of `MovieClip`.
+    ///
+    /// This runs the class constructor, which registers the frame scripts of
+    /// the clip, so the script of the current frame is queued afterwards.
+    pub fn construct_avm2_object(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        if self.0.read().avm2_object.is_some() {
+            return;
+        }
+
+        let class_name = context
+            .library
+            .library_for_movie_mut(self.0.read().movie())
+            .avm2_class_name(self.id())
+            .unwrap_or("flash.display.MovieClip")
+            .to_string();
+
+        if let Err(e) = Avm2::construct_display_object(self.into(), &class_name, context) {
+            log::error!(
+                "Got error when constructing AVM2 object {}: {}",
+                class_name,
+                e
+            );
+            return;
+        }
+
+        self.queue_frame_script(context);
+    }
+
+    /// Set the AS3 script to run when the playhead enters a frame, or remove
+    /// it if `callable` is `None`.
+    ///
+    /// `frame_id` should be 1-based.
+    pub fn register_frame_script(
+        self,
+        frame_id: FrameNumber,
+        callable: Option<Avm2Object<'gc>>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) {
+        let mut mc = self.0.write(context.gc_context);
+        mc.frame_scripts
+            .retain(|script| script.frame_id != frame_id);
+        if let Some(callable) = callable {
+            mc.frame_scripts
+                .push(Avm2FrameScript { frame_id, callable });
+        }
+    }
+
+    /// Queue the AS3 script of the current frame, if it has one.
+    fn queue_frame_script(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let mc = self.0.read();
+        let receiver = mc.avm2_object;
+        let current_frame = mc.current_frame;
+        let callable = mc
+            .frame_scripts
+            .iter()
+            .find(|script| script.frame_id == current_frame)
+            .map(|script| script.callable);
+        drop(mc);
+
+        if let Some(callable) = callable {
+            context.action_queue.queue_actions(
+                self.into(),
+                ActionType::CallAvm2 {
+                    callable,
+                    receiver,
+                    args: Vec::new(),
+                },
+                false,
+            );
+        }
+    }
+
     #[allow(dead_code)]
     pub fn playing(self) -> bool {
         self.0.read().playing()
     }
 
+    /// Whether this clip is returned as `_root` by code running inside it,
+    /// instead of the root of the level. Set by the `_lockroot` property.
+    pub fn lock_root(self) -> bool {
+        self.0.read().flags.contains(MovieClipFlags::LockRoot)
+    }
+
+    pub fn set_lock_root(self, gc_context: MutationContext<'gc, '_>, value: bool) {
+        let mut mc = self.0.write(gc_context);
+        if value {
+            mc.flags.insert(MovieClipFlags::LockRoot);
+        } else {
+            mc.flags.remove(MovieClipFlags::LockRoot);
+        }
+    }
+
     pub fn next_frame(self, avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
         if self.current_frame() < self.total_frames() {
             self.goto_frame(avm, context, self.current_frame() + 1, true);
@@ -455,23 +733,266 @@ impl<'gc> MovieClip<'gc> {
         self.0.read().static_data.total_frames
     }
 
-    pub fn frames_loaded(self) -> FrameNumber {
-        // TODO(Herschel): root needs to progressively stream in frames.
-        self.0.read().static_data.total_frames
+    /// Returns the stream sound currently playing on this clip's timeline,
+    /// along with the frame that the stream started on.
+    pub fn audio_stream(self) -> Option<(AudioStreamHandle, FrameNumber)> {
+        self.0.read().audio_stream
+    }
+
+    /// Attaches a dynamic audio source to this clip, replacing any previously attached
+    /// source. The clip's sound transform is applied to the source as it plays.
+    /// Passing `None` detaches the current source.
+    pub fn attach_audio(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        source: Option<AudioSource>,
+    ) {
+        self.0
+            .write(context.gc_context)
+            .stop_attached_audio(context);
+        if let Some(source) = source {
+            match context.audio.start_source(source) {
+                Ok(instance) => {
+                    context
+                        .audio_manager
+                        .add_instance(context.audio, instance, Some(self.into()));
+                    self.0.write(context.gc_context).attached_audio = Some(instance);
+                }
+                Err(e) => log::warn!("Unable to attach audio source: {}", e),
+            }
+        }
+    }
+
+    /// The volume and panning applied to sounds owned by this clip and its children.
+    pub fn sound_transform(self) -> SoundTransform {
+        self.0.read().sound_transform
+    }
+
+    pub fn set_sound_transform(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        sound_transform: SoundTransform,
+    ) {
+        self.0.write(context.gc_context).sound_transform = sound_transform;
+        context.audio_manager.update_sound_transforms(context.audio);
+    }
+
+    /// Whether this clip has any button event handlers, making it act as a button.
+    pub fn is_button_mode(
+        self,
+        avm: &mut Avm1<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> bool {
+        if self.0.read().has_button_clip_event {
+            return true;
+        }
+
+        let mut activation = Activation::from_nothing(
+            avm,
+            ActivationIdentifier::root("[Mouse Pick]"),
+            context.swf.version(),
+            avm.global_object_cell(),
+            context.gc_context,
+            *context.levels.get(&0).unwrap(),
+        );
+        let object = self.object().coerce_to_object(&mut activation, context);
+        ClipEvent::BUTTON_EVENT_METHODS
+            .iter()
+            .any(|handler| object.has_property(&mut activation, context, handler))
+    }
+
+    /// The 9-slice scaling grid of this clip, if any.
+    pub fn scaling_grid(self) -> Option<BoundingBox> {
+        self.0.read().scaling_grid.clone()
+    }
+
+    pub fn set_scaling_grid(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        scaling_grid: Option<BoundingBox>,
+    ) {
+        let mut mc = self.0.write(gc_context);
+        mc.scaling_grid = scaling_grid;
+        mc.base.invalidate_content();
+    }
+
+    /// The 9-slice grid to render this clip's children with, or `None` if they can be
+    /// rendered normally.
+    fn scale9_grid(&self) -> Option<Scale9Grid> {
+        let grid = self.0.read().scaling_grid.clone()?;
+        let matrix = self.matrix();
+        let scale_x = f64::from(matrix.a).hypot(f64::from(matrix.b));
+        let scale_y = f64::from(matrix.c).hypot(f64::from(matrix.d));
+        let bounds = self.bounds();
+        if !grid.valid
+            || !bounds.valid
+            || ((scale_x - 1.0).abs() < std::f64::EPSILON
+                && (scale_y - 1.0).abs() < std::f64::EPSILON)
+        {
+            return None;
+        }
+        Some(Scale9Grid {
+            grid,
+            bounds,
+            scale_x,
+            scale_y,
+        })
+    }
+
+    /// The number of frames that have been preloaded and can be played.
+    pub fn frames_loaded(self) -> FrameNumber {
+        self.0.read().frames_loaded()
+    }
+
+    /// Returns whether every frame of this clip has been preloaded.
+    pub fn is_fully_loaded(self) -> bool {
+        self.0.read().preload_progress.complete
+    }
+
+    /// The number of bytes of this clip's movie that have been loaded.
+    ///
+    /// For the root clip of a movie, this grows as its frames are downloaded
+    /// and preloaded.
+    pub fn bytes_loaded(self) -> usize {
+        let mc = self.0.read();
+        let total = mc.bytes_total();
+        if mc.preload_progress.complete {
+            total
+        } else {
+            // The tag stream of the root clip comes after the SWF header.
+            let header_len = if mc.static_data.id == 0 {
+                mc.movie().header_len()
+            } else {
+                0
+            };
+            (header_len + mc.preload_progress.next_tag_pos as usize).min(total)
+        }
+    }
+
+    /// The total number of bytes of this clip's movie.
+    pub fn bytes_total(self) -> usize {
+        self.0.read().bytes_total()
+    }
+
+    pub fn set_avm1_constructor(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        prototype: Option<Object<'gc>>,
+    ) {
+        self.0.write(gc_context).avm1_constructor = prototype;
+    }
+
+    pub fn frame_label_to_number(self, frame_label: &str) -> Option<FrameNumber> {
+        // Frame labels are case insensitive.
+        let label = frame_label.to_ascii_lowercase();
+        self.0.read().static_data.frame_labels.get(&label).copied()
+    }
+
+    /// Returns the first frame of the scene with the given name.
+    pub fn scene_label_to_number(self, scene_label: &str) -> Option<FrameNumber> {
+        // Scene labels are case insensitive.
+        self.0
+            .read()
+            .static_data
+            .scenes
+            .iter()
+            .find(|scene| scene.name.eq_ignore_ascii_case(scene_label))
+            .map(|scene| scene.start)
+    }
+
+    /// The display object that this clip was last dragged over.
+    pub fn drop_target(self) -> Option<DisplayObject<'gc>> {
+        self.0.read().drop_target
+    }
+
+    pub fn set_drop_target(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        drop_target: Option<DisplayObject<'gc>>,
+    ) {
+        self.0.write(gc_context).drop_target = drop_target;
+    }
+
+    /// Finds the innermost movie clip with content under the given point, skipping `ignore`
+    /// and its children. This is the drop target of `ignore` when it is dragged to `point`.
+    pub fn find_drop_target(
+        self,
+        point: (Twips, Twips),
+        ignore: DisplayObject<'gc>,
+    ) -> Option<DisplayObject<'gc>> {
+        if DisplayObject::ptr_eq(self.into(), ignore) || !self.visible() {
+            return None;
+        }
+
+        // Children are checked from top to bottom.
+        let children: SmallVec<[_; 16]> = self.0.read().children.values().rev().copied().collect();
+        for child in children {
+            if DisplayObject::ptr_eq(child, ignore) || !child.visible() {
+                continue;
+            }
+            if let Some(clip) = child.as_movie_clip() {
+                if let Some(target) = clip.find_drop_target(point, ignore) {
+                    return Some(target);
+                }
+            } else if child.world_bounds().contains(point) {
+                return Some(self.into());
+            }
+        }
+
+        let drawing_bounds = self
+            .0
+            .read()
+            .drawing
+            .self_bounds()
+            .transform(&self.local_to_global_matrix());
+        if drawing_bounds.contains(point) {
+            Some(self.into())
+        } else {
+            None
+        }
+    }
+
+    /// Finds the topmost visible text field under the given point, in this clip or its children.
+    pub fn find_text_field(self, point: (Twips, Twips)) -> Option<EditText<'gc>> {
+        if !self.visible() {
+            return None;
+        }
+
+        // Children are checked from top to bottom.
+        let children: SmallVec<[_; 16]> = self.0.read().children.values().rev().copied().collect();
+        for child in children {
+            if !child.visible() {
+                continue;
+            }
+            if let Some(clip) = child.as_movie_clip() {
+                if let Some(text_field) = clip.find_text_field(point) {
+                    return Some(text_field);
+                }
+            } else if let Some(text_field) = child.as_edit_text() {
+                if child.world_bounds().contains(point) {
+                    return Some(text_field);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the child of this movie clip at the given depth, if any.
+    pub fn child_by_depth(self, depth: Depth) -> Option<DisplayObject<'gc>> {
+        self.0.read().children.get(&depth).copied()
     }
 
-    pub fn set_avm1_constructor(
-        self,
-        gc_context: MutationContext<'gc, '_>,
-        prototype: Option<Object<'gc>>,
-    ) {
-        self.0.write(gc_context).avm1_constructor = prototype;
+    /// Returns the children of this movie clip in depth order.
+    pub fn children_by_depth(self) -> Vec<DisplayObject<'gc>> {
+        self.0.read().children.values().copied().collect()
     }
 
-    pub fn frame_label_to_number(self, frame_label: &str) -> Option<FrameNumber> {
-        // Frame labels are case insensitive.
-        let label = frame_label.to_ascii_lowercase();
-        self.0.read().static_data.frame_labels.get(&label).copied()
+    /// Returns the static text children of this movie clip, in depth order.
+    pub fn static_texts(self) -> Vec<Text<'gc>> {
+        self.children_by_depth()
+            .into_iter()
+            .filter_map(|child| child.as_text())
+            .collect()
     }
 
     /// Returns the highest depth in use by this movie clip, or `None` if there are no children.
@@ -510,6 +1031,7 @@ impl<'gc> MovieClip<'gc> {
         child.set_parent(context.gc_context, Some((*self).into()));
         child.set_place_frame(context.gc_context, 0);
         child.set_depth(context.gc_context, depth);
+        child.set_placed_by_script(context.gc_context, true);
     }
 
     /// Remove a child from this clip.
@@ -596,11 +1118,13 @@ impl<'gc> MovieClip<'gc> {
     ) {
         let mut mc = self.0.write(context.gc_context);
         mc.drawing.set_fill_style(style);
+        mc.base.invalidate_content();
     }
 
     pub fn clear(self, context: &mut UpdateContext<'_, 'gc, '_>) {
         let mut mc = self.0.write(context.gc_context);
         mc.drawing.clear();
+        mc.base.invalidate_content();
     }
 
     pub fn set_line_style(
@@ -610,11 +1134,31 @@ impl<'gc> MovieClip<'gc> {
     ) {
         let mut mc = self.0.write(context.gc_context);
         mc.drawing.set_line_style(style);
+        mc.base.invalidate_content();
+    }
+
+    pub fn set_line_fill_style(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        style: Option<FillStyle>,
+    ) {
+        let mut mc = self.0.write(context.gc_context);
+        mc.drawing.set_line_fill_style(style);
+        mc.base.invalidate_content();
+    }
+
+    /// Replaces the drawing API content of this clip with a copy of another clip's.
+    pub fn copy_drawing(self, gc_context: MutationContext<'gc, '_>, from: MovieClip<'gc>) {
+        let drawing = from.0.read().drawing.clone();
+        let mut mc = self.0.write(gc_context);
+        mc.drawing = drawing;
+        mc.base.invalidate_content();
     }
 
     pub fn draw_command(self, context: &mut UpdateContext<'_, 'gc, '_>, command: DrawCommand) {
         let mut mc = self.0.write(context.gc_context);
         mc.drawing.draw_command(command);
+        mc.base.invalidate_content();
     }
 
     pub fn run_clip_event(
@@ -636,6 +1180,10 @@ impl<'gc> MovieClip<'gc> {
     ) {
         // Advance frame number.
         if self.current_frame() < self.total_frames() {
+            if self.current_frame() >= self.frames_loaded() {
+                // Wait for the next frame to be preloaded.
+                return;
+            }
             self.0.write(context.gc_context).current_frame += 1;
         } else if self.total_frames() > 1 {
             // Looping acts exactly like a gotoAndPlay(1).
@@ -656,6 +1204,7 @@ impl<'gc> MovieClip<'gc> {
         drop(mc);
 
         let version = reader.version();
+        let avm_type = data.movie.avm_type();
         use swf::TagCode;
         let tag_callback = |reader: &mut SwfStream<&[u8]>, tag_code, tag_len| {
             let data = *reader.get_inner().get_ref();
@@ -663,7 +1212,9 @@ impl<'gc> MovieClip<'gc> {
             let tag_slice = &data[tag_pos..tag_pos + tag_len];
             let reader = &mut SwfStream::new(std::io::Cursor::new(tag_slice), version);
             match tag_code {
-                TagCode::DoAction => self.do_action(self_display_object, context, reader, tag_len),
+                TagCode::DoAction if avm_type == AvmType::Avm1 => {
+                    self.do_action(self_display_object, context, reader, tag_len)
+                }
                 TagCode::PlaceObject if run_display_actions => {
                     self.place_object(self_display_object, avm, context, reader, tag_len, 1)
                 }
@@ -694,6 +1245,7 @@ impl<'gc> MovieClip<'gc> {
         let _ = tag_utils::decode_tags(&mut reader, tag_callback, TagCode::ShowFrame);
 
         self.0.write(context.gc_context).tag_stream_pos = reader.get_ref().position();
+        self.queue_frame_script(context);
 
         // If we are playing a streaming sound, there should(?) be a `SoundStreamBlock` on each frame.
         if !has_stream_block {
@@ -791,7 +1343,7 @@ impl<'gc> MovieClip<'gc> {
                 .children
                 .iter()
                 .filter_map(|(depth, clip)| {
-                    if clip.place_frame() > frame {
+                    if clip.place_frame() > frame && !clip.placed_by_script() {
                         Some((*depth, *clip))
                     } else {
                         None
@@ -817,11 +1369,10 @@ impl<'gc> MovieClip<'gc> {
 
         let len = mc.tag_stream_len() as u64;
         // Sanity; let's make sure we don't seek way too far.
-        // TODO: This should be self.frames_loaded() when we implement that.
-        let clamped_frame = if frame <= mc.total_frames() {
+        let clamped_frame = if frame <= mc.frames_loaded() {
             frame
         } else {
-            mc.total_frames()
+            mc.frames_loaded()
         };
         drop(mc);
 
@@ -974,20 +1525,39 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         Some(self.0.read().movie())
     }
 
+    fn enter_frame(&mut self, avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        // Children must run first.
+        for mut child in self.children() {
+            child.enter_frame(avm, context);
+        }
+
+        // Clips that haven't loaded yet receive their load event in `run_frame` instead.
+        let mc = self.0.read();
+        if mc.initialized() {
+            mc.run_clip_event((*self).into(), context, ClipEvent::EnterFrame);
+        }
+
+        let avm2_object = mc.avm2_object;
+        drop(mc);
+        if let Some(avm2_object) = avm2_object {
+            if let Err(e) = Avm2::dispatch_event(context, avm2_object, "enterFrame") {
+                log::error!("Unhandled AVM2 exception in enterFrame: {}", e);
+            }
+        }
+    }
+
     fn run_frame(&mut self, avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
         // Children must run first.
         for mut child in self.children() {
             child.run_frame(avm, context);
         }
 
-        // Run my load/enterFrame clip event.
+        // Run my `onClipEvent(load)` handlers.
         let mut mc = self.0.write(context.gc_context);
         let is_load_frame = !mc.initialized();
         if is_load_frame {
-            mc.run_clip_event((*self).into(), context, ClipEvent::Load);
+            mc.run_clip_actions((*self).into(), context, ClipEvent::Load);
             mc.set_initialized(true);
-        } else {
-            mc.run_clip_event((*self).into(), context, ClipEvent::EnterFrame);
         }
         drop(mc);
 
@@ -997,17 +1567,20 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         }
 
         if is_load_frame {
-            self.0.write(context.gc_context).run_clip_postevent(
-                (*self).into(),
-                context,
-                ClipEvent::Load,
-            );
+            // Children placed by this frame have queued their `onLoad` by now,
+            // so they run before their parent's.
+            let mc = self.0.read();
+            mc.run_clip_method((*self).into(), context, ClipEvent::Load);
+            mc.run_clip_postevent((*self).into(), context, ClipEvent::Load);
         }
     }
 
     fn render(&self, context: &mut RenderContext<'_, 'gc>) {
         context.transform_stack.push(&*self.transform());
+        // The scaling grid only applies to the shapes directly inside this clip.
+        let parent_grid = std::mem::replace(&mut context.scale9_grid, self.scale9_grid());
         crate::display_object::render_children(context, &self.0.read().children);
+        context.scale9_grid = parent_grid;
         self.0.read().drawing.render(context);
         context.transform_stack.pop();
     }
@@ -1017,7 +1590,16 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
     }
 
     fn hit_test(&self, point: (Twips, Twips)) -> bool {
-        self.world_bounds().contains(point)
+        // Test against each visible child rather than the bounds of the whole clip.
+        // Masks are hit tested even when invisible, since visibility doesn't affect masking.
+        let mc = self.0.read();
+        mc.children.values().any(|child| {
+            (child.visible() || child.maskee().is_some()) && child.world_bounds().contains(point)
+        }) || mc
+            .drawing
+            .self_bounds()
+            .transform(&self.local_to_global_matrix())
+            .contains(point)
     }
 
     fn mouse_pick(
@@ -1027,26 +1609,32 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         self_node: DisplayObject<'gc>,
         point: (Twips, Twips),
     ) -> Option<DisplayObject<'gc>> {
-        if self.visible() {
-            if self.world_bounds().contains(point) {
-                if self.0.read().has_button_clip_event {
-                    return Some(self_node);
-                }
-
-                let mut activation = Activation::from_nothing(
-                    avm,
-                    ActivationIdentifier::root("[Mouse Pick]"),
-                    context.swf.version(),
-                    avm.global_object_cell(),
-                    context.gc_context,
-                    *context.levels.get(&0).unwrap(),
-                );
-                let object = self.object().coerce_to_object(&mut activation, context);
+        // Script masks aren't drawn, and masked clips can only be clicked inside their mask.
+        if self.maskee().is_some() {
+            return None;
+        }
+        if let Some(masker) = self.masker() {
+            if !masker.hit_test(point) {
+                return None;
+            }
+        }
 
-                if ClipEvent::BUTTON_EVENT_METHODS
-                    .iter()
-                    .any(|handler| object.has_property(&mut activation, context, handler))
-                {
+        if self.visible() {
+            // Clips with button event handlers act as buttons, using their children or their
+            // `hitArea` clip as the hit area.
+            if self.is_button_mode(avm, context) && is_enabled(avm, context, self_node) {
+                let hit_area = get_avm1_property(avm, context, self_node, "hitArea")
+                    .and_then(|value| match value {
+                        Value::Object(object) => object.as_display_object(),
+                        _ => None,
+                    })
+                    .filter(|hit_area| !DisplayObject::ptr_eq(*hit_area, self_node));
+                let is_hit = if let Some(hit_area) = hit_area {
+                    hit_area.hit_test(point)
+                } else {
+                    self.hit_test(point)
+                };
+                if is_hit {
                     return Some(self_node);
                 }
             }
@@ -1095,6 +1683,20 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
     ) {
         self.set_default_instance_name(context);
 
+        // Clips in AS3 movies get an AVM2 object instead. It is constructed
+        // once the classes of the movie have been loaded.
+        if self.0.read().movie().avm_type() == AvmType::Avm2 {
+            if self.0.read().avm2_object.is_none() {
+                context.action_queue.queue_actions(
+                    display_object,
+                    ActionType::ConstructAvm2,
+                    false,
+                );
+            }
+
+            return;
+        }
+
         if self.0.read().object.is_none() {
             // If we are running within the AVM, this must be an immediate action.
             // If we are not, then this must be queued to be ran first-thing
@@ -1175,6 +1777,22 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
                 },
                 false,
             );
+
+            // `initialize` handlers run after the constructor, but before `load` and the
+            // clip's first frame.
+            for clip_action in mc
+                .clip_actions()
+                .iter()
+                .filter(|action| action.event == ClipEvent::Initialize)
+            {
+                context.action_queue.queue_actions(
+                    display_object,
+                    ActionType::Normal {
+                        bytecode: clip_action.action_data.clone(),
+                    },
+                    false,
+                );
+            }
         }
 
         // If this text field has a variable set, initialize text field binding.
@@ -1196,6 +1814,18 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
             .unwrap_or(Value::Undefined)
     }
 
+    fn object2(&self) -> Avm2Value<'gc> {
+        self.0
+            .read()
+            .avm2_object
+            .map(Avm2Value::from)
+            .unwrap_or(Avm2Value::Undefined)
+    }
+
+    fn set_object2(&mut self, mc: MutationContext<'gc, '_>, to: Avm2Object<'gc>) {
+        self.0.write(mc).avm2_object = Some(to);
+    }
+
     fn unload(&mut self, context: &mut UpdateContext<'_, 'gc, '_>) {
         for mut child in self.children() {
             child.unload(context);
@@ -1211,9 +1841,15 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         {
             let mut mc = self.0.write(context.gc_context);
             mc.stop_audio_stream(context);
+            mc.stop_attached_audio(context);
             mc.run_clip_event((*self).into(), context, ClipEvent::Unload);
         }
-        self.set_removed(context.gc_context, true);
+        self.clear_masks(context.gc_context);
+
+        // Actions queued before the clip was removed don't run, but scripts run on
+        // the clip afterward still do until the next frame; see `Player::run_frame`.
+        context.action_queue.cancel_actions((*self).into());
+        context.unloaded_clips.push((*self).into());
     }
 
     fn allow_as_mask(&self) -> bool {
@@ -1231,6 +1867,12 @@ unsafe impl<'gc> Collect for MovieClipData<'gc> {
         self.static_data.trace(cc);
         self.object.trace(cc);
         self.avm1_constructor.trace(cc);
+        self.drop_target.trace(cc);
+        self.avm2_object.trace(cc);
+        self.frame_scripts.trace(cc);
+        for morph_shape in self.preload_progress.morph_shapes.values() {
+            morph_shape.trace(cc);
+        }
     }
 }
 
@@ -1260,13 +1902,17 @@ impl<'gc> MovieClipData<'gc> {
                 total_frames,
                 audio_stream_info: None,
                 frame_labels: HashMap::new(),
+                scenes: Vec::new(),
             },
         );
         self.tag_stream_pos = 0;
+        self.preload_progress = Default::default();
         self.flags = MovieClipFlags::Playing.into();
         self.current_frame = 0;
         self.audio_stream = None;
         self.children = BTreeMap::new();
+        self.avm2_object = None;
+        self.frame_scripts = Vec::new();
     }
 
     fn id(&self) -> CharacterId {
@@ -1320,6 +1966,24 @@ impl<'gc> MovieClipData<'gc> {
         self.static_data.swf.end - self.static_data.swf.start
     }
 
+    fn frames_loaded(&self) -> FrameNumber {
+        if self.preload_progress.complete {
+            self.total_frames()
+        } else {
+            self.preload_progress.frames_loaded.min(self.total_frames())
+        }
+    }
+
+    /// The size of the SWF file for the root clip of a movie, or the size of the
+    /// `DefineSprite` tag data for other clips.
+    fn bytes_total(&self) -> usize {
+        if self.static_data.id == 0 && self.tag_stream_len() > 0 {
+            self.movie().uncompressed_len()
+        } else {
+            self.tag_stream_len()
+        }
+    }
+
     /// Adds a child to the front of the execution list.
     /// This does not affect the render list.
     fn add_child_to_exec_list(
@@ -1413,9 +2077,11 @@ impl<'gc> MovieClipData<'gc> {
             // Don't do this for rewinds, because they conceptually
             // start from an empty display list, and we also want to examine
             // the old children to decide if they persist (place_frame <= goto_frame).
-            let child = self.children.remove(&depth);
-            if let Some(child) = child {
-                self.remove_child_from_exec_list(context, child);
+            if let Some(child) = self.children.get(&depth).copied() {
+                if !child.placed_by_script() {
+                    self.children.remove(&depth);
+                    self.remove_child_from_exec_list(context, child);
+                }
             }
         }
         Ok(())
@@ -1427,6 +2093,21 @@ impl<'gc> MovieClipData<'gc> {
         self_display_object: DisplayObject<'gc>,
         context: &mut UpdateContext<'_, 'gc, '_>,
         event: ClipEvent,
+    ) -> ClipEventResult {
+        let handled = self.run_clip_actions(self_display_object, context, event);
+
+        // Queue ActionScript-defined event handlers after the SWF defined ones.
+        self.run_clip_method(self_display_object, context, event);
+
+        handled
+    }
+
+    /// Run the `onClipEvent` handlers placed on this clip for the given clip event.
+    fn run_clip_actions(
+        &self,
+        self_display_object: DisplayObject<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        event: ClipEvent,
     ) -> ClipEventResult {
         let mut handled = ClipEventResult::NotHandled;
 
@@ -1449,27 +2130,35 @@ impl<'gc> MovieClipData<'gc> {
                     event == ClipEvent::Unload,
                 );
             }
-
-            // Queue ActionScript-defined event handlers after the SWF defined ones.
-            // (e.g., clip.onEnterFrame = foo).
-            if context.swf.version() >= 6 {
-                if let Some(name) = event.method_name() {
-                    context.action_queue.queue_actions(
-                        self_display_object,
-                        ActionType::Method {
-                            object: self.object.unwrap(),
-                            name,
-                            args: vec![],
-                        },
-                        event == ClipEvent::Unload,
-                    );
-                }
-            }
         }
 
         handled
     }
 
+    /// Run the ActionScript-defined handler for the given clip event
+    /// (e.g., clip.onEnterFrame = foo).
+    fn run_clip_method(
+        &self,
+        self_display_object: DisplayObject<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        event: ClipEvent,
+    ) {
+        // Clips in AS3 movies have no AVM1 object to call them on.
+        if context.swf.version() >= 6 {
+            if let (Some(name), Some(object)) = (event.method_name(), self.object) {
+                context.action_queue.queue_actions(
+                    self_display_object,
+                    ActionType::Method {
+                        object,
+                        name,
+                        args: vec![],
+                    },
+                    event == ClipEvent::Unload,
+                );
+            }
+        }
+    }
+
     /// Run clip actions that trigger after the clip's own actions.
     ///
     /// Currently, this is purely limited to `MovieClipLoader`'s `onLoadInit`
@@ -1517,8 +2206,16 @@ impl<'gc> MovieClipData<'gc> {
 
     /// Stops the audio stream if one is playing.
     fn stop_audio_stream(&mut self, context: &mut UpdateContext<'_, 'gc, '_>) {
-        if let Some(audio_stream) = self.audio_stream.take() {
+        if let Some((audio_stream, _)) = self.audio_stream.take() {
             context.audio.stop_stream(audio_stream);
+            context.audio_manager.remove_instance(audio_stream);
+        }
+    }
+
+    /// Detaches the dynamic audio source if one is attached.
+    fn stop_attached_audio(&mut self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        if let Some(instance) = self.attached_audio.take() {
+            context.audio_manager.stop_sound(context.audio, instance);
         }
     }
 
@@ -1559,12 +2256,16 @@ impl<'gc, 'a> MovieClipData<'gc> {
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<&'a [u8]>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
         version: u8,
     ) -> DecodeResult {
-        // Certain backends may have to preload morph shape frames, so defer registering until the end.
+        // Certain backends may have to preload morph shape frames, so defer registering until
+        // the ratios it's placed at have been preloaded.
         let swf_shape = reader.read_define_morph_shape(version)?;
-        let morph_shape = MorphShapeStatic::from_swf_tag(context.renderer, &swf_shape);
+        let span = context.profiler.start();
+        let static_data = MorphShapeStatic::from_swf_tag(context.renderer, &swf_shape);
+        context.profiler.record(Subsystem::Tessellation, span);
+        let morph_shape = MorphShape::new(context.gc_context, static_data);
         morph_shapes.insert(swf_shape.id, morph_shape);
         Ok(())
     }
@@ -1585,6 +2286,27 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    /// Finds a morph shape defined by this movie, whether its frame is still being
+    /// preloaded or it has already been registered.
+    fn preloaded_morph_shape(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        morph_shapes: &fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
+        id: CharacterId,
+    ) -> Option<MorphShape<'gc>> {
+        if let Some(morph_shape) = morph_shapes.get(&id) {
+            return Some(*morph_shape);
+        }
+        match context
+            .library
+            .library_for_movie(self.movie())?
+            .get_character_by_id(id)
+        {
+            Some(Character::MorphShape(morph_shape)) => Some(*morph_shape),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn preload_place_object(
         &mut self,
@@ -1592,7 +2314,7 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<&'a [u8]>,
         tag_len: usize,
         ids: &mut fnv::FnvHashMap<Depth, CharacterId>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
         version: u8,
     ) -> DecodeResult {
         use swf::PlaceObjectAction;
@@ -1603,35 +2325,41 @@ impl<'gc, 'a> MovieClipData<'gc> {
         }?;
         match place_object.action {
             PlaceObjectAction::Place(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+                if let Some(morph_shape) = self.preloaded_morph_shape(context, morph_shapes, id) {
                     ids.insert(place_object.depth.into(), id);
                     if let Some(ratio) = place_object.ratio {
+                        let span = context.profiler.start();
                         morph_shape.register_ratio(context.renderer, ratio);
+                        context.profiler.record(Subsystem::Tessellation, span);
                     }
                 }
             }
             PlaceObjectAction::Modify => {
                 if let Some(&id) = ids.get(&place_object.depth.into()) {
-                    if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+                    if let Some(morph_shape) = self.preloaded_morph_shape(context, morph_shapes, id)
+                    {
                         ids.insert(place_object.depth.into(), id);
                         if let Some(ratio) = place_object.ratio {
+                            let span = context.profiler.start();
                             morph_shape.register_ratio(context.renderer, ratio);
+                            context.profiler.record(Subsystem::Tessellation, span);
                         }
                     }
                 }
             }
             PlaceObjectAction::Replace(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+                if let Some(morph_shape) = self.preloaded_morph_shape(context, morph_shapes, id) {
                     ids.insert(place_object.depth.into(), id);
                     if let Some(ratio) = place_object.ratio {
+                        let span = context.profiler.start();
                         morph_shape.register_ratio(context.renderer, ratio);
+                        context.profiler.record(Subsystem::Tessellation, span);
                     }
                 } else {
                     ids.remove(&place_object.depth.into());
                 }
             }
-        };
-
+        }
         Ok(())
     }
 
@@ -1744,7 +2472,10 @@ impl<'gc, 'a> MovieClipData<'gc> {
         use std::io::Read;
         let id = reader.read_u16()?;
         let jpeg_len = reader.read_u32()? as usize;
-        let alpha_len = tag_len - 6 - jpeg_len;
+        let alpha_len = tag_len
+            .checked_sub(6)
+            .and_then(|len| len.checked_sub(jpeg_len))
+            .ok_or("DefineBitsJPEG3 tag is shorter than its JPEG data")?;
         let mut jpeg_data = Vec::with_capacity(jpeg_len);
         let mut alpha_data = Vec::with_capacity(alpha_len);
         reader
@@ -1782,8 +2513,12 @@ impl<'gc, 'a> MovieClipData<'gc> {
         use std::io::Read;
         let id = reader.read_u16()?;
         let jpeg_len = reader.read_u32()? as usize;
+        // Flash applies a deblocking filter to the JPEG, which we don't emulate.
         let _deblocking = reader.read_u16()?;
-        let alpha_len = tag_len - 6 - jpeg_len;
+        let alpha_len = tag_len
+            .checked_sub(8)
+            .and_then(|len| len.checked_sub(jpeg_len))
+            .ok_or("DefineBitsJPEG4 tag is shorter than its JPEG data")?;
         let mut jpeg_data = Vec::with_capacity(jpeg_len);
         let mut alpha_data = Vec::with_capacity(alpha_len);
         reader
@@ -1957,7 +2692,9 @@ impl<'gc, 'a> MovieClipData<'gc> {
             is_bold: false,
             is_italic: false,
         };
+        let span = context.profiler.start();
         let font_object = Font::from_swf_tag(context.gc_context, context.renderer, &font).unwrap();
+        context.profiler.record(Subsystem::Tessellation, span);
         context
             .library
             .library_for_movie_mut(self.movie())
@@ -1972,7 +2709,9 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<&'a [u8]>,
     ) -> DecodeResult {
         let font = reader.read_define_font_2(2)?;
+        let span = context.profiler.start();
         let font_object = Font::from_swf_tag(context.gc_context, context.renderer, &font).unwrap();
+        context.profiler.record(Subsystem::Tessellation, span);
         context
             .library
             .library_for_movie_mut(self.movie())
@@ -1987,7 +2726,9 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<&'a [u8]>,
     ) -> DecodeResult {
         let font = reader.read_define_font_2(3)?;
+        let span = context.profiler.start();
         let font_object = Font::from_swf_tag(context.gc_context, context.renderer, &font).unwrap();
+        context.profiler.record(Subsystem::Tessellation, span);
         context
             .library
             .library_for_movie_mut(self.movie())
@@ -1996,6 +2737,35 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn define_font_4(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+    ) -> DecodeResult {
+        // DefineFont4 embeds an OpenType/CFF font, which the font backend converts to glyphs.
+        let font = reader.read_define_font_4()?;
+        if let Some(mut swf_font) = context.font.load_font_data(&font) {
+            swf_font.id = font.id;
+            let span = context.profiler.start();
+            let font_object =
+                Font::from_swf_tag(context.gc_context, context.renderer, &swf_font).unwrap();
+            context.profiler.record(Subsystem::Tessellation, span);
+            context
+                .library
+                .library_for_movie_mut(self.movie())
+                .register_character(font.id, Character::Font(font_object));
+        } else {
+            log::warn!(
+                "DefineFont4: Unable to load font data for font ID {} ({})",
+                font.id,
+                font.name
+            );
+        }
+
+        Ok(())
+    }
+
     #[inline]
     fn define_sound(
         &mut self,
@@ -2017,13 +2787,34 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn define_scaling_grid(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+    ) -> DecodeResult {
+        let id = reader.read_u16()?;
+        let splitter_rect = reader.read_rectangle()?;
+        // Instances are cloned from the library clip, so they all share its grid.
+        let library = context.library.library_for_movie_mut(self.movie());
+        if let Some(Character::MovieClip(movie_clip)) = library.get_character_by_id(id) {
+            movie_clip.set_scaling_grid(context.gc_context, Some(splitter_rect.into()));
+        } else {
+            log::warn!(
+                "DefineScalingGrid: Character {} is not a movie clip; ignoring",
+                id
+            );
+        }
+        Ok(())
+    }
+
     fn define_sprite(
         &mut self,
         avm: &mut Avm1<'gc>,
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<&'a [u8]>,
         tag_len: usize,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
     ) -> DecodeResult {
         let id = reader.read_character_id()?;
         let num_frames = reader.read_u16()?;
@@ -2068,6 +2859,44 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn define_video_stream(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+    ) -> DecodeResult {
+        let video_stream = reader.read_define_video_stream()?;
+        let video = Video::from_swf_tag(context, &video_stream);
+        context
+            .library
+            .library_for_movie_mut(self.movie())
+            .register_character(video_stream.id, Character::Video(video));
+        Ok(())
+    }
+
+    #[inline]
+    fn preload_video_frame(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+    ) -> DecodeResult {
+        let video_frame = reader.read_video_frame()?;
+        match context
+            .library
+            .library_for_movie_mut(self.movie())
+            .get_character_by_id(video_frame.stream_id)
+        {
+            Some(Character::Video(video)) => {
+                video.preload_frame(video_frame.frame_num, video_frame.data)
+            }
+            _ => log::warn!(
+                "VideoFrame: Video stream {} doesn't exist",
+                video_frame.stream_id
+            ),
+        }
+        Ok(())
+    }
+
     #[inline]
     fn script_limits(
         &mut self,
@@ -2098,6 +2927,57 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn symbol_class(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+    ) -> DecodeResult {
+        let num_symbols = reader.read_u16()?;
+        for _ in 0..num_symbols {
+            let id = reader.read_u16()?;
+            let class_name = reader.read_c_string()?;
+            context
+                .library
+                .library_for_movie_mut(self.movie())
+                .register_avm2_class_name(id, &class_name);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn import_assets(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<&'a [u8]>,
+        version: u8,
+    ) -> DecodeResult {
+        let (url, imports) = if version == 1 {
+            reader.read_import_assets()?
+        } else {
+            reader.read_import_assets_2()?
+        };
+        let player = if let Some(player) = context.player.clone() {
+            player
+        } else {
+            log::warn!("Can't import assets from {} without a player", url);
+            return Ok(());
+        };
+
+        // The importing movie won't play until the imported movie has loaded.
+        let movie = self.movie();
+        context
+            .library
+            .library_for_movie_mut(movie.clone())
+            .begin_import();
+        let fetch = context.navigator.fetch(&url, RequestOptions::get());
+        let process = context
+            .load_manager
+            .load_imported_assets(player, movie, imports, fetch);
+        context.navigator.spawn_future(process);
+        Ok(())
+    }
+
     #[inline]
     fn frame_label(
         &mut self,
@@ -2120,6 +3000,38 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn scene_and_frame_labels(
+        &mut self,
+        reader: &mut SwfStream<&'a [u8]>,
+        static_data: &mut MovieClipStatic,
+    ) -> DecodeResult {
+        let data = reader.read_define_scene_and_frame_label_data()?;
+
+        // Frame numbers in this tag are 0-based.
+        static_data.scenes = data
+            .scenes
+            .into_iter()
+            .map(|scene| Scene {
+                name: scene.label,
+                start: FrameNumber::try_from(scene.frame_num.saturating_add(1))
+                    .unwrap_or(FrameNumber::MAX),
+            })
+            .collect();
+
+        for frame_label in data.frame_labels {
+            let frame = FrameNumber::try_from(frame_label.frame_num.saturating_add(1))
+                .unwrap_or(FrameNumber::MAX);
+            // Frame labels are case insensitive (ASCII).
+            // `FrameLabel` tags take priority over duplicates in this tag.
+            static_data
+                .frame_labels
+                .entry(frame_label.label.to_ascii_lowercase())
+                .or_insert(frame);
+        }
+        Ok(())
+    }
+
     #[inline]
     fn jpeg_tables(
         &mut self,
@@ -2264,10 +3176,14 @@ impl<'gc, 'a> MovieClip<'gc> {
         } else {
             reader.read_remove_object_2()
         }?;
+        let depth = remove_object.depth.into();
         let mut mc = self.0.write(context.gc_context);
-        let child = mc.children.remove(&remove_object.depth.into());
-        if let Some(child) = child {
-            mc.remove_child_from_exec_list(context, child);
+        if let Some(child) = mc.children.get(&depth).copied() {
+            // Clips placed or moved by ActionScript are out of the timeline's control.
+            if !child.placed_by_script() {
+                mc.children.remove(&depth);
+                mc.remove_child_from_exec_list(context, child);
+            }
         }
         Ok(())
     }
@@ -2300,11 +3216,19 @@ impl<'gc, 'a> MovieClip<'gc> {
                         "Invalid slice generated when constructing sound stream block",
                     )
                 })?;
+            let start_frame = mc.current_frame();
             let audio_stream =
                 context
                     .audio
-                    .start_stream(mc.id(), mc.current_frame() + 1, slice, &stream_info);
-            mc.audio_stream = audio_stream.ok();
+                    .start_stream(mc.id(), start_frame + 1, slice, &stream_info);
+            mc.audio_stream = audio_stream.ok().map(|handle| (handle, start_frame));
+            if let Some((handle, _)) = mc.audio_stream {
+                // The stream is affected by the sound transforms of this clip.
+                drop(mc);
+                context
+                    .audio_manager
+                    .add_instance(context.audio, handle, Some(self.into()));
+            }
         }
 
         Ok(())
@@ -2327,24 +3251,58 @@ impl<'gc, 'a> MovieClip<'gc> {
             match start_sound.sound_info.event {
                 // "Event" sounds always play, independent of the timeline.
                 SoundEvent::Event => {
-                    let _ = context.audio.start_sound(handle, &start_sound.sound_info);
+                    context.audio_manager.start_sound(
+                        context.audio,
+                        handle,
+                        &start_sound.sound_info,
+                        Some(self.into()),
+                        None,
+                    );
                 }
 
                 // "Start" sounds only play if an instance of the same sound is not already playing.
                 SoundEvent::Start => {
                     if !context.audio.is_sound_playing_with_handle(handle) {
-                        let _ = context.audio.start_sound(handle, &start_sound.sound_info);
+                        context.audio_manager.start_sound(
+                            context.audio,
+                            handle,
+                            &start_sound.sound_info,
+                            Some(self.into()),
+                            None,
+                        );
                     }
                 }
 
                 // "Stop" stops any active instances of a given sound.
-                SoundEvent::Stop => context.audio.stop_sounds_with_handle(handle),
+                SoundEvent::Stop => context
+                    .audio_manager
+                    .stop_sounds_with_handle(context.audio, handle),
             }
         }
         Ok(())
     }
 }
 
+/// The progress of preloading the definition tags of a movie clip.
+#[derive(Clone, Debug, Default)]
+struct PreloadProgress<'gc> {
+    /// The position of the next tag to preload in the clip's tag stream.
+    next_tag_pos: u64,
+
+    /// The number of frames that have been preloaded.
+    frames_loaded: FrameNumber,
+
+    /// The character placed at each depth so far, used to register morph shape ratios.
+    ids: fnv::FnvHashMap<Depth, CharacterId>,
+
+    /// The morph shapes defined by a frame of a downloading movie that has only been
+    /// partly preloaded. See `MovieClip::preload_streaming_frame`.
+    morph_shapes: fnv::FnvHashMap<CharacterId, MorphShape<'gc>>,
+
+    /// Whether the whole tag stream has been preloaded.
+    complete: bool,
+}
+
 /// Static data shared between all instances of a movie clip.
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -2352,6 +3310,7 @@ struct MovieClipStatic {
     id: CharacterId,
     swf: SwfSlice,
     frame_labels: HashMap<String, FrameNumber>,
+    scenes: Vec<Scene>,
     audio_stream_info: Option<swf::SoundStreamHead>,
     total_frames: FrameNumber,
 }
@@ -2363,11 +3322,19 @@ impl MovieClipStatic {
             swf,
             total_frames: 1,
             frame_labels: HashMap::new(),
+            scenes: Vec::new(),
             audio_stream_info: None,
         }
     }
 }
 
+/// A named scene of a movie clip's timeline.
+#[derive(Clone, Debug)]
+struct Scene {
+    name: String,
+    start: FrameNumber,
+}
+
 unsafe impl<'gc> Collect for MovieClipStatic {
     #[inline]
     fn needs_trace() -> bool {
@@ -2492,6 +3459,9 @@ enum MovieClipFlags {
 
     /// Whether this `MovieClip` is playing or stopped.
     Playing,
+
+    /// Whether this `MovieClip` is the `_root` of its children (`_lockroot` property).
+    LockRoot,
 }
 
 /// Actions that are attached to a `MovieClip` event in
@@ -2555,3 +3525,14 @@ impl ClipAction {
         })
     }
 }
+
+/// An AS3 script that runs when the playhead of a clip enters a frame.
+#[derive(Clone, Debug, Collect)]
+#[collect(no_drop)]
+struct Avm2FrameScript<'gc> {
+    /// The frame the script runs on, which is 1-based.
+    frame_id: FrameNumber,
+
+    /// The function to call.
+    callable: Avm2Object<'gc>,
+}
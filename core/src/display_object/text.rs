@@ -4,7 +4,7 @@ use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
 use crate::tag_utils::SwfMovie;
 use crate::transform::Transform;
-use gc_arena::{Collect, GcCell};
+use gc_arena::{Collect, GcCell, MutationContext};
 use std::sync::Arc;
 
 #[derive(Clone, Debug, Collect, Copy)]
@@ -15,6 +15,25 @@ pub struct Text<'gc>(GcCell<'gc, TextData<'gc>>);
 pub struct TextData<'gc> {
     base: DisplayObjectBase<'gc>,
     static_data: gc_arena::Gc<'gc, TextStatic>,
+
+    /// Which glyphs have been selected by `TextSnapshot.setSelected`.
+    selected_glyphs: Vec<bool>,
+
+    /// The color used to render selected glyphs.
+    select_color: swf::Color,
+}
+
+/// A character of static text, as seen by a `TextSnapshot`.
+#[derive(Debug, Clone)]
+pub struct StaticTextChar {
+    /// The character represented by this glyph.
+    pub character: char,
+
+    /// Whether this character starts a new line of text.
+    pub new_line: bool,
+
+    /// The bounds of the glyph in the coordinate space of the text's parent.
+    pub bounds: BoundingBox,
 }
 
 impl<'gc> Text<'gc> {
@@ -37,9 +56,99 @@ impl<'gc> Text<'gc> {
                         text_blocks: tag.records.clone(),
                     },
                 ),
+                selected_glyphs: vec![],
+                select_color: swf::Color {
+                    r: 255,
+                    g: 255,
+                    b: 0,
+                    a: 255,
+                },
             },
         ))
     }
+
+    /// Returns the characters of this text, in the order that they are drawn.
+    ///
+    /// Glyphs whose font can't be found are skipped, so the index of a
+    /// character matches the index used by `is_glyph_selected`.
+    pub fn chars(self, context: &mut UpdateContext<'_, 'gc, '_>) -> Vec<StaticTextChar> {
+        let tf = self.0.read();
+        let static_data = &tf.static_data;
+        let matrix = *self.matrix() * static_data.text_transform;
+        let library = context.library.library_for_movie(static_data.swf.clone());
+
+        let mut chars = vec![];
+        let mut new_line = true;
+        let mut font_id = 0;
+        let mut height = Twips::new(0);
+        let mut x = Twips::new(0);
+        let mut y = Twips::new(0);
+        for block in &static_data.text_blocks {
+            if let Some(x_offset) = block.x_offset {
+                x = x_offset;
+            }
+            if let Some(y_offset) = block.y_offset {
+                new_line |= y_offset != y;
+                y = y_offset;
+            }
+            font_id = block.font_id.unwrap_or(font_id);
+            height = block.height.unwrap_or(height);
+            let font = match library.and_then(|library| library.get_font(font_id)) {
+                Some(font) => font,
+                None => continue,
+            };
+            let ascent = font.get_baseline_for_height(height);
+            let descent = font.get_descent_for_height(height);
+            for entry in &block.glyphs {
+                let advance = Twips::new(entry.advance);
+                if let Some(glyph) = font.get_glyph(entry.index as usize) {
+                    let bounds = BoundingBox {
+                        x_min: x,
+                        y_min: y - ascent,
+                        x_max: x + advance,
+                        y_max: y + descent,
+                        valid: true,
+                    };
+                    chars.push(StaticTextChar {
+                        character: std::char::from_u32(glyph.code.into())
+                            .unwrap_or(std::char::REPLACEMENT_CHARACTER),
+                        new_line,
+                        bounds: bounds.transform(&matrix),
+                    });
+                    new_line = false;
+                    x += advance;
+                }
+            }
+        }
+        chars
+    }
+
+    /// Returns whether the glyph at the given index is selected.
+    pub fn is_glyph_selected(self, index: usize) -> bool {
+        self.0
+            .read()
+            .selected_glyphs
+            .get(index)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn set_glyph_selected(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        index: usize,
+        selected: bool,
+    ) {
+        let mut write = self.0.write(gc_context);
+        if write.selected_glyphs.len() <= index {
+            write.selected_glyphs.resize(index + 1, false);
+        }
+        write.selected_glyphs[index] = selected;
+    }
+
+    pub fn set_select_color(self, gc_context: MutationContext<'gc, '_>, color: swf::Color) {
+        self.0.write(gc_context).select_color = color;
+    }
 }
 
 impl<'gc> TDisplayObject<'gc> for Text<'gc> {
@@ -53,6 +162,10 @@ impl<'gc> TDisplayObject<'gc> for Text<'gc> {
         Some(self.0.read().static_data.swf.clone())
     }
 
+    fn as_text(&self) -> Option<Text<'gc>> {
+        Some(*self)
+    }
+
     fn run_frame(&mut self, _avm: &mut Avm1<'gc>, _context: &mut UpdateContext) {
         // Noop
     }
@@ -74,6 +187,7 @@ impl<'gc> TDisplayObject<'gc> for Text<'gc> {
         let mut font_id = 0;
         let mut height = Twips::new(0);
         let mut transform: Transform = Default::default();
+        let mut glyph_index = 0;
         for block in &tf.static_data.text_blocks {
             if let Some(x) = block.x_offset {
                 transform.matrix.tx = x;
@@ -93,12 +207,18 @@ impl<'gc> TDisplayObject<'gc> for Text<'gc> {
                 let scale = (height.get() as f32) / font.scale();
                 transform.matrix.a = scale;
                 transform.matrix.d = scale;
-                transform.color_transform.r_mult = f32::from(color.r) / 255.0;
-                transform.color_transform.g_mult = f32::from(color.g) / 255.0;
-                transform.color_transform.b_mult = f32::from(color.b) / 255.0;
-                transform.color_transform.a_mult = f32::from(color.a) / 255.0;
                 for c in &block.glyphs {
                     if let Some(glyph) = font.get_glyph(c.index as usize) {
+                        let glyph_color = if tf.selected_glyphs.get(glyph_index) == Some(&true) {
+                            &tf.select_color
+                        } else {
+                            &color
+                        };
+                        transform.color_transform.r_mult = f32::from(glyph_color.r) / 255.0;
+                        transform.color_transform.g_mult = f32::from(glyph_color.g) / 255.0;
+                        transform.color_transform.b_mult = f32::from(glyph_color.b) / 255.0;
+                        transform.color_transform.a_mult = f32::from(glyph_color.a) / 255.0;
+                        glyph_index += 1;
                         context.transform_stack.push(&transform);
                         context
                             .renderer
@@ -0,0 +1,255 @@
+//! Video display object
+
+use crate::avm1::Avm1;
+use crate::backend::render::{BitmapHandle, DecodedFrame, RenderBackend};
+use crate::context::{RenderContext, UpdateContext};
+use crate::display_object::{DisplayObjectBase, TDisplayObject};
+use crate::prelude::*;
+use crate::video::ScreenVideoDecoder;
+use gc_arena::{Collect, Gc, GcCell, MutationContext};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use swf::{VideoCodec, VideoDeblocking};
+
+/// A video stream embedded in the SWF with `DefineVideoStream`.
+///
+/// The frames of the stream come from `VideoFrame` tags on the timeline of the movie
+/// containing it, and the frame that is shown is chosen by the `ratio` of the
+/// `PlaceObject` tags that move it.
+#[derive(Clone, Debug, Collect, Copy)]
+#[collect(no_drop)]
+pub struct Video<'gc>(GcCell<'gc, VideoData<'gc>>);
+
+#[derive(Clone, Debug)]
+pub struct VideoData<'gc> {
+    base: DisplayObjectBase<'gc>,
+    static_data: Gc<'gc, VideoStatic>,
+
+    /// The frame of the stream to show.
+    frame: u16,
+
+    /// Whether the video is drawn with bilinear filtering when scaled.
+    smoothing: bool,
+
+    /// How much decoders should smooth out the edges of compressed blocks.
+    deblocking: VideoDeblocking,
+}
+
+impl<'gc> Video<'gc> {
+    pub fn from_swf_tag(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        swf_tag: &swf::DefineVideoStream,
+    ) -> Self {
+        let (width, height) = (swf_tag.width.into(), swf_tag.height.into());
+        let bitmap_handle = context
+            .renderer
+            .register_video_stream(swf_tag.id, width, height)
+            .map_err(|e| log::warn!("Unable to register video stream {}: {}", swf_tag.id, e))
+            .ok();
+        let decoder = if swf_tag.codec == VideoCodec::ScreenVideo {
+            Some(ScreenVideoDecoder::new(width, height))
+        } else {
+            log::warn!("Video codec {:?} is unsupported", swf_tag.codec);
+            None
+        };
+
+        Video(GcCell::allocate(
+            context.gc_context,
+            VideoData {
+                base: Default::default(),
+                static_data: Gc::allocate(
+                    context.gc_context,
+                    VideoStatic {
+                        id: swf_tag.id,
+                        width: swf_tag.width,
+                        height: swf_tag.height,
+                        bitmap_handle,
+                        frames: RefCell::new(BTreeMap::new()),
+                        decoder: RefCell::new(decoder),
+                        decoded_frame: Cell::new(None),
+                    },
+                ),
+                frame: 0,
+                smoothing: swf_tag.is_smoothed,
+                deblocking: swf_tag.deblocking,
+            },
+        ))
+    }
+
+    /// Adds the encoded data of a frame of the stream, from a `VideoFrame` tag.
+    pub fn preload_frame(self, frame_num: u16, data: Vec<u8>) {
+        let static_data = self.0.read().static_data;
+        static_data.frames.borrow_mut().insert(frame_num, data);
+    }
+
+    pub fn frame(self) -> u16 {
+        self.0.read().frame
+    }
+
+    pub fn set_frame(self, gc_context: MutationContext<'gc, '_>, frame: u16) {
+        let mut video = self.0.write(gc_context);
+        video.frame = frame;
+        video.base.invalidate_content();
+    }
+
+    pub fn smoothing(self) -> bool {
+        self.0.read().smoothing
+    }
+
+    pub fn set_smoothing(self, gc_context: MutationContext<'gc, '_>, smoothing: bool) {
+        let mut video = self.0.write(gc_context);
+        video.smoothing = smoothing;
+        video.base.invalidate_content();
+    }
+
+    /// The deblocking filter applied by the H.263 and VP6 decoders.
+    /// Screen Video has no compression artifacts to smooth out, so it ignores this.
+    pub fn deblocking(self) -> VideoDeblocking {
+        self.0.read().deblocking
+    }
+
+    pub fn set_deblocking(self, gc_context: MutationContext<'gc, '_>, deblocking: VideoDeblocking) {
+        self.0.write(gc_context).deblocking = deblocking;
+    }
+}
+
+impl<'gc> TDisplayObject<'gc> for Video<'gc> {
+    impl_display_object!(base);
+
+    fn id(&self) -> CharacterId {
+        self.0.read().static_data.id
+    }
+
+    fn as_video(&self) -> Option<Self> {
+        Some(*self)
+    }
+
+    fn self_bounds(&self) -> BoundingBox {
+        let static_data = self.0.read().static_data;
+        BoundingBox {
+            x_min: Twips::new(0),
+            y_min: Twips::new(0),
+            x_max: Twips::from_pixels(static_data.width.into()),
+            y_max: Twips::from_pixels(static_data.height.into()),
+            valid: true,
+        }
+    }
+
+    fn run_frame(&mut self, _avm: &mut Avm1<'gc>, _context: &mut UpdateContext) {
+        // Noop
+    }
+
+    fn render(&self, context: &mut RenderContext) {
+        if !self.world_bounds().intersects(&context.view_bounds) {
+            // Off-screen; culled
+            return;
+        }
+
+        let static_data = self.0.read().static_data;
+        let bitmap_handle = match static_data.bitmap_handle {
+            Some(handle) => handle,
+            None => return,
+        };
+        static_data.seek(context.renderer, bitmap_handle, self.frame());
+
+        context.transform_stack.push(&*self.transform());
+        context.renderer.render_bitmap(
+            bitmap_handle,
+            context.transform_stack.transform(),
+            self.smoothing(),
+        );
+        context.transform_stack.pop();
+    }
+}
+
+unsafe impl<'gc> gc_arena::Collect for VideoData<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        self.base.trace(cc);
+        self.static_data.trace(cc);
+    }
+}
+
+/// Static data shared between all instances of a video stream.
+///
+/// TODO: The decoded image is shared too, so instances showing different frames of
+/// the same stream show the same one.
+struct VideoStatic {
+    id: CharacterId,
+    width: u16,
+    height: u16,
+
+    /// The image that frames are uploaded to, or `None` if the renderer can't show video.
+    bitmap_handle: Option<BitmapHandle>,
+
+    /// The encoded frames of the stream that have been preloaded, keyed by frame number.
+    frames: RefCell<BTreeMap<u16, Vec<u8>>>,
+
+    /// `None` if the codec of the stream is unsupported.
+    decoder: RefCell<Option<ScreenVideoDecoder>>,
+
+    /// The frame last uploaded to the renderer.
+    decoded_frame: Cell<Option<u16>>,
+}
+
+impl VideoStatic {
+    /// Decodes the given frame and uploads it to the renderer, if it isn't already shown.
+    fn seek(&self, renderer: &mut dyn RenderBackend, bitmap_handle: BitmapHandle, frame: u16) {
+        let last_frame = self.decoded_frame.get();
+        if last_frame == Some(frame) {
+            return;
+        }
+        self.decoded_frame.set(Some(frame));
+
+        let mut decoder = self.decoder.borrow_mut();
+        let decoder = match &mut *decoder {
+            Some(decoder) => decoder,
+            None => return,
+        };
+
+        // Frames build on the ones before them. Seeking backwards starts over from the
+        // first frame, which is always a keyframe.
+        let first_frame = match last_frame {
+            Some(last_frame) if last_frame < frame => last_frame + 1,
+            _ => 0,
+        };
+        for (frame_num, data) in self.frames.borrow().range(first_frame..=frame) {
+            if let Err(e) = decoder.decode(data) {
+                log::warn!(
+                    "Unable to decode frame {} of video {}: {}",
+                    frame_num,
+                    self.id,
+                    e
+                );
+                return;
+            }
+        }
+
+        if let Err(e) =
+            renderer.upload_video_frame(bitmap_handle, DecodedFrame::Rgba(decoder.pixels()))
+        {
+            log::warn!(
+                "Unable to upload frame {} of video {}: {}",
+                frame,
+                self.id,
+                e
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for VideoStatic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VideoStatic")
+            .field("id", &self.id)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+unsafe impl gc_arena::Collect for VideoStatic {
+    #[inline]
+    fn needs_trace() -> bool {
+        false
+    }
+}
@@ -1,18 +1,28 @@
 use crate::backend::render::ShapeHandle;
 use crate::bounding_box::BoundingBox;
 use crate::context::RenderContext;
-use crate::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use crate::profiler::Subsystem;
+use crate::shape_utils::{matrix_scale, DistilledShape, DrawCommand, DrawPath};
 use gc_arena::Collect;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use swf::{FillStyle, LineStyle, Twips};
 
-#[derive(Clone, Debug, Collect)]
+#[derive(Debug, Collect)]
 #[collect(require_static)]
 pub struct Drawing {
     render_handle: Cell<Option<ShapeHandle>>,
     shape_bounds: BoundingBox,
     edge_bounds: BoundingBox,
     dirty: Cell<bool>,
+
+    /// The scale that the shape was last tessellated for, and whether that
+    /// affects its strokes.
+    scale: Cell<(f32, f32)>,
+    has_scale_dependent_strokes: Cell<bool>,
+
+    /// The commands that the shape was last tessellated from.
+    tessellated: RefCell<Option<DrawingCommands>>,
+
     fills: Vec<(FillStyle, Vec<DrawCommand>)>,
     lines: Vec<(LineStyle, Vec<DrawCommand>)>,
     current_fill: Option<(FillStyle, Vec<DrawCommand>)>,
@@ -20,6 +30,36 @@ pub struct Drawing {
     cursor: (Twips, Twips),
 }
 
+/// The fills and lines of a drawing, in the order they are drawn.
+#[derive(Debug, PartialEq)]
+struct DrawingCommands {
+    fills: Vec<(FillStyle, Vec<DrawCommand>)>,
+    lines: Vec<(LineStyle, Vec<DrawCommand>)>,
+}
+
+impl Clone for Drawing {
+    /// Copies the drawing commands of this drawing.
+    ///
+    /// The copy registers its own shape with the renderer, so that later changes to
+    /// either drawing don't affect the other.
+    fn clone(&self) -> Self {
+        Self {
+            render_handle: Cell::new(None),
+            shape_bounds: self.shape_bounds.clone(),
+            edge_bounds: self.edge_bounds.clone(),
+            dirty: Cell::new(true),
+            scale: self.scale.clone(),
+            has_scale_dependent_strokes: self.has_scale_dependent_strokes.clone(),
+            tessellated: RefCell::new(None),
+            fills: self.fills.clone(),
+            lines: self.lines.clone(),
+            current_fill: self.current_fill.clone(),
+            current_line: self.current_line.clone(),
+            cursor: self.cursor,
+        }
+    }
+}
+
 impl Drawing {
     pub fn new() -> Self {
         Self {
@@ -27,6 +67,9 @@ impl Drawing {
             shape_bounds: BoundingBox::default(),
             edge_bounds: BoundingBox::default(),
             dirty: Cell::new(false),
+            scale: Cell::new((1.0, 1.0)),
+            has_scale_dependent_strokes: Cell::new(false),
+            tessellated: RefCell::new(None),
             fills: Vec::new(),
             lines: Vec::new(),
             current_fill: None,
@@ -82,6 +125,19 @@ impl Drawing {
         self.dirty.set(true);
     }
 
+    /// Sets the fill used to paint the strokes of the current line style, such as a gradient.
+    ///
+    /// Only lines drawn after this call use the new fill.
+    pub fn set_line_fill_style(&mut self, style: Option<FillStyle>) {
+        if let Some((line_style, _)) = &self.current_line {
+            let line_style = LineStyle {
+                fill_style: style,
+                ..line_style.clone()
+            };
+            self.set_line_style(Some(line_style));
+        }
+    }
+
     pub fn draw_command(&mut self, command: DrawCommand) {
         let mut include_last = false;
         let stroke_width = if let Some((style, _)) = &self.current_line {
@@ -137,35 +193,32 @@ impl Drawing {
     }
 
     pub fn render(&self, context: &mut RenderContext) {
-        if self.dirty.get() {
+        let scale = matrix_scale(&context.transform_stack.transform().matrix);
+        let rescaled = self.has_scale_dependent_strokes.get() && self.scale.get() != scale;
+        if self.dirty.get() || rescaled {
             self.dirty.set(false);
-            let mut paths = Vec::new();
 
-            for (style, commands) in &self.fills {
-                paths.push(DrawPath::Fill {
-                    style,
-                    commands: commands.to_owned(),
-                })
+            // Drawings are often cleared and drawn again the same way every frame, in which
+            // case the shape doesn't have to be tessellated again.
+            let commands = self.commands();
+            if !rescaled
+                && self.render_handle.get().is_some()
+                && self.tessellated.borrow().as_ref() == Some(&commands)
+            {
+                self.render_shape(context);
+                return;
             }
 
-            // TODO: If the current_fill is not closed, we should automatically close current_line
+            let mut paths = Vec::new();
 
-            if let Some((style, commands)) = &self.current_fill {
+            for (style, commands) in &commands.fills {
                 paths.push(DrawPath::Fill {
                     style,
                     commands: commands.to_owned(),
                 })
             }
 
-            for (style, commands) in &self.lines {
-                paths.push(DrawPath::Stroke {
-                    style,
-                    commands: commands.to_owned(),
-                    is_closed: false, // TODO: Determine this
-                })
-            }
-
-            if let Some((style, commands)) = &self.current_line {
+            for (style, commands) in &commands.lines {
                 paths.push(DrawPath::Stroke {
                     style,
                     commands: commands.to_owned(),
@@ -178,16 +231,27 @@ impl Drawing {
                 shape_bounds: self.shape_bounds.clone(),
                 edge_bounds: self.edge_bounds.clone(),
                 id: 0,
+                scale,
             };
+            self.scale.set(scale);
+            self.has_scale_dependent_strokes
+                .set(shape.has_scale_dependent_strokes());
 
+            let span = context.profiler.start();
             if let Some(handle) = self.render_handle.get() {
                 context.renderer.replace_shape(shape, handle);
             } else {
                 self.render_handle
                     .set(Some(context.renderer.register_shape(shape)));
             }
+            context.profiler.record(Subsystem::Tessellation, span);
+            self.tessellated.replace(Some(commands));
         }
 
+        self.render_shape(context);
+    }
+
+    fn render_shape(&self, context: &mut RenderContext) {
         if let Some(handle) = self.render_handle.get() {
             context
                 .renderer
@@ -195,6 +259,25 @@ impl Drawing {
         }
     }
 
+    /// The fills and lines of this drawing, including the ones still being drawn.
+    fn commands(&self) -> DrawingCommands {
+        // TODO: If the current_fill is not closed, we should automatically close current_line
+        DrawingCommands {
+            fills: self
+                .fills
+                .iter()
+                .chain(self.current_fill.iter())
+                .cloned()
+                .collect(),
+            lines: self
+                .lines
+                .iter()
+                .chain(self.current_line.iter())
+                .cloned()
+                .collect(),
+        }
+    }
+
     pub fn self_bounds(&self) -> BoundingBox {
         self.shape_bounds.clone()
     }
@@ -1,7 +1,7 @@
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 #[allow(clippy::enum_variant_names)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum PlayerEvent {
     KeyDown { key_code: KeyCode },
     KeyUp { key_code: KeyCode },
@@ -9,7 +9,69 @@ pub enum PlayerEvent {
     MouseUp { x: f64, y: f64 },
     MouseDown { x: f64, y: f64 },
     MouseLeft,
+    MouseWheel { delta: MouseWheelDelta },
     TextInput { codepoint: char },
+    Ime(ImeEvent),
+
+    /// A finger touched, moved on, or left a touchscreen. `id` identifies the touch
+    /// point while it is down, so several touches can be tracked at once.
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    },
+}
+
+/// The stage of a touch point on a touchscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    Start,
+    Move,
+    End,
+
+    /// The touch was interrupted by the system, such as by a gesture of the browser.
+    Cancel,
+}
+
+/// Text input from an input method editor, used to type characters that
+/// aren't on the keyboard, such as CJK text.
+#[derive(Debug, Clone)]
+pub enum ImeEvent {
+    /// The text being composed changed. It is shown in the focused text field,
+    /// but isn't part of its text yet.
+    Preedit(String),
+
+    /// The composition finished, and its text should be typed into the text field.
+    Commit(String),
+
+    /// The composition was abandoned without typing anything.
+    Cancel,
+}
+
+/// The distance scrolled by the mouse wheel.
+/// Positive values scroll up, away from the user.
+#[derive(Debug, Clone, Copy)]
+pub enum MouseWheelDelta {
+    /// Scrolled by a number of lines. Flash on Windows reported 3 lines for each notch
+    /// of the wheel, following the system default.
+    Lines(f64),
+
+    /// Scrolled by a number of pixels, as reported by touchpads and browsers.
+    Pixels(f64),
+}
+
+impl MouseWheelDelta {
+    /// Browsers scroll about 100 pixels for each notch of the wheel, which Flash reported as 3 lines.
+    const PIXELS_PER_LINE: f64 = 100.0 / 3.0;
+
+    /// The number of lines scrolled, which is the delta passed to `Mouse.onMouseWheel`.
+    pub fn lines(self) -> f64 {
+        match self {
+            MouseWheelDelta::Lines(lines) => lines,
+            MouseWheelDelta::Pixels(pixels) => pixels / Self::PIXELS_PER_LINE,
+        }
+    }
 }
 
 /// Whether this button event was handled by some child.
@@ -102,6 +164,8 @@ impl ClipEvent {
 pub enum KeyCode {
     Unknown = 0,
     Backspace = 8,
+    Tab = 9,
+    Clear = 12,
     Return = 13,
     Shift = 16,
     Control = 17,
@@ -181,7 +245,9 @@ pub enum KeyCode {
     Down = 40,
     Insert = 45,
     Delete = 46,
+    Help = 47,
     Pause = 19,
+    NumLock = 144,
     ScrollLock = 145,
     F1 = 112,
     F2 = 113,
@@ -195,6 +261,9 @@ pub enum KeyCode {
     F10 = 121,
     F11 = 122,
     F12 = 123,
+    F13 = 124,
+    F14 = 125,
+    F15 = 126,
 }
 
 /// Key codes for SWF4 keyPress button handlers. These are annoyingly different than
@@ -331,6 +400,7 @@ pub fn key_code_to_button_key_code(key_code: KeyCode) -> Option<ButtonKeyCode> {
         KeyCode::Down => ButtonKeyCode::Down,
         KeyCode::PgUp => ButtonKeyCode::PgUp,
         KeyCode::PgDown => ButtonKeyCode::PgDown,
+        KeyCode::Tab => ButtonKeyCode::Tab,
         KeyCode::Escape => ButtonKeyCode::Escape,
         _ => return None,
     };
@@ -0,0 +1,184 @@
+//! Keyboard focus, and moving it between objects with the Tab key.
+//!
+//! Buttons, button-mode movie clips and input text fields can take focus. Movies
+//! change the tab order with the `tabEnabled`, `tabIndex` and `tabChildren`
+//! properties, which are plain AVM1 properties set by scripts.
+
+use crate::avm1::listeners::SystemListener;
+use crate::avm1::{Avm1, Value};
+use crate::context::{ActionType, UpdateContext};
+use crate::display_object::{get_avm1_property, is_enabled, DisplayObject, TDisplayObject};
+use std::cmp::Ordering;
+
+/// Moves keyboard focus to the given object, or removes focus.
+///
+/// The object losing focus gets `onKillFocus(newFocus)`, the object gaining focus
+/// gets `onSetFocus(oldFocus)`, and `Selection` listeners get
+/// `onSetFocus(oldFocus, newFocus)`.
+pub fn set_focus<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    new_focus: Option<DisplayObject<'gc>>,
+) {
+    let old_focus = *context.focus;
+    let is_same = match (old_focus, new_focus) {
+        (Some(old), Some(new)) => DisplayObject::ptr_eq(old, new),
+        (None, None) => true,
+        _ => false,
+    };
+    if is_same {
+        return;
+    }
+
+    if let Some(text_field) = old_focus.and_then(|old| old.as_edit_text()) {
+        // Any unfinished composition is abandoned when focus moves.
+        text_field.set_composition(None, context);
+    }
+    if let Some(text_field) = new_focus.and_then(|new| new.as_edit_text()) {
+        text_field.move_cursor_to_end(context.gc_context);
+    }
+    *context.focus = new_focus;
+    context
+        .input
+        .set_ime_enabled(new_focus.and_then(|new| new.as_edit_text()).is_some());
+
+    let old_value = old_focus.map_or(Value::Null, |old| old.object());
+    let new_value = new_focus.map_or(Value::Null, |new| new.object());
+    if let Some(old) = old_focus {
+        if let Value::Object(object) = old.object() {
+            context.action_queue.queue_actions(
+                old,
+                ActionType::Method {
+                    object,
+                    name: "onKillFocus",
+                    args: vec![new_value.clone()],
+                },
+                false,
+            );
+        }
+    }
+    if let Some(new) = new_focus {
+        if let Value::Object(object) = new.object() {
+            context.action_queue.queue_actions(
+                new,
+                ActionType::Method {
+                    object,
+                    name: "onSetFocus",
+                    args: vec![old_value.clone()],
+                },
+                false,
+            );
+        }
+    }
+    if let Some(root) = context.levels.get(&0).copied() {
+        context.action_queue.queue_actions(
+            root,
+            ActionType::NotifyListeners {
+                listener: SystemListener::Selection,
+                method: "onSetFocus",
+                args: vec![old_value, new_value],
+            },
+            false,
+        );
+    }
+}
+
+/// The object after the focused one in tab order, wrapping around at the end,
+/// or the one before it if `reverse` is set. Returns `None` if nothing can take focus.
+pub fn next_focus<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    reverse: bool,
+) -> Option<DisplayObject<'gc>> {
+    let order = tab_order(avm, context);
+    if order.is_empty() {
+        return None;
+    }
+    let position = context
+        .focus
+        .and_then(|focus| order.iter().position(|&o| DisplayObject::ptr_eq(o, focus)));
+    let index = match (position, reverse) {
+        (Some(i), false) => (i + 1) % order.len(),
+        (Some(i), true) => (i + order.len() - 1) % order.len(),
+        (None, false) => 0,
+        (None, true) => order.len() - 1,
+    };
+    Some(order[index])
+}
+
+/// The objects that can take focus, in the order the Tab key visits them.
+///
+/// If any object has a `tabIndex`, only objects with a `tabIndex` are visited, in
+/// increasing order. Otherwise objects are visited from top to bottom, then left to right.
+pub fn tab_order<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Vec<DisplayObject<'gc>> {
+    let mut candidates = vec![];
+    let levels: Vec<_> = context.levels.values().copied().collect();
+    for level in levels {
+        for child in level.children() {
+            collect_tab_candidates(avm, context, child, &mut candidates);
+        }
+    }
+
+    let mut indexed: Vec<_> = candidates
+        .iter()
+        .filter_map(
+            |&object| match get_avm1_property(avm, context, object, "tabIndex") {
+                Some(Value::Number(index)) => Some((index, object)),
+                _ => None,
+            },
+        )
+        .collect();
+    if !indexed.is_empty() {
+        indexed.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        return indexed.into_iter().map(|(_, object)| object).collect();
+    }
+
+    candidates.sort_by_key(|object| {
+        let bounds = object.world_bounds();
+        (bounds.y_min, bounds.x_min)
+    });
+    candidates
+}
+
+/// Adds the given object and its descendants to the list if they can take focus.
+fn collect_tab_candidates<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    object: DisplayObject<'gc>,
+    candidates: &mut Vec<DisplayObject<'gc>>,
+) {
+    if !object.visible() {
+        return;
+    }
+
+    let swf_version = context.swf.version();
+    let tab_enabled = get_avm1_property(avm, context, object, "tabEnabled")
+        .map(|value| value.as_bool(swf_version));
+    let is_focusable = match tab_enabled {
+        Some(tab_enabled) => tab_enabled,
+        None => {
+            if let Some(text_field) = object.as_edit_text() {
+                text_field.is_editable()
+            } else if let Some(clip) = object.as_movie_clip() {
+                clip.is_button_mode(avm, context) && is_enabled(avm, context, object)
+            } else {
+                object.as_button().is_some() && is_enabled(avm, context, object)
+            }
+        }
+    };
+    if is_focusable {
+        candidates.push(object);
+    }
+
+    if object.as_movie_clip().is_some() {
+        let tab_children = get_avm1_property(avm, context, object, "tabChildren")
+            .map_or(true, |value| value.as_bool(swf_version));
+        if tab_children {
+            for child in object.children() {
+                collect_tab_candidates(avm, context, child, candidates);
+            }
+        }
+    }
+}
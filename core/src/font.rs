@@ -108,6 +108,7 @@ impl<'gc> Font<'gc> {
             let glyph = Glyph {
                 shape: renderer.register_glyph_shape(swf_glyph),
                 advance: swf_glyph.advance.unwrap_or(0),
+                code: swf_glyph.code,
             };
             let index = glyphs.len();
             glyphs.push(glyph);
@@ -123,11 +124,16 @@ impl<'gc> Font<'gc> {
             fnv::FnvHashMap::default()
         };
 
+        // DefineFont3 stores coordinates at 20x the scale of DefineFont1/2.
+        // (SWF19 p.164)
+        let scale = if tag.version >= 3 { 20480.0 } else { 1024.0 };
+
         let descriptor = FontDescriptor::from_swf_tag(tag);
         let (ascent, descent, leading) = if let Some(layout) = &tag.layout {
             (layout.ascent, layout.descent, layout.leading)
         } else {
-            (0, 0, 0)
+            // Without layout info, place the baseline at the bottom of the EM square.
+            (scale as u16, 0, 0)
         };
 
         Ok(Font(Gc::allocate(
@@ -135,10 +141,7 @@ impl<'gc> Font<'gc> {
             FontData {
                 glyphs,
                 code_point_to_glyph,
-
-                /// DefineFont3 stores coordinates at 20x the scale of DefineFont1/2.
-                /// (SWF19 p.164)
-                scale: if tag.version >= 3 { 20480.0 } else { 1024.0 },
+                scale,
                 kerning_pairs,
                 ascent,
                 descent,
@@ -172,6 +175,12 @@ impl<'gc> Font<'gc> {
         }
     }
 
+    /// Returns whether this font contains a glyph for the given character.
+    pub fn has_glyph_for_char(self, c: char) -> bool {
+        // TODO: Properly handle UTF-16/out-of-bounds code points.
+        self.0.code_point_to_glyph.contains_key(&(c as u16))
+    }
+
     /// Given a pair of characters, applies the offset that should be applied
     /// to the advance value between these two characters.
     /// Returns 0 twips if no kerning offset exists between these two characters.
@@ -200,6 +209,13 @@ impl<'gc> Font<'gc> {
         Twips::new((self.0.ascent as f32 * scale) as i32)
     }
 
+    /// Get the distance from the baseline to the bottom of the glyph at a given height.
+    pub fn get_descent_for_height(self, height: Twips) -> Twips {
+        let scale = height.get() as f32 / self.scale();
+
+        Twips::new((self.0.descent as f32 * scale) as i32)
+    }
+
     /// Returns whether this font contains kerning information.
     pub fn has_kerning_info(self) -> bool {
         !self.0.kerning_pairs.is_empty()
@@ -360,6 +376,9 @@ impl<'gc> Font<'gc> {
 pub struct Glyph {
     pub shape: ShapeHandle,
     pub advance: i16,
+
+    /// The character code that this glyph represents.
+    pub code: u16,
 }
 
 /// Structure which identifies a particular font by name and properties.
@@ -595,4 +614,74 @@ mod tests {
             assert_eq!(None, breakpoint5);
         });
     }
+
+    fn glyph(code: char, advance: i16) -> swf::Glyph {
+        swf::Glyph {
+            shape_records: vec![],
+            code: code as u16,
+            advance: Some(advance),
+            bounds: None,
+        }
+    }
+
+    fn swf_font(version: u8, layout: Option<swf::FontLayout>) -> swf::Font {
+        swf::Font {
+            version,
+            id: 1,
+            name: "Test".to_string(),
+            language: swf::Language::Unknown,
+            layout,
+            glyphs: vec![glyph('A', 1000), glyph('V', 1000)],
+            is_small_text: false,
+            is_shift_jis: false,
+            is_ansi: false,
+            is_bold: false,
+            is_italic: false,
+        }
+    }
+
+    #[test]
+    fn measure_uses_advances_and_kerning() {
+        rootless_arena(|gc_context| {
+            let layout = swf::FontLayout {
+                ascent: 16000,
+                descent: 4000,
+                leading: 0,
+                kerning: vec![swf::KerningRecord {
+                    left_code: 'A' as u16,
+                    right_code: 'V' as u16,
+                    adjustment: Twips::new(-200),
+                }],
+            };
+            let tag = swf_font(3, Some(layout));
+            let font = Font::from_swf_tag(gc_context, &mut NullRenderer::new(), &tag).unwrap();
+
+            // DefineFont3 uses a 20480 unit EM square, so this height renders at 1:1.
+            let height = Twips::new(20480);
+            let params = EvalParameters::from_parts(height, Twips::zero(), false);
+            assert_eq!(font.measure("AV", params, false).0, Twips::new(2000));
+
+            let params = EvalParameters::from_parts(height, Twips::zero(), true);
+            assert_eq!(font.measure("AV", params, false).0, Twips::new(1800));
+
+            assert_eq!(font.get_baseline_for_height(height), Twips::new(16000));
+            assert_eq!(font.get_descent_for_height(height), Twips::new(4000));
+        })
+    }
+
+    #[test]
+    fn define_font_2_scale() {
+        rootless_arena(|gc_context| {
+            let tag = swf_font(2, None);
+            let font = Font::from_swf_tag(gc_context, &mut NullRenderer::new(), &tag).unwrap();
+
+            // DefineFont1/2 use a 1024 unit EM square.
+            let height = Twips::new(512);
+            let params = EvalParameters::from_parts(height, Twips::zero(), false);
+            assert_eq!(font.measure("A", params, false).0, Twips::new(500));
+
+            // Without layout info, the baseline is at the bottom of the EM square.
+            assert_eq!(font.get_baseline_for_height(height), height);
+        })
+    }
 }
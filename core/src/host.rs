@@ -0,0 +1,153 @@
+//! Functions and objects added to the AVM1 globals by applications that embed
+//! Ruffle, such as a `Host.saveScore()` method for a game launcher.
+//!
+//! Values are passed between the movie and the host as `HostValue`s, which only
+//! hold primitives. Objects passed by the movie are converted to strings.
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use gc_arena::MutationContext;
+use std::rc::Rc;
+
+/// A value passed between a movie and the application embedding Ruffle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl From<bool> for HostValue {
+    fn from(value: bool) -> Self {
+        HostValue::Bool(value)
+    }
+}
+
+impl From<f64> for HostValue {
+    fn from(value: f64) -> Self {
+        HostValue::Number(value)
+    }
+}
+
+impl From<String> for HostValue {
+    fn from(value: String) -> Self {
+        HostValue::String(value)
+    }
+}
+
+impl From<&str> for HostValue {
+    fn from(value: &str) -> Self {
+        HostValue::String(value.to_string())
+    }
+}
+
+/// A function implemented by the host, which is called with the arguments given
+/// by the movie and returns the value the movie receives.
+pub type HostFunction = dyn Fn(&[HostValue]) -> HostValue;
+
+/// An object implemented by the host, with methods and constant properties.
+#[derive(Clone, Default)]
+pub struct HostObject {
+    members: Vec<(String, HostMember)>,
+}
+
+#[derive(Clone)]
+enum HostMember {
+    Method(Rc<HostFunction>),
+    Value(HostValue),
+}
+
+impl HostObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a method to the object.
+    pub fn with_method(
+        mut self,
+        name: &str,
+        method: impl 'static + Fn(&[HostValue]) -> HostValue,
+    ) -> Self {
+        self.members
+            .push((name.to_string(), HostMember::Method(Rc::new(method))));
+        self
+    }
+
+    /// Adds a property with the given value to the object.
+    pub fn with_value(mut self, name: &str, value: impl Into<HostValue>) -> Self {
+        self.members
+            .push((name.to_string(), HostMember::Value(value.into())));
+        self
+    }
+
+    /// Creates the AVM1 object.
+    pub(crate) fn create<'gc>(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        proto: Object<'gc>,
+        fn_proto: Object<'gc>,
+    ) -> Object<'gc> {
+        let object = ScriptObject::object(gc_context, Some(proto));
+        for (name, member) in &self.members {
+            let value = match member {
+                HostMember::Method(method) => {
+                    host_function(gc_context, Rc::clone(method), fn_proto).into()
+                }
+                HostMember::Value(value) => value.clone().into(),
+            };
+            object.define_value(gc_context, name, value, EnumSet::empty());
+        }
+        object.into()
+    }
+}
+
+/// Creates an AVM1 function that calls the given host function.
+pub(crate) fn host_function<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    function: Rc<HostFunction>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    FunctionObject::function(gc_context, Executable::Host(function), Some(fn_proto), None)
+}
+
+/// Calls a host function with AVM1 arguments.
+pub(crate) fn call_host_function<'gc>(
+    function: &HostFunction,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let mut host_args = Vec::with_capacity(args.len());
+    for arg in args {
+        let arg = match arg {
+            Value::Undefined => HostValue::Undefined,
+            Value::Null => HostValue::Null,
+            Value::Bool(value) => HostValue::Bool(*value),
+            Value::Number(value) => HostValue::Number(*value),
+            Value::String(value) => HostValue::String(value.clone()),
+            Value::Object(_) => {
+                HostValue::String(arg.coerce_to_string(activation, context)?.into_owned())
+            }
+        };
+        host_args.push(arg);
+    }
+    Ok(function(&host_args).into())
+}
+
+impl<'gc> From<HostValue> for Value<'gc> {
+    fn from(value: HostValue) -> Self {
+        match value {
+            HostValue::Undefined => Value::Undefined,
+            HostValue::Null => Value::Null,
+            HostValue::Bool(value) => Value::Bool(value),
+            HostValue::Number(value) => Value::Number(value),
+            HostValue::String(value) => Value::String(value),
+        }
+    }
+}
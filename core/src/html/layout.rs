@@ -47,6 +47,9 @@ pub struct LayoutContext<'a, 'gc> {
     /// The resolved font object to use when measuring text.
     font: Option<Font<'gc>>,
 
+    /// The font used to render characters that are missing from `font`.
+    fallback_font: Option<Font<'gc>>,
+
     /// The underlying bundle of text being formatted.
     text: &'a str,
 
@@ -94,6 +97,7 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             movie,
             cursor: Default::default(),
             font: None,
+            fallback_font: None,
             text,
             max_font_size: Default::default(),
             boxes: Vec::new(),
@@ -226,11 +230,14 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         }
 
         let mut line_bounds = None;
+        let mut line_baseline = Twips::zero();
         let mut box_count: i32 = 0;
         for linebox in self.boxes.get_mut(self.current_line..).unwrap() {
             let (text, _tf, font, params, _color) =
                 linebox.as_renderable_text(self.text).expect("text");
 
+            line_baseline = max(line_baseline, font.get_baseline_for_height(params.height()));
+
             //Flash ignores trailing spaces when aligning lines, so should we
             if self.current_line_span.align != swf::TextAlign::Left {
                 linebox.bounds = linebox.bounds.with_size(Size::from(font.measure(
@@ -286,15 +293,16 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
 
         box_count = 0;
         for linebox in self.boxes.get_mut(self.current_line..).unwrap() {
-            // TODO: This attempts to keep text of multiple font sizes vertically
-            // aligned correctly. It does not consider the baseline of the font,
-            // which is information we don't have yet.
             let font_size_adjustment = self.max_font_size - linebox.bounds.height();
 
-            if linebox.is_text_box() {
+            if let Some((_text, _tf, font, params, _color)) = linebox.as_renderable_text(self.text)
+            {
+                // Text of different fonts and sizes on the same line shares a common baseline.
+                let baseline_adjustment =
+                    line_baseline - font.get_baseline_for_height(params.height());
                 linebox.bounds += Position::from((
                     left_adjustment + align_adjustment + (interim_adjustment * box_count),
-                    font_size_adjustment,
+                    baseline_adjustment,
                 ));
             } else if linebox.is_bullet() {
                 linebox.bounds += Position::from((Default::default(), font_size_adjustment));
@@ -395,21 +403,37 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         span: &TextSpan,
         is_device_font: bool,
     ) -> Option<Font<'gc>> {
+        // Device text is rendered with a matching font installed on the system, or our
+        // embedded Noto Sans if there is none.
+        let device_font = context
+            .library
+            .get_or_load_device_font(
+                &span.font,
+                span.bold,
+                span.italic,
+                context.font,
+                context.renderer,
+                context.gc_context,
+            )
+            .or_else(|| {
+                context
+                    .library
+                    .library_for_movie_mut(self.movie.clone())
+                    .device_font()
+            });
+
+        // If this text field is set to use device fonts, use the device font even if an
+        // embedded font exists. Note that the SWF can still contain a DefineFont tag with no
+        // glyphs/layout info in this case (see #451).
         let library = context.library.library_for_movie_mut(self.movie.clone());
-
-        // If this text field is set to use device fonts, fallback to using our embedded Noto Sans.
-        // Note that the SWF can still contain a DefineFont tag with no glyphs/layout info in this case (see #451).
-        // In an ideal world, device fonts would search for a matching font on the system and render it in some way.
-        if let Some(font) = library
+        let embedded_font = library
             .get_font_by_name(&span.font, span.bold, span.italic)
-            .filter(|f| !is_device_font && f.has_glyphs())
-            .or_else(|| library.device_font())
-        {
-            self.font = Some(font);
-            return self.font;
-        }
+            .filter(|f| !is_device_font && f.has_glyphs());
 
-        None
+        // Characters missing from an embedded font are drawn with the device font.
+        self.fallback_font = device_font.filter(|_| embedded_font.is_some());
+        self.font = embedded_font.or(device_font);
+        self.font
     }
 
     /// Append text to the current line of the ongoing layout operation.
@@ -439,10 +463,58 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
     /// This function bypasses the text fragmentation necessary for justify to
     /// work and it should only be called internally.
     fn append_text_fragment(&mut self, text: &'a str, start: usize, end: usize, span: &TextSpan) {
+        let font = self.font.unwrap();
+        let fallback_font = match self.fallback_font {
+            Some(fallback_font) => fallback_font,
+            None => return self.append_text_run(text, start, end, font, span),
+        };
+
+        // Split the fragment into runs of characters that the font can and can't render,
+        // so that missing glyphs are drawn with the fallback font instead.
+        let uses_fallback =
+            |c: char| !font.has_glyph_for_char(c) && fallback_font.has_glyph_for_char(c);
+        let mut run_start = 0;
+        let mut run_uses_fallback = None;
+        for (i, c) in text.char_indices() {
+            let char_uses_fallback = uses_fallback(c);
+            if let Some(run_uses_fallback) = run_uses_fallback {
+                if run_uses_fallback != char_uses_fallback {
+                    let run_font = if run_uses_fallback {
+                        fallback_font
+                    } else {
+                        font
+                    };
+                    let run = &text[run_start..i];
+                    self.append_text_run(run, start + run_start, start + i, run_font, span);
+                    run_start = i;
+                }
+            }
+            run_uses_fallback = Some(char_uses_fallback);
+        }
+
+        if let Some(run_uses_fallback) = run_uses_fallback {
+            let run_font = if run_uses_fallback {
+                fallback_font
+            } else {
+                font
+            };
+            self.append_text_run(&text[run_start..], start + run_start, end, run_font, span);
+        }
+    }
+
+    /// Append a run of text that is rendered entirely in one font.
+    fn append_text_run(
+        &mut self,
+        text: &'a str,
+        start: usize,
+        end: usize,
+        font: Font<'gc>,
+        span: &TextSpan,
+    ) {
         let params = EvalParameters::from_span(span);
-        let text_size = Size::from(self.font.unwrap().measure(text, params, false));
+        let text_size = Size::from(font.measure(text, params, false));
         let text_bounds = BoxBounds::from_position_and_size(self.cursor, text_size);
-        let mut new_text = LayoutBox::from_text(start, end, self.font.unwrap(), span);
+        let mut new_text = LayoutBox::from_text(start, end, font, span);
 
         new_text.bounds = text_bounds;
 
@@ -0,0 +1,394 @@
+//! Scripted input, which plays a fixed sequence of input events into the player
+//! at given frames, such as for replays and regression tests.
+//!
+//! A script is a text file with one event per line, after the number of the frame
+//! it is sent before. Frames are numbered from 1. Blank lines and lines starting
+//! with `#` are ignored.
+//!
+//! ```text
+//! # Click the start button, then hold the right arrow key.
+//! 10 mouse_move 200 150
+//! 10 mouse_down 200 150
+//! 11 mouse_up 200 150
+//! 30 key_down 39
+//! 90 key_up 39
+//! ```
+//!
+//! Key codes are Flash key codes, and characters typed with `text_input` are given
+//! as Unicode code points.
+//!
+//! Scripts can be recorded from the input given to a player with
+//! `Player::start_input_recording`.
+
+use crate::backend::input::{InputBackend, MouseCursor};
+use crate::events::{ImeEvent, KeyCode, MouseWheelDelta, PlayerEvent, TouchPhase};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A sequence of input events, each sent before a given frame runs.
+#[derive(Debug, Default)]
+pub struct InputScript {
+    events: VecDeque<(u32, PlayerEvent)>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event to send before the given frame runs. Events must be added in
+    /// the order they are sent.
+    pub fn push(&mut self, frame: u32, event: PlayerEvent) {
+        self.events.push_back((frame, event));
+    }
+
+    /// The number of events that haven't been sent yet.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The events that haven't been sent yet, with the frames they are sent before.
+    pub fn events(&self) -> impl Iterator<Item = &(u32, PlayerEvent)> {
+        self.events.iter()
+    }
+
+    /// Removes and returns the events to send before the given frame, including
+    /// any events of earlier frames that weren't sent yet.
+    pub(crate) fn take_events(&mut self, frame: u32) -> Vec<PlayerEvent> {
+        let mut events = vec![];
+        while let Some((event_frame, _)) = self.events.front() {
+            if *event_frame > frame {
+                break;
+            }
+            if let Some((_, event)) = self.events.pop_front() {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+impl FromStr for InputScript {
+    type Err = InputScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut script = InputScript::new();
+        let mut last_frame = 0;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| InputScriptError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+
+            let mut parts = line.splitn(2, char::is_whitespace);
+            let frame: u32 = parts
+                .next()
+                .and_then(|frame| frame.parse().ok())
+                .ok_or_else(|| error("expected a frame number"))?;
+            if frame < last_frame {
+                return Err(error("events must be in frame order"));
+            }
+            last_frame = frame;
+            let event = parse_event(parts.next().unwrap_or("").trim()).map_err(|e| error(&e))?;
+            script.push(frame, event);
+        }
+        Ok(script)
+    }
+}
+
+impl Display for InputScript {
+    /// Writes the script in the format it is parsed from.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (frame, event) in &self.events {
+            write!(f, "{} ", frame)?;
+            match event {
+                PlayerEvent::KeyDown { key_code } => write!(f, "key_down {}", *key_code as u8)?,
+                PlayerEvent::KeyUp { key_code } => write!(f, "key_up {}", *key_code as u8)?,
+                PlayerEvent::MouseMove { x, y } => write!(f, "mouse_move {} {}", x, y)?,
+                PlayerEvent::MouseDown { x, y } => write!(f, "mouse_down {} {}", x, y)?,
+                PlayerEvent::MouseUp { x, y } => write!(f, "mouse_up {} {}", x, y)?,
+                PlayerEvent::MouseLeft => write!(f, "mouse_left")?,
+                PlayerEvent::MouseWheel {
+                    delta: MouseWheelDelta::Lines(lines),
+                } => write!(f, "mouse_wheel_lines {}", lines)?,
+                PlayerEvent::MouseWheel {
+                    delta: MouseWheelDelta::Pixels(pixels),
+                } => write!(f, "mouse_wheel_pixels {}", pixels)?,
+                PlayerEvent::TextInput { codepoint } => {
+                    write!(f, "text_input {}", u32::from(*codepoint))?
+                }
+                // Compositions are single lines, so their text can end the line.
+                PlayerEvent::Ime(ImeEvent::Preedit(text)) => write!(f, "ime_preedit {}", text)?,
+                PlayerEvent::Ime(ImeEvent::Commit(text)) => write!(f, "ime_commit {}", text)?,
+                PlayerEvent::Ime(ImeEvent::Cancel) => write!(f, "ime_cancel")?,
+                PlayerEvent::Touch { id, phase, x, y } => {
+                    let phase = match phase {
+                        TouchPhase::Start => "start",
+                        TouchPhase::Move => "move",
+                        TouchPhase::End => "end",
+                        TouchPhase::Cancel => "cancel",
+                    };
+                    write!(f, "touch {} {} {} {}", id, phase, x, y)?
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when an input script could not be parsed.
+#[derive(Debug, Error)]
+#[error("Invalid input script at line {line}: {message}")]
+pub struct InputScriptError {
+    line: usize,
+    message: String,
+}
+
+fn parse_event(s: &str) -> Result<PlayerEvent, String> {
+    let mut parts = s.splitn(2, char::is_whitespace);
+    let name = parts.next().unwrap_or("");
+    let args = parts.next().unwrap_or("").trim();
+    let numbers = || -> Result<Vec<f64>, String> {
+        args.split_whitespace()
+            .map(|arg| {
+                arg.parse()
+                    .map_err(|_| format!("invalid number {} for {}", arg, name))
+            })
+            .collect()
+    };
+    let number_args = |count: usize| -> Result<Vec<f64>, String> {
+        let numbers = numbers()?;
+        if numbers.len() == count {
+            Ok(numbers)
+        } else {
+            Err(format!("{} takes {} arguments", name, count))
+        }
+    };
+    let key_code = || -> Result<KeyCode, String> {
+        let code = number_args(1)?[0];
+        KeyCode::try_from(code as u8).map_err(|_| format!("unknown key code {}", code))
+    };
+
+    let event = match name {
+        "key_down" => PlayerEvent::KeyDown {
+            key_code: key_code()?,
+        },
+        "key_up" => PlayerEvent::KeyUp {
+            key_code: key_code()?,
+        },
+        "mouse_move" => {
+            let position = number_args(2)?;
+            PlayerEvent::MouseMove {
+                x: position[0],
+                y: position[1],
+            }
+        }
+        "mouse_down" => {
+            let position = number_args(2)?;
+            PlayerEvent::MouseDown {
+                x: position[0],
+                y: position[1],
+            }
+        }
+        "mouse_up" => {
+            let position = number_args(2)?;
+            PlayerEvent::MouseUp {
+                x: position[0],
+                y: position[1],
+            }
+        }
+        "mouse_left" => PlayerEvent::MouseLeft,
+        "mouse_wheel_lines" => PlayerEvent::MouseWheel {
+            delta: MouseWheelDelta::Lines(number_args(1)?[0]),
+        },
+        "mouse_wheel_pixels" => PlayerEvent::MouseWheel {
+            delta: MouseWheelDelta::Pixels(number_args(1)?[0]),
+        },
+        "text_input" => {
+            let codepoint = number_args(1)?[0];
+            PlayerEvent::TextInput {
+                codepoint: std::char::from_u32(codepoint as u32)
+                    .ok_or_else(|| format!("invalid code point {}", codepoint))?,
+            }
+        }
+        "ime_preedit" => PlayerEvent::Ime(ImeEvent::Preedit(args.to_string())),
+        "ime_commit" => PlayerEvent::Ime(ImeEvent::Commit(args.to_string())),
+        "ime_cancel" => PlayerEvent::Ime(ImeEvent::Cancel),
+        "touch" => {
+            let args: Vec<&str> = args.split_whitespace().collect();
+            if args.len() != 4 {
+                return Err("touch takes 4 arguments".to_string());
+            }
+            let phase = match args[1] {
+                "start" => TouchPhase::Start,
+                "move" => TouchPhase::Move,
+                "end" => TouchPhase::End,
+                "cancel" => TouchPhase::Cancel,
+                phase => return Err(format!("unknown touch phase {}", phase)),
+            };
+            let number = |arg: &str| {
+                arg.parse()
+                    .map_err(|_| format!("invalid number {} for touch", arg))
+            };
+            PlayerEvent::Touch {
+                id: args[0]
+                    .parse()
+                    .map_err(|_| format!("invalid touch id {}", args[0]))?,
+                phase,
+                x: number(args[2])?,
+                y: number(args[3])?,
+            }
+        }
+        _ => return Err(format!("unknown event {}", name)),
+    };
+    Ok(event)
+}
+
+/// Stands in for the input backend of the frontend while a script plays, so that
+/// `Key.isDown` sees the keys held by the script instead of the real keyboard.
+///
+/// Everything that isn't keyboard state is passed on to the frontend's backend.
+pub(crate) struct ScriptedInputBackend {
+    pub(crate) inner: Box<dyn InputBackend>,
+    keys_down: Vec<KeyCode>,
+    last_key_code: KeyCode,
+}
+
+impl ScriptedInputBackend {
+    pub(crate) fn new(inner: Box<dyn InputBackend>) -> Self {
+        Self {
+            inner,
+            keys_down: vec![],
+            last_key_code: KeyCode::Unknown,
+        }
+    }
+
+    /// Updates the held keys with a scripted event.
+    pub(crate) fn handle_event(&mut self, event: &PlayerEvent) {
+        match *event {
+            PlayerEvent::KeyDown { key_code } => {
+                self.last_key_code = key_code;
+                if !self.keys_down.contains(&key_code) {
+                    self.keys_down.push(key_code);
+                }
+            }
+            PlayerEvent::KeyUp { key_code } => {
+                self.last_key_code = key_code;
+                self.keys_down.retain(|&key| key != key_code);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl InputBackend for ScriptedInputBackend {
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    fn get_last_key_code(&self) -> KeyCode {
+        self.last_key_code
+    }
+
+    fn mouse_visible(&self) -> bool {
+        self.inner.mouse_visible()
+    }
+
+    fn hide_mouse(&mut self) {
+        self.inner.hide_mouse()
+    }
+
+    fn show_mouse(&mut self) {
+        self.inner.show_mouse()
+    }
+
+    fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        self.inner.set_mouse_cursor(cursor)
+    }
+
+    fn set_clipboard_content(&mut self, content: String) {
+        self.inner.set_clipboard_content(content)
+    }
+
+    fn set_trap_all_keys(&mut self, trap_all_keys: bool) {
+        self.inner.set_trap_all_keys(trap_all_keys)
+    }
+
+    fn set_ime_enabled(&mut self, enabled: bool) {
+        self.inner.set_ime_enabled(enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let mut script: InputScript = "
+            # A comment
+            1 mouse_move 10 20.5
+            1 key_down 39
+
+            3 text_input 97
+            3 ime_commit 日本語
+            4 touch 7 start 1 2
+        "
+        .parse()
+        .unwrap();
+        assert_eq!(script.len(), 5);
+
+        let events = script.take_events(1);
+        assert_eq!(events.len(), 2);
+        match events[0] {
+            PlayerEvent::MouseMove { x, y } => assert_eq!((x, y), (10.0, 20.5)),
+            _ => panic!("expected a mouse move"),
+        }
+        match events[1] {
+            PlayerEvent::KeyDown { key_code } => assert_eq!(key_code, KeyCode::Right),
+            _ => panic!("expected a key press"),
+        }
+
+        assert!(script.take_events(2).is_empty());
+        let events = script.take_events(4);
+        assert_eq!(events.len(), 3);
+        match &events[1] {
+            PlayerEvent::Ime(ImeEvent::Commit(text)) => assert_eq!(text, "日本語"),
+            _ => panic!("expected an IME commit"),
+        }
+        assert!(script.is_empty());
+    }
+
+    #[test]
+    fn write_script() {
+        let source = "1 mouse_move 10 20.5\n\
+                      1 key_down 39\n\
+                      2 mouse_wheel_pixels -100\n\
+                      3 text_input 97\n\
+                      3 ime_commit 日本語\n\
+                      4 touch 7 cancel 1 2\n";
+        let script: InputScript = source.parse().unwrap();
+        assert_eq!(script.to_string(), source);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("mouse_move 1 2".parse::<InputScript>().is_err());
+        assert!("1 mouse_move 1".parse::<InputScript>().is_err());
+        assert!("1 key_down 255".parse::<InputScript>().is_err());
+        assert!("2 mouse_left\n1 mouse_left".parse::<InputScript>().is_err());
+        assert!("1 dance".parse::<InputScript>().is_err());
+    }
+}
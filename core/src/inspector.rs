@@ -0,0 +1,305 @@
+//! Snapshots of the display list and of AVM1 objects, for inspector panels in
+//! frontends, external tools and tests.
+
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::debug::describe_value;
+use crate::avm1::{Avm1, Object, ObjectPtr, TObject, Value};
+use crate::color_transform::ColorTransform;
+use crate::context::UpdateContext;
+use crate::host::HostValue;
+use crate::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+/// A display object as it was when the snapshot was taken, with its children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayObjectInfo {
+    /// The kind of object: `MovieClip`, `Graphic`, `Button`, `EditText`, `Text`,
+    /// `MorphShape`, `Bitmap` or `Video`.
+    pub kind: &'static str,
+
+    /// The instance name, which is empty for unnamed objects.
+    pub name: String,
+
+    /// The dot path of the object, such as `_level0.menu.button`.
+    pub path: String,
+
+    pub depth: Depth,
+
+    /// The ID of the character the object was placed from, or 0 for objects created
+    /// by scripts.
+    pub character_id: CharacterId,
+
+    /// The transform relative to the parent.
+    pub matrix: Matrix,
+    pub color_transform: ColorTransform,
+    pub visible: bool,
+
+    /// The bounds on the stage, or `None` if the object draws nothing.
+    pub bounds: Option<Bounds>,
+
+    /// The current and total frames of movie clips.
+    pub frames: Option<(u16, u16)>,
+
+    /// The text of text fields.
+    pub text: Option<String>,
+
+    /// The children, from the bottom up.
+    pub children: Vec<DisplayObjectInfo>,
+}
+
+/// A rectangle on the stage, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x_min: f64,
+    pub y_min: f64,
+    pub x_max: f64,
+    pub y_max: f64,
+}
+
+impl DisplayObjectInfo {
+    pub(crate) fn new(object: DisplayObject<'_>) -> Self {
+        let kind = match object {
+            DisplayObject::Bitmap(_) => "Bitmap",
+            DisplayObject::Button(_) => "Button",
+            DisplayObject::EditText(_) => "EditText",
+            DisplayObject::Graphic(_) => "Graphic",
+            DisplayObject::MorphShape(_) => "MorphShape",
+            DisplayObject::MovieClip(_) => "MovieClip",
+            DisplayObject::Text(_) => "Text",
+            DisplayObject::Video(_) => "Video",
+        };
+        let bounds = object.world_bounds();
+        let bounds = if bounds.valid {
+            Some(Bounds {
+                x_min: bounds.x_min.to_pixels(),
+                y_min: bounds.y_min.to_pixels(),
+                x_max: bounds.x_max.to_pixels(),
+                y_max: bounds.y_max.to_pixels(),
+            })
+        } else {
+            None
+        };
+        let mut children: Vec<_> = object.children().collect();
+        children.sort_by_key(|child| child.depth());
+        Self {
+            kind,
+            name: object.name().to_string(),
+            path: object.path(),
+            depth: object.depth(),
+            character_id: object.id(),
+            matrix: *object.matrix(),
+            color_transform: *object.color_transform(),
+            visible: object.visible(),
+            bounds,
+            frames: object
+                .as_movie_clip()
+                .map(|clip| (clip.current_frame(), clip.total_frames())),
+            text: object.as_edit_text().map(|text| text.text()),
+            children: children.into_iter().map(Self::new).collect(),
+        }
+    }
+
+    /// Finds the object at the given dot path among this object and its descendants.
+    pub fn find(&self, path: &str) -> Option<&DisplayObjectInfo> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(path))
+    }
+}
+
+/// Writes the tree as indented text, one object per line.
+impl fmt::Display for DisplayObjectInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_tree(
+            info: &DisplayObjectInfo,
+            indent: usize,
+            f: &mut fmt::Formatter,
+        ) -> fmt::Result {
+            write!(
+                f,
+                "{:indent$}{} {} (id {}, depth {})",
+                "",
+                info.kind,
+                info.path,
+                info.character_id,
+                info.depth,
+                indent = indent
+            )?;
+            if let Some((current_frame, total_frames)) = info.frames {
+                write!(f, " frame {}/{}", current_frame, total_frames)?;
+            }
+            if !info.visible {
+                write!(f, " hidden")?;
+            }
+            writeln!(f)?;
+            for child in &info.children {
+                write_tree(child, indent + 2, f)?;
+            }
+            Ok(())
+        }
+
+        write_tree(self, 0, f)
+    }
+}
+
+/// An AVM1 object, with its properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectInfo {
+    /// A path that leads to the object, such as `_level0.player.inventory` or
+    /// `_global.settings`.
+    pub path: String,
+
+    /// The type of the object, as `typeof` gives it: `object`, `function` or
+    /// `movieclip`.
+    pub type_of: &'static str,
+
+    /// The names of the enumerable properties, with a short description of each
+    /// value, such as `"Alice"`, `3` or `[object]`.
+    pub properties: Vec<(String, String)>,
+}
+
+/// An activation that resolves paths as code on the timeline of `_level0` does, so
+/// that paths can start with `_root` or the name of a clip.
+fn root_activation<'a, 'gc>(
+    avm: &'a mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Option<Activation<'a, 'gc>> {
+    let root = *context.levels.get(&0)?;
+    let root_object = match root.object() {
+        Value::Object(object) => object,
+        _ => return None,
+    };
+    Some(Activation::from_timeline(
+        avm,
+        ActivationIdentifier::root("[Inspector]"),
+        context.swf.version(),
+        context.gc_context,
+        root,
+        root_object,
+    ))
+}
+
+fn object_info<'gc>(
+    path: String,
+    object: Object<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> (ObjectInfo, Vec<(String, Object<'gc>)>) {
+    let mut properties = vec![];
+    let mut children = vec![];
+    for key in object.get_keys(activation) {
+        let value = object
+            .get(&key, activation, context)
+            .unwrap_or(Value::Undefined);
+        properties.push((key.clone(), describe_value(&value)));
+        if let Value::Object(child) = value {
+            children.push((format!("{}.{}", path, key), child));
+        }
+    }
+    let info = ObjectInfo {
+        path,
+        type_of: object.type_of(),
+        properties,
+    };
+    (info, children)
+}
+
+/// Lists the objects reachable through enumerable properties from the display list
+/// and `_global`, each once, at the first path it was found at.
+pub(crate) fn avm1_objects<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> Vec<ObjectInfo> {
+    fn push_display_objects<'gc>(
+        display_object: DisplayObject<'gc>,
+        queue: &mut VecDeque<(String, Object<'gc>)>,
+    ) {
+        if let Value::Object(object) = display_object.object() {
+            queue.push_back((display_object.path(), object));
+        }
+        for child in display_object.children() {
+            push_display_objects(child, queue);
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    for level in context.levels.values() {
+        push_display_objects(*level, &mut queue);
+    }
+    queue.push_back(("_global".to_string(), avm.global_object_cell()));
+
+    let mut activation = match root_activation(avm, context) {
+        Some(activation) => activation,
+        None => return vec![],
+    };
+    let mut seen: HashSet<*const ObjectPtr> = HashSet::new();
+    let mut objects = vec![];
+    while let Some((path, object)) = queue.pop_front() {
+        if !seen.insert(object.as_ptr()) {
+            continue;
+        }
+        let (info, children) = object_info(path, object, &mut activation, context);
+        objects.push(info);
+        queue.extend(children);
+    }
+    objects
+}
+
+/// Looks up the object at a path, such as `_root.player.inventory`.
+pub(crate) fn avm1_object<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    path: &str,
+) -> Option<ObjectInfo> {
+    let mut activation = root_activation(avm, context)?;
+    match activation.get_variable(context, path) {
+        Ok(Value::Object(object)) => {
+            Some(object_info(path.to_string(), object, &mut activation, context).0)
+        }
+        _ => None,
+    }
+}
+
+/// Reads the value of a variable or property at a path, such as `_root.player.score`.
+/// Objects are given as strings, as they are to host functions.
+pub(crate) fn get_avm1_value<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    path: &str,
+) -> HostValue {
+    let mut activation = match root_activation(avm, context) {
+        Some(activation) => activation,
+        None => return HostValue::Undefined,
+    };
+    let value = match activation.get_variable(context, path) {
+        Ok(value) => value,
+        Err(_) => return HostValue::Undefined,
+    };
+    match value {
+        Value::Undefined => HostValue::Undefined,
+        Value::Null => HostValue::Null,
+        Value::Bool(value) => HostValue::Bool(value),
+        Value::Number(value) => HostValue::Number(value),
+        Value::String(value) => HostValue::String(value),
+        Value::Object(_) => match value.coerce_to_string(&mut activation, context) {
+            Ok(string) => HostValue::String(string.into_owned()),
+            Err(_) => HostValue::Undefined,
+        },
+    }
+}
+
+/// Sets a variable or property at a path, as the movie's own code would.
+pub(crate) fn set_avm1_value<'gc>(
+    avm: &mut Avm1<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    path: &str,
+    value: HostValue,
+) {
+    if let Some(mut activation) = root_activation(avm, context) {
+        if let Err(e) = activation.set_variable(context, path, value.into()) {
+            log::warn!("Couldn't set {}: {}", path, e);
+        }
+    }
+}
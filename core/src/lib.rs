@@ -9,29 +9,48 @@ extern crate smallvec;
 #[macro_use]
 extern crate downcast_rs;
 
+pub mod amf;
+mod audio_manager;
 mod avm1;
+mod avm2;
 mod bounding_box;
 mod character;
 pub mod color_transform;
 mod context;
+pub mod context_menu;
 mod drawing;
 pub mod events;
+mod focus_tracker;
 mod font;
+pub mod host;
 mod html;
+pub mod input_script;
+pub mod inspector;
 mod library;
 pub mod loader;
+mod local_connection;
+pub mod memory;
+pub mod parameters;
 mod player;
 mod prelude;
+pub mod profiler;
 mod property_map;
+#[cfg(feature = "rtmp")]
+mod rtmp;
 pub mod shape_utils;
+pub mod stage;
 pub mod string_utils;
 pub mod tag_utils;
 mod transform;
+pub mod unimplemented;
+mod video;
 mod xml;
 
 pub mod backend;
 
+pub use avm1::debugger;
+pub use avm1::disassembler;
 pub use events::PlayerEvent;
-pub use player::Player;
+pub use player::{Player, PlayerBuilder};
 pub use swf;
 pub use swf::Color;
@@ -1,10 +1,13 @@
 use crate::backend::audio::SoundHandle;
+use crate::backend::font::{self, FontBackend};
+use crate::backend::render::RenderBackend;
 use crate::character::Character;
 use crate::display_object::TDisplayObject;
 use crate::font::{Font, FontDescriptor};
 use crate::prelude::*;
-use crate::tag_utils::SwfMovie;
+use crate::tag_utils::{MovieIdentity, SwfMovie};
 use gc_arena::{Collect, MutationContext};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
 use swf::CharacterId;
@@ -19,16 +22,41 @@ pub struct MovieLibrary<'gc> {
     jpeg_tables: Option<Vec<u8>>,
     device_font: Option<Font<'gc>>,
     fonts: HashMap<FontDescriptor, Font<'gc>>,
+
+    /// The SWF version of the movie this library belongs to.
+    /// Export names are case-insensitive before SWF7.
+    swf_version: u8,
+
+    /// The number of `ImportAssets` tags whose movies are still loading.
+    pending_imports: usize,
+
+    /// The AS3 classes linked to characters by `SymbolClass` tags.
+    avm2_class_names: HashMap<CharacterId, String>,
 }
 
 impl<'gc> MovieLibrary<'gc> {
-    pub fn new() -> Self {
+    pub fn new(swf_version: u8) -> Self {
         MovieLibrary {
             characters: HashMap::new(),
             export_characters: HashMap::new(),
             jpeg_tables: None,
             device_font: None,
             fonts: HashMap::new(),
+            swf_version,
+            pending_imports: 0,
+            avm2_class_names: HashMap::new(),
+        }
+    }
+
+    /// Normalizes an export name for lookup in `export_characters`.
+    ///
+    /// Like the rest of ActionScript, linkage identifiers are matched case-insensitively in
+    /// SWF6 and below, so `attachMovie("Foo")` will find a symbol exported as `foo`.
+    fn export_key<'a>(&self, export_name: &'a str) -> Cow<'a, str> {
+        if self.swf_version < 7 {
+            Cow::Owned(export_name.to_lowercase())
+        } else {
+            Cow::Borrowed(export_name)
         }
     }
 
@@ -45,21 +73,31 @@ impl<'gc> MovieLibrary<'gc> {
         }
     }
 
+    /// Link a character to the fully-qualified name of the AS3 class that
+    /// its instances are constructed with.
+    pub fn register_avm2_class_name(&mut self, id: CharacterId, class_name: &str) {
+        self.avm2_class_names.insert(id, class_name.to_string());
+    }
+
+    /// The AS3 class linked to a character, if any.
+    pub fn avm2_class_name(&self, id: CharacterId) -> Option<&str> {
+        self.avm2_class_names.get(&id).map(|name| name.as_str())
+    }
+
     /// Registers an export name for a given character ID.
     /// This character will then be instantiable from AVM1.
+    ///
+    /// Unlike character IDs, where the first definition wins, a later `ExportAssets` tag
+    /// reusing an export name replaces the earlier export, as in Flash Player.
     pub fn register_export(&mut self, id: CharacterId, export_name: &str) {
-        use std::collections::hash_map::Entry;
         if let Some(character) = self.characters.get(&id) {
-            match self.export_characters.entry(export_name.to_string()) {
-                Entry::Vacant(e) => {
-                    e.insert(character.clone());
-                }
-                Entry::Occupied(_) => {
-                    log::warn!(
-                        "Can't register export {}: Export already exists",
-                        export_name
-                    );
-                }
+            let key = self.export_key(export_name).into_owned();
+            if self
+                .export_characters
+                .insert(key, character.clone())
+                .is_some()
+            {
+                log::info!("Export {} redefined by character ID {}", export_name, id);
             }
         } else {
             log::warn!(
@@ -70,6 +108,35 @@ impl<'gc> MovieLibrary<'gc> {
         }
     }
 
+    /// Registers a character imported from another movie by an `ImportAssets` tag.
+    ///
+    /// The character is available under the importing movie's character ID, and can also
+    /// be attached by its export name.
+    pub fn register_import(
+        &mut self,
+        id: CharacterId,
+        export_name: &str,
+        character: Character<'gc>,
+    ) {
+        self.register_character(id, character);
+        self.register_export(id, export_name);
+    }
+
+    /// Marks the start of an `ImportAssets` load.
+    pub fn begin_import(&mut self) {
+        self.pending_imports += 1;
+    }
+
+    /// Marks the end of an `ImportAssets` load, whether or not it succeeded.
+    pub fn finish_import(&mut self) {
+        self.pending_imports = self.pending_imports.saturating_sub(1);
+    }
+
+    /// Returns whether any movies imported by this movie are still loading.
+    pub fn has_pending_imports(&self) -> bool {
+        self.pending_imports > 0
+    }
+
     pub fn contains_character(&self, id: CharacterId) -> bool {
         self.characters.contains_key(&id)
     }
@@ -79,9 +146,9 @@ impl<'gc> MovieLibrary<'gc> {
         self.characters.get(&id)
     }
 
-    #[allow(dead_code)]
+    /// Returns the character exported under the given linkage identifier.
     pub fn get_character_by_export_name(&self, name: &str) -> Option<&Character<'gc>> {
-        self.export_characters.get(name)
+        self.export_characters.get(self.export_key(name).as_ref())
     }
 
     /// Instantiates the library item with the given character ID into a display object.
@@ -106,7 +173,7 @@ impl<'gc> MovieLibrary<'gc> {
         export_name: &str,
         gc_context: MutationContext<'gc, '_>,
     ) -> Result<DisplayObject<'gc>, Box<dyn std::error::Error>> {
-        if let Some(character) = self.export_characters.get(export_name) {
+        if let Some(character) = self.get_character_by_export_name(export_name) {
             self.instantiate_display_object(character, gc_context)
         } else {
             log::error!(
@@ -132,6 +199,7 @@ impl<'gc> MovieLibrary<'gc> {
             Character::MovieClip(movie_clip) => Ok(movie_clip.instantiate(gc_context)),
             Character::Button(button) => Ok(button.instantiate(gc_context)),
             Character::Text(text) => Ok(text.instantiate(gc_context)),
+            Character::Video(video) => Ok(video.instantiate(gc_context)),
             _ => Err("Not a DisplayObject".into()),
         }
     }
@@ -195,16 +263,14 @@ impl<'gc> MovieLibrary<'gc> {
     }
 }
 
-impl Default for MovieLibrary<'_> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Symbol library for multiple movies.
 pub struct Library<'gc> {
-    /// All the movie libraries.
-    movie_libraries: PtrWeakKeyHashMap<Weak<SwfMovie>, MovieLibrary<'gc>>,
+    /// All the movie libraries, shared by the versions of a movie made while it downloads.
+    movie_libraries: PtrWeakKeyHashMap<Weak<MovieIdentity>, MovieLibrary<'gc>>,
+
+    /// System fonts loaded by the font backend, keyed by the requested name and style.
+    /// `None` marks fonts that are not installed, so that they are only searched for once.
+    device_fonts: HashMap<FontDescriptor, Option<Font<'gc>>>,
 }
 
 unsafe impl<'gc> gc_arena::Collect for Library<'gc> {
@@ -213,21 +279,69 @@ unsafe impl<'gc> gc_arena::Collect for Library<'gc> {
         for (_, val) in self.movie_libraries.iter() {
             val.trace(cc);
         }
+        for font in self.device_fonts.values() {
+            font.trace(cc);
+        }
     }
 }
 
 impl<'gc> Library<'gc> {
     pub fn library_for_movie(&self, movie: Arc<SwfMovie>) -> Option<&MovieLibrary<'gc>> {
-        self.movie_libraries.get(&movie)
+        self.movie_libraries.get(movie.identity())
     }
 
     pub fn library_for_movie_mut(&mut self, movie: Arc<SwfMovie>) -> &mut MovieLibrary<'gc> {
-        if !self.movie_libraries.contains_key(&movie) {
+        let identity = movie.identity();
+        if !self.movie_libraries.contains_key(identity) {
             self.movie_libraries
-                .insert(movie.clone(), MovieLibrary::default());
+                .insert(identity.clone(), MovieLibrary::new(movie.version()));
         };
 
-        self.movie_libraries.get_mut(&movie).unwrap()
+        self.movie_libraries.get_mut(identity).unwrap()
+    }
+
+    /// Returns whether any movie is waiting on assets imported from another movie.
+    pub fn has_pending_imports(&self) -> bool {
+        self.movie_libraries
+            .values()
+            .any(|library| library.has_pending_imports())
+    }
+
+    /// Returns the system font used to render a device font with the given name and style,
+    /// loading it from the font backend on first use.
+    ///
+    /// Flash's generic `_sans`, `_serif` and `_typewriter` names are mapped to the first
+    /// matching installed family. Returns `None` if no suitable font is installed.
+    pub fn get_or_load_device_font(
+        &mut self,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+        font_backend: &mut dyn FontBackend,
+        renderer: &mut dyn RenderBackend,
+        gc_context: MutationContext<'gc, '_>,
+    ) -> Option<Font<'gc>> {
+        let descriptor = FontDescriptor::from_parts(name, is_bold, is_italic);
+        if let Some(font) = self.device_fonts.get(&descriptor) {
+            return *font;
+        }
+
+        let families = font::device_font_families(descriptor.class())
+            .map(|families| families.to_vec())
+            .unwrap_or_else(|| vec![descriptor.class()]);
+        let font = families
+            .into_iter()
+            .filter_map(|family| font_backend.load_font(family, is_bold, is_italic))
+            .next()
+            .and_then(|tag| match Font::from_swf_tag(gc_context, renderer, &tag) {
+                Ok(font) => Some(font),
+                Err(e) => {
+                    log::warn!("Unable to load device font \"{}\": {}", tag.name, e);
+                    None
+                }
+            });
+        self.device_fonts.insert(descriptor, font);
+        font
     }
 }
 
@@ -235,6 +349,74 @@ impl<'gc> Default for Library<'gc> {
     fn default() -> Self {
         Self {
             movie_libraries: PtrWeakKeyHashMap::new(),
+            device_fonts: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generational_arena::Index;
+
+    fn sound(n: usize) -> Character<'static> {
+        Character::Sound(Index::from_raw_parts(n, 0))
+    }
+
+    fn exported_sound(library: &MovieLibrary<'_>, name: &str) -> Option<usize> {
+        match library.get_character_by_export_name(name) {
+            Some(Character::Sound(handle)) => Some(handle.into_raw_parts().0),
+            _ => None,
         }
     }
+
+    #[test]
+    fn export_names_case_insensitive_before_swf7() {
+        let mut library = MovieLibrary::new(6);
+        library.register_character(1, sound(1));
+        library.register_export(1, "MySound");
+        assert_eq!(exported_sound(&library, "MySound"), Some(1));
+        assert_eq!(exported_sound(&library, "mysound"), Some(1));
+        assert_eq!(exported_sound(&library, "MYSOUND"), Some(1));
+    }
+
+    #[test]
+    fn export_names_case_sensitive_in_swf7() {
+        let mut library = MovieLibrary::new(7);
+        library.register_character(1, sound(1));
+        library.register_export(1, "MySound");
+        assert_eq!(exported_sound(&library, "MySound"), Some(1));
+        assert_eq!(exported_sound(&library, "mysound"), None);
+    }
+
+    #[test]
+    fn export_name_last_wins() {
+        let mut library = MovieLibrary::new(6);
+        library.register_character(1, sound(1));
+        library.register_character(2, sound(2));
+        library.register_export(1, "snd");
+        library.register_export(2, "SND");
+        assert_eq!(exported_sound(&library, "snd"), Some(2));
+    }
+
+    #[test]
+    fn character_id_first_wins() {
+        let mut library = MovieLibrary::new(8);
+        library.register_character(1, sound(1));
+        library.register_character(1, sound(2));
+        library.register_export(1, "snd");
+        assert_eq!(exported_sound(&library, "snd"), Some(1));
+    }
+
+    #[test]
+    fn imports_are_attachable_by_name() {
+        let mut library = MovieLibrary::new(8);
+        library.begin_import();
+        assert!(library.has_pending_imports());
+        library.register_import(5, "Imported", sound(1));
+        library.finish_import();
+        assert!(!library.has_pending_imports());
+        assert!(library.contains_character(5));
+        assert_eq!(exported_sound(&library, "Imported"), Some(1));
+    }
 }
@@ -1,17 +1,28 @@
 //! Management of async loaders
 
+use crate::amf::remoting::{Envelope, ResponseKind};
 use crate::avm1::activation::{Activation, ActivationIdentifier};
-use crate::avm1::{Object, TObject, Value};
+use crate::avm1::{Object, ScriptObject, TObject, Value};
 use crate::backend::navigator::OwnedFuture;
 use crate::context::{ActionQueue, ActionType};
-use crate::display_object::{DisplayObject, MorphShape, TDisplayObject};
+use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
+use crate::events::ClipEvent;
 use crate::player::{Player, NEWEST_PLAYER_VERSION};
 use crate::tag_utils::SwfMovie;
 use crate::xml::XMLNode;
+use enumset::EnumSet;
 use gc_arena::{Collect, CollectionContext};
 use generational_arena::{Arena, Index};
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
 use std::string::FromUtf8Error;
 use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 use thiserror::Error;
 use url::form_urlencoded;
 
@@ -22,6 +33,9 @@ pub enum Error {
     #[error("Load cancelled")]
     Cancelled,
 
+    #[error("Load timed out")]
+    TimedOut,
+
     #[error("Non-movie loader spawned as movie loader")]
     NotMovieLoader,
 
@@ -31,6 +45,15 @@ pub enum Error {
     #[error("Non-XML loader spawned as XML loader")]
     NotXmlLoader,
 
+    #[error("Non-LoadVars loader spawned as LoadVars loader")]
+    NotLoadVarsLoader,
+
+    #[error("Non-import loader spawned as import loader")]
+    NotImportLoader,
+
+    #[error("Non-remoting loader spawned as remoting loader")]
+    NotRemotingLoader,
+
     #[error("Invalid SWF")]
     InvalidSwf(#[from] crate::tag_utils::Error),
 
@@ -40,6 +63,9 @@ pub enum Error {
     #[error("Network error")]
     NetworkError(#[from] std::io::Error),
 
+    #[error("HTTP status {0}")]
+    HttpNotOk(u16),
+
     // TODO: We can't support lifetimes on this error object yet (or we'll need some backends inside
     // the GC arena). We're losing info here. How do we fix that?
     #[error("Error running avm1 script: {0}")]
@@ -52,12 +78,50 @@ impl From<crate::avm1::error::Error<'_>> for Error {
     }
 }
 
+/// The HTTP status reported to `onHTTPStatus` for the result of a fetch.
+///
+/// Like Flash Player, this is 0 when no response was received at all, such as
+/// when the server couldn't be reached.
+fn http_status(result: &Result<Vec<u8>, Error>) -> u16 {
+    match result {
+        Ok(_) => 200,
+        Err(Error::HttpNotOk(status)) => *status,
+        Err(Error::NetworkError(error)) if error.kind() == std::io::ErrorKind::NotFound => 404,
+        Err(_) => 0,
+    }
+}
+
+/// How soon the fetch of a load starts when many loads are waiting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoadPriority {
+    /// Loads of data, and of movies into clips.
+    Normal,
+
+    /// Loads of movies into levels, and of the movies they import characters
+    /// from, which hold up the movie until they are done.
+    High,
+}
+
+/// The most fetches that run at once by default, like the connection limit of browsers.
+const DEFAULT_MAX_CONNECTIONS: usize = 6;
+
 /// Holds all in-progress loads for the player.
-pub struct LoadManager<'gc>(Arena<Loader<'gc>>);
+pub struct LoadManager<'gc> {
+    loaders: Arena<Loader<'gc>>,
+
+    /// The fetch of each loader, which can be cancelled or time out.
+    fetches: HashMap<Handle, Rc<RefCell<FetchState>>>,
+
+    /// Decides which fetches run.
+    scheduler: Rc<RefCell<FetchScheduler>>,
+
+    /// How long a fetch may run before it fails. `None` means forever.
+    timeout: Option<Duration>,
+}
 
 unsafe impl<'gc> Collect for LoadManager<'gc> {
     fn trace(&self, cc: CollectionContext) {
-        for (_, loader) in self.0.iter() {
+        for (_, loader) in self.loaders.iter() {
             loader.trace(cc)
         }
     }
@@ -66,7 +130,105 @@ unsafe impl<'gc> Collect for LoadManager<'gc> {
 impl<'gc> LoadManager<'gc> {
     /// Construct a new `LoadManager`.
     pub fn new() -> Self {
-        Self(Arena::new())
+        Self {
+            loaders: Arena::new(),
+            fetches: HashMap::new(),
+            scheduler: Rc::new(RefCell::new(FetchScheduler::new())),
+            timeout: None,
+        }
+    }
+
+    /// Set how long fetches may run before they fail. `None` lets them run forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Set the most fetches that may run at once. Other fetches wait for them to finish.
+    pub fn set_max_connections(&mut self, max_connections: usize) {
+        let mut scheduler = self.scheduler.borrow_mut();
+        scheduler.max_active = max_connections.max(1);
+        scheduler.wake_next();
+    }
+
+    /// Limit the speed of fetches to the given number of bytes per second, to test
+    /// preloaders like the "Simulate Download" option of the Flash IDE. Each fetch
+    /// gets the full bandwidth, and its data is held back until it would have been
+    /// downloaded at that speed. `None` removes the limit.
+    pub fn set_bandwidth_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.scheduler.borrow_mut().bandwidth = bytes_per_second.map(|b| b.max(1));
+    }
+
+    /// Wrap the fetch of a loader, so that it waits its turn to start, and can be
+    /// cancelled or time out.
+    fn manage_fetch(
+        &mut self,
+        handle: Handle,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+        priority: LoadPriority,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        let state = self.scheduler.borrow_mut().add(priority);
+        self.fetches.insert(handle, Rc::clone(&state));
+        Box::pin(ManagedFetch {
+            fetch,
+            state,
+            scheduler: Rc::clone(&self.scheduler),
+            data: None,
+        })
+    }
+
+    /// Cancel a load. Its fetch is stopped, and none of its events fire.
+    pub fn cancel(&mut self, handle: Handle) {
+        self.loaders.remove(handle);
+        if let Some(state) = self.fetches.remove(&handle) {
+            state.borrow_mut().stop(StopReason::Cancelled);
+        }
+    }
+
+    /// Cancel the unfinished loads of movies into the given clip, such as when the
+    /// clip is unloaded.
+    pub fn cancel_loads_into(&mut self, clip: DisplayObject<'gc>) {
+        let handles: Vec<_> = self
+            .loaders
+            .iter()
+            .filter_map(|(handle, loader)| match loader {
+                Loader::Movie {
+                    target_clip,
+                    load_complete: false,
+                    ..
+                } if DisplayObject::ptr_eq(*target_clip, clip) => Some(handle),
+                _ => None,
+            })
+            .collect();
+        for handle in handles {
+            self.cancel(handle);
+        }
+    }
+
+    /// Time out fetches that ran for too long, and release the data of throttled
+    /// fetches once it would have been downloaded. This should be called every frame.
+    pub fn update(&mut self, now: Duration) {
+        let timeout = self.timeout;
+        let bandwidth = self.scheduler.borrow().bandwidth;
+        self.fetches.retain(|_, state| {
+            let mut state = state.borrow_mut();
+            if let FetchPhase::Active { started_at } = &mut state.phase {
+                // Fetches are timed from the first frame they were seen running.
+                let started_at = *started_at.get_or_insert(now);
+                let elapsed = now.checked_sub(started_at).unwrap_or_default();
+                if let Some(received) = state.received {
+                    let download_time = bandwidth.map_or(Duration::default(), |bandwidth| {
+                        Duration::from_secs_f64(received as f64 / bandwidth as f64)
+                    });
+                    if elapsed >= download_time {
+                        state.release();
+                    }
+                }
+                if timeout.map_or(false, |timeout| elapsed >= timeout) {
+                    state.stop(StopReason::TimedOut);
+                }
+            }
+            state.phase != FetchPhase::Finished
+        });
     }
 
     /// Add a new loader to the `LoadManager`.
@@ -76,8 +238,8 @@ impl<'gc> LoadManager<'gc> {
     /// finishes, the handle will be invalidated (and the underlying loader
     /// deleted).
     pub fn add_loader(&mut self, loader: Loader<'gc>) -> Handle {
-        let handle = self.0.insert(loader);
-        self.0
+        let handle = self.loaders.insert(loader);
+        self.loaders
             .get_mut(handle)
             .unwrap()
             .introduce_loader_handle(handle);
@@ -87,12 +249,12 @@ impl<'gc> LoadManager<'gc> {
 
     /// Retrieve a loader by handle.
     pub fn get_loader(&self, handle: Handle) -> Option<&Loader<'gc>> {
-        self.0.get(handle)
+        self.loaders.get(handle)
     }
 
     /// Retrieve a loader by handle for mutation.
     pub fn get_loader_mut(&mut self, handle: Handle) -> Option<&mut Loader<'gc>> {
-        self.0.get_mut(handle)
+        self.loaders.get_mut(handle)
     }
 
     /// Kick off a movie clip load.
@@ -103,8 +265,18 @@ impl<'gc> LoadManager<'gc> {
         player: Weak<Mutex<Player>>,
         target_clip: DisplayObject<'gc>,
         fetch: OwnedFuture<Vec<u8>, Error>,
+        url: String,
         target_broadcaster: Option<Object<'gc>>,
     ) -> OwnedFuture<(), Error> {
+        // A new load into a clip replaces any load already in progress.
+        self.cancel_loads_into(target_clip);
+
+        // Levels are loaded first, as the clips inside them depend on them.
+        let priority = if target_clip.parent().is_none() {
+            LoadPriority::High
+        } else {
+            LoadPriority::Normal
+        };
         let loader = Loader::Movie {
             self_handle: None,
             target_clip,
@@ -112,11 +284,12 @@ impl<'gc> LoadManager<'gc> {
             load_complete: false,
         };
         let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, priority);
 
         let loader = self.get_loader_mut(handle).unwrap();
         loader.introduce_loader_handle(handle);
 
-        loader.movie_loader(player, fetch)
+        loader.movie_loader(player, fetch, url)
     }
 
     /// Indicates that a movie clip has initialized (ran it's first frame).
@@ -130,14 +303,14 @@ impl<'gc> LoadManager<'gc> {
     ) {
         let mut invalidated_loaders = vec![];
 
-        for (index, loader) in self.0.iter_mut() {
+        for (index, loader) in self.loaders.iter_mut() {
             if loader.movie_clip_loaded(loaded_clip, clip_object, queue) {
                 invalidated_loaders.push(index);
             }
         }
 
         for index in invalidated_loaders {
-            self.0.remove(index);
+            self.loaders.remove(index);
         }
     }
 
@@ -155,6 +328,7 @@ impl<'gc> LoadManager<'gc> {
             target_object,
         };
         let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, LoadPriority::Normal);
 
         let loader = self.get_loader_mut(handle).unwrap();
         loader.introduce_loader_handle(handle);
@@ -178,12 +352,89 @@ impl<'gc> LoadManager<'gc> {
             target_node,
         };
         let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, LoadPriority::Normal);
 
         let loader = self.get_loader_mut(handle).unwrap();
         loader.introduce_loader_handle(handle);
 
         loader.xml_loader(player, fetch)
     }
+
+    /// Kick off a load of variables into a `LoadVars` object.
+    ///
+    /// Returns the loader's async process, which you will need to spawn.
+    pub fn load_vars_into_object(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        target_object: Object<'gc>,
+        active_clip: DisplayObject<'gc>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let loader = Loader::LoadVars {
+            self_handle: None,
+            active_clip,
+            target_object,
+        };
+        let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, LoadPriority::Normal);
+
+        let loader = self.get_loader_mut(handle).unwrap();
+        loader.introduce_loader_handle(handle);
+
+        loader.load_vars_loader(player, fetch)
+    }
+
+    /// Kick off a load of the movie that an `ImportAssets` tag imports characters from.
+    ///
+    /// Returns the loader's async process, which you will need to spawn.
+    pub fn load_imported_assets(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        importing_movie: Arc<SwfMovie>,
+        imports: Vec<swf::ExportedAsset>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let loader = Loader::ImportAssets {
+            self_handle: None,
+            importing_movie,
+            imports,
+        };
+        let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, LoadPriority::High);
+
+        let loader = self.get_loader_mut(handle).unwrap();
+        loader.introduce_loader_handle(handle);
+
+        loader.import_assets_loader(player, fetch)
+    }
+
+    /// Kick off a Flash Remoting call.
+    ///
+    /// Returns the loader's async process, which you will need to spawn.
+    pub fn load_remoting_call(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        connection: Object<'gc>,
+        responder: Option<Object<'gc>>,
+        response_uri: String,
+        active_clip: DisplayObject<'gc>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let loader = Loader::Remoting {
+            self_handle: None,
+            active_clip,
+            connection,
+            responder,
+            response_uri,
+        };
+        let handle = self.add_loader(loader);
+        let fetch = self.manage_fetch(handle, fetch, LoadPriority::Normal);
+
+        let loader = self.get_loader_mut(handle).unwrap();
+        loader.introduce_loader_handle(handle);
+
+        loader.remoting_loader(player, fetch)
+    }
 }
 
 impl<'gc> Default for LoadManager<'gc> {
@@ -192,6 +443,232 @@ impl<'gc> Default for LoadManager<'gc> {
     }
 }
 
+/// Why a fetch was stopped before it completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StopReason {
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FetchPhase {
+    /// Waiting for other fetches to finish before starting.
+    Waiting,
+
+    /// Running. `started_at` is the time the `LoadManager` first saw it running.
+    Active { started_at: Option<Duration> },
+
+    /// Completed, failed or stopped.
+    Finished,
+}
+
+/// The state of a fetch, shared between the `LoadManager` and the fetch's future.
+struct FetchState {
+    priority: LoadPriority,
+
+    /// Orders the fetches of the same priority by when they were made.
+    sequence: u64,
+
+    phase: FetchPhase,
+
+    stopped: Option<StopReason>,
+
+    /// The size of the data of a throttled fetch, which has completed but is held
+    /// back until it would have been downloaded.
+    received: Option<usize>,
+
+    /// Whether the held back data of a throttled fetch may be delivered.
+    released: bool,
+
+    /// Wakes the task running the fetch, so that it sees that it was stopped or
+    /// can start.
+    waker: Option<Waker>,
+}
+
+impl FetchState {
+    fn release(&mut self) {
+        if !self.released {
+            self.released = true;
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn stop(&mut self, reason: StopReason) {
+        if self.stopped.is_none() && self.phase != FetchPhase::Finished {
+            self.stopped = Some(reason);
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Limits the number of fetches running at once, and starts the waiting fetches
+/// in order of priority.
+struct FetchScheduler {
+    active: usize,
+    max_active: usize,
+
+    /// The simulated download speed in bytes per second, if fetches are throttled.
+    bandwidth: Option<u64>,
+
+    next_sequence: u64,
+    waiting: Vec<Rc<RefCell<FetchState>>>,
+}
+
+impl FetchScheduler {
+    fn new() -> Self {
+        Self {
+            active: 0,
+            max_active: DEFAULT_MAX_CONNECTIONS,
+            bandwidth: None,
+            next_sequence: 0,
+            waiting: vec![],
+        }
+    }
+
+    fn add(&mut self, priority: LoadPriority) -> Rc<RefCell<FetchState>> {
+        let state = Rc::new(RefCell::new(FetchState {
+            priority,
+            sequence: self.next_sequence,
+            phase: FetchPhase::Waiting,
+            stopped: None,
+            received: None,
+            released: false,
+            waker: None,
+        }));
+        self.next_sequence += 1;
+        self.waiting.push(Rc::clone(&state));
+        state
+    }
+
+    /// The waiting fetch that should start next.
+    fn next(&self) -> Option<&Rc<RefCell<FetchState>>> {
+        self.waiting.iter().max_by_key(|state| {
+            let state = state.borrow();
+            (state.priority, Reverse(state.sequence))
+        })
+    }
+
+    /// Start the given fetch if it is next in line and there is room for it.
+    fn try_start(&mut self, state: &Rc<RefCell<FetchState>>) -> bool {
+        if self.active >= self.max_active {
+            return false;
+        }
+        match self.next() {
+            Some(next) if Rc::ptr_eq(next, state) => (),
+            _ => return false,
+        }
+        self.waiting.retain(|waiting| !Rc::ptr_eq(waiting, state));
+        self.active += 1;
+        self.wake_next();
+        true
+    }
+
+    /// Forget a fetch that is done, and wake the next waiting fetch.
+    fn finish(&mut self, state: &Rc<RefCell<FetchState>>, was_active: bool) {
+        if was_active {
+            self.active -= 1;
+        } else {
+            self.waiting.retain(|waiting| !Rc::ptr_eq(waiting, state));
+        }
+        self.wake_next();
+    }
+
+    /// Wake the next waiting fetch if there is room for it to start.
+    fn wake_next(&self) {
+        if self.active < self.max_active {
+            if let Some(next) = self.next() {
+                if let Some(waker) = next.borrow_mut().waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+/// A fetch managed by the `LoadManager`.
+struct ManagedFetch {
+    fetch: OwnedFuture<Vec<u8>, Error>,
+    state: Rc<RefCell<FetchState>>,
+    scheduler: Rc<RefCell<FetchScheduler>>,
+
+    /// The data of a throttled fetch, held back until it is released.
+    data: Option<Vec<u8>>,
+}
+
+impl ManagedFetch {
+    fn finish(&mut self) {
+        let was_active = match self.state.borrow().phase {
+            FetchPhase::Waiting => false,
+            FetchPhase::Active { .. } => true,
+            FetchPhase::Finished => return,
+        };
+        self.state.borrow_mut().phase = FetchPhase::Finished;
+        self.scheduler.borrow_mut().finish(&self.state, was_active);
+    }
+}
+
+impl Future for ManagedFetch {
+    type Output = Result<Vec<u8>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let stopped = this.state.borrow().stopped;
+        if let Some(reason) = stopped {
+            this.finish();
+            return Poll::Ready(Err(match reason {
+                StopReason::Cancelled => Error::Cancelled,
+                StopReason::TimedOut => Error::TimedOut,
+            }));
+        }
+
+        if this.data.is_some() {
+            if this.state.borrow().released {
+                this.finish();
+                return Poll::Ready(Ok(this.data.take().unwrap_or_default()));
+            }
+            this.state.borrow_mut().waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        if this.state.borrow().phase == FetchPhase::Waiting {
+            if !this.scheduler.borrow_mut().try_start(&this.state) {
+                this.state.borrow_mut().waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            this.state.borrow_mut().phase = FetchPhase::Active { started_at: None };
+        }
+
+        match this.fetch.as_mut().poll(cx) {
+            Poll::Ready(Ok(data)) if this.scheduler.borrow().bandwidth.is_some() => {
+                let mut state = this.state.borrow_mut();
+                state.received = Some(data.len());
+                state.waker = Some(cx.waker().clone());
+                this.data = Some(data);
+                Poll::Pending
+            }
+            Poll::Ready(result) => {
+                this.finish();
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                this.state.borrow_mut().waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for ManagedFetch {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
 /// A struct that holds garbage-collected pointers for asynchronous code.
 pub enum Loader<'gc> {
     /// Loader that is loading a new movie into a movieclip.
@@ -242,6 +719,52 @@ pub enum Loader<'gc> {
         /// The target node whose contents will be replaced with the parsed XML.
         target_node: XMLNode<'gc>,
     },
+
+    /// Loader that is loading variables into a `LoadVars` object.
+    LoadVars {
+        /// The handle to refer to this loader instance.
+        self_handle: Option<Handle>,
+
+        /// The active movie clip at the time of load invocation.
+        active_clip: DisplayObject<'gc>,
+
+        /// The `LoadVars` object that receives the `onHTTPStatus` and `onData`
+        /// callbacks.
+        target_object: Object<'gc>,
+    },
+
+    /// Loader that is loading a movie to import characters from.
+    ImportAssets {
+        /// The handle to refer to this loader instance.
+        self_handle: Option<Handle>,
+
+        /// The movie whose `ImportAssets` tag started this load.
+        importing_movie: Arc<SwfMovie>,
+
+        /// The characters to import, by their character ID in the importing movie and
+        /// their export name in the imported movie.
+        imports: Vec<swf::ExportedAsset>,
+    },
+
+    /// Loader that is waiting for the response to a Flash Remoting call.
+    Remoting {
+        /// The handle to refer to this loader instance.
+        self_handle: Option<Handle>,
+
+        /// The active movie clip at the time of the call.
+        active_clip: DisplayObject<'gc>,
+
+        /// The `NetConnection` that made the call, which is told when the
+        /// call fails.
+        connection: Object<'gc>,
+
+        /// The object whose `onResult` or `onStatus` method receives the
+        /// response.
+        responder: Option<Object<'gc>>,
+
+        /// The response URI of the call, which identifies its response.
+        response_uri: String,
+    },
 }
 
 unsafe impl<'gc> Collect for Loader<'gc> {
@@ -257,6 +780,25 @@ unsafe impl<'gc> Collect for Loader<'gc> {
             }
             Loader::Form { target_object, .. } => target_object.trace(cc),
             Loader::XML { target_node, .. } => target_node.trace(cc),
+            Loader::LoadVars {
+                active_clip,
+                target_object,
+                ..
+            } => {
+                active_clip.trace(cc);
+                target_object.trace(cc);
+            }
+            Loader::ImportAssets { .. } => {}
+            Loader::Remoting {
+                active_clip,
+                connection,
+                responder,
+                ..
+            } => {
+                active_clip.trace(cc);
+                connection.trace(cc);
+                responder.trace(cc);
+            }
         }
     }
 }
@@ -271,6 +813,9 @@ impl<'gc> Loader<'gc> {
             Loader::Movie { self_handle, .. } => *self_handle = Some(handle),
             Loader::Form { self_handle, .. } => *self_handle = Some(handle),
             Loader::XML { self_handle, .. } => *self_handle = Some(handle),
+            Loader::LoadVars { self_handle, .. } => *self_handle = Some(handle),
+            Loader::ImportAssets { self_handle, .. } => *self_handle = Some(handle),
+            Loader::Remoting { self_handle, .. } => *self_handle = Some(handle),
         }
     }
 
@@ -285,6 +830,7 @@ impl<'gc> Loader<'gc> {
         &mut self,
         player: Weak<Mutex<Player>>,
         fetch: OwnedFuture<Vec<u8>, Error>,
+        url: String,
     ) -> OwnedFuture<(), Error> {
         let handle = match self {
             Loader::Movie { self_handle, .. } => self_handle.expect("Loader not self-introduced"),
@@ -329,7 +875,10 @@ impl<'gc> Loader<'gc> {
                 },
             )?;
 
-            let data = (fetch.await).and_then(|data| Ok((data.len(), SwfMovie::from_data(&data)?)));
+            let data = fetch.await;
+            let status = http_status(&data);
+            let data =
+                data.and_then(|data| Ok((data.len(), SwfMovie::from_data(&data, Some(url))?)));
             if let Ok((length, movie)) = data {
                 let movie = Arc::new(movie);
 
@@ -374,8 +923,7 @@ impl<'gc> Loader<'gc> {
                         mc.preload(avm, uc, &mut morph_shapes);
 
                         // Finalize morph shapes.
-                        for (id, static_data) in morph_shapes {
-                            let morph_shape = MorphShape::new(uc.gc_context, static_data);
+                        for (id, morph_shape) in morph_shapes {
                             uc.library
                                 .library_for_movie_mut(movie.clone())
                                 .register_character(
@@ -391,10 +939,16 @@ impl<'gc> Loader<'gc> {
                                 NEWEST_PLAYER_VERSION,
                                 uc,
                                 "broadcastMessage",
-                                &["onLoadComplete".into(), Value::Object(broadcaster)],
+                                &[
+                                    "onLoadComplete".into(),
+                                    Value::Object(broadcaster),
+                                    status.into(),
+                                ],
                             );
                         }
 
+                        mc.run_clip_event(uc, ClipEvent::Data);
+
                         if let Some(Loader::Movie { load_complete, .. }) =
                             uc.load_manager.get_loader_mut(handle)
                         {
@@ -432,6 +986,7 @@ impl<'gc> Loader<'gc> {
                                     "onLoadError".into(),
                                     Value::Object(broadcaster),
                                     "LoadNeverCompleted".into(),
+                                    status.into(),
                                 ],
                             );
                         }
@@ -486,6 +1041,102 @@ impl<'gc> Loader<'gc> {
                     that.set(&k, v.into_owned().into(), &mut activation, uc)?;
                 }
 
+                // Movie clips receiving variables fire their `data` clip event.
+                if let Some(clip) = that.as_display_object().and_then(|o| o.as_movie_clip()) {
+                    clip.run_clip_event(uc, ClipEvent::Data);
+                }
+
+                Ok(())
+            })
+        })
+    }
+
+    /// Construct a future for the given import loader.
+    ///
+    /// The imported movie is preloaded into its own library, and its exported characters
+    /// are then registered in the importing movie's library.
+    pub fn import_assets_loader(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let (handle, importing_movie) = match self {
+            Loader::ImportAssets {
+                self_handle,
+                importing_movie,
+                ..
+            } => (
+                self_handle.expect("Loader not self-introduced"),
+                importing_movie.clone(),
+            ),
+            _ => return Box::pin(async { Err(Error::NotImportLoader) }),
+        };
+
+        let player = player
+            .upgrade()
+            .expect("Could not upgrade weak reference to player");
+
+        Box::pin(async move {
+            let data = (fetch.await).and_then(|data| Ok(SwfMovie::from_data(&data, None)?));
+
+            player.lock().unwrap().update(|avm, uc| {
+                // The importing movie waits for this load however it ends, even if cancelled.
+                let imports = match uc.load_manager.get_loader(handle) {
+                    Some(Loader::ImportAssets { imports, .. }) => imports.clone(),
+                    None => {
+                        uc.library
+                            .library_for_movie_mut(importing_movie)
+                            .finish_import();
+                        return Err(Error::Cancelled);
+                    }
+                    _ => unreachable!(),
+                };
+
+                let movie = match data {
+                    Ok(movie) => Arc::new(movie),
+                    Err(e) => {
+                        log::warn!("Unable to load imported assets: {}", e);
+                        uc.library
+                            .library_for_movie_mut(importing_movie)
+                            .finish_import();
+                        return Ok(());
+                    }
+                };
+
+                // Preload the imported movie to define its characters and exports.
+                let clip = MovieClip::from_movie(uc.gc_context, movie.clone());
+                let mut morph_shapes = fnv::FnvHashMap::default();
+                clip.preload(avm, uc, &mut morph_shapes);
+
+                // Finalize morph shapes.
+                for (id, morph_shape) in morph_shapes {
+                    uc.library
+                        .library_for_movie_mut(movie.clone())
+                        .register_character(
+                            id,
+                            crate::character::Character::MorphShape(morph_shape),
+                        );
+                }
+
+                let exporting_library = uc.library.library_for_movie_mut(movie);
+                let characters: Vec<_> = imports
+                    .iter()
+                    .filter_map(|import| {
+                        let character =
+                            exporting_library.get_character_by_export_name(&import.name);
+                        if character.is_none() {
+                            log::warn!("Imported movie does not export {}", import.name);
+                        }
+                        character.map(|character| (import, character.clone()))
+                    })
+                    .collect();
+
+                let importing_library = uc.library.library_for_movie_mut(importing_movie);
+                for (import, character) in characters {
+                    importing_library.register_import(import.id, &import.name, character);
+                }
+                importing_library.finish_import();
+
                 Ok(())
             })
         })
@@ -550,6 +1201,7 @@ impl<'gc> Loader<'gc> {
 
         Box::pin(async move {
             let data = fetch.await;
+            let status = http_status(&data);
             if let Ok(data) = data {
                 let xmlstring = String::from_utf8(data)?;
 
@@ -573,7 +1225,7 @@ impl<'gc> Loader<'gc> {
                             NEWEST_PLAYER_VERSION,
                             uc,
                             "onHTTPStatus",
-                            &[200.into()],
+                            &[status.into()],
                         );
 
                         avm.run_stack_frame_for_method(
@@ -609,7 +1261,7 @@ impl<'gc> Loader<'gc> {
                             NEWEST_PLAYER_VERSION,
                             uc,
                             "onHTTPStatus",
-                            &[404.into()],
+                            &[status.into()],
                         );
 
                         avm.run_stack_frame_for_method(
@@ -629,4 +1281,189 @@ impl<'gc> Loader<'gc> {
             Ok(())
         })
     }
+
+    /// Construct a future for the given `LoadVars` loader.
+    ///
+    /// The object receives `onHTTPStatus` with the status of the response, then
+    /// `onData` with the raw response, or `undefined` if the load failed. The
+    /// default `onData` decodes the variables and calls `onLoad`.
+    pub fn load_vars_loader(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let handle = match self {
+            Loader::LoadVars { self_handle, .. } => {
+                self_handle.expect("Loader not self-introduced")
+            }
+            _ => return Box::pin(async { Err(Error::NotLoadVarsLoader) }),
+        };
+
+        let player = player
+            .upgrade()
+            .expect("Could not upgrade weak reference to player");
+
+        Box::pin(async move {
+            let data = fetch.await;
+            let status = http_status(&data);
+            let src = data
+                .ok()
+                .map(|data| String::from_utf8_lossy(&data).into_owned());
+
+            player
+                .lock()
+                .expect("Could not lock player!!")
+                .update(|avm, uc| -> Result<(), Error> {
+                    let (object, active_clip) = match uc.load_manager.get_loader(handle) {
+                        Some(Loader::LoadVars {
+                            target_object,
+                            active_clip,
+                            ..
+                        }) => (*target_object, *active_clip),
+                        None => return Err(Error::Cancelled),
+                        _ => unreachable!(),
+                    };
+
+                    avm.run_stack_frame_for_method(
+                        active_clip,
+                        object,
+                        NEWEST_PLAYER_VERSION,
+                        uc,
+                        "onHTTPStatus",
+                        &[status.into()],
+                    );
+
+                    avm.run_stack_frame_for_method(
+                        active_clip,
+                        object,
+                        NEWEST_PLAYER_VERSION,
+                        uc,
+                        "onData",
+                        &[src.map_or(Value::Undefined, Value::from)],
+                    );
+
+                    Ok(())
+                })
+        })
+    }
+
+    /// Construct a future for the given remoting call.
+    ///
+    /// The given future should be passed immediately to an executor; it will
+    /// take responsibility for running the loader to completion.
+    ///
+    /// If the loader is not a remoting call then the returned future will
+    /// yield an error immediately once spawned.
+    pub fn remoting_loader(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let handle = match self {
+            Loader::Remoting { self_handle, .. } => {
+                self_handle.expect("Loader not self-introduced")
+            }
+            _ => return Box::pin(async { Err(Error::NotRemotingLoader) }),
+        };
+
+        let player = player
+            .upgrade()
+            .expect("Could not upgrade weak reference to player");
+
+        Box::pin(async move {
+            let response = fetch.await.map(|data| Envelope::decode(&data));
+
+            player
+                .lock()
+                .expect("Could not lock player!!")
+                .update(|avm, uc| -> Result<(), Error> {
+                    let (active_clip, connection, responder, response_uri) =
+                        match uc.load_manager.get_loader(handle) {
+                            Some(Loader::Remoting {
+                                active_clip,
+                                connection,
+                                responder,
+                                response_uri,
+                                ..
+                            }) => (*active_clip, *connection, *responder, response_uri.clone()),
+                            None => return Err(Error::Cancelled),
+                            _ => unreachable!(),
+                        };
+
+                    let status_code = match response {
+                        Ok(Ok(envelope)) => {
+                            let response = envelope.messages.iter().find_map(|message| {
+                                message
+                                    .response_to(&response_uri)
+                                    .map(|kind| (kind, &message.value))
+                            });
+                            let (kind, value) = match response {
+                                Some(response) => response,
+                                None => return Ok(()),
+                            };
+
+                            let mut activation = Activation::from_nothing(
+                                avm,
+                                ActivationIdentifier::root("[NetConnection]"),
+                                NEWEST_PLAYER_VERSION,
+                                avm.global_object_cell(),
+                                uc.gc_context,
+                                active_clip,
+                            );
+                            let value = crate::avm1::amf::deserialize(
+                                &mut activation,
+                                uc,
+                                &envelope.graph,
+                                value,
+                            );
+
+                            // Statuses of calls without a responder go to
+                            // the connection instead.
+                            match (kind, responder) {
+                                (ResponseKind::Result, Some(responder)) => {
+                                    responder.call_method(
+                                        "onResult",
+                                        &[value],
+                                        &mut activation,
+                                        uc,
+                                    )?;
+                                }
+                                (ResponseKind::Status, responder) => {
+                                    responder.unwrap_or(connection).call_method(
+                                        "onStatus",
+                                        &[value],
+                                        &mut activation,
+                                        uc,
+                                    )?;
+                                }
+                                (ResponseKind::Result, None) => {}
+                            }
+                            return Ok(());
+                        }
+                        Ok(Err(e)) => {
+                            log::warn!("Invalid Flash Remoting response: {}", e);
+                            "NetConnection.Call.BadVersion"
+                        }
+                        Err(e) => {
+                            log::warn!("Flash Remoting call failed: {}", e);
+                            "NetConnection.Call.Failed"
+                        }
+                    };
+
+                    let info = ScriptObject::object(uc.gc_context, Some(avm.prototypes().object));
+                    info.define_value(uc.gc_context, "level", "error".into(), EnumSet::empty());
+                    info.define_value(uc.gc_context, "code", status_code.into(), EnumSet::empty());
+                    avm.run_stack_frame_for_method(
+                        active_clip,
+                        connection,
+                        NEWEST_PLAYER_VERSION,
+                        uc,
+                        "onStatus",
+                        &[info.into()],
+                    );
+
+                    Ok(())
+                })
+        })
+    }
 }
@@ -0,0 +1,304 @@
+//! `LocalConnection` message passing between movies.
+//!
+//! Movies listen on named connections and send messages to connections that
+//! other movies opened. Both VMs share the same connections, which is the only
+//! way for an AVM1 movie to talk to an AVM2 movie that it loaded, or the other
+//! way around.
+
+use crate::amf::{self, AmfError, Graph};
+use crate::avm1::activation::{Activation as Avm1Activation, ActivationIdentifier};
+use crate::avm1::{self, Avm1};
+use crate::avm2::activation::Activation as Avm2Activation;
+use crate::avm2::names::QName;
+use crate::avm2::{self, TObject as _};
+use crate::context::UpdateContext;
+use gc_arena::Collect;
+use std::collections::HashMap;
+
+/// The methods of `LocalConnection` itself, which can't be called remotely.
+const RESERVED_METHODS: &[&str] = &[
+    "send",
+    "connect",
+    "close",
+    "allowDomain",
+    "allowInsecureDomain",
+    "domain",
+];
+
+/// The largest message that can be sent, in bytes of AMF.
+const MAX_MESSAGE_SIZE: usize = 40960;
+
+/// The `LocalConnection` object listening on a connection.
+#[derive(Clone, Copy, Collect, Debug)]
+#[collect(no_drop)]
+pub enum LocalConnectionReceiver<'gc> {
+    Avm1(avm1::Object<'gc>),
+    Avm2(avm2::Object<'gc>),
+}
+
+impl<'gc> LocalConnectionReceiver<'gc> {
+    fn ptr_eq(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Avm1(a), Self::Avm1(b)) => avm1::Object::ptr_eq(a, b),
+            (Self::Avm2(a), Self::Avm2(b)) => avm2::Object::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// A message waiting to be delivered to a connection.
+#[derive(Clone, Debug, Collect)]
+#[collect(require_static)]
+struct Message {
+    connection_name: String,
+    method_name: String,
+
+    /// The arguments of the call, serialized as AMF0 one after the other.
+    args: Vec<u8>,
+}
+
+/// The connections opened by every movie of the player, and the messages
+/// sent to them.
+///
+/// Messages are delivered on the next frame, like in Flash Player.
+#[derive(Collect, Default)]
+#[collect(no_drop)]
+pub struct LocalConnections<'gc> {
+    receivers: HashMap<String, LocalConnectionReceiver<'gc>>,
+    messages: Vec<Message>,
+}
+
+impl<'gc> LocalConnections<'gc> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the key of a connection name.
+    ///
+    /// Names are case-insensitive. Names that don't start with an underscore
+    /// are scoped to the domain of the movie; as every movie is considered to
+    /// be on `localhost`, that's the domain of every name.
+    fn key(connection_name: &str) -> String {
+        let name = connection_name.to_lowercase();
+        if name.starts_with('_') || name.contains(':') {
+            name
+        } else {
+            format!("localhost:{}", name)
+        }
+    }
+
+    /// Listen on a connection.
+    ///
+    /// Returns `false` if the connection is already in use, or the receiver
+    /// is already listening on another one.
+    pub fn connect(
+        &mut self,
+        connection_name: &str,
+        receiver: LocalConnectionReceiver<'gc>,
+    ) -> bool {
+        let key = Self::key(connection_name);
+        if self.receivers.contains_key(&key) || self.is_connected(receiver) {
+            return false;
+        }
+
+        self.receivers.insert(key, receiver);
+        true
+    }
+
+    /// Whether the receiver is listening on a connection.
+    pub fn is_connected(&self, receiver: LocalConnectionReceiver<'gc>) -> bool {
+        self.receivers.values().any(|r| r.ptr_eq(receiver))
+    }
+
+    /// Stop listening on the connection of a receiver.
+    pub fn close(&mut self, receiver: LocalConnectionReceiver<'gc>) {
+        self.receivers.retain(|_, r| !r.ptr_eq(receiver));
+    }
+
+    /// Queue a method call on the receiver of a connection.
+    ///
+    /// The arguments are serialized right away, so that later changes to them
+    /// aren't seen by the receiver. Returns `false` if the method can't be
+    /// called remotely, or if the arguments are too large.
+    pub fn send(
+        &mut self,
+        connection_name: &str,
+        method_name: &str,
+        graph: &Graph,
+        args: &[amf::Value],
+    ) -> bool {
+        if method_name.is_empty() || RESERVED_METHODS.contains(&method_name) {
+            return false;
+        }
+
+        let mut encoder = amf::amf0::Encoder::new(graph);
+        for arg in args {
+            encoder.write_value(arg);
+        }
+        let args = encoder.into_bytes();
+        if args.len() > MAX_MESSAGE_SIZE {
+            return false;
+        }
+
+        self.messages.push(Message {
+            connection_name: Self::key(connection_name),
+            method_name: method_name.to_string(),
+            args,
+        });
+        true
+    }
+
+    /// Deserialize the arguments of a message.
+    fn read_args(data: &[u8]) -> Result<(Graph, Vec<amf::Value>), AmfError> {
+        let mut graph = Graph::new();
+        let mut decoder = amf::amf0::Decoder::new(data, &mut graph);
+        let mut args = vec![];
+        while !decoder.is_empty() {
+            args.push(decoder.read_value()?);
+        }
+        Ok((graph, args))
+    }
+
+    /// Deliver the messages sent since the last call to their receivers.
+    ///
+    /// Messages to connections that nobody listens on are dropped.
+    pub fn deliver_messages(avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let messages = std::mem::take(&mut context.local_connections.messages);
+        for message in messages {
+            let receiver = match context
+                .local_connections
+                .receivers
+                .get(&message.connection_name)
+            {
+                Some(receiver) => *receiver,
+                None => continue,
+            };
+
+            let (graph, args) = match Self::read_args(&message.args) {
+                Ok(args) => args,
+                Err(e) => {
+                    log::error!("Invalid LocalConnection message: {}", e);
+                    continue;
+                }
+            };
+
+            match receiver {
+                LocalConnectionReceiver::Avm1(object) => {
+                    let root = match context.levels.get(&0) {
+                        Some(root) => *root,
+                        None => continue,
+                    };
+                    let swf_version = context.swf.version();
+                    let args: Vec<_> = {
+                        let mut activation = Avm1Activation::from_nothing(
+                            avm,
+                            ActivationIdentifier::root("[LocalConnection]"),
+                            swf_version,
+                            avm.global_object_cell(),
+                            context.gc_context,
+                            root,
+                        );
+                        args.iter()
+                            .map(|arg| {
+                                avm1::amf::deserialize(&mut activation, context, &graph, arg)
+                            })
+                            .collect()
+                    };
+                    avm.run_stack_frame_for_method(
+                        root,
+                        object,
+                        swf_version,
+                        context,
+                        &message.method_name,
+                        &args,
+                    );
+                }
+                LocalConnectionReceiver::Avm2(object) => {
+                    if let Err(e) =
+                        Self::call_avm2(object, &message.method_name, &graph, &args, context)
+                    {
+                        log::error!(
+                            "Unhandled AVM2 exception in LocalConnection method {}: {}",
+                            message.method_name,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Call a method on the `client` of an AVM2 `LocalConnection`.
+    fn call_avm2(
+        object: avm2::Object<'gc>,
+        method_name: &str,
+        graph: &Graph,
+        args: &[amf::Value],
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Result<(), avm2::Error> {
+        let mut activation = Avm2Activation::from_nothing();
+        let args = args
+            .iter()
+            .map(|arg| avm2::amf::deserialize(&mut activation, context, graph, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let client = match object.get_property(
+            object,
+            &QName::dynamic_name("client"),
+            &mut activation,
+            context,
+        )? {
+            avm2::Value::Object(client) => client,
+            _ => object,
+        };
+
+        let method = client
+            .get_property(
+                client,
+                &QName::dynamic_name(method_name),
+                &mut activation,
+                context,
+            )?
+            .as_object()?;
+        method.call(Some(client), &args, &mut activation, context)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_names_are_case_insensitive_and_domain_scoped() {
+        assert_eq!(LocalConnections::key("_Shell"), "_shell");
+        assert_eq!(LocalConnections::key("Shell"), "localhost:shell");
+        assert_eq!(LocalConnections::key("localhost:Shell"), "localhost:shell");
+    }
+
+    #[test]
+    fn reserved_methods_cant_be_sent() {
+        let mut connections = LocalConnections::new();
+        let graph = Graph::new();
+        assert!(!connections.send("_shell", "close", &graph, &[]));
+        assert!(!connections.send("_shell", "", &graph, &[]));
+        assert!(connections.send("_shell", "onGameOver", &graph, &[amf::Value::Number(1.0)]));
+        assert_eq!(connections.messages.len(), 1);
+    }
+
+    #[test]
+    fn messages_round_trip_through_amf() {
+        let mut connections = LocalConnections::new();
+        let args = [
+            amf::Value::String("score".to_string()),
+            amf::Value::Number(120.0),
+            amf::Value::Bool(true),
+        ];
+        assert!(connections.send("_shell", "onScore", &Graph::new(), &args));
+        let (_, decoded) = LocalConnections::read_args(&connections.messages[0].args).unwrap();
+        assert_eq!(decoded, args);
+
+        let too_large = [amf::Value::String("a".repeat(MAX_MESSAGE_SIZE))];
+        assert!(!connections.send("_shell", "onScore", &Graph::new(), &too_large));
+    }
+}
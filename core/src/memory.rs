@@ -0,0 +1,65 @@
+//! Statistics and tuning of the garbage-collected heap that holds display objects
+//! and ActionScript objects.
+
+use gc_arena::ArenaParameters;
+
+/// How eagerly the garbage collector runs.
+///
+/// Collection is incremental: allocating creates a debt of collection work, which
+/// the player pays off between events and frames. Collecting sooner and faster keeps
+/// the heap smaller, at the cost of more time spent collecting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcParameters {
+    /// How much the heap may grow after a collection before the next one starts, as a
+    /// fraction of the heap that survived the collection. Defaults to 0.5.
+    pub pause_factor: f64,
+
+    /// How much collection work is done for each byte allocated while collecting.
+    /// Higher values finish collections in fewer, longer pauses. Defaults to 1.5.
+    pub timing_factor: f64,
+
+    /// The fewest bytes that are allocated before a collection starts. Defaults to 4096.
+    pub min_sleep: usize,
+}
+
+impl Default for GcParameters {
+    fn default() -> Self {
+        Self {
+            pause_factor: 0.5,
+            timing_factor: 1.5,
+            min_sleep: 4096,
+        }
+    }
+}
+
+impl GcParameters {
+    pub(crate) fn arena_parameters(&self) -> ArenaParameters {
+        ArenaParameters::default()
+            .set_pause_factor(self.pause_factor)
+            .set_timing_factor(self.timing_factor)
+            .set_min_sleep(self.min_sleep)
+    }
+}
+
+/// The state of the garbage-collected heap, to diagnose movies whose memory use keeps
+/// growing.
+///
+/// The time spent collecting is part of the frame timings of the profiler.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GcStats {
+    /// The bytes allocated on the heap, including garbage that isn't collected yet.
+    pub allocated_bytes: usize,
+
+    /// The most bytes that were allocated at once since the player started.
+    pub peak_allocated_bytes: usize,
+
+    /// The collection work that is owed for recent allocations, in bytes.
+    pub allocation_debt: f64,
+
+    /// The number of display objects on the stage.
+    pub display_objects: usize,
+
+    /// The number of AVM1 objects reachable through enumerable properties from the
+    /// stage and `_global`. Objects only reachable in other ways aren't counted.
+    pub avm1_objects: usize,
+}
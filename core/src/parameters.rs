@@ -0,0 +1,250 @@
+//! The parameters a movie is embedded with.
+//!
+//! On a web page these are the attributes of an `<embed>` tag, or the `<param>`
+//! children of an `<object>` tag. Other embedders set them from their own
+//! configuration, such as the command line of the desktop player.
+
+use crate::stage::{ParseEnumError, StageAlign, StageQuality, StageScaleMode};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use swf::Color;
+use url::form_urlencoded;
+
+/// The settings given by the page that embeds a movie.
+///
+/// Parameter names are compared case-insensitively, like in Flash Player.
+/// Unknown parameters are ignored.
+#[derive(Debug, Clone)]
+pub struct PlayerParameters {
+    /// Variables defined on `_root` before the first frame runs, from the
+    /// `FlashVars` parameter.
+    pub flash_vars: Vec<(String, String)>,
+
+    /// The initial rendering quality, from the `quality` parameter.
+    pub quality: Option<StageQuality>,
+
+    /// The initial scale mode, from the `scale` parameter.
+    pub scale_mode: Option<StageScaleMode>,
+
+    /// The initial alignment, from the `salign` parameter.
+    pub stage_align: Option<StageAlign>,
+
+    /// How the movie is composited with the page, from the `wmode` parameter.
+    pub window_mode: WindowMode,
+
+    /// A background color that replaces the one set by the movie, from the
+    /// `bgcolor` parameter.
+    pub background_color: Option<Color>,
+
+    /// Whether the full context menu is shown, from the `menu` parameter.
+    pub show_menu: bool,
+
+    /// Whether the main timeline loops when it reaches its last frame, from the
+    /// `loop` parameter.
+    pub is_looping: bool,
+}
+
+impl PlayerParameters {
+    pub fn new() -> Self {
+        Self {
+            flash_vars: vec![],
+            quality: None,
+            scale_mode: None,
+            stage_align: None,
+            window_mode: WindowMode::default(),
+            background_color: None,
+            show_menu: true,
+            is_looping: true,
+        }
+    }
+
+    /// Sets a parameter by name. Invalid values are logged and ignored.
+    pub fn set(&mut self, name: &str, value: &str) {
+        match name.to_ascii_lowercase().as_str() {
+            "flashvars" => self
+                .flash_vars
+                .extend(form_urlencoded::parse(value.as_bytes()).into_owned()),
+            "quality" => match parse_quality(value) {
+                Some(quality) => self.quality = Some(quality),
+                None => log::warn!("Invalid quality parameter: {}", value),
+            },
+            "scale" => match value.parse() {
+                Ok(scale_mode) => self.scale_mode = Some(scale_mode),
+                Err(_) => log::warn!("Invalid scale parameter: {}", value),
+            },
+            "salign" => self.stage_align = Some(StageAlign::from(value)),
+            "wmode" => match value.parse() {
+                Ok(window_mode) => self.window_mode = window_mode,
+                Err(_) => log::warn!("Invalid wmode parameter: {}", value),
+            },
+            "bgcolor" => match parse_color(value) {
+                Some(color) => self.background_color = Some(color),
+                None => log::warn!("Invalid bgcolor parameter: {}", value),
+            },
+            "menu" => match parse_bool(value) {
+                Some(show_menu) => self.show_menu = show_menu,
+                None => log::warn!("Invalid menu parameter: {}", value),
+            },
+            "loop" => match parse_bool(value) {
+                Some(is_looping) => self.is_looping = is_looping,
+                None => log::warn!("Invalid loop parameter: {}", value),
+            },
+            _ => log::debug!("Ignoring parameter {}", name),
+        }
+    }
+}
+
+impl Default for PlayerParameters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How the movie is composited with the page that embeds it.
+///
+/// Ruffle always draws to its own canvas, so only `Transparent` changes anything:
+/// the background of the stage isn't drawn, and the page shows through.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowMode {
+    /// The movie is drawn in its own window, above the page. This is the default.
+    Window,
+
+    /// The movie is drawn with the page, with an opaque background.
+    Opaque,
+
+    /// The movie is drawn with the page, without a background.
+    Transparent,
+
+    /// The movie is drawn with hardware acceleration.
+    Direct,
+
+    /// The movie is drawn and composited with hardware acceleration.
+    Gpu,
+}
+
+impl Default for WindowMode {
+    fn default() -> Self {
+        WindowMode::Window
+    }
+}
+
+impl Display for WindowMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            WindowMode::Window => "window",
+            WindowMode::Opaque => "opaque",
+            WindowMode::Transparent => "transparent",
+            WindowMode::Direct => "direct",
+            WindowMode::Gpu => "gpu",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for WindowMode {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let window_mode = match s.to_ascii_lowercase().as_str() {
+            "window" => WindowMode::Window,
+            "opaque" => WindowMode::Opaque,
+            "transparent" => WindowMode::Transparent,
+            "direct" => WindowMode::Direct,
+            "gpu" => WindowMode::Gpu,
+            _ => return Err(ParseEnumError),
+        };
+        Ok(window_mode)
+    }
+}
+
+/// Parses a `quality` parameter. The automatic qualities, which Flash Player
+/// lowers on slow machines, are treated as their starting quality.
+fn parse_quality(value: &str) -> Option<StageQuality> {
+    match value.to_ascii_lowercase().as_str() {
+        "autolow" => Some(StageQuality::Low),
+        "autohigh" => Some(StageQuality::High),
+        value => value.parse().ok(),
+    }
+}
+
+/// Parses a color such as `#FF8000` or `FF8000`.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim_start_matches('#');
+    if value.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(value, 16).ok()?;
+    Some(Color {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+        a: 255,
+    })
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flash_vars() {
+        let mut parameters = PlayerParameters::new();
+        parameters.set("FlashVars", "name=Ruffle&greeting=hello%20world&empty=");
+        assert_eq!(
+            parameters.flash_vars,
+            vec![
+                ("name".to_string(), "Ruffle".to_string()),
+                ("greeting".to_string(), "hello world".to_string()),
+                ("empty".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn embed_parameters() {
+        let mut parameters = PlayerParameters::new();
+        parameters.set("QUALITY", "autolow");
+        parameters.set("scale", "noScale");
+        parameters.set("salign", "tl");
+        parameters.set("wmode", "Transparent");
+        parameters.set("bgcolor", "#ff8000");
+        parameters.set("menu", "false");
+        parameters.set("loop", "FALSE");
+
+        assert_eq!(parameters.quality, Some(StageQuality::Low));
+        assert_eq!(parameters.scale_mode, Some(StageScaleMode::NoScale));
+        assert_eq!(parameters.stage_align, Some(StageAlign::from("TL")));
+        assert_eq!(parameters.window_mode, WindowMode::Transparent);
+        assert_eq!(
+            parameters.background_color,
+            Some(Color {
+                r: 255,
+                g: 128,
+                b: 0,
+                a: 255
+            })
+        );
+        assert!(!parameters.show_menu);
+        assert!(!parameters.is_looping);
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let mut parameters = PlayerParameters::new();
+        parameters.set("quality", "ultra");
+        parameters.set("bgcolor", "orange");
+        parameters.set("menu", "maybe");
+
+        assert_eq!(parameters.quality, None);
+        assert_eq!(parameters.background_color, None);
+        assert!(parameters.show_menu);
+    }
+}
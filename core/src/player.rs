@@ -1,29 +1,59 @@
+use crate::audio_manager::AudioManager;
 use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::debug::VariableDumper;
+use crate::avm1::debugger::{Debugger, DebuggerConnection};
 use crate::avm1::globals::system::SystemProperties;
 use crate::avm1::listeners::SystemListener;
 use crate::avm1::object::Object;
 use crate::avm1::{Avm1, TObject, Value};
-use crate::backend::input::{InputBackend, MouseCursor};
-use crate::backend::storage::StorageBackend;
+use crate::avm2::Avm2;
+use crate::backend::accessibility::{AccessibilityBackend, NullAccessibilityBackend};
+use crate::backend::font::{FontBackend, NullFontBackend};
+use crate::backend::input::{InputBackend, MouseCursor, NullInputBackend};
+use crate::backend::log::{LogBackend, LogCrateBackend};
+use crate::backend::printer::{NullPrinterBackend, PrinterBackend};
+#[cfg(feature = "rtmp")]
+use crate::backend::rtmp::{NullRtmpBackend, RtmpBackend};
+use crate::backend::storage::{MemoryStorageBackend, StorageBackend};
 use crate::backend::{
-    audio::AudioBackend, navigator::NavigatorBackend, render::Letterbox, render::RenderBackend,
+    audio::AudioBackend, audio::NullAudioBackend, navigator::NavigatorBackend,
+    navigator::NullNavigatorBackend, render::Bitmap, render::Letterbox, render::NullRenderer,
+    render::RenderBackend,
 };
 use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
-use crate::display_object::{EditText, MorphShape, MovieClip};
-use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode, PlayerEvent};
+use crate::context_menu::{self, ContextMenuCallback, ContextMenuItem};
+use crate::display_object::{get_avm1_property, EditText, MovieClip};
+use crate::drawing::Drawing;
+use crate::events::{
+    ButtonKeyCode, ClipEvent, ClipEventResult, ImeEvent, KeyCode, MouseWheelDelta, PlayerEvent,
+    TouchPhase,
+};
+use crate::focus_tracker;
+use crate::host::{self, HostFunction, HostObject, HostValue};
+use crate::input_script::{InputScript, ScriptedInputBackend};
+use crate::inspector::{self, DisplayObjectInfo, ObjectInfo};
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::LocalConnections;
+use crate::memory::{GcParameters, GcStats};
+use crate::parameters::{PlayerParameters, WindowMode};
 use crate::prelude::*;
+use crate::profiler::{Clock, FrameTimings, Profiler, Subsystem};
+#[cfg(feature = "rtmp")]
+use crate::rtmp::RtmpConnections;
+use crate::shape_utils::DrawCommand;
+use crate::stage::{StageAlign, StageDisplayState, StageQuality, StageScaleMode};
 use crate::tag_utils::SwfMovie;
 use crate::transform::TransformStack;
+use crate::unimplemented::UnimplementedFeatures;
 use enumset::EnumSet;
-use gc_arena::{make_arena, ArenaParameters, Collect, GcCell};
+use gc_arena::{make_arena, Collect, GcCell};
 use log::info;
 use rand::{rngs::SmallRng, SeedableRng};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::ops::DerefMut;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex, Weak};
 
 pub static DEVICE_FONT_TAG: &[u8] = include_bytes!("../assets/noto-sans-definefont3.bin");
@@ -32,6 +62,9 @@ pub static DEVICE_FONT_TAG: &[u8] = include_bytes!("../assets/noto-sans-definefo
 /// `player_version`.
 pub const NEWEST_PLAYER_VERSION: u8 = 32;
 
+/// The number of bytes of the root movie's tags to preload before each frame.
+const PRELOAD_CHUNK_SIZE: usize = 1 << 20;
+
 #[derive(Collect)]
 #[collect(no_drop)]
 struct GcRoot<'gc>(GcCell<'gc, GcRootData<'gc>>);
@@ -53,7 +86,11 @@ struct GcRootData<'gc> {
     /// The object being dragged via a `startDrag` action.
     drag_object: Option<DragObject<'gc>>,
 
+    /// The object that has keyboard focus.
+    focus: Option<DisplayObject<'gc>>,
+
     avm: Avm1<'gc>,
+    avm2: Avm2<'gc>,
     action_queue: ActionQueue<'gc>,
 
     /// Object which manages asynchronous processes that need to interact with
@@ -64,36 +101,22 @@ struct GcRootData<'gc> {
 
     /// Text fields with unbound variable bindings.
     unbound_text_fields: Vec<EditText<'gc>>,
-}
 
-impl<'gc> GcRootData<'gc> {
-    /// Splits out parameters for creating an `UpdateContext`
-    /// (because we can borrow fields of `self` independently)
-    #[allow(clippy::type_complexity)]
-    fn update_context_params(
-        &mut self,
-    ) -> (
-        &mut BTreeMap<u32, DisplayObject<'gc>>,
-        &mut Library<'gc>,
-        &mut ActionQueue<'gc>,
-        &mut Avm1<'gc>,
-        &mut Option<DragObject<'gc>>,
-        &mut LoadManager<'gc>,
-        &mut HashMap<String, Object<'gc>>,
-        &mut Vec<EditText<'gc>>,
-    ) {
-        (
-            &mut self.levels,
-            &mut self.library,
-            &mut self.action_queue,
-            &mut self.avm,
-            &mut self.drag_object,
-            &mut self.load_manager,
-            &mut self.shared_objects,
-            &mut self.unbound_text_fields,
-        )
-    }
+    /// Clips removed from the display list whose scripts can still run until the
+    /// end of the next frame.
+    unloaded_clips: Vec<DisplayObject<'gc>>,
+
+    /// The connections opened by `LocalConnection` objects of both VMs.
+    local_connections: LocalConnections<'gc>,
+
+    /// The connections of `NetConnection` objects to media servers.
+    #[cfg(feature = "rtmp")]
+    rtmp_connections: RtmpConnections<'gc>,
+
+    /// The sounds played by movies, along with the objects that own them.
+    audio_manager: AudioManager<'gc>,
 }
+
 type Error = Box<dyn std::error::Error>;
 
 make_arena!(GcArena, GcRoot);
@@ -103,6 +126,12 @@ type Navigator = Box<dyn NavigatorBackend>;
 type Renderer = Box<dyn RenderBackend>;
 type Input = Box<dyn InputBackend>;
 type Storage = Box<dyn StorageBackend>;
+type Font = Box<dyn FontBackend>;
+type Printer = Box<dyn PrinterBackend>;
+type Accessibility = Box<dyn AccessibilityBackend>;
+#[cfg(feature = "rtmp")]
+type Rtmp = Box<dyn RtmpBackend>;
+type Log = Box<dyn LogBackend>;
 
 pub struct Player {
     /// The version of the player we're emulating.
@@ -120,8 +149,19 @@ pub struct Player {
     swf: Arc<SwfMovie>,
 
     is_playing: bool,
+
+    /// Whether the player is paused by the embedder, such as by a pause button or
+    /// a debugger. Unlike a stopped player, a paused player also silences its audio.
+    is_paused: bool,
+
     needs_render: bool,
 
+    /// The volume of all audio played by the player, from 0.0 to 1.0.
+    volume: f32,
+
+    /// Whether all audio is silenced, regardless of `volume`.
+    is_muted: bool,
+
     audio: Audio,
     renderer: Renderer,
     pub navigator: Navigator,
@@ -131,6 +171,12 @@ pub struct Player {
     inverse_view_matrix: Matrix,
 
     storage: Storage,
+    font: Font,
+    printer: Printer,
+    accessibility: Accessibility,
+    #[cfg(feature = "rtmp")]
+    rtmp: Rtmp,
+    log: Log,
 
     rng: SmallRng,
 
@@ -138,18 +184,97 @@ pub struct Player {
     background_color: Color,
 
     frame_rate: f64,
+
+    /// A frame rate set by the embedder, which replaces the frame rate of the movie.
+    frame_rate_override: Option<f64>,
+
+    /// How fast time passes in the player, such as 0.5 for slow motion.
+    time_scale: f64,
+
     frame_accumulator: f64,
-    global_time: u64,
 
+    /// The time the player has been running, in milliseconds, which `getTimer` returns.
+    /// This doesn't advance while the player is paused, and is scaled by `time_scale`.
+    global_time: f64,
+
+    /// The number of frames run since the movie started.
+    frame_number: u32,
+
+    /// Whether runs of the movie are reproducible, with the clock advancing by
+    /// exactly one frame each frame, regardless of how long frames take to run.
+    is_deterministic: bool,
+
+    /// The input events being played into the player, if any.
+    input_script: Option<InputScript>,
+
+    /// The input events given to the player since recording started, if recording.
+    input_recording: Option<InputScript>,
+
+    /// The size of the viewport in device pixels.
     viewport_width: u32,
     viewport_height: u32,
+
+    /// The number of device pixels per logical pixel of the viewport, such as 2.0 on a
+    /// high-DPI display.
+    device_pixel_ratio: f64,
+
     movie_width: u32,
     movie_height: u32,
     letterbox: Letterbox,
 
+    /// How the movie is scaled to fit the viewport.
+    scale_mode: StageScaleMode,
+
+    /// Where the movie is placed in the viewport.
+    stage_align: StageAlign,
+
+    /// The rendering quality of the stage.
+    quality: StageQuality,
+
+    /// Whether the stage fills the screen.
+    display_state: StageDisplayState,
+
+    /// How the movie is composited with the page that embeds it.
+    window_mode: WindowMode,
+
+    /// A background color set by the embedder, which replaces the one set by the movie.
+    background_color_override: Option<Color>,
+
+    /// Whether the full context menu is shown.
+    show_menu: bool,
+
+    /// Whether the root movie returns to its first frame after its last frame.
+    is_looping: bool,
+
+    /// The number of seconds of streaming sound to buffer, set by `_soundbuftime`.
+    sound_buffer_time: i32,
+
     mouse_pos: (Twips, Twips),
     is_mouse_down: bool,
 
+    /// The part of a line scrolled by the mouse wheel that hasn't been reported yet.
+    /// Touchpads scroll by small amounts, which add up to whole lines.
+    mouse_wheel_remainder: f64,
+
+    /// The touch points currently on the screen by id, in stage coordinates.
+    /// Kept for AVM2 `TouchEvent`s, which aren't implemented yet.
+    touch_points: BTreeMap<u64, (Twips, Twips)>,
+
+    /// The touch point that moves the mouse. Movies without touch support see
+    /// the first finger on the screen as the mouse.
+    primary_touch: Option<u64>,
+
+    /// Whether the focused object is outlined, which is only the case after
+    /// moving focus with the keyboard.
+    is_focus_rect_visible: bool,
+
+    /// The rectangle drawn around the focused object.
+    focus_rect: Drawing,
+
+    /// Whether the mouse is over the object that it was pressed on.
+    /// Only meaningful while the mouse button is held.
+    is_mouse_over_pressed: bool,
+
     /// The current mouse cursor icon.
     mouse_cursor: MouseCursor,
 
@@ -158,6 +283,15 @@ pub struct Player {
     /// The current instance ID. Used to generate default `instanceN` names.
     instance_counter: i32,
 
+    /// Records how long each subsystem takes per frame, when profiling is on.
+    profiler: Profiler,
+
+    /// The most bytes that were allocated on the GC heap at once.
+    peak_allocated_bytes: usize,
+
+    /// The features the movie used that aren't implemented.
+    unimplemented_features: UnimplementedFeatures,
+
     /// Self-reference to ourselves.
     ///
     /// This is a weak reference that is upgraded and handed out in various
@@ -166,7 +300,313 @@ pub struct Player {
     self_reference: Option<Weak<Mutex<Self>>>,
 }
 
+/// Builds a `Player`, for applications that embed Ruffle.
+///
+/// Only a movie is required. Backends that aren't given are replaced by ones that
+/// do nothing: no rendering, no audio, no network access, and shared objects that
+/// are only kept in memory.
+///
+/// ```ignore
+/// let player = PlayerBuilder::new()
+///     .with_renderer(renderer)
+///     .with_audio(audio)
+///     .with_movie_bytes(swf_data)
+///     .with_viewport_dimensions(800, 600, 1.0)
+///     .with_autoplay(true)
+///     .build()?;
+/// ```
+pub struct PlayerBuilder {
+    movie: Option<MovieSource>,
+    renderer: Option<Renderer>,
+    audio: Option<Audio>,
+    navigator: Option<Navigator>,
+    input: Option<Input>,
+    storage: Option<Storage>,
+    font: Option<Font>,
+    printer: Option<Printer>,
+    accessibility: Option<Accessibility>,
+    #[cfg(feature = "rtmp")]
+    rtmp: Option<Rtmp>,
+    log: Option<Log>,
+    viewport_dimensions: Option<(u32, u32, f64)>,
+    host_functions: Vec<(String, Rc<HostFunction>)>,
+    host_objects: Vec<(String, HostObject)>,
+    autoplay: bool,
+    volume: f32,
+    scale_mode: StageScaleMode,
+    stage_align: StageAlign,
+    quality: StageQuality,
+    parameters: Option<PlayerParameters>,
+    gc_parameters: GcParameters,
+}
+
+/// The movie given to a `PlayerBuilder`, which is parsed when the player is built.
+enum MovieSource {
+    Movie(SwfMovie),
+    Bytes(Vec<u8>, Option<String>),
+}
+
+impl PlayerBuilder {
+    /// Creates a builder with no movie, and backends that do nothing.
+    pub fn new() -> Self {
+        Self {
+            movie: None,
+            renderer: None,
+            audio: None,
+            navigator: None,
+            input: None,
+            storage: None,
+            font: None,
+            printer: None,
+            accessibility: None,
+            #[cfg(feature = "rtmp")]
+            rtmp: None,
+            log: None,
+            viewport_dimensions: None,
+            host_functions: vec![],
+            host_objects: vec![],
+            autoplay: false,
+            volume: 1.0,
+            scale_mode: StageScaleMode::default(),
+            stage_align: StageAlign::default(),
+            quality: StageQuality::default(),
+            parameters: None,
+            gc_parameters: GcParameters::default(),
+        }
+    }
+
+    /// Plays the given movie.
+    pub fn with_movie(mut self, movie: SwfMovie) -> Self {
+        self.movie = Some(MovieSource::Movie(movie));
+        self
+    }
+
+    /// Plays the movie in the given SWF file data. The data is parsed by `build`.
+    pub fn with_movie_bytes(mut self, data: Vec<u8>) -> Self {
+        self.movie = Some(MovieSource::Bytes(data, None));
+        self
+    }
+
+    /// Plays the movie in the given SWF file data, which was loaded from `url`.
+    /// The URL is what the movie sees as its own `_url`, and relative loads are
+    /// made from it.
+    pub fn with_movie_bytes_from_url(mut self, data: Vec<u8>, url: String) -> Self {
+        self.movie = Some(MovieSource::Bytes(data, Some(url)));
+        self
+    }
+
+    /// Draws the movie with the given renderer.
+    pub fn with_renderer(mut self, renderer: impl 'static + RenderBackend) -> Self {
+        self.renderer = Some(Box::new(renderer));
+        self
+    }
+
+    /// Plays sounds with the given audio backend.
+    pub fn with_audio(mut self, audio: impl 'static + AudioBackend) -> Self {
+        self.audio = Some(Box::new(audio));
+        self
+    }
+
+    /// Loads URLs and runs asynchronous loads with the given navigator.
+    pub fn with_navigator(mut self, navigator: impl 'static + NavigatorBackend) -> Self {
+        self.navigator = Some(Box::new(navigator));
+        self
+    }
+
+    /// Reads the state of the keyboard and mouse cursor from the given input backend.
+    pub fn with_input(mut self, input: impl 'static + InputBackend) -> Self {
+        self.input = Some(Box::new(input));
+        self
+    }
+
+    /// Saves shared objects with the given storage backend.
+    pub fn with_storage(mut self, storage: impl 'static + StorageBackend) -> Self {
+        self.storage = Some(Box::new(storage));
+        self
+    }
+
+    /// Finds device fonts with the given font backend.
+    pub fn with_font(mut self, font: impl 'static + FontBackend) -> Self {
+        self.font = Some(Box::new(font));
+        self
+    }
+
+    /// Prints with the given printer backend.
+    pub fn with_printer(mut self, printer: impl 'static + PrinterBackend) -> Self {
+        self.printer = Some(Box::new(printer));
+        self
+    }
+
+    /// Exposes the accessibility tree of the movie through the given backend.
+    pub fn with_accessibility(
+        mut self,
+        accessibility: impl 'static + AccessibilityBackend,
+    ) -> Self {
+        self.accessibility = Some(Box::new(accessibility));
+        self
+    }
+
+    /// Connects to RTMP servers with the given backend.
+    #[cfg(feature = "rtmp")]
+    pub fn with_rtmp(mut self, rtmp: impl 'static + RtmpBackend) -> Self {
+        self.rtmp = Some(Box::new(rtmp));
+        self
+    }
+
+    /// Sends the output of `trace()` and the errors shown to the author of the movie to
+    /// the given backend. Defaults to the `log` crate.
+    pub fn with_log(mut self, log: impl 'static + LogBackend) -> Self {
+        self.log = Some(Box::new(log));
+        self
+    }
+
+    /// Sets the size of the viewport in device pixels. Defaults to the size of the movie.
+    /// See `Player::set_viewport_dimensions`.
+    pub fn with_viewport_dimensions(
+        mut self,
+        width: u32,
+        height: u32,
+        device_pixel_ratio: f64,
+    ) -> Self {
+        self.viewport_dimensions = Some((width, height, device_pixel_ratio));
+        self
+    }
+
+    /// Adds a global function to AVM1 that calls back into the application.
+    /// See `Player::register_host_function`.
+    pub fn with_host_function(
+        mut self,
+        name: &str,
+        function: impl 'static + Fn(&[HostValue]) -> HostValue,
+    ) -> Self {
+        self.host_functions
+            .push((name.to_string(), Rc::new(function)));
+        self
+    }
+
+    /// Adds a global object to AVM1 whose methods call back into the application.
+    /// See `Player::register_host_object`.
+    pub fn with_host_object(mut self, name: &str, object: HostObject) -> Self {
+        self.host_objects.push((name.to_string(), object));
+        self
+    }
+
+    /// Starts playing the movie as soon as it is built. Defaults to `false`.
+    pub fn with_autoplay(mut self, autoplay: bool) -> Self {
+        self.autoplay = autoplay;
+        self
+    }
+
+    /// Sets the volume of the player's audio, from 0.0 to 1.0.
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Sets how the movie is scaled to fit the viewport.
+    pub fn with_scale_mode(mut self, scale_mode: StageScaleMode) -> Self {
+        self.scale_mode = scale_mode;
+        self
+    }
+
+    /// Sets where the movie is placed in the viewport.
+    pub fn with_stage_align(mut self, stage_align: StageAlign) -> Self {
+        self.stage_align = stage_align;
+        self
+    }
+
+    /// Sets the rendering quality of the stage.
+    pub fn with_quality(mut self, quality: StageQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Applies the parameters the movie is embedded with, such as `FlashVars`.
+    /// These take precedence over the other settings of the builder.
+    pub fn with_parameters(mut self, parameters: PlayerParameters) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Sets how eagerly the garbage collector runs. See `GcParameters`.
+    pub fn with_gc_parameters(mut self, gc_parameters: GcParameters) -> Self {
+        self.gc_parameters = gc_parameters;
+        self
+    }
+
+    /// Builds the player. Fails if no movie was given, or if the movie couldn't be parsed.
+    pub fn build(self) -> Result<Arc<Mutex<Player>>, Error> {
+        let movie = match self.movie {
+            Some(MovieSource::Movie(movie)) => movie,
+            Some(MovieSource::Bytes(data, url)) => SwfMovie::from_data(&data, url)?,
+            None => return Err("No movie given to the player".into()),
+        };
+        let (width, height) = (movie.width(), movie.height());
+
+        let player = Player::new(
+            self.renderer
+                .unwrap_or_else(|| Box::new(NullRenderer::new())),
+            self.audio
+                .unwrap_or_else(|| Box::new(NullAudioBackend::new())),
+            self.navigator
+                .unwrap_or_else(|| Box::new(NullNavigatorBackend::new())),
+            self.input
+                .unwrap_or_else(|| Box::new(NullInputBackend::new())),
+            movie,
+            self.storage
+                .unwrap_or_else(|| Box::new(MemoryStorageBackend::default())),
+            self.font
+                .unwrap_or_else(|| Box::new(NullFontBackend::new())),
+            self.printer
+                .unwrap_or_else(|| Box::new(NullPrinterBackend::new())),
+            self.accessibility
+                .unwrap_or_else(|| Box::new(NullAccessibilityBackend::new())),
+            self.log.unwrap_or_else(|| Box::new(LogCrateBackend::new())),
+            self.gc_parameters,
+        )?;
+
+        {
+            let mut player = player.lock().unwrap();
+            #[cfg(feature = "rtmp")]
+            {
+                if let Some(rtmp) = self.rtmp {
+                    player.set_rtmp_backend(rtmp);
+                }
+            }
+            let (width, height, device_pixel_ratio) =
+                self.viewport_dimensions.unwrap_or((width, height, 1.0));
+            player.set_viewport_dimensions(width, height, device_pixel_ratio);
+            player.set_scale_mode(self.scale_mode);
+            player.set_stage_align(self.stage_align);
+            player.set_quality(self.quality);
+            player.set_volume(self.volume);
+            if let Some(parameters) = &self.parameters {
+                player.apply_parameters(parameters);
+            }
+            for (name, function) in self.host_functions {
+                player.define_host_function(&name, function);
+            }
+            for (name, object) in &self.host_objects {
+                player.register_host_object(name, object);
+            }
+            player.set_is_playing(self.autoplay);
+        }
+
+        Ok(player)
+    }
+}
+
+impl Default for PlayerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Player {
+    /// Creates a player for the given movie, with every backend given.
+    ///
+    /// `PlayerBuilder` is easier to use when not every backend is needed.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         mut renderer: Renderer,
         audio: Audio,
@@ -174,6 +614,11 @@ impl Player {
         input: Input,
         movie: SwfMovie,
         storage: Storage,
+        font: Font,
+        printer: Printer,
+        accessibility: Accessibility,
+        log: Log,
+        gc_parameters: GcParameters,
     ) -> Result<Arc<Mutex<Self>>, Error> {
         let movie = Arc::new(movie);
 
@@ -193,6 +638,9 @@ impl Player {
             swf: movie.clone(),
 
             is_playing: false,
+            is_paused: false,
+            volume: 1.0,
+            is_muted: false,
             needs_render: true,
 
             background_color: Color {
@@ -207,7 +655,7 @@ impl Player {
 
             rng: SmallRng::from_seed([0u8; 16]), // TODO(Herschel): Get a proper seed on all platforms.
 
-            gc_arena: GcArena::new(ArenaParameters::default(), |gc_context| {
+            gc_arena: GcArena::new(gc_parameters.arena_parameters(), |gc_context| {
                 // Load and parse the device font.
                 let device_font =
                     match Self::load_device_font(gc_context, DEVICE_FONT_TAG, &mut renderer) {
@@ -231,27 +679,56 @@ impl Player {
                         levels: BTreeMap::new(),
                         mouse_hovered_object: None,
                         drag_object: None,
+                        focus: None,
                         avm: Avm1::new(gc_context, NEWEST_PLAYER_VERSION),
+                        avm2: Avm2::new(gc_context),
                         action_queue: ActionQueue::new(),
                         load_manager: LoadManager::new(),
                         shared_objects: HashMap::new(),
                         unbound_text_fields: Vec::new(),
+                        unloaded_clips: Vec::new(),
+                        local_connections: LocalConnections::new(),
+                        #[cfg(feature = "rtmp")]
+                        rtmp_connections: RtmpConnections::new(),
+                        audio_manager: AudioManager::new(),
                     },
                 ))
             }),
 
             frame_rate: movie.header().frame_rate.into(),
+            frame_rate_override: None,
+            time_scale: 1.0,
             frame_accumulator: 0.0,
-            global_time: 0,
+            global_time: 0.0,
+            frame_number: 0,
+            is_deterministic: false,
+            input_script: None,
+            input_recording: None,
 
             movie_width,
             movie_height,
             viewport_width: movie_width,
             viewport_height: movie_height,
+            device_pixel_ratio: 1.0,
             letterbox: Letterbox::None,
+            scale_mode: StageScaleMode::default(),
+            stage_align: StageAlign::default(),
+            quality: StageQuality::default(),
+            display_state: StageDisplayState::default(),
+            window_mode: WindowMode::default(),
+            background_color_override: None,
+            show_menu: true,
+            is_looping: true,
+            sound_buffer_time: 5,
 
             mouse_pos: (Twips::new(0), Twips::new(0)),
             is_mouse_down: false,
+            mouse_wheel_remainder: 0.0,
+            touch_points: BTreeMap::new(),
+            primary_touch: None,
+            is_focus_rect_visible: false,
+            focus_rect: Drawing::new(),
+            is_mouse_over_pressed: false,
             mouse_cursor: MouseCursor::Arrow,
 
             renderer,
@@ -261,7 +738,16 @@ impl Player {
             self_reference: None,
             system: SystemProperties::default(),
             instance_counter: 0,
+            profiler: Profiler::new(),
+            peak_allocated_bytes: 0,
+            unimplemented_features: UnimplementedFeatures::new(),
             storage,
+            font,
+            printer,
+            accessibility,
+            #[cfg(feature = "rtmp")]
+            rtmp: Box::new(NullRtmpBackend::new()),
+            log,
         };
 
         player.mutate_with_update_context(|avm, context| {
@@ -290,31 +776,61 @@ impl Player {
         });
 
         player.build_matrices();
-        player.preload();
 
         let player_box = Arc::new(Mutex::new(player));
         let mut player_lock = player_box.lock().unwrap();
         player_lock.self_reference = Some(Arc::downgrade(&player_box));
+        // Preloading may start loads (such as `ImportAssets`), which need the player reference.
+        player_lock.preload();
         std::mem::drop(player_lock);
 
         Ok(player_box)
     }
 
+    /// Advances the player by `dt` milliseconds, running as many frames as are due.
+    ///
+    /// Embedders should call this regularly, for example after waiting for
+    /// `time_til_next_frame`, and call `render` afterwards if `needs_render` is set.
     pub fn tick(&mut self, dt: f64) {
-        // Don't run until preloading is complete.
-        // TODO: Eventually we want to stream content similar to the Flash player.
+        // Wait for the audio backend to finish decoding the sounds preloaded so far.
+        // Movies still downloading keep running: each frame preloads the data that has
+        // arrived, and timelines wait on frames that haven't been preloaded yet.
         if !self.audio.is_loading_complete() {
             return;
         }
 
-        if self.is_playing() {
+        // Wait for imported assets before running frames that may use them.
+        if self.has_pending_imports() {
+            return;
+        }
+
+        if self.is_playing() && !self.is_paused {
+            let dt = dt * self.time_scale;
             self.frame_accumulator += dt;
-            self.global_time += dt as u64;
-            let frame_time = 1000.0 / self.frame_rate;
+            if !self.is_deterministic {
+                self.global_time += dt;
+            }
+            let frame_time = 1000.0 / self.frame_rate();
+
+            // Streaming sounds drive the timeline: skip or delay frames to match the audio.
+            // Audio always plays at normal speed, so this is skipped when the timing is changed.
+            // The audio device isn't reproducible either, so neither is it in deterministic mode.
+            // The correction is spread over ticks, so the timeline catches up by running at
+            // most twice as fast, or waits by pausing, rather than jumping on audio jitter.
+            if self.frame_rate_override.is_none()
+                && self.time_scale == 1.0
+                && !self.is_deterministic
+            {
+                if let Some(frames_ahead) = self.audio_stream_frames_ahead() {
+                    let drift = frames_ahead * frame_time - self.frame_accumulator;
+                    self.frame_accumulator += drift.clamp(-dt, dt);
+                }
+            }
 
             const MAX_FRAMES_PER_TICK: u32 = 5; // Sanity cap on frame tick.
+            let max_frames = (f64::from(MAX_FRAMES_PER_TICK) * self.time_scale.max(1.0)) as u32;
             let mut frame = 0;
-            while frame < MAX_FRAMES_PER_TICK && self.frame_accumulator >= frame_time {
+            while frame < max_frames && self.frame_accumulator >= frame_time {
                 self.frame_accumulator -= frame_time;
                 self.run_frame();
                 frame += 1;
@@ -326,14 +842,42 @@ impl Player {
                 self.frame_accumulator = 0.0;
             }
 
+            let span = self.profiler.start();
             self.audio.tick();
+            self.profiler.record(Subsystem::Audio, span);
+        }
+    }
+
+    /// Returns how many frames the stream sound on the root timeline is ahead of the timeline.
+    ///
+    /// A negative value means the timeline is ahead of the audio and should wait.
+    /// Returns `None` if no stream sound is playing, or if the audio backend can't report
+    /// the stream's position.
+    /// TODO: Flash syncs to streams on any timeline, not only the root.
+    fn audio_stream_frames_ahead(&mut self) -> Option<f64> {
+        let (stream, start_frame, current_frame) =
+            self.mutate_with_update_context(|_avm, context| {
+                let root = context.levels.get(&0)?.as_movie_clip()?;
+                let (stream, start_frame) = root.audio_stream()?;
+                Some((stream, start_frame, root.current_frame()))
+            })?;
+
+        // The timeline may have looped or jumped since the stream started.
+        if current_frame < start_frame {
+            return None;
         }
+
+        let stream_position = self.audio.get_stream_position(stream)?;
+        let frame_time = 1000.0 / self.frame_rate();
+        let audio_frames = f64::from(stream_position) / frame_time;
+        let timeline_frames = f64::from(current_frame - start_frame);
+        Some(audio_frames - timeline_frames)
     }
 
     /// Returns the approximate duration of time until the next frame is due to run.
     /// This is only an approximation to be used for sleep durations.
     pub fn time_til_next_frame(&self) -> std::time::Duration {
-        let frame_time = 1000.0 / self.frame_rate;
+        let frame_time = 1000.0 / self.frame_rate();
         let dt = if self.frame_accumulator <= 0.0 {
             frame_time
         } else if self.frame_accumulator >= frame_time {
@@ -341,13 +885,101 @@ impl Player {
         } else {
             frame_time - self.frame_accumulator
         };
+        let dt = dt / self.time_scale;
         std::time::Duration::from_micros(dt as u64 * 1000)
     }
 
+    /// Makes runs of the movie reproducible, for regression tests and replays.
+    ///
+    /// `Math.random` is seeded with `seed`, and the clock returned by `getTimer`
+    /// advances by exactly one frame each frame, instead of following real time.
+    /// Combined with an input script, every run of the movie is the same, as long
+    /// as the movie doesn't load anything from the network.
+    pub fn enable_deterministic_mode(&mut self, seed: u64) {
+        self.is_deterministic = true;
+        self.rng = SmallRng::seed_from_u64(seed);
+        self.global_time = 1000.0 / self.frame_rate() * f64::from(self.frame_number);
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.is_deterministic
+    }
+
+    /// The number of frames run since the movie started.
+    pub fn frame_number(&self) -> u32 {
+        self.frame_number
+    }
+
+    /// Plays the events of the given script into the player, before the frames they
+    /// are given for. Until the script ends, input from the embedder is ignored, and
+    /// `Key.isDown` only sees the keys held by the script.
+    pub fn play_input_script(&mut self, script: InputScript) {
+        self.stop_input_script();
+        let input = std::mem::replace(&mut self.input, Box::new(NullInputBackend::new()));
+        self.input = Box::new(ScriptedInputBackend::new(input));
+        self.input_script = Some(script);
+    }
+
+    /// Whether an input script is playing.
+    pub fn is_playing_input_script(&self) -> bool {
+        self.input_script.is_some()
+    }
+
+    /// Stops the input script that is playing, if any, and gives input back to the embedder.
+    pub fn stop_input_script(&mut self) {
+        if self.input_script.take().is_some() {
+            let input = std::mem::replace(&mut self.input, Box::new(NullInputBackend::new()));
+            self.input = match input.downcast::<ScriptedInputBackend>() {
+                Ok(scripted) => scripted.inner,
+                Err(input) => input,
+            };
+        }
+    }
+
+    /// Starts recording the input events given to the player, to be replayed later
+    /// with `play_input_script`. Any previous recording is discarded.
+    pub fn start_input_recording(&mut self) {
+        self.input_recording = Some(InputScript::new());
+    }
+
+    pub fn is_recording_input(&self) -> bool {
+        self.input_recording.is_some()
+    }
+
+    /// Stops recording input, and returns the recorded events.
+    /// Returns `None` if input wasn't being recorded.
+    pub fn stop_input_recording(&mut self) -> Option<InputScript> {
+        self.input_recording.take()
+    }
+
+    /// Sends the events of the input script that are due before the current frame.
+    fn play_scripted_input(&mut self) {
+        let events = match &mut self.input_script {
+            Some(script) => script.take_events(self.frame_number),
+            None => return,
+        };
+        for event in events {
+            if let Some(input) = self.input.downcast_mut::<ScriptedInputBackend>() {
+                input.handle_event(&event);
+            }
+            self.dispatch_event(event);
+        }
+        if self
+            .input_script
+            .as_ref()
+            .map_or(false, |script| script.is_empty())
+        {
+            log::info!("Input script finished at frame {}", self.frame_number);
+            self.stop_input_script();
+        }
+    }
+
+    /// Whether the movie is running. A paused player doesn't run frames when ticked.
     pub fn is_playing(&self) -> bool {
         self.is_playing
     }
 
+    /// Starts or pauses the movie.
     pub fn set_is_playing(&mut self, v: bool) {
         if v {
             // Allow auto-play after user gesture for web backends.
@@ -356,6 +988,256 @@ impl Player {
         self.is_playing = v;
     }
 
+    /// Whether the player is paused with `set_paused`.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Pauses or resumes the player.
+    ///
+    /// A paused player keeps all of its state, but doesn't run frames or advance
+    /// its clock, and its audio is suspended until it is resumed.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        if self.is_paused != is_paused {
+            self.is_paused = is_paused;
+            self.audio.set_paused(is_paused);
+            // Don't catch up on the frames that would have run while paused.
+            self.frame_accumulator = 0.0;
+        }
+    }
+
+    /// Runs a single frame and pauses the player, for stepping through a movie
+    /// frame by frame. The clock advances by the length of one frame.
+    pub fn step_frame(&mut self) {
+        self.set_paused(true);
+        if !self.audio.is_loading_complete() || self.has_pending_imports() {
+            return;
+        }
+        if !self.is_deterministic {
+            self.global_time += 1000.0 / self.frame_rate();
+        }
+        self.run_frame();
+        self.audio.tick();
+    }
+
+    /// The volume of the player's audio, from 0.0 to 1.0.
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Sets the volume of the player's audio, from 0.0 to 1.0.
+    ///
+    /// This scales the final mix, and isn't visible to the movie through its sound transforms.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.max(0.0).min(1.0);
+        self.update_output_volume();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    /// Silences all audio without changing the volume, like when a browser only
+    /// allows audio to play after a user gesture.
+    pub fn set_muted(&mut self, is_muted: bool) {
+        self.is_muted = is_muted;
+        self.update_output_volume();
+    }
+
+    /// Adds a global function to AVM1 that calls back into the application.
+    ///
+    /// Functions should be registered before the movie starts playing, so that they
+    /// exist when the first frame runs.
+    pub fn register_host_function(
+        &mut self,
+        name: &str,
+        function: impl 'static + Fn(&[HostValue]) -> HostValue,
+    ) {
+        self.define_host_function(name, Rc::new(function));
+    }
+
+    fn define_host_function(&mut self, name: &str, function: Rc<HostFunction>) {
+        self.mutate_with_update_context(|avm, context| {
+            let function =
+                host::host_function(context.gc_context, function, avm.prototypes().function);
+            avm.global_object_cell().define_value(
+                context.gc_context,
+                name,
+                function.into(),
+                EnumSet::empty(),
+            );
+        });
+    }
+
+    /// Adds a global object to AVM1 whose methods call back into the application,
+    /// such as `Host` for `Host.saveScore()`.
+    ///
+    /// Objects should be registered before the movie starts playing, so that they
+    /// exist when the first frame runs.
+    pub fn register_host_object(&mut self, name: &str, object: &HostObject) {
+        self.mutate_with_update_context(|avm, context| {
+            let object = object.create(
+                context.gc_context,
+                avm.prototypes().object,
+                avm.prototypes().function,
+            );
+            avm.global_object_cell().define_value(
+                context.gc_context,
+                name,
+                object.into(),
+                EnumSet::empty(),
+            );
+        });
+    }
+
+    /// Sets how long loads of movies and data may take before they fail.
+    /// `None` lets them take as long as they need.
+    pub fn set_load_timeout(&mut self, timeout: Option<std::time::Duration>) {
+        self.mutate_with_update_context(|_avm, context| {
+            context.load_manager.set_timeout(timeout);
+        });
+    }
+
+    /// Logs each AVM1 action as it runs, with the values on the stack, to compare how
+    /// scripts run with Flash Player. See `Avm1::set_trace_actions`.
+    pub fn set_trace_avm1(&mut self, enabled: bool) {
+        self.mutate_with_update_context(|avm, _context| {
+            avm.set_trace_actions(enabled);
+        });
+    }
+
+    /// Starts recording how long ActionScript, tags, tessellation, rendering, audio and
+    /// garbage collection take each frame, keeping the timings of the last `frames`
+    /// frames.
+    ///
+    /// The clock should be precise to at least a microsecond, such as `Instant` or
+    /// `performance.now()`.
+    pub fn start_profiling(&mut self, clock: Clock, frames: usize) {
+        self.profiler.enable(clock, frames);
+    }
+
+    /// Stops recording frame timings. The timings recorded so far are kept.
+    pub fn stop_profiling(&mut self) {
+        self.profiler.disable();
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_enabled()
+    }
+
+    /// The timings of the last frames that finished running and rendering, oldest
+    /// first. A frame's timings include rendering it, so they are only available once
+    /// the next frame starts.
+    pub fn frame_timings(&self) -> Vec<FrameTimings> {
+        self.profiler.frames().copied().collect()
+    }
+
+    /// Returns the state of the garbage-collected heap.
+    ///
+    /// Counting the objects walks the display list and the objects reachable from it,
+    /// so this is best called occasionally, such as once a second.
+    pub fn gc_stats(&mut self) -> GcStats {
+        let (display_objects, avm1_objects) = self.mutate_with_update_context(|avm, context| {
+            fn count(object: DisplayObject<'_>) -> usize {
+                1 + object.children().map(count).sum::<usize>()
+            }
+            let display_objects = context.levels.values().copied().map(count).sum();
+            let avm1_objects = inspector::avm1_objects(avm, context).len();
+            (display_objects, avm1_objects)
+        });
+        let allocated_bytes = self.gc_arena.total_allocated();
+        GcStats {
+            allocated_bytes,
+            peak_allocated_bytes: self.peak_allocated_bytes.max(allocated_bytes),
+            allocation_debt: self.gc_arena.allocation_debt(),
+            display_objects,
+            avm1_objects,
+        }
+    }
+
+    /// Collects all garbage now, instead of a little at a time, such as before taking
+    /// `gc_stats` to see how much memory is still in use.
+    pub fn collect_garbage(&mut self) {
+        let span = self.profiler.start();
+        self.gc_arena.collect_all();
+        self.profiler.record(Subsystem::GarbageCollection, span);
+    }
+
+    /// The features the movie used since it started that aren't implemented, such as
+    /// AVM1 actions, tags and built-in methods, with how often and from which frame
+    /// they were used.
+    pub fn unimplemented_features(&self) -> &UnimplementedFeatures {
+        &self.unimplemented_features
+    }
+
+    /// Takes a snapshot of the display list, with a tree for each level.
+    ///
+    /// Frontends can take one each frame to show an inspector panel, and tests can
+    /// check the objects a movie has on the stage.
+    pub fn display_list(&mut self) -> Vec<DisplayObjectInfo> {
+        self.mutate_with_update_context(|_avm, context| {
+            context
+                .levels
+                .values()
+                .map(|level| DisplayObjectInfo::new(*level))
+                .collect()
+        })
+    }
+
+    /// Lists the AVM1 objects reachable from the display list and `_global`, with
+    /// their properties.
+    pub fn avm1_objects(&mut self) -> Vec<ObjectInfo> {
+        self.mutate_with_update_context(|avm, context| inspector::avm1_objects(avm, context))
+    }
+
+    /// Looks up the AVM1 object at a path, such as `_root.player.inventory`.
+    pub fn avm1_object(&mut self, path: &str) -> Option<ObjectInfo> {
+        self.mutate_with_update_context(|avm, context| inspector::avm1_object(avm, context, path))
+    }
+
+    /// Reads a variable or property of the movie by its path, such as
+    /// `_root.player.score`, without running any ActionScript of the host's own.
+    pub fn get_avm1_value(&mut self, path: &str) -> HostValue {
+        self.mutate_with_update_context(|avm, context| {
+            inspector::get_avm1_value(avm, context, path)
+        })
+    }
+
+    /// Sets a variable or property of the movie by its path. Watchers and setters
+    /// run as they do when the movie sets it.
+    pub fn set_avm1_value(&mut self, path: &str, value: HostValue) {
+        self.update(|avm, context| inspector::set_avm1_value(avm, context, path, value));
+    }
+
+    /// Attaches a debugger UI, which can pause the movie's AVM1 code at breakpoints
+    /// and inspect it. See `debugger` for the messages it exchanges.
+    pub fn attach_debugger(&mut self, connection: Box<dyn DebuggerConnection>) {
+        self.mutate_with_update_context(|avm, _context| {
+            avm.set_debugger(Some(Debugger::new(connection)));
+        });
+    }
+
+    /// Sets the most loads that may download at once.
+    pub fn set_max_connections(&mut self, max_connections: usize) {
+        self.mutate_with_update_context(|_avm, context| {
+            context.load_manager.set_max_connections(max_connections);
+        });
+    }
+
+    /// Limits the download speed of loads to the given number of bytes per second,
+    /// to see how a movie and its preloader behave on a slow connection.
+    pub fn set_bandwidth_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.mutate_with_update_context(|_avm, context| {
+            context.load_manager.set_bandwidth_limit(bytes_per_second);
+        });
+    }
+
+    fn update_output_volume(&mut self) {
+        let volume = if self.is_muted { 0.0 } else { self.volume };
+        self.audio.set_volume(volume);
+    }
+
+    /// Whether the stage changed since it was last rendered.
     pub fn needs_render(&self) -> bool {
         self.needs_render
     }
@@ -368,17 +1250,245 @@ impl Player {
         self.movie_height
     }
 
+    /// The SWF version of the movie.
+    pub fn swf_version(&self) -> u8 {
+        self.swf.version()
+    }
+
     pub fn viewport_dimensions(&self) -> (u32, u32) {
         (self.viewport_width, self.viewport_height)
     }
 
-    pub fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.device_pixel_ratio
+    }
+
+    /// Sets the size of the viewport in device pixels, and how many device pixels make up
+    /// a logical pixel.
+    ///
+    /// The movie is scaled up by `device_pixel_ratio` in `noScale` mode, so that it looks
+    /// the same size on high-DPI displays. Mouse events are still given in device pixels.
+    pub fn set_viewport_dimensions(&mut self, width: u32, height: u32, device_pixel_ratio: f64) {
+        let old_stage_size = self.stage_size();
         self.viewport_width = width;
         self.viewport_height = height;
+        self.device_pixel_ratio = device_pixel_ratio;
+        self.build_matrices();
+        self.needs_render = true;
+
+        // The stage is only resized in `noScale` mode.
+        if self.stage_size() != old_stage_size {
+            self.update(|_avm, context| {
+                context.action_queue.queue_actions(
+                    *context.levels.get(&0).expect("root level"),
+                    ActionType::NotifyListeners {
+                        listener: SystemListener::Stage,
+                        method: "onResize",
+                        args: vec![],
+                    },
+                    false,
+                );
+            });
+        }
+    }
+
+    pub fn scale_mode(&self) -> StageScaleMode {
+        self.scale_mode
+    }
+
+    pub fn set_scale_mode(&mut self, scale_mode: StageScaleMode) {
+        self.scale_mode = scale_mode;
+        self.build_matrices();
+        self.needs_render = true;
+    }
+
+    pub fn stage_align(&self) -> StageAlign {
+        self.stage_align
+    }
+
+    pub fn set_stage_align(&mut self, stage_align: StageAlign) {
+        self.stage_align = stage_align;
         self.build_matrices();
+        self.needs_render = true;
+    }
+
+    pub fn quality(&self) -> StageQuality {
+        self.quality
+    }
+
+    pub fn set_quality(&mut self, quality: StageQuality) {
+        self.quality = quality;
+        self.renderer.set_quality(quality);
+        self.needs_render = true;
+    }
+
+    pub fn display_state(&self) -> StageDisplayState {
+        self.display_state
+    }
+
+    /// Tells the movie that the frontend entered or left fullscreen on its own, such
+    /// as with a keyboard shortcut. The frontend must then resize the viewport to match.
+    pub fn set_display_state(&mut self, display_state: StageDisplayState) {
+        if self.display_state != display_state {
+            self.display_state = display_state;
+            let is_full_screen = display_state == StageDisplayState::FullScreen;
+            self.update(|_avm, context| {
+                context.action_queue.queue_actions(
+                    *context.levels.get(&0).expect("root level"),
+                    ActionType::NotifyListeners {
+                        listener: SystemListener::Stage,
+                        method: "onFullScreen",
+                        args: vec![is_full_screen.into()],
+                    },
+                    false,
+                );
+            });
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        self.window_mode
+    }
+
+    /// Whether the full context menu is shown. Movies can hide it with `Stage.showMenu`.
+    pub fn show_menu(&self) -> bool {
+        self.show_menu
+    }
+
+    /// Builds the context menu for a right-click on the object under the mouse: the
+    /// items the movie added with `ContextMenu`, followed by the player's built-in
+    /// items, unless the movie hides them. The movie's `onSelect` handler for the
+    /// menu runs first.
+    ///
+    /// Frontends show these items along with their own, and call
+    /// `run_context_menu_callback` with the index of the chosen one.
+    pub fn prepare_context_menu(&mut self) -> Vec<ContextMenuItem> {
+        let show_menu = self.show_menu;
+        let is_looping = self.is_looping;
+        self.update(|avm, context| {
+            match context_menu::build_context_menu(avm, context, true, show_menu, is_looping) {
+                Ok(items) => items.into_iter().map(|(item, _)| item).collect(),
+                Err(e) => {
+                    log::error!("Unable to build the context menu: {}", e);
+                    vec![]
+                }
+            }
+        })
+    }
+
+    /// Runs the item at `index` of the menu returned by `prepare_context_menu`.
+    pub fn run_context_menu_callback(&mut self, index: usize) {
+        let show_menu = self.show_menu;
+        let mut is_looping = self.is_looping;
+        self.update(|avm, context| {
+            let result =
+                context_menu::build_context_menu(avm, context, false, show_menu, is_looping)
+                    .and_then(|mut items| {
+                        if index >= items.len() {
+                            return Ok(());
+                        }
+                        match items.swap_remove(index).1 {
+                            ContextMenuCallback::Loop => {
+                                is_looping = !is_looping;
+                                Ok(())
+                            }
+                            callback => {
+                                context_menu::run_context_menu_callback(avm, context, callback)
+                            }
+                        }
+                    });
+            if let Err(e) = result {
+                log::error!("Unable to run the context menu item: {}", e);
+            }
+        });
+        self.is_looping = is_looping;
+        self.needs_render = true;
+    }
+
+    /// Applies the parameters the movie is embedded with.
+    ///
+    /// This must be called before the first frame runs, so that the movie sees its
+    /// `FlashVars` as variables on `_root`.
+    pub fn apply_parameters(&mut self, parameters: &PlayerParameters) {
+        if let Some(quality) = parameters.quality {
+            self.set_quality(quality);
+        }
+        if let Some(scale_mode) = parameters.scale_mode {
+            self.set_scale_mode(scale_mode);
+        }
+        if let Some(stage_align) = parameters.stage_align {
+            self.set_stage_align(stage_align);
+        }
+        self.window_mode = parameters.window_mode;
+        self.background_color_override = parameters.background_color.clone();
+        self.show_menu = parameters.show_menu;
+        self.is_looping = parameters.is_looping;
+
+        self.set_flash_vars(&parameters.flash_vars);
+        self.needs_render = true;
+    }
+
+    /// Defines the given variables on `_root`, as the `FlashVars` embed parameter does.
+    pub fn set_flash_vars(&mut self, flash_vars: &[(String, String)]) {
+        self.mutate_with_update_context(|avm, context| {
+            let root = *context.levels.get(&0).unwrap();
+            let mut activation = Activation::from_nothing(
+                avm,
+                ActivationIdentifier::root("[FlashVars]"),
+                context.swf.version(),
+                avm.global_object_cell(),
+                context.gc_context,
+                root,
+            );
+            let object = root.object().coerce_to_object(&mut activation, context);
+            for (name, value) in flash_vars {
+                object.define_value(
+                    context.gc_context,
+                    name,
+                    value.clone().into(),
+                    EnumSet::empty(),
+                );
+            }
+        });
     }
 
+    /// The dimensions of the stage as reported to ActionScript by `Stage.width` and `Stage.height`.
+    ///
+    /// In `noScale` mode the stage is resized along with the viewport, in logical pixels;
+    /// in all other modes it always reports the size of the movie.
+    pub fn stage_size(&self) -> (u32, u32) {
+        if self.scale_mode == StageScaleMode::NoScale {
+            let dpr = self.device_pixel_ratio;
+            (
+                (f64::from(self.viewport_width) / dpr).round() as u32,
+                (f64::from(self.viewport_height) / dpr).round() as u32,
+            )
+        } else {
+            (self.movie_width, self.movie_height)
+        }
+    }
+
+    /// Passes an input event to the movie, such as a key press or mouse movement.
+    ///
+    /// Mouse coordinates are in device pixels, relative to the top left of the viewport.
     pub fn handle_event(&mut self, event: PlayerEvent) {
+        // The input script has control until it ends.
+        if self.input_script.is_some() {
+            return;
+        }
+        // Events given between frames are replayed before the next frame.
+        if let Some(recording) = &mut self.input_recording {
+            recording.push(self.frame_number + 1, event.clone());
+        }
+        self.dispatch_event(event);
+    }
+
+    fn dispatch_event(&mut self, event: PlayerEvent) {
+        if let PlayerEvent::Touch { id, phase, x, y } = event {
+            self.handle_touch(id, phase, x, y);
+            return;
+        }
+
         let mut needs_render = self.needs_render;
 
         if let PlayerEvent::KeyDown {
@@ -435,6 +1545,20 @@ impl Player {
             }
         }
 
+        if let PlayerEvent::MouseDown { .. } = event {
+            self.update_focus();
+        }
+
+        if let PlayerEvent::KeyDown { key_code } = event {
+            if self.handle_focus_key(key_code) {
+                needs_render = true;
+            }
+        }
+
+        if self.handle_text_field_input(&event) {
+            needs_render = true;
+        }
+
         // Propagate button events.
         let button_event = match event {
             // ASCII characters convert directly to keyPress button events.
@@ -505,7 +1629,14 @@ impl Player {
             });
         }
 
+        if let PlayerEvent::MouseWheel { delta } = event {
+            if self.handle_mouse_wheel(delta) {
+                needs_render = true;
+            }
+        }
+
         let mut is_mouse_down = self.is_mouse_down;
+        let mut is_mouse_over_pressed = self.is_mouse_over_pressed;
         self.mutate_with_update_context(|avm, context| {
             if let Some(node) = context.mouse_hovered_object {
                 if node.removed() {
@@ -517,6 +1648,7 @@ impl Player {
                 PlayerEvent::MouseDown { .. } => {
                     is_mouse_down = true;
                     needs_render = true;
+                    is_mouse_over_pressed = context.mouse_hovered_object.is_some();
                     if let Some(node) = context.mouse_hovered_object {
                         node.handle_clip_event(avm, context, ClipEvent::Press);
                     }
@@ -526,8 +1658,16 @@ impl Player {
                     is_mouse_down = false;
                     needs_render = true;
                     if let Some(node) = context.mouse_hovered_object {
-                        node.handle_clip_event(avm, context, ClipEvent::Release);
+                        if is_mouse_over_pressed {
+                            node.handle_clip_event(avm, context, ClipEvent::Release);
+                        } else {
+                            // The mouse was dragged off the pressed object; the object under
+                            // the mouse will get a roll over on the next hover update.
+                            node.handle_clip_event(avm, context, ClipEvent::ReleaseOutside);
+                            context.mouse_hovered_object = None;
+                        }
                     }
+                    is_mouse_over_pressed = false;
                 }
 
                 _ => (),
@@ -536,9 +1676,195 @@ impl Player {
             Self::run_actions(avm, context);
         });
         self.is_mouse_down = is_mouse_down;
+        self.is_mouse_over_pressed = is_mouse_over_pressed;
         self.needs_render = needs_render;
     }
 
+    /// Tracks a touch point, and emulates the mouse with the primary touch point.
+    ///
+    /// A tap moves the mouse before pressing it, so that buttons roll over first
+    /// as they would with a real mouse. The mouse stays where the finger was lifted.
+    fn handle_touch(&mut self, id: u64, phase: TouchPhase, x: f64, y: f64) {
+        let position = self.inverse_view_matrix * (Twips::from_pixels(x), Twips::from_pixels(y));
+        let is_primary = self.primary_touch == Some(id);
+        match phase {
+            TouchPhase::Start => {
+                self.touch_points.insert(id, position);
+                if self.primary_touch.is_none() {
+                    self.primary_touch = Some(id);
+                    self.handle_event(PlayerEvent::MouseMove { x, y });
+                    self.handle_event(PlayerEvent::MouseDown { x, y });
+                }
+            }
+            TouchPhase::Move => {
+                if let Some(touch_point) = self.touch_points.get_mut(&id) {
+                    *touch_point = position;
+                }
+                if is_primary {
+                    self.handle_event(PlayerEvent::MouseMove { x, y });
+                }
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                self.touch_points.remove(&id);
+                if is_primary {
+                    self.primary_touch = None;
+                    self.handle_event(PlayerEvent::MouseUp { x, y });
+                }
+            }
+        }
+    }
+
+    /// The touch points currently on the screen by id, in stage coordinates.
+    pub fn touch_points(&self) -> impl Iterator<Item = (u64, (Twips, Twips))> + '_ {
+        self.touch_points
+            .iter()
+            .map(|(&id, &position)| (id, position))
+    }
+
+    /// Focuses the editable text field under the mouse, or removes focus when
+    /// clicking elsewhere.
+    fn update_focus(&mut self) {
+        let mouse_pos = self.mouse_pos;
+        self.is_focus_rect_visible = false;
+        self.mutate_with_update_context(|_avm, context| {
+            let text_field = context
+                .levels
+                .values()
+                .rev()
+                .find_map(|level| {
+                    level
+                        .as_movie_clip()
+                        .and_then(|level| level.find_text_field(mouse_pos))
+                })
+                .filter(|text_field| text_field.is_editable());
+            focus_tracker::set_focus(context, text_field.map(DisplayObject::from));
+        });
+    }
+
+    /// Moves focus with the Tab key, and presses the focused button with Enter or Space.
+    /// Returns whether the focus changed.
+    fn handle_focus_key(&mut self, key_code: KeyCode) -> bool {
+        match key_code {
+            KeyCode::Tab => {
+                let reverse = self.input.is_key_down(KeyCode::Shift);
+                self.mutate_with_update_context(|avm, context| {
+                    if let Some(next) = focus_tracker::next_focus(avm, context, reverse) {
+                        focus_tracker::set_focus(context, Some(next));
+                    }
+                });
+                self.is_focus_rect_visible = true;
+                true
+            }
+            KeyCode::Return | KeyCode::Space if self.is_focus_rect_visible => {
+                self.mutate_with_update_context(|avm, context| {
+                    if let Some(focus) = *context.focus {
+                        if !focus.removed() && focus.as_edit_text().is_none() {
+                            focus.handle_clip_event(avm, context, ClipEvent::Press);
+                            focus.handle_clip_event(avm, context, ClipEvent::Release);
+                        }
+                    }
+                });
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Sends typed text, editing keys and input method compositions to the focused
+    /// text field. Returns whether the text field changed.
+    fn handle_text_field_input(&mut self, event: &PlayerEvent) -> bool {
+        self.mutate_with_update_context(|avm, context| {
+            let text_field = match context.focus.and_then(|focus| focus.as_edit_text()) {
+                Some(text_field) if !text_field.removed() => text_field,
+                _ => return false,
+            };
+
+            let is_text_changed = match event {
+                PlayerEvent::TextInput { codepoint } if !codepoint.is_control() => {
+                    text_field.type_text(&codepoint.to_string(), context);
+                    true
+                }
+                PlayerEvent::KeyDown { key_code } => text_field.handle_key_down(*key_code, context),
+                PlayerEvent::Ime(ImeEvent::Preedit(text)) => {
+                    text_field.set_composition(Some(text.clone()), context);
+                    return true;
+                }
+                PlayerEvent::Ime(ImeEvent::Commit(text)) => {
+                    text_field.set_composition(None, context);
+                    text_field.type_text(text, context);
+                    true
+                }
+                PlayerEvent::Ime(ImeEvent::Cancel) => {
+                    text_field.set_composition(None, context);
+                    return true;
+                }
+                _ => false,
+            };
+
+            if is_text_changed {
+                if let Value::Object(_) = text_field.object() {
+                    avm.run_with_stack_frame_for_display_object(
+                        text_field.into(),
+                        text_field.swf_version(),
+                        context,
+                        |activation, context| {
+                            text_field.propagate_text_binding(activation, context);
+                        },
+                    );
+                }
+            }
+            is_text_changed
+        })
+    }
+
+    /// Scrolls the text field under the mouse, and notifies `Mouse.onMouseWheel` listeners.
+    /// Returns whether a text field was scrolled.
+    fn handle_mouse_wheel(&mut self, delta: MouseWheelDelta) -> bool {
+        // Flash only reports whole lines.
+        self.mouse_wheel_remainder += delta.lines();
+        if self.mouse_wheel_remainder.abs() < 1.0 {
+            return false;
+        }
+        let lines = self.mouse_wheel_remainder.trunc();
+        self.mouse_wheel_remainder -= lines;
+
+        let mouse_pos = self.mouse_pos;
+        self.mutate_with_update_context(|_avm, context| {
+            let text_field = context.levels.values().rev().find_map(|level| {
+                level
+                    .as_movie_clip()
+                    .and_then(|level| level.find_text_field(mouse_pos))
+            });
+
+            let mut is_scrolled = false;
+            if let Some(text_field) =
+                text_field.filter(|text_field| text_field.is_mouse_wheel_enabled())
+            {
+                // Scrolling the wheel up shows earlier lines.
+                let old_scroll = text_field.scroll();
+                let scroll = (old_scroll as f64 - lines).max(1.0) as usize;
+                text_field.set_scroll(context.gc_context, scroll);
+                is_scrolled = text_field.scroll() != old_scroll;
+            }
+
+            // The scroll target is the object under the mouse.
+            let scroll_target = text_field
+                .map(DisplayObject::from)
+                .or(context.mouse_hovered_object)
+                .map_or(Value::Undefined, |object| object.object());
+            context.action_queue.queue_actions(
+                *context.levels.get(&0).expect("root level"),
+                ActionType::NotifyListeners {
+                    listener: SystemListener::Mouse,
+                    method: "onMouseWheel",
+                    args: vec![lines.into(), scroll_target],
+                },
+                false,
+            );
+            is_scrolled
+        })
+    }
+
     /// Update dragged object, if any.
     fn update_drag(&mut self) {
         let mouse_pos = self.mouse_pos;
@@ -562,6 +1888,16 @@ impl Player {
                     drag_object
                         .display_object
                         .set_y(context.gc_context, drag_point.1.to_pixels());
+
+                    // Update `_droptarget` with the topmost clip under the cursor.
+                    if let Some(clip) = drag_object.display_object.as_movie_clip() {
+                        let drop_target = context.levels.values().rev().find_map(|level| {
+                            level.as_movie_clip().and_then(|level| {
+                                level.find_drop_target(mouse_pos, drag_object.display_object)
+                            })
+                        });
+                        clip.set_drop_target(context.gc_context, drop_target);
+                    }
                 }
             }
         });
@@ -570,9 +1906,9 @@ impl Player {
     /// Checks to see if a recent update has caused the current mouse hover
     /// node to change.
     fn update_roll_over(&mut self) -> bool {
-        // TODO: While the mouse is down, maintain the hovered node.
+        // While the mouse is down, the pressed node stays hovered and gets drag events instead.
         if self.is_mouse_down {
-            return false;
+            return self.update_drag_over();
         }
         let mouse_pos = self.mouse_pos;
 
@@ -602,78 +1938,310 @@ impl Player {
                 // RollOver on new node.
                 new_cursor = MouseCursor::Arrow;
                 if let Some(node) = new_hovered {
-                    new_cursor = MouseCursor::Hand;
+                    let swf_version = context.swf.version();
+                    let use_hand_cursor = get_avm1_property(avm, context, node, "useHandCursor")
+                        .map(|value| value.as_bool(swf_version))
+                        .unwrap_or(true);
+                    if use_hand_cursor {
+                        new_cursor = MouseCursor::Hand;
+                    }
                     node.handle_clip_event(avm, context, ClipEvent::RollOver);
                 }
 
-                context.mouse_hovered_object = new_hovered;
+                context.mouse_hovered_object = new_hovered;
+
+                Self::run_actions(avm, context);
+                true
+            } else {
+                false
+            }
+        });
+
+        // Update mouse cursor if it has changed.
+        if new_cursor != self.mouse_cursor {
+            self.mouse_cursor = new_cursor;
+            self.input.set_mouse_cursor(new_cursor)
+        }
+
+        hover_changed
+    }
+
+    /// Checks whether the mouse has been dragged on or off the node that it was pressed on,
+    /// firing `DragOver` and `DragOut` events.
+    ///
+    /// Buttons that track as menus also release the mouse to other menu buttons that it is
+    /// dragged over.
+    fn update_drag_over(&mut self) -> bool {
+        let mouse_pos = self.mouse_pos;
+        let mut is_mouse_over_pressed = self.is_mouse_over_pressed;
+        let changed = self.mutate_with_update_context(|avm, context| {
+            let pressed = match context.mouse_hovered_object {
+                Some(node) if !node.removed() => node,
+                _ => return false,
+            };
+
+            let mut new_hovered = None;
+            for (_depth, level) in context.levels.clone().iter().rev() {
+                if new_hovered.is_none() {
+                    new_hovered =
+                        level.mouse_pick(avm, context, *level, (mouse_pos.0, mouse_pos.1));
+                } else {
+                    break;
+                }
+            }
+
+            let is_over = new_hovered.map(|d| d.as_ptr()) == Some(pressed.as_ptr());
+            let changed = match new_hovered {
+                Some(new_hovered)
+                    if !is_over
+                        && Self::is_tracked_as_menu(avm, context, pressed)
+                        && Self::is_tracked_as_menu(avm, context, new_hovered) =>
+                {
+                    if is_mouse_over_pressed {
+                        pressed.handle_clip_event(avm, context, ClipEvent::DragOut);
+                    }
+                    new_hovered.handle_clip_event(avm, context, ClipEvent::DragOver);
+                    context.mouse_hovered_object = Some(new_hovered);
+                    is_mouse_over_pressed = true;
+                    true
+                }
+                _ if is_over != is_mouse_over_pressed => {
+                    let event = if is_over {
+                        ClipEvent::DragOver
+                    } else {
+                        ClipEvent::DragOut
+                    };
+                    pressed.handle_clip_event(avm, context, event);
+                    is_mouse_over_pressed = is_over;
+                    true
+                }
+                _ => false,
+            };
 
+            if changed {
                 Self::run_actions(avm, context);
-                true
-            } else {
-                false
             }
+            changed
         });
+        self.is_mouse_over_pressed = is_mouse_over_pressed;
+        changed
+    }
 
-        // Update mouse cursor if it has changed.
-        if new_cursor != self.mouse_cursor {
-            self.mouse_cursor = new_cursor;
-            self.input.set_mouse_cursor(new_cursor)
+    /// Whether a button or button-mode clip tracks the mouse as a menu item.
+    fn is_tracked_as_menu<'gc>(
+        avm: &mut Avm1<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        node: DisplayObject<'gc>,
+    ) -> bool {
+        let swf_version = context.swf.version();
+        node.as_button()
+            .map(|button| button.is_tracked_as_menu())
+            .unwrap_or(false)
+            || get_avm1_property(avm, context, node, "trackAsMenu")
+                .map(|value| value.as_bool(swf_version))
+                .unwrap_or(false)
+    }
+
+    /// Returns whether any movie is waiting for assets imported from another movie.
+    fn has_pending_imports(&mut self) -> bool {
+        self.gc_arena
+            .mutate(|_gc_context, gc_root| gc_root.0.read().library.has_pending_imports())
+    }
+
+    /// Continue the root movie with a newer version of it made by its
+    /// `SwfDownload`, which has more of the movie's data.
+    ///
+    /// The new frames are preloaded and played as usual. Nothing happens if
+    /// another movie has been loaded into `_level0` in the meantime.
+    pub fn append_movie_data(&mut self, movie: Arc<SwfMovie>) {
+        if !Arc::ptr_eq(self.swf.identity(), movie.identity()) {
+            return;
         }
 
-        hover_changed
+        self.swf = movie.clone();
+        self.mutate_with_update_context(|_avm, context| {
+            let root = context.levels.get(&0).and_then(|root| root.as_movie_clip());
+            if let Some(root) = root {
+                if Arc::ptr_eq(root.movie().unwrap().identity(), movie.identity()) {
+                    root.append_movie_data(context.gc_context, movie);
+                }
+            }
+        });
     }
 
-    /// Preload the first movie in the player.
+    /// Preload the next chunk of the first movie in the player.
     ///
-    /// This should only be called once. Further movie loads should preload the
-    /// specific `MovieClip` referenced.
+    /// At least one frame is preloaded per call, up to `PRELOAD_CHUNK_SIZE` bytes of tags, so
+    /// that large movies can start playing before they have been fully preloaded.
+    /// Further movie loads should preload the specific `MovieClip` referenced.
     fn preload(&mut self) {
         self.mutate_with_update_context(|activation, context| {
-            let mut morph_shapes = fnv::FnvHashMap::default();
             let root = *context.levels.get(&0).expect("root level");
-            root.as_movie_clip()
-                .unwrap()
-                .preload(activation, context, &mut morph_shapes);
+            let root = root.as_movie_clip().unwrap();
+            if root.is_fully_loaded() {
+                return;
+            }
 
-            // Finalize morph shapes.
-            for (id, static_data) in morph_shapes {
-                let morph_shape = MorphShape::new(context.gc_context, static_data);
-                context
-                    .library
-                    .library_for_movie_mut(root.as_movie_clip().unwrap().movie().unwrap())
-                    .register_character(id, crate::character::Character::MorphShape(morph_shape));
+            let span = context.profiler.start();
+            let start = root.bytes_loaded();
+            loop {
+                let before = root.bytes_loaded();
+                if root.preload_streaming_frame(activation, context) {
+                    break;
+                }
+
+                // Stop at the end of the data that has been downloaded so far.
+                let bytes_loaded = root.bytes_loaded();
+                if bytes_loaded == before || bytes_loaded - start >= PRELOAD_CHUNK_SIZE {
+                    break;
+                }
             }
+            context.profiler.record(Subsystem::Tags, span);
         });
     }
 
+    /// Runs a single frame of the movie, regardless of the frame rate.
     pub fn run_frame(&mut self) {
-        self.update(|avm, update_context| {
+        self.frame_number += 1;
+        self.profiler.begin_frame(self.frame_number);
+        self.unimplemented_features.set_frame(self.frame_number);
+        self.preload();
+        if self.is_deterministic {
+            self.global_time += 1000.0 / self.frame_rate();
+        }
+        self.play_scripted_input();
+        let is_looping = self.is_looping;
+        let unloaded_clips = self.update(|avm, update_context| {
+            // Clips unloaded before this frame are marked as removed once its actions run.
+            let unloaded_clips = update_context.unloaded_clips.len();
+
+            // With the `loop` parameter off, the root movie stops on its last frame
+            // instead of returning to its first.
+            if !is_looping {
+                if let Some(root) = update_context
+                    .levels
+                    .get(&0)
+                    .and_then(|root| root.as_movie_clip())
+                {
+                    if root.playing() && root.current_frame() >= root.total_frames() {
+                        root.stop(update_context);
+                    }
+                }
+            }
+
             // TODO: In what order are levels run?
             // NOTE: We have to copy all the layer pointers into a separate list
             // because level updates can create more levels, which we don't
             // want to run frames on
             let levels: Vec<_> = update_context.levels.values().copied().collect();
 
+            // A frame runs in phases: `enterFrame` is broadcast to every clip before
+            // any frame actions run, and the stage is rendered afterward.
+            let span = update_context.profiler.start();
+            for mut level in levels.iter().copied() {
+                level.enter_frame(avm, update_context);
+            }
+
             for mut level in levels {
                 level.run_frame(avm, update_context);
             }
+            update_context.profiler.record(Subsystem::Tags, span);
+
+            LocalConnections::deliver_messages(avm, update_context);
+            #[cfg(feature = "rtmp")]
+            RtmpConnections::poll(avm, update_context);
+
+            let now = update_context.navigator.time_since_launch();
+            update_context.load_manager.update(now);
+
+            // Fire `onSoundComplete` for sounds that finished since the last frame.
+            let span = update_context.profiler.start();
+            let completed_sounds = update_context
+                .audio_manager
+                .update_sounds(update_context.gc_context, update_context.audio);
+            update_context.profiler.record(Subsystem::Audio, span);
+            for sound in completed_sounds {
+                let clip = sound
+                    .owner()
+                    .or_else(|| update_context.levels.get(&0).copied());
+                if let Some(clip) = clip {
+                    update_context.action_queue.queue_actions(
+                        clip,
+                        ActionType::Method {
+                            object: sound.into(),
+                            name: "onSoundComplete",
+                            args: vec![],
+                        },
+                        false,
+                    );
+                }
+            }
+
+            let mut activation = Activation::from_nothing(
+                avm,
+                ActivationIdentifier::root("[Accessibility]"),
+                update_context.swf.version(),
+                avm.global_object_cell(),
+                update_context.gc_context,
+                *update_context.levels.get(&0).unwrap(),
+            );
+            let _ =
+                crate::avm1::globals::accessibility::update_tree(&mut activation, update_context);
+
+            unloaded_clips
+        });
+        self.mutate_with_update_context(|_avm, context| {
+            for mut clip in context.unloaded_clips.drain(..unloaded_clips) {
+                clip.set_removed(context.gc_context, true);
+            }
         });
         self.needs_render = true;
     }
 
+    /// Draws the stage with the renderer.
     pub fn render(&mut self) {
+        let span = self.profiler.start();
+        let background_color = self.render_background_color();
+        self.renderer.begin_frame(background_color);
+        self.render_stage();
+        self.renderer.end_frame();
+        self.needs_render = false;
+        self.profiler.record(Subsystem::Rendering, span);
+    }
+
+    /// The color the viewport is cleared to before the stage is drawn.
+    fn render_background_color(&self) -> Color {
+        if self.window_mode == WindowMode::Transparent {
+            Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            }
+        } else {
+            self.background_color_override
+                .clone()
+                .unwrap_or_else(|| self.background_color.clone())
+        }
+    }
+
+    /// Draws the stage into the frame that the renderer is rendering.
+    fn render_stage(&mut self) {
+        // The region of the stage that is visible in the viewport. This can be larger
+        // than the movie in `noScale` mode, or smaller when cropped in `noBorder` mode.
         let view_bounds = BoundingBox {
             x_min: Twips::new(0),
             y_min: Twips::new(0),
-            x_max: Twips::from_pixels(self.movie_width.into()),
-            y_max: Twips::from_pixels(self.movie_height.into()),
+            x_max: Twips::from_pixels(self.viewport_width.into()),
+            y_max: Twips::from_pixels(self.viewport_height.into()),
             valid: true,
-        };
-
-        self.renderer.begin_frame(self.background_color.clone());
+        }
+        .transform(&self.inverse_view_matrix);
 
         let (renderer, transform_stack) = (&mut self.renderer, &mut self.transform_stack);
+        let is_focus_rect_visible = self.is_focus_rect_visible;
+        let focus_rect = &mut self.focus_rect;
+        let profiler = &mut self.profiler;
 
         transform_stack.push(&crate::transform::Transform {
             matrix: self.view_matrix,
@@ -687,17 +2255,60 @@ impl Player {
                 transform_stack,
                 view_bounds,
                 clip_depth_stack: vec![],
+                scale9_grid: None,
+                profiler,
             };
 
             for (_depth, level) in root_data.levels.iter() {
                 level.render(&mut render_context);
             }
+
+            // Text fields show their cursor instead of a focus rectangle.
+            let focus = root_data
+                .focus
+                .filter(|focus| !focus.removed() && focus.as_edit_text().is_none());
+            if let (true, Some(focus)) = (is_focus_rect_visible, focus) {
+                let root_focus_rect = root_data.levels.get(&0).and_then(|root| root.focus_rect());
+                if focus.focus_rect().or(root_focus_rect).unwrap_or(true) {
+                    draw_focus_rect(focus_rect, &focus.world_bounds());
+                    focus_rect.render(&mut render_context);
+                }
+            }
         });
         transform_stack.pop();
 
         self.renderer.draw_letterbox(self.letterbox);
-        self.renderer.end_frame();
-        self.needs_render = false;
+    }
+
+    /// Renders the current frame and returns it as an RGBA image, such as for
+    /// screenshots. Returns `None` if the render backend can't read back frames.
+    ///
+    /// Backends that can't read back the frames they show, such as a window's swap
+    /// chain, render the frame again offscreen instead.
+    pub fn capture_frame(&mut self) -> Option<Bitmap> {
+        self.render();
+        if let Some(frame) = self.renderer.capture_frame() {
+            return Some(frame);
+        }
+
+        let background_color = self.render_background_color();
+        if !self.renderer.begin_offscreen_frame(
+            self.viewport_width,
+            self.viewport_height,
+            background_color,
+        ) {
+            return None;
+        }
+        self.render_stage();
+        self.renderer.end_offscreen_frame()
+    }
+
+    /// Renders the current frame and returns it as an SVG document, for archiving or
+    /// extracting art. Returns `None` unless the render backend can export vectors,
+    /// such as `ruffle_render_svg`.
+    pub fn export_frame_svg(&mut self) -> Option<String> {
+        self.render();
+        self.renderer.export_svg()
     }
 
     pub fn audio(&self) -> &Audio {
@@ -708,11 +2319,48 @@ impl Player {
         &mut self.audio
     }
 
-    // The frame rate of the current movie in FPS.
+    /// The frame rate that frames run at in FPS, which is the frame rate of the
+    /// movie unless it was overridden.
     pub fn frame_rate(&self) -> f64 {
+        self.frame_rate_override.unwrap_or(self.frame_rate)
+    }
+
+    /// The frame rate of the movie in FPS, as given in its header.
+    pub fn movie_frame_rate(&self) -> f64 {
         self.frame_rate
     }
 
+    /// Runs frames at the given frame rate instead of the frame rate of the movie,
+    /// or at the movie's frame rate again if `None`. Invalid frame rates are ignored.
+    pub fn set_frame_rate_override(&mut self, frame_rate: Option<f64>) {
+        match frame_rate {
+            Some(frame_rate) if !(frame_rate > 0.0 && frame_rate.is_finite()) => {
+                log::warn!("Ignoring invalid frame rate {}", frame_rate);
+            }
+            _ => {
+                self.frame_rate_override = frame_rate;
+                let frame_rate = self.frame_rate();
+                self.audio.set_frame_rate(frame_rate);
+            }
+        }
+    }
+
+    /// How fast time passes in the player, relative to real time.
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Sets how fast time passes in the player, such as 0.5 for slow motion or 2.0
+    /// to fast-forward. This scales the frame rate and `getTimer` alike.
+    /// Invalid scales are ignored.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        if time_scale > 0.0 && time_scale.is_finite() {
+            self.time_scale = time_scale;
+        } else {
+            log::warn!("Ignoring invalid time scale {}", time_scale);
+        }
+    }
+
     pub fn renderer(&self) -> &Renderer {
         &self.renderer
     }
@@ -721,15 +2369,43 @@ impl Player {
         &mut self.renderer
     }
 
+    pub fn navigator(&self) -> &Navigator {
+        &self.navigator
+    }
+
+    pub fn navigator_mut(&mut self) -> &mut dyn NavigatorBackend {
+        self.navigator.deref_mut()
+    }
+
+    pub fn log_mut(&mut self) -> &mut dyn LogBackend {
+        self.log.deref_mut()
+    }
+
+    /// Connects to RTMP servers with the given backend, instead of failing every connection.
+    #[cfg(feature = "rtmp")]
+    pub fn set_rtmp_backend(&mut self, rtmp: Rtmp) {
+        self.rtmp = rtmp;
+    }
+
+    /// The input backend of the embedder, even while an input script is playing.
     pub fn input(&self) -> &Input {
-        &self.input
+        match self.input.downcast_ref::<ScriptedInputBackend>() {
+            Some(scripted) => &scripted.inner,
+            None => &self.input,
+        }
     }
 
     pub fn input_mut(&mut self) -> &mut dyn InputBackend {
-        self.input.deref_mut()
+        if self.input.is::<ScriptedInputBackend>() {
+            let scripted = self.input.downcast_mut::<ScriptedInputBackend>().unwrap();
+            scripted.inner.deref_mut()
+        } else {
+            self.input.deref_mut()
+        }
     }
 
     fn run_actions<'gc>(avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let span = context.profiler.start();
         // Note that actions can queue further actions, so a while loop is necessary here.
         while let Some(actions) = context.action_queue.pop_action() {
             // We don't run frame actions if the clip was removed after it queued the action.
@@ -832,8 +2508,36 @@ impl Player {
                         &args,
                     );
                 }
+
+                // Construct the AVM2 object of a timeline clip
+                ActionType::ConstructAvm2 => {
+                    if let Some(clip) = actions.clip.as_movie_clip() {
+                        clip.construct_avm2_object(context);
+                    }
+                }
+
+                // AVM2 frame script or event listener call
+                ActionType::CallAvm2 {
+                    callable,
+                    receiver,
+                    args,
+                } => {
+                    if let Err(e) =
+                        Avm2::run_stack_frame_for_callable(callable, receiver, &args, context)
+                    {
+                        log::error!("Unhandled AVM2 exception: {}", e);
+                    }
+                }
+
+                // Goto requested by AVM2 code
+                ActionType::GotoAvm2 { frame, stop } => {
+                    if let Some(clip) = actions.clip.as_movie_clip() {
+                        clip.goto_frame(avm, context, frame, stop);
+                    }
+                }
             }
         }
+        context.profiler.record(Subsystem::Avm1, span);
     }
 
     fn build_matrices(&mut self) {
@@ -841,33 +2545,59 @@ impl Player {
         let (movie_width, movie_height) = (self.movie_width as f32, self.movie_height as f32);
         let (viewport_width, viewport_height) =
             (self.viewport_width as f32, self.viewport_height as f32);
-        let movie_aspect = movie_width / movie_height;
-        let viewport_aspect = viewport_width / viewport_height;
-        let (scale, margin_width, margin_height) = if viewport_aspect > movie_aspect {
-            let scale = viewport_height / movie_height;
-            (scale, (viewport_width - movie_width * scale) / 2.0, 0.0)
-        } else {
-            let scale = viewport_width / movie_width;
-            (scale, 0.0, (viewport_height - movie_height * scale) / 2.0)
+        let (scale_x, scale_y) = match self.scale_mode {
+            StageScaleMode::ShowAll => {
+                let scale = f32::min(viewport_width / movie_width, viewport_height / movie_height);
+                (scale, scale)
+            }
+            StageScaleMode::NoBorder => {
+                let scale = f32::max(viewport_width / movie_width, viewport_height / movie_height);
+                (scale, scale)
+            }
+            StageScaleMode::ExactFit => {
+                (viewport_width / movie_width, viewport_height / movie_height)
+            }
+            StageScaleMode::NoScale => {
+                let scale = self.device_pixel_ratio as f32;
+                (scale, scale)
+            }
         };
+
+        // The leftover space is split between the margins according to `Stage.align`.
+        // In `noBorder` and `noScale` modes, it may be negative, cropping the edges of the movie.
+        let (align_x, align_y) = self.stage_align.offset();
+        let extra_width = viewport_width - movie_width * scale_x;
+        let extra_height = viewport_height - movie_height * scale_y;
+        let margin_left = extra_width * align_x;
+        let margin_top = extra_height * align_y;
         self.view_matrix = Matrix {
-            a: scale,
+            a: scale_x,
             b: 0.0,
             c: 0.0,
-            d: scale,
-            tx: Twips::from_pixels(margin_width.into()),
-            ty: Twips::from_pixels(margin_height.into()),
+            d: scale_y,
+            tx: Twips::from_pixels(margin_left.into()),
+            ty: Twips::from_pixels(margin_top.into()),
         };
         self.inverse_view_matrix = self.view_matrix;
         self.inverse_view_matrix.invert();
 
         // Calculate letterbox dimensions.
+        // Only `showAll` mode leaves margins around the movie; `noScale` shows stage content
+        // in the extra space instead.
         // TODO: Letterbox should be an option; the original Flash Player defaults to showing content
         // in the extra margins.
-        self.letterbox = if margin_width > 0.0 {
-            Letterbox::Pillarbox(margin_width)
-        } else if margin_height > 0.0 {
-            Letterbox::Letterbox(margin_height)
+        self.letterbox = if self.scale_mode != StageScaleMode::ShowAll {
+            Letterbox::None
+        } else if extra_width > 0.0 {
+            Letterbox::Pillarbox {
+                left: margin_left,
+                right: extra_width - margin_left,
+            }
+        } else if extra_height > 0.0 {
+            Letterbox::Letterbox {
+                top: margin_top,
+                bottom: extra_height - margin_top,
+            }
         } else {
             Letterbox::None
         };
@@ -879,6 +2609,8 @@ impl Player {
     where
         F: for<'a, 'gc> FnOnce(&mut Avm1<'gc>, &mut UpdateContext<'a, 'gc, '_>) -> R,
     {
+        let stage_size = self.stage_size();
+
         // We have to do this piecewise borrowing of fields before the closure to avoid
         // completely borrowing `self`.
         let (
@@ -894,13 +2626,25 @@ impl Player {
             mouse_position,
             stage_width,
             stage_height,
+            stage_scale_mode,
+            stage_align,
+            stage_quality,
+            stage_display_state,
+            show_menu,
+            sound_buffer_time,
             player,
             system_properties,
             instance_counter,
             storage,
+            font,
+            printer,
+            accessibility,
+            log,
+            profiler,
+            unimplemented_features,
         ) = (
             self.player_version,
-            self.global_time,
+            self.global_time as u64,
             &self.swf,
             &mut self.background_color,
             self.renderer.deref_mut(),
@@ -909,54 +2653,85 @@ impl Player {
             self.input.deref_mut(),
             &mut self.rng,
             &self.mouse_pos,
-            Twips::from_pixels(self.movie_width.into()),
-            Twips::from_pixels(self.movie_height.into()),
+            Twips::from_pixels(stage_size.0.into()),
+            Twips::from_pixels(stage_size.1.into()),
+            &mut self.scale_mode,
+            &mut self.stage_align,
+            &mut self.quality,
+            &mut self.display_state,
+            &mut self.show_menu,
+            &mut self.sound_buffer_time,
             self.self_reference.clone(),
             &mut self.system,
             &mut self.instance_counter,
             self.storage.deref_mut(),
+            self.font.deref_mut(),
+            self.printer.deref_mut(),
+            self.accessibility.deref_mut(),
+            self.log.deref_mut(),
+            &mut self.profiler,
+            &mut self.unimplemented_features,
         );
+        #[cfg(feature = "rtmp")]
+        let rtmp = self.rtmp.deref_mut();
 
-        self.gc_arena.mutate(|gc_context, gc_root| {
+        let old_scale_mode = *stage_scale_mode;
+        let old_stage_align = *stage_align;
+
+        let ret = self.gc_arena.mutate(|gc_context, gc_root| {
             let mut root_data = gc_root.0.write(gc_context);
+            // Borrow the fields of the root separately, so that they can all be in the context.
+            let root_data = &mut *root_data;
+            let avm = &mut root_data.avm;
             let mouse_hovered_object = root_data.mouse_hovered_object;
-            let (
-                levels,
-                library,
-                action_queue,
-                avm,
-                drag_object,
-                load_manager,
-                shared_objects,
-                unbound_text_fields,
-            ) = root_data.update_context_params();
 
             let mut update_context = UpdateContext {
                 player_version,
                 global_time,
                 swf,
-                library,
+                library: &mut root_data.library,
                 background_color,
                 rng,
                 renderer,
                 audio,
                 navigator,
                 input,
-                action_queue,
+                action_queue: &mut root_data.action_queue,
                 gc_context,
-                levels,
+                levels: &mut root_data.levels,
                 mouse_hovered_object,
                 mouse_position,
-                drag_object,
+                drag_object: &mut root_data.drag_object,
+                focus: &mut root_data.focus,
                 stage_size: (stage_width, stage_height),
+                stage_scale_mode,
+                stage_align,
+                stage_quality,
+                stage_display_state,
+                show_menu,
+                sound_buffer_time,
                 system_prototypes: avm.prototypes().clone(),
+                avm2: &mut root_data.avm2,
                 player,
-                load_manager,
+                load_manager: &mut root_data.load_manager,
                 system: system_properties,
                 instance_counter,
                 storage,
-                shared_objects,
-                unbound_text_fields,
+                font,
+                printer,
+                accessibility,
+                shared_objects: &mut root_data.shared_objects,
+                unbound_text_fields: &mut root_data.unbound_text_fields,
+                unloaded_clips: &mut root_data.unloaded_clips,
+                local_connections: &mut root_data.local_connections,
+                #[cfg(feature = "rtmp")]
+                rtmp,
+                #[cfg(feature = "rtmp")]
+                rtmp_connections: &mut root_data.rtmp_connections,
+                log,
+                audio_manager: &mut root_data.audio_manager,
+                profiler,
+                unimplemented_features,
             };
 
             let ret = f(avm, &mut update_context);
@@ -964,7 +2739,15 @@ impl Player {
             // Hovered object may have been updated; copy it back to the GC root.
             root_data.mouse_hovered_object = update_context.mouse_hovered_object;
             ret
-        })
+        });
+
+        // ActionScript may have changed the scale mode or alignment; rebuild the view matrix.
+        if self.scale_mode != old_scale_mode || self.stage_align != old_stage_align {
+            self.build_matrices();
+            self.needs_render = true;
+        }
+
+        ret
     }
 
     /// Loads font data from the given buffer.
@@ -1009,11 +2792,50 @@ impl Player {
         self.update_roll_over();
 
         // GC
+        self.peak_allocated_bytes = self
+            .peak_allocated_bytes
+            .max(self.gc_arena.total_allocated());
+        let span = self.profiler.start();
         self.gc_arena.collect_debt();
+        self.profiler.record(Subsystem::GarbageCollection, span);
 
         rval
     }
 
+    /// Get the data of every shared object saved by the movie, as names and
+    /// data, so that frontends can back them up. Loaded shared objects are
+    /// saved first.
+    pub fn export_saves(&mut self) -> Vec<(String, Vec<u8>)> {
+        self.flush_shared_objects();
+        let storage = &self.storage;
+        storage
+            .get_keys()
+            .into_iter()
+            .filter_map(|name| {
+                let data = storage.get_bytes(&name)?;
+                Some((name, data))
+            })
+            .collect()
+    }
+
+    /// Save shared objects exported by `export_saves`, replacing any saves with
+    /// the same names. Returns whether all of them were saved.
+    ///
+    /// Shared objects that the movie already loaded keep their old data, and will
+    /// overwrite the imported data if the movie saves them again, so saves are best
+    /// imported before the movie starts.
+    pub fn import_saves(&mut self, saves: &[(String, Vec<u8>)]) -> bool {
+        self.mutate_with_update_context(|_avm, context| {
+            // Later calls to `getLocal` load the imported data.
+            for (name, _) in saves {
+                context.shared_objects.remove(name);
+            }
+        });
+        saves.iter().fold(true, |saved, (name, data)| {
+            self.storage.put_bytes(name, data) && saved
+        })
+    }
+
     pub fn flush_shared_objects(&mut self) {
         self.update(|avm, context| {
             let mut activation = Activation::from_nothing(
@@ -1049,3 +2871,556 @@ unsafe impl<'gc> gc_arena::Collect for DragObject<'gc> {
         self.display_object.trace(cc);
     }
 }
+
+/// Draws the yellow rectangle that Flash shows around the focused object.
+fn draw_focus_rect(drawing: &mut Drawing, bounds: &BoundingBox) {
+    drawing.clear();
+    drawing.set_line_style(Some(swf::LineStyle::new_v1(
+        Twips::from_pixels(2.0),
+        Color::from_rgb(0xFFFF00, 0xFF),
+    )));
+    drawing.draw_command(DrawCommand::MoveTo {
+        x: bounds.x_min,
+        y: bounds.y_min,
+    });
+    for &(x, y) in &[
+        (bounds.x_max, bounds.y_min),
+        (bounds.x_max, bounds.y_max),
+        (bounds.x_min, bounds.y_max),
+        (bounds.x_min, bounds.y_min),
+    ] {
+        drawing.draw_command(DrawCommand::LineTo { x, y });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::audio::{AudioStreamHandle, SoundHandle, SoundInstanceHandle};
+    use crate::backend::navigator::{NullExecutor, NullNavigatorBackend};
+    use crate::loader::Loader;
+    use crate::tag_utils::SwfDownload;
+    use crate::unimplemented::FeatureKind;
+    use std::cell::Cell;
+    use std::path::Path;
+    use std::time::Duration;
+
+    /// Creates a player for the movie in a folder of `tests/swfs`, with the executor
+    /// that runs its loads.
+    fn create_test_player(folder: &str) -> (Arc<Mutex<Player>>, NullExecutor) {
+        let swf_path = Path::new("tests/swfs").join(folder).join("test.swf");
+        let (executor, channel) = NullExecutor::new();
+        let navigator = NullNavigatorBackend::with_base_path(swf_path.parent().unwrap(), channel);
+        let player = PlayerBuilder::new()
+            .with_movie(SwfMovie::from_path(&swf_path).unwrap())
+            .with_navigator(navigator)
+            .build()
+            .unwrap();
+        (player, executor)
+    }
+
+    /// Plays the movie in a folder of `tests/swfs` for a number of frames.
+    fn run_test_movie(folder: &str, num_frames: u32) -> Arc<Mutex<Player>> {
+        let (player, mut executor) = create_test_player(folder);
+        for _ in 0..num_frames {
+            player.lock().unwrap().run_frame();
+            executor.block_all().unwrap();
+        }
+        player
+    }
+
+    /// The names of the objects masking and masked by the child of `_level0` with the
+    /// given name.
+    fn mask_links(player: &mut Player, name: &str) -> (Option<String>, Option<String>) {
+        player.mutate_with_update_context(|_avm, context| {
+            let clip = context.levels[&0]
+                .children()
+                .find(|child| &*child.name() == name)
+                .expect("the clip is on the stage");
+            let name_of =
+                |object: Option<DisplayObject<'_>>| object.map(|object| object.name().to_string());
+            (name_of(clip.masker()), name_of(clip.maskee()))
+        })
+    }
+
+    #[test]
+    fn set_mask_links_both_clips() {
+        let player = run_test_movie("avm1/set_mask", 1);
+        let mut player = player.lock().unwrap();
+        assert_eq!(
+            mask_links(&mut player, "masked"),
+            (Some("mask".to_string()), None)
+        );
+        assert_eq!(
+            mask_links(&mut player, "mask"),
+            (None, Some("masked".to_string()))
+        );
+    }
+
+    #[test]
+    fn set_mask_null_unlinks_both_clips() {
+        let player = run_test_movie("avm1/set_mask", 2);
+        let mut player = player.lock().unwrap();
+        assert_eq!(mask_links(&mut player, "masked"), (None, None));
+        assert_eq!(mask_links(&mut player, "mask"), (None, None));
+    }
+
+    #[test]
+    fn removing_masked_clip_unlinks_mask() {
+        let player = run_test_movie("avm1/set_mask", 4);
+        let mut player = player.lock().unwrap();
+        assert_eq!(mask_links(&mut player, "mask"), (None, None));
+    }
+
+    #[test]
+    fn removing_mask_unlinks_masked_clip() {
+        let player = run_test_movie("avm1/set_mask", 6);
+        let mut player = player.lock().unwrap();
+        assert_eq!(mask_links(&mut player, "masked2"), (None, None));
+    }
+
+    #[test]
+    fn display_list_shows_created_clips() {
+        let player = run_test_movie("avm1/create_empty_movie_clip", 2);
+        let levels = player.lock().unwrap().display_list();
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].path, "_level0");
+        let clip = levels[0]
+            .find("_level0.clip2")
+            .expect("clip2 is on the stage");
+        assert_eq!(clip.kind, "MovieClip");
+        assert_eq!(clip.name, "clip2");
+        assert_eq!(clip.character_id, 0);
+    }
+
+    #[test]
+    fn avm1_values_by_path() {
+        let player = run_test_movie("avm1/create_empty_movie_clip", 2);
+        let mut player = player.lock().unwrap();
+        player.set_avm1_value("_root.clip2.score", HostValue::Number(5.0));
+        assert_eq!(
+            player.get_avm1_value("_root.clip2.score"),
+            HostValue::Number(5.0)
+        );
+        let clip = player
+            .avm1_object("_root.clip2")
+            .expect("clip2 is an object");
+        assert_eq!(clip.type_of, "movieclip");
+        assert!(clip
+            .properties
+            .contains(&("score".to_string(), "5".to_string())));
+        assert!(player
+            .avm1_objects()
+            .iter()
+            .any(|object| object.path == "_level0.clip2"));
+    }
+
+    #[test]
+    fn players_dont_share_prototypes() {
+        let first = run_test_movie("avm1/movieclip_prototype_extension", 1);
+        let second = run_test_movie("avm1/movieclip_prototype_extension", 1);
+        let mut first = first.lock().unwrap();
+        let mut second = second.lock().unwrap();
+        first.set_avm1_value("_global.Object.prototype.changed", HostValue::Bool(true));
+        assert_eq!(
+            first.get_avm1_value("_global.Object.prototype.changed"),
+            HostValue::Bool(true)
+        );
+        assert_eq!(
+            second.get_avm1_value("_global.Object.prototype.changed"),
+            HostValue::Undefined
+        );
+    }
+
+    #[test]
+    fn playback_waits_for_imported_assets() {
+        let (player, mut executor) = create_test_player("avm1/import_assets");
+        player.lock().unwrap().set_is_playing(true);
+
+        // The movie doesn't start until the movie it imports from has loaded.
+        player.lock().unwrap().tick(1000.0);
+        assert_eq!(
+            player.lock().unwrap().get_avm1_value("_root.ran"),
+            HostValue::Undefined
+        );
+
+        executor.block_all().unwrap();
+        player.lock().unwrap().tick(1000.0);
+        let mut player = player.lock().unwrap();
+        assert_eq!(player.get_avm1_value("_root.ran"), HostValue::Bool(true));
+        let levels = player.display_list();
+        let imported = levels[0]
+            .find("_level0.imported")
+            .expect("the imported clip is placed");
+        assert_eq!(imported.kind, "MovieClip");
+        assert_eq!(imported.children.len(), 1);
+    }
+
+    #[test]
+    fn playback_resumes_when_imported_assets_load_is_cancelled() {
+        let (player, mut executor) = create_test_player("avm1/import_assets");
+        player.lock().unwrap().set_is_playing(true);
+        player.lock().unwrap().tick(1000.0);
+
+        // The import is the only load the movie starts.
+        player
+            .lock()
+            .unwrap()
+            .mutate_with_update_context(|_avm, context| {
+                let handle = generational_arena::Index::from_raw_parts(0, 0);
+                assert!(matches!(
+                    context.load_manager.get_loader(handle),
+                    Some(Loader::ImportAssets { .. })
+                ));
+                context.load_manager.cancel(handle);
+            });
+
+        // The cancelled load fails, but the movie no longer waits for it.
+        assert!(executor.block_all().is_err());
+        player.lock().unwrap().tick(1000.0);
+        let mut player = player.lock().unwrap();
+        assert_eq!(player.get_avm1_value("_root.ran"), HostValue::Bool(true));
+        assert!(player.display_list()[0].find("_level0.imported").is_none());
+    }
+
+    /// An audio backend that plays nothing, but reports the stream position it's given.
+    struct StreamPositionAudioBackend {
+        inner: NullAudioBackend,
+        stream_position: Rc<Cell<Option<u32>>>,
+    }
+
+    impl AudioBackend for StreamPositionAudioBackend {
+        fn register_sound(&mut self, sound: &swf::Sound) -> Result<SoundHandle, Error> {
+            self.inner.register_sound(sound)
+        }
+
+        fn start_sound(
+            &mut self,
+            sound: SoundHandle,
+            settings: &swf::SoundInfo,
+        ) -> Result<SoundInstanceHandle, Error> {
+            self.inner.start_sound(sound, settings)
+        }
+
+        fn start_stream(
+            &mut self,
+            clip_id: CharacterId,
+            clip_frame: u16,
+            clip_data: crate::tag_utils::SwfSlice,
+            handle: &swf::SoundStreamHead,
+        ) -> Result<AudioStreamHandle, Error> {
+            self.inner
+                .start_stream(clip_id, clip_frame, clip_data, handle)
+        }
+
+        fn stop_sound(&mut self, sound: SoundInstanceHandle) {
+            self.inner.stop_sound(sound)
+        }
+
+        fn stop_stream(&mut self, stream: AudioStreamHandle) {
+            self.inner.stop_stream(stream)
+        }
+
+        fn stop_all_sounds(&mut self) {
+            self.inner.stop_all_sounds()
+        }
+
+        fn stop_sounds_with_handle(&mut self, handle: SoundHandle) {
+            self.inner.stop_sounds_with_handle(handle)
+        }
+
+        fn is_sound_playing_with_handle(&mut self, handle: SoundHandle) -> bool {
+            self.inner.is_sound_playing_with_handle(handle)
+        }
+
+        fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32> {
+            self.inner.get_sound_duration(sound)
+        }
+
+        fn is_sound_playing(&self, sound: SoundInstanceHandle) -> bool {
+            self.inner.is_sound_playing(sound)
+        }
+
+        fn get_stream_position(&self, _stream: AudioStreamHandle) -> Option<u32> {
+            self.stream_position.get()
+        }
+    }
+
+    #[test]
+    fn timeline_is_eased_towards_stream_position() {
+        // 10 frames per second, with a stream sound starting on frame 1.
+        let stream_position = Rc::new(Cell::new(None));
+        let player = PlayerBuilder::new()
+            .with_movie(SwfMovie::from_path("tests/swfs/avm1/stream_sound_sync/test.swf").unwrap())
+            .with_audio(StreamPositionAudioBackend {
+                inner: NullAudioBackend::new(),
+                stream_position: stream_position.clone(),
+            })
+            .build()
+            .unwrap();
+        let mut player = player.lock().unwrap();
+        player.set_is_playing(true);
+        let current_frame = |player: &mut Player| player.get_avm1_value("_root._currentframe");
+
+        player.tick(100.0);
+        assert_eq!(current_frame(&mut player), HostValue::Number(1.0));
+
+        // The audio is 10 frames ahead: the timeline catches up by one extra frame per tick,
+        // rather than skipping to the audio at once.
+        stream_position.set(Some(1000));
+        player.tick(100.0);
+        assert_eq!(current_frame(&mut player), HostValue::Number(3.0));
+
+        // The audio is 2 frames behind: the timeline waits for it.
+        stream_position.set(Some(0));
+        player.tick(100.0);
+        assert_eq!(current_frame(&mut player), HostValue::Number(3.0));
+
+        // Back in sync, time passes as usual.
+        stream_position.set(Some(300));
+        player.tick(100.0);
+        assert_eq!(current_frame(&mut player), HostValue::Number(4.0));
+    }
+
+    /// The view matrix scale and translation, and the letterbox, of a 550x400 movie shown
+    /// in a wider 1100x400 viewport with the given scale mode.
+    fn wide_viewport(scale_mode: StageScaleMode) -> ((f32, f32, Twips, Twips), Letterbox) {
+        let player = run_test_movie("avm1/depths", 0);
+        let mut player = player.lock().unwrap();
+        assert_eq!((player.movie_width, player.movie_height), (550, 400));
+        player.set_scale_mode(scale_mode);
+        player.set_viewport_dimensions(1100, 400, 1.0);
+        let matrix = player.view_matrix;
+        ((matrix.a, matrix.d, matrix.tx, matrix.ty), player.letterbox)
+    }
+
+    #[test]
+    fn show_all_pillarboxes_wide_viewport() {
+        assert_eq!(
+            wide_viewport(StageScaleMode::ShowAll),
+            (
+                (1.0, 1.0, Twips::from_pixels(275.0), Twips::from_pixels(0.0)),
+                Letterbox::Pillarbox {
+                    left: 275.0,
+                    right: 275.0
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn no_border_crops_wide_viewport() {
+        assert_eq!(
+            wide_viewport(StageScaleMode::NoBorder),
+            (
+                (
+                    2.0,
+                    2.0,
+                    Twips::from_pixels(0.0),
+                    Twips::from_pixels(-200.0)
+                ),
+                Letterbox::None
+            )
+        );
+    }
+
+    #[test]
+    fn exact_fit_stretches_to_wide_viewport() {
+        assert_eq!(
+            wide_viewport(StageScaleMode::ExactFit),
+            (
+                (2.0, 1.0, Twips::from_pixels(0.0), Twips::from_pixels(0.0)),
+                Letterbox::None
+            )
+        );
+    }
+
+    #[test]
+    fn no_scale_centers_movie_in_wide_viewport() {
+        assert_eq!(
+            wide_viewport(StageScaleMode::NoScale),
+            (
+                (1.0, 1.0, Twips::from_pixels(275.0), Twips::from_pixels(0.0)),
+                Letterbox::None
+            )
+        );
+    }
+
+    #[test]
+    fn frame_timings() {
+        let player = run_test_movie("avm1/create_empty_movie_clip", 1);
+        let mut player = player.lock().unwrap();
+
+        // Each reading of the clock is a millisecond after the last.
+        let ticks = Cell::new(0);
+        player.start_profiling(
+            Box::new(move || {
+                ticks.set(ticks.get() + 1);
+                Duration::from_millis(ticks.get())
+            }),
+            2,
+        );
+        for _ in 0..3 {
+            player.run_frame();
+            player.render();
+        }
+
+        let timings = player.frame_timings();
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].frame, 2);
+        assert_eq!(timings[1].frame, 3);
+        assert!(timings[1].avm1 > Duration::default());
+        assert!(timings[1].rendering > Duration::default());
+    }
+
+    #[test]
+    fn gc_stats() {
+        let player = run_test_movie("avm1/create_empty_movie_clip", 2);
+        let mut player = player.lock().unwrap();
+        player.collect_garbage();
+        let stats = player.gc_stats();
+        assert!(stats.allocated_bytes > 0);
+        assert!(stats.peak_allocated_bytes >= stats.allocated_bytes);
+        // At least the root movie and the clip it creates.
+        assert!(stats.display_objects >= 2);
+        assert!(stats.avm1_objects >= 2);
+    }
+
+    #[test]
+    fn no_unimplemented_features() {
+        let player = run_test_movie("avm1/create_empty_movie_clip", 2);
+        let player = player.lock().unwrap();
+        assert!(player.unimplemented_features().is_empty());
+        assert_eq!(player.unimplemented_features().to_string(), "");
+    }
+
+    #[test]
+    fn unimplemented_tags_are_reported() {
+        // This movie has a DefineFontAlignZones tag, which only affects anti-aliasing.
+        let player = run_test_movie("avm1/default_names", 1);
+        let player = player.lock().unwrap();
+        let feature = player
+            .unimplemented_features()
+            .iter()
+            .find(|feature| feature.name == "DefineFontAlignZones")
+            .expect("DefineFontAlignZones is reported");
+        assert_eq!(feature.kind, FeatureKind::Tag);
+        assert_eq!(feature.count, 1);
+    }
+
+    #[test]
+    fn partially_downloaded_movie_waits_for_frames() {
+        let data = std::fs::read("tests/swfs/avm1/partial_download/test.swf").unwrap();
+        let mut download = SwfDownload::new(None);
+        // The first half ends partway through the second frame.
+        let movie = download.append(&data[..data.len() / 2]).unwrap().unwrap();
+        let player = PlayerBuilder::new()
+            .with_movie((*movie).clone())
+            .build()
+            .unwrap();
+        let mut player = player.lock().unwrap();
+
+        player.run_frame();
+        player.run_frame();
+        assert_eq!(
+            player.get_avm1_value("_root._currentframe"),
+            HostValue::Number(1.0)
+        );
+        assert_eq!(
+            player.get_avm1_value("_root.framesloaded1"),
+            HostValue::Number(1.0)
+        );
+        assert_eq!(
+            player.get_avm1_value("_root.total1"),
+            HostValue::Number(data.len() as f64)
+        );
+        match player.get_avm1_value("_root.bytes1") {
+            HostValue::Number(bytes) => assert!(bytes > 0.0 && bytes < (data.len() / 2) as f64),
+            value => panic!("getBytesLoaded returned {:?}", value),
+        }
+
+        download.append(&data[data.len() / 2..]).unwrap();
+        player.append_movie_data(download.finish().unwrap());
+        player.run_frame();
+        assert_eq!(
+            player.get_avm1_value("_root._currentframe"),
+            HostValue::Number(2.0)
+        );
+        assert_eq!(
+            player.get_avm1_value("_root.framesloaded2"),
+            HostValue::Number(3.0)
+        );
+        assert_eq!(
+            player.get_avm1_value("_root.bytes2"),
+            HostValue::Number(data.len() as f64)
+        );
+    }
+
+    #[test]
+    fn wait_for_frame_skips_actions_until_frame_is_loaded() {
+        let data = std::fs::read("tests/swfs/avm1/wait_for_frame/test.swf").unwrap();
+        let mut download = SwfDownload::new(None);
+        // The first half ends partway through the second frame.
+        let movie = download.append(&data[..data.len() / 2]).unwrap().unwrap();
+        let player = PlayerBuilder::new()
+            .with_movie((*movie).clone())
+            .build()
+            .unwrap();
+        let mut player = player.lock().unwrap();
+        let is_set = |player: &mut Player, name: &str| {
+            player.get_avm1_value(&format!("_root.{}", name)) == HostValue::Bool(true)
+        };
+
+        // Each check sets its variable if its frame is loaded: frame 1, frame 3 by number
+        // with WaitForFrame and WaitForFrame2, frame 3 by its label, and a label that
+        // doesn't exist, which is only known once the whole movie has loaded.
+        player.run_frame();
+        assert!(is_set(&mut player, "frame1_1"));
+        assert!(!is_set(&mut player, "frame3_1"));
+        assert!(!is_set(&mut player, "frame3_2_1"));
+        assert!(!is_set(&mut player, "end_1"));
+        assert!(!is_set(&mut player, "missing_1"));
+
+        download.append(&data[data.len() / 2..]).unwrap();
+        player.append_movie_data(download.finish().unwrap());
+        player.run_frame();
+        player.run_frame();
+        assert!(is_set(&mut player, "frame1_3"));
+        assert!(is_set(&mut player, "frame3_3"));
+        assert!(is_set(&mut player, "frame3_2_3"));
+        assert!(is_set(&mut player, "end_3"));
+        assert!(is_set(&mut player, "missing_3"));
+    }
+
+    #[test]
+    fn morph_shape_is_registered_once_its_frame_is_preloaded() {
+        let data = std::fs::read("tests/swfs/avm1/partial_morph_shape/test.swf").unwrap();
+        let mut download = SwfDownload::new(None);
+        // The first half ends after the DefineMorphShape tag, but before it's placed.
+        let movie = download.append(&data[..data.len() / 2]).unwrap().unwrap();
+        let player = PlayerBuilder::new()
+            .with_movie((*movie).clone())
+            .build()
+            .unwrap();
+        let mut player = player.lock().unwrap();
+        let is_registered = |player: &mut Player| {
+            player.mutate_with_update_context(|_avm, context| {
+                context
+                    .library
+                    .library_for_movie(context.swf.clone())
+                    .map_or(false, |library| library.contains_character(1))
+            })
+        };
+
+        player.run_frame();
+        assert!(!is_registered(&mut player));
+
+        download.append(&data[data.len() / 2..]).unwrap();
+        player.append_movie_data(download.finish().unwrap());
+        player.run_frame();
+        assert!(is_registered(&mut player));
+        let levels = player.display_list();
+        assert_eq!(levels[0].children.len(), 1);
+        assert_eq!(levels[0].children[0].kind, "MorphShape");
+    }
+}
@@ -0,0 +1,258 @@
+//! Measures how long the player spends in each of its subsystems per frame, so that
+//! slow movies can be diagnosed with data.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+/// Gives the time elapsed since some fixed point, such as the launch of the player.
+///
+/// The core has no clock of its own that works on every platform, so frontends
+/// provide one when they start profiling.
+pub type Clock = Box<dyn Fn() -> Duration>;
+
+/// A part of the player that frame timings are broken down into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Running ActionScript, including event handlers and AVM2 scripts.
+    Avm1,
+
+    /// Preloading and running the tags of timelines.
+    Tags,
+
+    /// Converting shapes and glyphs to what the renderer draws.
+    Tessellation,
+
+    /// Drawing the stage.
+    Rendering,
+
+    /// Updating sounds and the audio backend.
+    Audio,
+
+    /// Collecting garbage on the heap of display objects and ActionScript objects.
+    GarbageCollection,
+}
+
+/// The time spent in each subsystem during a frame, and while rendering it.
+///
+/// Times are exclusive: tessellating shapes while running tags or rendering only
+/// counts towards tessellation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTimings {
+    /// The number of frames the player had run, counting this one.
+    pub frame: u32,
+    pub avm1: Duration,
+    pub tags: Duration,
+    pub tessellation: Duration,
+    pub rendering: Duration,
+    pub audio: Duration,
+    pub garbage_collection: Duration,
+}
+
+impl FrameTimings {
+    /// The time spent in all subsystems.
+    pub fn total(&self) -> Duration {
+        self.avm1
+            + self.tags
+            + self.tessellation
+            + self.rendering
+            + self.audio
+            + self.garbage_collection
+    }
+
+    pub fn get(&self, subsystem: Subsystem) -> Duration {
+        match subsystem {
+            Subsystem::Avm1 => self.avm1,
+            Subsystem::Tags => self.tags,
+            Subsystem::Tessellation => self.tessellation,
+            Subsystem::Rendering => self.rendering,
+            Subsystem::Audio => self.audio,
+            Subsystem::GarbageCollection => self.garbage_collection,
+        }
+    }
+
+    /// The mean timings of some frames, numbered as the last of them, or `None` if
+    /// there are no frames.
+    pub fn average(frames: &[FrameTimings]) -> Option<FrameTimings> {
+        let last = frames.last()?;
+        let count = frames.len() as u32;
+        let mean =
+            |get: fn(&FrameTimings) -> Duration| frames.iter().map(get).sum::<Duration>() / count;
+        Some(FrameTimings {
+            frame: last.frame,
+            avm1: mean(|frame| frame.avm1),
+            tags: mean(|frame| frame.tags),
+            tessellation: mean(|frame| frame.tessellation),
+            rendering: mean(|frame| frame.rendering),
+            audio: mean(|frame| frame.audio),
+            garbage_collection: mean(|frame| frame.garbage_collection),
+        })
+    }
+
+    fn get_mut(&mut self, subsystem: Subsystem) -> &mut Duration {
+        match subsystem {
+            Subsystem::Avm1 => &mut self.avm1,
+            Subsystem::Tags => &mut self.tags,
+            Subsystem::Tessellation => &mut self.tessellation,
+            Subsystem::Rendering => &mut self.rendering,
+            Subsystem::Audio => &mut self.audio,
+            Subsystem::GarbageCollection => &mut self.garbage_collection,
+        }
+    }
+}
+
+/// Writes the timings in milliseconds, such as
+/// `12.40 ms (ActionScript 4.10, tags 2.00, tessellation 0.80, rendering 5.40, audio 0.10,
+/// GC 0.30)`.
+impl fmt::Display for FrameTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "{:.2} ms (ActionScript {:.2}, tags {:.2}, tessellation {:.2}, rendering {:.2}, audio {:.2}, GC {:.2})",
+            ms(self.total()),
+            ms(self.avm1),
+            ms(self.tags),
+            ms(self.tessellation),
+            ms(self.rendering),
+            ms(self.audio),
+            ms(self.garbage_collection)
+        )
+    }
+}
+
+/// A measurement in progress, started by `Profiler::start`.
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    start: Duration,
+
+    /// The time recorded in the frame when the span started, so that spans
+    /// nested in this one aren't counted twice.
+    recorded: Duration,
+}
+
+/// Records the timings of the last few frames.
+///
+/// Profiling is off until a clock is given with `enable`, and measurements cost
+/// nothing while it is off.
+#[derive(Default)]
+pub struct Profiler {
+    clock: Option<Clock>,
+
+    /// How many frames of timings are kept.
+    capacity: usize,
+
+    /// The timings of the frame being run.
+    current: FrameTimings,
+
+    /// The total time recorded in the current frame.
+    recorded: Duration,
+
+    /// The timings of finished frames, oldest first.
+    frames: VecDeque<FrameTimings>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts profiling with the given clock, keeping the timings of the last
+    /// `capacity` frames.
+    pub fn enable(&mut self, clock: Clock, capacity: usize) {
+        self.clock = Some(clock);
+        self.capacity = capacity;
+        self.frames.clear();
+        self.current = FrameTimings::default();
+        self.recorded = Duration::default();
+    }
+
+    /// Stops profiling. The timings recorded so far are kept.
+    pub fn disable(&mut self) {
+        self.clock = None;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.clock.is_some()
+    }
+
+    /// The timings of the last finished frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTimings> {
+        self.frames.iter()
+    }
+
+    /// Starts measuring something. Returns `None` while profiling is off.
+    pub fn start(&self) -> Option<Span> {
+        let clock = self.clock.as_ref()?;
+        Some(Span {
+            start: clock(),
+            recorded: self.recorded,
+        })
+    }
+
+    /// Adds the time since a span started to a subsystem, leaving out the time of
+    /// the spans recorded within it.
+    pub fn record(&mut self, subsystem: Subsystem, span: Option<Span>) {
+        if let (Some(clock), Some(span)) = (&self.clock, span) {
+            let elapsed = clock().checked_sub(span.start).unwrap_or_default();
+            let nested = self.recorded.checked_sub(span.recorded).unwrap_or_default();
+            let exclusive = elapsed.checked_sub(nested).unwrap_or_default();
+            *self.current.get_mut(subsystem) += exclusive;
+            self.recorded += exclusive;
+        }
+    }
+
+    /// Finishes the current frame and starts recording the given one.
+    pub fn begin_frame(&mut self, frame: u32) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if self.current.frame != 0 || self.current.total() > Duration::default() {
+            if self.frames.len() >= self.capacity {
+                self.frames.pop_front();
+            }
+            if self.capacity > 0 {
+                self.frames.push_back(self.current);
+            }
+        }
+        self.current = FrameTimings {
+            frame,
+            ..Default::default()
+        };
+        self.recorded = Duration::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn nested_spans_are_exclusive() {
+        let now = Rc::new(Cell::new(0));
+        let clock_now = Rc::clone(&now);
+        let mut profiler = Profiler::new();
+        profiler.enable(Box::new(move || Duration::from_millis(clock_now.get())), 2);
+
+        for frame in 1..=3 {
+            profiler.begin_frame(frame);
+            let tags = profiler.start();
+            now.set(now.get() + 3);
+            let tessellation = profiler.start();
+            now.set(now.get() + 2);
+            profiler.record(Subsystem::Tessellation, tessellation);
+            profiler.record(Subsystem::Tags, tags);
+        }
+        profiler.begin_frame(4);
+
+        let frames: Vec<_> = profiler.frames().collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].frame, 2);
+        assert_eq!(frames[1].tags, Duration::from_millis(3));
+        assert_eq!(frames[1].tessellation, Duration::from_millis(2));
+        assert_eq!(frames[1].total(), Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,619 @@
+//! RTMP connections to media servers.
+//!
+//! `NetConnection` objects connect to media servers with RTMP, and `NetStream`
+//! objects play streams over these connections. The protocol is implemented
+//! here, while the `RtmpBackend` moves the bytes, so that frontends which
+//! can't open sockets leave it out.
+
+mod chunk;
+mod handshake;
+mod session;
+
+use session::{Event, MediaKind, RtmpUrl, Session};
+
+use crate::amf::{self, Graph};
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::{self, Avm1, Object, TObject};
+use crate::backend::audio::decoders::FlvAudioStream;
+use crate::backend::audio::AudioSource;
+use crate::backend::rtmp::{RtmpBackend, RtmpTransport};
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use gc_arena::{Collect, MutationContext};
+use rand::RngCore;
+
+type Error = Box<dyn std::error::Error>;
+
+/// A command that waits for the server's response.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+enum PendingCommand<'gc> {
+    Connect,
+
+    /// A call of `NetConnection.call`, and the responder to notify.
+    Call(Option<Object<'gc>>),
+
+    /// The creation of the stream of a `NetStream`.
+    CreateStream(Object<'gc>),
+}
+
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+struct Pending<'gc> {
+    transaction_id: u32,
+    command: PendingCommand<'gc>,
+}
+
+/// A command of a `NetStream`.
+#[derive(Clone, Debug, Collect)]
+#[collect(require_static)]
+pub enum StreamCommand {
+    /// Play a stream by name, from a time in seconds.
+    Play { name: String, start: f64 },
+
+    /// Pause or resume the stream, or toggle between them.
+    Pause(Option<bool>),
+
+    /// Seek to a time in seconds.
+    Seek(f64),
+
+    /// Set how many seconds of the stream are buffered.
+    SetBufferTime(f64),
+}
+
+/// The stream of a `NetStream`.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Stream<'gc> {
+    net_stream: Object<'gc>,
+
+    /// The ID of the stream, once the server created it.
+    id: Option<u32>,
+
+    /// The commands sent before the stream was created.
+    queued: Vec<StreamCommand>,
+    paused: bool,
+
+    /// The timestamp of the last audio or video data, in milliseconds.
+    ///
+    /// TODO: Video isn't decoded yet.
+    time: u32,
+    bytes_loaded: usize,
+
+    /// The audio of the stream, which clips play with `attachAudio`.
+    audio: FlvAudioStream,
+}
+
+impl<'gc> Stream<'gc> {
+    fn send(&mut self, session: &mut Session, command: StreamCommand) {
+        let id = match self.id {
+            Some(id) => id,
+            None => return self.queued.push(command),
+        };
+        match command {
+            StreamCommand::Play { name, start } => session.play(id, &name, start),
+            StreamCommand::Pause(paused) => {
+                self.paused = paused.unwrap_or(!self.paused);
+                session.pause(id, self.paused, f64::from(self.time));
+            }
+            StreamCommand::Seek(time) => session.seek(id, time * 1000.0),
+            StreamCommand::SetBufferTime(time) => {
+                session.set_buffer_length(id, (time * 1000.0) as u32)
+            }
+        }
+    }
+}
+
+/// The parts of a connection that don't reference the GC arena.
+#[derive(Collect)]
+#[collect(require_static)]
+struct Io {
+    session: Session,
+    transport: Box<dyn RtmpTransport>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+enum ConnectionState {
+    Connecting,
+    Connected,
+
+    /// The server rejected the connection, or the movie closed it.
+    Closed,
+}
+
+/// A connection of a `NetConnection` to a media server.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Connection<'gc> {
+    net_connection: Object<'gc>,
+    io: Io,
+    state: ConnectionState,
+    pending: Vec<Pending<'gc>>,
+    streams: Vec<Stream<'gc>>,
+}
+
+/// A method to call on an object, with arguments sent by the server.
+struct Call<'gc> {
+    object: Object<'gc>,
+    method: String,
+    graph: Graph,
+    args: Vec<amf::Value>,
+}
+
+impl<'gc> Connection<'gc> {
+    /// Exchange data with the server, and queue the calls it made.
+    fn update(
+        &mut self,
+        gc_context: MutationContext<'gc, '_>,
+        calls: &mut Vec<Call<'gc>>,
+    ) -> Result<(), Error> {
+        self.flush()?;
+        let data = self.io.transport.receive()?;
+        for event in self.io.session.receive(&data)? {
+            self.handle_event(event, gc_context, calls);
+        }
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        let output = self.io.session.take_output();
+        if !output.is_empty() {
+            self.io.transport.send(&output)?;
+        }
+        Ok(())
+    }
+
+    fn handle_event(
+        &mut self,
+        event: Event,
+        gc_context: MutationContext<'gc, '_>,
+        calls: &mut Vec<Call<'gc>>,
+    ) {
+        match event {
+            Event::Response {
+                transaction_id,
+                success,
+                graph,
+                args,
+            } => {
+                let index = match self
+                    .pending
+                    .iter()
+                    .position(|pending| pending.transaction_id == transaction_id)
+                {
+                    Some(index) => index,
+                    None => return,
+                };
+                let value = args.into_iter().next().unwrap_or(amf::Value::Undefined);
+
+                let (object, method) = match self.pending.remove(index).command {
+                    PendingCommand::Connect => {
+                        if success {
+                            self.state = ConnectionState::Connected;
+                            self.net_connection.define_value(
+                                gc_context,
+                                "isConnected",
+                                true.into(),
+                                EnumSet::empty(),
+                            );
+                        } else {
+                            self.state = ConnectionState::Closed;
+                        }
+                        (self.net_connection, "onStatus")
+                    }
+                    PendingCommand::Call(responder) => match (success, responder) {
+                        (true, Some(responder)) => (responder, "onResult"),
+                        (true, None) => return,
+                        // Errors of calls without a responder go to the
+                        // connection instead.
+                        (false, responder) => {
+                            (responder.unwrap_or(self.net_connection), "onStatus")
+                        }
+                    },
+                    PendingCommand::CreateStream(net_stream) => {
+                        let stream = self
+                            .streams
+                            .iter_mut()
+                            .find(|stream| Object::ptr_eq(stream.net_stream, net_stream));
+                        if let (true, amf::Value::Number(id), Some(stream)) =
+                            (success, &value, stream)
+                        {
+                            stream.id = Some(*id as u32);
+                            for command in std::mem::take(&mut stream.queued) {
+                                stream.send(&mut self.io.session, command);
+                            }
+                        }
+                        return;
+                    }
+                };
+                calls.push(Call {
+                    object,
+                    method: method.to_string(),
+                    graph,
+                    args: vec![value],
+                });
+            }
+            Event::Call {
+                stream_id,
+                name,
+                graph,
+                args,
+            } => {
+                let object = if stream_id == 0 {
+                    self.net_connection
+                } else {
+                    match self.stream_by_id(stream_id) {
+                        Some(stream) => stream.net_stream,
+                        None => return,
+                    }
+                };
+                calls.push(Call {
+                    object,
+                    method: name,
+                    graph,
+                    args,
+                });
+            }
+            Event::Media {
+                stream_id,
+                kind,
+                timestamp,
+                data,
+            } => {
+                if let Some(stream) = self.stream_by_id(stream_id) {
+                    stream.time = timestamp;
+                    stream.bytes_loaded += data.len();
+                    // Servers send empty audio messages at the start of a stream.
+                    if kind == MediaKind::Audio && !data.is_empty() {
+                        if let Err(e) = stream.audio.push_packet(&data) {
+                            log::warn!("Unable to decode NetStream audio: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn stream_by_id(&mut self, stream_id: u32) -> Option<&mut Stream<'gc>> {
+        self.streams
+            .iter_mut()
+            .find(|stream| stream.id == Some(stream_id))
+    }
+}
+
+/// The RTMP connections of `NetConnection` objects.
+#[derive(Collect, Default)]
+#[collect(no_drop)]
+pub struct RtmpConnections<'gc> {
+    connections: Vec<Connection<'gc>>,
+}
+
+impl<'gc> RtmpConnections<'gc> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect a `NetConnection` to the application at an RTMP URL.
+    ///
+    /// Returns `false` if the URL can't be connected to. Otherwise, the
+    /// connection is opened in the background, and the result is passed to
+    /// `onStatus`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn connect(
+        &mut self,
+        backend: &mut dyn RtmpBackend,
+        rng: &mut impl RngCore,
+        net_connection: Object<'gc>,
+        url: &str,
+        flash_version: &str,
+        graph: &Graph,
+        args: &[amf::Value],
+    ) -> bool {
+        self.connections
+            .retain(|c| !Object::ptr_eq(c.net_connection, net_connection));
+
+        let url = match RtmpUrl::parse(url) {
+            Some(url) => url,
+            None => return false,
+        };
+        let transport = match backend.connect(&url.host, url.port, url.tunneled) {
+            Ok(transport) => transport,
+            Err(e) => {
+                log::warn!("Unable to connect to {}: {}", url.tc_url, e);
+                return false;
+            }
+        };
+
+        let mut session = Session::new(rng);
+        let transaction_id = session.connect(&url, flash_version, graph, args);
+        self.connections.push(Connection {
+            net_connection,
+            io: Io { session, transport },
+            state: ConnectionState::Connecting,
+            pending: vec![Pending {
+                transaction_id,
+                command: PendingCommand::Connect,
+            }],
+            streams: vec![],
+        });
+        true
+    }
+
+    fn connection(&mut self, net_connection: Object<'gc>) -> Option<&mut Connection<'gc>> {
+        self.connections
+            .iter_mut()
+            .find(|c| Object::ptr_eq(c.net_connection, net_connection))
+    }
+
+    /// Whether a `NetConnection` uses RTMP.
+    pub fn is_rtmp(&self, net_connection: Object<'gc>) -> bool {
+        self.connections
+            .iter()
+            .any(|c| Object::ptr_eq(c.net_connection, net_connection))
+    }
+
+    /// Close the connection of a `NetConnection` and its streams.
+    ///
+    /// The connection is dropped on the next frame, when `onStatus` is told.
+    pub fn close(&mut self, net_connection: Object<'gc>) {
+        if let Some(connection) = self.connection(net_connection) {
+            connection.state = ConnectionState::Closed;
+        }
+    }
+
+    /// Call a method of the server, whose result is passed to the `onResult`
+    /// method of the responder.
+    ///
+    /// Returns `false` if the `NetConnection` isn't connected with RTMP.
+    pub fn call(
+        &mut self,
+        net_connection: Object<'gc>,
+        method: &str,
+        responder: Option<Object<'gc>>,
+        graph: &Graph,
+        args: &[amf::Value],
+    ) -> bool {
+        let connection = match self.connection(net_connection) {
+            Some(connection) => connection,
+            None => return false,
+        };
+        let transaction_id = connection.io.session.call(method, graph, args);
+        connection.pending.push(Pending {
+            transaction_id,
+            command: PendingCommand::Call(responder),
+        });
+        true
+    }
+
+    /// Create the stream of a `NetStream` on the connection of a
+    /// `NetConnection`.
+    pub fn create_stream(&mut self, net_connection: Object<'gc>, net_stream: Object<'gc>) -> bool {
+        let connection = match self.connection(net_connection) {
+            Some(connection) => connection,
+            None => return false,
+        };
+        let transaction_id = connection.io.session.create_stream();
+        connection.pending.push(Pending {
+            transaction_id,
+            command: PendingCommand::CreateStream(net_stream),
+        });
+        connection.streams.push(Stream {
+            net_stream,
+            id: None,
+            queued: vec![],
+            paused: false,
+            time: 0,
+            bytes_loaded: 0,
+            audio: FlvAudioStream::new(),
+        });
+        true
+    }
+
+    fn stream(&mut self, net_stream: Object<'gc>) -> Option<(&mut Session, &mut Stream<'gc>)> {
+        self.connections.iter_mut().find_map(|connection| {
+            let session = &mut connection.io.session;
+            connection
+                .streams
+                .iter_mut()
+                .find(|stream| Object::ptr_eq(stream.net_stream, net_stream))
+                .map(|stream| (session, stream))
+        })
+    }
+
+    /// Send a command to the stream of a `NetStream`.
+    ///
+    /// Returns `false` if the `NetStream` has no stream.
+    pub fn stream_command(&mut self, net_stream: Object<'gc>, command: StreamCommand) -> bool {
+        match self.stream(net_stream) {
+            Some((session, stream)) => {
+                stream.send(session, command);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delete the stream of a `NetStream`.
+    pub fn close_stream(&mut self, net_stream: Object<'gc>) {
+        for connection in &mut self.connections {
+            let session = &mut connection.io.session;
+            connection.streams.retain(|stream| {
+                if !Object::ptr_eq(stream.net_stream, net_stream) {
+                    return true;
+                }
+                if let Some(id) = stream.id {
+                    session.delete_stream(id);
+                }
+                false
+            });
+        }
+    }
+
+    /// The time of the stream of a `NetStream`, in seconds.
+    pub fn stream_time(&mut self, net_stream: Object<'gc>) -> f64 {
+        self.stream(net_stream)
+            .map(|(_, stream)| f64::from(stream.time) / 1000.0)
+            .unwrap_or_default()
+    }
+
+    /// Make a source that plays the audio of the stream of a `NetStream`.
+    ///
+    /// Returns `None` if the `NetStream` has no stream.
+    pub fn stream_audio_source(&mut self, net_stream: Object<'gc>) -> Option<AudioSource> {
+        self.stream(net_stream)
+            .map(|(_, stream)| stream.audio.source())
+    }
+
+    /// How many bytes of audio and video the stream of a `NetStream` has
+    /// received.
+    pub fn stream_bytes_loaded(&mut self, net_stream: Object<'gc>) -> usize {
+        self.stream(net_stream)
+            .map(|(_, stream)| stream.bytes_loaded)
+            .unwrap_or_default()
+    }
+
+    /// Exchange data with the servers, and call the methods that they call.
+    pub fn poll(avm: &mut Avm1<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let gc_context = context.gc_context;
+        let connections = &mut context.rtmp_connections.connections;
+        let mut calls = vec![];
+        let mut i = 0;
+        while i < connections.len() {
+            let connection = &mut connections[i];
+            let result = connection.update(gc_context, &mut calls);
+            if let Err(e) = &result {
+                log::warn!("RTMP connection failed: {}", e);
+            }
+
+            let (level, code) = match (result, connection.state) {
+                (Ok(_), ConnectionState::Closed) => ("status", "NetConnection.Connect.Closed"),
+                (Ok(_), _) => {
+                    i += 1;
+                    continue;
+                }
+                (Err(_), ConnectionState::Connecting) => ("error", "NetConnection.Connect.Failed"),
+                (Err(_), _) => ("status", "NetConnection.Connect.Closed"),
+            };
+            let connection = connections.remove(i);
+            connection.net_connection.define_value(
+                gc_context,
+                "isConnected",
+                false.into(),
+                EnumSet::empty(),
+            );
+            let (graph, info) = status(level, code);
+            calls.push(Call {
+                object: connection.net_connection,
+                method: "onStatus".to_string(),
+                graph,
+                args: vec![info],
+            });
+        }
+
+        let root = match context.levels.get(&0) {
+            Some(root) => *root,
+            None => return,
+        };
+        let swf_version = context.swf.version();
+        for call in calls {
+            let args: Vec<_> = {
+                let mut activation = Activation::from_nothing(
+                    avm,
+                    ActivationIdentifier::root("[NetConnection]"),
+                    swf_version,
+                    avm.global_object_cell(),
+                    context.gc_context,
+                    root,
+                );
+                call.args
+                    .iter()
+                    .map(|arg| avm1::amf::deserialize(&mut activation, context, &call.graph, arg))
+                    .collect()
+            };
+            avm.run_stack_frame_for_method(
+                root,
+                call.object,
+                swf_version,
+                context,
+                &call.method,
+                &args,
+            );
+        }
+    }
+}
+
+/// The info object of an `onStatus` event.
+fn status(level: &str, code: &str) -> (Graph, amf::Value) {
+    let mut graph = Graph::new();
+    let info = graph.add(amf::Object::Object {
+        class_name: None,
+        properties: vec![
+            ("level".to_string(), amf::Value::String(level.to_string())),
+            ("code".to_string(), amf::Value::String(code.to_string())),
+        ],
+    });
+    (graph, amf::Value::Object(info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    struct NullTransport;
+
+    impl RtmpTransport for NullTransport {
+        fn send(&mut self, _data: &[u8]) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Error> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn stream_audio() {
+        with_avm(10, |_activation, context, net_stream| {
+            let mut connections = RtmpConnections::new();
+            connections.connections.push(Connection {
+                net_connection: net_stream,
+                io: Io {
+                    session: Session::new(&mut SmallRng::seed_from_u64(0)),
+                    transport: Box::new(NullTransport),
+                },
+                state: ConnectionState::Connected,
+                pending: vec![],
+                streams: vec![],
+            });
+            connections.create_stream(net_stream, net_stream);
+            connections.connections[0].streams[0].id = Some(1);
+            let mut source = connections.stream_audio_source(net_stream).unwrap();
+
+            let audio = |data: &[u8]| Event::Media {
+                stream_id: 1,
+                kind: MediaKind::Audio,
+                timestamp: 0,
+                data: data.to_vec(),
+            };
+            let connection = &mut connections.connections[0];
+            connection.handle_event(audio(&[]), context.gc_context, &mut vec![]);
+            // 16-bit mono PCM at 44kHz.
+            connection.handle_event(
+                audio(&[0b0011_1110, 0xe8, 0x03]),
+                context.gc_context,
+                &mut vec![],
+            );
+            assert_eq!(source.next(), Some([1000, 1000]));
+            assert_eq!(source.next(), Some([0, 0]));
+
+            // The source ends once the stream is closed.
+            connections.close_stream(net_stream);
+            assert_eq!(source.next(), None);
+            Ok(())
+        });
+    }
+}
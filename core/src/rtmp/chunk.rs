@@ -0,0 +1,306 @@
+//! RTMP chunk streams
+//!
+//! Messages are split into chunks, so that large messages like video frames
+//! don't hold back others. Each chunk has a header that names the chunk stream
+//! it belongs to, and can leave out the parts of the message header that are
+//! the same as in the previous chunk of that chunk stream.
+
+use super::session::RtmpError;
+use std::collections::HashMap;
+
+/// The size of chunks until a `SetChunkSize` message changes it.
+pub const DEFAULT_CHUNK_SIZE: usize = 128;
+
+/// The largest timestamp that fits in a chunk header. Larger ones are written
+/// after the header.
+const EXTENDED_TIMESTAMP: u32 = 0xff_ffff;
+
+/// A message sent over a chunk stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawMessage {
+    pub chunk_stream_id: u32,
+    pub timestamp: u32,
+    pub type_id: u8,
+    pub stream_id: u32,
+    pub payload: Vec<u8>,
+}
+
+/// The header of the last chunk of a chunk stream, and the message it is in
+/// the middle of.
+#[derive(Clone, Debug, Default)]
+struct ChunkStream {
+    timestamp: u32,
+    timestamp_delta: u32,
+    extended_timestamp: bool,
+    length: usize,
+    type_id: u8,
+    stream_id: u32,
+    payload: Vec<u8>,
+}
+
+/// Joins received chunks back into messages.
+#[derive(Debug)]
+pub struct ChunkReader {
+    chunk_size: usize,
+    streams: HashMap<u32, ChunkStream>,
+}
+
+impl ChunkReader {
+    pub fn new() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size.max(1);
+    }
+
+    /// Drop the partial message of a chunk stream.
+    pub fn abort(&mut self, chunk_stream_id: u32) {
+        if let Some(stream) = self.streams.get_mut(&chunk_stream_id) {
+            stream.payload.clear();
+        }
+    }
+
+    /// Read the next complete message.
+    ///
+    /// Complete chunks are drained from `input`, and `None` is returned when
+    /// the rest of the message hasn't been received yet.
+    pub fn read(&mut self, input: &mut Vec<u8>) -> Result<Option<RawMessage>, RtmpError> {
+        loop {
+            let (len, message) = match self.read_chunk(input)? {
+                Some(chunk) => chunk,
+                None => return Ok(None),
+            };
+            input.drain(..len);
+            if message.is_some() {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Read one chunk from the start of `data`, returning its length and the
+    /// message it completes.
+    fn read_chunk(
+        &mut self,
+        data: &[u8],
+    ) -> Result<Option<(usize, Option<RawMessage>)>, RtmpError> {
+        let mut pos = 0;
+        let mut take = |len: usize| -> Option<&[u8]> {
+            let bytes = data.get(pos..pos + len)?;
+            pos += len;
+            Some(bytes)
+        };
+        macro_rules! take {
+            ($len:expr) => {
+                match take($len) {
+                    Some(bytes) => bytes,
+                    None => return Ok(None),
+                }
+            };
+        }
+
+        let first = take!(1)[0];
+        let format = first >> 6;
+        let chunk_stream_id = match first & 0x3f {
+            0 => 64 + u32::from(take!(1)[0]),
+            1 => {
+                let bytes = take!(2);
+                64 + u32::from(bytes[0]) + 256 * u32::from(bytes[1])
+            }
+            id => u32::from(id),
+        };
+
+        let mut stream = self
+            .streams
+            .get(&chunk_stream_id)
+            .cloned()
+            .unwrap_or_default();
+        let starts_message = stream.payload.is_empty();
+        if format < 3 {
+            let bytes = take!(3);
+            let timestamp = read_u24(bytes);
+            stream.extended_timestamp = timestamp == EXTENDED_TIMESTAMP;
+            if format == 0 {
+                stream.timestamp = timestamp;
+                stream.timestamp_delta = 0;
+            } else {
+                stream.timestamp_delta = timestamp;
+            }
+        }
+        if format < 2 {
+            let bytes = take!(4);
+            stream.length = read_u24(bytes) as usize;
+            stream.type_id = bytes[3];
+        }
+        if format == 0 {
+            let bytes = take!(4);
+            stream.stream_id = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        if stream.extended_timestamp {
+            let bytes = take!(4);
+            let timestamp = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            if format == 0 {
+                stream.timestamp = timestamp;
+            } else if format < 3 {
+                stream.timestamp_delta = timestamp;
+            }
+        }
+        if format > 0 && starts_message {
+            stream.timestamp = stream.timestamp.wrapping_add(stream.timestamp_delta);
+        }
+        if format < 3 && !starts_message {
+            return Err(RtmpError::InvalidChunk(chunk_stream_id));
+        }
+
+        let remaining = stream.length - stream.payload.len();
+        let payload = take!(remaining.min(self.chunk_size));
+        stream.payload.extend_from_slice(payload);
+
+        let message = if stream.payload.len() == stream.length {
+            Some(RawMessage {
+                chunk_stream_id,
+                timestamp: stream.timestamp,
+                type_id: stream.type_id,
+                stream_id: stream.stream_id,
+                payload: std::mem::take(&mut stream.payload),
+            })
+        } else {
+            None
+        };
+        self.streams.insert(chunk_stream_id, stream);
+        Ok(Some((pos, message)))
+    }
+}
+
+/// Splits messages into chunks.
+///
+/// Every message starts with a full header, so the writer doesn't need to
+/// remember the previous headers of each chunk stream.
+#[derive(Debug)]
+pub struct ChunkWriter {
+    chunk_size: usize,
+}
+
+impl ChunkWriter {
+    pub fn new() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    pub fn write(&self, output: &mut Vec<u8>, message: &RawMessage) {
+        let extended = message.timestamp >= EXTENDED_TIMESTAMP;
+
+        write_basic_header(output, 0, message.chunk_stream_id);
+        write_u24(output, message.timestamp.min(EXTENDED_TIMESTAMP));
+        write_u24(output, message.payload.len() as u32);
+        output.push(message.type_id);
+        output.extend_from_slice(&message.stream_id.to_le_bytes());
+        if extended {
+            output.extend_from_slice(&message.timestamp.to_be_bytes());
+        }
+
+        for (i, chunk) in message.payload.chunks(self.chunk_size).enumerate() {
+            if i > 0 {
+                write_basic_header(output, 3, message.chunk_stream_id);
+                if extended {
+                    output.extend_from_slice(&message.timestamp.to_be_bytes());
+                }
+            }
+            output.extend_from_slice(chunk);
+        }
+    }
+}
+
+fn write_basic_header(output: &mut Vec<u8>, format: u8, chunk_stream_id: u32) {
+    match chunk_stream_id {
+        0..=63 => output.push(format << 6 | chunk_stream_id as u8),
+        64..=319 => output.extend_from_slice(&[format << 6, (chunk_stream_id - 64) as u8]),
+        _ => {
+            let id = chunk_stream_id - 64;
+            output.extend_from_slice(&[format << 6 | 1, id as u8, (id >> 8) as u8]);
+        }
+    }
+}
+
+fn read_u24(bytes: &[u8]) -> u32 {
+    u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2])
+}
+
+fn write_u24(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&value.to_be_bytes()[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(chunk_stream_id: u32, timestamp: u32, len: usize) -> RawMessage {
+        RawMessage {
+            chunk_stream_id,
+            timestamp,
+            type_id: 9,
+            stream_id: 1,
+            payload: (0..len).map(|i| i as u8).collect(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let writer = ChunkWriter::new();
+        let messages = [
+            message(3, 0, 10),
+            message(4, 1000, 300),
+            message(70, 0x0100_0000, 200),
+            message(400, 5, 0),
+        ];
+        let mut data = vec![];
+        for message in &messages {
+            writer.write(&mut data, message);
+        }
+
+        let mut reader = ChunkReader::new();
+        for message in &messages {
+            assert_eq!(reader.read(&mut data), Ok(Some(message.clone())));
+        }
+        assert_eq!(reader.read(&mut data), Ok(None));
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn partial_chunks() {
+        let mut data = vec![];
+        ChunkWriter::new().write(&mut data, &message(3, 0, 200));
+
+        let mut reader = ChunkReader::new();
+        let mut input = vec![];
+        for byte in data {
+            assert_eq!(reader.read(&mut input), Ok(None));
+            input.push(byte);
+        }
+        assert_eq!(reader.read(&mut input), Ok(Some(message(3, 0, 200))));
+    }
+
+    #[test]
+    fn compressed_headers() {
+        let mut data = vec![];
+        // Format 0: timestamp 1000, length 2, video, stream 1.
+        data.extend(&[
+            0x04, 0x00, 0x03, 0xe8, 0x00, 0x00, 0x02, 0x09, 0x01, 0x00, 0x00, 0x00,
+        ]);
+        data.extend(&[0xaa, 0xbb]);
+        // Format 2: timestamp delta 40.
+        data.extend(&[0x84, 0x00, 0x00, 0x28, 0xcc, 0xdd]);
+        // Format 3: the same delta again.
+        data.extend(&[0xc4, 0xee, 0xff]);
+
+        let mut reader = ChunkReader::new();
+        let timestamps: Vec<_> = (0..3)
+            .map(|_| reader.read(&mut data).unwrap().unwrap().timestamp)
+            .collect();
+        assert_eq!(timestamps, [1000, 1040, 1080]);
+    }
+}
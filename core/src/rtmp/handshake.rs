@@ -0,0 +1,74 @@
+//! The RTMP handshake
+//!
+//! The client sends a version byte and a packet of random data, and the server
+//! answers with its own version byte and packet, followed by an echo of the
+//! client's packet. The client then echoes the server's packet, after which
+//! both sides send chunks.
+
+use super::session::RtmpError;
+use rand::RngCore;
+
+/// The version of RTMP without encryption.
+const VERSION: u8 = 3;
+
+/// The size of the packets exchanged after the version byte.
+const PACKET_SIZE: usize = 1536;
+
+/// The size of the server's answer to the client's first packet.
+const SERVER_HELLO_SIZE: usize = 1 + 2 * PACKET_SIZE;
+
+/// The version byte and first packet sent by the client.
+pub fn client_hello(rng: &mut impl RngCore) -> Vec<u8> {
+    let mut hello = vec![0; 1 + PACKET_SIZE];
+    hello[0] = VERSION;
+    // The packet starts with the time and four zero bytes, which the random
+    // bytes follow.
+    rng.fill_bytes(&mut hello[9..]);
+    hello
+}
+
+/// Answer the server's version byte and packets with an echo of its packet.
+///
+/// Returns `None` if they haven't been fully received yet. Otherwise, they
+/// are drained from `input`.
+pub fn client_answer(input: &mut Vec<u8>) -> Result<Option<Vec<u8>>, RtmpError> {
+    if input.len() < SERVER_HELLO_SIZE {
+        return Ok(None);
+    }
+    if input[0] != VERSION {
+        return Err(RtmpError::UnsupportedVersion(input[0]));
+    }
+
+    let answer = input[1..1 + PACKET_SIZE].to_vec();
+    input.drain(..SERVER_HELLO_SIZE);
+    Ok(Some(answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn handshake() {
+        let hello = client_hello(&mut SmallRng::seed_from_u64(0));
+        assert_eq!(hello.len(), 1537);
+        assert_eq!(&hello[..9], &[3, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let server_packet: Vec<u8> = (0..1536).map(|i| i as u8).collect();
+        let mut input = vec![3];
+        input.extend(&server_packet);
+        assert_eq!(client_answer(&mut input), Ok(None));
+        input.extend(&hello[1..]);
+        input.push(0x02);
+        assert_eq!(client_answer(&mut input), Ok(Some(server_packet)));
+        assert_eq!(input, [0x02]);
+
+        let mut input = vec![6; 3073];
+        assert_eq!(
+            client_answer(&mut input),
+            Err(RtmpError::UnsupportedVersion(6))
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! The client side of an RTMP connection
+//!
+//! A `Session` doesn't do any I/O itself: received bytes are passed to
+//! `receive`, which returns what the server sent, and the bytes to send to the
+//! server are taken with `take_output`.
+
+use super::chunk::{ChunkReader, ChunkWriter, RawMessage};
+use super::handshake;
+use crate::amf::{self, amf0, AmfError, Graph};
+use rand::RngCore;
+use thiserror::Error;
+use url::Url;
+
+const SET_CHUNK_SIZE: u8 = 1;
+const ABORT: u8 = 2;
+const ACKNOWLEDGEMENT: u8 = 3;
+const USER_CONTROL: u8 = 4;
+const WINDOW_ACKNOWLEDGEMENT_SIZE: u8 = 5;
+const SET_PEER_BANDWIDTH: u8 = 6;
+const AUDIO: u8 = 8;
+const VIDEO: u8 = 9;
+const DATA_AMF3: u8 = 15;
+const COMMAND_AMF3: u8 = 17;
+const DATA_AMF0: u8 = 18;
+const COMMAND_AMF0: u8 = 20;
+const AGGREGATE: u8 = 22;
+
+const SET_BUFFER_LENGTH: u16 = 3;
+const PING_REQUEST: u16 = 6;
+const PING_RESPONSE: u16 = 7;
+
+/// The chunk stream of protocol control messages.
+const CONTROL_CHUNK_STREAM: u32 = 2;
+
+/// The chunk stream of commands to the connection.
+const COMMAND_CHUNK_STREAM: u32 = 3;
+
+/// The chunk stream of commands to a stream.
+const STREAM_CHUNK_STREAM: u32 = 8;
+
+/// The transaction of the `connect` command.
+const CONNECT_TRANSACTION: u32 = 1;
+
+/// An error in the data sent by a server.
+#[derive(Debug, Error, PartialEq)]
+pub enum RtmpError {
+    #[error("Unsupported RTMP version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid chunk on chunk stream {0}")]
+    InvalidChunk(u32),
+
+    #[error("Invalid RTMP message of type {0}")]
+    InvalidMessage(u8),
+
+    #[error("Invalid AMF in RTMP message: {0}")]
+    Amf(#[from] AmfError),
+}
+
+/// The parts of an `rtmp://` or `rtmpt://` URL that the connection needs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RtmpUrl {
+    pub host: String,
+    pub port: u16,
+
+    /// Whether RTMP is tunneled over HTTP.
+    pub tunneled: bool,
+
+    /// The application to connect to, followed by its instance, if any.
+    pub app: String,
+
+    /// The whole URL, which is sent to the server as is.
+    pub tc_url: String,
+}
+
+impl RtmpUrl {
+    /// Parse a URL, returning `None` if it isn't an RTMP URL that can be
+    /// connected to. Encrypted RTMP isn't supported.
+    pub fn parse(url: &str) -> Option<Self> {
+        let parsed = Url::parse(url).ok()?;
+        let (tunneled, default_port) = match parsed.scheme() {
+            "rtmp" => (false, 1935),
+            "rtmpt" => (true, 80),
+            _ => return None,
+        };
+
+        let mut app = parsed.path().trim_start_matches('/').to_string();
+        if let Some(query) = parsed.query() {
+            app.push('?');
+            app.push_str(query);
+        }
+
+        Some(Self {
+            host: parsed.host_str()?.to_string(),
+            port: parsed.port().unwrap_or(default_port),
+            tunneled,
+            app,
+            tc_url: url.to_string(),
+        })
+    }
+}
+
+/// The kind of data in a media message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+/// Something that the server sent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The answer to a command of the client, which is an `_error` if it
+    /// failed.
+    Response {
+        transaction_id: u32,
+        success: bool,
+        graph: Graph,
+        args: Vec<amf::Value>,
+    },
+
+    /// A call to a method of the connection or one of its streams, like
+    /// `onStatus` or `onMetaData`.
+    Call {
+        stream_id: u32,
+        name: String,
+        graph: Graph,
+        args: Vec<amf::Value>,
+    },
+
+    /// Audio or video data of a stream, in the format of FLV tags.
+    Media {
+        stream_id: u32,
+        kind: MediaKind,
+        timestamp: u32,
+        data: Vec<u8>,
+    },
+}
+
+/// The state of a connection to a server.
+#[derive(Debug)]
+pub struct Session {
+    handshake_done: bool,
+    input: Vec<u8>,
+    output: Vec<u8>,
+
+    /// Messages sent before the handshake completed.
+    queued: Vec<u8>,
+    reader: ChunkReader,
+    writer: ChunkWriter,
+
+    /// How many bytes the server sends between acknowledgements.
+    window_size: u32,
+    bytes_received: u32,
+    bytes_acknowledged: u32,
+    next_transaction_id: u32,
+}
+
+impl Session {
+    /// Start a session, whose first output begins the handshake.
+    pub fn new(rng: &mut impl RngCore) -> Self {
+        Self {
+            handshake_done: false,
+            input: vec![],
+            output: handshake::client_hello(rng),
+            queued: vec![],
+            reader: ChunkReader::new(),
+            writer: ChunkWriter::new(),
+            window_size: 0,
+            bytes_received: 0,
+            bytes_acknowledged: 0,
+            next_transaction_id: CONNECT_TRANSACTION + 1,
+        }
+    }
+
+    /// Take the bytes to send to the server.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Handle bytes sent by the server.
+    ///
+    /// Any error ends the session, as chunks after it can't be read anymore.
+    pub fn receive(&mut self, data: &[u8]) -> Result<Vec<Event>, RtmpError> {
+        self.input.extend_from_slice(data);
+        self.bytes_received = self.bytes_received.wrapping_add(data.len() as u32);
+
+        if !self.handshake_done {
+            match handshake::client_answer(&mut self.input)? {
+                Some(answer) => {
+                    self.output.extend(answer);
+                    self.output.append(&mut self.queued);
+                    self.handshake_done = true;
+                }
+                None => return Ok(vec![]),
+            }
+        }
+
+        let mut events = vec![];
+        while let Some(message) = self.reader.read(&mut self.input)? {
+            self.handle_message(message, &mut events)?;
+        }
+
+        let unacknowledged = self.bytes_received.wrapping_sub(self.bytes_acknowledged);
+        if self.window_size > 0 && unacknowledged >= self.window_size {
+            self.send_control(ACKNOWLEDGEMENT, &self.bytes_received.to_be_bytes());
+            self.bytes_acknowledged = self.bytes_received;
+        }
+
+        Ok(events)
+    }
+
+    fn handle_message(
+        &mut self,
+        message: RawMessage,
+        events: &mut Vec<Event>,
+    ) -> Result<(), RtmpError> {
+        let payload = &message.payload[..];
+        match message.type_id {
+            SET_CHUNK_SIZE => {
+                let size = read_u32(payload, message.type_id)? & 0x7fff_ffff;
+                self.reader.set_chunk_size(size as usize);
+            }
+            ABORT => self.reader.abort(read_u32(payload, message.type_id)?),
+            USER_CONTROL if payload.len() >= 6 && payload[..2] == PING_REQUEST.to_be_bytes() => {
+                let mut response = PING_RESPONSE.to_be_bytes().to_vec();
+                response.extend_from_slice(&payload[2..6]);
+                self.send_control(USER_CONTROL, &response);
+            }
+            WINDOW_ACKNOWLEDGEMENT_SIZE => {
+                self.window_size = read_u32(payload, message.type_id)?;
+            }
+            SET_PEER_BANDWIDTH => {
+                let size = read_u32(payload, message.type_id)?;
+                self.send_control(WINDOW_ACKNOWLEDGEMENT_SIZE, &size.to_be_bytes());
+            }
+            AUDIO | VIDEO => events.push(Event::Media {
+                stream_id: message.stream_id,
+                kind: if message.type_id == AUDIO {
+                    MediaKind::Audio
+                } else {
+                    MediaKind::Video
+                },
+                timestamp: message.timestamp,
+                data: message.payload,
+            }),
+            DATA_AMF0 | DATA_AMF3 => {
+                // AMF3 messages start with a byte that is always zero.
+                let payload = if message.type_id == DATA_AMF3 {
+                    payload.get(1..).unwrap_or_default()
+                } else {
+                    payload
+                };
+                let (graph, mut values) = read_values(payload)?;
+                let mut name = take_string(&mut values, message.type_id)?;
+                // Data can be stored with the stream, and is then sent
+                // wrapped in a call to `@setDataFrame`.
+                if name == "@setDataFrame" {
+                    name = take_string(&mut values, message.type_id)?;
+                }
+                events.push(Event::Call {
+                    stream_id: message.stream_id,
+                    name,
+                    graph,
+                    args: values,
+                });
+            }
+            COMMAND_AMF0 | COMMAND_AMF3 => {
+                let payload = if message.type_id == COMMAND_AMF3 {
+                    payload.get(1..).unwrap_or_default()
+                } else {
+                    payload
+                };
+                let (graph, mut values) = read_values(payload)?;
+                let name = take_string(&mut values, message.type_id)?;
+                if values.len() < 2 {
+                    return Err(RtmpError::InvalidMessage(message.type_id));
+                }
+                let transaction_id = match values.remove(0) {
+                    amf::Value::Number(id) => id as u32,
+                    _ => return Err(RtmpError::InvalidMessage(message.type_id)),
+                };
+                let command_object = values.remove(0);
+
+                events.push(match name.as_str() {
+                    "_result" | "_error" => Event::Response {
+                        transaction_id,
+                        success: name == "_result",
+                        graph,
+                        // The command object of a `connect` response holds
+                        // the server's properties, and its info object comes
+                        // after it. Other responses have no command object.
+                        args: if transaction_id == CONNECT_TRANSACTION {
+                            values
+                        } else {
+                            std::iter::once(command_object).chain(values).collect()
+                        },
+                    },
+                    _ => Event::Call {
+                        stream_id: message.stream_id,
+                        name,
+                        graph,
+                        args: values,
+                    },
+                });
+            }
+            AGGREGATE => {
+                // The messages of an aggregate are FLV tags, whose
+                // timestamps are relative to the first of them.
+                let mut first_timestamp = None;
+                let mut data = payload;
+                while data.len() >= 11 {
+                    let len = read_u24(&data[1..4]) as usize;
+                    let timestamp = read_u24(&data[4..7]) | u32::from(data[7]) << 24;
+                    let first_timestamp = *first_timestamp.get_or_insert(timestamp);
+                    let payload = data
+                        .get(11..11 + len)
+                        .ok_or(RtmpError::InvalidMessage(message.type_id))?;
+                    self.handle_message(
+                        RawMessage {
+                            chunk_stream_id: message.chunk_stream_id,
+                            timestamp: message
+                                .timestamp
+                                .wrapping_add(timestamp.wrapping_sub(first_timestamp)),
+                            type_id: data[0],
+                            stream_id: message.stream_id,
+                            payload: payload.to_vec(),
+                        },
+                        events,
+                    )?;
+                    // Each tag is followed by its size.
+                    data = data.get(11 + len + 4..).unwrap_or_default();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Send the `connect` command, which has to be the first command of the
+    /// session.
+    ///
+    /// The server answers with a response to `CONNECT_TRANSACTION`, whose
+    /// first argument is the info object of the `onStatus` event that Flash
+    /// dispatches to the connection.
+    pub fn connect(
+        &mut self,
+        url: &RtmpUrl,
+        flash_version: &str,
+        graph: &Graph,
+        args: &[amf::Value],
+    ) -> u32 {
+        let mut graph = graph.clone();
+        let property = |name: &str, value| (name.to_string(), value);
+        let command_object = graph.add(amf::Object::Object {
+            class_name: None,
+            properties: vec![
+                property("app", amf::Value::String(url.app.clone())),
+                property("flashVer", amf::Value::String(flash_version.to_string())),
+                property("tcUrl", amf::Value::String(url.tc_url.clone())),
+                property("fpad", amf::Value::Bool(false)),
+                property("capabilities", amf::Value::Number(15.0)),
+                property("audioCodecs", amf::Value::Number(3191.0)),
+                property("videoCodecs", amf::Value::Number(252.0)),
+                property("videoFunction", amf::Value::Number(1.0)),
+                property("objectEncoding", amf::Value::Number(0.0)),
+            ],
+        });
+        self.send_command(
+            0,
+            "connect",
+            CONNECT_TRANSACTION,
+            &graph,
+            &amf::Value::Object(command_object),
+            args,
+        );
+        CONNECT_TRANSACTION
+    }
+
+    /// Call a method of the server, returning the transaction of its
+    /// response.
+    pub fn call(&mut self, name: &str, graph: &Graph, args: &[amf::Value]) -> u32 {
+        let transaction_id = self.next_transaction_id();
+        self.send_command(0, name, transaction_id, graph, &amf::Value::Null, args);
+        transaction_id
+    }
+
+    /// Create a stream, whose ID is the first argument of the response to
+    /// the returned transaction.
+    pub fn create_stream(&mut self) -> u32 {
+        self.call("createStream", &Graph::new(), &[])
+    }
+
+    pub fn delete_stream(&mut self, stream_id: u32) {
+        self.send_command(
+            0,
+            "deleteStream",
+            0,
+            &Graph::new(),
+            &amf::Value::Null,
+            &[amf::Value::Number(f64::from(stream_id))],
+        );
+    }
+
+    /// Play a stream by name.
+    ///
+    /// `start` is the time to start at in seconds, or -2 to play a live
+    /// stream if there is one and a recorded one otherwise, or -1 to only
+    /// play a live stream.
+    pub fn play(&mut self, stream_id: u32, name: &str, start: f64) {
+        let args = [
+            amf::Value::String(name.to_string()),
+            amf::Value::Number(if start >= 0.0 { start * 1000.0 } else { start }),
+        ];
+        self.send_stream_command(stream_id, "play", &args);
+    }
+
+    /// Pause or resume a stream at a time in milliseconds.
+    pub fn pause(&mut self, stream_id: u32, paused: bool, time: f64) {
+        let args = [amf::Value::Bool(paused), amf::Value::Number(time)];
+        self.send_stream_command(stream_id, "pause", &args);
+    }
+
+    /// Seek a stream to a time in milliseconds.
+    pub fn seek(&mut self, stream_id: u32, time: f64) {
+        self.send_stream_command(stream_id, "seek", &[amf::Value::Number(time)]);
+    }
+
+    /// Tell the server how many milliseconds of a stream the client buffers.
+    ///
+    /// Servers don't start sending recorded streams before this is known.
+    pub fn set_buffer_length(&mut self, stream_id: u32, length: u32) {
+        let mut payload = SET_BUFFER_LENGTH.to_be_bytes().to_vec();
+        payload.extend_from_slice(&stream_id.to_be_bytes());
+        payload.extend_from_slice(&length.to_be_bytes());
+        self.send_control(USER_CONTROL, &payload);
+    }
+
+    fn next_transaction_id(&mut self) -> u32 {
+        let id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+        id
+    }
+
+    fn send_stream_command(&mut self, stream_id: u32, name: &str, args: &[amf::Value]) {
+        self.send_command(stream_id, name, 0, &Graph::new(), &amf::Value::Null, args);
+    }
+
+    fn send_command(
+        &mut self,
+        stream_id: u32,
+        name: &str,
+        transaction_id: u32,
+        graph: &Graph,
+        command_object: &amf::Value,
+        args: &[amf::Value],
+    ) {
+        let mut encoder = amf0::Encoder::new(graph);
+        encoder.write_value(&amf::Value::String(name.to_string()));
+        encoder.write_value(&amf::Value::Number(f64::from(transaction_id)));
+        encoder.write_value(command_object);
+        for arg in args {
+            encoder.write_value(arg);
+        }
+
+        self.send(RawMessage {
+            chunk_stream_id: if stream_id == 0 {
+                COMMAND_CHUNK_STREAM
+            } else {
+                STREAM_CHUNK_STREAM
+            },
+            timestamp: 0,
+            type_id: COMMAND_AMF0,
+            stream_id,
+            payload: encoder.into_bytes(),
+        });
+    }
+
+    fn send_control(&mut self, type_id: u8, payload: &[u8]) {
+        self.send(RawMessage {
+            chunk_stream_id: CONTROL_CHUNK_STREAM,
+            timestamp: 0,
+            type_id,
+            stream_id: 0,
+            payload: payload.to_vec(),
+        });
+    }
+
+    fn send(&mut self, message: RawMessage) {
+        let output = if self.handshake_done {
+            &mut self.output
+        } else {
+            &mut self.queued
+        };
+        self.writer.write(output, &message);
+    }
+}
+
+fn read_u32(payload: &[u8], type_id: u8) -> Result<u32, RtmpError> {
+    match payload {
+        [a, b, c, d, ..] => Ok(u32::from_be_bytes([*a, *b, *c, *d])),
+        _ => Err(RtmpError::InvalidMessage(type_id)),
+    }
+}
+
+fn read_u24(bytes: &[u8]) -> u32 {
+    u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2])
+}
+
+fn read_values(payload: &[u8]) -> Result<(Graph, Vec<amf::Value>), RtmpError> {
+    let mut graph = Graph::new();
+    let mut values = vec![];
+    let mut decoder = amf0::Decoder::new(payload, &mut graph);
+    while !decoder.is_empty() {
+        values.push(decoder.read_value()?);
+    }
+    Ok((graph, values))
+}
+
+/// Take the name at the start of a command or data message.
+fn take_string(values: &mut Vec<amf::Value>, type_id: u8) -> Result<String, RtmpError> {
+    if values.is_empty() {
+        return Err(RtmpError::InvalidMessage(type_id));
+    }
+    match values.remove(0) {
+        amf::Value::String(name) => Ok(name),
+        _ => Err(RtmpError::InvalidMessage(type_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    fn command(stream_id: u32, values: &[amf::Value]) -> RawMessage {
+        let graph = Graph::new();
+        let mut encoder = amf0::Encoder::new(&graph);
+        for value in values {
+            encoder.write_value(value);
+        }
+        RawMessage {
+            chunk_stream_id: 3,
+            timestamp: 0,
+            type_id: COMMAND_AMF0,
+            stream_id,
+            payload: encoder.into_bytes(),
+        }
+    }
+
+    /// Complete the handshake of a session, returning what it sent after it.
+    fn handshake(session: &mut Session) -> Vec<RawMessage> {
+        assert_eq!(session.take_output().len(), 1537);
+        let mut server_hello = vec![3];
+        server_hello.extend(vec![0; 3072]);
+        assert_eq!(session.receive(&server_hello), Ok(vec![]));
+
+        let mut output = session.take_output();
+        assert!(output.len() >= 1536);
+        output.drain(..1536);
+        let mut reader = ChunkReader::new();
+        let mut messages = vec![];
+        while let Some(message) = reader.read(&mut output).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[test]
+    fn urls() {
+        let url = RtmpUrl::parse("rtmp://media.example.com/live/room?key=1").unwrap();
+        assert_eq!(url.host, "media.example.com");
+        assert_eq!(url.port, 1935);
+        assert!(!url.tunneled);
+        assert_eq!(url.app, "live/room?key=1");
+
+        let url = RtmpUrl::parse("rtmpt://media.example.com:8080/vod").unwrap();
+        assert_eq!(
+            (url.port, url.tunneled, url.app.as_str()),
+            (8080, true, "vod")
+        );
+
+        assert_eq!(RtmpUrl::parse("rtmpe://media.example.com/vod"), None);
+        assert_eq!(RtmpUrl::parse("http://media.example.com/vod"), None);
+    }
+
+    #[test]
+    fn connect() {
+        let mut session = Session::new(&mut SmallRng::seed_from_u64(0));
+        let url = RtmpUrl::parse("rtmp://localhost/vod").unwrap();
+        let args = [amf::Value::String("guest".to_string())];
+        assert_eq!(
+            session.connect(&url, "LNX 10,0,0,0", &Graph::new(), &args),
+            1
+        );
+
+        // Commands are held back until the handshake is done.
+        let messages = handshake(&mut session);
+        assert_eq!(messages.len(), 1);
+        let (graph, values) = read_values(&messages[0].payload).unwrap();
+        assert_eq!(values[0], amf::Value::String("connect".to_string()));
+        assert_eq!(values[1], amf::Value::Number(1.0));
+        assert_eq!(values[3], args[0]);
+        match (&values[2], graph.len()) {
+            (amf::Value::Object(id), 1) => match graph.get(*id) {
+                amf::Object::Object { properties, .. } => assert_eq!(
+                    properties[0],
+                    ("app".to_string(), amf::Value::String("vod".to_string()))
+                ),
+                object => panic!("Unexpected command object {:?}", object),
+            },
+            value => panic!("Unexpected command object {:?}", value),
+        }
+    }
+
+    #[test]
+    fn server_messages() {
+        let mut session = Session::new(&mut SmallRng::seed_from_u64(0));
+        handshake(&mut session);
+
+        let writer = ChunkWriter::new();
+        let mut input = vec![];
+        let control = |type_id, payload: &[u8]| RawMessage {
+            chunk_stream_id: 2,
+            timestamp: 0,
+            type_id,
+            stream_id: 0,
+            payload: payload.to_vec(),
+        };
+        writer.write(&mut input, &control(SET_CHUNK_SIZE, &[0, 0, 16, 0]));
+        writer.write(&mut input, &control(USER_CONTROL, &[0, 6, 0, 0, 1, 0]));
+        let info = amf::Value::String("NetConnection.Connect.Success".to_string());
+        writer.write(
+            &mut input,
+            &command(
+                0,
+                &[
+                    amf::Value::String("_result".to_string()),
+                    amf::Value::Number(1.0),
+                    amf::Value::Null,
+                    info.clone(),
+                ],
+            ),
+        );
+        let status = amf::Value::String("NetStream.Play.Start".to_string());
+        writer.write(
+            &mut input,
+            &command(
+                1,
+                &[
+                    amf::Value::String("onStatus".to_string()),
+                    amf::Value::Number(0.0),
+                    amf::Value::Null,
+                    status.clone(),
+                ],
+            ),
+        );
+
+        let events = session.receive(&input).unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::Response {
+                    transaction_id: 1,
+                    success: true,
+                    graph: Graph::new(),
+                    args: vec![info],
+                },
+                Event::Call {
+                    stream_id: 1,
+                    name: "onStatus".to_string(),
+                    graph: Graph::new(),
+                    args: vec![status],
+                }
+            ]
+        );
+
+        // The ping is answered right away.
+        let mut output = session.take_output();
+        assert_eq!(
+            ChunkReader::new().read(&mut output),
+            Ok(Some(control(USER_CONTROL, &[0, 7, 0, 0, 1, 0])))
+        );
+    }
+}
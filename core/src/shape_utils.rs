@@ -1,7 +1,7 @@
 use crate::bounding_box::BoundingBox;
 use fnv::FnvHashMap;
 use std::num::NonZeroU32;
-use swf::{CharacterId, FillStyle, LineStyle, Shape, ShapeRecord, Twips};
+use swf::{CharacterId, FillStyle, LineStyle, Matrix, Shape, ShapeRecord, Twips};
 
 pub fn calculate_shape_bounds(shape_records: &[swf::ShapeRecord]) -> swf::Rectangle {
     let mut bounds = swf::Rectangle {
@@ -83,6 +83,10 @@ pub struct DistilledShape<'a> {
     pub shape_bounds: BoundingBox,
     pub edge_bounds: BoundingBox,
     pub id: CharacterId,
+
+    /// The horizontal and vertical scale of the shape on screen, which the
+    /// width of strokes that don't scale with their shape depends on.
+    pub scale: (f32, f32),
 }
 
 impl<'a> From<&'a swf::Shape> for DistilledShape<'a> {
@@ -92,10 +96,58 @@ impl<'a> From<&'a swf::Shape> for DistilledShape<'a> {
             shape_bounds: (&shape.shape_bounds).into(),
             edge_bounds: (&shape.edge_bounds).into(),
             id: shape.id,
+            scale: (1.0, 1.0),
         }
     }
 }
 
+impl<'a> DistilledShape<'a> {
+    /// Whether the width of some stroke depends on the scale of the shape on
+    /// screen, so that the shape has to be tessellated for each scale.
+    pub fn has_scale_dependent_strokes(&self) -> bool {
+        self.paths.iter().any(|path| match path {
+            DrawPath::Stroke { style, .. } => {
+                !style.allow_scale_x
+                    || !style.allow_scale_y
+                    || style.width.to_pixels() < MIN_STROKE_WIDTH
+            }
+            DrawPath::Fill { .. } => false,
+        })
+    }
+}
+
+/// The minimum width of strokes on screen, in pixels. Flash widens thinner
+/// strokes to this, including "hairline" strokes with a width of 0.
+pub const MIN_STROKE_WIDTH: f64 = 1.0;
+
+/// Returns the width of a stroke in the space of its shape, in pixels, when
+/// the shape is scaled by `scale` on screen.
+///
+/// Strokes scale with their shape unless their style disallows it for an
+/// axis, and are never thinner than `MIN_STROKE_WIDTH` on screen.
+pub fn stroke_width(style: &LineStyle, scale: (f32, f32)) -> f32 {
+    let (scale_x, scale_y) = (scale.0.abs(), scale.1.abs());
+    let shape_scale = (scale_x * scale_y).sqrt();
+    let width = style.width.to_pixels() as f32;
+    if shape_scale == 0.0 || !shape_scale.is_finite() {
+        return width.max(MIN_STROKE_WIDTH as f32);
+    }
+
+    let screen_width = width
+        * match (style.allow_scale_x, style.allow_scale_y) {
+            (true, true) => shape_scale,
+            (false, true) => scale_y,
+            (true, false) => scale_x,
+            (false, false) => 1.0,
+        };
+    screen_width.max(MIN_STROKE_WIDTH as f32) / shape_scale
+}
+
+/// Returns the horizontal and vertical scale of a matrix.
+pub fn matrix_scale(matrix: &Matrix) -> (f32, f32) {
+    (matrix.a.hypot(matrix.b), matrix.c.hypot(matrix.d))
+}
+
 /// `DrawCommands` trace the outline of a path.
 /// Fills follow the even-odd fill rule, with opposite winding for holes.
 #[derive(Debug, PartialEq, Clone)]
@@ -558,6 +610,108 @@ impl<'a> ShapeConverter<'a> {
     }
 }
 
+/// The 9-slice scaling grid of a movie clip, used to distort the shapes inside of it.
+///
+/// The corners of the grid keep their original size when the clip is scaled, the
+/// edges only stretch along their length and the center stretches to fill the rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale9Grid {
+    /// The center region of the grid, in the clip's coordinate space.
+    pub grid: BoundingBox,
+
+    /// The bounds of the clip's content, in the clip's coordinate space.
+    pub bounds: BoundingBox,
+
+    /// The horizontal and vertical scale that the clip is displayed at.
+    pub scale_x: f64,
+    pub scale_y: f64,
+}
+
+impl Scale9Grid {
+    /// Maps a point in the clip's coordinate space to its 9-slice scaled position.
+    /// The result is still untransformed; applying the clip's matrix will give it
+    /// the expected size on screen.
+    pub fn map_point(&self, (x, y): (Twips, Twips)) -> (Twips, Twips) {
+        let x = scale9_axis(
+            x.get().into(),
+            (
+                self.bounds.x_min.get().into(),
+                self.bounds.x_max.get().into(),
+            ),
+            (self.grid.x_min.get().into(), self.grid.x_max.get().into()),
+            self.scale_x,
+        );
+        let y = scale9_axis(
+            y.get().into(),
+            (
+                self.bounds.y_min.get().into(),
+                self.bounds.y_max.get().into(),
+            ),
+            (self.grid.y_min.get().into(), self.grid.y_max.get().into()),
+            self.scale_y,
+        );
+        (Twips::new(x.round() as i32), Twips::new(y.round() as i32))
+    }
+}
+
+/// Applies a function to every point of the given paths, including curve control points.
+pub fn transform_paths(paths: &mut [DrawPath], f: impl Fn((Twips, Twips)) -> (Twips, Twips)) {
+    for path in paths {
+        let commands = match path {
+            DrawPath::Stroke { commands, .. } => commands,
+            DrawPath::Fill { commands, .. } => commands,
+        };
+        for command in commands {
+            match command {
+                DrawCommand::MoveTo { x, y } | DrawCommand::LineTo { x, y } => {
+                    let (new_x, new_y) = f((*x, *y));
+                    *x = new_x;
+                    *y = new_y;
+                }
+                DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                    let (new_x1, new_y1) = f((*x1, *y1));
+                    let (new_x2, new_y2) = f((*x2, *y2));
+                    *x1 = new_x1;
+                    *y1 = new_y1;
+                    *x2 = new_x2;
+                    *y2 = new_y2;
+                }
+            }
+        }
+    }
+}
+
+/// Maps a coordinate along one axis of a 9-slice grid.
+///
+/// The regions before and after the grid are divided by `scale` so that they keep their size once
+/// the clip is scaled, and the center region fills the remaining space. If the clip is too small
+/// to fit both corners, the corners shrink proportionally and the center disappears.
+fn scale9_axis(value: f64, bounds: (f64, f64), grid: (f64, f64), scale: f64) -> f64 {
+    let (min, max) = bounds;
+    if scale <= 0.0 || max <= min {
+        return value;
+    }
+    let grid_min = grid.0.max(min).min(max);
+    let grid_max = grid.1.max(grid_min).min(max);
+    let start = grid_min - min;
+    let end = max - grid_max;
+    let size = (max - min) * scale;
+    let corner_scale = if start + end > size {
+        size / (start + end) / scale
+    } else {
+        1.0 / scale
+    };
+    let center_min = min + start * corner_scale;
+    let center_max = max - end * corner_scale;
+    if value <= grid_min {
+        min + (value - min) * corner_scale
+    } else if value >= grid_max {
+        max - (max - value) * corner_scale
+    } else {
+        center_min + (value - grid_min) / (grid_max - grid_min) * (center_max - center_min)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -710,4 +864,39 @@ mod tests {
         }];
         assert_eq!(commands, expected);
     }
+
+    /// The corners of a 9-slice grid keep their size while the center stretches.
+    #[test]
+    fn scale9_axis_keeps_corners() {
+        // A 100px clip with 10px corners, displayed at twice its size.
+        assert_eq!(scale9_axis(0.0, (0.0, 100.0), (10.0, 90.0), 2.0), 0.0);
+        assert_eq!(scale9_axis(10.0, (0.0, 100.0), (10.0, 90.0), 2.0), 5.0);
+        assert_eq!(scale9_axis(50.0, (0.0, 100.0), (10.0, 90.0), 2.0), 50.0);
+        assert_eq!(scale9_axis(90.0, (0.0, 100.0), (10.0, 90.0), 2.0), 95.0);
+        assert_eq!(scale9_axis(100.0, (0.0, 100.0), (10.0, 90.0), 2.0), 100.0);
+
+        // Shrunk to 10px, the corners shrink to fit.
+        assert_eq!(scale9_axis(10.0, (0.0, 100.0), (10.0, 90.0), 0.1), 50.0);
+        assert_eq!(scale9_axis(50.0, (0.0, 100.0), (10.0, 90.0), 0.1), 50.0);
+        assert_eq!(scale9_axis(95.0, (0.0, 100.0), (10.0, 90.0), 0.1), 75.0);
+    }
+
+    /// Non-scaling strokes keep their width on screen, and no stroke is thinner than a pixel.
+    #[test]
+    fn stroke_widths() {
+        let mut style = LineStyle::new_v1(Twips::from_pixels(4.0), swf::Color::from_rgb(0, 255));
+        assert_eq!(stroke_width(&style, (2.0, 2.0)), 4.0);
+        assert_eq!(stroke_width(&style, (0.1, 0.1)), 10.0);
+
+        style.allow_scale_x = false;
+        style.allow_scale_y = false;
+        assert_eq!(stroke_width(&style, (2.0, 2.0)), 2.0);
+
+        // A horizontally non-scaling stroke only follows the vertical scale.
+        style.allow_scale_y = true;
+        assert_eq!(stroke_width(&style, (4.0, 1.0)), 2.0);
+
+        let hairline = LineStyle::new_v1(Twips::zero(), swf::Color::from_rgb(0, 255));
+        assert_eq!(stroke_width(&hairline, (4.0, 4.0)), 0.25);
+    }
 }
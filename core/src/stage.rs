@@ -0,0 +1,278 @@
+//! Stage-wide settings shared between the player and ActionScript.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// How the movie is scaled to fit the viewport.
+///
+/// Corresponds to the `Stage.scaleMode` property in AVM1 and the `SCALE`
+/// embed parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StageScaleMode {
+    /// The movie is scaled uniformly to fit entirely within the viewport,
+    /// with letterboxing in the leftover margins. This is the default.
+    ShowAll,
+
+    /// The movie is stretched non-uniformly to fill the viewport exactly.
+    ExactFit,
+
+    /// The movie is scaled uniformly to cover the entire viewport,
+    /// cropping the edges that do not fit.
+    NoBorder,
+
+    /// The movie is not scaled; the stage grows and shrinks with the viewport.
+    NoScale,
+}
+
+impl Default for StageScaleMode {
+    fn default() -> Self {
+        StageScaleMode::ShowAll
+    }
+}
+
+impl Display for StageScaleMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            StageScaleMode::ShowAll => "showAll",
+            StageScaleMode::ExactFit => "exactFit",
+            StageScaleMode::NoBorder => "noBorder",
+            StageScaleMode::NoScale => "noScale",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for StageScaleMode {
+    type Err = ParseEnumError;
+
+    /// Parses a scale mode name. Flash Player compares these case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let scale_mode = match s.to_ascii_lowercase().as_str() {
+            "showall" => StageScaleMode::ShowAll,
+            "exactfit" => StageScaleMode::ExactFit,
+            "noborder" => StageScaleMode::NoBorder,
+            "noscale" => StageScaleMode::NoScale,
+            _ => return Err(ParseEnumError),
+        };
+        Ok(scale_mode)
+    }
+}
+
+/// Where the movie is placed within the viewport when it doesn't fill it exactly.
+///
+/// Corresponds to the `Stage.align` property in AVM1 and the `SALIGN` embed
+/// parameter. The movie is centered along each axis with no edge set.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct StageAlign {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl StageAlign {
+    /// The fraction of the leftover space placed before the movie, horizontally
+    /// and vertically. Top and left take precedence over bottom and right.
+    pub fn offset(self) -> (f32, f32) {
+        let x = if self.left {
+            0.0
+        } else if self.right {
+            1.0
+        } else {
+            0.5
+        };
+        let y = if self.top {
+            0.0
+        } else if self.bottom {
+            1.0
+        } else {
+            0.5
+        };
+        (x, y)
+    }
+}
+
+impl Display for StageAlign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.top {
+            f.write_str("T")?;
+        } else if self.bottom {
+            f.write_str("B")?;
+        }
+        if self.left {
+            f.write_str("L")?;
+        } else if self.right {
+            f.write_str("R")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for StageAlign {
+    /// Parses an alignment such as `TL`. Flash Player accepts the letters in any
+    /// case and order, and ignores any other characters.
+    fn from(s: &str) -> Self {
+        let s = s.to_ascii_uppercase();
+        Self {
+            top: s.contains('T'),
+            bottom: s.contains('B'),
+            left: s.contains('L'),
+            right: s.contains('R'),
+        }
+    }
+}
+
+/// The rendering quality of the stage.
+///
+/// Corresponds to the global `_quality` property in AVM1 and the `QUALITY`
+/// embed parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StageQuality {
+    /// No anti-aliasing, and bitmaps are never smoothed.
+    Low,
+
+    /// 2x2 anti-aliasing, and bitmaps are never smoothed.
+    Medium,
+
+    /// 4x4 anti-aliasing, and bitmaps are smoothed if the movie is static.
+    /// This is the default.
+    High,
+
+    /// 4x4 anti-aliasing, and bitmaps are always smoothed.
+    Best,
+}
+
+impl StageQuality {
+    /// The number of anti-aliasing samples taken along each axis of a pixel.
+    pub fn samples_per_axis(self) -> u32 {
+        match self {
+            StageQuality::Low => 1,
+            StageQuality::Medium => 2,
+            StageQuality::High | StageQuality::Best => 4,
+        }
+    }
+
+    /// Whether a bitmap asking for smoothing is actually smoothed at this quality.
+    ///
+    /// Ruffle doesn't track whether the movie is static, so `High` smooths
+    /// bitmaps like `Best` does.
+    pub fn smooth_bitmap(self, is_smoothed: bool) -> bool {
+        match self {
+            StageQuality::Low | StageQuality::Medium => false,
+            StageQuality::High | StageQuality::Best => is_smoothed,
+        }
+    }
+}
+
+impl Default for StageQuality {
+    fn default() -> Self {
+        StageQuality::High
+    }
+}
+
+impl Display for StageQuality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            StageQuality::Low => "LOW",
+            StageQuality::Medium => "MEDIUM",
+            StageQuality::High => "HIGH",
+            StageQuality::Best => "BEST",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for StageQuality {
+    type Err = ParseEnumError;
+
+    /// Parses a quality name. Flash Player compares these case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let quality = match s.to_ascii_lowercase().as_str() {
+            "low" => StageQuality::Low,
+            "medium" => StageQuality::Medium,
+            "high" => StageQuality::High,
+            "best" => StageQuality::Best,
+            _ => return Err(ParseEnumError),
+        };
+        Ok(quality)
+    }
+}
+
+/// Whether the stage fills the screen.
+///
+/// Corresponds to `Stage.displayState` in AVM1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StageDisplayState {
+    /// The stage is shown in a window or in the page. This is the default.
+    Normal,
+
+    /// The stage fills the screen.
+    FullScreen,
+}
+
+impl Default for StageDisplayState {
+    fn default() -> Self {
+        StageDisplayState::Normal
+    }
+}
+
+impl Display for StageDisplayState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            StageDisplayState::Normal => "normal",
+            StageDisplayState::FullScreen => "fullScreen",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for StageDisplayState {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let display_state = match s.to_ascii_lowercase().as_str() {
+            "normal" => StageDisplayState::Normal,
+            "fullscreen" => StageDisplayState::FullScreen,
+            _ => return Err(ParseEnumError),
+        };
+        Ok(display_state)
+    }
+}
+
+/// Error returned when a stage setting could not be parsed from a string.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseEnumError;
+
+impl Display for ParseEnumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown value")
+    }
+}
+
+impl std::error::Error for ParseEnumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_mode_round_trip() {
+        for scale_mode in [
+            StageScaleMode::ShowAll,
+            StageScaleMode::ExactFit,
+            StageScaleMode::NoBorder,
+            StageScaleMode::NoScale,
+        ]
+        .iter()
+        {
+            assert_eq!(scale_mode.to_string().parse(), Ok(*scale_mode));
+        }
+    }
+
+    #[test]
+    fn scale_mode_is_case_insensitive() {
+        assert_eq!("NOBORDER".parse(), Ok(StageScaleMode::NoBorder));
+        assert_eq!("exactfit".parse(), Ok(StageScaleMode::ExactFit));
+        assert_eq!("stretch".parse::<StageScaleMode>(), Err(ParseEnumError));
+    }
+}
@@ -1,12 +1,20 @@
 use gc_arena::Collect;
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Read};
 use std::path::Path;
-use std::sync::Arc;
-use swf::{Header, TagCode};
+use std::sync::{Arc, Mutex};
+use swf::{Compression, Header, TagCode};
 
 pub type Error = Box<dyn std::error::Error>;
 pub type DecodeResult = Result<(), Error>;
 pub type SwfStream<R> = swf::read::Reader<std::io::Cursor<R>>;
 
+/// The most memory to reserve up front for decompressing a movie, as the length in
+/// the header of a corrupt SWF can be anything up to 4GB.
+const MAX_PREALLOCATED_LEN: usize = 1 << 24;
+
 /// An open, fully parsed SWF movie ready to play back, either in a Player or a
 /// MovieClip.
 #[derive(Debug, Clone, Collect)]
@@ -16,7 +24,138 @@ pub struct SwfMovie {
     header: Header,
 
     /// Uncompressed SWF data.
-    data: Vec<u8>,
+    data: MovieData,
+
+    /// The length of the uncompressed SWF file, including the header.
+    uncompressed_len: usize,
+
+    /// The length of the SWF header, which comes before the tag data.
+    header_len: usize,
+
+    /// Whether the rest of the movie is still being downloaded.
+    is_loading: bool,
+
+    /// Identifies the movie for its library, which is shared with the newer
+    /// versions of the movie made as more of it is downloaded.
+    identity: Arc<MovieIdentity>,
+
+    /// The URL the SWF was downloaded from, if any.
+    url: Option<String>,
+
+    /// The virtual machine that runs the code of this movie.
+    avm_type: AvmType,
+
+    /// Whether a local copy of this movie may access the network instead of local files.
+    use_network_sandbox: bool,
+}
+
+/// The identity of a movie, shared by every version of it made while it downloads.
+#[derive(Debug, Default)]
+pub struct MovieIdentity;
+
+/// The uncompressed data of a movie.
+///
+/// The versions of a downloading movie share one buffer, which the newest
+/// version appends to in place. Each version sees the part of the buffer that
+/// had been written when it was made, which is never written again.
+#[derive(Clone)]
+struct MovieData {
+    buffer: Arc<AppendBuffer>,
+    len: usize,
+}
+
+/// Storage that is written once, from front to back, and is never reallocated.
+struct AppendBuffer {
+    storage: Box<[UnsafeCell<u8>]>,
+
+    /// The number of bytes at the front of the storage that have been written.
+    written: Mutex<usize>,
+}
+
+// The written part of the storage is only ever read, and the rest is only
+// written while holding the lock.
+unsafe impl Sync for AppendBuffer {}
+
+impl MovieData {
+    /// Data with room for `capacity` bytes before it needs to be reallocated.
+    fn with_capacity(data: Vec<u8>, capacity: usize) -> Self {
+        let len = data.len();
+        let mut storage = data;
+        storage.resize(capacity.max(len), 0);
+        let storage = Box::into_raw(storage.into_boxed_slice()) as *mut [UnsafeCell<u8>];
+        Self {
+            buffer: Arc::new(AppendBuffer {
+                // `UnsafeCell<u8>` has the same layout as `u8`.
+                storage: unsafe { Box::from_raw(storage) },
+                written: Mutex::new(len),
+            }),
+            len,
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // The first `len` bytes have been written, and won't be written again.
+        unsafe { std::slice::from_raw_parts(self.buffer.storage.as_ptr() as *const u8, self.len) }
+    }
+
+    /// This data with more data after it.
+    ///
+    /// The data is appended in place, unless this isn't the newest data in its
+    /// buffer or the buffer is full, in which case it's copied to a new buffer with
+    /// twice the room.
+    fn append(&self, data: &[u8]) -> Self {
+        let len = self.len + data.len();
+        let storage = &self.buffer.storage;
+        let mut written = self.buffer.written.lock().unwrap();
+        if *written != self.len || len > storage.len() {
+            drop(written);
+            let mut new_data = Vec::with_capacity(len);
+            new_data.extend_from_slice(self.as_slice());
+            new_data.extend_from_slice(data);
+            return Self::with_capacity(new_data, len * 2);
+        }
+
+        // Nothing can read past `written` until it's updated, after the copy.
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr(),
+                storage[self.len..].as_ptr() as *mut u8,
+                data.len(),
+            );
+        }
+        *written = len;
+        Self {
+            buffer: self.buffer.clone(),
+            len,
+        }
+    }
+}
+
+impl From<Vec<u8>> for MovieData {
+    fn from(data: Vec<u8>) -> Self {
+        let len = data.len();
+        Self::with_capacity(data, len)
+    }
+}
+
+impl fmt::Debug for MovieData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+/// The ActionScript virtual machine that a movie runs on.
+///
+/// Each movie picks one with its `FileAttributes` tag. Movies of different
+/// types can be loaded into each other, but can't see each other's code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub enum AvmType {
+    /// ActionScript 1 and 2, which run `DoAction` and `DoInitAction` tags.
+    Avm1,
+
+    /// ActionScript 3, which runs `DoABC` tags.
+    Avm2,
 }
 
 impl SwfMovie {
@@ -30,7 +169,14 @@ impl SwfMovie {
                 frame_rate: 1.0,
                 num_frames: 0,
             },
-            data: vec![],
+            data: vec![].into(),
+            uncompressed_len: 0,
+            header_len: 0,
+            is_loading: false,
+            identity: Default::default(),
+            url: None,
+            avm_type: AvmType::Avm1,
+            use_network_sandbox: false,
         }
     }
 
@@ -38,18 +184,25 @@ impl SwfMovie {
     pub fn from_movie_and_subdata(&self, data: Vec<u8>) -> Self {
         Self {
             header: self.header.clone(),
-            data,
+            uncompressed_len: data.len(),
+            data: data.into(),
+            header_len: 0,
+            is_loading: false,
+            identity: Default::default(),
+            url: self.url.clone(),
+            avm_type: self.avm_type,
+            use_network_sandbox: self.use_network_sandbox,
         }
     }
 
     /// Utility method to construct a movie from a file on disk.
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let data = std::fs::read(path)?;
-        Self::from_data(&data)
+        let data = std::fs::read(&path)?;
+        Self::from_data(&data, Some(path.as_ref().to_string_lossy().into_owned()))
     }
 
     /// Construct a movie based on the contents of the SWF datastream.
-    pub fn from_data(swf_data: &[u8]) -> Result<Self, Error> {
+    pub fn from_data(swf_data: &[u8], url: Option<String>) -> Result<Self, Error> {
         let swf_stream = swf::read::read_swf_header(&swf_data[..])?;
         let header = swf_stream.header;
         let mut reader = swf_stream.reader;
@@ -67,14 +220,54 @@ impl SwfMovie {
             let _ = reader.get_mut().read_exact(&mut data);
             data
         } else {
-            let mut data = Vec::with_capacity(swf_stream.uncompressed_length);
+            // Don't trust the length in the header of a corrupt SWF to be reasonable.
+            let mut data =
+                Vec::with_capacity(swf_stream.uncompressed_length.min(MAX_PREALLOCATED_LEN));
             if let Err(e) = reader.get_mut().read_to_end(&mut data) {
                 return Err(format!("Error decompressing SWF, may be corrupt: {}", e).into());
             }
             data
         };
 
-        Ok(Self { header, data })
+        // The 8 byte header before the length is not included in `uncompressed_length`.
+        let uncompressed_len = swf_stream.uncompressed_length + 8;
+        let attributes = read_file_attributes(&data, header.version);
+        let avm_type = match attributes {
+            Some(ref attributes) if attributes.is_action_script_3 => AvmType::Avm2,
+            _ => AvmType::Avm1,
+        };
+        let use_network_sandbox = attributes.map_or(false, |a| a.use_network_sandbox);
+        Ok(Self {
+            header,
+            header_len: uncompressed_len.saturating_sub(data.len()),
+            data: data.into(),
+            uncompressed_len,
+            is_loading: false,
+            identity: Default::default(),
+            url,
+            avm_type,
+            use_network_sandbox,
+        })
+    }
+
+    /// Construct a newer version of a movie that is downloading, with more of
+    /// its tags appended to its data.
+    ///
+    /// `data` must end at the end of a tag. It's appended in place to the data
+    /// that older versions share, so slices of them are also slices of the newer
+    /// version.
+    pub fn append_data(&self, data: &[u8], is_loading: bool) -> Self {
+        Self {
+            header: self.header.clone(),
+            data: self.data.append(data),
+            uncompressed_len: self.uncompressed_len,
+            header_len: self.header_len,
+            is_loading,
+            identity: self.identity.clone(),
+            url: self.url.clone(),
+            avm_type: self.avm_type,
+            use_network_sandbox: self.use_network_sandbox,
+        }
     }
 
     pub fn header(&self) -> &Header {
@@ -87,7 +280,32 @@ impl SwfMovie {
     }
 
     pub fn data(&self) -> &[u8] {
-        &self.data
+        self.data.as_slice()
+    }
+
+    /// The length of the uncompressed SWF file, including the header.
+    pub fn uncompressed_len(&self) -> usize {
+        self.uncompressed_len
+    }
+
+    /// The length of the SWF header, which comes before the tag data.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// Whether the rest of the movie is still being downloaded, in which case
+    /// newer versions of it will have more data.
+    pub fn is_loading(&self) -> bool {
+        self.is_loading
+    }
+
+    pub(crate) fn identity(&self) -> &Arc<MovieIdentity> {
+        &self.identity
+    }
+
+    /// The URL the SWF was downloaded from, if any.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
     }
 
     pub fn width(&self) -> u32 {
@@ -97,6 +315,350 @@ impl SwfMovie {
     pub fn height(&self) -> u32 {
         (self.header.stage_size.y_max - self.header.stage_size.y_min).to_pixels() as u32
     }
+
+    /// The virtual machine that runs the code of this movie.
+    pub fn avm_type(&self) -> AvmType {
+        self.avm_type
+    }
+
+    /// Whether this movie asks to be able to access the network, rather than local
+    /// files, when it is played from the local filesystem.
+    pub fn use_network_sandbox(&self) -> bool {
+        self.use_network_sandbox
+    }
+
+    /// The XMP metadata of the movie from its `Metadata` tag, if it has one.
+    pub fn metadata(&self) -> Option<String> {
+        read_metadata(self.data(), self.version())
+    }
+
+    /// The title of the movie given in its metadata, if any.
+    pub fn title(&self) -> Option<String> {
+        self.metadata()
+            .and_then(|metadata| metadata_title(&metadata))
+    }
+}
+
+/// A SWF file that is still being downloaded.
+///
+/// The file is decompressed as it arrives, and its complete tags are appended
+/// to newer versions of its movie, so that the movie can start playing before
+/// the rest of it has arrived.
+pub struct SwfDownload {
+    /// The URL the file is downloaded from, if any.
+    url: Option<String>,
+
+    /// The bytes that have arrived before the compression of the file is known,
+    /// or the whole file if it can't be decompressed as it arrives.
+    received: Vec<u8>,
+
+    /// Decompresses the file, once its compression is known.
+    decoder: Option<SwfDecoder>,
+
+    /// The SWF version from the header of the file.
+    version: u8,
+
+    /// The length of the uncompressed file from its header.
+    uncompressed_len: usize,
+
+    /// Data that has been decompressed, but not yet appended to the movie.
+    decompressed: Vec<u8>,
+
+    /// The newest version of the movie.
+    movie: Option<Arc<SwfMovie>>,
+}
+
+enum SwfDecoder {
+    Uncompressed,
+    Zlib(Box<libflate::non_blocking::zlib::Decoder<ReceivedBytes>>),
+
+    /// LZMA streams can't be decompressed as they arrive, so these movies are
+    /// only created once the whole file has arrived.
+    Lzma,
+}
+
+/// Compressed bytes of a download that haven't been decompressed yet.
+///
+/// Reading past them fails with `WouldBlock`, so that decompression can carry
+/// on once more bytes arrive.
+#[derive(Default)]
+struct ReceivedBytes(VecDeque<u8>);
+
+impl Read for ReceivedBytes {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        self.0.read(buf)
+    }
+}
+
+impl SwfDownload {
+    pub fn new(url: Option<String>) -> Self {
+        Self {
+            url,
+            received: vec![],
+            decoder: None,
+            version: 0,
+            uncompressed_len: 0,
+            decompressed: vec![],
+            movie: None,
+        }
+    }
+
+    /// The newest version of the movie, once its header and first tag have arrived.
+    pub fn movie(&self) -> Option<Arc<SwfMovie>> {
+        self.movie.clone()
+    }
+
+    /// Add the next chunk of the file.
+    ///
+    /// Returns a newer version of the movie when more tags have arrived.
+    pub fn append(&mut self, chunk: &[u8]) -> Result<Option<Arc<SwfMovie>>, Error> {
+        match &mut self.decoder {
+            Some(SwfDecoder::Uncompressed) => self.decompressed.extend_from_slice(chunk),
+            Some(SwfDecoder::Zlib(decoder)) => {
+                decoder.as_inner_mut().0.extend(chunk);
+                decompress_available(decoder, &mut self.decompressed)?;
+            }
+            Some(SwfDecoder::Lzma) => {
+                self.received.extend_from_slice(chunk);
+                return Ok(None);
+            }
+            None => {
+                self.received.extend_from_slice(chunk);
+                if !self.start_decoding()? {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let tags_len = complete_tags_len(&self.decompressed, self.version);
+        let has_new_version = match &self.movie {
+            Some(_) => tags_len > 0,
+            None => self.read_header(true)?,
+        };
+        if !has_new_version {
+            return Ok(None);
+        }
+
+        self.append_tags(true).map(Some)
+    }
+
+    /// Finish the download once the whole file has arrived, and return the
+    /// final version of the movie.
+    pub fn finish(&mut self) -> Result<Arc<SwfMovie>, Error> {
+        if let Some(SwfDecoder::Lzma) = self.decoder {
+            let movie = Arc::new(SwfMovie::from_data(&self.received, self.url.clone())?);
+            self.movie = Some(movie.clone());
+            return Ok(movie);
+        }
+
+        if (self.decoder.is_none() && !self.start_decoding()?)
+            || (self.movie.is_none() && !self.read_header(false)?)
+        {
+            return Err("SWF file is too short".into());
+        }
+
+        self.append_tags(false)
+    }
+
+    /// Read the uncompressed part of the header, once it has arrived.
+    ///
+    /// Returns whether decoding has started.
+    fn start_decoding(&mut self) -> Result<bool, Error> {
+        if self.received.len() < 8 {
+            return Ok(false);
+        }
+
+        let compression = swf::read::Reader::read_compression_type(&self.received[..])?;
+        self.version = self.received[3];
+        self.uncompressed_len = u32::from_le_bytes([
+            self.received[4],
+            self.received[5],
+            self.received[6],
+            self.received[7],
+        ]) as usize;
+
+        let decoder = match compression {
+            Compression::None => {
+                self.decompressed = self.received.split_off(8);
+                SwfDecoder::Uncompressed
+            }
+            Compression::Zlib => {
+                let received = ReceivedBytes(self.received.split_off(8).into());
+                let mut decoder = Box::new(libflate::non_blocking::zlib::Decoder::new(received));
+                decompress_available(&mut decoder, &mut self.decompressed)?;
+                SwfDecoder::Zlib(decoder)
+            }
+            Compression::Lzma => SwfDecoder::Lzma,
+        };
+        self.decoder = Some(decoder);
+
+        Ok(true)
+    }
+
+    /// Create the first version of the movie once the rest of the header has
+    /// been decompressed, which is followed by its first tag unless the
+    /// download is complete.
+    ///
+    /// Returns whether the movie was created.
+    fn read_header(&mut self, is_loading: bool) -> Result<bool, Error> {
+        let data = &self.decompressed;
+        if data.is_empty() {
+            return Ok(false);
+        }
+
+        // The stage size rectangle, followed by the frame rate and frame count.
+        let num_bits = usize::from(data[0] >> 3);
+        let header_len = (5 + 4 * num_bits).div_ceil(8) + 4;
+        if data.len() < header_len
+            || (is_loading && complete_tags_len(&data[header_len..], self.version) == 0)
+        {
+            return Ok(false);
+        }
+
+        let mut header_data = b"FWS".to_vec();
+        header_data.push(self.version);
+        header_data.extend_from_slice(&(self.uncompressed_len as u32).to_le_bytes());
+        header_data.extend_from_slice(&data[..header_len]);
+        let mut header = swf::read::read_swf_header(&header_data[..])?.header;
+        if let Some(SwfDecoder::Zlib(_)) = self.decoder {
+            header.compression = Compression::Zlib;
+        }
+
+        let attributes = read_file_attributes(&data[header_len..], self.version);
+        let avm_type = match attributes {
+            Some(ref attributes) if attributes.is_action_script_3 => AvmType::Avm2,
+            _ => AvmType::Avm1,
+        };
+        // Make room for the whole movie up front, so that its data is never copied.
+        let tags_len = self
+            .uncompressed_len
+            .saturating_sub(header_data.len())
+            .min(MAX_PREALLOCATED_LEN);
+        self.movie = Some(Arc::new(SwfMovie {
+            header,
+            data: MovieData::with_capacity(vec![], tags_len),
+            uncompressed_len: self.uncompressed_len,
+            header_len: header_data.len(),
+            is_loading: true,
+            identity: Default::default(),
+            url: self.url.clone(),
+            avm_type,
+            use_network_sandbox: attributes.is_some_and(|a| a.use_network_sandbox),
+        }));
+        self.decompressed.drain(..header_len);
+
+        Ok(true)
+    }
+
+    /// Append the tags that have fully arrived to a newer version of the movie.
+    fn append_tags(&mut self, is_loading: bool) -> Result<Arc<SwfMovie>, Error> {
+        let tags_len = complete_tags_len(&self.decompressed, self.version);
+        if !is_loading && tags_len < self.decompressed.len() {
+            log::warn!("SWF file ends partway through a tag, may be corrupt");
+        }
+
+        let movie = self.movie.as_ref().ok_or("SWF header hasn't arrived")?;
+        let movie = Arc::new(movie.append_data(&self.decompressed[..tags_len], is_loading));
+        self.decompressed.drain(..tags_len);
+        self.movie = Some(movie.clone());
+
+        Ok(movie)
+    }
+}
+
+/// Decompress as much of a download as has arrived.
+fn decompress_available(
+    decoder: &mut libflate::non_blocking::zlib::Decoder<ReceivedBytes>,
+    decompressed: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let mut buffer = [0; 8192];
+    loop {
+        match decoder.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => decompressed.extend_from_slice(&buffer[..len]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(format!("Error decompressing SWF, may be corrupt: {}", e).into()),
+        }
+    }
+}
+
+/// The length of the tags at the start of `data` that have fully arrived.
+fn complete_tags_len(data: &[u8], version: u8) -> usize {
+    let mut reader = swf::read::Reader::new(data, version);
+    let mut len = 0;
+    while let Ok((_, tag_len)) = reader.read_tag_code_and_length() {
+        let tag_data = *reader.get_ref();
+        if tag_len > tag_data.len() {
+            break;
+        }
+        *reader.get_mut() = &tag_data[tag_len..];
+        len = data.len() - reader.get_ref().len();
+    }
+    len
+}
+
+/// Read the `FileAttributes` tag of a movie.
+///
+/// The tag must be the first one of the movie; movies without it run on AVM1.
+fn read_file_attributes(data: &[u8], version: u8) -> Option<swf::FileAttributes> {
+    let mut reader = swf::read::Reader::new(data, version);
+    match reader.read_tag_code_and_length() {
+        Ok((tag_code, _)) if tag_code == TagCode::FileAttributes as u16 => {
+            reader.read_file_attributes().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Read the `Metadata` tag of a movie, which is one of the tags of its first frame.
+fn read_metadata(data: &[u8], version: u8) -> Option<String> {
+    let mut reader = swf::read::Reader::new(data, version);
+    loop {
+        let (tag_code, length) = reader.read_tag_code_and_length().ok()?;
+        let tag_data = *reader.get_ref();
+        if length > tag_data.len() {
+            return None;
+        }
+        match TagCode::from_u16(tag_code) {
+            Some(TagCode::Metadata) => {
+                let metadata = String::from_utf8_lossy(&tag_data[..length]);
+                // The string may or may not be null-terminated.
+                return Some(metadata.trim_end_matches('\0').to_string());
+            }
+            Some(TagCode::ShowFrame) | Some(TagCode::End) => return None,
+            _ => *reader.get_mut() = &tag_data[length..],
+        }
+    }
+}
+
+/// Finds the title (`dc:title`) in XMP metadata. Titles can be given in several
+/// languages, in which case the first one is used.
+fn metadata_title(metadata: &str) -> Option<String> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(metadata);
+    let mut buf = vec![];
+    let mut is_in_title = false;
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(e)) if e.name() == b"dc:title" => is_in_title = true,
+            Ok(Event::End(e)) if e.name() == b"dc:title" => is_in_title = false,
+            Ok(Event::Text(e)) if is_in_title => {
+                let text = e.unescape_and_decode(&reader).ok()?;
+                let text = text.trim();
+                if !text.is_empty() {
+                    return Some(text.to_string());
+                }
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => (),
+        }
+        buf.clear();
+    }
 }
 
 /// A shared-ownership reference to some portion of an SWF datastream.
@@ -215,8 +777,8 @@ impl SwfSlice {
     /// would be invalid (e.g. negative length) or would extend past the end of
     /// the current slice.
     pub fn to_start_and_end(&self, start: usize, end: usize) -> Option<SwfSlice> {
-        let new_start = self.start + start;
-        let new_end = self.start + end;
+        let new_start = self.start.checked_add(start)?;
+        let new_end = self.start.checked_add(end)?;
 
         if new_start <= new_end {
             self.to_subslice(&self.movie.data().get(new_start..new_end)?)
@@ -258,6 +820,10 @@ where
     loop {
         let (tag_code, tag_len) = reader.read_tag_code_and_length()?;
         let end_pos = reader.get_ref().position() + tag_len as u64;
+        // Tag handlers slice the data of their tag, which a corrupt length could run past.
+        if end_pos > reader.get_ref().get_ref().as_ref().len() as u64 {
+            return Err("Tag extends past the end of the SWF data".into());
+        }
 
         let tag = TagCode::from_u16(tag_code);
         if let Some(tag) = tag {
@@ -280,3 +846,78 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_from_metadata() {
+        let metadata = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:format>application/x-shockwave-flash</dc:format>
+                <dc:title>
+                    <rdf:Alt>
+                        <rdf:li xml:lang="x-default">Tom &amp; Jerry</rdf:li>
+                        <rdf:li xml:lang="fr">Tom et Jerry</rdf:li>
+                    </rdf:Alt>
+                </dc:title>
+            </rdf:Description>
+        </rdf:RDF>"#;
+        assert_eq!(metadata_title(metadata), Some("Tom & Jerry".to_string()));
+        assert_eq!(
+            metadata_title("<dc:title>Simple</dc:title>"),
+            Some("Simple".to_string())
+        );
+        assert_eq!(metadata_title("<dc:format>swf</dc:format>"), None);
+    }
+
+    #[test]
+    fn tag_past_end_of_data_is_not_decoded() {
+        // A `DoAction` tag claiming 10 bytes, with only 2 of them present.
+        let data = [0x0a, 0x03, 0x00, 0x00];
+        let mut reader = SwfStream::new(std::io::Cursor::new(&data[..]), 8);
+        let mut decoded = false;
+        let result = decode_tags(
+            &mut reader,
+            |_, _, _| {
+                decoded = true;
+                Ok(())
+            },
+            TagCode::End,
+        );
+        assert!(result.is_err());
+        assert!(!decoded);
+    }
+
+    #[test]
+    fn download_in_chunks() {
+        for path in &[
+            "tests/swfs/avm1/partial_download/test.swf",
+            "tests/swfs/avm1/edittext_underline/test.swf",
+        ] {
+            let data = std::fs::read(path).unwrap();
+            let movie = SwfMovie::from_data(&data, None).unwrap();
+
+            let mut download = SwfDownload::new(None);
+            let mut versions = vec![];
+            for chunk in data.chunks(100) {
+                versions.extend(download.append(chunk).unwrap());
+            }
+            let downloaded = download.finish().unwrap();
+
+            assert!(!versions.is_empty());
+            for version in &versions {
+                assert!(version.is_loading());
+                assert!(movie.data().starts_with(version.data()));
+                // The data is appended in place rather than copied.
+                assert_eq!(version.data().as_ptr(), downloaded.data().as_ptr());
+                assert!(Arc::ptr_eq(version.identity(), downloaded.identity()));
+            }
+            assert!(!downloaded.is_loading());
+            assert_eq!(downloaded.header(), movie.header());
+            assert_eq!(downloaded.header_len(), movie.header_len());
+            assert_eq!(downloaded.data(), movie.data());
+        }
+    }
+}
@@ -5,7 +5,7 @@ use gc_arena::Collect;
 /// Represents the transform for a DisplayObject.
 /// This includes both the transformation matrix and the color transform.
 ///
-#[derive(Clone, Collect, Debug)]
+#[derive(Clone, Collect, Debug, PartialEq)]
 #[collect(require_static)]
 pub struct Transform {
     pub matrix: Matrix,
@@ -0,0 +1,140 @@
+//! A summary of the features a movie used that Ruffle doesn't implement, so that the
+//! movies blocked by a missing feature can be found without reading through logs.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The kind of a missing feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeatureKind {
+    /// An AVM1 action.
+    Action,
+
+    /// An AVM2 opcode.
+    Avm2Opcode,
+
+    /// An SWF tag.
+    Tag,
+
+    /// A method or property of the built-in ActionScript classes.
+    Builtin,
+}
+
+impl fmt::Display for FeatureKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FeatureKind::Action => "action",
+            FeatureKind::Avm2Opcode => "AVM2 opcode",
+            FeatureKind::Tag => "tag",
+            FeatureKind::Builtin => "builtin",
+        })
+    }
+}
+
+/// A missing feature that a movie used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnimplementedFeature {
+    pub kind: FeatureKind,
+
+    /// The name of the feature, such as `Sound.loadSound` or `DefineBinaryData`.
+    pub name: String,
+
+    /// How many times the feature was used.
+    pub count: u32,
+
+    /// The frame the feature was first used on, counting from 1. Features used while
+    /// the movie was loading, before its first frame, were used on frame 0.
+    pub first_frame: u32,
+}
+
+/// The missing features a movie used since it started.
+#[derive(Debug, Default)]
+pub struct UnimplementedFeatures {
+    features: BTreeMap<(FeatureKind, String), UnimplementedFeature>,
+
+    /// The frame being run.
+    frame: u32,
+}
+
+impl UnimplementedFeatures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn set_frame(&mut self, frame: u32) {
+        self.frame = frame;
+    }
+
+    /// Notes a use of a missing feature.
+    pub fn record(&mut self, kind: FeatureKind, name: &str) {
+        let frame = self.frame;
+        self.features
+            .entry((kind, name.to_string()))
+            .or_insert_with(|| UnimplementedFeature {
+                kind,
+                name: name.to_string(),
+                count: 0,
+                first_frame: frame,
+            })
+            .count += 1;
+    }
+
+    /// Notes a use of a missing feature named after an enum variant, such as an
+    /// `Action` or `Op`, leaving out its fields.
+    pub(crate) fn record_variant(&mut self, kind: FeatureKind, variant: &impl fmt::Debug) {
+        let debug = format!("{:?}", variant);
+        let end = debug
+            .find(|c| c == ' ' || c == '(' || c == '{')
+            .unwrap_or_else(|| debug.len());
+        self.record(kind, &debug[..end]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// The features that were used, by kind and then by name.
+    pub fn iter(&self) -> impl Iterator<Item = &UnimplementedFeature> {
+        self.features.values()
+    }
+}
+
+/// Writes a line for each feature, such as
+/// `builtin Sound.loadSound: used 3 times, first on frame 12`.
+impl fmt::Display for UnimplementedFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for feature in self.iter() {
+            writeln!(
+                f,
+                "{} {}: used {} time{}, first on frame {}",
+                feature.kind,
+                feature.name,
+                feature.count,
+                if feature.count == 1 { "" } else { "s" },
+                feature.first_frame
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_uses_from_first_frame() {
+        let mut features = UnimplementedFeatures::new();
+        features.record(FeatureKind::Tag, "DefineBinaryData");
+        features.set_frame(3);
+        features.record(FeatureKind::Builtin, "Sound.loadSound");
+        features.set_frame(4);
+        features.record(FeatureKind::Builtin, "Sound.loadSound");
+
+        assert_eq!(
+            features.to_string(),
+            "tag DefineBinaryData: used 1 time, first on frame 0\n\
+             builtin Sound.loadSound: used 2 times, first on frame 3\n"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Decoding of embedded video
+//!
+//! Only Screen Video is decoded so far. H.263 and VP6 streams are registered,
+//! but their frames are not shown.
+
+use std::io::Read;
+
+type Error = Box<dyn std::error::Error>;
+
+/// Decodes the frames of a Screen Video stream.
+///
+/// Each frame is split into blocks of zlib-compressed BGR pixels. Blocks left out
+/// of a frame keep their pixels from the previous frame, so frames must be decoded
+/// in order, starting from a keyframe.
+pub struct ScreenVideoDecoder {
+    width: usize,
+    height: usize,
+
+    /// The pixels of the last decoded frame as opaque RGBA, top row first.
+    pixels: Vec<u8>,
+}
+
+impl ScreenVideoDecoder {
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width as usize, height as usize);
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Decodes a frame from the data of a `VideoFrame` tag on top of the previous one.
+    pub fn decode(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() < 4 {
+            return Err("Screen Video frame is too short".into());
+        }
+        let block_width = (usize::from(data[0] >> 4) + 1) * 16;
+        let image_width = usize::from(u16::from_be_bytes([data[0], data[1]]) & 0xfff);
+        let block_height = (usize::from(data[2] >> 4) + 1) * 16;
+        let image_height = usize::from(u16::from_be_bytes([data[2], data[3]]) & 0xfff);
+        if image_width != self.width || image_height != self.height {
+            return Err("Screen Video frame size differs from its stream".into());
+        }
+
+        // Blocks are stored in rows from the bottom of the image up, and so are the
+        // pixels within each block.
+        let mut data = &data[4..];
+        let mut bgr = vec![];
+        for block_bottom in (0..image_height).step_by(block_height) {
+            for block_left in (0..image_width).step_by(block_width) {
+                if data.len() < 2 {
+                    return Err("Screen Video frame is truncated".into());
+                }
+                let size = usize::from(u16::from_be_bytes([data[0], data[1]]));
+                data = &data[2..];
+                if size == 0 {
+                    continue;
+                }
+                if data.len() < size {
+                    return Err("Screen Video frame is truncated".into());
+                }
+                let (block, rest) = data.split_at(size);
+                data = rest;
+
+                let width = block_width.min(image_width - block_left);
+                let height = block_height.min(image_height - block_bottom);
+                bgr.clear();
+                libflate::zlib::Decoder::new(block)?.read_to_end(&mut bgr)?;
+                if bgr.len() < width * height * 3 {
+                    return Err("Screen Video block is too short".into());
+                }
+                for (i, row) in bgr.chunks_exact(width * 3).take(height).enumerate() {
+                    let y = image_height - 1 - (block_bottom + i);
+                    let start = (y * image_width + block_left) * 4;
+                    let pixels = &mut self.pixels[start..start + width * 4];
+                    for (pixel, bgr) in pixels.chunks_exact_mut(4).zip(row.chunks_exact(3)) {
+                        pixel.copy_from_slice(&[bgr[2], bgr[1], bgr[0], 255]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn block(bgr: &[u8]) -> Vec<u8> {
+        let mut encoder = libflate::zlib::Encoder::new(vec![]).unwrap();
+        encoder.write_all(bgr).unwrap();
+        let data = encoder.finish().into_result().unwrap();
+        let mut block = (data.len() as u16).to_be_bytes().to_vec();
+        block.extend(data);
+        block
+    }
+
+    #[test]
+    fn screen_video() {
+        let mut decoder = ScreenVideoDecoder::new(2, 2);
+
+        // A 2x2 keyframe in a single 16x16 block, bottom row first.
+        let mut frame = vec![0x00, 0x02, 0x00, 0x02];
+        frame.extend(block(&[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]));
+        decoder.decode(&frame).unwrap();
+        assert_eq!(
+            decoder.pixels(),
+            &[255, 0, 0, 255, 255, 255, 255, 255, 0, 0, 255, 255, 0, 255, 0, 255][..]
+        );
+
+        // An empty block keeps the previous pixels.
+        decoder.decode(&[0x00, 0x02, 0x00, 0x02, 0, 0]).unwrap();
+        assert_eq!(decoder.pixels()[..4], [255, 0, 0, 255]);
+
+        assert!(decoder.decode(&[0x00, 0x04, 0x00, 0x02, 0, 0]).is_err());
+    }
+}
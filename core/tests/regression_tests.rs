@@ -3,22 +3,30 @@
 //! Trace output can be compared with correct output from the official Flash Payer.
 
 use approx::assert_abs_diff_eq;
-use log::{Metadata, Record};
+use ruffle_core::backend::accessibility::NullAccessibilityBackend;
+use ruffle_core::backend::font::NullFontBackend;
+use ruffle_core::backend::log::LogBackend;
 use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
+use ruffle_core::backend::printer::NullPrinterBackend;
 use ruffle_core::backend::storage::MemoryStorageBackend;
 use ruffle_core::backend::{
     audio::NullAudioBackend, input::NullInputBackend, render::NullRenderer,
 };
+use ruffle_core::memory::GcParameters;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::Player;
 use std::cell::RefCell;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 type Error = Box<dyn std::error::Error>;
 
 // This macro generates test cases for a given list of SWFs.
 macro_rules! swf_tests {
     ($($(#[$attr:meta])* ($name:ident, $path:expr, $num_frames:literal),)*) => {
+        /// The folders of the SWFs tested by `swf_tests!`.
+        const SWF_TESTS: &[&str] = &[$($path),*];
+
         $(
         #[test]
         $(#[$attr])*
@@ -36,6 +44,9 @@ macro_rules! swf_tests {
 // This macro generates test cases for a given list of SWFs using `test_swf_approx`.
 macro_rules! swf_tests_approx {
     ($($(#[$attr:meta])* ($name:ident, $path:expr, $num_frames:literal, $epsilon:literal),)*) => {
+        /// The folders of the SWFs tested by `swf_tests_approx!`.
+        const SWF_TESTS_APPROX: &[&str] = &[$($path),*];
+
         $(
         #[test]
         $(#[$attr])*
@@ -55,6 +66,8 @@ macro_rules! swf_tests_approx {
 // Format: (test_name, test_folder, number_of_frames_to_run)
 // The test folder is a relative to core/tests/swfs
 // Inside the folder is expected to be "test.swf" and "output.txt" with the correct output.
+// Run the tests with `RUFFLE_REGENERATE_OUTPUT=1` to write Ruffle's output to "output.txt"
+// instead of comparing against it, such as to record the current behavior before a refactor.
 swf_tests! {
     (add_property, "avm1/add_property", 1),
     (as_transformed_flag, "avm1/as_transformed_flag", 3),
@@ -71,6 +84,7 @@ swf_tests! {
     (execution_order1, "avm1/execution_order1", 3),
     (execution_order2, "avm1/execution_order2", 15),
     (execution_order3, "avm1/execution_order3", 5),
+    (execution_order4, "avm1/execution_order4", 2),
     (single_frame, "avm1/single_frame", 2),
     (looping, "avm1/looping", 6),
     (matrix, "avm1/matrix", 1),
@@ -84,6 +98,9 @@ swf_tests! {
     (goto_frame2, "avm1/goto_frame2", 5),
     (goto_label, "avm1/goto_label", 4),
     (goto_methods, "avm1/goto_methods", 1),
+    (goto_scene_number, "avm1/goto_scene_number", 1),
+    (goto_scene_numeric_string, "avm1/goto_scene_numeric_string", 1),
+    (goto_scene_label, "avm1/goto_scene_label", 1),
     (goto_rewind1, "avm1/goto_rewind1", 4),
     (goto_rewind2, "avm1/goto_rewind2", 5),
     (goto_rewind3, "avm1/goto_rewind3", 2),
@@ -129,6 +146,7 @@ swf_tests! {
     (logical_ops_swf4, "avm1/logical_ops_swf4", 1),
     (logical_ops_swf8, "avm1/logical_ops_swf8", 1),
     (movieclip_depth_methods, "avm1/movieclip_depth_methods", 3),
+    (depths, "avm1/depths", 1),
     (get_variable_in_scope, "avm1/get_variable_in_scope", 1),
     (movieclip_init_object, "avm1/movieclip_init_object", 1),
     (greater_swf6, "avm1/greater_swf6", 1),
@@ -174,6 +192,7 @@ swf_tests! {
     (undefined_to_string_swf6, "avm1/undefined_to_string_swf6", 1),
     (define_function2_preload, "avm1/define_function2_preload", 1),
     (define_function2_preload_order, "avm1/define_function2_preload_order", 1),
+    (truncated_jpeg, "avm1/truncated_jpeg", 1),
     (mcl_as_broadcaster, "avm1/mcl_as_broadcaster", 1),
     (uncaught_exception, "avm1/uncaught_exception", 1),
     (uncaught_exception_bubbled, "avm1/uncaught_exception_bubbled", 1),
@@ -185,12 +204,15 @@ swf_tests! {
     (unloadmovie, "avm1/unloadmovie", 11),
     (unloadmovienum, "avm1/unloadmovienum", 11),
     (unloadmovie_method, "avm1/unloadmovie_method", 11),
+    (unload_scripts, "avm1/unload_scripts", 5),
+    (remove_object_placed_by_script, "avm1/remove_object_placed_by_script", 2),
     (mcl_loadclip, "avm1/mcl_loadclip", 11),
     (mcl_unloadclip, "avm1/mcl_unloadclip", 11),
     (mcl_getprogress, "avm1/mcl_getprogress", 6),
     (loadvariables, "avm1/loadvariables", 3),
     (loadvariablesnum, "avm1/loadvariablesnum", 3),
     (loadvariables_method, "avm1/loadvariables_method", 3),
+    (clip_events_initialize_data, "avm1/clip_events_initialize_data", 3),
     (xml_load, "avm1/xml_load", 1),
     (with_return, "avm1/with_return", 1),
     (cross_movie_root, "avm1/cross_movie_root", 5),
@@ -222,6 +244,12 @@ swf_tests! {
     (with, "avm1/with", 1),
     (prototype_properties, "avm1/prototype_properties", 1),
     (stage_object_properties_get_var, "avm1/stage_object_properties_get_var", 1),
+    (avm2_constant_pool, "avm2/constant_pool", 1),
+    (avm2_method_body, "avm2/method_body", 1),
+    (avm2_infinite_loop, "avm2/infinite_loop", 2),
+    (avm2_simple_script, "avm2/simple_script", 1),
+    (avm2_event_dispatcher, "avm2/event_dispatcher", 1),
+    (avm2_document_class, "avm2/document_class", 4),
 }
 
 // TODO: These tests have some inaccuracies currently, so we use approx_eq to test that numeric values are close enough.
@@ -270,10 +298,17 @@ macro_rules! assert_eq {
 
 /// Loads an SWF and runs it through the Ruffle core for a number of frames.
 /// Tests that the trace output matches the given expected output.
+///
+/// With `RUFFLE_REGENERATE_OUTPUT` set, the output is written to the expected output
+/// file instead.
 fn test_swf(swf_path: &str, num_frames: u32, expected_output_path: &str) -> Result<(), Error> {
-    let expected_output = std::fs::read_to_string(expected_output_path)?.replace("\r\n", "\n");
-
     let trace_log = run_swf(swf_path, num_frames)?;
+    if std::env::var_os("RUFFLE_REGENERATE_OUTPUT").is_some() {
+        std::fs::write(expected_output_path, trace_log)?;
+        return Ok(());
+    }
+
+    let expected_output = std::fs::read_to_string(expected_output_path)?.replace("\r\n", "\n");
     assert_eq!(
         trace_log, expected_output,
         "ruffle output != flash player output"
@@ -311,13 +346,91 @@ fn test_swf_approx(
     Ok(())
 }
 
-/// Loads an SWF and runs it through the Ruffle core for a number of frames.
-/// Tests that the trace output matches the given expected output.
-fn run_swf(swf_path: &str, num_frames: u32) -> Result<String, Error> {
-    let _ = log::set_logger(&TRACE_LOGGER).map(|()| log::set_max_level(log::LevelFilter::Info));
+/// Every folder in `tests/swfs` with a movie and its expected output is tested, so
+/// that new tests aren't forgotten in the lists above.
+#[test]
+fn every_swf_is_tested() -> Result<(), Error> {
+    // Folders whose output isn't compared yet.
+    const UNTESTED: &[&str] = &["avm1/define_function2"];
+
+    for group in std::fs::read_dir("tests/swfs")? {
+        let group = group?;
+        for test in std::fs::read_dir(group.path())? {
+            let path = test?.path();
+            if !path.join("test.swf").is_file() || !path.join("output.txt").is_file() {
+                continue;
+            }
+            let folder = format!(
+                "{}/{}",
+                group.file_name().to_string_lossy(),
+                path.file_name().unwrap().to_string_lossy()
+            );
+            assert!(
+                SWF_TESTS.contains(&folder.as_str())
+                    || SWF_TESTS_APPROX.contains(&folder.as_str())
+                    || UNTESTED.contains(&folder.as_str()),
+                "{} isn't in the list of SWF tests",
+                folder
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Players share no state, so movies give the same output when several play at once
+/// on different threads.
+#[test]
+fn concurrent_players() -> Result<(), Error> {
+    let tests = [
+        ("avm1/array_prototyping", 1),
+        ("avm1/movieclip_prototype_extension", 1),
+        ("avm1/global_is_bare", 1),
+        ("avm1/loadmovie", 2),
+        ("avm1/execution_order2", 15),
+    ];
+    let threads: Vec<_> = tests
+        .iter()
+        .map(|&(path, num_frames)| {
+            std::thread::spawn(move || {
+                test_swf(
+                    &format!("tests/swfs/{}/test.swf", path),
+                    num_frames,
+                    &format!("tests/swfs/{}/output.txt", path),
+                )
+                .map_err(|e| e.to_string())
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    Ok(())
+}
+
+/// Players share no state, so a movie that changes built-in prototypes doesn't
+/// affect another player on the same thread.
+#[test]
+fn interleaved_players() -> Result<(), Error> {
+    let swf_path = "tests/swfs/avm1/movieclip_prototype_extension/test.swf";
+    let expected_output =
+        std::fs::read_to_string("tests/swfs/avm1/movieclip_prototype_extension/output.txt")?
+            .replace("\r\n", "\n");
+
+    let (first, mut first_executor) = create_player(swf_path)?;
+    let (second, mut second_executor) = create_player(swf_path)?;
+    first.lock().unwrap().run_frame();
+    first_executor.block_all()?;
+    second.lock().unwrap().run_frame();
+    second_executor.block_all()?;
+
+    assert_eq!(trace_log(), expected_output.repeat(2));
+    Ok(())
+}
 
+/// Creates a player for an SWF, with the executor that runs its loads.
+fn create_player(swf_path: &str) -> Result<(Arc<Mutex<Player>>, NullExecutor), Error> {
     let base_path = Path::new(swf_path).parent().unwrap();
-    let (mut executor, channel) = NullExecutor::new();
+    let (executor, channel) = NullExecutor::new();
     let movie = SwfMovie::from_path(swf_path)?;
     let player = Player::new(
         Box::new(NullRenderer),
@@ -326,7 +439,19 @@ fn run_swf(swf_path: &str, num_frames: u32) -> Result<String, Error> {
         Box::new(NullInputBackend::new()),
         movie,
         Box::new(MemoryStorageBackend::default()),
+        Box::new(NullFontBackend::new()),
+        Box::new(NullPrinterBackend::new()),
+        Box::new(NullAccessibilityBackend::new()),
+        Box::new(TestLogBackend),
+        GcParameters::default(),
     )?;
+    Ok((player, executor))
+}
+
+/// Loads an SWF and runs it through the Ruffle core for a number of frames.
+/// Tests that the trace output matches the given expected output.
+fn run_swf(swf_path: &str, num_frames: u32) -> Result<String, Error> {
+    let (player, mut executor) = create_player(swf_path)?;
 
     for _ in 0..num_frames {
         player.lock().unwrap().run_frame();
@@ -342,25 +467,20 @@ thread_local! {
     static TRACE_LOG: RefCell<String> = RefCell::new(String::new());
 }
 
-static TRACE_LOGGER: TraceLogger = TraceLogger;
-
-/// `TraceLogger` captures output from AVM trace actions into a String.
-struct TraceLogger;
+/// `TestLogBackend` captures the output of AVM trace actions, and the errors Flash
+/// Player shows with it, into a String.
+struct TestLogBackend;
 
 fn trace_log() -> String {
     TRACE_LOG.with(|log| log.borrow().clone())
 }
 
-impl log::Log for TraceLogger {
-    fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.target() == "avm_trace"
+impl LogBackend for TestLogBackend {
+    fn avm_trace(&mut self, message: &str) {
+        TRACE_LOG.with(|log| log.borrow_mut().push_str(&format!("{}\n", message)));
     }
 
-    fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            TRACE_LOG.with(|log| log.borrow_mut().push_str(&format!("{}\n", record.args())));
-        }
+    fn avm_warning(&mut self, message: &str) {
+        self.avm_trace(message);
     }
-
-    fn flush(&self) {}
 }
@@ -0,0 +1,393 @@
+//! Screenshots and recordings of the movie, saved to the capture folder.
+//!
+//! Recordings are saved as animated GIFs. With the `video_capture` feature, they can
+//! also be saved as MP4 or WebM videos with the movie's sound, which are encoded by
+//! running `ffmpeg`.
+
+#[cfg(feature = "video_capture")]
+use crate::audio::AudioRecording;
+use image::gif::GifEncoder;
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbaImage};
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use ruffle_core::Player;
+use std::error::Error;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The file format of recordings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureFormat {
+    Gif,
+    #[cfg(feature = "video_capture")]
+    Mp4,
+    #[cfg(feature = "video_capture")]
+    WebM,
+}
+
+impl CaptureFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            CaptureFormat::Gif => "gif",
+            #[cfg(feature = "video_capture")]
+            CaptureFormat::Mp4 => "mp4",
+            #[cfg(feature = "video_capture")]
+            CaptureFormat::WebM => "webm",
+        }
+    }
+}
+
+impl FromStr for CaptureFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gif" => Ok(CaptureFormat::Gif),
+            #[cfg(feature = "video_capture")]
+            "mp4" => Ok(CaptureFormat::Mp4),
+            #[cfg(feature = "video_capture")]
+            "webm" => Ok(CaptureFormat::WebM),
+            _ => Err(format!("Unknown capture format: {}", s)),
+        }
+    }
+}
+
+/// Takes screenshots and recordings of a movie, named after the movie.
+pub struct Capture {
+    dir: PathBuf,
+    name: String,
+    format: CaptureFormat,
+    recording: Option<Recording>,
+
+    #[cfg(feature = "video_capture")]
+    audio: Option<AudioRecording>,
+}
+
+impl Capture {
+    /// Captures into the given folder, or the user's pictures folder if none is given.
+    pub fn new(dir: Option<PathBuf>, movie_path: &Path, format: CaptureFormat) -> Self {
+        let dir = dir
+            .or_else(dirs::picture_dir)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let name = movie_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        Self {
+            dir,
+            name,
+            format,
+            recording: None,
+            #[cfg(feature = "video_capture")]
+            audio: None,
+        }
+    }
+
+    /// Records the movie's sound in video recordings.
+    #[cfg(feature = "video_capture")]
+    pub fn with_audio(mut self, audio: AudioRecording) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Saves the current frame as a PNG file, and returns its path.
+    pub fn save_screenshot(&self, player: &mut Player) -> Result<PathBuf, Box<dyn Error>> {
+        let frame = capture_image(player)?;
+        let path = self.next_path("png")?;
+        frame.save(&path)?;
+        Ok(path)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Starts recording each frame, until `stop_recording` is called.
+    pub fn start_recording(&mut self, player: &Player) -> Result<(), Box<dyn Error>> {
+        let path = self.next_path(self.format.extension())?;
+        let frame_rate = player.frame_rate();
+        let recording = match self.format {
+            CaptureFormat::Gif => Recording::Gif(GifRecording::new(path, frame_rate)),
+            #[cfg(feature = "video_capture")]
+            _ => Recording::Video(video::VideoRecording::new(
+                path,
+                self.format,
+                frame_rate,
+                self.audio.clone(),
+            )),
+        };
+        self.recording = Some(recording);
+        Ok(())
+    }
+
+    /// Adds the current frame to the recording, if one is running.
+    pub fn record_frame(&mut self, player: &mut Player) -> Result<(), Box<dyn Error>> {
+        if let Some(recording) = &mut self.recording {
+            let frame = capture_image(player)?;
+            recording.push(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Stops the recording, and returns the path it was saved to.
+    pub fn stop_recording(&mut self) -> Result<Option<PathBuf>, Box<dyn Error>> {
+        match self.recording.take() {
+            Some(recording) => recording.finish().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The first unused path in the capture folder for a file of the given type,
+    /// such as `movie-001.png`.
+    fn next_path(&self, extension: &str) -> Result<PathBuf, Box<dyn Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        (1..10000)
+            .map(|i| {
+                self.dir
+                    .join(format!("{}-{:03}.{}", self.name, i, extension))
+            })
+            .find(|path| !path.exists())
+            .ok_or_else(|| "The capture folder is full".into())
+    }
+}
+
+enum Recording {
+    Gif(GifRecording),
+    #[cfg(feature = "video_capture")]
+    Video(video::VideoRecording),
+}
+
+impl Recording {
+    fn push(&mut self, frame: RgbaImage) -> Result<(), Box<dyn Error>> {
+        match self {
+            Recording::Gif(recording) => {
+                recording.push(frame);
+                Ok(())
+            }
+            #[cfg(feature = "video_capture")]
+            Recording::Video(recording) => recording.push(frame),
+        }
+    }
+
+    fn finish(self) -> Result<PathBuf, Box<dyn Error>> {
+        match self {
+            Recording::Gif(recording) => recording.finish(),
+            #[cfg(feature = "video_capture")]
+            Recording::Video(recording) => recording.finish(),
+        }
+    }
+}
+
+/// Frames of an animated GIF, which are encoded once the recording stops.
+struct GifRecording {
+    path: PathBuf,
+    frame_rate: f64,
+    frames: Vec<RgbaImage>,
+}
+
+impl GifRecording {
+    fn new(path: PathBuf, frame_rate: f64) -> Self {
+        Self {
+            path,
+            frame_rate,
+            frames: vec![],
+        }
+    }
+
+    fn push(&mut self, frame: RgbaImage) {
+        let frame = match self.frames.first() {
+            Some(first) => fit_frame(frame, first.width(), first.height()),
+            None => frame,
+        };
+        self.frames.push(frame);
+    }
+
+    fn finish(self) -> Result<PathBuf, Box<dyn Error>> {
+        // GIF delays are in hundredths of a second.
+        let delay = Delay::from_numer_denom_ms(100_000, (self.frame_rate * 100.0).max(1.0) as u32);
+        let mut encoder = GifEncoder::new(File::create(&self.path)?);
+        encoder.encode_frames(
+            self.frames
+                .into_iter()
+                .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+        )?;
+        Ok(self.path)
+    }
+}
+
+/// Renders the current frame of the player.
+fn capture_image(player: &mut Player) -> Result<RgbaImage, Box<dyn Error>> {
+    let frame = player
+        .capture_frame()
+        .ok_or("The renderer is unable to capture frames")?;
+    to_image(frame)
+}
+
+fn to_image(bitmap: Bitmap) -> Result<RgbaImage, Box<dyn Error>> {
+    let data = match bitmap.data {
+        BitmapFormat::Rgba(data) => data,
+        BitmapFormat::Rgb(data) => data
+            .chunks_exact(3)
+            .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+    };
+    RgbaImage::from_raw(bitmap.width, bitmap.height, data)
+        .ok_or_else(|| "Captured frame is the wrong size".into())
+}
+
+/// Scales a frame to the size of the recording, if the window was resized since the
+/// recording started.
+fn fit_frame(frame: RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if frame.width() == width && frame.height() == height {
+        frame
+    } else {
+        image::imageops::resize(&frame, width, height, FilterType::Triangle)
+    }
+}
+
+#[cfg(feature = "video_capture")]
+mod video {
+    use super::{fit_frame, CaptureFormat};
+    use crate::audio::AudioRecording;
+    use image::RgbaImage;
+    use std::error::Error;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command, Stdio};
+
+    /// A video encoded by `ffmpeg` as frames are recorded. The sound is added once the
+    /// recording stops, as `ffmpeg` only reads one input from its standard input.
+    pub struct VideoRecording {
+        path: PathBuf,
+        format: CaptureFormat,
+        frame_rate: f64,
+        audio: Option<AudioRecording>,
+        encoder: Option<(Child, u32, u32)>,
+    }
+
+    impl VideoRecording {
+        pub fn new(
+            path: PathBuf,
+            format: CaptureFormat,
+            frame_rate: f64,
+            audio: Option<AudioRecording>,
+        ) -> Self {
+            if let Some(audio) = &audio {
+                audio.start();
+            }
+            Self {
+                path,
+                format,
+                frame_rate,
+                audio,
+                encoder: None,
+            }
+        }
+
+        /// The path the video is encoded to before the sound is added.
+        fn video_path(&self) -> PathBuf {
+            self.path.with_extension(format!(
+                "video.{}",
+                self.path.extension().unwrap_or_default().to_string_lossy()
+            ))
+        }
+
+        pub fn push(&mut self, frame: RgbaImage) -> Result<(), Box<dyn Error>> {
+            if self.encoder.is_none() {
+                let codec = match self.format {
+                    CaptureFormat::WebM => "libvpx-vp9",
+                    _ => "libx264",
+                };
+                let child = Command::new("ffmpeg")
+                    .args(&["-y", "-loglevel", "error"])
+                    .args(&["-f", "rawvideo", "-pix_fmt", "rgba"])
+                    .args(&["-s", &format!("{}x{}", frame.width(), frame.height())])
+                    .args(&["-r", &self.frame_rate.to_string()])
+                    .args(&["-i", "-"])
+                    // Most players need the size of YUV 4:2:0 videos to be even.
+                    .args(&["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+                    .args(&["-c:v", codec, "-pix_fmt", "yuv420p"])
+                    .arg(self.video_path())
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("Unable to run ffmpeg: {}", e))?;
+                self.encoder = Some((child, frame.width(), frame.height()));
+            }
+
+            let (child, width, height) = self.encoder.as_mut().unwrap();
+            let frame = fit_frame(frame, *width, *height);
+            child
+                .stdin
+                .as_mut()
+                .ok_or("ffmpeg closed its input")?
+                .write_all(&frame)?;
+            Ok(())
+        }
+
+        pub fn finish(mut self) -> Result<PathBuf, Box<dyn Error>> {
+            let samples = self.audio.as_ref().map(|audio| audio.stop());
+            let (mut child, _, _) = self.encoder.take().ok_or("No frames were recorded")?;
+            // Closing the input ends the video.
+            drop(child.stdin.take());
+            if !child.wait()?.success() {
+                return Err("ffmpeg was unable to encode the video".into());
+            }
+
+            let video_path = self.video_path();
+            let (audio, samples) = match (&self.audio, samples) {
+                (Some(audio), Some(samples)) if !samples.is_empty() => (audio, samples),
+                _ => {
+                    std::fs::rename(&video_path, &self.path)?;
+                    return Ok(self.path);
+                }
+            };
+
+            let audio_path = self.path.with_extension("wav");
+            write_wav(&audio_path, audio.sample_rate(), &samples)?;
+            let codec = match self.format {
+                CaptureFormat::WebM => "libopus",
+                _ => "aac",
+            };
+            let status = Command::new("ffmpeg")
+                .args(&["-y", "-loglevel", "error"])
+                .arg("-i")
+                .arg(&video_path)
+                .arg("-i")
+                .arg(&audio_path)
+                .args(&["-c:v", "copy", "-c:a", codec, "-shortest"])
+                .arg(&self.path)
+                .status()?;
+            std::fs::remove_file(&video_path)?;
+            std::fs::remove_file(&audio_path)?;
+            if !status.success() {
+                return Err("ffmpeg was unable to add the sound to the video".into());
+            }
+            Ok(self.path)
+        }
+    }
+
+    /// Writes 16-bit stereo samples to a WAV file.
+    fn write_wav(path: &Path, sample_rate: u32, samples: &[[i16; 2]]) -> std::io::Result<()> {
+        let data_len = samples.len() as u32 * 4;
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&2u16.to_le_bytes()); // Channels
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 4).to_le_bytes()); // Bytes per second
+        wav.extend_from_slice(&4u16.to_le_bytes()); // Bytes per sample frame
+        wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for [left, right] in samples {
+            wav.extend_from_slice(&left.to_le_bytes());
+            wav.extend_from_slice(&right.to_le_bytes());
+        }
+        std::fs::write(path, wav)
+    }
+}
@@ -0,0 +1,100 @@
+use ruffle_core::debugger::DebuggerConnection;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+enum Message {
+    Line(String),
+    Disconnected,
+}
+
+/// Lets a debugger UI attach over TCP, exchanging JSON messages one per line.
+///
+/// One UI can be attached at a time. Others can attach once it disconnects.
+pub struct TcpDebuggerConnection {
+    /// The stream of the attached UI, if any.
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    receiver: Receiver<Message>,
+}
+
+impl TcpDebuggerConnection {
+    /// Listens for debugger UIs on the given port of the local machine.
+    pub fn listen(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        log::info!("Waiting for a debugger on port {}", port);
+
+        let stream = Arc::new(Mutex::new(None));
+        let (sender, receiver) = mpsc::channel();
+        let accepted_stream = Arc::clone(&stream);
+        thread::spawn(move || {
+            for incoming in listener.incoming() {
+                match incoming {
+                    Ok(incoming) => {
+                        if let Err(e) = Self::serve(incoming, &accepted_stream, &sender) {
+                            log::warn!("Debugger connection failed: {}", e);
+                        }
+                        *accepted_stream.lock().unwrap() = None;
+                        if sender.send(Message::Disconnected).is_err() {
+                            // The player is gone.
+                            break;
+                        }
+                    }
+                    Err(e) => log::warn!("Couldn't accept a debugger: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { stream, receiver })
+    }
+
+    /// Forwards the messages of an attached UI until it disconnects.
+    fn serve(
+        incoming: TcpStream,
+        stream: &Mutex<Option<TcpStream>>,
+        sender: &Sender<Message>,
+    ) -> io::Result<()> {
+        log::info!("Debugger attached from {}", incoming.peer_addr()?);
+        *stream.lock().unwrap() = Some(incoming.try_clone()?);
+        for line in BufReader::new(incoming).lines() {
+            let line = line?;
+            if !line.trim().is_empty() && sender.send(Message::Line(line)).is_err() {
+                break;
+            }
+        }
+        log::info!("Debugger detached");
+        Ok(())
+    }
+}
+
+impl DebuggerConnection for TcpDebuggerConnection {
+    fn send(&mut self, message: &str) {
+        let mut stream = self.stream.lock().unwrap();
+        if let Some(writer) = stream.as_mut() {
+            if writeln!(writer, "{}", message).is_err() {
+                *stream = None;
+            }
+        }
+    }
+
+    fn poll(&mut self) -> Option<String> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Line(line)) => return Some(line),
+                Ok(Message::Disconnected) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn wait(&mut self) -> Option<String> {
+        if self.stream.lock().unwrap().is_none() {
+            return None;
+        }
+        match self.receiver.recv() {
+            Ok(Message::Line(line)) => Some(line),
+            Ok(Message::Disconnected) | Err(_) => None,
+        }
+    }
+}
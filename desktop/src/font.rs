@@ -0,0 +1,159 @@
+use font_kit::family_name::FamilyName;
+use font_kit::hinting::HintingOptions;
+use font_kit::outline::OutlineSink;
+use font_kit::properties::{Properties, Style, Weight};
+use font_kit::source::SystemSource;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+use ruffle_core::backend::font::{FontBackend, GlyphBuilder, EM_SQUARE_SIZE};
+use ruffle_core::swf;
+use std::sync::Arc;
+
+/// Font backend that loads fonts installed on the system using `font-kit`.
+pub struct SystemFontBackend {
+    source: SystemSource,
+}
+
+impl SystemFontBackend {
+    pub fn new() -> Self {
+        SystemFontBackend {
+            source: SystemSource::new(),
+        }
+    }
+
+    /// Converts a font into a `DefineFont3` definition, with a glyph for every character
+    /// in the Basic Multilingual Plane that the font supports.
+    fn convert_font(
+        font: &font_kit::font::Font,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+    ) -> swf::Font {
+        let metrics = font.metrics();
+        let units_per_em = metrics.units_per_em as f32;
+        let scale = EM_SQUARE_SIZE / units_per_em;
+
+        let mut glyphs = vec![];
+        for code in 0..=u16::MAX {
+            let c = match std::char::from_u32(code.into()) {
+                Some(c) => c,
+                None => continue,
+            };
+            let glyph_id = match font.glyph_for_char(c) {
+                Some(glyph_id) if glyph_id != 0 => glyph_id,
+                _ => continue,
+            };
+
+            let mut sink = GlyphSink(GlyphBuilder::new(units_per_em));
+            if let Err(e) = font.outline(glyph_id, HintingOptions::None, &mut sink) {
+                log::warn!("Unable to load glyph {} of font {}: {}", glyph_id, name, e);
+                continue;
+            }
+            let advance = font
+                .advance(glyph_id)
+                .map(|advance| advance.x() * scale)
+                .unwrap_or(0.0);
+
+            glyphs.push(swf::Glyph {
+                shape_records: sink.0.into_shape_records(),
+                code,
+                advance: Some(advance.min(f32::from(i16::MAX)) as i16),
+                bounds: None,
+            });
+        }
+
+        swf::Font {
+            version: 3,
+            id: 0,
+            name: name.to_string(),
+            language: swf::Language::Unknown,
+            layout: Some(swf::FontLayout {
+                ascent: (metrics.ascent * scale) as u16,
+                descent: (-metrics.descent * scale) as u16,
+                leading: (metrics.line_gap * scale) as i16,
+                kerning: vec![],
+            }),
+            glyphs,
+            is_small_text: false,
+            is_shift_jis: false,
+            is_ansi: false,
+            is_bold,
+            is_italic,
+        }
+    }
+}
+
+impl FontBackend for SystemFontBackend {
+    fn font_names(&self) -> Vec<String> {
+        self.source.all_families().unwrap_or_default()
+    }
+
+    fn load_font(&mut self, name: &str, is_bold: bool, is_italic: bool) -> Option<swf::Font> {
+        let mut properties = Properties::new();
+        if is_bold {
+            properties.weight(Weight::BOLD);
+        }
+        if is_italic {
+            properties.style(Style::Italic);
+        }
+
+        let handle = self
+            .source
+            .select_best_match(&[FamilyName::Title(name.to_string())], &properties)
+            .ok()?;
+        match handle.load() {
+            Ok(font) => Some(Self::convert_font(&font, name, is_bold, is_italic)),
+            Err(e) => {
+                log::warn!("Unable to load system font {}: {}", name, e);
+                None
+            }
+        }
+    }
+
+    fn load_font_data(&mut self, font: &swf::Font4) -> Option<swf::Font> {
+        let data = font.data.clone()?;
+        match font_kit::font::Font::from_bytes(Arc::new(data), 0) {
+            Ok(font_data) => Some(Self::convert_font(
+                &font_data,
+                &font.name,
+                font.is_bold,
+                font.is_italic,
+            )),
+            Err(e) => {
+                log::warn!("Unable to load DefineFont4 font {}: {}", font.name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Adapts a `GlyphBuilder` to receive outlines from `font-kit`.
+struct GlyphSink(GlyphBuilder);
+
+impl OutlineSink for GlyphSink {
+    fn move_to(&mut self, to: Vector2F) {
+        self.0.move_to(to.x(), to.y());
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.0.line_to(to.x(), to.y());
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.0
+            .quadratic_curve_to(ctrl.x(), ctrl.y(), to.x(), to.y());
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.0.cubic_curve_to(
+            (ctrl.from_x(), ctrl.from_y()),
+            (ctrl.to_x(), ctrl.to_y()),
+            to.x(),
+            to.y(),
+        );
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
@@ -0,0 +1,189 @@
+//! Gamepad support, by mapping gamepad buttons and sticks to keys.
+//!
+//! Flash has no gamepad API, so the gamepad presses keys on the movie's behalf.
+
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use ruffle_core::events::KeyCode;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// How far a stick must be pushed before it presses its key.
+const AXIS_THRESHOLD: f32 = 0.5;
+
+/// The keys pressed by each gamepad button and stick.
+#[derive(Debug, Clone)]
+pub struct GamepadMapping {
+    buttons: HashMap<Button, KeyCode>,
+
+    /// The keys pressed when an axis is pushed in its negative and positive directions.
+    axes: HashMap<Axis, (KeyCode, KeyCode)>,
+}
+
+impl GamepadMapping {
+    /// Sets the key pressed by a button, parsed from `BUTTON=KEY`, where `KEY` is a
+    /// Flash key code. For example, `south=32` makes the south face button press space.
+    pub fn set_from_str(&mut self, mapping: &str) -> Result<(), String> {
+        let mut parts = mapping.splitn(2, '=');
+        let button = parts.next().unwrap_or_default().trim();
+        let key = parts
+            .next()
+            .ok_or_else(|| format!("Expected BUTTON=KEY, got {}", mapping))?
+            .trim();
+        let button = parse_button(button).ok_or_else(|| format!("Unknown button: {}", button))?;
+        let key_code = key
+            .parse::<u8>()
+            .ok()
+            .and_then(|key| KeyCode::try_from(key).ok())
+            .ok_or_else(|| format!("Unknown key code: {}", key))?;
+        self.buttons.insert(button, key_code);
+        Ok(())
+    }
+}
+
+impl Default for GamepadMapping {
+    /// The arrow keys on the d-pad and left stick, with the common action keys on
+    /// the face buttons.
+    fn default() -> Self {
+        let buttons = [
+            (Button::DPadUp, KeyCode::Up),
+            (Button::DPadDown, KeyCode::Down),
+            (Button::DPadLeft, KeyCode::Left),
+            (Button::DPadRight, KeyCode::Right),
+            (Button::South, KeyCode::Space),
+            (Button::East, KeyCode::Control),
+            (Button::West, KeyCode::Shift),
+            (Button::North, KeyCode::Z),
+            (Button::Start, KeyCode::Return),
+            (Button::Select, KeyCode::Escape),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        let axes = [
+            (Axis::LeftStickX, (KeyCode::Left, KeyCode::Right)),
+            // Pushing a stick up gives a positive value.
+            (Axis::LeftStickY, (KeyCode::Down, KeyCode::Up)),
+            (Axis::DPadX, (KeyCode::Left, KeyCode::Right)),
+            (Axis::DPadY, (KeyCode::Down, KeyCode::Up)),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        Self { buttons, axes }
+    }
+}
+
+impl FromStr for GamepadMapping {
+    type Err = String;
+
+    /// Parses a comma-separated list of `BUTTON=KEY` mappings on top of the default mapping.
+    fn from_str(mappings: &str) -> Result<Self, Self::Err> {
+        let mut mapping = Self::default();
+        for button_mapping in mappings.split(',').filter(|s| !s.trim().is_empty()) {
+            mapping.set_from_str(button_mapping)?;
+        }
+        Ok(mapping)
+    }
+}
+
+/// Reads the connected gamepads, and turns their input into key presses.
+pub struct GamepadInput {
+    gilrs: Gilrs,
+    mapping: GamepadMapping,
+
+    /// The key held by each stick that is pushed past the threshold.
+    axis_keys: HashMap<(GamepadId, Axis), KeyCode>,
+}
+
+impl GamepadInput {
+    pub fn new(mapping: GamepadMapping) -> Result<Self, gilrs::Error> {
+        Ok(Self {
+            gilrs: Gilrs::new()?,
+            mapping,
+            axis_keys: HashMap::new(),
+        })
+    }
+
+    /// Reads the pending gamepad events, and returns the keys that were pressed
+    /// (`true`) or released (`false`), in order.
+    pub fn poll(&mut self) -> Vec<(KeyCode, bool)> {
+        let mut key_events = vec![];
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(&key_code) = self.mapping.buttons.get(&button) {
+                        key_events.push((key_code, true));
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(&key_code) = self.mapping.buttons.get(&button) {
+                        key_events.push((key_code, false));
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    if let Some(&(negative, positive)) = self.mapping.axes.get(&axis) {
+                        let key_code = if value <= -AXIS_THRESHOLD {
+                            Some(negative)
+                        } else if value >= AXIS_THRESHOLD {
+                            Some(positive)
+                        } else {
+                            None
+                        };
+                        let old_key_code = self.axis_keys.get(&(event.id, axis)).copied();
+                        if key_code != old_key_code {
+                            if let Some(old_key_code) = old_key_code {
+                                self.axis_keys.remove(&(event.id, axis));
+                                key_events.push((old_key_code, false));
+                            }
+                            if let Some(key_code) = key_code {
+                                self.axis_keys.insert((event.id, axis), key_code);
+                                key_events.push((key_code, true));
+                            }
+                        }
+                    }
+                }
+                EventType::Disconnected => {
+                    // Release the keys held by the sticks of a disconnected gamepad.
+                    let id = event.id;
+                    let released: Vec<_> = self
+                        .axis_keys
+                        .iter()
+                        .filter(|((gamepad, _), _)| *gamepad == id)
+                        .map(|(&key, &key_code)| (key, key_code))
+                        .collect();
+                    for (key, key_code) in released {
+                        self.axis_keys.remove(&key);
+                        key_events.push((key_code, false));
+                    }
+                }
+                _ => (),
+            }
+        }
+        key_events
+    }
+}
+
+fn parse_button(name: &str) -> Option<Button> {
+    let button = match name.to_ascii_lowercase().as_str() {
+        "south" | "a" => Button::South,
+        "east" | "b" => Button::East,
+        "north" | "y" => Button::North,
+        "west" | "x" => Button::West,
+        "lefttrigger" | "lb" => Button::LeftTrigger,
+        "lefttrigger2" | "lt" => Button::LeftTrigger2,
+        "righttrigger" | "rb" => Button::RightTrigger,
+        "righttrigger2" | "rt" => Button::RightTrigger2,
+        "select" | "back" => Button::Select,
+        "start" => Button::Start,
+        "mode" => Button::Mode,
+        "leftthumb" => Button::LeftThumb,
+        "rightthumb" => Button::RightThumb,
+        "dpadup" => Button::DPadUp,
+        "dpaddown" => Button::DPadDown,
+        "dpadleft" => Button::DPadLeft,
+        "dpadright" => Button::DPadRight,
+        _ => return None,
+    };
+    Some(button)
+}
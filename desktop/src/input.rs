@@ -1,17 +1,20 @@
-use clipboard::{ClipboardContext, ClipboardProvider};
+use arboard::Clipboard;
 use ruffle_core::backend::input::{InputBackend, MouseCursor};
-use ruffle_core::events::{KeyCode, PlayerEvent};
+use ruffle_core::events::{KeyCode, MouseWheelDelta, PlayerEvent};
 use std::collections::HashSet;
 use std::rc::Rc;
-use winit::event::{ElementState, VirtualKeyCode, WindowEvent};
-use winit::window::Window;
+use winit::event::{ElementState, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+use winit::window::{Fullscreen, Window};
 
 pub struct WinitInputBackend {
     keys_down: HashSet<VirtualKeyCode>,
     window: Rc<Window>,
     cursor_visible: bool,
     last_key: KeyCode,
-    clipboard: ClipboardContext,
+    clipboard: Option<Clipboard>,
+
+    /// Keys held by gamepad buttons. A key appears once for each button holding it.
+    virtual_keys_down: Vec<KeyCode>,
 }
 
 impl WinitInputBackend {
@@ -21,7 +24,25 @@ impl WinitInputBackend {
             cursor_visible: true,
             last_key: KeyCode::Unknown,
             window,
-            clipboard: ClipboardProvider::new().unwrap(),
+            clipboard: Clipboard::new()
+                .map_err(|e| log::warn!("Unable to access clipboard: {}", e))
+                .ok(),
+            virtual_keys_down: Vec::new(),
+        }
+    }
+
+    /// Presses or releases a key on behalf of another input device, like a gamepad,
+    /// and returns the event that should be forwarded to the player.
+    pub fn set_virtual_key(&mut self, key_code: KeyCode, is_down: bool) -> PlayerEvent {
+        self.last_key = key_code;
+        if is_down {
+            self.virtual_keys_down.push(key_code);
+            PlayerEvent::KeyDown { key_code }
+        } else {
+            if let Some(i) = self.virtual_keys_down.iter().position(|&k| k == key_code) {
+                self.virtual_keys_down.remove(i);
+            }
+            PlayerEvent::KeyUp { key_code }
         }
     }
 
@@ -55,6 +76,16 @@ impl WinitInputBackend {
             WindowEvent::ReceivedCharacter(codepoint) => {
                 return Some(PlayerEvent::TextInput { codepoint });
             }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    // Flash reported 3 lines for each notch of the wheel.
+                    MouseScrollDelta::LineDelta(_, notches) => {
+                        MouseWheelDelta::Lines(f64::from(notches) * 3.0)
+                    }
+                    MouseScrollDelta::PixelDelta(position) => MouseWheelDelta::Pixels(position.y),
+                };
+                return Some(PlayerEvent::MouseWheel { delta });
+            }
             _ => (),
         }
         None
@@ -63,112 +94,11 @@ impl WinitInputBackend {
 
 impl InputBackend for WinitInputBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
-        match key {
-            KeyCode::Unknown => false,
-            KeyCode::Backspace => self.keys_down.contains(&VirtualKeyCode::Back),
-            KeyCode::Return => self.keys_down.contains(&VirtualKeyCode::Return),
-            KeyCode::Shift => {
-                self.keys_down.contains(&VirtualKeyCode::LShift)
-                    || self.keys_down.contains(&VirtualKeyCode::RShift)
-            }
-            KeyCode::Control => {
-                self.keys_down.contains(&VirtualKeyCode::LControl)
-                    || self.keys_down.contains(&VirtualKeyCode::RControl)
-            }
-            KeyCode::Alt => {
-                self.keys_down.contains(&VirtualKeyCode::LAlt)
-                    || self.keys_down.contains(&VirtualKeyCode::RAlt)
-            }
-            KeyCode::CapsLock => self.keys_down.contains(&VirtualKeyCode::Capital),
-            KeyCode::Escape => self.keys_down.contains(&VirtualKeyCode::Escape),
-            KeyCode::Space => self.keys_down.contains(&VirtualKeyCode::Space),
-            KeyCode::Key0 => self.keys_down.contains(&VirtualKeyCode::Key0),
-            KeyCode::Key1 => self.keys_down.contains(&VirtualKeyCode::Key1),
-            KeyCode::Key2 => self.keys_down.contains(&VirtualKeyCode::Key2),
-            KeyCode::Key3 => self.keys_down.contains(&VirtualKeyCode::Key3),
-            KeyCode::Key4 => self.keys_down.contains(&VirtualKeyCode::Key4),
-            KeyCode::Key5 => self.keys_down.contains(&VirtualKeyCode::Key5),
-            KeyCode::Key6 => self.keys_down.contains(&VirtualKeyCode::Key6),
-            KeyCode::Key7 => self.keys_down.contains(&VirtualKeyCode::Key7),
-            KeyCode::Key8 => self.keys_down.contains(&VirtualKeyCode::Key8),
-            KeyCode::Key9 => self.keys_down.contains(&VirtualKeyCode::Key9),
-            KeyCode::A => self.keys_down.contains(&VirtualKeyCode::A),
-            KeyCode::B => self.keys_down.contains(&VirtualKeyCode::B),
-            KeyCode::C => self.keys_down.contains(&VirtualKeyCode::C),
-            KeyCode::D => self.keys_down.contains(&VirtualKeyCode::D),
-            KeyCode::E => self.keys_down.contains(&VirtualKeyCode::E),
-            KeyCode::F => self.keys_down.contains(&VirtualKeyCode::F),
-            KeyCode::G => self.keys_down.contains(&VirtualKeyCode::G),
-            KeyCode::H => self.keys_down.contains(&VirtualKeyCode::H),
-            KeyCode::I => self.keys_down.contains(&VirtualKeyCode::I),
-            KeyCode::J => self.keys_down.contains(&VirtualKeyCode::J),
-            KeyCode::K => self.keys_down.contains(&VirtualKeyCode::K),
-            KeyCode::L => self.keys_down.contains(&VirtualKeyCode::L),
-            KeyCode::M => self.keys_down.contains(&VirtualKeyCode::M),
-            KeyCode::N => self.keys_down.contains(&VirtualKeyCode::N),
-            KeyCode::O => self.keys_down.contains(&VirtualKeyCode::O),
-            KeyCode::P => self.keys_down.contains(&VirtualKeyCode::P),
-            KeyCode::Q => self.keys_down.contains(&VirtualKeyCode::Q),
-            KeyCode::R => self.keys_down.contains(&VirtualKeyCode::R),
-            KeyCode::S => self.keys_down.contains(&VirtualKeyCode::S),
-            KeyCode::T => self.keys_down.contains(&VirtualKeyCode::T),
-            KeyCode::U => self.keys_down.contains(&VirtualKeyCode::U),
-            KeyCode::V => self.keys_down.contains(&VirtualKeyCode::V),
-            KeyCode::W => self.keys_down.contains(&VirtualKeyCode::W),
-            KeyCode::X => self.keys_down.contains(&VirtualKeyCode::X),
-            KeyCode::Y => self.keys_down.contains(&VirtualKeyCode::Y),
-            KeyCode::Z => self.keys_down.contains(&VirtualKeyCode::Z),
-            KeyCode::Semicolon => self.keys_down.contains(&VirtualKeyCode::Semicolon),
-            KeyCode::Equals => self.keys_down.contains(&VirtualKeyCode::Equals),
-            KeyCode::Comma => self.keys_down.contains(&VirtualKeyCode::Comma),
-            KeyCode::Minus => self.keys_down.contains(&VirtualKeyCode::Minus),
-            KeyCode::Period => self.keys_down.contains(&VirtualKeyCode::Period),
-            KeyCode::Slash => self.keys_down.contains(&VirtualKeyCode::Slash),
-            KeyCode::Grave => self.keys_down.contains(&VirtualKeyCode::Grave),
-            KeyCode::LBracket => self.keys_down.contains(&VirtualKeyCode::LBracket),
-            KeyCode::Backslash => self.keys_down.contains(&VirtualKeyCode::Backslash),
-            KeyCode::RBracket => self.keys_down.contains(&VirtualKeyCode::RBracket),
-            KeyCode::Apostrophe => self.keys_down.contains(&VirtualKeyCode::Apostrophe),
-            KeyCode::Numpad0 => self.keys_down.contains(&VirtualKeyCode::Numpad0),
-            KeyCode::Numpad1 => self.keys_down.contains(&VirtualKeyCode::Numpad1),
-            KeyCode::Numpad2 => self.keys_down.contains(&VirtualKeyCode::Numpad2),
-            KeyCode::Numpad3 => self.keys_down.contains(&VirtualKeyCode::Numpad3),
-            KeyCode::Numpad4 => self.keys_down.contains(&VirtualKeyCode::Numpad4),
-            KeyCode::Numpad5 => self.keys_down.contains(&VirtualKeyCode::Numpad5),
-            KeyCode::Numpad6 => self.keys_down.contains(&VirtualKeyCode::Numpad6),
-            KeyCode::Numpad7 => self.keys_down.contains(&VirtualKeyCode::Numpad7),
-            KeyCode::Numpad8 => self.keys_down.contains(&VirtualKeyCode::Numpad8),
-            KeyCode::Numpad9 => self.keys_down.contains(&VirtualKeyCode::Numpad9),
-            KeyCode::Multiply => self.keys_down.contains(&VirtualKeyCode::Multiply),
-            KeyCode::Plus => self.keys_down.contains(&VirtualKeyCode::Add),
-            KeyCode::NumpadMinus => self.keys_down.contains(&VirtualKeyCode::Subtract),
-            KeyCode::NumpadPeriod => self.keys_down.contains(&VirtualKeyCode::Decimal),
-            KeyCode::NumpadSlash => self.keys_down.contains(&VirtualKeyCode::Divide),
-            KeyCode::PgUp => self.keys_down.contains(&VirtualKeyCode::PageUp),
-            KeyCode::PgDown => self.keys_down.contains(&VirtualKeyCode::PageDown),
-            KeyCode::End => self.keys_down.contains(&VirtualKeyCode::End),
-            KeyCode::Home => self.keys_down.contains(&VirtualKeyCode::Home),
-            KeyCode::Left => self.keys_down.contains(&VirtualKeyCode::Left),
-            KeyCode::Up => self.keys_down.contains(&VirtualKeyCode::Up),
-            KeyCode::Right => self.keys_down.contains(&VirtualKeyCode::Right),
-            KeyCode::Down => self.keys_down.contains(&VirtualKeyCode::Down),
-            KeyCode::Insert => self.keys_down.contains(&VirtualKeyCode::Insert),
-            KeyCode::Delete => self.keys_down.contains(&VirtualKeyCode::Delete),
-            KeyCode::Pause => self.keys_down.contains(&VirtualKeyCode::Pause),
-            KeyCode::ScrollLock => self.keys_down.contains(&VirtualKeyCode::Scroll),
-            KeyCode::F1 => self.keys_down.contains(&VirtualKeyCode::F1),
-            KeyCode::F2 => self.keys_down.contains(&VirtualKeyCode::F2),
-            KeyCode::F3 => self.keys_down.contains(&VirtualKeyCode::F3),
-            KeyCode::F4 => self.keys_down.contains(&VirtualKeyCode::F4),
-            KeyCode::F5 => self.keys_down.contains(&VirtualKeyCode::F5),
-            KeyCode::F6 => self.keys_down.contains(&VirtualKeyCode::F6),
-            KeyCode::F7 => self.keys_down.contains(&VirtualKeyCode::F7),
-            KeyCode::F8 => self.keys_down.contains(&VirtualKeyCode::F8),
-            KeyCode::F9 => self.keys_down.contains(&VirtualKeyCode::F9),
-            KeyCode::F10 => self.keys_down.contains(&VirtualKeyCode::F10),
-            KeyCode::F11 => self.keys_down.contains(&VirtualKeyCode::F11),
-            KeyCode::F12 => self.keys_down.contains(&VirtualKeyCode::F12),
-        }
+        // Some Flash key codes have more than one key, like the left and right shift keys.
+        self.keys_down
+            .iter()
+            .any(|&key_down| winit_to_ruffle_key_code(key_down) == Some(key))
+            || self.virtual_keys_down.contains(&key)
     }
 
     fn get_last_key_code(&self) -> KeyCode {
@@ -201,16 +131,36 @@ impl InputBackend for WinitInputBackend {
     }
 
     fn set_clipboard_content(&mut self, content: String) {
-        self.clipboard.set_contents(content).unwrap();
+        if let Some(clipboard) = &mut self.clipboard {
+            if let Err(e) = clipboard.set_text(content) {
+                log::warn!("Unable to set clipboard: {}", e);
+            }
+        }
+    }
+
+    fn set_full_screen(&mut self, full_screen: bool) -> bool {
+        set_window_full_screen(&self.window, full_screen);
+        true
     }
 }
 
+/// Switches the window to borderless fullscreen on its current monitor, or back.
+pub fn set_window_full_screen(window: &Window, full_screen: bool) {
+    let fullscreen = if full_screen {
+        Some(Fullscreen::Borderless(window.current_monitor()))
+    } else {
+        None
+    };
+    window.set_fullscreen(fullscreen);
+}
+
 /// Converts a winit `VirtualKeyCode` into a Ruffle `KeyCode`.
 /// Returns `None` if there is no matching Flash key code.
 fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
     let out = match key_code {
         VirtualKeyCode::Back => KeyCode::Backspace,
-        VirtualKeyCode::Return => KeyCode::Return,
+        VirtualKeyCode::Tab => KeyCode::Tab,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => KeyCode::Return,
         VirtualKeyCode::LShift | VirtualKeyCode::RShift => KeyCode::Shift,
         VirtualKeyCode::LControl | VirtualKeyCode::RControl => KeyCode::Control,
         VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => KeyCode::Alt,
@@ -291,6 +241,7 @@ fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
         VirtualKeyCode::Delete => KeyCode::Delete,
         VirtualKeyCode::Pause => KeyCode::Pause,
         VirtualKeyCode::Scroll => KeyCode::ScrollLock,
+        VirtualKeyCode::Numlock => KeyCode::NumLock,
         VirtualKeyCode::F1 => KeyCode::F1,
         VirtualKeyCode::F2 => KeyCode::F2,
         VirtualKeyCode::F3 => KeyCode::F3,
@@ -303,6 +254,9 @@ fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
         VirtualKeyCode::F10 => KeyCode::F10,
         VirtualKeyCode::F11 => KeyCode::F11,
         VirtualKeyCode::F12 => KeyCode::F12,
+        VirtualKeyCode::F13 => KeyCode::F13,
+        VirtualKeyCode::F14 => KeyCode::F14,
+        VirtualKeyCode::F15 => KeyCode::F15,
         _ => return None,
     };
     Some(out)
@@ -2,45 +2,269 @@
 #![allow(clippy::unneeded_field_pattern)]
 
 mod audio;
+mod capture;
 mod custom_event;
+mod debugger;
 mod executor;
+mod font;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod input;
 mod navigator;
+mod printer;
+mod recent;
+#[cfg(feature = "rtmp")]
+mod rtmp;
 mod storage;
 mod task;
 
+use crate::capture::{Capture, CaptureFormat};
 use crate::custom_event::RuffleEvent;
 use crate::executor::GlutinAsyncExecutor;
+use crate::recent::RecentFiles;
 use ruffle_core::{
+    backend::accessibility::NullAccessibilityBackend,
     backend::audio::{AudioBackend, NullAudioBackend},
-    Player,
+    backend::log::LogCrateBackend,
+    backend::navigator::{NavigatorBackend, UrlRewriter},
+    host::{HostObject, HostValue},
+    input_script::InputScript,
+    memory::GcParameters,
+    parameters::PlayerParameters,
+    profiler::FrameTimings,
+    stage::{StageDisplayState, StageQuality, StageScaleMode},
+    Player, PlayerBuilder,
 };
+use ruffle_render_svg::SvgRenderBackend;
 use ruffle_render_wgpu::WgpuRenderBackend;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
 use crate::storage::DiskStorageBackend;
 use ruffle_core::tag_utils::SwfMovie;
 use std::rc::Rc;
 use winit::dpi::{LogicalSize, PhysicalPosition};
-use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, TouchPhase, VirtualKeyCode,
+    WindowEvent,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Icon, WindowBuilder};
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "basic")]
 struct Opt {
+    /// The movie to play. If none is given, a dialog asks for one.
     #[structopt(name = "FILE", parse(from_os_str))]
-    input_path: PathBuf,
+    input_path: Option<PathBuf>,
+
+    /// Lists the movies that were played most recently, and exits.
+    #[structopt(long)]
+    recent: bool,
+
+    /// Plays the Nth movie listed by --recent, instead of FILE.
+    #[structopt(long, value_name = "N")]
+    open_recent: Option<usize>,
+
+    /// Exports frame N of the movie as an SVG file, without opening a window.
+    /// Frames are numbered from 1.
+    #[structopt(long, number_of_values = 2, value_names = &["N", "OUTPUT"])]
+    export_frame: Option<Vec<String>>,
+
+    /// Changes the keys pressed by gamepad buttons, as a comma-separated list of
+    /// BUTTON=KEY pairs, where KEY is a Flash key code (e.g. "south=32,start=13").
+    #[cfg(feature = "gamepad")]
+    #[structopt(long, default_value = "")]
+    gamepad_map: gamepad::GamepadMapping,
+
+    /// Loads URLs starting with FROM from TO instead, for movies whose servers are
+    /// gone (e.g. "http://example.com/=https://archive.example.org/example.com/").
+    /// Can be given more than once.
+    #[structopt(long, value_name = "FROM=TO", number_of_values = 1)]
+    rewrite_url: Vec<String>,
+
+    /// A folder of saved files, laid out as HOST/PATH, to load from when a remote
+    /// load fails.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    fallback_dir: Option<PathBuf>,
+
+    /// Lets the movie load both local files and the network. Otherwise the movie
+    /// can only load one or the other, depending on what it asks for.
+    #[structopt(long)]
+    trusted: bool,
+
+    /// Logs every request the movie makes and its response, at the info log level.
+    #[structopt(long)]
+    log_requests: bool,
+
+    /// Sets an embed parameter of the movie, as if it was given by the page
+    /// embedding it (e.g. "flashvars=name=value" or "scale=noscale").
+    /// Can be given more than once.
+    #[structopt(long, value_name = "NAME=VALUE", number_of_values = 1)]
+    param: Vec<String>,
+
+    /// Defines a variable on _root before the movie starts, as the FlashVars embed
+    /// parameter does. Can be given more than once.
+    #[structopt(long, value_name = "NAME=VALUE", number_of_values = 1)]
+    flashvars: Vec<String>,
+
+    /// How the movie is scaled to fit the window: showAll, exactFit, noBorder or
+    /// noScale.
+    #[structopt(long, value_name = "MODE")]
+    scale: Option<StageScaleMode>,
+
+    /// The rendering quality to start with: low, medium, high or best.
+    #[structopt(long)]
+    quality: Option<StageQuality>,
+
+    /// Replaces the background color of the movie, given as #RRGGBB.
+    #[structopt(long, value_name = "COLOR")]
+    background: Option<String>,
+
+    /// The URL the movie sees as its own in _url, instead of its path, for movies
+    /// that only play on the site they were made for.
+    #[structopt(long, value_name = "URL")]
+    spoof_url: Option<String>,
+
+    /// The folder that screenshots (F12) and recordings (Shift+F12) are saved to.
+    /// Defaults to the pictures folder.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    capture_dir: Option<PathBuf>,
+
+    /// The file format of recordings: gif, or with the video_capture feature, mp4 or
+    /// webm, which need ffmpeg to be installed.
+    #[structopt(long, value_name = "FORMAT", default_value = "gif")]
+    capture_format: CaptureFormat,
+
+    /// Never shows dialogs, such as the open dialog when no FILE is given.
+    #[structopt(long)]
+    no_gui: bool,
+
+    /// Runs the movie at FPS frames per second, instead of its own frame rate.
+    #[structopt(long, value_name = "FPS")]
+    frame_rate: Option<f64>,
+
+    /// Runs the movie SCALE times as fast as normal (e.g. 0.5 for slow motion).
+    #[structopt(long, value_name = "SCALE", default_value = "1.0")]
+    speed: f64,
+
+    /// Makes runs of the movie reproducible, seeding its random numbers with SEED
+    /// and advancing its clock by exactly one frame each frame.
+    #[structopt(long, value_name = "SEED")]
+    deterministic: Option<u64>,
+
+    /// Plays the input events in FILE into the movie, ignoring the keyboard and
+    /// mouse until they end. Best combined with --deterministic.
+    #[structopt(long, value_name = "FILE", parse(from_os_str))]
+    input_script: Option<PathBuf>,
+
+    /// Records the input events given to the movie to FILE when the player is
+    /// closed, to be played back later with --input-script.
+    #[structopt(long, value_name = "FILE", parse(from_os_str))]
+    record_input: Option<PathBuf>,
+
+    /// Fails loads of movies and data that take longer than SECS seconds.
+    #[structopt(long, value_name = "SECS")]
+    load_timeout: Option<f64>,
+
+    /// The most loads that may download at once.
+    #[structopt(long, value_name = "N")]
+    max_connections: Option<usize>,
+
+    /// Logs each ActionScript 1/2 action as it runs, with the values on the stack
+    /// before it, to compare how the movie runs in Flash Player.
+    #[structopt(long)]
+    trace_avm1: bool,
+
+    /// Lets a debugger UI attach on PORT of the local machine, to set breakpoints in
+    /// the movie's ActionScript and step through it.
+    #[structopt(long, value_name = "PORT")]
+    debugger_port: Option<u16>,
+
+    /// Logs how long ActionScript, tags, tessellation, rendering, audio and garbage
+    /// collection take per frame, averaged over each second, along with the memory in
+    /// use, to find out what makes a movie slow.
+    #[structopt(long)]
+    profile: bool,
+
+    /// How much memory may grow after a garbage collection before the next one
+    /// starts, as a fraction of the memory in use. Lower values collect more often.
+    #[structopt(long, value_name = "FACTOR")]
+    gc_pause_factor: Option<f64>,
+
+    /// How much garbage is collected for each byte allocated during a collection.
+    /// Higher values finish collections sooner, in longer pauses.
+    #[structopt(long, value_name = "FACTOR")]
+    gc_timing_factor: Option<f64>,
+
+    /// Slows loads down to BYTES bytes per second, to test preloaders.
+    #[structopt(long, value_name = "BYTES")]
+    simulate_download: Option<u64>,
+
+    /// Copies the shared objects saved by the movie to .sol files in DIR, without
+    /// opening a window.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    export_saves: Option<PathBuf>,
+
+    /// Replaces the shared objects saved by the movie with the .sol files in DIR,
+    /// as written by --export-saves, without opening a window.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    import_saves: Option<PathBuf>,
 }
 
 fn main() {
-    env_logger::init();
+    let mut opt = Opt::from_args();
+
+    let mut logger = env_logger::Builder::from_default_env();
+    if opt.trace_avm1 {
+        // The actions are logged at the info level, which is hidden by default.
+        logger.filter(Some("avm1_actions"), log::LevelFilter::Info);
+    }
+    if opt.profile {
+        logger.filter(Some("profiler"), log::LevelFilter::Info);
+    }
+    logger.init();
+    let mut recent_files = RecentFiles::load();
+
+    if opt.recent {
+        for (i, file) in recent_files.files().iter().enumerate() {
+            println!("{}: {}", i + 1, file.display());
+        }
+        return;
+    }
 
-    let opt = Opt::from_args();
+    let input_path = if let Some(n) = opt.open_recent {
+        match n.checked_sub(1).and_then(|i| recent_files.files().get(i)) {
+            Some(file) => file.clone(),
+            None => {
+                eprintln!("There is no recent movie {}", n);
+                std::process::exit(-1);
+            }
+        }
+    } else if let Some(input_path) = opt.input_path.take() {
+        input_path
+    } else if opt.no_gui {
+        eprintln!("No movie was given");
+        std::process::exit(-1);
+    } else {
+        match pick_file(&recent_files) {
+            Some(input_path) => input_path,
+            // The dialog was cancelled.
+            None => return,
+        }
+    };
 
-    let ret = run_player(opt.input_path);
+    let ret = if let Some(args) = &opt.export_frame {
+        export_frame(&input_path, &args[0], Path::new(&args[1]))
+    } else if let Some(dir) = &opt.export_saves {
+        export_saves(&input_path, dir)
+    } else if let Some(dir) = &opt.import_saves {
+        import_saves(&input_path, dir)
+    } else {
+        run_player(opt, input_path, &mut recent_files)
+    };
 
     if let Err(e) = ret {
         eprintln!("Fatal error:\n{}", e);
@@ -48,8 +272,251 @@ fn main() {
     }
 }
 
-fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let movie = SwfMovie::from_path(&input_path)?;
+/// Asks the user to pick a movie, starting in the folder of the movie that was played
+/// most recently.
+fn pick_file(recent_files: &RecentFiles) -> Option<PathBuf> {
+    let default_path = recent_files
+        .files()
+        .first()
+        .map(|file| file.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    tinyfiledialogs::open_file_dialog(
+        "Open a Flash movie",
+        &default_path,
+        Some((&["*.swf"], "Flash movies (*.swf)")),
+    )
+    .map(PathBuf::from)
+}
+
+/// Plays a movie in a new player, such as one dropped on the window. Each movie needs
+/// backends of its own, for its saves and its files, so this is simpler than
+/// replacing the movie of the running player.
+fn open_in_new_player(path: &Path) -> std::io::Result<()> {
+    std::process::Command::new(std::env::current_exe()?)
+        .arg(path)
+        .spawn()?;
+    Ok(())
+}
+
+/// The title of the movie's window: the title in its metadata, or its file name,
+/// along with its size and frame rate.
+fn window_title(movie: &SwfMovie, input_path: &Path, frame_rate_override: Option<f64>) -> String {
+    let name = movie.title().unwrap_or_else(|| {
+        input_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    });
+    let frame_rate = frame_rate_override.map_or_else(
+        || movie.header().frame_rate.to_string(),
+        |frame_rate| frame_rate.to_string(),
+    );
+    format!(
+        "Ruffle - {} ({}x{}, {} fps)",
+        name,
+        movie.width(),
+        movie.height(),
+        frame_rate
+    )
+}
+
+/// Whether a key press enters or leaves fullscreen: F11 or Alt+Enter, or Escape to
+/// leave it, as in Flash Player.
+fn is_full_screen_shortcut(
+    input: &KeyboardInput,
+    modifiers: ModifiersState,
+    is_full_screen: bool,
+) -> bool {
+    if input.state != ElementState::Pressed {
+        return false;
+    }
+    match input.virtual_keycode {
+        Some(VirtualKeyCode::F11) => true,
+        Some(VirtualKeyCode::Return) => modifiers.alt(),
+        Some(VirtualKeyCode::Escape) => is_full_screen,
+        _ => false,
+    }
+}
+
+/// Saves a screenshot of the movie.
+fn save_screenshot(capture: &Capture, player: &mut Player) {
+    match capture.save_screenshot(player) {
+        Ok(path) => log::info!("Saved a screenshot to {}", path.display()),
+        Err(e) => log::error!("Unable to save a screenshot: {}", e),
+    }
+}
+
+/// Starts a recording of the movie, or stops and saves the running one.
+fn toggle_recording(capture: &mut Capture, player: &Player) {
+    if capture.is_recording() {
+        stop_recording(capture);
+    } else if let Err(e) = capture.start_recording(player) {
+        log::error!("Unable to start a recording: {}", e);
+    } else {
+        log::info!("Started recording");
+    }
+}
+
+fn stop_recording(capture: &mut Capture) {
+    match capture.stop_recording() {
+        Ok(Some(path)) => log::info!("Saved a recording to {}", path.display()),
+        Ok(None) => (),
+        Err(e) => log::error!("Unable to save the recording: {}", e),
+    }
+}
+
+/// Runs a movie without a window up to the given frame, and writes that frame to an
+/// SVG file.
+fn export_frame(
+    input_path: &Path,
+    frame: &str,
+    output_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frame = match frame.parse::<u32>() {
+        Ok(frame) if frame > 0 => frame,
+        _ => return Err(format!("Invalid frame number: {}", frame).into()),
+    };
+    let movie = SwfMovie::from_path(input_path)?;
+    let (width, height) = (movie.width(), movie.height());
+    let player = PlayerBuilder::new()
+        .with_renderer(SvgRenderBackend::new(width, height))
+        .with_movie(movie)
+        .build()?;
+
+    let mut player = player.lock().unwrap();
+    for _ in 0..frame {
+        player.run_frame();
+    }
+    let svg = player
+        .export_frame_svg()
+        .ok_or("Unable to export the frame")?;
+    std::fs::write(output_path, svg)?;
+    Ok(())
+}
+
+/// Creates a player without a window, which saves shared objects where the
+/// windowed player does.
+fn saves_player(input_path: &Path) -> Result<Arc<Mutex<Player>>, Box<dyn std::error::Error>> {
+    let player = PlayerBuilder::new()
+        .with_movie(SwfMovie::from_path(input_path)?)
+        .with_storage(DiskStorageBackend::new(&input_path.to_string_lossy()))
+        .build()?;
+    Ok(player)
+}
+
+/// Writes each shared object saved by the movie to `NAME.sol` in the given folder.
+fn export_saves(input_path: &Path, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let player = saves_player(input_path)?;
+    let saves = player.lock().unwrap().export_saves();
+    for (name, data) in &saves {
+        let path = dir.join(format!("{}.sol", name));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+    }
+    println!("Exported {} shared objects", saves.len());
+    Ok(())
+}
+
+/// Saves each `NAME.sol` file in the given folder and its subfolders as a shared
+/// object of the movie.
+fn import_saves(input_path: &Path, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fn read_saves(
+        dir: &Path,
+        prefix: &str,
+        saves: &mut Vec<(String, Vec<u8>)>,
+    ) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_dir() {
+                read_saves(&entry.path(), &format!("{}{}/", prefix, file_name), saves)?;
+            } else if file_name.ends_with(".sol") {
+                let name = &file_name[..file_name.len() - ".sol".len()];
+                saves.push((format!("{}{}", prefix, name), std::fs::read(entry.path())?));
+            }
+        }
+        Ok(())
+    }
+
+    let mut saves = vec![];
+    read_saves(dir, "", &mut saves)?;
+    let player = saves_player(input_path)?;
+    if !player.lock().unwrap().import_saves(&saves) {
+        return Err("Unable to save some of the shared objects".into());
+    }
+    println!("Imported {} shared objects", saves.len());
+    Ok(())
+}
+
+/// How many frames of timings the player keeps with `--profile`, which is more
+/// than run in `PROFILE_INTERVAL` at any usual frame rate.
+const PROFILE_FRAMES: usize = 240;
+
+/// How often the frame timings are logged with `--profile`.
+const PROFILE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Logs the mean timings of the frames after `last_frame`, and moves it to the last
+/// of them, along with the memory in use.
+fn log_frame_timings(player: &mut Player, last_frame: &mut u32) {
+    let timings: Vec<FrameTimings> = player
+        .frame_timings()
+        .into_iter()
+        .filter(|timings| timings.frame > *last_frame)
+        .collect();
+    if let Some(average) = FrameTimings::average(&timings) {
+        log::info!(
+            target: "profiler",
+            "{} frames, {} per frame",
+            timings.len(),
+            average
+        );
+        *last_frame = average.frame;
+    }
+
+    let stats = player.gc_stats();
+    log::info!(
+        target: "profiler",
+        "{} KiB allocated (peak {} KiB), {} display objects, {} AVM1 objects",
+        stats.allocated_bytes / 1024,
+        stats.peak_allocated_bytes / 1024,
+        stats.display_objects,
+        stats.avm1_objects
+    );
+}
+
+/// Writes the input recorded by the player to an input script file.
+fn save_input_recording(player: &Mutex<Player>, path: &Path, deterministic_seed: Option<u64>) {
+    let recording = match player.lock().unwrap().stop_input_recording() {
+        Some(recording) => recording,
+        None => return,
+    };
+    let mut script = String::new();
+    if let Some(seed) = deterministic_seed {
+        script.push_str(&format!(
+            "# Recorded with --deterministic {}; play back with the same seed.\n",
+            seed
+        ));
+    }
+    script.push_str(&recording.to_string());
+    match std::fs::write(path, script) {
+        Ok(()) => log::info!("Recorded {} input events", recording.len()),
+        Err(e) => log::error!("Unable to save the input recording: {}", e),
+    }
+}
+
+fn run_player(
+    opt: Opt,
+    input_path: PathBuf,
+    recent_files: &mut RecentFiles,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let movie = match &opt.spoof_url {
+        Some(url) => SwfMovie::from_data(&std::fs::read(&input_path)?, Some(url.clone()))?,
+        None => SwfMovie::from_path(&input_path)?,
+    };
+    recent_files.add(&input_path);
     let movie_size = LogicalSize::new(movie.width(), movie.height());
 
     let icon_bytes = include_bytes!("../assets/favicon-32.rgba");
@@ -58,18 +525,23 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     let event_loop: EventLoop<RuffleEvent> = EventLoop::with_user_event();
     let window = Rc::new(
         WindowBuilder::new()
-            .with_title(format!(
-                "Ruffle - {}",
-                input_path.file_name().unwrap_or_default().to_string_lossy()
-            ))
+            .with_title(window_title(&movie, &input_path, opt.frame_rate))
             .with_window_icon(Some(icon))
             .with_inner_size(movie_size)
             .build(&event_loop)?,
     );
     let viewport_size = movie_size.to_physical(window.scale_factor());
 
+    #[cfg(feature = "video_capture")]
+    let mut audio_recording = None;
     let audio: Box<dyn AudioBackend> = match audio::CpalAudioBackend::new() {
-        Ok(audio) => Box::new(audio),
+        Ok(audio) => {
+            #[cfg(feature = "video_capture")]
+            {
+                audio_recording = Some(audio.recording());
+            }
+            Box::new(audio)
+        }
         Err(e) => {
             log::error!("Unable to create audio device: {}", e);
             Box::new(NullAudioBackend::new())
@@ -79,40 +551,229 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         window.as_ref(),
         (viewport_size.width, viewport_size.height),
     )?);
+    let mut parameters = PlayerParameters::new();
+    for param in &opt.param {
+        let mut parts = param.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) => parameters.set(name, value),
+            _ => return Err(format!("Expected NAME=VALUE, got {}", param).into()),
+        }
+    }
+    for flash_var in &opt.flashvars {
+        let mut parts = flash_var.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) => parameters
+                .flash_vars
+                .push((name.to_string(), value.to_string())),
+            _ => return Err(format!("Expected NAME=VALUE, got {}", flash_var).into()),
+        }
+    }
+    if opt.scale.is_some() {
+        parameters.scale_mode = opt.scale;
+    }
+    if opt.quality.is_some() {
+        parameters.quality = opt.quality;
+    }
+    if let Some(background) = &opt.background {
+        parameters.background_color = None;
+        parameters.set("bgcolor", background);
+        if parameters.background_color.is_none() {
+            return Err(format!("Invalid background color: {}", background).into());
+        }
+    }
+    let mut url_rewriter = UrlRewriter::new();
+    for rewrite in &opt.rewrite_url {
+        let mut parts = rewrite.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(from), Some(to)) => url_rewriter.add_prefix(from, to),
+            _ => return Err(format!("Expected FROM=TO, got {}", rewrite).into()),
+        }
+    }
     let (executor, chan) = GlutinAsyncExecutor::new(event_loop.create_proxy());
-    let navigator = Box::new(navigator::ExternalNavigatorBackend::with_base_path(
-        input_path
-            .parent()
-            .unwrap_or_else(|| std::path::Path::new("")),
-        chan,
-        event_loop.create_proxy(),
-    )); //TODO: actually implement this backend type
+    let mut navigator = Box::new(
+        navigator::ExternalNavigatorBackend::with_base_path(
+            input_path
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("")),
+            chan,
+            event_loop.create_proxy(),
+        )
+        .with_sandbox(if opt.trusted {
+            navigator::LocalSandbox::LocalTrusted
+        } else {
+            navigator::LocalSandbox::for_movie(&movie)
+        })
+        .with_url_rewriter(url_rewriter)
+        .with_local_fallback(opt.fallback_dir),
+    );
+    if opt.log_requests {
+        navigator.add_network_observer(Rc::new(navigator::LogNetworkObserver));
+    }
     let input = Box::new(input::WinitInputBackend::new(window.clone()));
-    let storage = Box::new(DiskStorageBackend::new(
+    let storage = Box::new(DiskStorageBackend::new(&input_path.to_string_lossy()));
+    let font = Box::new(font::SystemFontBackend::new());
+    let printer = Box::new(printer::PdfPrinterBackend::new(
         input_path.file_name().unwrap_or_default().as_ref(),
     ));
-    let player = Player::new(renderer, audio, navigator, input, movie, storage)?;
+    // TODO: Expose the accessibility tree through the platform accessibility APIs.
+    let accessibility = Box::new(NullAccessibilityBackend::new());
+    // `trace()` output goes to the terminal with the rest of the log.
+    let log = Box::new(LogCrateBackend::new());
+    let mut gc_parameters = GcParameters::default();
+    if let Some(pause_factor) = opt.gc_pause_factor {
+        gc_parameters.pause_factor = pause_factor;
+    }
+    if let Some(timing_factor) = opt.gc_timing_factor {
+        gc_parameters.timing_factor = timing_factor;
+    }
+    let player = Player::new(
+        renderer,
+        audio,
+        navigator,
+        input,
+        movie,
+        storage,
+        font,
+        printer,
+        accessibility,
+        log,
+        gc_parameters,
+    )?;
     player.lock().unwrap().set_is_playing(true); // Desktop player will auto-play.
+    #[cfg(feature = "rtmp")]
+    player
+        .lock()
+        .unwrap()
+        .set_rtmp_backend(Box::new(rtmp::SocketRtmpBackend::new()));
 
+    // Movies made for the desktop player can retitle the window with
+    // `Ruffle.setWindowTitle(title)`.
+    {
+        let window = window.clone();
+        let host = HostObject::new().with_method("setWindowTitle", move |args| {
+            if let Some(HostValue::String(title)) = args.get(0) {
+                window.set_title(title);
+            }
+            HostValue::Undefined
+        });
+        player.lock().unwrap().register_host_object("Ruffle", &host);
+    }
+    if let Some(load_timeout) = opt.load_timeout {
+        player
+            .lock()
+            .unwrap()
+            .set_load_timeout(Some(Duration::from_secs_f64(load_timeout.max(0.0))));
+    }
+    if let Some(max_connections) = opt.max_connections {
+        player.lock().unwrap().set_max_connections(max_connections);
+    }
     player
         .lock()
         .unwrap()
-        .set_viewport_dimensions(viewport_size.width, viewport_size.height);
+        .set_bandwidth_limit(opt.simulate_download);
+    player.lock().unwrap().apply_parameters(&parameters);
+    if opt.frame_rate.is_some() {
+        player
+            .lock()
+            .unwrap()
+            .set_frame_rate_override(opt.frame_rate);
+    }
+    player.lock().unwrap().set_time_scale(opt.speed);
+    if let Some(seed) = opt.deterministic {
+        player.lock().unwrap().enable_deterministic_mode(seed);
+    }
+    if let Some(path) = &opt.input_script {
+        let script: InputScript = std::fs::read_to_string(path)?.parse()?;
+        player.lock().unwrap().play_input_script(script);
+    }
+    if opt.record_input.is_some() {
+        player.lock().unwrap().start_input_recording();
+    }
+    if opt.trace_avm1 {
+        player.lock().unwrap().set_trace_avm1(true);
+    }
+    if let Some(port) = opt.debugger_port {
+        let connection = debugger::TcpDebuggerConnection::listen(port)?;
+        player.lock().unwrap().attach_debugger(Box::new(connection));
+    }
+    let profile = opt.profile;
+    if profile {
+        let start = Instant::now();
+        player
+            .lock()
+            .unwrap()
+            .start_profiling(Box::new(move || start.elapsed()), PROFILE_FRAMES);
+    }
+    let record_input = opt.record_input.clone();
+    let deterministic_seed = opt.deterministic;
+
+    #[allow(unused_mut)]
+    let mut capture = Capture::new(opt.capture_dir.clone(), &input_path, opt.capture_format);
+    #[cfg(feature = "video_capture")]
+    {
+        if let Some(audio_recording) = audio_recording {
+            capture = capture.with_audio(audio_recording);
+        }
+    }
+    let mut last_frame_number = 0;
+
+    player.lock().unwrap().set_viewport_dimensions(
+        viewport_size.width,
+        viewport_size.height,
+        window.scale_factor(),
+    );
+
+    #[cfg(feature = "gamepad")]
+    let mut gamepad = match gamepad::GamepadInput::new(opt.gamepad_map) {
+        Ok(gamepad) => Some(gamepad),
+        Err(e) => {
+            log::error!("Unable to read gamepads: {}", e);
+            None
+        }
+    };
 
     let mut mouse_pos = PhysicalPosition::new(0.0, 0.0);
+    let mut modifiers = ModifiersState::empty();
     let mut time = Instant::now();
     let mut next_frame_time = Instant::now();
+    let mut last_profile_time = Instant::now();
+    let mut last_profiled_frame = 0;
     loop {
         // Poll UI events
         event_loop.run(move |event, _window_target, control_flow| {
             match event {
                 winit::event::Event::LoopDestroyed => {
-                    player.lock().unwrap().flush_shared_objects();
+                    let mut player_lock = player.lock().unwrap();
+                    player_lock.flush_shared_objects();
+                    let unimplemented_features = player_lock.unimplemented_features();
+                    if !unimplemented_features.is_empty() {
+                        eprintln!("Unimplemented features used:\n{}", unimplemented_features);
+                    }
+                    drop(player_lock);
+                    if let Some(path) = &record_input {
+                        save_input_recording(&player, path, deterministic_seed);
+                    }
+                    stop_recording(&mut capture);
                     return;
                 }
 
                 // Core loop
                 winit::event::Event::MainEventsCleared => {
+                    #[cfg(feature = "gamepad")]
+                    {
+                        if let Some(gamepad) = &mut gamepad {
+                            let mut player_lock = player.lock().unwrap();
+                            for (key_code, is_down) in gamepad.poll() {
+                                let event = player_lock
+                                    .input_mut()
+                                    .downcast_mut::<input::WinitInputBackend>()
+                                    .unwrap()
+                                    .set_virtual_key(key_code, is_down);
+                                player_lock.handle_event(event);
+                            }
+                        }
+                    }
+
                     let new_time = Instant::now();
                     let dt = new_time.duration_since(time).as_micros();
                     if dt > 0 {
@@ -120,6 +781,19 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                         let mut player_lock = player.lock().unwrap();
                         player_lock.tick(dt as f64 / 1000.0);
                         next_frame_time = new_time + player_lock.time_til_next_frame();
+                        if capture.is_recording() && player_lock.frame_number() != last_frame_number
+                        {
+                            if let Err(e) = capture.record_frame(&mut player_lock) {
+                                log::error!("Unable to record the frame: {}", e);
+                                stop_recording(&mut capture);
+                            }
+                        }
+                        last_frame_number = player_lock.frame_number();
+                        if profile && new_time.duration_since(last_profile_time) >= PROFILE_INTERVAL
+                        {
+                            last_profile_time = new_time;
+                            log_frame_timings(&mut player_lock, &mut last_profiled_frame);
+                        }
                         if player_lock.needs_render() {
                             window.request_redraw();
                         }
@@ -132,12 +806,32 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                 winit::event::Event::WindowEvent { event, .. } => match event {
                     WindowEvent::Resized(size) => {
                         let mut player_lock = player.lock().unwrap();
-                        player_lock.set_viewport_dimensions(size.width, size.height);
+                        player_lock.set_viewport_dimensions(
+                            size.width,
+                            size.height,
+                            window.scale_factor(),
+                        );
                         player_lock
                             .renderer_mut()
                             .set_viewport_dimensions(size.width, size.height);
                         window.request_redraw();
                     }
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    } => {
+                        // The window may have moved to a display with a different DPI.
+                        let mut player_lock = player.lock().unwrap();
+                        player_lock.set_viewport_dimensions(
+                            new_inner_size.width,
+                            new_inner_size.height,
+                            scale_factor,
+                        );
+                        player_lock
+                            .renderer_mut()
+                            .set_viewport_dimensions(new_inner_size.width, new_inner_size.height);
+                        window.request_redraw();
+                    }
                     WindowEvent::CursorMoved { position, .. } => {
                         let mut player_lock = player.lock().unwrap();
                         mouse_pos = position;
@@ -179,8 +873,66 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                             window.request_redraw();
                         }
                     }
+                    WindowEvent::Touch(touch) => {
+                        let mut player_lock = player.lock().unwrap();
+                        let phase = match touch.phase {
+                            TouchPhase::Started => ruffle_core::events::TouchPhase::Start,
+                            TouchPhase::Moved => ruffle_core::events::TouchPhase::Move,
+                            TouchPhase::Ended => ruffle_core::events::TouchPhase::End,
+                            TouchPhase::Cancelled => ruffle_core::events::TouchPhase::Cancel,
+                        };
+                        player_lock.handle_event(ruffle_core::PlayerEvent::Touch {
+                            id: touch.id,
+                            phase,
+                            x: touch.location.x,
+                            y: touch.location.y,
+                        });
+                        if player_lock.needs_render() {
+                            window.request_redraw();
+                        }
+                    }
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                    WindowEvent::KeyboardInput { .. } | WindowEvent::ReceivedCharacter(_) => {
+                    WindowEvent::DroppedFile(path) => match open_in_new_player(&path) {
+                        // The dropped movie replaces this one.
+                        Ok(()) => *control_flow = ControlFlow::Exit,
+                        Err(e) => log::error!("Unable to open {}: {}", path.display(), e),
+                    },
+                    WindowEvent::ModifiersChanged(state) => modifiers = state,
+                    WindowEvent::KeyboardInput { input, .. }
+                        if is_full_screen_shortcut(
+                            &input,
+                            modifiers,
+                            window.fullscreen().is_some(),
+                        ) =>
+                    {
+                        let full_screen = window.fullscreen().is_none();
+                        input::set_window_full_screen(&window, full_screen);
+                        // The window is resized afterwards, which resizes the stage.
+                        player.lock().unwrap().set_display_state(if full_screen {
+                            StageDisplayState::FullScreen
+                        } else {
+                            StageDisplayState::Normal
+                        });
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F12),
+                                ..
+                            },
+                        ..
+                    } => {
+                        let mut player_lock = player.lock().unwrap();
+                        if modifiers.shift() {
+                            toggle_recording(&mut capture, &player_lock);
+                        } else {
+                            save_screenshot(&capture, &mut player_lock);
+                        }
+                    }
+                    WindowEvent::KeyboardInput { .. }
+                    | WindowEvent::ReceivedCharacter(_)
+                    | WindowEvent::MouseWheel { .. } => {
                         let mut player_lock = player.lock().unwrap();
                         if let Some(event) = player_lock
                             .input_mut()
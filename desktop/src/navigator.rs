@@ -1,18 +1,60 @@
 //! Navigator backend for web
 
 use crate::custom_event::RuffleEvent;
+use futures::AsyncReadExt;
+use isahc::config::{Configurable, RedirectPolicy};
+use isahc::http::Request;
+use isahc::HttpClient;
 use ruffle_core::backend::navigator::{
-    NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    NavigationMethod, NavigatorBackend, NetworkObserver, NetworkObservers, OwnedFuture,
+    RequestInfo, RequestOptions, ResponseInfo, UrlRewriter, WindowTarget,
 };
 use ruffle_core::loader::Error;
+use ruffle_core::tag_utils::SwfMovie;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 use url::Url;
 use winit::event_loop::EventLoopProxy;
 
+/// What a movie played from the local filesystem may load, like the local
+/// sandboxes of Flash Player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalSandbox {
+    /// Local files, but not the network.
+    LocalWithFile,
+
+    /// The network, but not local files.
+    LocalWithNetwork,
+
+    /// Both local files and the network, for movies the user trusts.
+    LocalTrusted,
+}
+
+impl LocalSandbox {
+    /// The sandbox a movie asks for with its `FileAttributes` tag.
+    pub fn for_movie(movie: &SwfMovie) -> Self {
+        if movie.use_network_sandbox() {
+            LocalSandbox::LocalWithNetwork
+        } else {
+            LocalSandbox::LocalWithFile
+        }
+    }
+
+    fn allows_files(self) -> bool {
+        self != LocalSandbox::LocalWithNetwork
+    }
+
+    fn allows_network(self) -> bool {
+        self != LocalSandbox::LocalWithFile
+    }
+}
+
 /// Implementation of `NavigatorBackend` for non-web environments that can call
 /// out to a web browser.
 pub struct ExternalNavigatorBackend {
@@ -25,8 +67,29 @@ pub struct ExternalNavigatorBackend {
     /// The base path for all relative fetches.
     relative_base_path: PathBuf,
 
+    /// What the movie may load.
+    sandbox: LocalSandbox,
+
     /// The time that the SWF was launched.
     start_time: Instant,
+
+    /// The client for remote fetches, which keeps cookies between requests.
+    /// `None` if the client couldn't be created.
+    client: Option<Rc<HttpClient>>,
+
+    /// Rewrites the URLs of fetches, to redirect loads from servers that are gone.
+    url_rewriter: UrlRewriter,
+
+    /// A folder of saved files, laid out as `HOST/PATH`, to load from when a
+    /// remote fetch fails.
+    local_fallback: Option<PathBuf>,
+
+    /// Called with the URL and target of each `getURL` navigation before the
+    /// browser is opened. Returns `true` if it handled the navigation itself.
+    navigation_handler: Option<Box<dyn Fn(&str, &WindowTarget) -> bool>>,
+
+    /// Watch the requests made by the movie.
+    network_observers: NetworkObservers,
 }
 
 impl ExternalNavigatorBackend {
@@ -39,7 +102,13 @@ impl ExternalNavigatorBackend {
             channel,
             event_loop,
             relative_base_path: PathBuf::new(),
+            sandbox: LocalSandbox::LocalTrusted,
             start_time: Instant::now(),
+            client: Self::create_client(),
+            url_rewriter: UrlRewriter::new(),
+            local_fallback: None,
+            navigation_handler: None,
+            network_observers: NetworkObservers::new(),
         }
     }
 
@@ -57,22 +126,231 @@ impl ExternalNavigatorBackend {
             channel,
             event_loop,
             relative_base_path,
+            sandbox: LocalSandbox::LocalTrusted,
             start_time: Instant::now(),
+            client: Self::create_client(),
+            url_rewriter: UrlRewriter::new(),
+            local_fallback: None,
+            navigation_handler: None,
+            network_observers: NetworkObservers::new(),
+        }
+    }
+
+    pub fn with_sandbox(mut self, sandbox: LocalSandbox) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    pub fn with_url_rewriter(mut self, url_rewriter: UrlRewriter) -> Self {
+        self.url_rewriter = url_rewriter;
+        self
+    }
+
+    pub fn with_local_fallback(mut self, local_fallback: Option<PathBuf>) -> Self {
+        self.local_fallback = local_fallback;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_navigation_handler(
+        mut self,
+        handler: impl Fn(&str, &WindowTarget) -> bool + 'static,
+    ) -> Self {
+        self.navigation_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// The file in the local fallback folder to load in place of a remote URL.
+    fn local_fallback_path(&self, url: &Url) -> Option<PathBuf> {
+        let mut path = self.local_fallback.clone()?;
+        path.push(url.host_str()?);
+        for segment in url.path_segments()?.filter(|s| !s.is_empty() && *s != "..") {
+            path.push(segment);
+        }
+        Some(path)
+    }
+
+    fn create_client() -> Option<Rc<HttpClient>> {
+        let client = HttpClient::builder()
+            .redirect_policy(RedirectPolicy::Limit(10))
+            .auto_referer()
+            .automatic_decompression(true)
+            .cookies()
+            .build();
+        match client {
+            Ok(client) => Some(Rc::new(client)),
+            Err(e) => {
+                log::error!("Unable to create HTTP client: {}", e);
+                None
+            }
         }
     }
+
+    /// Fetches a URL, local or remote, recording the HTTP status of the response
+    /// in `status`.
+    fn fetch_url(
+        &self,
+        url: &str,
+        options: RequestOptions,
+        status: Rc<Cell<Option<u16>>>,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        let path = match Url::parse(url) {
+            // Windows paths like `C:\movie.swf` parse as URLs with a `c:` scheme.
+            _ if Path::new(url).is_absolute() => PathBuf::from(url),
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                if !self.sandbox.allows_network() {
+                    return sandbox_error(url.into_string(), self.sandbox);
+                }
+                let fallback_path = self.local_fallback_path(&url);
+                let fetch = self.fetch_http(url, options, status);
+                return match fallback_path {
+                    Some(fallback_path) => Box::pin(async move {
+                        match fetch.await {
+                            Ok(data) => Ok(data),
+                            Err(e) => {
+                                log::info!("{}; loading {} instead", e, fallback_path.display());
+                                fs::read(&fallback_path).map_err(|_| e)
+                            }
+                        }
+                    }),
+                    None => fetch,
+                };
+            }
+            Ok(url) if url.scheme() == "file" => match url.to_file_path() {
+                Ok(path) => path,
+                Err(()) => {
+                    let url = url.into_string();
+                    return Box::pin(async move {
+                        Err(network_error(format!("Invalid file URL: {}", url)))
+                    });
+                }
+            },
+            Ok(url) => {
+                let url = url.into_string();
+                return Box::pin(
+                    async move { Err(network_error(format!("Unsupported URL: {}", url))) },
+                );
+            }
+            // Relative URLs are loaded from the folder of the movie.
+            Err(_) => {
+                let mut path = self.relative_base_path.clone();
+                path.push(url);
+                path
+            }
+        };
+        if !self.sandbox.allows_files() {
+            return sandbox_error(path.to_string_lossy().into_owned(), self.sandbox);
+        }
+
+        Box::pin(async move { fs::read(path).map_err(Error::NetworkError) })
+    }
+
+    /// Fetches a remote URL over HTTP.
+    fn fetch_http(
+        &self,
+        url: Url,
+        options: RequestOptions,
+        status: Rc<Cell<Option<u16>>>,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        let client = self.client.clone();
+        let mut request = Request::builder()
+            .uri(url.as_str())
+            .method(match options.method() {
+                NavigationMethod::GET => "GET",
+                NavigationMethod::POST => "POST",
+            });
+        for (name, value) in options.headers() {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let body = match options.body() {
+            Some((data, mime)) => {
+                request = request.header("Content-Type", mime.as_str());
+                data.clone()
+            }
+            None => Vec::new(),
+        };
+
+        Box::pin(async move {
+            let client = client.ok_or_else(|| network_error("No HTTP client"))?;
+            let request = request.body(body).map_err(network_error)?;
+            let mut response = client.send_async(request).await.map_err(network_error)?;
+            status.set(Some(response.status().as_u16()));
+            if !response.status().is_success() {
+                log::warn!("HTTP status {} for {}", response.status(), url);
+                return Err(Error::HttpNotOk(response.status().as_u16()));
+            }
+
+            let mut data = Vec::new();
+            response.body_mut().read_to_end(&mut data).await?;
+            Ok(data)
+        })
+    }
+}
+
+/// Logs every request and response, to help find out why a movie's loads fail.
+pub struct LogNetworkObserver;
+
+impl NetworkObserver for LogNetworkObserver {
+    fn request_sent(&self, request: &RequestInfo) {
+        log::info!(
+            "Request #{}: {:?} {} ({} bytes)",
+            request.id,
+            request.method,
+            request.url,
+            request.body_size
+        );
+    }
+
+    fn response_received(&self, response: &ResponseInfo) {
+        let status = response
+            .status
+            .map_or_else(|| "-".to_string(), |status| status.to_string());
+        match &response.error {
+            Some(error) => log::info!(
+                "Response #{}: {} status {}, failed: {}",
+                response.id,
+                response.url,
+                status,
+                error
+            ),
+            None => log::info!(
+                "Response #{}: {} status {} ({} bytes)",
+                response.id,
+                response.url,
+                status,
+                response.size
+            ),
+        }
+    }
+}
+
+fn sandbox_error(url: String, sandbox: LocalSandbox) -> OwnedFuture<Vec<u8>, Error> {
+    log::warn!("Blocked load of {} by the {:?} sandbox", url, sandbox);
+    Box::pin(async move {
+        Err(network_error(format!(
+            "{:?} sandbox doesn't allow loading {}",
+            sandbox, url
+        )))
+    })
+}
+
+fn network_error(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+    Error::NetworkError(io::Error::new(io::ErrorKind::Other, error))
 }
 
 impl NavigatorBackend for ExternalNavigatorBackend {
     fn navigate_to_url(
         &self,
         url: String,
-        _window_spec: Option<String>,
+        window_spec: Option<String>,
         vars_method: Option<(NavigationMethod, HashMap<String, String>)>,
     ) {
         //TODO: Should we return a result for failed opens? Does Flash care?
 
         //NOTE: Flash desktop players / projectors ignore the window parameter,
         //      unless it's a `_layer`, and we shouldn't handle that anyway.
+        //      Every target opens the system browser.
+        let target = WindowTarget::from_window_spec(window_spec.as_deref());
         let mut parsed_url = match Url::parse(&url) {
             Ok(parsed_url) => parsed_url,
             Err(e) => {
@@ -101,6 +379,17 @@ impl NavigatorBackend for ExternalNavigatorBackend {
             None => url,
         };
 
+        if let Some(handler) = &self.navigation_handler {
+            if handler(&modified_url, &target) {
+                return;
+            }
+        }
+
+        if modified_url.starts_with("javascript:") {
+            log::warn!("Ignoring navigation to {}", modified_url);
+            return;
+        }
+
         match webbrowser::open(&modified_url) {
             Ok(_output) => {}
             Err(e) => log::error!("Could not open URL {}: {}", modified_url, e),
@@ -111,13 +400,17 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         Instant::now().duration_since(self.start_time)
     }
 
-    fn fetch(&self, url: &str, _options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
-        // Load from local filesystem.
-        // TODO: Support network loads, honor sandbox type (local-with-filesystem, local-with-network, remote, ...)
-        let mut path = self.relative_base_path.clone();
-        path.push(url);
-
-        Box::pin(async move { fs::read(path).map_err(Error::NetworkError) })
+    fn fetch(&self, url: &str, options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
+        let url = self.url_rewriter.rewrite(url);
+        let request = self.network_observers.request(&url, &options);
+        let status = Rc::new(Cell::new(None));
+        let fetch = self.fetch_url(&url, options, Rc::clone(&status));
+        let network_observers = self.network_observers.clone();
+        Box::pin(async move {
+            let result = fetch.await;
+            network_observers.response(&request, status.get(), &result);
+            result
+        })
     }
 
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
@@ -129,4 +422,8 @@ impl NavigatorBackend for ExternalNavigatorBackend {
             );
         }
     }
+
+    fn add_network_observer(&mut self, observer: Rc<dyn NetworkObserver>) {
+        self.network_observers.add(observer);
+    }
 }
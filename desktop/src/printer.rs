@@ -0,0 +1,202 @@
+use image::jpeg::JPEGEncoder;
+use image::{ColorType, RgbImage};
+use ruffle_core::backend::printer::{PrintJobSettings, PrinterBackend};
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Printer backend that saves print jobs as PDF files.
+///
+/// The saved file is opened with the system PDF viewer, from which it can be
+/// sent to a printer.
+pub struct PdfPrinterBackend {
+    output_dir: PathBuf,
+    name: String,
+    settings: PrintJobSettings,
+    pages: Vec<Bitmap>,
+    job_count: u32,
+}
+
+impl PdfPrinterBackend {
+    pub fn new(scope: &Path) -> Self {
+        let output_dir = dirs::document_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default()
+            .join(Path::new("Ruffle"));
+        let name = scope
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "print".to_string());
+
+        PdfPrinterBackend {
+            output_dir,
+            name,
+            settings: PrintJobSettings::default(),
+            pages: vec![],
+            job_count: 0,
+        }
+    }
+
+    fn save_job(&mut self) -> Result<PathBuf, Box<dyn Error>> {
+        let pdf = write_pdf(&self.settings, &self.pages)?;
+        fs::create_dir_all(&self.output_dir)?;
+        self.job_count += 1;
+        let path = self
+            .output_dir
+            .join(format!("{}-{}.pdf", self.name, self.job_count));
+        fs::write(&path, pdf)?;
+        Ok(path)
+    }
+}
+
+impl PrinterBackend for PdfPrinterBackend {
+    fn start_job(&mut self) -> Option<PrintJobSettings> {
+        self.pages.clear();
+        Some(self.settings.clone())
+    }
+
+    fn add_page(&mut self, page: Bitmap) {
+        self.pages.push(page);
+    }
+
+    fn send_job(&mut self) {
+        if self.pages.is_empty() {
+            return;
+        }
+
+        match self.save_job() {
+            Ok(path) => {
+                log::info!("Saved print job to {:?}", path);
+                if let Err(e) = webbrowser::open(&path.to_string_lossy()) {
+                    log::warn!("Unable to open print job {:?}: {}", path, e);
+                }
+            }
+            Err(e) => log::error!("Unable to save print job: {}", e),
+        }
+        self.pages.clear();
+    }
+}
+
+/// Writes a PDF document with one page per bitmap.
+///
+/// Each bitmap is embedded as a JPEG image that fills the printable area of the page.
+fn write_pdf(settings: &PrintJobSettings, pages: &[Bitmap]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut pdf = PdfWriter::default();
+
+    // Objects 1 and 2 are the catalog and page tree, followed by three objects per page.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 3 + i * 3).collect();
+    pdf.object(b"<< /Type /Catalog /Pages 2 0 R >>");
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    pdf.object(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .as_bytes(),
+    );
+
+    // Center the printable area on the paper.
+    let margin_x = settings.paper_width.saturating_sub(settings.page_width) / 2;
+    let margin_y = settings.paper_height.saturating_sub(settings.page_height) / 2;
+    for (page, page_id) in pages.iter().zip(page_ids) {
+        pdf.object(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /XObject << /Page {} 0 R >> >> /Contents {} 0 R >>",
+                settings.paper_width,
+                settings.paper_height,
+                page_id + 2,
+                page_id + 1
+            )
+            .as_bytes(),
+        );
+
+        let contents = format!(
+            "q {} 0 0 {} {} {} cm /Page Do Q",
+            settings.page_width, settings.page_height, margin_x, margin_y
+        );
+        pdf.stream("", contents.as_bytes());
+
+        let jpeg = encode_jpeg(page)?;
+        pdf.stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode",
+                page.width, page.height
+            ),
+            &jpeg,
+        );
+    }
+
+    Ok(pdf.finish())
+}
+
+fn encode_jpeg(page: &Bitmap) -> Result<Vec<u8>, Box<dyn Error>> {
+    let rgb = match &page.data {
+        BitmapFormat::Rgb(data) => data.clone(),
+        BitmapFormat::Rgba(data) => data
+            .chunks_exact(4)
+            .flat_map(|pixel| pixel[..3].iter().copied())
+            .collect(),
+    };
+    let image =
+        RgbImage::from_raw(page.width, page.height, rgb).ok_or("Invalid print page size")?;
+    let mut jpeg = vec![];
+    JPEGEncoder::new_with_quality(&mut jpeg, 90).encode(
+        &image,
+        page.width,
+        page.height,
+        ColorType::Rgb8,
+    )?;
+    Ok(jpeg)
+}
+
+/// Writes numbered PDF objects and the cross-reference table that locates them.
+#[derive(Default)]
+struct PdfWriter {
+    data: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn begin_object(&mut self) {
+        if self.data.is_empty() {
+            self.data.extend_from_slice(b"%PDF-1.4\n");
+        }
+        self.offsets.push(self.data.len());
+        self.data
+            .extend_from_slice(format!("{} 0 obj\n", self.offsets.len()).as_bytes());
+    }
+
+    fn object(&mut self, value: &[u8]) {
+        self.begin_object();
+        self.data.extend_from_slice(value);
+        self.data.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, dictionary: &str, contents: &[u8]) {
+        self.begin_object();
+        self.data.extend_from_slice(
+            format!("<< {} /Length {} >>\nstream\n", dictionary, contents.len()).as_bytes(),
+        );
+        self.data.extend_from_slice(contents);
+        self.data.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref_offset = self.data.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            xref.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            xref_offset
+        ));
+        self.data.extend_from_slice(xref.as_bytes());
+        self.data
+    }
+}
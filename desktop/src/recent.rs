@@ -0,0 +1,59 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The most movies kept in the list.
+const MAX_RECENT_FILES: usize = 10;
+
+/// The movies most recently opened in the player, newest first.
+///
+/// The list is kept in `recent.txt` in the player's data folder, with one path per
+/// line.
+pub struct RecentFiles {
+    path: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    pub fn load() -> Self {
+        let path = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ruffle")
+            .join("recent.txt");
+        let files = std::fs::read_to_string(&path)
+            .map(|list| {
+                list.lines()
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, files }
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Moves a movie to the top of the list, and saves the list.
+    pub fn add(&mut self, file: &Path) {
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        self.files.retain(|recent| *recent != file);
+        self.files.insert(0, file);
+        self.files.truncate(MAX_RECENT_FILES);
+        if let Err(e) = self.save() {
+            log::warn!("Unable to save the recent files list: {}", e);
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut list = String::new();
+        for file in &self.files {
+            list.push_str(&file.to_string_lossy());
+            list.push('\n');
+        }
+        std::fs::write(&self.path, list)
+    }
+}
@@ -0,0 +1,222 @@
+//! RTMP backend over sockets
+
+use ruffle_core::backend::rtmp::{RtmpBackend, RtmpTransport};
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait before polling a tunneled connection that had nothing to
+/// send or receive.
+const TUNNEL_IDLE_DELAY: Duration = Duration::from_millis(100);
+
+/// Implementation of `RtmpBackend` with a thread for each connection.
+#[derive(Default)]
+pub struct SocketRtmpBackend;
+
+impl SocketRtmpBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl RtmpBackend for SocketRtmpBackend {
+    fn connect(
+        &mut self,
+        host: &str,
+        port: u16,
+        tunneled: bool,
+    ) -> Result<Box<dyn RtmpTransport>, Box<dyn Error>> {
+        let (outgoing_sender, outgoing) = channel();
+        let (incoming, incoming_receiver) = channel();
+        let address = (host.to_string(), port);
+        if tunneled {
+            thread::spawn(move || run_tunnel(address, outgoing, incoming));
+        } else {
+            thread::spawn(move || run_socket(address, outgoing, incoming));
+        }
+
+        Ok(Box::new(ThreadTransport {
+            outgoing: outgoing_sender,
+            incoming: incoming_receiver,
+        }))
+    }
+}
+
+/// A connection whose I/O is done by another thread.
+///
+/// Dropping the transport closes the channel of outgoing data, which tells
+/// the thread to close the connection.
+struct ThreadTransport {
+    outgoing: Sender<Vec<u8>>,
+    incoming: Receiver<io::Result<Vec<u8>>>,
+}
+
+impl RtmpTransport for ThreadTransport {
+    fn send(&mut self, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.outgoing
+            .send(data.to_vec())
+            .map_err(|_| "RTMP connection closed".into())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut received = vec![];
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Ok(data)) => received.extend(data),
+                Ok(Err(e)) => return Err(e.into()),
+                Err(TryRecvError::Empty) => return Ok(received),
+                Err(TryRecvError::Disconnected) => return Err("RTMP connection closed".into()),
+            }
+        }
+    }
+}
+
+/// Connect to a server over TCP, and pass data through until either side
+/// closes the connection.
+fn run_socket(
+    address: (String, u16),
+    outgoing: Receiver<Vec<u8>>,
+    incoming: Sender<io::Result<Vec<u8>>>,
+) {
+    let mut stream = match TcpStream::connect(&address) {
+        Ok(stream) => stream,
+        Err(e) => {
+            let _ = incoming.send(Err(e));
+            return;
+        }
+    };
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            let _ = incoming.send(Err(e));
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for data in outgoing {
+            if writer.write_all(&data).is_err() {
+                break;
+            }
+        }
+        let _ = writer.shutdown(std::net::Shutdown::Both);
+    });
+
+    let mut buffer = [0; 4096];
+    loop {
+        let result = match stream.read(&mut buffer) {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(len) => Ok(buffer[..len].to_vec()),
+            Err(e) => Err(e),
+        };
+        let failed = result.is_err();
+        if incoming.send(result).is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// Tunnel RTMP over HTTP requests to a server.
+///
+/// The client opens a session, and then sends its data in numbered requests.
+/// The server answers each request with the data it has for the client, so
+/// the client polls with empty requests when it has nothing to send.
+fn run_tunnel(
+    address: (String, u16),
+    outgoing: Receiver<Vec<u8>>,
+    incoming: Sender<io::Result<Vec<u8>>>,
+) {
+    let session_id = match post(&address, "/open/1", &[0]) {
+        Ok(response) => String::from_utf8_lossy(&response).trim().to_string(),
+        Err(e) => {
+            let _ = incoming.send(Err(e));
+            return;
+        }
+    };
+
+    for sequence in 1.. {
+        let mut data = vec![];
+        let mut closed = false;
+        loop {
+            match outgoing.try_recv() {
+                Ok(chunk) => data.extend(chunk),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+        if closed {
+            let _ = post(
+                &address,
+                &format!("/close/{}/{}", session_id, sequence),
+                &[0],
+            );
+            return;
+        }
+
+        let path = if data.is_empty() {
+            format!("/idle/{}/{}", session_id, sequence)
+        } else {
+            format!("/send/{}/{}", session_id, sequence)
+        };
+        let body = if data.is_empty() { vec![0] } else { data };
+        match post(&address, &path, &body) {
+            // The first byte of each response is a hint of how often to
+            // poll, which is ignored.
+            Ok(response) if response.len() > 1 => {
+                if incoming.send(Ok(response[1..].to_vec())).is_err() {
+                    return;
+                }
+            }
+            Ok(_) => thread::sleep(TUNNEL_IDLE_DELAY),
+            Err(e) => {
+                let _ = incoming.send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+/// Send an HTTP request to an RTMPT server, returning the body of the
+/// response.
+fn post(address: &(String, u16), path: &str, body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(address)?;
+    // HTTP/1.0 keeps servers from answering with chunked bodies.
+    write!(
+        stream,
+        "POST {} HTTP/1.0\r\n\
+         Host: {}:{}\r\n\
+         Content-Type: application/x-fcs\r\n\
+         Content-Length: {}\r\n\
+         \r\n",
+        path,
+        address.0,
+        address.1,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+    let header_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid HTTP response"))?;
+    let status_line = String::from_utf8_lossy(&response[..header_end]);
+    if status_line.split(' ').nth(1) != Some("200") {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "RTMPT request failed: {}",
+                status_line.lines().next().unwrap_or("")
+            ),
+        ));
+    }
+
+    Ok(response.split_off(header_end + 4))
+}
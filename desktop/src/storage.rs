@@ -1,19 +1,34 @@
 use ruffle_core::backend::storage::StorageBackend;
 use std::fs;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
 
+/// Saves shared objects as files in the user's data directory.
+///
+/// Each movie gets its own folder, named after the domain it was loaded from, or
+/// for local movies, after a hash of its path. Shared objects are saved to
+/// `NAME.sol` files in that folder, following their local path.
 pub struct DiskStorageBackend {
     base_path: PathBuf,
+
+    /// Where older versions saved the shared objects of this movie, which are
+    /// loaded if the movie hasn't saved anything in `base_path` yet.
+    legacy_path: Option<PathBuf>,
 }
 
 impl DiskStorageBackend {
-    pub fn new(scope: &Path) -> Self {
-        let base_path = dirs::data_local_dir()
-            .unwrap()
-            .join(Path::new("ruffle"))
-            .join(scope);
+    /// Creates the storage of the movie at the given URL or file path.
+    pub fn new(movie_url: &str) -> Self {
+        let data_path = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ruffle");
+        let base_path = data_path
+            .join("SharedObjects")
+            .join(storage_namespace(movie_url));
+        let legacy_path = Path::new(movie_url)
+            .file_name()
+            .map(|file_name| data_path.join(file_name));
 
         // Create a base dir if one doesn't exist yet
         if !base_path.exists() {
@@ -23,52 +38,181 @@ impl DiskStorageBackend {
             }
         }
 
-        DiskStorageBackend { base_path }
+        DiskStorageBackend {
+            base_path,
+            legacy_path,
+        }
     }
-}
 
-impl StorageBackend for DiskStorageBackend {
-    fn get_string(&self, name: &str) -> Option<String> {
-        let full_path = self.base_path.join(Path::new(name));
-
-        match File::open(full_path) {
-            Ok(mut file) => {
-                let mut buffer = String::new();
-                if let Err(r) = file.read_to_string(&mut buffer) {
-                    log::warn!("Unable to read file content {:?}", r);
-                    None
-                } else {
-                    Some(buffer)
+    /// The file that the given shared object is saved to, or `None` if the name
+    /// would point outside of the storage folder.
+    fn path_for(&self, name: &str) -> Option<PathBuf> {
+        let mut components = vec![];
+        for component in Path::new(name).components() {
+            match component {
+                Component::Normal(component) => {
+                    components.push(component.to_string_lossy().into_owned())
+                }
+                Component::CurDir => (),
+                _ => {
+                    log::warn!("Invalid shared object name {}", name);
+                    return None;
                 }
             }
-            Err(r) => {
-                log::warn!("Unable to open file {:?}", r);
-                None
+        }
+        let file_name = format!("{}.sol", components.pop()?);
+        let mut path = self.base_path.clone();
+        path.extend(&components);
+        path.push(file_name);
+        Some(path)
+    }
+
+    /// Reads a saved file, falling back to the backup copy of the previous save
+    /// if the file is missing or empty, such as after a crash during a save.
+    fn read(&self, name: &str) -> Option<Vec<u8>> {
+        let path = self.path_for(name)?;
+        match fs::read(&path) {
+            Ok(bytes) if !bytes.is_empty() => return Some(bytes),
+            Ok(_) => log::warn!("Shared object file {} is empty", path.display()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => log::warn!("Unable to read file {}: {}", path.display(), e),
+        }
+
+        if let Ok(bytes) = fs::read(backup_path(&path)) {
+            if !bytes.is_empty() {
+                log::info!("Loading backup of shared object {}", name);
+                return Some(bytes);
             }
         }
+
+        let legacy_path = self.legacy_path.as_ref()?.join(name);
+        fs::read(legacy_path).ok()
     }
 
-    fn put_string(&mut self, name: &str, value: String) -> bool {
-        let full_path = self.base_path.join(Path::new(name));
-
-        match File::create(full_path) {
-            Ok(mut file) => {
-                if let Err(r) = file.write_all(value.as_bytes()) {
-                    log::warn!("Unable to write file content {:?}", r);
-                    false
-                } else {
-                    true
-                }
+    /// Writes a file atomically, by writing to a temporary file that replaces the
+    /// old file once it is complete. The old file is kept as a backup.
+    fn write(&self, name: &str, value: &[u8]) -> bool {
+        let path = match self.path_for(name) {
+            Some(path) => path,
+            None => return false,
+        };
+        let result = (|| -> io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
             }
+            let temp_path = path.with_extension("sol.tmp");
+            fs::write(&temp_path, value)?;
+            if path.exists() {
+                let backup_path = backup_path(&path);
+                let _ = fs::remove_file(&backup_path);
+                fs::rename(&path, backup_path)?;
+            }
+            fs::rename(&temp_path, &path)
+        })();
+        match result {
+            Ok(()) => true,
             Err(r) => {
-                log::warn!("Unable to save file {:?}", r);
+                log::warn!("Unable to save file {}: {}", path.display(), r);
                 false
             }
         }
     }
+}
+
+impl StorageBackend for DiskStorageBackend {
+    fn get_string(&self, name: &str) -> Option<String> {
+        String::from_utf8(self.read(name)?).ok()
+    }
+
+    fn put_string(&mut self, name: &str, value: String) -> bool {
+        self.write(name, value.as_bytes())
+    }
+
+    fn get_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        self.read(name)
+    }
+
+    fn put_bytes(&mut self, name: &str, value: &[u8]) -> bool {
+        self.write(name, value)
+    }
 
     fn remove_key(&mut self, name: &str) {
-        let full_path = self.base_path.join(Path::new(name));
-        let _ = fs::remove_file(full_path);
+        if let Some(path) = self.path_for(name) {
+            let _ = fs::remove_file(backup_path(&path));
+            let _ = fs::remove_file(path);
+        }
+        if let Some(legacy_path) = &self.legacy_path {
+            let _ = fs::remove_file(legacy_path.join(name));
+        }
+    }
+
+    fn get_keys(&self) -> Vec<String> {
+        let mut keys = vec![];
+        collect_keys(&self.base_path, "", &mut keys);
+        keys.sort();
+        keys
+    }
+}
+
+/// Adds the names of the shared objects saved in the given folder and its
+/// subfolders to the list.
+fn collect_keys(dir: &Path, prefix: &str, keys: &mut Vec<String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            collect_keys(&path, &format!("{}{}/", prefix, file_name), keys);
+        } else if file_name.ends_with(".sol") {
+            let name = &file_name[..file_name.len() - ".sol".len()];
+            keys.push(format!("{}{}", prefix, name));
+        }
     }
 }
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("sol.bak")
+}
+
+/// The folder name for the shared objects of the movie at the given URL.
+///
+/// Remote movies use their domain, so that movies from the same site share their
+/// shared objects like in Flash Player. Local movies use a hash of their path, so
+/// that movies with the same file name don't overwrite each other's saves.
+fn storage_namespace(movie_url: &str) -> PathBuf {
+    if let Ok(url) = Url::parse(movie_url) {
+        if let Some(host) = url.host_str() {
+            return PathBuf::from(sanitize(host));
+        }
+    }
+
+    let path = Path::new(movie_url);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let file_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    PathBuf::from("localhost").join(format!(
+        "{}-{:016x}",
+        sanitize(&file_stem),
+        fnv1a(path.to_string_lossy().as_bytes())
+    ))
+}
+
+/// Replaces the characters that aren't allowed in file names on some platforms.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// A hash that stays the same across Rust versions, unlike `DefaultHasher`, so that
+/// saves are found again after an update.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
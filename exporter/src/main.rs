@@ -1,10 +1,16 @@
 use futures::executor::block_on;
 use image::RgbaImage;
 use indicatif::{ProgressBar, ProgressStyle};
+use ruffle_core::backend::accessibility::NullAccessibilityBackend;
 use ruffle_core::backend::audio::NullAudioBackend;
+use ruffle_core::backend::font::NullFontBackend;
 use ruffle_core::backend::input::NullInputBackend;
+use ruffle_core::backend::log::NullLogBackend;
 use ruffle_core::backend::navigator::NullNavigatorBackend;
+use ruffle_core::backend::printer::NullPrinterBackend;
+use ruffle_core::backend::render::BitmapFormat;
 use ruffle_core::backend::storage::MemoryStorageBackend;
+use ruffle_core::memory::GcParameters;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::Player;
 use ruffle_render_wgpu::target::TextureTarget;
@@ -86,12 +92,17 @@ fn take_screenshot(
         Box::new(NullInputBackend::new()),
         movie,
         Box::new(MemoryStorageBackend::default()),
+        Box::new(NullFontBackend::new()),
+        Box::new(NullPrinterBackend::new()),
+        Box::new(NullAccessibilityBackend::new()),
+        Box::new(NullLogBackend::new()),
+        GcParameters::default(),
     )?;
 
     player
         .lock()
         .unwrap()
-        .set_viewport_dimensions(width, height);
+        .set_viewport_dimensions(width, height, 1.0);
 
     let mut result = Vec::new();
     let totalframes = frames + skipframes;
@@ -106,14 +117,18 @@ fn take_screenshot(
         }
         player.lock().unwrap().run_frame();
         if i >= skipframes {
-            player.lock().unwrap().render();
-            let mut player = player.lock().unwrap();
-            let renderer = player
-                .renderer_mut()
-                .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
-                .unwrap();
-            let target = renderer.target();
-            if let Some(image) = target.capture(renderer.device()) {
+            let image =
+                player
+                    .lock()
+                    .unwrap()
+                    .capture_frame()
+                    .and_then(|bitmap| match bitmap.data {
+                        BitmapFormat::Rgba(data) => {
+                            RgbaImage::from_raw(bitmap.width, bitmap.height, data)
+                        }
+                        BitmapFormat::Rgb(_) => None,
+                    });
+            if let Some(image) = image {
                 result.push(image);
             } else {
                 return Err(format!("Unable to capture frame {} of {:?}", i, swf_path).into());
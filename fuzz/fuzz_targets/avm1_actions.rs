@@ -0,0 +1,14 @@
+//! Reads AVM1 bytecode one action at a time, as the interpreter does.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use swf::avm1::read::Reader;
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks the SWF version, as some actions are read differently
+    // depending on it.
+    if let Some((&version, actions)) = data.split_first() {
+        let mut reader = Reader::new(actions, version);
+        while let Ok(Some(_)) = reader.read_action() {}
+    }
+});
@@ -0,0 +1,8 @@
+//! Parses a whole SWF, including the contents of every tag.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = swf::read_swf(data);
+});
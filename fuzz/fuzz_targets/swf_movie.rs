@@ -0,0 +1,15 @@
+//! Loads a movie the way the player does, and preloads its definition tags.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::PlayerBuilder;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(movie) = SwfMovie::from_data(data, None) {
+        // Building a player with the default null backends preloads the movie,
+        // registering the characters of its bitmap, font, sound and other
+        // definition tags in the library.
+        let _ = PlayerBuilder::new().with_movie(movie).build();
+    }
+});
@@ -0,0 +1,183 @@
+//! Comparison of rendered frames with golden images.
+//!
+//! Colors are compared by how different they look rather than by their values, so
+//! that small differences in anti-aliasing or rounding between renderers don't
+//! fail a test, but a missing stroke or a wrong gradient does.
+
+use image::{Rgba, RgbaImage};
+use std::fmt;
+
+/// How different a frame may be from its golden image and still match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// How different two pixels may look before they count as different, from 0.0
+    /// for identical colors to 1.0 for black and white. Defaults to 0.1.
+    pub threshold: f64,
+
+    /// How many pixels may be different, as a fraction of all pixels, to allow for
+    /// differences in anti-aliasing along edges. Defaults to 0.001.
+    pub max_different_pixels: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_different_pixels: 0.001,
+        }
+    }
+}
+
+/// Why a frame didn't match its golden image.
+#[derive(Debug)]
+pub enum Mismatch {
+    /// The images have different sizes.
+    Size {
+        actual: (u32, u32),
+        expected: (u32, u32),
+    },
+
+    /// Too many pixels are different.
+    Pixels {
+        different_pixels: usize,
+        total_pixels: usize,
+
+        /// The expected image, faded, with the different pixels in red.
+        difference: RgbaImage,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Size { actual, expected } => write!(
+                f,
+                "Frame is {}x{}, but the golden image is {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            Mismatch::Pixels {
+                different_pixels,
+                total_pixels,
+                ..
+            } => write!(
+                f,
+                "{} of {} pixels are different from the golden image",
+                different_pixels, total_pixels
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Compares a frame with its golden image.
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: Tolerance,
+) -> Result<(), Mismatch> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(Mismatch::Size {
+            actual: actual.dimensions(),
+            expected: expected.dimensions(),
+        });
+    }
+
+    let mut difference = RgbaImage::new(expected.width(), expected.height());
+    let mut different_pixels = 0;
+    for (x, y, expected_pixel) in expected.enumerate_pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+        if color_difference(*actual_pixel, *expected_pixel) > tolerance.threshold {
+            different_pixels += 1;
+            difference.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            let gray = 191 + (luma(blend_with_white(*expected_pixel)) / 4.0) as u8;
+            difference.put_pixel(x, y, Rgba([gray, gray, gray, 255]));
+        }
+    }
+
+    let total_pixels = (expected.width() * expected.height()) as usize;
+    if different_pixels as f64 > total_pixels as f64 * tolerance.max_different_pixels {
+        return Err(Mismatch::Pixels {
+            different_pixels,
+            total_pixels,
+            difference,
+        });
+    }
+    Ok(())
+}
+
+/// The perceived difference between two colors, from 0.0 to 1.0.
+///
+/// Colors are compared in the YIQ color space, which separates brightness from
+/// hue, with brightness weighted the most as the eye is most sensitive to it.
+fn color_difference(a: Rgba<u8>, b: Rgba<u8>) -> f64 {
+    // The difference between black and white.
+    const MAX_DIFFERENCE: f64 = 35215.0;
+
+    let a = blend_with_white(a);
+    let b = blend_with_white(b);
+    let y = luma(a) - luma(b);
+    let i = in_phase(a) - in_phase(b);
+    let q = quadrature(a) - quadrature(b);
+    ((0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q) / MAX_DIFFERENCE).sqrt()
+}
+
+/// Blends a pixel with a white background, so that transparent pixels of any color
+/// look the same.
+fn blend_with_white(pixel: Rgba<u8>) -> [f64; 3] {
+    let alpha = f64::from(pixel[3]) / 255.0;
+    let blend = |channel: u8| 255.0 + (f64::from(channel) - 255.0) * alpha;
+    [blend(pixel[0]), blend(pixel[1]), blend(pixel[2])]
+}
+
+fn luma([r, g, b]: [f64; 3]) -> f64 {
+    0.298_895_31 * r + 0.586_622_47 * g + 0.114_482_23 * b
+}
+
+fn in_phase([r, g, b]: [f64; 3]) -> f64 {
+    0.595_977_99 * r - 0.274_176_10 * g - 0.321_801_89 * b
+}
+
+fn quadrature([r, g, b]: [f64; 3]) -> f64 {
+    0.211_470_17 * r - 0.522_617_24 * g + 0.311_147_07 * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerates_small_differences() {
+        let expected = RgbaImage::from_pixel(100, 100, Rgba([255, 0, 0, 255]));
+        let mut actual = RgbaImage::from_pixel(100, 100, Rgba([254, 1, 0, 255]));
+        assert!(compare_images(&actual, &expected, Tolerance::default()).is_ok());
+
+        // A few pixels of the wrong color along an edge are allowed, but not more.
+        for x in 0..10 {
+            actual.put_pixel(x, 0, Rgba([0, 0, 255, 255]));
+        }
+        assert!(compare_images(&actual, &expected, Tolerance::default()).is_ok());
+        for x in 10..20 {
+            actual.put_pixel(x, 0, Rgba([0, 0, 255, 255]));
+        }
+        match compare_images(&actual, &expected, Tolerance::default()) {
+            Err(Mismatch::Pixels {
+                different_pixels,
+                difference,
+                ..
+            }) => {
+                assert_eq!(different_pixels, 20);
+                assert_eq!(*difference.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+            }
+            other => panic!("Expected different pixels, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn transparent_pixels_are_alike() {
+        let expected = RgbaImage::from_pixel(10, 10, Rgba([0, 0, 0, 0]));
+        let actual = RgbaImage::from_pixel(10, 10, Rgba([255, 0, 255, 0]));
+        assert!(compare_images(&actual, &expected, Tolerance::default()).is_ok());
+    }
+}
@@ -0,0 +1,136 @@
+//! Runs movies without a window, for automated testing and for checking large
+//! collections of content.
+//!
+//! Movies are rendered with the software renderer, and use backends that do
+//! nothing for everything else. Each frame can be captured as an image, and the
+//! output of `trace()` calls is collected as text. Captured frames can be compared
+//! with golden images using `compare`.
+
+pub mod compare;
+
+use image::RgbaImage;
+use ruffle_core::backend::log::LogBackend;
+use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::PlayerBuilder;
+use ruffle_render_software::SoftwareRenderBackend;
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::Path;
+use std::rc::Rc;
+
+/// How a movie is run by `run_movie`.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// The number of frames to run.
+    pub frames: u32,
+
+    /// Whether each frame is rendered and captured. Movies run faster without.
+    pub capture_frames: bool,
+
+    /// The width of the captured frames in pixels. Defaults to the movie's width.
+    pub width: Option<u32>,
+
+    /// The height of the captured frames in pixels. Defaults to the movie's height.
+    pub height: Option<u32>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            frames: 1,
+            capture_frames: true,
+            width: None,
+            height: None,
+        }
+    }
+}
+
+/// What a movie did while it was run.
+pub struct RunOutput {
+    /// Each frame, in order. Empty unless `RunOptions::capture_frames` is set.
+    pub frames: Vec<RgbaImage>,
+
+    /// The output of `trace()` calls, and the errors shown to the author of the
+    /// movie, one per line.
+    pub trace: String,
+}
+
+impl RunOutput {
+    /// Writes each frame to `frame_NNNN.png`, counting from 1, and the trace output
+    /// to `trace.txt` in the given folder, which is created if needed.
+    pub fn save(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(dir)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            frame.save(dir.join(format!("frame_{:04}.png", i + 1)))?;
+        }
+        std::fs::write(dir.join("trace.txt"), &self.trace)?;
+        Ok(())
+    }
+}
+
+/// Runs the movie at the given path. Files it loads are relative to its folder.
+pub fn run_movie(path: &Path, options: &RunOptions) -> Result<RunOutput, Box<dyn Error>> {
+    let movie = SwfMovie::from_path(path)?;
+    let width = options.width.unwrap_or_else(|| movie.width());
+    let height = options.height.unwrap_or_else(|| movie.height());
+    let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let trace = Rc::new(RefCell::new(String::new()));
+    let (mut executor, channel) = NullExecutor::new();
+    let mut builder = PlayerBuilder::new()
+        .with_movie(movie)
+        .with_log(TraceLogBackend(Rc::clone(&trace)))
+        .with_navigator(NullNavigatorBackend::with_base_path(base_path, channel))
+        .with_viewport_dimensions(width, height, 1.0);
+    if options.capture_frames {
+        builder = builder.with_renderer(SoftwareRenderBackend::new(width, height));
+    }
+    let player = builder.build()?;
+
+    let mut frames = vec![];
+    for _ in 0..options.frames {
+        player.lock().unwrap().run_frame();
+        // Loads lock the player, so it must be unlocked while they run.
+        executor.poll_all()?;
+        if options.capture_frames {
+            let frame = player
+                .lock()
+                .unwrap()
+                .capture_frame()
+                .ok_or("Unable to capture the frame")?;
+            frames.push(to_image(frame)?);
+        }
+    }
+
+    let trace = trace.replace(String::new());
+    Ok(RunOutput { frames, trace })
+}
+
+fn to_image(bitmap: Bitmap) -> Result<RgbaImage, Box<dyn Error>> {
+    let data = match bitmap.data {
+        BitmapFormat::Rgba(data) => data,
+        BitmapFormat::Rgb(data) => data
+            .chunks_exact(3)
+            .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+    };
+    RgbaImage::from_raw(bitmap.width, bitmap.height, data)
+        .ok_or_else(|| "Captured frame is the wrong size".into())
+}
+
+/// Collects the output of the movie for `run_movie`.
+struct TraceLogBackend(Rc<RefCell<String>>);
+
+impl LogBackend for TraceLogBackend {
+    fn avm_trace(&mut self, message: &str) {
+        let mut trace = self.0.borrow_mut();
+        trace.push_str(message);
+        trace.push('\n');
+    }
+
+    fn avm_warning(&mut self, message: &str) {
+        self.avm_trace(message);
+    }
+}
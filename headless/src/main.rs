@@ -0,0 +1,58 @@
+use ruffle_headless::{run_movie, RunOptions};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "ruffle-headless")]
+struct Opt {
+    /// The swf file to run.
+    #[structopt(name = "FILE", parse(from_os_str))]
+    input_path: PathBuf,
+
+    /// The folder to write each frame and the trace output to.
+    #[structopt(name = "OUTPUT", parse(from_os_str))]
+    output_path: PathBuf,
+
+    /// The number of frames to run.
+    #[structopt(short, long, default_value = "1")]
+    frames: u32,
+
+    /// Only writes the trace output, without rendering any frames.
+    #[structopt(long)]
+    no_frames: bool,
+
+    /// The width of the frames in pixels, instead of the movie's width.
+    #[structopt(long)]
+    width: Option<u32>,
+
+    /// The height of the frames in pixels, instead of the movie's height.
+    #[structopt(long)]
+    height: Option<u32>,
+}
+
+fn main() {
+    env_logger::init();
+
+    let opt = Opt::from_args();
+    let options = RunOptions {
+        frames: opt.frames,
+        capture_frames: !opt.no_frames,
+        width: opt.width,
+        height: opt.height,
+    };
+    let ret = run_movie(&opt.input_path, &options).and_then(|output| {
+        output.save(&opt.output_path)?;
+        println!(
+            "Ran {} frames, and wrote {} frames and {} lines of trace output",
+            options.frames,
+            output.frames.len(),
+            output.trace.lines().count()
+        );
+        Ok(())
+    });
+
+    if let Err(e) = ret {
+        eprintln!("Fatal error:\n{}", e);
+        std::process::exit(-1);
+    }
+}
@@ -0,0 +1,66 @@
+//! Tests that movies look the same as their golden images when rendered with the
+//! software renderer, to catch changes in how strokes, gradients and masks are drawn.
+//!
+//! Each test is a folder in `tests/swfs` with a `test.swf`, and an `expected.png` of
+//! its last frame. When a frame doesn't match, it is saved as `actual.png` next to the
+//! golden image, with the pixels that differ shown in `difference.png`.
+
+use ruffle_headless::compare::{compare_images, Mismatch, Tolerance};
+use ruffle_headless::{run_movie, RunOptions};
+use std::path::Path;
+
+type Error = Box<dyn std::error::Error>;
+
+// This macro generates test cases for a given list of SWFs.
+macro_rules! image_tests {
+    ($($(#[$attr:meta])* ($name:ident, $path:expr, $num_frames:literal),)*) => {
+        $(
+        #[test]
+        $(#[$attr])*
+        fn $name() -> Result<(), Error> {
+            test_swf_image(concat!("tests/swfs/", $path), $num_frames, Tolerance::default())
+        }
+        )*
+    };
+}
+
+// List of SWFs to test.
+// Format: (test_name, test_folder, number_of_frames_to_run)
+// The test folder is relative to headless/tests/swfs.
+// Run the tests with `RUFFLE_REGENERATE_OUTPUT=1` to write the rendered frames to
+// "expected.png" instead of comparing against them.
+image_tests! {
+    (cache_as_bitmap, "cache_as_bitmap", 3),
+    (gradients, "gradients", 1),
+    (masks, "masks", 1),
+    (solid_fills, "solid_fills", 1),
+    (strokes, "strokes", 1),
+}
+
+/// Runs an SWF for a number of frames, and tests that its last frame looks like the
+/// golden image in its folder.
+fn test_swf_image(folder: &str, num_frames: u32, tolerance: Tolerance) -> Result<(), Error> {
+    let folder = Path::new(folder);
+    let options = RunOptions {
+        frames: num_frames,
+        ..Default::default()
+    };
+    let output = run_movie(&folder.join("test.swf"), &options)?;
+    let frame = output.frames.last().ok_or("No frames were captured")?;
+
+    let expected_path = folder.join("expected.png");
+    if std::env::var_os("RUFFLE_REGENERATE_OUTPUT").is_some() {
+        frame.save(&expected_path)?;
+        return Ok(());
+    }
+
+    let expected = image::open(&expected_path)?.into_rgba();
+    if let Err(mismatch) = compare_images(frame, &expected, tolerance) {
+        frame.save(folder.join("actual.png"))?;
+        if let Mismatch::Pixels { difference, .. } = &mismatch {
+            difference.save(folder.join("difference.png"))?;
+        }
+        return Err(format!("{}: {}", folder.display(), mismatch).into());
+    }
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use ruffle_core::backend::render::{
     swf::{self, CharacterId, GradientInterpolation, GradientSpread},
-    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, JpegTagFormat, Letterbox, RenderBackend,
-    ShapeHandle, Transform,
+    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Composite, CompositeShader,
+    DecodedFrame, JpegTagFormat, Letterbox, RenderBackend, ShapeHandle, Transform,
 };
 use ruffle_core::color_transform::ColorTransform;
-use ruffle_core::shape_utils::{DistilledShape, DrawCommand};
+use ruffle_core::shape_utils::{stroke_width, DistilledShape, DrawCommand};
+use ruffle_core::stage::StageQuality;
 use ruffle_web_common::JsResult;
 use std::collections::HashMap;
 use std::convert::TryInto;
@@ -30,6 +31,15 @@ pub struct WebCanvasRenderBackend {
     viewport_height: u32,
     use_color_transform_hack: bool,
     pixelated_property_value: &'static str,
+    offscreen: Option<OffscreenFrame>,
+    quality: StageQuality,
+}
+
+/// The main render target and viewport, saved while rendering an offscreen frame.
+struct OffscreenFrame {
+    main_target: (HtmlCanvasElement, CanvasRenderingContext2d),
+    viewport_width: u32,
+    viewport_height: u32,
 }
 
 /// Canvas-drawable shape data extracted from an SWF file.
@@ -91,7 +101,8 @@ enum CanvasFillStyle {
     Color(CanvasColor),
     #[allow(dead_code)]
     Gradient(CanvasGradient),
-    Pattern(CanvasPattern),
+    /// A bitmap pattern, and whether it is smoothed.
+    Pattern(CanvasPattern, bool),
 }
 
 impl CanvasFillStyle {
@@ -113,15 +124,19 @@ struct BitmapData {
 }
 
 impl WebCanvasRenderBackend {
-    pub fn new(canvas: &HtmlCanvasElement) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Creates a renderer for the given canvas. With `is_transparent` set, the canvas
+    /// has an alpha channel, so that the page shows through a transparent background.
+    pub fn new(
+        canvas: &HtmlCanvasElement,
+        is_transparent: bool,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // Request the CanvasRenderingContext2d.
-        // Disable alpha for possible speedup.
-        // TODO: Allow user to enable transparent background (transparent wmode in legacy Flash).
+        // Alpha is disabled unless needed, for possible speedup.
         let context_options = js_sys::Object::new();
         let _ = js_sys::Reflect::set(
             &context_options,
             &"alpha".into(),
-            &wasm_bindgen::JsValue::FALSE,
+            &wasm_bindgen::JsValue::from_bool(is_transparent),
         );
         let context: CanvasRenderingContext2d = canvas
             .get_context_with_context_options("2d", &context_options)
@@ -220,6 +235,8 @@ impl WebCanvasRenderBackend {
             } else {
                 "pixelated"
             },
+            offscreen: None,
+            quality: StageQuality::default(),
         };
         Ok(renderer)
     }
@@ -253,7 +270,7 @@ impl WebCanvasRenderBackend {
     }
 
     // Pushes a fresh canvas onto the stack to use as a render target.
-    fn push_render_target(&mut self) {
+    fn push_target_canvas(&mut self) {
         self.cur_render_target += 1;
         if self.cur_render_target >= self.render_targets.len() {
             // Create offscreen canvas to use as the render target.
@@ -289,7 +306,7 @@ impl WebCanvasRenderBackend {
             .clear_rect(0.0, 0.0, width.into(), height.into());
     }
 
-    fn pop_render_target(&mut self) -> (HtmlCanvasElement, CanvasRenderingContext2d) {
+    fn pop_target_canvas(&mut self) -> (HtmlCanvasElement, CanvasRenderingContext2d) {
         if self.cur_render_target > 0 {
             let out = (self.canvas.clone(), self.context.clone());
             self.cur_render_target -= 1;
@@ -577,23 +594,116 @@ impl RenderBackend for WebCanvasRenderBackend {
         let width = self.canvas.width();
         let height = self.canvas.height();
 
-        let color = format!("rgb({}, {}, {})", clear.r, clear.g, clear.b);
-        self.context.set_fill_style(&color.into());
-        self.context
-            .fill_rect(0.0, 0.0, width.into(), height.into());
+        if clear.a == 0 {
+            self.context
+                .clear_rect(0.0, 0.0, width.into(), height.into());
+        } else {
+            let color = format!("rgb({}, {}, {})", clear.r, clear.g, clear.b);
+            self.context.set_fill_style(&color.into());
+            self.context
+                .fill_rect(0.0, 0.0, width.into(), height.into());
+        }
     }
 
     fn end_frame(&mut self) {
         // Noop
     }
 
-    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform) {
+    fn begin_offscreen_frame(&mut self, width: u32, height: u32, clear: Color) -> bool {
+        if self.offscreen.is_some() || self.cur_render_target != 0 {
+            return false;
+        }
+
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas: HtmlCanvasElement = match document
+            .create_element("canvas")
+            .ok()
+            .and_then(|element| element.dyn_into().ok())
+        {
+            Some(canvas) => canvas,
+            None => return false,
+        };
+        let context: CanvasRenderingContext2d = match canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into().ok())
+        {
+            Some(context) => context,
+            None => return false,
+        };
+        canvas.set_width(width);
+        canvas.set_height(height);
+
+        // Masks return to the bottom render target, so the page replaces the main canvas.
+        let main_target = std::mem::replace(
+            &mut self.render_targets[0],
+            (canvas.clone(), context.clone()),
+        );
+        self.offscreen = Some(OffscreenFrame {
+            main_target,
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+        });
+        self.canvas = canvas;
+        self.context = context;
+        self.viewport_width = width;
+        self.viewport_height = height;
+        self.begin_frame(clear);
+        true
+    }
+
+    fn end_offscreen_frame(&mut self) -> Option<Bitmap> {
+        let offscreen = self.offscreen.take()?;
+        let image_data = self
+            .context
+            .get_image_data(
+                0.0,
+                0.0,
+                self.viewport_width.into(),
+                self.viewport_height.into(),
+            )
+            .ok();
+
+        let (canvas, context) = offscreen.main_target;
+        self.canvas = canvas.clone();
+        self.context = context.clone();
+        self.render_targets[0] = (canvas, context);
+        self.viewport_width = offscreen.viewport_width;
+        self.viewport_height = offscreen.viewport_height;
+
+        image_data.map(|image_data| Bitmap {
+            width: image_data.width(),
+            height: image_data.height(),
+            data: BitmapFormat::Rgba(image_data.data().0),
+        })
+    }
+
+    fn capture_frame(&mut self) -> Option<Bitmap> {
+        let (canvas, context) = match &self.offscreen {
+            Some(offscreen) => &offscreen.main_target,
+            None => &self.render_targets[0],
+        };
+        let image_data = context
+            .get_image_data(0.0, 0.0, canvas.width().into(), canvas.height().into())
+            .ok()?;
+        Some(Bitmap {
+            width: image_data.width(),
+            height: image_data.height(),
+            data: BitmapFormat::Rgba(image_data.data().0),
+        })
+    }
+
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
         self.set_transform(transform);
         self.set_color_filter(transform);
         if let Some(bitmap) = self.bitmaps.get(bitmap.0) {
+            self.context
+                .set_image_smoothing_enabled(self.quality.smooth_bitmap(smoothing));
             let _ = self
                 .context
                 .draw_image_with_html_image_element(&bitmap.image, 0.0, 0.0);
+            self.context.set_image_smoothing_enabled(true);
         }
         self.clear_color_filter();
     }
@@ -615,10 +725,16 @@ impl RenderBackend for WebCanvasRenderBackend {
                                 self.context.set_fill_style(&JsValue::from_str(&color))
                             }
                             CanvasFillStyle::Gradient(grad) => self.context.set_fill_style(grad),
-                            CanvasFillStyle::Pattern(patt) => self.context.set_fill_style(patt),
+                            CanvasFillStyle::Pattern(patt, is_smoothed) => {
+                                self.context.set_image_smoothing_enabled(
+                                    self.quality.smooth_bitmap(*is_smoothed),
+                                );
+                                self.context.set_fill_style(patt)
+                            }
                         };
 
                         self.context.fill_with_path_2d(&path);
+                        self.context.set_image_smoothing_enabled(true);
 
                         if xformed_fill_style.is_none() {
                             self.clear_color_filter();
@@ -664,23 +780,23 @@ impl RenderBackend for WebCanvasRenderBackend {
 
         match letterbox {
             Letterbox::None => (),
-            Letterbox::Letterbox(margin_height) => {
+            Letterbox::Letterbox { top, bottom } => {
                 self.context
-                    .fill_rect(0.0, 0.0, self.viewport_width.into(), margin_height.into());
+                    .fill_rect(0.0, 0.0, self.viewport_width.into(), top.into());
                 self.context.fill_rect(
                     0.0,
-                    (self.viewport_height as f32 - margin_height).into(),
+                    (self.viewport_height as f32 - bottom).into(),
                     self.viewport_width.into(),
-                    self.viewport_height.into(),
+                    bottom.into(),
                 );
             }
-            Letterbox::Pillarbox(margin_width) => {
+            Letterbox::Pillarbox { left, right } => {
                 self.context
-                    .fill_rect(0.0, 0.0, margin_width.into(), self.viewport_height.into());
+                    .fill_rect(0.0, 0.0, left.into(), self.viewport_height.into());
                 self.context.fill_rect(
-                    (self.viewport_width as f32 - margin_width).into(),
+                    (self.viewport_width as f32 - right).into(),
                     0.0,
-                    margin_width.into(),
+                    right.into(),
                     self.viewport_height.into(),
                 );
             }
@@ -690,15 +806,15 @@ impl RenderBackend for WebCanvasRenderBackend {
     fn push_mask(&mut self) {
         // In the canvas backend, masks are implemented using two render targets.
         // We render the masker clips to the first render target.
-        self.push_render_target();
+        self.push_target_canvas();
     }
     fn activate_mask(&mut self) {
         // We render the maskee clips to the second render target.
-        self.push_render_target();
+        self.push_target_canvas();
     }
     fn pop_mask(&mut self) {
-        let (maskee_canvas, maskee_context) = self.pop_render_target();
-        let (masker_canvas, _masker_context) = self.pop_render_target();
+        let (maskee_canvas, maskee_context) = self.pop_target_canvas();
+        let (masker_canvas, _masker_context) = self.pop_target_canvas();
 
         // We have to be sure to reset the transforms here so that
         // the texture is drawn starting from the upper-left corner.
@@ -736,6 +852,111 @@ impl RenderBackend for WebCanvasRenderBackend {
             .draw_image_with_html_canvas_element(&maskee_canvas, 0.0, 0.0)
             .unwrap();
     }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        // Canvas always anti-aliases paths, so only the smoothing of bitmaps
+        // follows the quality.
+        self.quality = quality;
+    }
+
+    fn push_render_target(&mut self) -> bool {
+        self.push_target_canvas();
+        true
+    }
+
+    fn pop_and_composite(&mut self, composite: &Composite) {
+        let (layer_canvas, _layer_context) = self.pop_target_canvas();
+
+        // Canvas has no equivalent of the `Subtract` and `Invert` blend modes.
+        let operation = match composite.blend_mode {
+            swf::BlendMode::Multiply => "multiply",
+            swf::BlendMode::Screen => "screen",
+            swf::BlendMode::Lighten => "lighten",
+            swf::BlendMode::Darken => "darken",
+            swf::BlendMode::Difference => "difference",
+            swf::BlendMode::Add => "lighter",
+            swf::BlendMode::Alpha => "destination-in",
+            swf::BlendMode::Erase => "destination-out",
+            swf::BlendMode::Overlay => "overlay",
+            swf::BlendMode::HardLight => "hard-light",
+            _ => "source-over",
+        };
+        let filter = match &composite.shader {
+            Some(CompositeShader::ColorMatrix(matrix)) => {
+                // SVG color matrices have the same layout as Flash ones.
+                let values: Vec<String> = matrix.iter().map(|value| value.to_string()).collect();
+                self.color_matrix
+                    .set_attribute("values", &values.join(" "))
+                    .warn_on_error();
+                "url('#_cm')".to_string()
+            }
+            Some(CompositeShader::Blur {
+                blur_x,
+                blur_y,
+                passes,
+            }) => {
+                // Canvas blurs are gaussian, so use one with the deviation of the box blurs.
+                let size = (blur_x + blur_y) / 2.0;
+                let deviation = size * (*passes as f32 / 12.0).sqrt();
+                format!("blur({}px)", deviation)
+            }
+            None => "none".to_string(),
+        };
+
+        self.context.reset_transform().warn_on_error();
+        self.context
+            .set_global_composite_operation(operation)
+            .warn_on_error();
+        self.context.set_filter(&filter);
+        self.context
+            .draw_image_with_html_canvas_element(&layer_canvas, 0.0, 0.0)
+            .warn_on_error();
+        self.context
+            .set_global_composite_operation("source-over")
+            .warn_on_error();
+        self.context.set_filter("none");
+    }
+
+    fn register_video_stream(
+        &mut self,
+        id: CharacterId,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        let bitmap = Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![0; width as usize * height as usize * 4]),
+        };
+        Ok(self.register_bitmap_raw(id, bitmap)?.handle)
+    }
+
+    fn upload_video_frame(
+        &mut self,
+        handle: BitmapHandle,
+        frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        let bitmap_data = self
+            .bitmaps
+            .get_mut(handle.0)
+            .ok_or("Unknown video stream")?;
+        let (width, height) = (bitmap_data.width, bitmap_data.height);
+        let data = frame.to_rgba(width, height);
+        if data.len() != width as usize * height as usize * 4 {
+            return Err("Video frame size differs from its stream".into());
+        }
+
+        // The image loads asynchronously, so the frame may show up a little late.
+        // TODO: Draw frames onto a canvas instead of encoding them as PNGs.
+        let png = Self::bitmap_to_png_data_uri(Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(data),
+        })?;
+        bitmap_data.image.set_src(&png);
+        bitmap_data.data = png;
+        Ok(())
+    }
 }
 
 #[allow(clippy::cognitive_complexity)]
@@ -786,13 +1007,15 @@ fn swf_shape_to_svg(
     let width = (shape.shape_bounds.x_max - shape.shape_bounds.x_min).get() as f32;
     let height = (shape.shape_bounds.y_max - shape.shape_bounds.y_min).get() as f32;
 
-    let mut bitmap_defs: FnvHashSet<CharacterId> = FnvHashSet::default();
+    // Bitmaps are defined once for each of their smoothing modes.
+    let mut bitmap_defs: FnvHashSet<(CharacterId, bool)> = FnvHashSet::default();
 
     let mut defs = Definitions::new();
     let mut num_defs = 0;
     let mut has_linear_rgb_gradient = false;
 
     let mut svg_paths = vec![];
+    let scale = shape.scale;
     for path in shape.paths {
         match path {
             DrawPath::Fill { style, commands } => {
@@ -938,7 +1161,7 @@ fn swf_shape_to_svg(
 
                         let mut svg_gradient = RadialGradient::new()
                             .set("id", format!("f{}", num_defs))
-                            .set("fx", focal_point / 2.0)
+                            .set("fx", focal_point.max(-1.0).min(1.0) / 2.0)
                             .set("gradientUnits", "userSpaceOnUse")
                             .set("cx", "0")
                             .set("cy", "0")
@@ -1000,7 +1223,12 @@ fn swf_shape_to_svg(
                         let (bitmap_data, bitmap_width, bitmap_height) =
                             bitmaps.get(&id).unwrap_or(&("", 0, 0));
 
-                        if !bitmap_defs.contains(&id) {
+                        let bitmap_def_id = if *is_smoothed {
+                            format!("b{}", id)
+                        } else {
+                            format!("b{}p", id)
+                        };
+                        if !bitmap_defs.contains(&(*id, *is_smoothed)) {
                             let mut image = Image::new()
                                 .set("width", *bitmap_width)
                                 .set("height", *bitmap_height)
@@ -1011,7 +1239,7 @@ fn swf_shape_to_svg(
                             }
 
                             let mut bitmap_pattern = Pattern::new()
-                                .set("id", format!("b{}", id))
+                                .set("id", bitmap_def_id.clone())
                                 .set("patternUnits", "userSpaceOnUse");
 
                             if !*is_repeating {
@@ -1031,12 +1259,12 @@ fn swf_shape_to_svg(
                             bitmap_pattern = bitmap_pattern.add(image);
 
                             defs = defs.add(bitmap_pattern);
-                            bitmap_defs.insert(*id);
+                            bitmap_defs.insert((*id, *is_smoothed));
                         }
 
                         let svg_pattern = Pattern::new()
                             .set("id", format!("f{}", num_defs))
-                            .set("xlink:href", format!("#b{}", id))
+                            .set("xlink:href", format!("#{}", bitmap_def_id))
                             .set(
                                 "patternTransform",
                                 format!(
@@ -1078,15 +1306,10 @@ fn swf_shape_to_svg(
                 commands,
                 is_closed,
             } => {
-                // Flash always renders strokes with a minimum width of 1 pixel (20 twips).
-                // Additionally, many SWFs use the "hairline" stroke setting, which sets the stroke's width
-                // to 1 twip. Because of the minimum, this will effectively make the stroke nearly-always render
-                // as 1 pixel wide.
-                // SVG doesn't have a minimum and can render strokes at fractional widths, so these hairline
-                // strokes end up rendering very faintly if we use the actual width of 1 twip.
-                // Therefore, we clamp the stroke width to 1 pixel (20 twips). This won't be 100% accurate
-                // if the shape is scaled, but it looks much closer to the Flash Player.
-                let stroke_width = std::cmp::max(style.width.get(), 20);
+                // Flash always renders strokes with a minimum width of 1 pixel, which SVG doesn't
+                // have. Shapes with hairlines or non-scaling strokes are registered for each scale
+                // they are rendered at, so the width can be computed here.
+                let stroke_width = stroke_width(style, scale) * 20.0;
                 let mut svg_path = SvgPath::new();
                 svg_path = svg_path
                     .set("fill", "none")
@@ -1283,10 +1506,6 @@ fn swf_shape_to_canvas_commands(
                         )
                         .expect("html image element");
 
-                        if !*is_smoothed {
-                            //image = image.set("image-rendering", pixelated_property_value);
-                        }
-
                         let repeat = if !*is_repeating {
                             "no-repeat"
                         } else {
@@ -1314,7 +1533,7 @@ fn swf_shape_to_canvas_commands(
 
                         bitmap_pattern.set_transform(&matrix);
 
-                        CanvasFillStyle::Pattern(bitmap_pattern)
+                        CanvasFillStyle::Pattern(bitmap_pattern, *is_smoothed)
                     }
                 };
 
@@ -1333,15 +1552,10 @@ fn swf_shape_to_canvas_commands(
                 commands,
                 is_closed,
             } => {
-                // Flash always renders strokes with a minimum width of 1 pixel (20 twips).
-                // Additionally, many SWFs use the "hairline" stroke setting, which sets the stroke's width
-                // to 1 twip. Because of the minimum, this will effectively make the stroke nearly-always render
-                // as 1 pixel wide.
-                // SVG doesn't have a minimum and can render strokes at fractional widths, so these hairline
-                // strokes end up rendering very faintly if we use the actual width of 1 twip.
-                // Therefore, we clamp the stroke width to 1 pixel (20 twips). This won't be 100% accurate
-                // if the shape is scaled, but it looks much closer to the Flash Player.
-                let line_width = std::cmp::max(style.width.get(), 20);
+                // Flash always renders strokes with a minimum width of 1 pixel, which canvas doesn't
+                // have. Shapes with hairlines or non-scaling strokes are registered for each scale
+                // they are rendered at, so the width can be computed here.
+                let line_width = stroke_width(style, shape.scale);
                 let stroke_style = CanvasColor(
                     format!(
                         "rgba({},{},{},{})",
@@ -1371,11 +1585,11 @@ fn swf_shape_to_canvas_commands(
 
                 canvas_data.0.push(CanvasDrawCommand::Stroke {
                     path,
-                    line_width: line_width as f64 / 20.0,
+                    line_width: line_width.into(),
                     stroke_style,
                     line_cap: line_cap.to_string(),
                     line_join: line_join.to_string(),
-                    miter_limit: miter_limit as f64 / 20.0,
+                    miter_limit: miter_limit.into(),
                 });
             }
         }
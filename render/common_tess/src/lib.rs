@@ -6,7 +6,7 @@ use lyon::tessellation::{
 };
 use lyon::tessellation::{FillOptions, StrokeOptions};
 use ruffle_core::backend::render::swf::{self, FillStyle, GradientInterpolation, Twips};
-use ruffle_core::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use ruffle_core::shape_utils::{stroke_width, DistilledShape, DrawCommand, DrawPath};
 
 pub struct ShapeTessellator {
     fill_tess: FillTessellator,
@@ -42,6 +42,7 @@ impl ShapeTessellator {
             });
         }
 
+        let scale = shape.scale;
         for path in shape.paths {
             match path {
                 DrawPath::Fill { style, commands } => match style {
@@ -190,7 +191,9 @@ impl ShapeTessellator {
                             num_colors: gradient.records.len() as u32,
                             matrix: swf_to_gl_matrix(gradient.matrix),
                             repeat_mode: gradient.spread,
-                            focal_point: *focal_point,
+                            // Focal points outside of the gradient's circle are
+                            // clamped to its edge.
+                            focal_point: focal_point.max(-1.0).min(1.0),
                             interpolation: gradient.interpolation,
                         };
 
@@ -245,12 +248,7 @@ impl ShapeTessellator {
                     let mut buffers_builder =
                         BuffersBuilder::new(&mut lyon_mesh, RuffleVertexCtor { color });
 
-                    // TODO(Herschel): 0 width indicates "hairline".
-                    let width = if style.width.to_pixels() >= 1.0 {
-                        style.width.to_pixels() as f32
-                    } else {
-                        1.0
-                    };
+                    let width = stroke_width(style, scale);
 
                     let mut options = StrokeOptions::default()
                         .with_line_width(width)
@@ -271,7 +269,9 @@ impl ShapeTessellator {
                         });
 
                     if let swf::LineJoinStyle::Miter(limit) = style.join_style {
-                        options = options.with_miter_limit(limit);
+                        // Lyon panics on limits below its minimum, which SWFs can contain.
+                        options =
+                            options.with_miter_limit(limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
                     }
 
                     if let Err(e) = self.stroke_tess.tessellate_path(
@@ -0,0 +1,213 @@
+//! Compositing of offscreen surfaces
+//!
+//! The separable blend modes follow the W3C compositing specification, which
+//! matches Flash. The others have no equivalent there, and follow Flash.
+
+use crate::raster::Pixmap;
+use ruffle_core::backend::render::swf::BlendMode;
+use ruffle_core::backend::render::CompositeShader;
+
+/// Blends a pre-multiplied source color over a pre-multiplied destination color.
+pub fn blend(mode: BlendMode, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+    let (sa, da) = (src[3], dst[3]);
+    let alpha = sa + da - sa * da;
+    let separable = |f: fn(f32, f32) -> f32| {
+        let mut color = [0.0, 0.0, 0.0, alpha];
+        for i in 0..3 {
+            let cs = if sa > 0.0 { src[i] / sa } else { 0.0 };
+            let cd = if da > 0.0 { dst[i] / da } else { 0.0 };
+            color[i] = src[i] * (1.0 - da) + dst[i] * (1.0 - sa) + sa * da * f(cs, cd);
+        }
+        color
+    };
+
+    match mode {
+        BlendMode::Normal | BlendMode::Layer => [
+            src[0] + dst[0] * (1.0 - sa),
+            src[1] + dst[1] * (1.0 - sa),
+            src[2] + dst[2] * (1.0 - sa),
+            alpha,
+        ],
+        BlendMode::Multiply => separable(|cs, cd| cs * cd),
+        BlendMode::Screen => separable(screen),
+        BlendMode::Lighten => separable(f32::max),
+        BlendMode::Darken => separable(f32::min),
+        BlendMode::Difference => separable(|cs, cd| (cs - cd).abs()),
+        BlendMode::Overlay => separable(|cs, cd| hard_light(cd, cs)),
+        BlendMode::HardLight => separable(hard_light),
+        BlendMode::Add => [
+            (src[0] + dst[0]).min(alpha),
+            (src[1] + dst[1]).min(alpha),
+            (src[2] + dst[2]).min(alpha),
+            alpha,
+        ],
+        BlendMode::Subtract => [
+            (dst[0] - src[0]).max(0.0),
+            (dst[1] - src[1]).max(0.0),
+            (dst[2] - src[2]).max(0.0),
+            alpha,
+        ],
+        // Inverts the destination where the source is opaque.
+        BlendMode::Invert => [
+            (da - dst[0]) * sa + dst[0] * (1.0 - sa),
+            (da - dst[1]) * sa + dst[1] * (1.0 - sa),
+            (da - dst[2]) * sa + dst[2] * (1.0 - sa),
+            da,
+        ],
+        // Keeps the destination where the source is opaque.
+        BlendMode::Alpha => scale(dst, sa),
+        // Keeps the destination where the source is transparent.
+        BlendMode::Erase => scale(dst, 1.0 - sa),
+    }
+}
+
+fn screen(cs: f32, cd: f32) -> f32 {
+    cs + cd - cs * cd
+}
+
+fn hard_light(cs: f32, cd: f32) -> f32 {
+    if cs <= 0.5 {
+        cd * 2.0 * cs
+    } else {
+        screen(cd, 2.0 * cs - 1.0)
+    }
+}
+
+fn scale(color: [f32; 4], factor: f32) -> [f32; 4] {
+    [
+        color[0] * factor,
+        color[1] * factor,
+        color[2] * factor,
+        color[3] * factor,
+    ]
+}
+
+/// Applies a shader to a whole surface.
+pub fn apply_shader(pixmap: &mut Pixmap, shader: &CompositeShader) {
+    match shader {
+        CompositeShader::ColorMatrix(matrix) => {
+            for pixel in &mut pixmap.pixels {
+                let a = pixel[3];
+                let color = if a > 0.0 {
+                    [pixel[0] / a, pixel[1] / a, pixel[2] / a, a]
+                } else {
+                    [0.0; 4]
+                };
+                let mut out = [0.0; 4];
+                for (out, row) in out.iter_mut().zip(matrix.chunks_exact(5)) {
+                    *out = (row[0] * color[0]
+                        + row[1] * color[1]
+                        + row[2] * color[2]
+                        + row[3] * color[3]
+                        + row[4])
+                        .max(0.0)
+                        .min(1.0);
+                }
+                *pixel = [out[0] * out[3], out[1] * out[3], out[2] * out[3], out[3]];
+            }
+        }
+        CompositeShader::Blur {
+            blur_x,
+            blur_y,
+            passes,
+        } => {
+            let (width, height) = (pixmap.width as usize, pixmap.height as usize);
+            // A box blur of a given size averages the pixels within half of it on each side.
+            let radius_x = (blur_x / 2.0).max(0.0) as usize;
+            let radius_y = (blur_y / 2.0).max(0.0) as usize;
+            let mut line = vec![];
+            let mut blurred = vec![];
+            for _ in 0..*passes {
+                if radius_x > 0 {
+                    for y in 0..height {
+                        let row = &mut pixmap.pixels[y * width..(y + 1) * width];
+                        line.clear();
+                        line.extend_from_slice(row);
+                        blurred.resize(width, [0.0; 4]);
+                        blur_line(&line, radius_x, &mut blurred);
+                        row.copy_from_slice(&blurred);
+                    }
+                }
+                if radius_y > 0 {
+                    for x in 0..width {
+                        line.clear();
+                        line.extend((0..height).map(|y| pixmap.pixels[y * width + x]));
+                        blurred.resize(height, [0.0; 4]);
+                        blur_line(&line, radius_y, &mut blurred);
+                        for (y, &pixel) in blurred.iter().enumerate() {
+                            pixmap.pixels[y * width + x] = pixel;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Averages each pixel of a line with the `radius` pixels on each side of it.
+/// Pixels beyond the ends of the line are transparent.
+fn blur_line(line: &[[f32; 4]], radius: usize, out: &mut [[f32; 4]]) {
+    let len = line.len();
+    let factor = 1.0 / (2 * radius + 1) as f32;
+    let mut sum = [0.0; 4];
+    for pixel in &line[..radius.min(len)] {
+        for i in 0..4 {
+            sum[i] += pixel[i];
+        }
+    }
+    for x in 0..len {
+        if x + radius < len {
+            for i in 0..4 {
+                sum[i] += line[x + radius][i];
+            }
+        }
+        out[x] = scale(sum, factor);
+        if x >= radius {
+            for i in 0..4 {
+                sum[i] -= line[x - radius][i];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blend_modes() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        assert_eq!(blend(BlendMode::Multiply, red, gray), [0.5, 0.0, 0.0, 1.0]);
+        assert_eq!(blend(BlendMode::Screen, red, gray), [1.0, 0.5, 0.5, 1.0]);
+        assert_eq!(blend(BlendMode::Subtract, red, gray), [0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(blend(BlendMode::Invert, red, gray), [0.5, 0.5, 0.5, 1.0]);
+
+        // Blending a transparent source leaves the destination as it is.
+        for &mode in &[
+            BlendMode::Normal,
+            BlendMode::Multiply,
+            BlendMode::Difference,
+        ] {
+            assert_eq!(blend(mode, [0.0; 4], gray), gray);
+        }
+        assert_eq!(blend(BlendMode::Erase, [0.0; 4], gray), gray);
+        assert_eq!(blend(BlendMode::Alpha, [0.0; 4], gray), [0.0; 4]);
+    }
+
+    #[test]
+    fn blur() {
+        let mut pixmap = Pixmap::new(5, 1, [0.0; 4]);
+        pixmap.pixels[2] = [0.0, 0.0, 0.0, 1.0];
+        apply_shader(
+            &mut pixmap,
+            &CompositeShader::Blur {
+                blur_x: 3.0,
+                blur_y: 0.0,
+                passes: 1,
+            },
+        );
+        let alphas: Vec<f32> = pixmap.pixels.iter().map(|pixel| pixel[3]).collect();
+        assert_eq!(alphas, [0.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 0.0]);
+    }
+}
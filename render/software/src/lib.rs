@@ -0,0 +1,1225 @@
+//! A render backend that rasterizes on the CPU.
+//!
+//! Shapes are tessellated into triangles like in the GPU backends, which are
+//! then rasterized into an image in memory. This doesn't need a GPU or a
+//! browser, so it works for headless rendering and screenshots.
+//!
+//! The draw calls of a frame are recorded, and carried out when the frame
+//! ends. With dirty regions enabled, only the area covered by the calls that
+//! differ from the last frame is redrawn.
+
+mod composite;
+mod paint;
+mod raster;
+
+use paint::{gradient_color, Texture};
+pub use raster::Rect;
+use raster::{Coverage, Pixmap, Point};
+use ruffle_core::backend::render::swf::{self, FillStyle};
+use ruffle_core::backend::render::{
+    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, CacheHandle, Color, Composite,
+    CompositeShader, DecodedFrame, Letterbox, RenderBackend, ShapeHandle, Transform,
+};
+use ruffle_core::color_transform::ColorTransform;
+use ruffle_core::shape_utils::DistilledShape;
+use ruffle_core::stage::StageQuality;
+use ruffle_render_common_tess::{Bitmap as BitmapDraw, Draw, DrawType, ShapeTessellator, Vertex};
+use std::collections::HashMap;
+
+type Error = Box<dyn std::error::Error>;
+
+pub struct SoftwareRenderBackend {
+    shape_tessellator: ShapeTessellator,
+
+    textures: Vec<(swf::CharacterId, Texture)>,
+    meshes: Vec<Mesh>,
+
+    frame: Frame,
+
+    /// The draw calls of the frame being rendered.
+    commands: Vec<Recorded>,
+    clear: [f32; 4],
+
+    /// The draw calls and background of the last on-screen frame.
+    last_commands: Vec<Recorded>,
+    last_clear: [f32; 4],
+
+    /// Whether to only redraw the parts of each frame that changed.
+    dirty_regions: bool,
+
+    /// The area redrawn by the last on-screen frame.
+    dirty_region: Rect,
+
+    /// The on-screen frame and its draw calls, while an offscreen frame is being rendered.
+    onscreen_frame: Option<(Frame, Vec<Recorded>)>,
+
+    /// Where the draw calls of each surface pushed by `push_render_target` start.
+    render_target_starts: Vec<usize>,
+
+    /// The surfaces kept by `pop_and_cache`.
+    caches: HashMap<usize, CachedSurface>,
+    next_cache_id: usize,
+
+    coverage: Coverage,
+    quality: StageQuality,
+
+    viewport_width: u32,
+    viewport_height: u32,
+}
+
+/// A tessellated shape.
+struct Mesh {
+    draws: Vec<Draw>,
+
+    /// The corners of the box around all vertices, or `None` if there are none.
+    bounds: Option<[Point; 2]>,
+
+    /// Increased whenever the shape is replaced, so that frames drawing it differ.
+    version: u32,
+}
+
+/// A surface kept by `pop_and_cache`.
+struct CachedSurface {
+    /// The draw calls of the surface.
+    commands: Vec<Recorded>,
+
+    /// The pixels the draw calls can change, or `None` if they can change the whole frame.
+    bounds: Option<Rect>,
+
+    /// The surface, once it has been drawn.
+    pixmap: Option<Pixmap>,
+
+    /// Whether the surface was drawn in the frame being rendered.
+    used: bool,
+}
+
+/// A draw call, and the pixels it can change.
+#[derive(Clone, Debug, PartialEq)]
+struct Recorded {
+    command: Command,
+
+    /// `None` if the call can change the whole frame.
+    bounds: Option<Rect>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Command {
+    Shape {
+        index: usize,
+        version: u32,
+        transform: Transform,
+    },
+    Bitmap {
+        index: usize,
+        version: u32,
+        transform: Transform,
+        smoothing: bool,
+    },
+    Letterbox(Letterbox),
+    PushMask,
+    ActivateMask,
+    PopMask,
+    PushRenderTarget,
+    PopAndComposite(Composite),
+    CompositeCached {
+        handle: usize,
+        dx: i32,
+        dy: i32,
+        composite: Composite,
+    },
+}
+
+/// An image being rendered, and the masks applied to it.
+struct Frame {
+    pixmap: Pixmap,
+
+    /// How much of each pixel is covered by each mask. Once a mask is
+    /// active, it is intersected with the mask below it.
+    masks: Vec<Vec<f32>>,
+    num_masks_active: usize,
+}
+
+impl SoftwareRenderBackend {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            shape_tessellator: ShapeTessellator::new(),
+            textures: vec![],
+            meshes: vec![],
+            frame: Frame::new(width, height, [0.0; 4]),
+            commands: vec![],
+            clear: [0.0; 4],
+            last_commands: vec![],
+            last_clear: [0.0; 4],
+            dirty_regions: false,
+            dirty_region: Rect::default(),
+            onscreen_frame: None,
+            render_target_starts: vec![],
+            caches: HashMap::new(),
+            next_cache_id: 0,
+            coverage: Coverage::new(),
+            quality: StageQuality::default(),
+            viewport_width: width,
+            viewport_height: height,
+        }
+    }
+
+    /// Enables redrawing only the parts of each frame that changed since the last one.
+    ///
+    /// The draw calls of each frame are compared with those of the last frame, and
+    /// only the area covered by the calls that differ is redrawn. This speeds up
+    /// movies where little changes from frame to frame.
+    pub fn set_dirty_regions(&mut self, enabled: bool) {
+        self.dirty_regions = enabled;
+    }
+
+    /// The area redrawn by the last frame, such as for only copying that area to the
+    /// screen. This is the whole frame unless dirty regions are enabled.
+    pub fn dirty_region(&self) -> Rect {
+        self.dirty_region
+    }
+
+    fn register_shape_internal(&mut self, shape: DistilledShape) -> Mesh {
+        let textures = &self.textures;
+        let mut mesh = self.shape_tessellator.tessellate_shape(shape, |id| {
+            textures
+                .iter()
+                .find(|(other_id, _tex)| *other_id == id)
+                .map(|tex| (tex.1.width, tex.1.height))
+        });
+
+        // Convert to linear color space if this is a linear-interpolated gradient.
+        for draw in &mut mesh {
+            if let DrawType::Gradient(gradient) = &mut draw.draw_type {
+                if gradient.interpolation == swf::GradientInterpolation::LinearRGB {
+                    for color in &mut gradient.colors {
+                        *color = srgb_to_linear(*color);
+                    }
+                }
+            }
+        }
+
+        Mesh::new(mesh)
+    }
+
+    fn register_bitmap(
+        &mut self,
+        id: swf::CharacterId,
+        bitmap: Bitmap,
+    ) -> Result<BitmapInfo, Error> {
+        let handle = BitmapHandle(self.textures.len());
+        let texture = Texture::new(bitmap);
+        let (width, height) = (texture.width as u16, texture.height as u16);
+        self.textures.push((id, texture));
+
+        Ok(BitmapInfo {
+            handle,
+            width,
+            height,
+        })
+    }
+}
+
+impl RenderBackend for SoftwareRenderBackend {
+    fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+        // The frame is resized when the next one begins.
+        self.viewport_width = width;
+        self.viewport_height = height;
+    }
+
+    fn register_shape(&mut self, shape: DistilledShape) -> ShapeHandle {
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal(shape);
+        self.meshes.push(mesh);
+        handle
+    }
+
+    fn replace_shape(&mut self, shape: DistilledShape, handle: ShapeHandle) {
+        let mut mesh = self.register_shape_internal(shape);
+        mesh.version = self.meshes[handle.0].version.wrapping_add(1);
+        self.meshes[handle.0] = mesh;
+    }
+
+    fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
+        let shape = swf::Shape {
+            version: 2,
+            id: 0,
+            shape_bounds: Default::default(),
+            edge_bounds: Default::default(),
+            has_fill_winding_rule: false,
+            has_non_scaling_strokes: false,
+            has_scaling_strokes: true,
+            styles: swf::ShapeStyles {
+                fill_styles: vec![FillStyle::Color(Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                })],
+                line_styles: vec![],
+            },
+            shape: glyph.shape_records.clone(),
+        };
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal((&shape).into());
+        self.meshes.push(mesh);
+        handle
+    }
+
+    fn register_bitmap_jpeg(
+        &mut self,
+        id: swf::CharacterId,
+        data: &[u8],
+        jpeg_tables: Option<&[u8]>,
+    ) -> Result<BitmapInfo, Error> {
+        let data = ruffle_core::backend::render::glue_tables_to_jpeg(data, jpeg_tables);
+        self.register_bitmap_jpeg_2(id, &data[..])
+    }
+
+    fn register_bitmap_jpeg_2(
+        &mut self,
+        id: swf::CharacterId,
+        data: &[u8],
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap = ruffle_core::backend::render::decode_define_bits_jpeg(data, None)?;
+        self.register_bitmap(id, bitmap)
+    }
+
+    fn register_bitmap_jpeg_3(
+        &mut self,
+        id: swf::CharacterId,
+        jpeg_data: &[u8],
+        alpha_data: &[u8],
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap =
+            ruffle_core::backend::render::decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
+        self.register_bitmap(id, bitmap)
+    }
+
+    fn register_bitmap_png(
+        &mut self,
+        swf_tag: &swf::DefineBitsLossless,
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap = ruffle_core::backend::render::decode_define_bits_lossless(swf_tag)?;
+        self.register_bitmap(swf_tag.id, bitmap)
+    }
+
+    fn begin_frame(&mut self, clear: Color) {
+        self.commands.clear();
+        self.render_target_starts.clear();
+        self.clear = clear_color(clear);
+    }
+
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
+        if let Some((_id, texture)) = self.textures.get(bitmap.0) {
+            let size = [texture.width as f32, texture.height as f32];
+            self.commands.push(Recorded {
+                command: Command::Bitmap {
+                    index: bitmap.0,
+                    version: texture.version,
+                    transform: transform.clone(),
+                    smoothing,
+                },
+                bounds: Some(device_bounds([[0.0, 0.0], size], transform)),
+            });
+        }
+    }
+
+    fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+        let mesh = &self.meshes[shape.0];
+        let bounds = mesh
+            .bounds
+            .map_or_else(Rect::default, |bounds| device_bounds(bounds, transform));
+        self.commands.push(Recorded {
+            command: Command::Shape {
+                index: shape.0,
+                version: mesh.version,
+                transform: transform.clone(),
+            },
+            bounds: Some(bounds),
+        });
+    }
+
+    fn end_frame(&mut self) {
+        let commands = std::mem::take(&mut self.commands);
+        let (width, height) = (self.viewport_width, self.viewport_height);
+        let full = Rect::new(0, 0, width, height);
+        let pixmap = &self.frame.pixmap;
+        let region = if self.dirty_regions
+            && pixmap.width == width
+            && pixmap.height == height
+            && self.clear == self.last_clear
+        {
+            self.changed_region(&commands).intersection(full)
+        } else {
+            self.frame = Frame::new(width, height, self.clear);
+            full
+        };
+
+        if !region.is_empty() {
+            self.frame.pixmap.fill_rect(
+                region.x_min as i32,
+                region.y_min as i32,
+                (region.x_max - region.x_min) as i32,
+                (region.y_max - region.y_min) as i32,
+                self.clear,
+            );
+            self.replay(&commands, region);
+        }
+        self.last_commands = commands;
+        self.last_clear = self.clear;
+        self.dirty_region = region;
+
+        // Surfaces that weren't drawn in this frame are released.
+        self.caches
+            .retain(|_, cache| std::mem::replace(&mut cache.used, false));
+    }
+
+    fn draw_letterbox(&mut self, letterbox: Letterbox) {
+        self.record(Command::Letterbox(letterbox));
+    }
+
+    fn push_mask(&mut self) {
+        self.record(Command::PushMask);
+    }
+
+    fn activate_mask(&mut self) {
+        self.record(Command::ActivateMask);
+    }
+
+    fn pop_mask(&mut self) {
+        self.record(Command::PopMask);
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.quality = quality;
+        for cache in self.caches.values_mut() {
+            cache.pixmap = None;
+        }
+    }
+
+    fn begin_offscreen_frame(&mut self, width: u32, height: u32, clear: Color) -> bool {
+        if self.onscreen_frame.is_some() {
+            return false;
+        }
+
+        let frame = Frame::new(width, height, clear_color(clear));
+        self.onscreen_frame = Some((
+            std::mem::replace(&mut self.frame, frame),
+            std::mem::take(&mut self.commands),
+        ));
+        true
+    }
+
+    fn end_offscreen_frame(&mut self) -> Option<Bitmap> {
+        let (onscreen_frame, onscreen_commands) = self.onscreen_frame.take()?;
+        let commands = std::mem::replace(&mut self.commands, onscreen_commands);
+        let pixmap = &self.frame.pixmap;
+        let full = Rect::new(0, 0, pixmap.width, pixmap.height);
+        self.replay(&commands, full);
+        let frame = std::mem::replace(&mut self.frame, onscreen_frame);
+        Some(frame.pixmap.to_bitmap())
+    }
+
+    fn capture_frame(&mut self) -> Option<Bitmap> {
+        let frame = self
+            .onscreen_frame
+            .as_ref()
+            .map_or(&self.frame, |(frame, _)| frame);
+        Some(frame.pixmap.to_bitmap())
+    }
+
+    fn push_render_target(&mut self) -> bool {
+        self.render_target_starts.push(self.commands.len());
+        self.record(Command::PushRenderTarget);
+        true
+    }
+
+    fn pop_and_composite(&mut self, composite: &Composite) {
+        self.render_target_starts.pop();
+        self.record(Command::PopAndComposite(composite.clone()));
+    }
+
+    fn pop_and_cache(&mut self, composite: &Composite) -> Option<CacheHandle> {
+        let start = match self.render_target_starts.pop() {
+            Some(start) => start,
+            None => {
+                log::error!("Render target stack underflow");
+                return None;
+            }
+        };
+
+        // The draw calls of the surface are kept, without the `PushRenderTarget`.
+        let commands = self.commands.split_off(start + 1);
+        self.commands.pop();
+        let bounds = commands
+            .iter()
+            .try_fold(Rect::default(), |region, recorded| {
+                recorded.bounds.map(|bounds| region.union(bounds))
+            });
+
+        let handle = CacheHandle(self.next_cache_id);
+        self.next_cache_id += 1;
+        self.caches.insert(
+            handle.0,
+            CachedSurface {
+                commands,
+                bounds,
+                pixmap: None,
+                used: false,
+            },
+        );
+        self.composite_cached(handle, 0, 0, composite);
+        Some(handle)
+    }
+
+    fn composite_cached(
+        &mut self,
+        handle: CacheHandle,
+        dx: i32,
+        dy: i32,
+        composite: &Composite,
+    ) -> bool {
+        let cache = match self.caches.get_mut(&handle.0) {
+            Some(cache) => cache,
+            None => return false,
+        };
+        cache.used = true;
+        let bounds = cache.bounds.map(|bounds| bounds.translate(dx, dy));
+        self.commands.push(Recorded {
+            command: Command::CompositeCached {
+                handle: handle.0,
+                dx,
+                dy,
+                composite: composite.clone(),
+            },
+            bounds,
+        });
+        true
+    }
+
+    fn register_video_stream(
+        &mut self,
+        id: swf::CharacterId,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        let bitmap = Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![0; width as usize * height as usize * 4]),
+        };
+        Ok(self.register_bitmap(id, bitmap)?.handle)
+    }
+
+    fn upload_video_frame(
+        &mut self,
+        handle: BitmapHandle,
+        frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        let (_id, texture) = self
+            .textures
+            .get_mut(handle.0)
+            .ok_or("Unknown video stream")?;
+        let data = frame.to_rgba(texture.width, texture.height);
+        if data.len() != texture.width as usize * texture.height as usize * 4 {
+            return Err("Video frame size differs from its stream".into());
+        }
+        texture.replace(data);
+        Ok(())
+    }
+}
+
+impl SoftwareRenderBackend {
+    /// Records a draw call that can change the whole frame.
+    fn record(&mut self, command: Command) {
+        self.commands.push(Recorded {
+            command,
+            bounds: None,
+        });
+    }
+
+    /// The area covered by the draw calls that differ from those of the last frame.
+    fn changed_region(&self, commands: &[Recorded]) -> Rect {
+        let full = Rect::new(0, 0, self.viewport_width, self.viewport_height);
+        let mut region = Rect::default();
+        for i in 0..commands.len().max(self.last_commands.len()) {
+            let (new, old) = (commands.get(i), self.last_commands.get(i));
+            if new == old {
+                continue;
+            }
+            for recorded in new.iter().chain(old.iter()) {
+                match recorded.bounds {
+                    Some(bounds) => region = region.union(bounds),
+                    None => return full,
+                }
+            }
+        }
+
+        // Blurs spread changes beyond the pixels that changed.
+        let has_blur = commands.iter().any(|recorded| match &recorded.command {
+            Command::PopAndComposite(composite) => {
+                matches!(composite.shader, Some(CompositeShader::Blur { .. }))
+            }
+            _ => false,
+        });
+        if has_blur && !region.is_empty() {
+            full
+        } else {
+            region
+        }
+    }
+
+    /// Carries out draw calls, only changing the pixels within `clip`.
+    fn replay(&mut self, commands: &[Recorded], clip: Rect) {
+        self.frame.masks.clear();
+        self.frame.num_masks_active = 0;
+
+        // The surfaces below the current one, pushed by `push_render_target`.
+        let mut render_targets = vec![];
+        for recorded in commands {
+            match &recorded.command {
+                Command::Shape {
+                    index, transform, ..
+                } => {
+                    let textures = &self.textures;
+                    for draw in &self.meshes[*index].draws {
+                        let texture = match &draw.draw_type {
+                            DrawType::Bitmap(bitmap) => textures
+                                .iter()
+                                .find(|(id, _tex)| *id == bitmap.id)
+                                .map(|tex| &tex.1),
+                            _ => None,
+                        };
+                        self.frame.draw(
+                            &mut self.coverage,
+                            draw,
+                            texture,
+                            transform,
+                            self.quality,
+                            clip,
+                        );
+                    }
+                }
+                Command::Bitmap {
+                    index,
+                    transform,
+                    smoothing,
+                    ..
+                } => {
+                    let (id, texture) = &self.textures[*index];
+                    let draw = bitmap_quad(*id, texture, *smoothing);
+                    self.frame.draw(
+                        &mut self.coverage,
+                        &draw,
+                        Some(texture),
+                        transform,
+                        self.quality,
+                        clip,
+                    );
+                }
+                Command::Letterbox(letterbox) => self.frame.draw_letterbox(*letterbox, clip),
+                Command::PushMask => self.frame.push_mask(),
+                Command::ActivateMask => self.frame.activate_mask(),
+                Command::PopMask => self.frame.pop_mask(),
+                Command::PushRenderTarget => {
+                    let pixmap = &self.frame.pixmap;
+                    let frame = Frame::new(pixmap.width, pixmap.height, [0.0; 4]);
+                    render_targets.push(std::mem::replace(&mut self.frame, frame));
+                }
+                Command::PopAndComposite(composite) => match render_targets.pop() {
+                    Some(below) => {
+                        let mut layer = std::mem::replace(&mut self.frame, below).pixmap;
+                        if let Some(shader) = &composite.shader {
+                            composite::apply_shader(&mut layer, shader);
+                        }
+                        self.frame.composite(&layer, composite.blend_mode, clip);
+                    }
+                    None => log::error!("Render target stack underflow"),
+                },
+                Command::CompositeCached {
+                    handle,
+                    dx,
+                    dy,
+                    composite,
+                } => {
+                    let pixmap = &self.frame.pixmap;
+                    self.draw_cache(*handle, pixmap.width, pixmap.height);
+                    if let Some(pixmap) = self.caches.get(handle).and_then(|c| c.pixmap.as_ref()) {
+                        let mut layer = pixmap.translate(*dx, *dy);
+                        if let Some(shader) = &composite.shader {
+                            composite::apply_shader(&mut layer, shader);
+                        }
+                        self.frame.composite(&layer, composite.blend_mode, clip);
+                    }
+                }
+            }
+        }
+
+        // Surfaces that were never popped are drawn as they are.
+        while let Some(below) = render_targets.pop() {
+            let layer = std::mem::replace(&mut self.frame, below).pixmap;
+            self.frame.composite(&layer, swf::BlendMode::Normal, clip);
+        }
+    }
+}
+
+impl SoftwareRenderBackend {
+    /// Draws a kept surface from its draw calls, unless it has already been drawn
+    /// at this size.
+    fn draw_cache(&mut self, handle: usize, width: u32, height: u32) {
+        let cache = match self.caches.get_mut(&handle) {
+            Some(cache) => cache,
+            None => return,
+        };
+        if let Some(pixmap) = &cache.pixmap {
+            if pixmap.width == width && pixmap.height == height {
+                return;
+            }
+        }
+
+        // The whole surface is drawn, as later frames can move it.
+        let commands = std::mem::take(&mut cache.commands);
+        let below = std::mem::replace(&mut self.frame, Frame::new(width, height, [0.0; 4]));
+        self.replay(&commands, Rect::new(0, 0, width, height));
+        let frame = std::mem::replace(&mut self.frame, below);
+        if let Some(cache) = self.caches.get_mut(&handle) {
+            cache.commands = commands;
+            cache.pixmap = Some(frame.pixmap);
+        }
+    }
+}
+
+impl Mesh {
+    fn new(draws: Vec<Draw>) -> Self {
+        let mut bounds: Option<[Point; 2]> = None;
+        for vertex in draws.iter().flat_map(|draw| &draw.vertices) {
+            let [x, y] = vertex.position;
+            bounds = Some(match bounds {
+                Some([min, max]) => [
+                    [min[0].min(x), min[1].min(y)],
+                    [max[0].max(x), max[1].max(y)],
+                ],
+                None => [[x, y], [x, y]],
+            });
+        }
+        Self {
+            draws,
+            bounds,
+            version: 0,
+        }
+    }
+}
+
+impl Frame {
+    fn new(width: u32, height: u32, clear: [f32; 4]) -> Self {
+        Self {
+            pixmap: Pixmap::new(width, height, clear),
+            masks: vec![],
+            num_masks_active: 0,
+        }
+    }
+
+    fn push_mask(&mut self) {
+        let pixmap = &self.pixmap;
+        self.masks
+            .push(vec![0.0; pixmap.width as usize * pixmap.height as usize]);
+    }
+
+    fn activate_mask(&mut self) {
+        let masks = &mut self.masks;
+        let len = masks.len();
+        if len >= 2 {
+            let (below, top) = masks.split_at_mut(len - 1);
+            for (coverage, below) in top[0].iter_mut().zip(&below[len - 2]) {
+                *coverage *= below;
+            }
+        }
+        self.num_masks_active += 1;
+    }
+
+    fn pop_mask(&mut self) {
+        if self.masks.pop().is_some() {
+            self.num_masks_active = self.num_masks_active.saturating_sub(1);
+        }
+    }
+
+    fn draw_letterbox(&mut self, letterbox: Letterbox, clip: Rect) {
+        let pixmap = &mut self.pixmap;
+        let (width, height) = (pixmap.width as i32, pixmap.height as i32);
+        let mut fill = |x: i32, y: i32, width: i32, height: i32| {
+            let x_min = x.max(clip.x_min as i32);
+            let y_min = y.max(clip.y_min as i32);
+            let x_max = (x + width).min(clip.x_max as i32);
+            let y_max = (y + height).min(clip.y_max as i32);
+            pixmap.fill_rect(x_min, y_min, x_max - x_min, y_max - y_min, [0.0; 4]);
+        };
+        match letterbox {
+            Letterbox::None => (),
+            Letterbox::Letterbox { top, bottom } => {
+                let (top, bottom) = (top as i32, bottom as i32);
+                fill(0, 0, width, top);
+                fill(0, height - bottom, width, bottom + 1);
+            }
+            Letterbox::Pillarbox { left, right } => {
+                let (left, right) = (left as i32, right as i32);
+                fill(0, 0, left, height);
+                fill(width - right, 0, right + 1, height);
+            }
+        }
+    }
+
+    fn draw(
+        &mut self,
+        coverage: &mut Coverage,
+        draw: &Draw,
+        texture: Option<&Texture>,
+        transform: &Transform,
+        quality: StageQuality,
+        clip: Rect,
+    ) {
+        let matrix = Affine::from_transform(transform);
+        let position = |index: u32| matrix.apply(draw.vertices[index as usize].position);
+        let triangles: Vec<[Point; 3]> = draw
+            .indices
+            .chunks_exact(3)
+            .map(|indices| {
+                [
+                    position(indices[0]),
+                    position(indices[1]),
+                    position(indices[2]),
+                ]
+            })
+            .collect();
+        let samples_per_axis = quality.samples_per_axis();
+        coverage.rasterize(&triangles, clip, samples_per_axis);
+
+        let width = self.pixmap.width;
+        let covered = |samples: &[u32]| {
+            samples.iter().filter(|&&sample| sample != 0).count() as f32 / samples.len() as f32
+        };
+
+        if self.num_masks_active < self.masks.len() {
+            // Only the shape of mask contents matters, and not their colors.
+            let mask = self.masks.last_mut().unwrap();
+            coverage.for_each_pixel(|x, y, samples| {
+                let pixel = &mut mask[(y * width + x) as usize];
+                *pixel += covered(samples) * (1.0 - *pixel);
+            });
+            return;
+        }
+
+        let mask = self.masks.last();
+        let pixmap = &mut self.pixmap;
+        let mut blend = |x: u32, y: u32, color: [f32; 4], coverage: f32| {
+            let coverage = coverage * mask.map_or(1.0, |mask| mask[(y * width + x) as usize]);
+            if coverage > 0.0 {
+                pixmap.blend(x, y, scale(color, coverage));
+            }
+        };
+        let color_transform = &transform.color_transform;
+
+        match &draw.draw_type {
+            DrawType::Color => {
+                // Each triangle has a single color.
+                let colors: Vec<[f32; 4]> = draw
+                    .indices
+                    .chunks_exact(3)
+                    .map(|indices| {
+                        let color = unpack_color(draw.vertices[indices[0] as usize].color);
+                        premultiply(apply_color_transform(color, color_transform))
+                    })
+                    .collect();
+                coverage.for_each_pixel(|x, y, samples| {
+                    let mut color = [0.0; 4];
+                    for &sample in samples.iter().filter(|&&sample| sample != 0) {
+                        let sample_color = colors[sample as usize - 1];
+                        for i in 0..4 {
+                            color[i] += sample_color[i];
+                        }
+                    }
+                    blend(x, y, color, 1.0 / samples.len() as f32);
+                });
+            }
+            DrawType::Gradient(gradient) => {
+                let inverse = match matrix.inverse() {
+                    Some(inverse) => inverse,
+                    None => return,
+                };
+                coverage.for_each_pixel(|x, y, samples| {
+                    let uv = apply_gl_matrix(
+                        &gradient.matrix,
+                        inverse.apply([x as f32 + 0.5, y as f32 + 0.5]),
+                    );
+                    let color = gradient_color(gradient, uv);
+                    let color = premultiply(apply_color_transform(color, color_transform));
+                    blend(x, y, color, covered(samples));
+                });
+            }
+            DrawType::Bitmap(bitmap) => {
+                let (inverse, texture) = match (matrix.inverse(), texture) {
+                    (Some(inverse), Some(texture)) => (inverse, texture),
+                    _ => return,
+                };
+                let is_smoothed = quality.smooth_bitmap(bitmap.is_smoothed);
+                coverage.for_each_pixel(|x, y, samples| {
+                    let uv = apply_gl_matrix(
+                        &bitmap.matrix,
+                        inverse.apply([x as f32 + 0.5, y as f32 + 0.5]),
+                    );
+                    let color = texture.sample(uv, is_smoothed, bitmap.is_repeating);
+                    // Unmultiply alpha before applying the color transform.
+                    let color = if color[3] > 0.0 {
+                        let color = [
+                            color[0] / color[3],
+                            color[1] / color[3],
+                            color[2] / color[3],
+                            color[3],
+                        ];
+                        premultiply(apply_color_transform(color, color_transform))
+                    } else {
+                        color
+                    };
+                    blend(x, y, color, covered(samples));
+                });
+            }
+        }
+    }
+}
+
+impl Frame {
+    /// Blends the pixels within `clip` of a surface of the same size onto this frame.
+    fn composite(&mut self, layer: &Pixmap, blend_mode: swf::BlendMode, clip: Rect) {
+        let width = self.pixmap.width;
+        let pixels = (clip.y_min..clip.y_max)
+            .flat_map(|y| (clip.x_min..clip.x_max).map(move |x| (y * width + x) as usize));
+
+        if self.num_masks_active < self.masks.len() {
+            // Only the shape of mask contents matters, and not their colors.
+            let mask = self.masks.last_mut().unwrap();
+            for i in pixels {
+                mask[i] += layer.pixels[i][3] * (1.0 - mask[i]);
+            }
+            return;
+        }
+
+        let mask = self.masks.last();
+        for i in pixels {
+            let coverage = mask.map_or(1.0, |mask| mask[i]);
+            if coverage > 0.0 {
+                let pixel = &mut self.pixmap.pixels[i];
+                let blended = composite::blend(blend_mode, layer.pixels[i], *pixel);
+                for c in 0..4 {
+                    pixel[c] += (blended[c] - pixel[c]) * coverage;
+                }
+            }
+        }
+    }
+}
+
+/// A quad the size of a bitmap, filled with it.
+fn bitmap_quad(id: swf::CharacterId, texture: &Texture, smoothing: bool) -> Draw {
+    let (width, height) = (texture.width as f32, texture.height as f32);
+    let vertex = |x, y| Vertex {
+        position: [x, y],
+        color: 0xffff_ffff,
+    };
+    Draw {
+        draw_type: DrawType::Bitmap(BitmapDraw {
+            matrix: [
+                [1.0 / width, 0.0, 0.0],
+                [0.0, 1.0 / height, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+            id,
+            is_smoothed: smoothing,
+            is_repeating: false,
+        }),
+        vertices: vec![
+            vertex(0.0, 0.0),
+            vertex(width, 0.0),
+            vertex(width, height),
+            vertex(0.0, height),
+        ],
+        indices: vec![0, 1, 2, 0, 2, 3],
+    }
+}
+
+/// The pixels that a box in shape space can touch once transformed.
+fn device_bounds(bounds: [Point; 2], transform: &Transform) -> Rect {
+    let matrix = Affine::from_transform(transform);
+    let corners = [
+        matrix.apply(bounds[0]),
+        matrix.apply([bounds[1][0], bounds[0][1]]),
+        matrix.apply(bounds[1]),
+        matrix.apply([bounds[0][0], bounds[1][1]]),
+    ];
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for corner in &corners {
+        min_x = min_x.min(corner[0]);
+        min_y = min_y.min(corner[1]);
+        max_x = max_x.max(corner[0]);
+        max_y = max_y.max(corner[1]);
+    }
+    // Anti-aliasing can touch the pixels next to the box.
+    Rect::new(
+        (min_x.floor() - 1.0).max(0.0) as u32,
+        (min_y.floor() - 1.0).max(0.0) as u32,
+        (max_x.ceil() + 1.0).max(0.0) as u32,
+        (max_y.ceil() + 1.0).max(0.0) as u32,
+    )
+}
+
+impl Pixmap {
+    fn to_bitmap(&self) -> Bitmap {
+        Bitmap {
+            width: self.width,
+            height: self.height,
+            data: BitmapFormat::Rgba(self.to_rgba()),
+        }
+    }
+}
+
+/// A transform from shape space to device pixels.
+#[derive(Copy, Clone, Debug)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+}
+
+impl Affine {
+    fn from_transform(transform: &Transform) -> Self {
+        let matrix = &transform.matrix;
+        Self {
+            a: matrix.a,
+            b: matrix.b,
+            c: matrix.c,
+            d: matrix.d,
+            tx: matrix.tx.to_pixels() as f32,
+            ty: matrix.ty.to_pixels() as f32,
+        }
+    }
+
+    fn apply(&self, point: Point) -> Point {
+        [
+            self.a * point[0] + self.c * point[1] + self.tx,
+            self.b * point[0] + self.d * point[1] + self.ty,
+        ]
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Self {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            tx: (self.c * self.ty - self.d * self.tx) / det,
+            ty: (self.b * self.tx - self.a * self.ty) / det,
+        })
+    }
+}
+
+/// Applies a column-major 3x3 matrix, as passed to the shaders of the GPU
+/// backends, to a point.
+fn apply_gl_matrix(matrix: &[[f32; 3]; 3], point: Point) -> Point {
+    [
+        matrix[0][0] * point[0] + matrix[1][0] * point[1] + matrix[2][0],
+        matrix[0][1] * point[0] + matrix[1][1] * point[1] + matrix[2][1],
+    ]
+}
+
+fn apply_color_transform(color: [f32; 4], transform: &ColorTransform) -> [f32; 4] {
+    [
+        (color[0] * transform.r_mult + transform.r_add)
+            .max(0.0)
+            .min(1.0),
+        (color[1] * transform.g_mult + transform.g_add)
+            .max(0.0)
+            .min(1.0),
+        (color[2] * transform.b_mult + transform.b_add)
+            .max(0.0)
+            .min(1.0),
+        (color[3] * transform.a_mult + transform.a_add)
+            .max(0.0)
+            .min(1.0),
+    ]
+}
+
+/// Unpacks a vertex color, which is stored as RGBA bytes.
+fn unpack_color(color: u32) -> [f32; 4] {
+    let bytes = color.to_le_bytes();
+    [
+        f32::from(bytes[0]) / 255.0,
+        f32::from(bytes[1]) / 255.0,
+        f32::from(bytes[2]) / 255.0,
+        f32::from(bytes[3]) / 255.0,
+    ]
+}
+
+fn clear_color(color: Color) -> [f32; 4] {
+    premultiply([
+        f32::from(color.r) / 255.0,
+        f32::from(color.g) / 255.0,
+        f32::from(color.b) / 255.0,
+        f32::from(color.a) / 255.0,
+    ])
+}
+
+fn premultiply(color: [f32; 4]) -> [f32; 4] {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+fn scale(color: [f32; 4], factor: f32) -> [f32; 4] {
+    [
+        color[0] * factor,
+        color[1] * factor,
+        color[2] * factor,
+        color[3] * factor,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::backend::render::swf::{Matrix, Twips};
+
+    #[test]
+    fn dirty_regions() {
+        let mut renderer = SoftwareRenderBackend::new(100, 100);
+        renderer.set_dirty_regions(true);
+        let bitmap = Bitmap {
+            width: 10,
+            height: 10,
+            data: BitmapFormat::Rgba(vec![255; 400]),
+        };
+        let bitmap = renderer.register_bitmap(1, bitmap).unwrap().handle;
+        let render = |renderer: &mut SoftwareRenderBackend, x| {
+            let transform = Transform {
+                matrix: Matrix::translate(Twips::from_pixels(x), Twips::zero()),
+                ..Default::default()
+            };
+            renderer.begin_frame(Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            });
+            renderer.render_bitmap(bitmap, &transform, false);
+            renderer.end_frame();
+        };
+
+        render(&mut renderer, 0.0);
+        assert_eq!(renderer.dirty_region(), Rect::new(0, 0, 100, 100));
+        render(&mut renderer, 0.0);
+        assert!(renderer.dirty_region().is_empty());
+
+        // Both the old and the new position of the bitmap are redrawn.
+        render(&mut renderer, 20.0);
+        assert_eq!(renderer.dirty_region(), Rect::new(0, 0, 31, 11));
+        let frame = match renderer.capture_frame().unwrap().data {
+            BitmapFormat::Rgba(data) => data,
+            BitmapFormat::Rgb(_) => unreachable!(),
+        };
+        let pixel = |x: usize, y: usize| &frame[(y * 100 + x) * 4..(y * 100 + x) * 4 + 4];
+        assert_eq!(pixel(5, 5), [0, 0, 0, 255]);
+        assert_eq!(pixel(25, 5), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn cached_surfaces() {
+        let mut renderer = SoftwareRenderBackend::new(100, 100);
+        renderer.set_dirty_regions(true);
+        let bitmap = Bitmap {
+            width: 10,
+            height: 10,
+            data: BitmapFormat::Rgba(vec![255; 400]),
+        };
+        let bitmap = renderer.register_bitmap(1, bitmap).unwrap().handle;
+        let black = Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let composite = Composite::default();
+        let pixel = |renderer: &mut SoftwareRenderBackend, x: usize| match renderer
+            .capture_frame()
+            .unwrap()
+            .data
+        {
+            BitmapFormat::Rgba(data) => data[(5 * 100 + x) * 4],
+            BitmapFormat::Rgb(_) => unreachable!(),
+        };
+
+        renderer.begin_frame(black.clone());
+        assert!(renderer.push_render_target());
+        renderer.render_bitmap(bitmap, &Default::default(), false);
+        let handle = renderer.pop_and_cache(&composite).unwrap();
+        renderer.end_frame();
+        assert_eq!(pixel(&mut renderer, 5), 255);
+
+        // The surface is drawn again at another position, without its draw calls.
+        renderer.begin_frame(black.clone());
+        assert!(renderer.composite_cached(handle, 20, 0, &composite));
+        renderer.end_frame();
+        assert_eq!(renderer.dirty_region(), Rect::new(0, 0, 31, 11));
+        assert_eq!(pixel(&mut renderer, 5), 0);
+        assert_eq!(pixel(&mut renderer, 25), 255);
+
+        // Surfaces that aren't drawn in a frame are released.
+        renderer.begin_frame(black.clone());
+        renderer.end_frame();
+        renderer.begin_frame(black);
+        assert!(!renderer.composite_cached(handle, 0, 0, &composite));
+        renderer.end_frame();
+    }
+
+    #[test]
+    fn video_frames() {
+        let mut renderer = SoftwareRenderBackend::new(2, 1);
+        renderer.set_dirty_regions(true);
+        let video = renderer.register_video_stream(1, 2, 1).unwrap();
+        let mut render = |frame| {
+            renderer.upload_video_frame(video, frame).unwrap();
+            renderer.begin_frame(Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            });
+            renderer.render_bitmap(video, &Default::default(), false);
+            renderer.end_frame();
+            match renderer.capture_frame().unwrap().data {
+                BitmapFormat::Rgba(data) => data,
+                BitmapFormat::Rgb(_) => unreachable!(),
+            }
+        };
+
+        let white = [255, 255, 255, 255, 255, 255, 255, 255];
+        assert_eq!(render(DecodedFrame::Rgba(&white)), white);
+
+        // A new frame is redrawn even though the draw calls are the same.
+        let frame = DecodedFrame::Yuv420 {
+            y: &[16, 16],
+            u: &[128],
+            v: &[128],
+        };
+        assert_eq!(render(frame), [0, 0, 0, 255, 0, 0, 0, 255]);
+        assert!(renderer
+            .upload_video_frame(video, DecodedFrame::Rgba(&[0; 4]))
+            .is_err());
+    }
+}
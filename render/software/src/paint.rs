@@ -0,0 +1,232 @@
+//! Colors of gradient and bitmap fills
+//!
+//! These follow the fragment shaders of the GPU backends, so that all backends
+//! render fills alike.
+
+use ruffle_core::backend::render::swf::GradientInterpolation;
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use ruffle_render_common_tess::{Gradient, GradientSpread, GradientType};
+
+/// A bitmap as RGBA bytes with pre-multiplied alpha.
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    data: Vec<u8>,
+
+    /// Increased whenever the pixels are replaced, so that frames drawing it differ.
+    pub version: u32,
+}
+
+impl Texture {
+    pub fn new(bitmap: Bitmap) -> Self {
+        let data = match bitmap.data {
+            BitmapFormat::Rgba(data) => data,
+            BitmapFormat::Rgb(data) => data
+                .chunks_exact(3)
+                .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+        };
+        Self {
+            width: bitmap.width,
+            height: bitmap.height,
+            data,
+            version: 0,
+        }
+    }
+
+    /// Replaces the pixels with RGBA bytes of the same size, such as a frame of video.
+    pub fn replace(&mut self, data: Vec<u8>) {
+        self.data = data;
+        self.version += 1;
+    }
+
+    /// The pre-multiplied color at a position, where the bitmap spans 0 to 1
+    /// on both axes.
+    pub fn sample(&self, uv: [f32; 2], is_smoothed: bool, is_repeating: bool) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+
+        let x = uv[0] * self.width as f32;
+        let y = uv[1] * self.height as f32;
+        if !is_smoothed {
+            return self.texel(x.floor() as i32, y.floor() as i32, is_repeating);
+        }
+
+        // Bilinear filtering between the centers of the four nearest texels.
+        let x = x - 0.5;
+        let y = y - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = mix(
+            self.texel(x0, y0, is_repeating),
+            self.texel(x0 + 1, y0, is_repeating),
+            fx,
+        );
+        let bottom = mix(
+            self.texel(x0, y0 + 1, is_repeating),
+            self.texel(x0 + 1, y0 + 1, is_repeating),
+            fx,
+        );
+        mix(top, bottom, fy)
+    }
+
+    /// The color of a texel. Coordinates outside of the bitmap either wrap
+    /// around, or are clamped to its edges.
+    fn texel(&self, x: i32, y: i32, is_repeating: bool) -> [f32; 4] {
+        let (width, height) = (self.width as i32, self.height as i32);
+        let (x, y) = if is_repeating {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.max(0).min(width - 1), y.max(0).min(height - 1))
+        };
+        let i = (y * width + x) as usize * 4;
+        let texel = &self.data[i..i + 4];
+        [
+            f32::from(texel[0]) / 255.0,
+            f32::from(texel[1]) / 255.0,
+            f32::from(texel[2]) / 255.0,
+            f32::from(texel[3]) / 255.0,
+        ]
+    }
+}
+
+/// The color of a gradient at a position in gradient space, without
+/// pre-multiplied alpha.
+///
+/// The colors of gradients interpolated in linear RGB must already have been
+/// converted to linear RGB.
+pub fn gradient_color(gradient: &Gradient, uv: [f32; 2]) -> [f32; 4] {
+    let t = match gradient.gradient_type {
+        GradientType::Linear => uv[0],
+        GradientType::Radial => length([uv[0] * 2.0 - 1.0, uv[1] * 2.0 - 1.0]),
+        GradientType::Focal => {
+            let focal_point = gradient.focal_point;
+            let uv = [uv[0] * 2.0 - 1.0, uv[1] * 2.0 - 1.0];
+            let d = [focal_point - uv[0], -uv[1]];
+            let l = length(d);
+            if l > 0.0 {
+                let d = [d[0] / l, d[1] / l];
+                l / ((1.0 - focal_point * focal_point * d[1] * d[1]).sqrt() + focal_point * d[0])
+            } else {
+                0.0
+            }
+        }
+    };
+    let t = match gradient.repeat_mode {
+        GradientSpread::Pad => t.max(0.0).min(1.0),
+        GradientSpread::Repeat => t - t.floor(),
+        GradientSpread::Reflect => {
+            let t = t.abs();
+            if t.rem_euclid(2.0) < 1.0 {
+                t - t.floor()
+            } else {
+                1.0 - (t - t.floor())
+            }
+        }
+    };
+
+    let num_colors = (gradient.num_colors as usize)
+        .min(gradient.colors.len())
+        .min(gradient.ratios.len());
+    if num_colors == 0 {
+        return [0.0; 4];
+    }
+    let ratios = &gradient.ratios[..num_colors];
+    let colors = &gradient.colors[..num_colors];
+    let color = if t <= ratios[0] {
+        colors[0]
+    } else if let Some(i) = ratios.iter().position(|&ratio| t <= ratio) {
+        let range = ratios[i] - ratios[i - 1];
+        let a = if range > 0.0 {
+            (t - ratios[i - 1]) / range
+        } else {
+            1.0
+        };
+        mix(colors[i - 1], colors[i], a)
+    } else {
+        colors[num_colors - 1]
+    };
+
+    if gradient.interpolation == GradientInterpolation::LinearRGB {
+        linear_to_srgb(color)
+    } else {
+        color
+    }
+}
+
+fn linear_to_srgb(color: [f32; 4]) -> [f32; 4] {
+    fn to_srgb_channel(n: f32) -> f32 {
+        if n < 0.003_130_8 {
+            n * 12.92
+        } else {
+            1.055 * n.powf(1.0 / 2.4) - 0.055
+        }
+    }
+    [
+        to_srgb_channel(color[0]),
+        to_srgb_channel(color[1]),
+        to_srgb_channel(color[2]),
+        color[3],
+    ]
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+fn length(v: [f32; 2]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(gradient_type: GradientType, repeat_mode: GradientSpread) -> Gradient {
+        Gradient {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            gradient_type,
+            ratios: vec![0.0, 1.0],
+            colors: vec![[0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0]],
+            num_colors: 2,
+            repeat_mode,
+            focal_point: 0.5,
+            interpolation: GradientInterpolation::RGB,
+        }
+    }
+
+    #[test]
+    fn spread_modes() {
+        let pad = gradient(GradientType::Linear, GradientSpread::Pad);
+        assert_eq!(gradient_color(&pad, [1.25, 0.0])[0], 1.0);
+        assert_eq!(gradient_color(&pad, [-0.25, 0.0])[0], 0.0);
+
+        let repeat = gradient(GradientType::Linear, GradientSpread::Repeat);
+        assert_eq!(gradient_color(&repeat, [1.25, 0.0])[0], 0.25);
+        assert_eq!(gradient_color(&repeat, [-0.25, 0.0])[0], 0.75);
+
+        let reflect = gradient(GradientType::Linear, GradientSpread::Reflect);
+        assert_eq!(gradient_color(&reflect, [1.25, 0.0])[0], 0.75);
+        assert_eq!(gradient_color(&reflect, [-0.25, 0.0])[0], 0.25);
+    }
+
+    #[test]
+    fn focal_gradients() {
+        let focal = gradient(GradientType::Focal, GradientSpread::Pad);
+        // The focal point is at the start of the gradient, and the circle at
+        // its end.
+        assert_eq!(gradient_color(&focal, [0.75, 0.5])[0], 0.0);
+        assert_eq!(gradient_color(&focal, [1.0, 0.5])[0], 1.0);
+        assert_eq!(gradient_color(&focal, [0.0, 0.5])[0], 1.0);
+        assert!((gradient_color(&focal, [0.375, 0.5])[0] - 0.5).abs() < 1e-6);
+    }
+}
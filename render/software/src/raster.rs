@@ -0,0 +1,342 @@
+//! Rasterization of triangles into pixels
+//!
+//! Anti-aliasing is done by testing a grid of sample points in each pixel.
+//! Every sample remembers the last triangle covering it, so the triangles of a
+//! tessellated shape never blend with each other along their shared edges.
+
+/// A point in device pixels.
+pub type Point = [f32; 2];
+
+/// A rectangle of pixels, from its top-left corner up to but excluding its
+/// bottom-right corner.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x_min: u32,
+    pub y_min: u32,
+    pub x_max: u32,
+    pub y_max: u32,
+}
+
+impl Rect {
+    pub fn new(x_min: u32, y_min: u32, x_max: u32, y_max: u32) -> Self {
+        Self {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x_min >= self.x_max || self.y_min >= self.y_max
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(self, other: Self) -> Self {
+        if self.is_empty() {
+            other
+        } else if other.is_empty() {
+            self
+        } else {
+            Self::new(
+                self.x_min.min(other.x_min),
+                self.y_min.min(other.y_min),
+                self.x_max.max(other.x_max),
+                self.y_max.max(other.y_max),
+            )
+        }
+    }
+
+    /// Moves the rectangle by a number of pixels, clamping it to positive coordinates.
+    pub fn translate(self, dx: i32, dy: i32) -> Self {
+        let offset = |value: u32, delta: i32| (value as i64 + delta as i64).max(0) as u32;
+        Self::new(
+            offset(self.x_min, dx),
+            offset(self.y_min, dy),
+            offset(self.x_max, dx),
+            offset(self.y_max, dy),
+        )
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        let rect = Self::new(
+            self.x_min.max(other.x_min),
+            self.y_min.max(other.y_min),
+            self.x_max.min(other.x_max),
+            self.y_max.min(other.y_max),
+        );
+        if rect.is_empty() {
+            Self::default()
+        } else {
+            rect
+        }
+    }
+}
+
+/// An image with pre-multiplied alpha, with components ranging from 0 to 1.
+#[derive(Clone, Debug)]
+pub struct Pixmap {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 4]>,
+}
+
+impl Pixmap {
+    pub fn new(width: u32, height: u32, color: [f32; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+        }
+    }
+
+    /// A copy of the image moved by a number of pixels. The pixels moved in from
+    /// outside the image are transparent.
+    pub fn translate(&self, dx: i32, dy: i32) -> Self {
+        let mut moved = Self::new(self.width, self.height, [0.0; 4]);
+        let (width, height) = (self.width as i32, self.height as i32);
+        let x0 = dx.max(0).min(width);
+        let x1 = (width + dx).max(0).min(width);
+        if x0 >= x1 {
+            return moved;
+        }
+        for y in dy.max(0)..(height + dy).min(height) {
+            let src = ((y - dy) * width + x0 - dx) as usize;
+            let dst = (y * width) as usize;
+            moved.pixels[dst + x0 as usize..dst + x1 as usize]
+                .copy_from_slice(&self.pixels[src..src + (x1 - x0) as usize]);
+        }
+        moved
+    }
+
+    /// Replaces the pixels of a rectangle with a color.
+    /// The rectangle is clipped to the image.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: [f32; 4]) {
+        let x0 = x.max(0) as u32;
+        let y0 = y.max(0) as u32;
+        let x1 = (x + width).max(0).min(self.width as i32) as u32;
+        let y1 = (y + height).max(0).min(self.height as i32) as u32;
+        for y in y0..y1 {
+            let row = (y * self.width) as usize;
+            for pixel in &mut self.pixels[row + x0 as usize..row + x1.max(x0) as usize] {
+                *pixel = color;
+            }
+        }
+    }
+
+    /// Draws a pre-multiplied color over a pixel.
+    pub fn blend(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        let inverse_alpha = 1.0 - color[3];
+        for i in 0..4 {
+            pixel[i] = color[i] + pixel[i] * inverse_alpha;
+        }
+    }
+
+    /// The pixels as RGBA bytes, still with pre-multiplied alpha.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        // Casts saturate, so components outside of 0 to 1 are clamped.
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.iter().map(|&c| (c * 255.0).round() as u8))
+            .collect()
+    }
+}
+
+/// The triangles covering each sample of a rectangle of pixels.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    samples_per_axis: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// For each sample, one plus the index of the triangle covering it, or 0.
+    /// The samples of a pixel are next to each other.
+    samples: Vec<u32>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Rasterizes the parts of triangles within a rectangle of pixels, with
+    /// `samples_per_axis` squared samples in each pixel.
+    ///
+    /// Where triangles overlap, the later one wins.
+    pub fn rasterize(&mut self, triangles: &[[Point; 3]], clip: Rect, samples_per_axis: u32) {
+        let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+        let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for point in triangles.iter().flatten() {
+            min_x = min_x.min(point[0]);
+            min_y = min_y.min(point[1]);
+            max_x = max_x.max(point[0]);
+            max_y = max_y.max(point[1]);
+        }
+
+        let n = samples_per_axis.max(1);
+        self.samples_per_axis = n;
+        self.x = (min_x.floor().max(0.0) as u32).max(clip.x_min);
+        self.y = (min_y.floor().max(0.0) as u32).max(clip.y_min);
+        let x1 = (max_x.ceil().max(0.0) as u32).min(clip.x_max);
+        let y1 = (max_y.ceil().max(0.0) as u32).min(clip.y_max);
+        self.width = x1.saturating_sub(self.x);
+        self.height = y1.saturating_sub(self.y);
+        self.samples.clear();
+        self.samples
+            .resize((self.width * self.height * n * n) as usize, 0);
+        if self.samples.is_empty() {
+            return;
+        }
+
+        // Sample `i` of the region is at pixel coordinate `(i + 0.5) / n`.
+        let scale = n as f32;
+        let to_samples = |value: f32, origin: u32| (value - origin as f32) * scale - 0.5;
+        let samples_wide = self.width * n;
+        let samples_high = self.height * n;
+        for (index, triangle) in triangles.iter().enumerate() {
+            let mut p = [[0.0; 2]; 3];
+            for (p, point) in p.iter_mut().zip(triangle) {
+                *p = [to_samples(point[0], self.x), to_samples(point[1], self.y)];
+            }
+            let area = edge(p[0], p[1], p[2]);
+            if area == 0.0 || !area.is_finite() {
+                continue;
+            }
+            if area < 0.0 {
+                p.swap(1, 2);
+            }
+
+            let min_x = p[0][0].min(p[1][0]).min(p[2][0]).ceil().max(0.0) as u32;
+            let min_y = p[0][1].min(p[1][1]).min(p[2][1]).ceil().max(0.0) as u32;
+            let max_x = p[0][0].max(p[1][0]).max(p[2][0]).floor();
+            let max_y = p[0][1].max(p[1][1]).max(p[2][1]).floor();
+            if max_x < 0.0 || max_y < 0.0 {
+                continue;
+            }
+            let max_x = (max_x as u32).min(samples_wide - 1);
+            let max_y = (max_y as u32).min(samples_high - 1);
+
+            for sample_y in min_y..=max_y {
+                for sample_x in min_x..=max_x {
+                    let point = [sample_x as f32, sample_y as f32];
+                    // Samples on an edge belong to both triangles, so that no
+                    // gaps open up between them.
+                    if edge(p[0], p[1], point) >= 0.0
+                        && edge(p[1], p[2], point) >= 0.0
+                        && edge(p[2], p[0], point) >= 0.0
+                    {
+                        let pixel = (sample_y / n) * self.width + sample_x / n;
+                        let sample = (sample_y % n) * n + sample_x % n;
+                        self.samples[(pixel * n * n + sample) as usize] = index as u32 + 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the position of each covered pixel, and the triangle
+    /// covering each of its samples.
+    pub fn for_each_pixel(&self, mut f: impl FnMut(u32, u32, &[u32])) {
+        let samples_per_pixel = (self.samples_per_axis * self.samples_per_axis) as usize;
+        if samples_per_pixel == 0 {
+            return;
+        }
+        for (i, samples) in self.samples.chunks_exact(samples_per_pixel).enumerate() {
+            if samples.iter().any(|&sample| sample != 0) {
+                let i = i as u32;
+                f(self.x + i % self.width, self.y + i / self.width, samples);
+            }
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`.
+fn edge(a: Point, b: Point, c: Point) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(coverage: &Coverage, width: u32, height: u32) -> Vec<u32> {
+        let mut pixels = vec![0; (width * height) as usize];
+        coverage.for_each_pixel(|x, y, samples| {
+            pixels[(y * width + x) as usize] =
+                samples.iter().filter(|&&sample| sample != 0).count() as u32;
+        });
+        pixels
+    }
+
+    #[test]
+    fn shared_edges() {
+        // A square split along its diagonal covers every sample exactly once.
+        let triangles = [
+            [[1.0, 1.0], [3.0, 1.0], [3.0, 3.0]],
+            [[1.0, 1.0], [3.0, 3.0], [1.0, 3.0]],
+        ];
+        let mut result = Coverage::new();
+        result.rasterize(&triangles, Rect::new(0, 0, 4, 4), 4);
+        #[rustfmt::skip]
+        assert_eq!(
+            coverage(&result, 4, 4),
+            [
+                0, 0, 0, 0,
+                0, 16, 16, 0,
+                0, 16, 16, 0,
+                0, 0, 0, 0,
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_pixels() {
+        let triangles = [[[0.5, 0.0], [2.0, 0.0], [0.5, 4.0]]];
+        let mut result = Coverage::new();
+        result.rasterize(&triangles, Rect::new(0, 0, 1, 1), 2);
+        assert_eq!(coverage(&result, 1, 1), [2]);
+
+        // Without anti-aliasing, pixels are covered if their center is.
+        result.rasterize(&triangles, Rect::new(0, 0, 2, 1), 1);
+        assert_eq!(coverage(&result, 2, 1), [1, 1]);
+    }
+
+    #[test]
+    fn blending() {
+        let mut pixmap = Pixmap::new(1, 1, [0.0, 0.0, 1.0, 1.0]);
+        pixmap.blend(0, 0, [0.5, 0.0, 0.0, 0.5]);
+        assert_eq!(pixmap.to_rgba(), [128, 0, 128, 255]);
+
+        pixmap.fill_rect(-1, -1, 5, 5, [0.0; 4]);
+        assert_eq!(pixmap.to_rgba(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn clipping() {
+        let triangles = [[[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]];
+        let mut result = Coverage::new();
+        result.rasterize(&triangles, Rect::new(1, 0, 3, 1), 1);
+        assert_eq!(coverage(&result, 4, 1), [0, 1, 1, 0]);
+
+        let rect = Rect::new(0, 0, 2, 2);
+        assert_eq!(rect.union(Rect::default()), rect);
+        assert_eq!(rect.union(Rect::new(3, 1, 4, 4)), Rect::new(0, 0, 4, 4));
+        assert!(rect.intersection(Rect::new(2, 0, 4, 2)).is_empty());
+        assert_eq!(rect.translate(-1, 3), Rect::new(0, 3, 1, 5));
+    }
+
+    #[test]
+    fn translation() {
+        let mut pixmap = Pixmap::new(3, 2, [0.0; 4]);
+        pixmap.fill_rect(0, 0, 1, 1, [1.0; 4]);
+        let moved = pixmap.translate(2, 1);
+        assert_eq!(moved.pixels[5], [1.0; 4]);
+        assert_eq!(moved.pixels.iter().filter(|p| p[3] > 0.0).count(), 1);
+
+        // Pixels moved out of the image are dropped.
+        assert!(moved.translate(1, 0).pixels.iter().all(|p| p[3] == 0.0));
+        assert!(moved.translate(-5, 0).pixels.iter().all(|p| p[3] == 0.0));
+    }
+}
@@ -0,0 +1,854 @@
+//! A render backend that exports frames as SVG documents.
+//!
+//! Shapes stay vector paths, along with their gradients and bitmap fills, and
+//! text is drawn with the outlines of its glyphs. This is meant for archiving
+//! movies and extracting their art rather than for display: nothing is drawn
+//! to the screen, and each frame becomes a document when it ends.
+
+use ruffle_core::backend::render::swf::{
+    self, FillStyle, Gradient, GradientInterpolation, GradientSpread, LineCapStyle, LineJoinStyle,
+    Matrix,
+};
+use ruffle_core::backend::render::{
+    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Composite, CompositeShader,
+    DecodedFrame, Letterbox, RenderBackend, ShapeHandle, Transform,
+};
+use ruffle_core::color_transform::ColorTransform;
+use ruffle_core::shape_utils::{stroke_width, DistilledShape, DrawCommand, DrawPath};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+type Error = Box<dyn std::error::Error>;
+
+pub struct SvgRenderBackend {
+    shapes: Vec<ShapeDef>,
+    bitmaps: Vec<BitmapDef>,
+
+    /// The contents of the frame being rendered. Masks and render targets each
+    /// add a layer, which is wrapped in a group once it ends.
+    layers: Vec<Layer>,
+    clear: Color,
+
+    /// The filters and masks used by the frame being rendered.
+    defs: String,
+    num_defs: usize,
+
+    /// The ids of the filters for the color transforms used by the frame, keyed
+    /// by the values of their color matrices.
+    color_filters: HashMap<String, String>,
+
+    /// The shapes and bitmaps drawn by the frame, whose definitions it needs.
+    used_shapes: BTreeSet<usize>,
+    used_bitmaps: BTreeSet<usize>,
+
+    /// The last frame rendered, as an SVG document.
+    last_frame: Option<String>,
+
+    viewport_width: u32,
+    viewport_height: u32,
+}
+
+/// A shape, converted to SVG paths in twips.
+struct ShapeDef {
+    /// The gradients and patterns that the paths are filled with.
+    defs: String,
+    paths: String,
+
+    /// The bitmaps used by bitmap fills.
+    bitmaps: Vec<usize>,
+}
+
+struct BitmapDef {
+    id: swf::CharacterId,
+    width: u32,
+    height: u32,
+
+    /// The image as a PNG data URI.
+    data_uri: String,
+}
+
+struct Layer {
+    kind: LayerKind,
+    contents: String,
+}
+
+enum LayerKind {
+    Frame,
+
+    /// The contents of a mask, which haven't been activated yet.
+    Mask,
+
+    /// Contents drawn through the mask with the given id.
+    Masked(String),
+
+    RenderTarget,
+}
+
+impl SvgRenderBackend {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            shapes: vec![],
+            bitmaps: vec![],
+            layers: vec![Layer::new(LayerKind::Frame)],
+            clear: Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+            defs: String::new(),
+            num_defs: 0,
+            color_filters: HashMap::new(),
+            used_shapes: BTreeSet::new(),
+            used_bitmaps: BTreeSet::new(),
+            last_frame: None,
+            viewport_width: width,
+            viewport_height: height,
+        }
+    }
+
+    fn register_shape_internal(&self, index: usize, shape: DistilledShape) -> ShapeDef {
+        let mut defs = String::new();
+        let mut paths = String::new();
+        let mut bitmaps = vec![];
+        let mut num_fills = 0;
+        for path in &shape.paths {
+            match path {
+                DrawPath::Fill { style, commands } => {
+                    let fill_id = format!("s{}f{}", index, num_fills);
+                    let fill = match style {
+                        FillStyle::Color(color) => paint("fill", color),
+                        FillStyle::LinearGradient(gradient) => {
+                            write_gradient(
+                                &mut defs,
+                                &fill_id,
+                                "linearGradient",
+                                r#"x1="-16384" x2="16384""#,
+                                gradient,
+                            );
+                            format!(r#" fill="url(#{})""#, fill_id)
+                        }
+                        FillStyle::RadialGradient(gradient) => {
+                            write_gradient(
+                                &mut defs,
+                                &fill_id,
+                                "radialGradient",
+                                r#"cx="0" cy="0" r="16384""#,
+                                gradient,
+                            );
+                            format!(r#" fill="url(#{})""#, fill_id)
+                        }
+                        FillStyle::FocalGradient {
+                            gradient,
+                            focal_point,
+                        } => {
+                            let focal_point = focal_point.max(-1.0).min(1.0) * 16384.0;
+                            write_gradient(
+                                &mut defs,
+                                &fill_id,
+                                "radialGradient",
+                                &format!(r#"cx="0" cy="0" r="16384" fx="{}""#, focal_point),
+                                gradient,
+                            );
+                            format!(r#" fill="url(#{})""#, fill_id)
+                        }
+                        FillStyle::Bitmap {
+                            id,
+                            matrix,
+                            is_smoothed,
+                            ..
+                        } => {
+                            let bitmap = match self.bitmaps.iter().position(|b| b.id == *id) {
+                                Some(bitmap) => bitmap,
+                                None => {
+                                    log::warn!("Shape {} uses unknown bitmap {}", shape.id, id);
+                                    continue;
+                                }
+                            };
+                            // TODO: Patterns always repeat, while Flash stretches the edges
+                            // of bitmaps that don't.
+                            let _ = write!(
+                                defs,
+                                r##"<pattern id="{}" patternUnits="userSpaceOnUse" width="{}" height="{}" patternTransform="{}"><use xlink:href="#b{}"{}/></pattern>"##,
+                                fill_id,
+                                self.bitmaps[bitmap].width,
+                                self.bitmaps[bitmap].height,
+                                matrix_value(
+                                    matrix,
+                                    1.0,
+                                    matrix.tx.get() as f32,
+                                    matrix.ty.get() as f32
+                                ),
+                                bitmap,
+                                image_rendering(*is_smoothed),
+                            );
+                            bitmaps.push(bitmap);
+                            format!(r#" fill="url(#{})""#, fill_id)
+                        }
+                    };
+                    num_fills += 1;
+                    let _ = write!(
+                        paths,
+                        r#"<path d="{}"{} fill-rule="evenodd"/>"#,
+                        path_data(commands, false),
+                        fill
+                    );
+                }
+                DrawPath::Stroke {
+                    style,
+                    is_closed,
+                    commands,
+                } => {
+                    // Shapes with hairlines or non-scaling strokes are registered for
+                    // each scale they are rendered at, so the width is known here.
+                    let width = stroke_width(style, shape.scale) * 20.0;
+                    let cap = match style.start_cap {
+                        LineCapStyle::Round => "round",
+                        LineCapStyle::Square => "square",
+                        LineCapStyle::None => "butt",
+                    };
+                    let join = match style.join_style {
+                        LineJoinStyle::Round => "round".to_string(),
+                        LineJoinStyle::Bevel => "bevel".to_string(),
+                        LineJoinStyle::Miter(limit) => {
+                            format!(r#"miter" stroke-miterlimit="{}"#, limit)
+                        }
+                    };
+                    let _ = write!(
+                        paths,
+                        r#"<path d="{}" fill="none"{} stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}"/>"#,
+                        path_data(commands, *is_closed),
+                        paint("stroke", &style.color),
+                        width,
+                        cap,
+                        join
+                    );
+                }
+            }
+        }
+
+        ShapeDef {
+            defs,
+            paths,
+            bitmaps,
+        }
+    }
+
+    fn register_bitmap(
+        &mut self,
+        id: swf::CharacterId,
+        bitmap: Bitmap,
+    ) -> Result<BitmapInfo, Error> {
+        let handle = BitmapHandle(self.bitmaps.len());
+        let (width, height) = (bitmap.width, bitmap.height);
+        self.bitmaps.push(BitmapDef {
+            id,
+            width,
+            height,
+            data_uri: bitmap_to_png_data_uri(bitmap)?,
+        });
+
+        Ok(BitmapInfo {
+            handle,
+            width: width as u16,
+            height: height as u16,
+        })
+    }
+
+    /// The layer that draw calls currently go to.
+    fn layer(&mut self) -> &mut String {
+        &mut self.layers.last_mut().unwrap().contents
+    }
+
+    /// Returns a new id for an element of the frame's definitions.
+    fn next_def_id(&mut self, prefix: &str) -> String {
+        self.num_defs += 1;
+        format!("{}{}", prefix, self.num_defs)
+    }
+
+    /// The `filter` attribute that applies a color transform, if it does anything.
+    fn color_filter(&mut self, color_transform: &ColorTransform) -> String {
+        if color_transform.is_identity() {
+            return String::new();
+        }
+
+        let ct = color_transform;
+        let values = format!(
+            "{} 0 0 0 {} 0 {} 0 0 {} 0 0 {} 0 {} 0 0 0 {} {}",
+            ct.r_mult, ct.r_add, ct.g_mult, ct.g_add, ct.b_mult, ct.b_add, ct.a_mult, ct.a_add
+        );
+        let id = match self.color_filters.get(&values) {
+            Some(id) => id.clone(),
+            None => {
+                let id = self.next_def_id("c");
+                let _ = write!(
+                    self.defs,
+                    r#"<filter id="{}" color-interpolation-filters="sRGB"><feColorMatrix type="matrix" values="{}"/></filter>"#,
+                    id, values
+                );
+                self.color_filters.insert(values, id.clone());
+                id
+            }
+        };
+        format!(r#" filter="url(#{})""#, id)
+    }
+
+    fn draw_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = write!(
+            self.layer(),
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#000000"/>"##,
+            x,
+            y,
+            width,
+            height
+        );
+    }
+
+    /// Ends the top layer, and returns it unless it is the frame itself.
+    fn pop_layer(&mut self) -> Option<Layer> {
+        if self.layers.len() > 1 {
+            self.layers.pop()
+        } else {
+            None
+        }
+    }
+
+    /// The filter region covering the whole viewport, for filters of render targets.
+    fn viewport_region(&self) -> String {
+        format!(
+            r#"filterUnits="userSpaceOnUse" x="0" y="0" width="{}" height="{}""#,
+            self.viewport_width, self.viewport_height
+        )
+    }
+}
+
+impl RenderBackend for SvgRenderBackend {
+    fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+        self.viewport_width = width;
+        self.viewport_height = height;
+    }
+
+    fn register_shape(&mut self, shape: DistilledShape) -> ShapeHandle {
+        let handle = ShapeHandle(self.shapes.len());
+        let shape = self.register_shape_internal(handle.0, shape);
+        self.shapes.push(shape);
+        handle
+    }
+
+    fn replace_shape(&mut self, shape: DistilledShape, handle: ShapeHandle) {
+        self.shapes[handle.0] = self.register_shape_internal(handle.0, shape);
+    }
+
+    fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
+        let shape = swf::Shape {
+            version: 2,
+            id: 0,
+            shape_bounds: Default::default(),
+            edge_bounds: Default::default(),
+            has_fill_winding_rule: false,
+            has_non_scaling_strokes: false,
+            has_scaling_strokes: true,
+            styles: swf::ShapeStyles {
+                fill_styles: vec![FillStyle::Color(Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                })],
+                line_styles: vec![],
+            },
+            shape: glyph.shape_records.clone(),
+        };
+        self.register_shape((&shape).into())
+    }
+
+    fn register_bitmap_jpeg(
+        &mut self,
+        id: swf::CharacterId,
+        data: &[u8],
+        jpeg_tables: Option<&[u8]>,
+    ) -> Result<BitmapInfo, Error> {
+        let data = ruffle_core::backend::render::glue_tables_to_jpeg(data, jpeg_tables);
+        self.register_bitmap_jpeg_2(id, &data[..])
+    }
+
+    fn register_bitmap_jpeg_2(
+        &mut self,
+        id: swf::CharacterId,
+        data: &[u8],
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap = ruffle_core::backend::render::decode_define_bits_jpeg(data, None)?;
+        self.register_bitmap(id, bitmap)
+    }
+
+    fn register_bitmap_jpeg_3(
+        &mut self,
+        id: swf::CharacterId,
+        jpeg_data: &[u8],
+        alpha_data: &[u8],
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap =
+            ruffle_core::backend::render::decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
+        self.register_bitmap(id, bitmap)
+    }
+
+    fn register_bitmap_png(
+        &mut self,
+        swf_tag: &swf::DefineBitsLossless,
+    ) -> Result<BitmapInfo, Error> {
+        let bitmap = ruffle_core::backend::render::decode_define_bits_lossless(swf_tag)?;
+        self.register_bitmap(swf_tag.id, bitmap)
+    }
+
+    fn begin_frame(&mut self, clear: Color) {
+        self.layers = vec![Layer::new(LayerKind::Frame)];
+        self.clear = clear;
+        self.defs.clear();
+        self.num_defs = 0;
+        self.color_filters.clear();
+        self.used_shapes.clear();
+        self.used_bitmaps.clear();
+    }
+
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
+        if bitmap.0 >= self.bitmaps.len() {
+            return;
+        }
+        self.used_bitmaps.insert(bitmap.0);
+        let filter = self.color_filter(&transform.color_transform);
+        let _ = write!(
+            self.layer(),
+            r##"<use xlink:href="#b{}" transform="{}"{}{}/>"##,
+            bitmap.0,
+            transform_value(&transform.matrix, 1.0),
+            filter,
+            image_rendering(smoothing)
+        );
+    }
+
+    fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+        self.used_shapes.insert(shape.0);
+        let filter = self.color_filter(&transform.color_transform);
+        let _ = write!(
+            self.layer(),
+            r##"<use xlink:href="#s{}" transform="{}"{}/>"##,
+            shape.0,
+            transform_value(&transform.matrix, 1.0 / 20.0),
+            filter
+        );
+    }
+
+    fn end_frame(&mut self) {
+        // Close any masks and render targets that were left open.
+        while self.layers.len() > 1 {
+            self.pop_mask();
+        }
+
+        let (width, height) = (self.viewport_width, self.viewport_height);
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{0}" height="{1}" viewBox="0 0 {0} {1}"><defs>"#,
+            width, height
+        );
+        let mut bitmaps = self.used_bitmaps.clone();
+        for &shape in &self.used_shapes {
+            bitmaps.extend(&self.shapes[shape].bitmaps);
+        }
+        for bitmap in bitmaps {
+            let bitmap_def = &self.bitmaps[bitmap];
+            let _ = write!(
+                svg,
+                r#"<image id="b{}" width="{}" height="{}" xlink:href="{}"/>"#,
+                bitmap, bitmap_def.width, bitmap_def.height, bitmap_def.data_uri
+            );
+        }
+        for &shape in &self.used_shapes {
+            let shape_def = &self.shapes[shape];
+            let _ = write!(
+                svg,
+                r#"{}<g id="s{}">{}</g>"#,
+                shape_def.defs, shape, shape_def.paths
+            );
+        }
+        svg.push_str(&self.defs);
+        svg.push_str("</defs>");
+        if self.clear.a > 0 {
+            let _ = write!(
+                svg,
+                r#"<rect width="{}" height="{}"{}/>"#,
+                width,
+                height,
+                paint("fill", &self.clear)
+            );
+        }
+        svg.push_str(&self.layers[0].contents);
+        svg.push_str("</svg>");
+        self.last_frame = Some(svg);
+    }
+
+    fn draw_letterbox(&mut self, letterbox: Letterbox) {
+        let (width, height) = (self.viewport_width as f32, self.viewport_height as f32);
+        match letterbox {
+            Letterbox::None => (),
+            Letterbox::Letterbox { top, bottom } => {
+                self.draw_rect(0.0, 0.0, width, top);
+                self.draw_rect(0.0, height - bottom, width, bottom);
+            }
+            Letterbox::Pillarbox { left, right } => {
+                self.draw_rect(0.0, 0.0, left, height);
+                self.draw_rect(width - right, 0.0, right, height);
+            }
+        }
+    }
+
+    fn push_mask(&mut self) {
+        self.layers.push(Layer::new(LayerKind::Mask));
+    }
+
+    fn activate_mask(&mut self) {
+        let mask = match self.pop_layer() {
+            Some(mask) => mask,
+            None => return,
+        };
+        let id = self.next_def_id("m");
+        // Only the shape of the mask matters, and not its colors.
+        let _ = write!(
+            self.defs,
+            r#"<mask id="{}" maskUnits="userSpaceOnUse" x="0" y="0" width="{}" height="{}" style="mask-type:alpha">{}</mask>"#,
+            id, self.viewport_width, self.viewport_height, mask.contents
+        );
+        self.layers.push(Layer::new(LayerKind::Masked(id)));
+    }
+
+    fn pop_mask(&mut self) {
+        let layer = match self.pop_layer() {
+            Some(layer) => layer,
+            None => return,
+        };
+        let group = match layer.kind {
+            LayerKind::Masked(id) => format!(r#"<g mask="url(#{})">{}</g>"#, id, layer.contents),
+            LayerKind::RenderTarget => layer.contents,
+            LayerKind::Frame | LayerKind::Mask => return,
+        };
+        self.layer().push_str(&group);
+    }
+
+    fn export_svg(&mut self) -> Option<String> {
+        self.last_frame.clone()
+    }
+
+    fn push_render_target(&mut self) -> bool {
+        self.layers.push(Layer::new(LayerKind::RenderTarget));
+        true
+    }
+
+    fn pop_and_composite(&mut self, composite: &Composite) {
+        let layer = match self.pop_layer() {
+            Some(layer) => layer,
+            None => return,
+        };
+
+        let mut attributes = String::new();
+        if let Some(shader) = &composite.shader {
+            let effect = match shader {
+                CompositeShader::ColorMatrix(matrix) => {
+                    let values: Vec<String> = matrix.iter().map(|v| v.to_string()).collect();
+                    format!(
+                        r#"<feColorMatrix type="matrix" values="{}"/>"#,
+                        values.join(" ")
+                    )
+                }
+                CompositeShader::Blur {
+                    blur_x,
+                    blur_y,
+                    passes,
+                } => {
+                    // Repeated box blurs add up to about a gaussian blur with the same variance.
+                    let deviation =
+                        |size: f32| (*passes as f32 * (size * size - 1.0).max(0.0) / 12.0).sqrt();
+                    format!(
+                        r#"<feGaussianBlur stdDeviation="{} {}"/>"#,
+                        deviation(*blur_x),
+                        deviation(*blur_y)
+                    )
+                }
+            };
+            let id = self.next_def_id("e");
+            let region = self.viewport_region();
+            let _ = write!(
+                self.defs,
+                r#"<filter id="{}" {} color-interpolation-filters="sRGB">{}</filter>"#,
+                id, region, effect
+            );
+            let _ = write!(attributes, r#" filter="url(#{})""#, id);
+        }
+        if let Some(blend_mode) = mix_blend_mode(composite.blend_mode) {
+            let _ = write!(attributes, r#" style="mix-blend-mode:{}""#, blend_mode);
+        }
+
+        let group = format!("<g{}>{}</g>", attributes, layer.contents);
+        self.layer().push_str(&group);
+    }
+
+    fn register_video_stream(
+        &mut self,
+        id: swf::CharacterId,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        let bitmap = Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![0; width as usize * height as usize * 4]),
+        };
+        Ok(self.register_bitmap(id, bitmap)?.handle)
+    }
+
+    fn upload_video_frame(
+        &mut self,
+        handle: BitmapHandle,
+        frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        let bitmap_def = self
+            .bitmaps
+            .get_mut(handle.0)
+            .ok_or("Unknown video stream")?;
+        let data = frame.to_rgba(bitmap_def.width, bitmap_def.height);
+        if data.len() != bitmap_def.width as usize * bitmap_def.height as usize * 4 {
+            return Err("Video frame size differs from its stream".into());
+        }
+        bitmap_def.data_uri = bitmap_to_png_data_uri(Bitmap {
+            width: bitmap_def.width,
+            height: bitmap_def.height,
+            data: BitmapFormat::Rgba(data),
+        })?;
+        Ok(())
+    }
+}
+
+impl Layer {
+    fn new(kind: LayerKind) -> Self {
+        Self {
+            kind,
+            contents: String::new(),
+        }
+    }
+}
+
+/// The attributes that paint a fill or stroke with a solid color.
+fn paint(attribute: &str, color: &Color) -> String {
+    let mut paint = format!(r#" {}="{}""#, attribute, hex_color(color));
+    if color.a < 255 {
+        let _ = write!(
+            paint,
+            r#" {}-opacity="{}""#,
+            attribute,
+            f32::from(color.a) / 255.0
+        );
+    }
+    paint
+}
+
+fn hex_color(color: &Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// A `transform` attribute value for a matrix, for content whose units are
+/// `scale` pixels.
+fn transform_value(matrix: &Matrix, scale: f32) -> String {
+    matrix_value(
+        matrix,
+        scale,
+        matrix.tx.to_pixels() as f32,
+        matrix.ty.to_pixels() as f32,
+    )
+}
+
+fn matrix_value(matrix: &Matrix, scale: f32, tx: f32, ty: f32) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        matrix.a * scale,
+        matrix.b * scale,
+        matrix.c * scale,
+        matrix.d * scale,
+        tx,
+        ty
+    )
+}
+
+fn image_rendering(smoothing: bool) -> &'static str {
+    if smoothing {
+        ""
+    } else {
+        r#" image-rendering="optimizeSpeed""#
+    }
+}
+
+fn path_data(commands: &[DrawCommand], is_closed: bool) -> String {
+    let mut data = String::new();
+    for command in commands {
+        let _ = match command {
+            DrawCommand::MoveTo { x, y } => write!(data, "M{} {}", x.get(), y.get()),
+            DrawCommand::LineTo { x, y } => write!(data, "L{} {}", x.get(), y.get()),
+            DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                write!(data, "Q{} {} {} {}", x1.get(), y1.get(), x2.get(), y2.get())
+            }
+        };
+    }
+    if is_closed {
+        data.push('Z');
+    }
+    data
+}
+
+/// Defines a gradient in the gradient square of Flash, from -16384 to 16384 twips.
+fn write_gradient(defs: &mut String, id: &str, element: &str, geometry: &str, gradient: &Gradient) {
+    let matrix = &gradient.matrix;
+    let _ = write!(
+        defs,
+        r#"<{} id="{}" gradientUnits="userSpaceOnUse" {} gradientTransform="{}""#,
+        element,
+        id,
+        geometry,
+        matrix_value(matrix, 1.0, matrix.tx.get() as f32, matrix.ty.get() as f32)
+    );
+    match gradient.spread {
+        GradientSpread::Pad => (),
+        GradientSpread::Reflect => defs.push_str(r#" spreadMethod="reflect""#),
+        GradientSpread::Repeat => defs.push_str(r#" spreadMethod="repeat""#),
+    }
+    if gradient.interpolation == GradientInterpolation::LinearRGB {
+        defs.push_str(r#" color-interpolation="linearRGB""#);
+    }
+    defs.push('>');
+    for record in &gradient.records {
+        let _ = write!(
+            defs,
+            r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+            f32::from(record.ratio) / 255.0,
+            hex_color(&record.color),
+            f32::from(record.color.a) / 255.0
+        );
+    }
+    let _ = write!(defs, "</{}>", element);
+}
+
+/// The CSS blend mode of a Flash blend mode, or `None` to draw normally.
+/// The modes that CSS has no equivalent for are drawn normally too.
+fn mix_blend_mode(blend_mode: swf::BlendMode) -> Option<&'static str> {
+    use swf::BlendMode;
+    match blend_mode {
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Lighten => Some("lighten"),
+        BlendMode::Darken => Some("darken"),
+        BlendMode::Difference => Some("difference"),
+        BlendMode::Add => Some("plus-lighter"),
+        BlendMode::Overlay => Some("overlay"),
+        BlendMode::HardLight => Some("hard-light"),
+        _ => None,
+    }
+}
+
+fn bitmap_to_png_data_uri(bitmap: Bitmap) -> Result<String, Error> {
+    let mut png_data: Vec<u8> = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png_data, bitmap.width, bitmap.height);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data = match bitmap.data {
+            BitmapFormat::Rgba(mut data) => {
+                ruffle_core::backend::render::unmultiply_alpha_rgba(&mut data[..]);
+                encoder.set_color(png::ColorType::RGBA);
+                data
+            }
+            BitmapFormat::Rgb(data) => {
+                encoder.set_color(png::ColorType::RGB);
+                data
+            }
+        };
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+    }
+
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::encode(&png_data[..])
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::backend::render::swf::{Shape, ShapeRecord, ShapeStyles, Twips};
+
+    #[test]
+    fn export_frame() {
+        let mut renderer = SvgRenderBackend::new(100, 50);
+        let shape = Shape {
+            version: 1,
+            id: 1,
+            shape_bounds: Default::default(),
+            edge_bounds: Default::default(),
+            has_fill_winding_rule: false,
+            has_non_scaling_strokes: false,
+            has_scaling_strokes: true,
+            styles: ShapeStyles {
+                fill_styles: vec![FillStyle::Color(Color {
+                    r: 255,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                })],
+                line_styles: vec![],
+            },
+            shape: vec![
+                ShapeRecord::StyleChange(swf::StyleChangeData {
+                    move_to: Some((Twips::new(0), Twips::new(0))),
+                    fill_style_0: None,
+                    fill_style_1: Some(1),
+                    line_style: None,
+                    new_styles: None,
+                }),
+                ShapeRecord::StraightEdge {
+                    delta_x: Twips::new(200),
+                    delta_y: Twips::new(0),
+                },
+                ShapeRecord::StraightEdge {
+                    delta_x: Twips::new(0),
+                    delta_y: Twips::new(200),
+                },
+                ShapeRecord::StraightEdge {
+                    delta_x: Twips::new(-200),
+                    delta_y: Twips::new(-200),
+                },
+            ],
+        };
+        let unused = renderer.register_shape((&shape).into());
+        let shape = renderer.register_shape((&shape).into());
+
+        let mut transform = Transform {
+            matrix: Matrix::translate(Twips::from_pixels(10.0), Twips::from_pixels(5.0)),
+            ..Default::default()
+        };
+        transform.color_transform.a_mult = 0.5;
+        renderer.begin_frame(Color {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        });
+        renderer.push_mask();
+        renderer.render_shape(shape, &Default::default());
+        renderer.activate_mask();
+        renderer.render_shape(shape, &transform);
+        renderer.pop_mask();
+        renderer.end_frame();
+
+        let svg = renderer.export_svg().unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(r##"<g id="s1"><path d="M0 0L200 0L200 200L0 0" fill="#ff0000""##));
+        assert!(!svg.contains(&format!(r#"id="s{}""#, unused.0)));
+        assert!(svg.contains(r##"<rect width="100" height="50" fill="#ffffff"/>"##));
+        assert!(svg.contains(r##"<g mask="url(#m1)"><use xlink:href="#s1" transform="matrix(0.05 0 0 0.05 10 5)" filter="url(#c2)"/></g>"##));
+    }
+}
@@ -8,8 +8,8 @@ uniform vec4 add_color;
 uniform mat3 u_matrix;
 
 uniform int u_gradient_type;
-uniform float u_ratios[8];
-uniform vec4 u_colors[8];
+uniform float u_ratios[16];
+uniform vec4 u_colors[16];
 uniform int u_num_colors;
 uniform int u_repeat_mode;
 uniform float u_focal_point;
@@ -40,8 +40,12 @@ void main() {
         vec2 uv = frag_uv * 2.0 - 1.0;
         vec2 d = vec2(u_focal_point, 0.0) - uv;
         float l = length(d);
-        d /= l;
-        t = l / (sqrt(1.0 -  u_focal_point*u_focal_point*d.y*d.y) + u_focal_point*d.x);
+        if( l > 0.0 ) {
+            d /= l;
+            t = l / (sqrt(1.0 -  u_focal_point*u_focal_point*d.y*d.y) + u_focal_point*d.x);
+        } else {
+            t = 0.0;
+        }
     }
     if( u_repeat_mode == 0 )
     {
@@ -68,41 +72,22 @@ void main() {
         }
     }
 
-    // TODO: No non-constant array access in WebGL 1, so the following is kind of painful.
-    // We'd probably be better off passing in the gradient as a texture and sampling from there.
-    vec4 color;
-    float a;
-    if( t <= u_ratios[0] ) {
-        color = u_colors[0];
-    } else if( t <= u_ratios[1] ) {    
-        a = (t - u_ratios[0]) / (u_ratios[1] - u_ratios[0]);
-        color = mix(u_colors[0], u_colors[1], a);
-    } else if( t <= u_ratios[2] ) {    
-        a = (t - u_ratios[1]) / (u_ratios[2] - u_ratios[1]);
-        color = mix(u_colors[1], u_colors[2], a);
-    } else if( t <= u_ratios[3] ) {    
-        a = (t - u_ratios[2]) / (u_ratios[3] - u_ratios[2]);
-        color = mix(u_colors[2], u_colors[3], a);
-    } else if( t <= u_ratios[4] ) {    
-        a = (t - u_ratios[3]) / (u_ratios[4] - u_ratios[3]);
-        color = mix(u_colors[3], u_colors[4], a);
-    } else if( t <= u_ratios[5] ) {    
-        a = (t - u_ratios[4]) / (u_ratios[5] - u_ratios[4]);
-        color = mix(u_colors[4], u_colors[5], a);
-    } else if( t <= u_ratios[6] ) {    
-        a = (t - u_ratios[5]) / (u_ratios[6] - u_ratios[5]);
-        color = mix(u_colors[5], u_colors[6], a);
-    } else if( t <= u_ratios[7] ) {
-        a = (t - u_ratios[6]) / (u_ratios[7] - u_ratios[6]);
-        color = mix(u_colors[6], u_colors[7], a);
-    } else {
-        color = u_colors[7];
+    // WebGL 1 only allows indexing arrays with loop indices, so walk the
+    // ratios with a constant bound. Unused entries repeat the last color.
+    vec4 color = u_colors[0];
+    for( int i = 1; i < 16; i++ ) {
+        if( t <= u_ratios[i - 1] ) {
+            break;
+        }
+        float range = u_ratios[i] - u_ratios[i - 1];
+        float a = range > 0.0 ? clamp((t - u_ratios[i - 1]) / range, 0.0, 1.0) : 1.0;
+        color = mix(u_colors[i - 1], u_colors[i], a);
     }
 
     if( u_interpolation != 0 ) {
         color = vec4(linear_to_srgb(vec3(color)), color.a);
     }
 
-    gl_FragColor = mult_color * color + add_color;;
+    gl_FragColor = mult_color * color + add_color;
 }
 
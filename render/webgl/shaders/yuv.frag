@@ -0,0 +1,25 @@
+#version 100
+precision mediump float;
+
+uniform mat4 view_matrix;
+uniform mat4 world_matrix;
+uniform vec4 mult_color;
+uniform vec4 add_color;
+uniform mat3 u_matrix;
+
+// The luma plane, and the two chroma planes at half size.
+uniform sampler2D u_texture;
+uniform sampler2D u_texture_u;
+uniform sampler2D u_texture_v;
+
+varying vec2 frag_uv;
+
+void main() {
+    // Limited range BT.601, as decoded from H.263 and VP6.
+    float y = (texture2D(u_texture, frag_uv).r - 16.0 / 255.0) * (255.0 / 219.0);
+    float u = (texture2D(u_texture_u, frag_uv).r - 128.0 / 255.0) * (255.0 / 224.0);
+    float v = (texture2D(u_texture_v, frag_uv).r - 128.0 / 255.0) * (255.0 / 224.0);
+
+    vec3 rgb = vec3(y + 1.402 * v, y - 0.344136 * u - 0.714136 * v, y + 1.772 * u);
+    gl_FragColor = vec4(clamp(rgb, 0.0, 1.0), 1.0);
+}
\ No newline at end of file
@@ -1,9 +1,10 @@
 use ruffle_core::backend::render::swf::{self, FillStyle};
 use ruffle_core::backend::render::{
-    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox,
+    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, DecodedFrame, Letterbox,
     RenderBackend, ShapeHandle, Transform,
 };
 use ruffle_core::shape_utils::DistilledShape;
+use ruffle_core::stage::StageQuality;
 use ruffle_render_common_tess::{GradientSpread, GradientType, ShapeTessellator, Vertex};
 use ruffle_web_common::JsResult;
 use wasm_bindgen::{JsCast, JsValue};
@@ -20,8 +21,12 @@ const COLOR_FRAGMENT_GLSL: &str = include_str!("../shaders/color.frag");
 const TEXTURE_VERTEX_GLSL: &str = include_str!("../shaders/texture.vert");
 const GRADIENT_FRAGMENT_GLSL: &str = include_str!("../shaders/gradient.frag");
 const BITMAP_FRAGMENT_GLSL: &str = include_str!("../shaders/bitmap.frag");
+const YUV_FRAGMENT_GLSL: &str = include_str!("../shaders/yuv.frag");
 const NUM_VERTEX_ATTRIBUTES: u32 = 2;
 
+/// The number of colors the gradient shader takes. SWFs have at most 15.
+const MAX_GRADIENT_COLORS: usize = 16;
+
 pub struct WebGlRenderBackend {
     /// WebGL1 context
     gl: Gl,
@@ -34,11 +39,16 @@ pub struct WebGlRenderBackend {
 
     // The frame buffers used for resolving MSAA.
     msaa_buffers: Option<MsaaBuffers>,
-    msaa_sample_count: u32,
+
+    /// The most MSAA samples this device should use.
+    max_msaa_sample_count: u32,
+
+    quality: StageQuality,
 
     color_program: ShaderProgram,
     bitmap_program: ShaderProgram,
     gradient_program: ShaderProgram,
+    yuv_program: ShaderProgram,
 
     shape_tessellator: ShapeTessellator,
 
@@ -66,11 +76,13 @@ pub struct WebGlRenderBackend {
 }
 
 impl WebGlRenderBackend {
-    pub fn new(canvas: &HtmlCanvasElement) -> Result<Self, Error> {
+    /// Creates a renderer for the given canvas. With `is_transparent` set, the canvas
+    /// has an alpha channel, so that the page shows through a transparent background.
+    pub fn new(canvas: &HtmlCanvasElement, is_transparent: bool) -> Result<Self, Error> {
         // Create WebGL context.
         let options = [
             ("stencil", JsValue::TRUE),
-            ("alpha", JsValue::FALSE),
+            ("alpha", JsValue::from_bool(is_transparent)),
             ("antialias", JsValue::FALSE),
             ("depth", JsValue::FALSE),
         ];
@@ -144,10 +156,12 @@ impl WebGlRenderBackend {
         let bitmap_fragment = Self::compile_shader(&gl, Gl::FRAGMENT_SHADER, BITMAP_FRAGMENT_GLSL)?;
         let gradient_fragment =
             Self::compile_shader(&gl, Gl::FRAGMENT_SHADER, GRADIENT_FRAGMENT_GLSL)?;
+        let yuv_fragment = Self::compile_shader(&gl, Gl::FRAGMENT_SHADER, YUV_FRAGMENT_GLSL)?;
 
         let color_program = ShaderProgram::new(&gl, &color_vertex, &color_fragment)?;
         let bitmap_program = ShaderProgram::new(&gl, &texture_vertex, &bitmap_fragment)?;
         let gradient_program = ShaderProgram::new(&gl, &texture_vertex, &gradient_fragment)?;
+        let yuv_program = ShaderProgram::new(&gl, &texture_vertex, &yuv_fragment)?;
 
         gl.enable(Gl::BLEND);
         gl.blend_func(Gl::SRC_ALPHA, Gl::ONE_MINUS_SRC_ALPHA);
@@ -161,11 +175,13 @@ impl WebGlRenderBackend {
             vao_ext,
 
             msaa_buffers: None,
-            msaa_sample_count,
+            max_msaa_sample_count: msaa_sample_count,
+            quality: StageQuality::default(),
 
             color_program,
             gradient_program,
             bitmap_program,
+            yuv_program,
 
             shape_tessellator: ShapeTessellator::new(),
 
@@ -306,16 +322,8 @@ impl WebGlRenderBackend {
     }
 
     fn build_msaa_buffers(&mut self) -> Result<(), Error> {
-        if self.gl2.is_none() || self.msaa_sample_count <= 1 {
-            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
-            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
-            return Ok(());
-        }
-
-        let gl = self.gl2.as_ref().unwrap();
-
         // Delete previous buffers, if they exist.
-        if let Some(msaa_buffers) = self.msaa_buffers.take() {
+        if let (Some(gl), Some(msaa_buffers)) = (&self.gl2, self.msaa_buffers.take()) {
             gl.delete_renderbuffer(Some(&msaa_buffers.color_renderbuffer));
             gl.delete_renderbuffer(Some(&msaa_buffers.stencil_renderbuffer));
             gl.delete_framebuffer(Some(&msaa_buffers.render_framebuffer));
@@ -323,6 +331,20 @@ impl WebGlRenderBackend {
             gl.delete_texture(Some(&msaa_buffers.framebuffer_texture));
         }
 
+        // MSAA takes far fewer samples than Flash's grid, so lower qualities
+        // use one sample for each row of the grid.
+        let msaa_sample_count = self
+            .quality
+            .samples_per_axis()
+            .min(self.max_msaa_sample_count);
+        if self.gl2.is_none() || msaa_sample_count <= 1 {
+            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
+            return Ok(());
+        }
+
+        let gl = self.gl2.as_ref().unwrap();
+
         // Create frame and render buffers.
         let render_framebuffer = gl
             .create_framebuffer()
@@ -341,7 +363,7 @@ impl WebGlRenderBackend {
         gl.bind_renderbuffer(Gl2::RENDERBUFFER, Some(&color_renderbuffer));
         gl.renderbuffer_storage_multisample(
             Gl2::RENDERBUFFER,
-            4,
+            msaa_sample_count as i32,
             Gl2::RGB8,
             self.viewport_width as i32,
             self.viewport_height as i32,
@@ -353,7 +375,7 @@ impl WebGlRenderBackend {
         gl.bind_renderbuffer(Gl2::RENDERBUFFER, Some(&stencil_renderbuffer));
         gl.renderbuffer_storage_multisample(
             Gl2::RENDERBUFFER,
-            4,
+            msaa_sample_count as i32,
             Gl2::STENCIL_INDEX8,
             self.viewport_width as i32,
             self.viewport_height as i32,
@@ -478,9 +500,9 @@ impl WebGlRenderBackend {
                     },
                 ),
                 TessDrawType::Gradient(gradient) => {
-                    let mut ratios = [0.0; 8];
-                    let mut colors = [[0.0; 4]; 8];
-                    let num_colors = gradient.num_colors as usize;
+                    let mut ratios = [0.0; MAX_GRADIENT_COLORS];
+                    let mut colors = [[0.0; 4]; MAX_GRADIENT_COLORS];
+                    let num_colors = (gradient.num_colors as usize).min(MAX_GRADIENT_COLORS);
                     ratios[..num_colors].copy_from_slice(&gradient.ratios[..num_colors]);
                     colors[..num_colors].copy_from_slice(&gradient.colors[..num_colors]);
                     // Convert to linear color space if this is a linear-interpolated gradient.
@@ -489,7 +511,7 @@ impl WebGlRenderBackend {
                             *color = srgb_to_linear(*color);
                         }
                     }
-                    for i in num_colors..8 {
+                    for i in num_colors.max(1)..MAX_GRADIENT_COLORS {
                         ratios[i] = ratios[i - 1];
                         colors[i] = colors[i - 1];
                     }
@@ -502,7 +524,7 @@ impl WebGlRenderBackend {
                         },
                         ratios,
                         colors,
-                        num_colors: gradient.num_colors,
+                        num_colors: num_colors as u32,
                         repeat_mode: match gradient.repeat_mode {
                             GradientSpread::Pad => 0,
                             GradientSpread::Repeat => 1,
@@ -708,6 +730,121 @@ impl WebGlRenderBackend {
             height: bitmap.height as u16,
         })
     }
+
+    /// Converts a YUV 4:2:0 frame to RGB on the GPU, by drawing its planes into the
+    /// texture of a video stream.
+    fn convert_yuv(&mut self, index: usize, planes: [&[u8]; 3]) -> Result<(), Error> {
+        let texture = &self.textures[index].1;
+        let (width, height) = (texture.width, texture.height);
+        let chroma_size = ((width + 1) / 2, (height + 1) / 2);
+        let sizes = [(width, height), chroma_size, chroma_size];
+
+        // Upload each plane as a single-channel texture.
+        let mut plane_textures = vec![];
+        for (i, (plane, &(width, height))) in planes.iter().zip(&sizes).enumerate() {
+            if plane.len() < width as usize * height as usize {
+                return Err("Video frame size differs from its stream".into());
+            }
+            let plane_texture = self.gl.create_texture().unwrap();
+            self.gl.active_texture(Gl::TEXTURE0 + i as u32);
+            self.gl.bind_texture(Gl::TEXTURE_2D, Some(&plane_texture));
+            self.gl
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                    Gl::TEXTURE_2D,
+                    0,
+                    Gl::LUMINANCE as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    Gl::LUMINANCE,
+                    Gl::UNSIGNED_BYTE,
+                    Some(&plane[..]),
+                )
+                .into_js_result()?;
+            for &(parameter, value) in &[
+                (Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE),
+                (Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE),
+                (Gl::TEXTURE_MIN_FILTER, Gl::LINEAR),
+                (Gl::TEXTURE_MAG_FILTER, Gl::LINEAR),
+            ] {
+                self.gl
+                    .tex_parameteri(Gl::TEXTURE_2D, parameter, value as i32);
+            }
+            plane_textures.push(plane_texture);
+        }
+
+        // Draw a quad covering the whole texture of the stream.
+        let framebuffer = self.gl.create_framebuffer().unwrap();
+        self.gl
+            .bind_framebuffer(Gl::FRAMEBUFFER, Some(&framebuffer));
+        self.gl.framebuffer_texture_2d(
+            Gl::FRAMEBUFFER,
+            Gl::COLOR_ATTACHMENT0,
+            Gl::TEXTURE_2D,
+            Some(&texture.texture),
+            0,
+        );
+        self.gl.viewport(0, 0, width as i32, height as i32);
+        self.gl.disable(Gl::BLEND);
+        self.gl.disable(Gl::STENCIL_TEST);
+        self.gl.color_mask(true, true, true, true);
+
+        let program = &self.yuv_program;
+        self.gl.use_program(Some(&program.program));
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        // Maps the unit quad to the whole framebuffer.
+        let view_matrix = [
+            [2.0, 0.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-1.0, -1.0, 0.0, 1.0],
+        ];
+        program.uniform_matrix4fv(&self.gl, ShaderUniform::ViewMatrix, &view_matrix);
+        program.uniform_matrix4fv(&self.gl, ShaderUniform::WorldMatrix, &identity);
+        program.uniform_matrix3fv(
+            &self.gl,
+            ShaderUniform::TextureMatrix,
+            &[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        );
+        program.uniform1i(&self.gl, ShaderUniform::BitmapTexture, 0);
+        program.uniform1i(&self.gl, ShaderUniform::YuvTextureU, 1);
+        program.uniform1i(&self.gl, ShaderUniform::YuvTextureV, 2);
+
+        let quad = &self.meshes[self.quad_shape.0].draws[0];
+        self.bind_vertex_array(Some(&quad.vao));
+        self.gl
+            .draw_elements_with_i32(Gl::TRIANGLES, quad.num_indices, Gl::UNSIGNED_SHORT, 0);
+
+        // Restore the state used to render frames, as this may happen in the middle of one.
+        for plane_texture in &plane_textures {
+            self.gl.delete_texture(Some(plane_texture));
+        }
+        self.gl.delete_framebuffer(Some(&framebuffer));
+        self.gl.active_texture(Gl::TEXTURE0);
+        self.gl.enable(Gl::BLEND);
+        self.gl.blend_func(self.blend_func.0, self.blend_func.1);
+        self.gl.viewport(
+            0,
+            0,
+            self.viewport_width as i32,
+            self.viewport_height as i32,
+        );
+        let framebuffer = self
+            .msaa_buffers
+            .as_ref()
+            .map(|msaa_buffers| &msaa_buffers.render_framebuffer);
+        self.gl.bind_framebuffer(Gl::FRAMEBUFFER, framebuffer);
+        self.active_program = std::ptr::null();
+        self.mask_state_dirty = true;
+        self.mult_color = None;
+        self.add_color = None;
+        Ok(())
+    }
 }
 
 impl RenderBackend for WebGlRenderBackend {
@@ -902,7 +1039,7 @@ impl RenderBackend for WebGlRenderBackend {
         }
     }
 
-    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform) {
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
         // TODO: Might be better to make this separate code to render the bitmap
         // instead of going through render_shape. But render_shape already handles
         // masking etc.
@@ -912,8 +1049,14 @@ impl RenderBackend for WebGlRenderBackend {
             let draw = &mut mesh.draws[0];
             let width = bitmap.width as f32;
             let height = bitmap.height as f32;
-            if let DrawType::Bitmap(BitmapDraw { id: draw_id, .. }) = &mut draw.draw_type {
+            if let DrawType::Bitmap(BitmapDraw {
+                id: draw_id,
+                is_smoothed,
+                ..
+            }) = &mut draw.draw_type
+            {
                 *draw_id = *id;
+                *is_smoothed = smoothing;
             }
 
             // Scale the quad to the bitmap's dimensions.
@@ -1020,8 +1163,12 @@ impl RenderBackend for WebGlRenderBackend {
                         gradient.gradient_type,
                     );
                     program.uniform1fv(&self.gl, ShaderUniform::GradientRatios, &gradient.ratios);
-                    let colors =
-                        unsafe { std::slice::from_raw_parts(gradient.colors[0].as_ptr(), 32) };
+                    let colors = unsafe {
+                        std::slice::from_raw_parts(
+                            gradient.colors[0].as_ptr(),
+                            MAX_GRADIENT_COLORS * 4,
+                        )
+                    };
                     program.uniform4fv(&self.gl, ShaderUniform::GradientColors, &colors);
                     program.uniform1i(
                         &self.gl,
@@ -1064,7 +1211,7 @@ impl RenderBackend for WebGlRenderBackend {
                     program.uniform1i(&self.gl, ShaderUniform::BitmapTexture, 0);
 
                     // Set texture parameters.
-                    let filter = if bitmap.is_smoothed {
+                    let filter = if self.quality.smooth_bitmap(bitmap.is_smoothed) {
                         Gl::LINEAR as i32
                     } else {
                         Gl::NEAREST as i32
@@ -1073,9 +1220,11 @@ impl RenderBackend for WebGlRenderBackend {
                         .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, filter);
                     self.gl
                         .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, filter);
-                    // On WebGL1, you are unable to change the wrapping parameter causes non-power-of-2 textures.
-                    let wrap = if self.gl2.is_some() && bitmap.is_repeating {
-                        Gl::MIRRORED_REPEAT as i32
+                    // WebGL1 can only repeat textures with power-of-2 dimensions.
+                    let can_repeat = self.gl2.is_some()
+                        || (texture.width.is_power_of_two() && texture.height.is_power_of_two());
+                    let wrap = if can_repeat && bitmap.is_repeating {
+                        Gl::REPEAT as i32
                     } else {
                         Gl::CLAMP_TO_EDGE as i32
                     };
@@ -1099,29 +1248,30 @@ impl RenderBackend for WebGlRenderBackend {
 
         match letterbox {
             Letterbox::None => (),
-            Letterbox::Letterbox(margin_height) => {
+            Letterbox::Letterbox { top, bottom } => {
+                // The origin of the scissor box is at the bottom-left.
                 self.gl.enable(Gl::SCISSOR_TEST);
                 self.gl
-                    .scissor(0, 0, self.viewport_width as i32, margin_height as i32);
+                    .scissor(0, 0, self.viewport_width as i32, bottom as i32);
                 self.gl.clear(Gl::COLOR_BUFFER_BIT);
                 self.gl.scissor(
                     0,
-                    (self.viewport_height - margin_height) as i32,
+                    (self.viewport_height - top) as i32,
                     self.viewport_width as i32,
-                    margin_height as i32 + 1,
+                    top as i32 + 1,
                 );
                 self.gl.clear(Gl::COLOR_BUFFER_BIT);
                 self.gl.disable(Gl::SCISSOR_TEST);
             }
-            Letterbox::Pillarbox(margin_width) => {
+            Letterbox::Pillarbox { left, right } => {
                 self.gl.enable(Gl::SCISSOR_TEST);
                 self.gl
-                    .scissor(0, 0, margin_width as i32, self.viewport_height as i32);
+                    .scissor(0, 0, left as i32, self.viewport_height as i32);
                 self.gl.clear(Gl::COLOR_BUFFER_BIT);
                 self.gl.scissor(
-                    (self.viewport_width - margin_width) as i32,
+                    (self.viewport_width - right) as i32,
                     0,
-                    margin_width as i32 + 1,
+                    right as i32 + 1,
                     self.viewport_height as i32,
                 );
                 self.gl.clear(Gl::COLOR_BUFFER_BIT);
@@ -1180,6 +1330,102 @@ impl RenderBackend for WebGlRenderBackend {
             log::warn!("Mask stack underflow\n");
         }
     }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        if quality != self.quality {
+            self.quality = quality;
+            if let Err(e) = self.build_msaa_buffers() {
+                log::error!("Unable to change MSAA sample count: {}", e);
+            }
+        }
+    }
+
+    fn capture_frame(&mut self) -> Option<Bitmap> {
+        let width = self.viewport_width as u32;
+        let height = self.viewport_height as u32;
+        let mut data = vec![0; width as usize * height as usize * 4];
+
+        // With MSAA, the resolved frame stays in its framebuffer. Otherwise, the
+        // canvas is only readable until the browser presents it.
+        let framebuffer = self
+            .msaa_buffers
+            .as_ref()
+            .map(|msaa_buffers| &msaa_buffers.color_framebuffer);
+        self.gl.bind_framebuffer(Gl::FRAMEBUFFER, framebuffer);
+        let result = self.gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            Gl::RGBA,
+            Gl::UNSIGNED_BYTE,
+            Some(&mut data),
+        );
+        self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+        if let Err(e) = result {
+            log::error!("Unable to read back frame: {:?}", e);
+            return None;
+        }
+
+        // GL images start at the bottom row.
+        let row_len = width as usize * 4;
+        let data = data
+            .chunks_exact(row_len.max(1))
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+        Some(Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(data),
+        })
+    }
+
+    fn register_video_stream(
+        &mut self,
+        id: swf::CharacterId,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        let bitmap = Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![0; width as usize * height as usize * 4]),
+        };
+        Ok(self.register_bitmap(id, bitmap)?.handle)
+    }
+
+    fn upload_video_frame(
+        &mut self,
+        handle: BitmapHandle,
+        frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        let (_id, texture) = self.textures.get(handle.0).ok_or("Unknown video stream")?;
+        match frame {
+            DecodedFrame::Rgba(data) => {
+                if data.len() != texture.width as usize * texture.height as usize * 4 {
+                    return Err("Video frame size differs from its stream".into());
+                }
+                self.gl.bind_texture(Gl::TEXTURE_2D, Some(&texture.texture));
+                self.gl
+                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                        Gl::TEXTURE_2D,
+                        0,
+                        0,
+                        0,
+                        texture.width as i32,
+                        texture.height as i32,
+                        Gl::RGBA,
+                        Gl::UNSIGNED_BYTE,
+                        Some(data),
+                    )
+                    .into_js_result()?;
+                Ok(())
+            }
+            DecodedFrame::Yuv420 { y, u, v } => self.convert_yuv(handle.0, [y, u, v]),
+        }
+    }
 }
 
 struct Texture {
@@ -1192,8 +1438,8 @@ struct Texture {
 struct Gradient {
     matrix: [[f32; 3]; 3],
     gradient_type: i32,
-    ratios: [f32; 8],
-    colors: [[f32; 4]; 8],
+    ratios: [f32; MAX_GRADIENT_COLORS],
+    colors: [[f32; 4]; MAX_GRADIENT_COLORS],
     num_colors: u32,
     repeat_mode: i32,
     focal_point: f32,
@@ -1247,7 +1493,7 @@ struct ShaderProgram {
 }
 
 // These should match the uniform names in the shaders.
-const NUM_UNIFORMS: usize = 13;
+const NUM_UNIFORMS: usize = 15;
 const UNIFORM_NAMES: [&str; NUM_UNIFORMS] = [
     "world_matrix",
     "view_matrix",
@@ -1262,6 +1508,8 @@ const UNIFORM_NAMES: [&str; NUM_UNIFORMS] = [
     "u_focal_point",
     "u_interpolation",
     "u_texture",
+    "u_texture_u",
+    "u_texture_v",
 ];
 
 enum ShaderUniform {
@@ -1278,6 +1526,8 @@ enum ShaderUniform {
     GradientFocalPoint,
     GradientInterpolation,
     BitmapTexture,
+    YuvTextureU,
+    YuvTextureV,
 }
 
 impl ShaderProgram {
@@ -5,10 +5,10 @@ use lyon::tessellation::{
 };
 use ruffle_core::backend::render::swf::{self, FillStyle};
 use ruffle_core::backend::render::{
-    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox,
+    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, DecodedFrame, Letterbox,
     RenderBackend, ShapeHandle, Transform,
 };
-use ruffle_core::shape_utils::{DistilledShape, DrawPath};
+use ruffle_core::shape_utils::{stroke_width, DistilledShape, DrawPath};
 use std::convert::TryInto;
 use swf::{CharacterId, DefineBitsLossless, Glyph, GradientInterpolation};
 
@@ -18,12 +18,13 @@ use raw_window_handle::HasRawWindowHandle;
 
 use crate::pipelines::Pipelines;
 use crate::shapes::{Draw, DrawType, GradientUniforms, IncompleteDrawType, Mesh};
-use crate::target::{RenderTarget, RenderTargetFrame, SwapChainTarget};
+use crate::target::{RenderTarget, RenderTargetFrame, SwapChainTarget, TextureTarget};
 use crate::utils::{
     build_view_matrix, create_buffer_with_data, gradient_spread_mode_index,
     ruffle_path_to_lyon_path, swf_bitmap_to_gl_matrix, swf_to_gl_matrix,
 };
 use ruffle_core::color_transform::ColorTransform;
+use ruffle_core::stage::StageQuality;
 use std::mem::replace;
 use std::rc::Rc;
 
@@ -44,7 +45,8 @@ pub struct WgpuRenderBackend<T: RenderTarget> {
     pipelines: Pipelines,
     frame_buffer_view: wgpu::TextureView,
     depth_texture_view: wgpu::TextureView,
-    current_frame: Option<(T::Frame, wgpu::CommandEncoder)>,
+    current_frame: Option<(Box<dyn RenderTargetFrame>, wgpu::CommandEncoder)>,
+    offscreen: Option<OffscreenFrame>,
     register_encoder: wgpu::CommandEncoder,
     meshes: Vec<Mesh>,
     viewport_width: f32,
@@ -60,6 +62,19 @@ pub struct WgpuRenderBackend<T: RenderTarget> {
     quad_vbo: wgpu::Buffer,
     quad_ibo: wgpu::Buffer,
     quad_tex_transforms: wgpu::Buffer,
+    quality: StageQuality,
+}
+
+/// The viewport state that is saved while rendering an offscreen frame.
+struct OffscreenFrame {
+    target: TextureTarget,
+    width: u32,
+    height: u32,
+    frame_buffer_view: wgpu::TextureView,
+    depth_texture_view: wgpu::TextureView,
+    viewport_width: f32,
+    viewport_height: f32,
+    view_matrix: [[f32; 4]; 4],
 }
 
 #[repr(C)]
@@ -207,6 +222,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             frame_buffer_view,
             depth_texture_view,
             current_frame: None,
+            offscreen: None,
             register_encoder,
             meshes: Vec::new(),
             viewport_width,
@@ -222,9 +238,111 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             quad_vbo,
             quad_ibo,
             quad_tex_transforms,
+            quality: StageQuality::default(),
         })
     }
 
+    /// Creates the multisampled frame buffer and depth texture used to render a frame.
+    fn create_frame_buffers(
+        &self,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> (wgpu::TextureView, wgpu::TextureView) {
+        let label = create_debug_label!("Framebuffer texture");
+        let frame_buffer = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: label.as_deref(),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: self.msaa_sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+
+        let label = create_debug_label!("Depth texture");
+        let depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: label.as_deref(),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: self.msaa_sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth24PlusStencil8,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+
+        (
+            frame_buffer.create_default_view(),
+            depth_texture.create_default_view(),
+        )
+    }
+
+    /// Resets the mask state and clears the current frame.
+    fn clear_frame(&mut self, clear: Color) {
+        self.num_masks = 0;
+        self.num_masks_active = 0;
+        self.write_stencil_mask = 0;
+        self.test_stencil_mask = 0;
+        self.next_stencil_mask = 1;
+
+        if let Some((frame_output, encoder)) = &mut self.current_frame {
+            let (color_attachment, resolve_target) = if self.msaa_sample_count >= 2 {
+                (&self.frame_buffer_view, Some(frame_output.view()))
+            } else {
+                (frame_output.view(), None)
+            };
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: color_attachment,
+                    load_op: wgpu::LoadOp::Clear,
+                    store_op: wgpu::StoreOp::Store,
+                    clear_color: wgpu::Color {
+                        r: f64::from(clear.r) / 255.0,
+                        g: f64::from(clear.g) / 255.0,
+                        b: f64::from(clear.b) / 255.0,
+                        a: f64::from(clear.a) / 255.0,
+                    },
+                    resolve_target,
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                    attachment: &self.depth_texture_view,
+                    depth_load_op: wgpu::LoadOp::Clear,
+                    depth_store_op: wgpu::StoreOp::Store,
+                    stencil_load_op: wgpu::LoadOp::Clear,
+                    stencil_store_op: wgpu::StoreOp::Store,
+                    clear_depth: 0.0,
+                    clear_stencil: 0,
+                }),
+            });
+        }
+    }
+
+    /// Finishes recording the current frame.
+    ///
+    /// Returns the frame along with the command buffers to submit, which include any
+    /// pending resource uploads. The frame should be kept alive until they are submitted.
+    fn finish_frame(&mut self) -> Option<(Box<dyn RenderTargetFrame>, [wgpu::CommandBuffer; 2])> {
+        let (frame, encoder) = self.current_frame.take()?;
+        let register_encoder_label = create_debug_label!("Register encoder");
+        let new_register_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: register_encoder_label.as_deref(),
+                });
+        let register_buffer = replace(&mut self.register_encoder, new_register_encoder).finish();
+        Some((frame, [register_buffer, encoder.finish()]))
+    }
+
     #[allow(clippy::cognitive_complexity)]
     fn register_shape_internal(&mut self, shape: DistilledShape) -> Mesh {
         use lyon::tessellation::{FillOptions, StrokeOptions};
@@ -298,6 +416,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             *lyon_mesh = VertexBuffers::new();
         }
 
+        let scale = shape.scale;
         for path in shape.paths {
             match path {
                 DrawPath::Fill { style, commands } => match style {
@@ -446,7 +565,8 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                             continue;
                         }
 
-                        let uniforms = swf_gradient_to_uniforms(2, gradient, *focal_point);
+                        let uniforms =
+                            swf_gradient_to_uniforms(2, gradient, focal_point.max(-1.0).min(1.0));
                         let matrix = swf_to_gl_matrix(gradient.matrix);
 
                         flush_draw(
@@ -547,12 +667,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                     let mut buffers_builder =
                         BuffersBuilder::new(&mut lyon_mesh, RuffleVertexCtor { color });
 
-                    // TODO(Herschel): 0 width indicates "hairline".
-                    let width = if style.width.to_pixels() >= 1.0 {
-                        style.width.to_pixels() as f32
-                    } else {
-                        1.0
-                    };
+                    let width = stroke_width(style, scale);
 
                     let mut options = StrokeOptions::default()
                         .with_line_width(width)
@@ -573,7 +688,9 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                         });
 
                     if let swf::LineJoinStyle::Miter(limit) = style.join_style {
-                        options = options.with_miter_limit(limit);
+                        // Lyon panics on limits below its minimum, which SWFs can contain.
+                        options =
+                            options.with_miter_limit(limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
                     }
 
                     if let Err(e) = stroke_tess.tessellate_path(
@@ -815,39 +932,10 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
 
         self.target.resize(&self.device, width, height);
 
-        let label = create_debug_label!("Framebuffer texture");
-        let frame_buffer = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: label.as_deref(),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth: 1,
-            },
-            array_layer_count: 1,
-            mip_level_count: 1,
-            sample_count: self.msaa_sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: self.target.format(),
-            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        });
-        self.frame_buffer_view = frame_buffer.create_default_view();
-
-        let label = create_debug_label!("Depth texture");
-        let depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: label.as_deref(),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth: 1,
-            },
-            array_layer_count: 1,
-            mip_level_count: 1,
-            sample_count: self.msaa_sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        });
-        self.depth_texture_view = depth_texture.create_default_view();
+        let (frame_buffer_view, depth_texture_view) =
+            self.create_frame_buffers(width, height, self.target.format());
+        self.frame_buffer_view = frame_buffer_view;
+        self.depth_texture_view = depth_texture_view;
 
         self.viewport_width = width as f32;
         self.viewport_height = height as f32;
@@ -929,7 +1017,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             Ok(frame) => {
                 let label = create_debug_label!("Frame encoder");
                 Some((
-                    frame,
+                    Box::new(frame) as Box<dyn RenderTargetFrame>,
                     self.device
                         .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                             label: label.as_deref(),
@@ -941,45 +1029,10 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                 None
             }
         };
-        self.num_masks = 0;
-        self.num_masks_active = 0;
-        self.write_stencil_mask = 0;
-        self.test_stencil_mask = 0;
-        self.next_stencil_mask = 1;
-
-        if let Some((frame_output, encoder)) = &mut self.current_frame {
-            let (color_attachment, resolve_target) = if self.msaa_sample_count >= 2 {
-                (&self.frame_buffer_view, Some(frame_output.view()))
-            } else {
-                (frame_output.view(), None)
-            };
-            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: color_attachment,
-                    load_op: wgpu::LoadOp::Clear,
-                    store_op: wgpu::StoreOp::Store,
-                    clear_color: wgpu::Color {
-                        r: f64::from(clear.r) / 255.0,
-                        g: f64::from(clear.g) / 255.0,
-                        b: f64::from(clear.b) / 255.0,
-                        a: f64::from(clear.a) / 255.0,
-                    },
-                    resolve_target,
-                }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
-                    attachment: &self.depth_texture_view,
-                    depth_load_op: wgpu::LoadOp::Clear,
-                    depth_store_op: wgpu::StoreOp::Store,
-                    stencil_load_op: wgpu::LoadOp::Clear,
-                    stencil_store_op: wgpu::StoreOp::Store,
-                    clear_depth: 0.0,
-                    clear_stencil: 0,
-                }),
-            });
-        }
+        self.clear_frame(clear);
     }
 
-    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform) {
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
         if let Some((_id, texture)) = self.textures.get(bitmap.0) {
             let (frame_output, encoder) =
                 if let Some((frame_output, encoder)) = &mut self.current_frame {
@@ -1029,13 +1082,18 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             );
 
             let texture_view = texture.texture.create_default_view();
+            let filter = if self.quality.smooth_bitmap(smoothing) {
+                wgpu::FilterMode::Linear
+            } else {
+                wgpu::FilterMode::Nearest
+            };
             let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
                 address_mode_u: wgpu::AddressMode::ClampToEdge,
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
                 address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                mipmap_filter: wgpu::FilterMode::Linear,
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_filter: filter,
                 lod_min_clamp: 0.0,
                 lod_max_clamp: 100.0,
                 compare: wgpu::CompareFunction::Undefined,
@@ -1248,76 +1306,171 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn end_frame(&mut self) {
-        if let Some((_frame, encoder)) = self.current_frame.take() {
-            let register_encoder_label = create_debug_label!("Register encoder");
-            let new_register_encoder =
-                self.device
-                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                        label: register_encoder_label.as_deref(),
-                    });
-            let register_buffer =
-                replace(&mut self.register_encoder, new_register_encoder).finish();
-            self.target.submit(
-                &self.device,
-                &self.queue,
-                &[register_buffer, encoder.finish()],
-            );
+        if let Some((_frame, command_buffers)) = self.finish_frame() {
+            self.target
+                .submit(&self.device, &self.queue, &command_buffers);
+        }
+    }
+
+    fn begin_offscreen_frame(&mut self, width: u32, height: u32, clear: Color) -> bool {
+        if self.current_frame.is_some() || self.offscreen.is_some() {
+            return false;
+        }
+
+        // Rows copied out of a texture must be aligned to 256 bytes, so pad the
+        // texture to a multiple of 64 pixels and crop the image afterwards.
+        let padded_width = (width.max(1) + 63) / 64 * 64;
+        let height = height.max(1);
+        let mut target = TextureTarget::new(&self.device, (padded_width, height));
+        let frame = match target.get_next_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::TimeOut) => return false,
+        };
+        let (frame_buffer_view, depth_texture_view) =
+            self.create_frame_buffers(padded_width, height, target.format());
+
+        self.offscreen = Some(OffscreenFrame {
+            target,
+            width,
+            height,
+            frame_buffer_view: replace(&mut self.frame_buffer_view, frame_buffer_view),
+            depth_texture_view: replace(&mut self.depth_texture_view, depth_texture_view),
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            view_matrix: self.view_matrix,
+        });
+        self.viewport_width = padded_width as f32;
+        self.viewport_height = height as f32;
+        self.view_matrix = build_view_matrix(padded_width, height);
+
+        let label = create_debug_label!("Offscreen frame encoder");
+        self.current_frame = Some((
+            Box::new(frame) as Box<dyn RenderTargetFrame>,
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: label.as_deref(),
+                }),
+        ));
+        self.clear_frame(clear);
+        true
+    }
+
+    fn end_offscreen_frame(&mut self) -> Option<Bitmap> {
+        let offscreen = self.offscreen.take()?;
+        let frame = self.finish_frame();
+
+        // Restore the viewport.
+        self.frame_buffer_view = offscreen.frame_buffer_view;
+        self.depth_texture_view = offscreen.depth_texture_view;
+        self.viewport_width = offscreen.viewport_width;
+        self.viewport_height = offscreen.viewport_height;
+        self.view_matrix = offscreen.view_matrix;
+
+        let (_frame, command_buffers) = frame?;
+        offscreen
+            .target
+            .submit(&self.device, &self.queue, &command_buffers);
+        let mut image = offscreen.target.capture(&self.device)?;
+        let image =
+            image::imageops::crop(&mut image, 0, 0, offscreen.width, offscreen.height).to_image();
+        Some(Bitmap {
+            width: image.width(),
+            height: image.height(),
+            data: BitmapFormat::Rgba(image.into_raw()),
+        })
+    }
+
+    fn capture_frame(&mut self) -> Option<Bitmap> {
+        let image = self.target.capture(&self.device)?;
+        Some(Bitmap {
+            width: image.width(),
+            height: image.height(),
+            data: BitmapFormat::Rgba(image.into_raw()),
+        })
+    }
+
+    fn register_video_stream(
+        &mut self,
+        id: swf::CharacterId,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, Error> {
+        let bitmap = Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![0; width as usize * height as usize * 4]),
+        };
+        Ok(self.register_bitmap(id, bitmap, "Video")?.handle)
+    }
+
+    fn upload_video_frame(
+        &mut self,
+        handle: BitmapHandle,
+        frame: DecodedFrame<'_>,
+    ) -> Result<(), Error> {
+        let (id, texture) = self.textures.get(handle.0).ok_or("Unknown video stream")?;
+        // The shaders are precompiled, so YUV frames are converted on the CPU.
+        // TODO: Add a shader to convert them on the GPU.
+        let data = frame.to_rgba(texture.width, texture.height);
+        if data.len() != texture.width as usize * texture.height as usize * 4 {
+            return Err("Video frame size differs from its stream".into());
         }
+
+        let buffer = create_buffer_with_data(
+            &self.device,
+            &data,
+            wgpu::BufferUsage::COPY_SRC,
+            create_debug_label!("Video frame transfer buffer {}", id),
+        );
+        self.register_encoder.copy_buffer_to_texture(
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                offset: 0,
+                bytes_per_row: 4 * texture.width,
+                rows_per_image: 0,
+            },
+            wgpu::TextureCopyView {
+                texture: &texture.texture,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::Extent3d {
+                width: texture.width,
+                height: texture.height,
+                depth: 1,
+            },
+        );
+        Ok(())
     }
 
     fn draw_letterbox(&mut self, letterbox: Letterbox) {
+        const BLACK: Color = Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
         match letterbox {
             Letterbox::None => {}
-            Letterbox::Letterbox(margin) => {
+            Letterbox::Letterbox { top, bottom } => {
+                self.draw_rect(0.0, 0.0, self.viewport_width, top, BLACK);
                 self.draw_rect(
                     0.0,
-                    0.0,
+                    self.viewport_height - bottom,
                     self.viewport_width,
-                    margin,
-                    Color {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: 255,
-                    },
-                );
-                self.draw_rect(
-                    0.0,
-                    self.viewport_height - margin,
-                    self.viewport_width,
-                    margin,
-                    Color {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: 255,
-                    },
+                    bottom,
+                    BLACK,
                 );
             }
-            Letterbox::Pillarbox(margin) => {
+            Letterbox::Pillarbox { left, right } => {
+                self.draw_rect(0.0, 0.0, left, self.viewport_height, BLACK);
                 self.draw_rect(
+                    self.viewport_width - right,
                     0.0,
-                    0.0,
-                    margin,
+                    right,
                     self.viewport_height,
-                    Color {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: 255,
-                    },
-                );
-                self.draw_rect(
-                    self.viewport_width - margin,
-                    0.0,
-                    margin,
-                    self.viewport_height,
-                    Color {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: 255,
-                    },
+                    BLACK,
                 );
             }
         }
@@ -1385,6 +1538,13 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             self.test_stencil_mask = test;
         }
     }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        // Anti-aliasing and the samplers of bitmap fills are fixed when the
+        // pipelines and shapes are created, so only bitmap display objects
+        // follow the quality.
+        self.quality = quality;
+    }
 }
 
 fn create_quad_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
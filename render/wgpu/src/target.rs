@@ -8,7 +8,7 @@ pub trait RenderTargetFrame: Debug {
 }
 
 pub trait RenderTarget: Debug + 'static {
-    type Frame: RenderTargetFrame;
+    type Frame: RenderTargetFrame + 'static;
 
     fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32);
 
@@ -26,6 +26,9 @@ pub trait RenderTarget: Debug + 'static {
         queue: &wgpu::Queue,
         command_buffers: &[wgpu::CommandBuffer],
     );
+
+    /// Reads back the last submitted frame, if this target supports it.
+    fn capture(&self, device: &wgpu::Device) -> Option<RgbaImage>;
 }
 
 #[derive(Debug)]
@@ -95,6 +98,11 @@ impl RenderTarget for SwapChainTarget {
     ) {
         queue.submit(command_buffers);
     }
+
+    fn capture(&self, _device: &wgpu::Device) -> Option<RgbaImage> {
+        // Swap chain images can't be copied out once presented.
+        None
+    }
 }
 
 #[derive(Debug)]
@@ -148,27 +156,6 @@ impl TextureTarget {
             buffer,
         }
     }
-
-    pub fn capture(&self, device: &wgpu::Device) -> Option<RgbaImage> {
-        let buffer_future = self
-            .buffer
-            .map_read(0, self.size.width as u64 * self.size.height as u64 * 4);
-        device.poll(wgpu::Maintain::Wait);
-        match block_on(buffer_future) {
-            Ok(map) => {
-                let bgra = BgraImage::from_raw(
-                    self.size.width,
-                    self.size.height,
-                    Vec::from(map.as_slice()),
-                );
-                bgra.map(|image| image.convert())
-            }
-            Err(e) => {
-                log::error!("Unknown error reading capture buffer: {:?}", e);
-                None
-            }
-        }
-    }
 }
 
 impl RenderTarget for TextureTarget {
@@ -242,4 +229,25 @@ impl RenderTarget for TextureTarget {
         queue.submit(command_buffers);
         queue.submit(&[encoder.finish()]);
     }
+
+    fn capture(&self, device: &wgpu::Device) -> Option<RgbaImage> {
+        let buffer_future = self
+            .buffer
+            .map_read(0, self.size.width as u64 * self.size.height as u64 * 4);
+        device.poll(wgpu::Maintain::Wait);
+        match block_on(buffer_future) {
+            Ok(map) => {
+                let bgra = BgraImage::from_raw(
+                    self.size.width,
+                    self.size.height,
+                    Vec::from(map.as_slice()),
+                );
+                bgra.map(|image| image.convert())
+            }
+            Err(e) => {
+                log::error!("Unknown error reading capture buffer: {:?}", e);
+                None
+            }
+        }
+    }
 }
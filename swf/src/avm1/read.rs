@@ -34,14 +34,23 @@ impl<'a> Reader<'a> {
     #[inline]
     pub fn seek(&mut self, relative_offset: isize) {
         let new_pos = self.inner.position() as i64 + relative_offset as i64;
-        self.inner.set_position(new_pos as u64);
+        if new_pos >= 0 {
+            self.inner.set_position(new_pos as u64);
+        } else {
+            // Jumping to before the start of the actions ends them, the same as jumping
+            // past the end does.
+            self.inner.set_position(self.inner.get_ref().len() as u64);
+        }
     }
 
     #[inline]
     fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
         let pos = self.pos();
-        self.inner.set_position(pos as u64 + len as u64);
-        let slice = self.inner.get_ref().get(pos..pos + len).ok_or_else(|| {
+        let end = pos.checked_add(len).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Buffer underrun")
+        })?;
+        self.inner.set_position(end as u64);
+        let slice = self.inner.get_ref().get(pos..end).ok_or_else(|| {
             std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Buffer underrun")
         })?;
         Ok(slice)
@@ -282,7 +291,7 @@ impl<'a> Reader<'a> {
 
     fn read_push(&mut self, length: usize) -> Result<Action<'a>> {
         let end_pos = self.pos() + length;
-        let mut values = Vec::with_capacity(end_pos);
+        let mut values = Vec::with_capacity(length);
         while self.pos() < end_pos {
             values.push(self.read_push_value()?);
         }
@@ -453,4 +462,15 @@ pub mod tests {
         let action = reader.read_action().unwrap().unwrap();
         assert_eq!(action, Action::Push(vec![Value::Null, Value::Undefined]));
     }
+
+    #[test]
+    fn seek_before_start() {
+        // Jumping to before the first action ends the actions instead of wrapping around.
+        let action_bytes = [0x07, 0x07];
+        let mut reader = Reader::new(&action_bytes[..], 5);
+        reader.read_action().unwrap();
+        reader.seek(-10);
+        assert_eq!(reader.pos(), action_bytes.len());
+        assert!(reader.read_action().is_err());
+    }
 }
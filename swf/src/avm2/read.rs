@@ -1,6 +1,7 @@
 use crate::avm2::types::*;
 use crate::error::{Error, Result};
 use crate::read::SwfRead;
+use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Read;
 
 pub struct Reader<R: Read> {
@@ -11,6 +12,11 @@ impl<R: Read> SwfRead<R> for Reader<R> {
     fn get_inner(&mut self) -> &mut R {
         &mut self.inner
     }
+
+    fn read_f64(&mut self) -> std::io::Result<f64> {
+        // Unlike in SWF tags, doubles in ABC files are plain little-endian.
+        self.inner.read_f64::<LittleEndian>()
+    }
 }
 
 impl<R: Read> Reader<R> {
@@ -90,11 +96,12 @@ impl<R: Read> Reader<R> {
         self.read_u30()
     }
 
-    #[allow(dead_code)]
     fn read_i24(&mut self) -> Result<i32> {
-        Ok(i32::from(self.read_u8()?)
+        let n = i32::from(self.read_u8()?)
             | (i32::from(self.read_u8()?) << 8)
-            | (i32::from(self.read_u8()?) << 16))
+            | (i32::from(self.read_u8()?) << 16);
+        // Sign-extend the 24-bit value.
+        Ok((n << 8) >> 8)
     }
     fn read_i32(&mut self) -> Result<i32> {
         let mut n: i32 = 0;
@@ -104,8 +111,11 @@ impl<R: Read> Reader<R> {
             n |= (byte & 0b0111_1111) << i;
             i += 7;
             if byte & 0b1000_0000 == 0 {
-                n <<= 32 - i;
-                n >>= 32 - i;
+                // Sign-extend shorter encodings; five bytes already fill all 32 bits.
+                if i < 32 {
+                    n <<= 32 - i;
+                    n >>= 32 - i;
+                }
                 break;
             }
         }
@@ -517,8 +527,8 @@ impl<R: Read> Reader<R> {
         })
     }
 
-    #[allow(dead_code)]
-    fn read_op(&mut self) -> Result<Option<Op>> {
+    /// Reads a single instruction from method body bytecode.
+    pub fn read_op(&mut self) -> Result<Option<Op>> {
         use crate::avm2::opcode::OpCode;
         use num_traits::FromPrimitive;
 
@@ -889,4 +899,17 @@ pub mod tests {
             }
         }
     }
+    #[test]
+    fn read_i32() {
+        let read = |data: &[u8]| Reader::new(data).read_i32().unwrap();
+        assert_eq!(read(&[0x7b]), -5);
+        assert_eq!(read(&[0xfb, 0xff, 0xff, 0xff, 0x0f]), -5);
+        assert_eq!(read(&[0x80, 0x80, 0x80, 0x80, 0x08]), i32::min_value());
+    }
+
+    #[test]
+    fn read_f64() {
+        let data = 1.5f64.to_le_bytes();
+        assert_eq!(Reader::new(&data[..]).read_f64().unwrap(), 1.5);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::avm2::opcode::OpCode;
 use crate::avm2::types::*;
 use crate::write::SwfWrite;
+use byteorder::{LittleEndian, WriteBytesExt};
 use std::io::{Result, Write};
 
 pub struct Writer<W: Write> {
@@ -11,6 +12,11 @@ impl<W: Write> SwfWrite<W> for Writer<W> {
     fn get_inner(&mut self) -> &mut W {
         &mut self.inner
     }
+
+    fn write_f64(&mut self, n: f64) -> Result<()> {
+        // Unlike in SWF tags, doubles in ABC files are plain little-endian.
+        self.inner.write_f64::<LittleEndian>(n)
+    }
 }
 
 impl<W: Write> Writer<W> {
@@ -12,6 +12,19 @@ use std::collections::HashSet;
 use std::convert::TryInto;
 use std::io::{self, Read};
 
+/// The most memory to reserve up front for data whose size is read from the SWF.
+///
+/// Lengths in a corrupt SWF can be anything up to 4GB, so buffers larger than this
+/// grow as data is actually read instead of being allocated all at once.
+const MAX_PREALLOCATED_LEN: usize = 1 << 20;
+
+/// Creates a `Vec` for `len` elements read from the SWF, reserving at most
+/// `MAX_PREALLOCATED_LEN` bytes.
+fn preallocated_vec<T>(len: usize) -> Vec<T> {
+    let max_len = MAX_PREALLOCATED_LEN / std::mem::size_of::<T>().max(1);
+    Vec::with_capacity(len.min(max_len))
+}
+
 /// Convenience method to parse an SWF.
 ///
 /// Decompresses the SWF in memory and returns a `Vec` of tags.
@@ -39,7 +52,7 @@ pub fn read_swf<R: Read>(input: R) -> Result<Swf> {
         let _ = reader.get_mut().read_exact(&mut data);
         data
     } else {
-        let mut data = Vec::with_capacity(swf_stream.uncompressed_length);
+        let mut data = preallocated_vec(swf_stream.uncompressed_length);
         if let Err(e) = reader.get_mut().read_to_end(&mut data) {
             log::error!("Error decompressing SWF, may be corrupt: {}", e);
         }
@@ -84,7 +97,10 @@ pub fn read_swf_header<'a, R: Read + 'a>(mut input: R) -> Result<SwfStream<'a>>
 
     // Uncompressed length includes the 4-byte header and 4-byte uncompressed length itself,
     // subtract it here.
-    let uncompressed_length = input.read_u32::<LittleEndian>()? - 8;
+    let uncompressed_length = input
+        .read_u32::<LittleEndian>()?
+        .checked_sub(8)
+        .ok_or_else(|| Error::invalid_data("Invalid SWF length"))?;
 
     // Now the SWF switches to a compressed stream.
     let decompressed_input: Box<dyn Read> = match compression {
@@ -184,10 +200,11 @@ fn make_lzma_reader<'a, R: Read + 'a>(
     lzma_header.write_u64::<LittleEndian>(uncompressed_length.into())?;
 
     // Create LZMA decoder stream and write header
-    let mut lzma_stream = Stream::new_lzma_decoder(u64::max_value()).unwrap();
+    let mut lzma_stream = Stream::new_lzma_decoder(u64::max_value())
+        .map_err(|_| Error::invalid_data("Unable to create LZMA decoder"))?;
     lzma_stream
         .process(&lzma_header.into_inner(), &mut [0u8; 1], Action::Run)
-        .unwrap();
+        .map_err(|_| Error::invalid_data("Invalid LZMA header"))?;
 
     // Decoder is ready
     Ok(Box::new(XzDecoder::new_stream(input, lzma_stream)))
@@ -388,19 +405,19 @@ impl<R: Read> Reader<R> {
             Some(TagCode::DefineBinaryData) => {
                 let id = tag_reader.read_u16()?;
                 tag_reader.read_u32()?; // Reserved
-                let mut data = Vec::with_capacity(length - 6);
+                let mut data = preallocated_vec(length.saturating_sub(6));
                 tag_reader.input.read_to_end(&mut data)?;
                 Tag::DefineBinaryData { id, data }
             }
             Some(TagCode::DefineBits) => {
                 let id = tag_reader.read_u16()?;
-                let mut jpeg_data = Vec::with_capacity(length - 2);
+                let mut jpeg_data = preallocated_vec(length.saturating_sub(2));
                 tag_reader.input.read_to_end(&mut jpeg_data)?;
                 Tag::DefineBits { id, jpeg_data }
             }
             Some(TagCode::DefineBitsJpeg2) => {
                 let id = tag_reader.read_u16()?;
-                let mut jpeg_data = Vec::with_capacity(length - 2);
+                let mut jpeg_data = preallocated_vec(length.saturating_sub(2));
                 tag_reader.input.read_to_end(&mut jpeg_data)?;
                 Tag::DefineBitsJpeg2 { id, jpeg_data }
             }
@@ -452,7 +469,9 @@ impl<R: Read> Reader<R> {
             Some(TagCode::DefineText2) => {
                 Tag::DefineText(Box::new(tag_reader.read_define_text(2)?))
             }
-            Some(TagCode::DefineVideoStream) => tag_reader.read_define_video_stream()?,
+            Some(TagCode::DefineVideoStream) => {
+                Tag::DefineVideoStream(tag_reader.read_define_video_stream()?)
+            }
             Some(TagCode::EnableTelemetry) => {
                 tag_reader.read_u16()?; // Reserved
                 let password_hash = if length > 2 {
@@ -465,40 +484,22 @@ impl<R: Read> Reader<R> {
                 Tag::EnableTelemetry { password_hash }
             }
             Some(TagCode::ImportAssets) => {
-                let url = tag_reader.read_c_string()?;
-                let num_imports = tag_reader.read_u16()?;
-                let mut imports = Vec::with_capacity(num_imports as usize);
-                for _ in 0..num_imports {
-                    imports.push(ExportedAsset {
-                        id: tag_reader.read_u16()?,
-                        name: tag_reader.read_c_string()?,
-                    });
-                }
+                let (url, imports) = tag_reader.read_import_assets()?;
                 Tag::ImportAssets { url, imports }
             }
             Some(TagCode::ImportAssets2) => {
-                let url = tag_reader.read_c_string()?;
-                tag_reader.read_u8()?; // Reserved; must be 1
-                tag_reader.read_u8()?; // Reserved; must be 0
-                let num_imports = tag_reader.read_u16()?;
-                let mut imports = Vec::with_capacity(num_imports as usize);
-                for _ in 0..num_imports {
-                    imports.push(ExportedAsset {
-                        id: tag_reader.read_u16()?,
-                        name: tag_reader.read_c_string()?,
-                    });
-                }
+                let (url, imports) = tag_reader.read_import_assets_2()?;
                 Tag::ImportAssets { url, imports }
             }
 
             Some(TagCode::JpegTables) => {
-                let mut data = Vec::with_capacity(length);
+                let mut data = preallocated_vec(length);
                 tag_reader.input.read_to_end(&mut data)?;
                 Tag::JpegTables(data)
             }
 
             Some(TagCode::Metadata) => {
-                let mut s = String::with_capacity(length);
+                let mut s = String::with_capacity(length.min(MAX_PREALLOCATED_LEN));
                 tag_reader.get_mut().read_to_string(&mut s)?;
                 // Remove trailing null bytes. There may or may not be a null byte.
                 s = s.trim_end_matches(char::from(0)).to_string();
@@ -508,7 +509,7 @@ impl<R: Read> Reader<R> {
             Some(TagCode::SetBackgroundColor) => Tag::SetBackgroundColor(tag_reader.read_rgb()?),
 
             Some(TagCode::SoundStreamBlock) => {
-                let mut data = Vec::with_capacity(length);
+                let mut data = preallocated_vec(length);
                 tag_reader.input.read_to_end(&mut data)?;
                 Tag::SoundStreamBlock(data)
             }
@@ -546,7 +547,7 @@ impl<R: Read> Reader<R> {
             Some(TagCode::DoAbc) => {
                 let flags = tag_reader.read_u32()?;
                 let name = tag_reader.read_c_string()?;
-                let mut abc_data = Vec::with_capacity(length - 4 - name.len());
+                let mut abc_data = preallocated_vec(length.saturating_sub(4 + name.len()));
                 tag_reader.input.read_to_end(&mut abc_data)?;
                 Tag::DoAbc(DoAbc {
                     name,
@@ -556,14 +557,14 @@ impl<R: Read> Reader<R> {
             }
 
             Some(TagCode::DoAction) => {
-                let mut action_data = Vec::with_capacity(length);
+                let mut action_data = preallocated_vec(length);
                 tag_reader.input.read_to_end(&mut action_data)?;
                 Tag::DoAction(action_data)
             }
 
             Some(TagCode::DoInitAction) => {
                 let id = tag_reader.read_u16()?;
-                let mut action_data = Vec::with_capacity(length);
+                let mut action_data = preallocated_vec(length);
                 tag_reader.input.read_to_end(&mut action_data)?;
                 Tag::DoInitAction { id, action_data }
             }
@@ -643,12 +644,14 @@ impl<R: Read> Reader<R> {
 
             Some(TagCode::RemoveObject2) => Tag::RemoveObject(tag_reader.read_remove_object_2()?),
 
-            Some(TagCode::VideoFrame) => tag_reader.read_video_frame()?,
+            Some(TagCode::VideoFrame) => Tag::VideoFrame(tag_reader.read_video_frame()?),
             Some(TagCode::ProductInfo) => Tag::ProductInfo(tag_reader.read_product_info()?),
             _ => {
-                let size = length as usize;
-                let mut data = vec![0; size];
-                tag_reader.input.read_exact(&mut data[..])?;
+                let mut data = preallocated_vec(length);
+                tag_reader.input.read_to_end(&mut data)?;
+                if data.len() < length {
+                    return Err(Error::invalid_data("Unexpected end of tag"));
+                }
                 Tag::Unknown { tag_code, data }
             }
         };
@@ -719,7 +722,9 @@ impl<R: Read> Reader<R> {
     }
 
     pub fn read_sbits(&mut self, num_bits: usize) -> Result<i32> {
-        if num_bits > 0 {
+        if num_bits > 32 {
+            Err(Error::invalid_data("Too many bits for a signed integer"))
+        } else if num_bits > 0 {
             self.read_ubits(num_bits)
                 .map(|n| (n as i32) << (32 - num_bits) >> (32 - num_bits))
         } else {
@@ -1133,7 +1138,7 @@ impl<R: Read> Reader<R> {
         &mut self,
     ) -> Result<DefineSceneAndFrameLabelData> {
         let num_scenes = self.read_encoded_u32()? as usize;
-        let mut scenes = Vec::with_capacity(num_scenes);
+        let mut scenes = preallocated_vec(num_scenes);
         for _ in 0..num_scenes {
             scenes.push(FrameLabelData {
                 frame_num: self.read_encoded_u32()?,
@@ -1142,7 +1147,7 @@ impl<R: Read> Reader<R> {
         }
 
         let num_frame_labels = self.read_encoded_u32()? as usize;
-        let mut frame_labels = Vec::with_capacity(num_frame_labels);
+        let mut frame_labels = preallocated_vec(num_frame_labels);
         for _ in 0..num_frame_labels {
             frame_labels.push(FrameLabelData {
                 frame_num: self.read_encoded_u32()?,
@@ -2066,13 +2071,29 @@ impl<R: Read> Reader<R> {
         Ok(exports)
     }
 
+    pub fn read_import_assets(&mut self) -> Result<(String, ExportAssets)> {
+        let url = self.read_c_string()?;
+        let imports = self.read_export_assets()?;
+        Ok((url, imports))
+    }
+
+    pub fn read_import_assets_2(&mut self) -> Result<(String, ExportAssets)> {
+        let url = self.read_c_string()?;
+        self.read_u8()?; // Reserved; must be 1
+        self.read_u8()?; // Reserved; must be 0
+        let imports = self.read_export_assets()?;
+        Ok((url, imports))
+    }
+
     pub fn read_place_object(&mut self, tag_length: usize) -> Result<PlaceObject> {
         // TODO: What's a best way to know if the tag has a color transform?
         // You only know if there is still data remaining after the matrix.
         // This sucks.
-        let mut vector = [0; 128];
-        self.get_mut().read_exact(&mut vector[..tag_length])?;
-        let mut reader = Reader::new(&vector[..], self.version);
+        let mut data = preallocated_vec(tag_length);
+        self.get_mut()
+            .take(tag_length as u64)
+            .read_to_end(&mut data)?;
+        let mut reader = Reader::new(&data[..], self.version);
         Ok(PlaceObject {
             version: 1,
             action: PlaceObjectAction::Place(reader.read_u16()?),
@@ -2092,7 +2113,7 @@ impl<R: Read> Reader<R> {
             blend_mode: BlendMode::Normal,
             clip_actions: vec![],
             is_image: false,
-            is_bitmap_cached: false,
+            is_bitmap_cached: None,
             is_visible: true,
             amf_data: None,
         })
@@ -2166,7 +2187,11 @@ impl<R: Read> Reader<R> {
         } else {
             BlendMode::Normal
         };
-        let is_bitmap_cached = (flags & 0b100_00000000) != 0 && self.read_u8()? != 0;
+        let is_bitmap_cached = if (flags & 0b100_00000000) != 0 {
+            Some(self.read_u8()? != 0)
+        } else {
+            None
+        };
         let is_visible = (flags & 0b100000_00000000) == 0 || self.read_u8()? != 0;
         let background_color = if (flags & 0b1000000_00000000) != 0 {
             Some(self.read_rgba()?)
@@ -2256,17 +2281,23 @@ impl<R: Read> Reader<R> {
         if events.is_empty() {
             Ok(None)
         } else {
-            let mut length = self.read_u32()?;
+            let mut length = self.read_u32()? as usize;
             let key_code = if events.contains(ClipEventFlag::KeyPress) {
                 // ActionData length includes the 1 byte key code.
-                length -= 1;
+                length = length.saturating_sub(1);
                 Some(self.read_u8()?)
             } else {
                 None
             };
 
-            let mut action_data = vec![0; length as usize];
-            self.input.read_exact(&mut action_data)?;
+            let mut action_data = preallocated_vec(length);
+            self.input
+                .by_ref()
+                .take(length as u64)
+                .read_to_end(&mut action_data)?;
+            if action_data.len() < length {
+                return Err(Error::invalid_data("Unexpected end of clip action"));
+            }
 
             Ok(Some(ClipAction {
                 events,
@@ -2424,8 +2455,8 @@ impl<R: Read> Reader<R> {
                 let num_matrix_rows = self.read_u8()?;
                 let divisor = self.read_fixed16()?;
                 let bias = self.read_fixed16()?;
-                let num_entries = num_matrix_cols * num_matrix_rows;
-                let mut matrix = Vec::with_capacity(num_entries as usize);
+                let num_entries = usize::from(num_matrix_cols) * usize::from(num_matrix_rows);
+                let mut matrix = Vec::with_capacity(num_entries);
                 for _ in 0..num_entries {
                     matrix.push(self.read_fixed16()?);
                 }
@@ -2723,7 +2754,7 @@ impl<R: Read> Reader<R> {
         })
     }
 
-    fn read_define_video_stream(&mut self) -> Result<Tag> {
+    pub fn read_define_video_stream(&mut self) -> Result<DefineVideoStream> {
         let id = self.read_character_id()?;
         let num_frames = self.read_u16()?;
         let width = self.read_u16()?;
@@ -2737,14 +2768,14 @@ impl<R: Read> Reader<R> {
             5 => VideoCodec::VP6WithAlpha,
             _ => return Err(Error::invalid_data("Invalid video codec.")),
         };
-        Ok(Tag::DefineVideoStream(DefineVideoStream {
+        Ok(DefineVideoStream {
             id,
             num_frames,
             width,
             height,
             is_smoothed: flags & 0b1 != 0,
             codec,
-            deblocking: match flags & 0b100_0 {
+            deblocking: match flags & 0b111_0 {
                 0b000_0 => VideoDeblocking::UseVideoPacketValue,
                 0b001_0 => VideoDeblocking::None,
                 0b010_0 => VideoDeblocking::Level1,
@@ -2753,19 +2784,19 @@ impl<R: Read> Reader<R> {
                 0b101_0 => VideoDeblocking::Level4,
                 _ => return Err(Error::invalid_data("Invalid video deblocking value.")),
             },
-        }))
+        })
     }
 
-    fn read_video_frame(&mut self) -> Result<Tag> {
+    pub fn read_video_frame(&mut self) -> Result<VideoFrame> {
         let stream_id = self.read_character_id()?;
         let frame_num = self.read_u16()?;
         let mut data = vec![];
         self.input.read_to_end(&mut data)?;
-        Ok(Tag::VideoFrame(VideoFrame {
+        Ok(VideoFrame {
             stream_id,
             frame_num,
             data,
-        }))
+        })
     }
 
     fn read_define_bits_jpeg_3(&mut self, version: u8) -> Result<Tag> {
@@ -2776,9 +2807,14 @@ impl<R: Read> Reader<R> {
         } else {
             0.0
         };
-        let mut data = vec![];
-        data.resize(data_size, 0);
-        self.input.read_exact(&mut data)?;
+        let mut data = preallocated_vec(data_size);
+        self.input
+            .by_ref()
+            .take(data_size as u64)
+            .read_to_end(&mut data)?;
+        if data.len() < data_size {
+            return Err(Error::invalid_data("Unexpected end of JPEG data"));
+        }
         let mut alpha_data = vec![];
         self.input.read_to_end(&mut alpha_data)?;
         Ok(Tag::DefineBitsJpeg3(DefineBitsJpeg3 {
@@ -3005,6 +3041,12 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn read_sbits_too_many_bits() {
+        let mut reader = Reader::new(&[0xff; 8][..], 1);
+        assert!(reader.read_sbits(33).is_err());
+    }
+
     #[test]
     fn read_fbits() {
         assert_eq!(Reader::new(&[0][..], 1).read_fbits(5).unwrap(), 0f32);
@@ -3304,4 +3346,34 @@ pub mod tests {
             }
         }
     }
+
+    /// Ensure that lengths in a corrupt SWF return an error instead of panicking or
+    /// allocating the whole length.
+    #[test]
+    fn read_truncated_tags() {
+        let tag_header = |tag_code: u16, length: u32| {
+            let mut bytes = ((tag_code << 6) | 0x3f).to_le_bytes().to_vec();
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes
+        };
+
+        // Unknown tag longer than the data.
+        let tag_bytes = tag_header(1000, 0xffff_ffff);
+        assert!(Reader::new(&tag_bytes[..], 5).read_tag().is_err());
+
+        // DefineBinaryData shorter than its header.
+        let mut tag_bytes = tag_header(TagCode::DefineBinaryData as u16, 2);
+        tag_bytes.extend_from_slice(&[1, 0]);
+        assert!(Reader::new(&tag_bytes[..], 5).read_tag().is_err());
+
+        // DefineBitsJpeg3 with a JPEG longer than the tag.
+        let mut tag_bytes = tag_header(TagCode::DefineBitsJpeg3 as u16, 6);
+        tag_bytes.extend_from_slice(&[1, 0, 0xff, 0xff, 0xff, 0xff]);
+        assert!(Reader::new(&tag_bytes[..], 5).read_tag().is_err());
+    }
+
+    #[test]
+    fn read_invalid_swf_length() {
+        assert!(read_swf(&b"FWS\x0a\x04\x00\x00\x00"[..]).is_err());
+    }
 }
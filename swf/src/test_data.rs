@@ -2101,7 +2101,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                 blend_mode: BlendMode::Normal,
                 clip_actions: vec![],
                 is_image: false,
-                is_bitmap_cached: false,
+                is_bitmap_cached: None,
                 is_visible: true,
                 amf_data: None,
             })),
@@ -2128,7 +2128,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                     action_data: vec![150, 6, 0, 0, 99, 108, 105, 112, 0, 38, 0],
                 }],
                 is_image: false,
-                is_bitmap_cached: false,
+                is_bitmap_cached: None,
                 is_visible: true,
                 amf_data: None,
             })),
@@ -2170,7 +2170,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                     },
                 ],
                 is_image: false,
-                is_bitmap_cached: false,
+                is_bitmap_cached: None,
                 is_visible: true,
                 amf_data: None,
             })),
@@ -2203,7 +2203,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                 blend_mode: BlendMode::Normal,
                 clip_actions: vec![],
                 is_image: true,
-                is_bitmap_cached: false,
+                is_bitmap_cached: None,
                 is_visible: true,
                 amf_data: None,
             })),
@@ -2335,7 +2335,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                     },
                 ],
                 is_image: false,
-                is_bitmap_cached: true,
+                is_bitmap_cached: Some(true),
                 is_visible: false,
                 amf_data: None,
             })),
@@ -2369,7 +2369,7 @@ pub fn tag_tests() -> Vec<TagTestData> {
                 blend_mode: BlendMode::Normal,
                 clip_actions: vec![],
                 is_image: false,
-                is_bitmap_cached: false,
+                is_bitmap_cached: None,
                 is_visible: true,
                 amf_data: Some(vec![
                     10, 11, 1, 9, 116, 101, 115, 116, 6, 17, 84, 101, 115, 116, 105, 110, 103, 33,
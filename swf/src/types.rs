@@ -271,7 +271,7 @@ pub struct PlaceObject {
     pub blend_mode: BlendMode,
     pub clip_actions: Vec<ClipAction>,
     pub is_image: bool,
-    pub is_bitmap_cached: bool,
+    pub is_bitmap_cached: Option<bool>,
     pub is_visible: bool,
     pub amf_data: Option<Vec<u8>>,
 }
@@ -576,7 +576,7 @@ pub struct SymbolClassLink {
     pub class_name: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Shape {
     pub version: u8,
     pub id: CharacterId,
@@ -732,8 +732,9 @@ impl LineStyle {
             end_cap: LineCapStyle::Round,
             join_style: LineJoinStyle::Round,
             fill_style: None,
-            allow_scale_x: false,
-            allow_scale_y: false,
+            // Strokes always scaled before the scaling flags of LineStyle2.
+            allow_scale_x: true,
+            allow_scale_y: true,
             is_pixel_hinted: false,
             allow_close: true,
         }
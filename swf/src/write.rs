@@ -1975,7 +1975,7 @@ impl<W: Write> Writer<W> {
                         } else {
                             0
                         }
-                        | if place_object.is_bitmap_cached {
+                        | if place_object.is_bitmap_cached.is_some() {
                             0b100
                         } else {
                             0
@@ -2033,8 +2033,8 @@ impl<W: Write> Writer<W> {
                     writer.write_blend_mode(place_object.blend_mode)?;
                 }
 
-                if place_object.is_bitmap_cached {
-                    writer.write_u8(1)?;
+                if let Some(is_bitmap_cached) = place_object.is_bitmap_cached {
+                    writer.write_u8(if is_bitmap_cached { 1 } else { 0 })?;
                 }
 
                 if !place_object.is_visible {
@@ -1,8 +1,5 @@
 const {
-    FLASH_MIMETYPE,
-    FUTURESPLASH_MIMETYPE,
-    FLASH7_AND_8_MIMETYPE,
-    FLASH_MOVIE_MIMETYPE,
+    is_flash_mimetype,
     is_swf_filename,
     RufflePlayer,
 } = require("./ruffle-player.js");
@@ -17,7 +14,19 @@ module.exports = class RuffleEmbed extends RufflePlayer {
 
     connectedCallback() {
         super.connectedCallback();
-        this.stream_swf_url(this.attributes.src.value);
+        if (this.attributes.src) {
+            this.stream_swf_url(this.attributes.src.value);
+        }
+    }
+
+    parameters() {
+        let params = {};
+
+        for (let attrib of this.attributes) {
+            params[attrib.name] = attrib.value;
+        }
+
+        return params;
     }
 
     get src() {
@@ -25,7 +34,7 @@ module.exports = class RuffleEmbed extends RufflePlayer {
     }
 
     set src(srcval) {
-        this.attributes.src = srcval;
+        this.setAttribute("src", srcval);
     }
 
     static get observedAttributes() {
@@ -44,12 +53,7 @@ module.exports = class RuffleEmbed extends RufflePlayer {
         if (!elem.src) {
             return false;
         }
-        if (
-            elem.type === FLASH_MIMETYPE ||
-            elem.type === FUTURESPLASH_MIMETYPE ||
-            elem.type == FLASH7_AND_8_MIMETYPE ||
-            elem.type == FLASH_MOVIE_MIMETYPE
-        ) {
+        if (is_flash_mimetype(elem.type)) {
             return true;
         } else if (elem.type === undefined || elem.type === "") {
             return is_swf_filename(elem.src);
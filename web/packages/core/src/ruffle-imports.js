@@ -21,3 +21,177 @@ exports.copy_to_audio_buffer = function copy_to_audio_buffer(
         dst_buffer.set(right_data);
     }
 };
+
+/**
+ * The source of the audio worklet that plays Ruffle's mixed audio.
+ *
+ * The processor keeps a ring buffer of interleaved stereo samples, and asks the
+ * main thread for more whenever fewer than `LOW_WATER_FRAMES` are queued.
+ */
+const AUDIO_WORKLET_SOURCE = `
+const BUFFER_FRAMES = 8192;
+const LOW_WATER_FRAMES = 1024;
+const TARGET_FRAMES = 2048;
+
+class RuffleAudioProcessor extends AudioWorkletProcessor {
+    constructor() {
+        super();
+        this.buffer = new Float32Array(BUFFER_FRAMES * 2);
+        this.readIndex = 0;
+        this.queuedFrames = 0;
+        this.requestedFrames = 0;
+        this.port.onmessage = (event) => this.push(event.data);
+    }
+
+    push(samples) {
+        const numFrames = Math.min(
+            samples.length / 2,
+            BUFFER_FRAMES - this.queuedFrames
+        );
+        let writeIndex = (this.readIndex + this.queuedFrames * 2) % this.buffer.length;
+        for (let i = 0; i < numFrames * 2; i++) {
+            this.buffer[writeIndex] = samples[i];
+            writeIndex = (writeIndex + 1) % this.buffer.length;
+        }
+        this.queuedFrames += numFrames;
+        this.requestedFrames = Math.max(this.requestedFrames - samples.length / 2, 0);
+    }
+
+    process(inputs, outputs) {
+        const output = outputs[0];
+        const left = output[0];
+        const right = output.length > 1 ? output[1] : null;
+        for (let i = 0; i < left.length; i++) {
+            if (this.queuedFrames > 0) {
+                left[i] = this.buffer[this.readIndex];
+                if (right) {
+                    right[i] = this.buffer[this.readIndex + 1];
+                }
+                this.readIndex = (this.readIndex + 2) % this.buffer.length;
+                this.queuedFrames--;
+            } else {
+                left[i] = 0;
+                if (right) {
+                    right[i] = 0;
+                }
+            }
+        }
+
+        // Ask for more audio before the buffer runs dry.
+        const pendingFrames = this.queuedFrames + this.requestedFrames;
+        if (pendingFrames < LOW_WATER_FRAMES) {
+            const numFrames = TARGET_FRAMES - pendingFrames;
+            this.requestedFrames += numFrames;
+            this.port.postMessage(numFrames);
+        }
+        return true;
+    }
+}
+
+registerProcessor("ruffle-audio-processor", RuffleAudioProcessor);
+`;
+
+let audio_worklet_url = null;
+
+/**
+ * Loads the audio worklet into the given audio context, and creates a node
+ * that plays the samples returned by `request_samples`.
+ * Rejects if the browser doesn't support `AudioWorklet`.
+ */
+exports.create_audio_worklet_node = async function create_audio_worklet_node(
+    context,
+    request_samples
+) {
+    if (!context.audioWorklet || typeof AudioWorkletNode === "undefined") {
+        throw new Error("AudioWorklet is not supported");
+    }
+    if (!audio_worklet_url) {
+        const blob = new Blob([AUDIO_WORKLET_SOURCE], {
+            type: "application/javascript",
+        });
+        audio_worklet_url = URL.createObjectURL(blob);
+    }
+    await context.audioWorklet.addModule(audio_worklet_url);
+
+    const node = new AudioWorkletNode(context, "ruffle-audio-processor", {
+        numberOfInputs: 0,
+        outputChannelCount: [2],
+    });
+    node.port.onmessage = (event) => {
+        const samples = request_samples(event.data);
+        node.port.postMessage(samples, [samples.buffer]);
+    };
+    return node;
+};
+
+const IDB_NAME = "ruffle";
+const IDB_STORE = "shared-objects";
+
+/**
+ * Opens the IndexedDB database that holds shared objects too large for
+ * `localStorage`.
+ */
+function open_storage_db() {
+    return new Promise((resolve, reject) => {
+        if (typeof indexedDB === "undefined") {
+            reject(new Error("IndexedDB is not supported"));
+            return;
+        }
+        const request = indexedDB.open(IDB_NAME, 1);
+        request.onupgradeneeded = () => {
+            request.result.createObjectStore(IDB_STORE);
+        };
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+}
+
+/**
+ * Runs `operation` on the shared object store, and resolves with the result of
+ * its request once the transaction completes.
+ */
+async function with_storage_store(mode, operation) {
+    const db = await open_storage_db();
+    return new Promise((resolve, reject) => {
+        const transaction = db.transaction(IDB_STORE, mode);
+        const request = operation(transaction.objectStore(IDB_STORE));
+        transaction.oncomplete = () => {
+            db.close();
+            resolve(request.result);
+        };
+        transaction.onerror = () => {
+            db.close();
+            reject(transaction.error);
+        };
+    });
+}
+
+/**
+ * Loads the entries whose keys start with `prefix`, as an array of
+ * `[key, Uint8Array]` pairs.
+ */
+exports.storage_db_load = async function storage_db_load(prefix) {
+    const range = IDBKeyRange.bound(prefix, prefix + "\uffff");
+    const keys = await with_storage_store("readonly", (store) =>
+        store.getAllKeys(range)
+    );
+    const values = await with_storage_store("readonly", (store) =>
+        store.getAll(range)
+    );
+    return keys.map((key, i) => [key, values[i]]);
+};
+
+/**
+ * Saves an entry. The data is copied, as it may be a view into WebAssembly memory.
+ */
+exports.storage_db_put = function storage_db_put(key, data) {
+    const copy = data.slice();
+    return with_storage_store("readwrite", (store) => store.put(copy, key));
+};
+
+/**
+ * Deletes an entry.
+ */
+exports.storage_db_delete = function storage_db_delete(key) {
+    return with_storage_store("readwrite", (store) => store.delete(key));
+};
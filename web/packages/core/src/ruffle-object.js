@@ -1,9 +1,6 @@
 const {
-    FLASH_MIMETYPE,
-    FUTURESPLASH_MIMETYPE,
-    FLASH7_AND_8_MIMETYPE,
-    FLASH_MOVIE_MIMETYPE,
     FLASH_ACTIVEX_CLASSID,
+    is_flash_mimetype,
     is_swf_filename,
     RufflePlayer,
 } = require("./ruffle-player.js");
@@ -20,66 +17,72 @@ module.exports = class RuffleObject extends RufflePlayer {
         this.params = RuffleObject.params_of(this);
 
         //Kick off the SWF download.
-        if (this.attributes.data) {
-            this.stream_swf_url(this.attributes.data.value);
-        } else if (this.params.movie) {
-            this.stream_swf_url(this.params.movie);
+        let url = RuffleObject.movie_url_of(this);
+        if (url) {
+            this.stream_swf_url(url);
         }
     }
 
+    parameters() {
+        return RuffleObject.params_of(this);
+    }
+
     get data() {
         return this.attributes.data.value;
     }
 
     set data(href) {
-        this.attributes.data = href;
+        this.setAttribute("data", href);
     }
 
     static is_interdictable(elem) {
-        if (!elem.data) {
-            let has_movie = false;
-            let params = elem.getElementsByTagName("param");
-            for (let i = 0; i < params.length; i++) {
-                if (params[i].name == "movie" && params[i].value) {
-                    has_movie = true;
-                }
-            }
-            if (!has_movie) {
-                return false;
-            }
+        let url = RuffleObject.movie_url_of(elem);
+        if (!url) {
+            return false;
         }
-        if (
-            elem.type === FLASH_MIMETYPE ||
-            elem.type === FUTURESPLASH_MIMETYPE ||
-            elem.type == FLASH7_AND_8_MIMETYPE ||
-            elem.type == FLASH_MOVIE_MIMETYPE
-        ) {
+        if (is_flash_mimetype(elem.type)) {
             return true;
         } else if (
             elem.attributes &&
             elem.attributes.classid &&
-            elem.attributes.classid.value === FLASH_ACTIVEX_CLASSID
+            elem.attributes.classid.value.toLowerCase() ===
+                FLASH_ACTIVEX_CLASSID.toLowerCase()
         ) {
             return true;
         } else if (
             (elem.type === undefined || elem.type === "") &&
             elem.attributes.classid === undefined
         ) {
-            let params = RuffleObject.params_of(elem);
-            if (params && params.movie) {
-                return is_swf_filename(params.movie);
-            }
+            return is_swf_filename(url);
         }
 
         return false;
     }
 
+    /*
+     * The URL of the movie of an object element: its `data` attribute, or
+     * its `movie` or `src` param, as used by the ActiveX control.
+     */
+    static movie_url_of(elem) {
+        if (elem.attributes.data && elem.attributes.data.value) {
+            return elem.attributes.data.value;
+        }
+        let params = RuffleObject.params_of(elem);
+        return params.movie || params.src || null;
+    }
+
+    /*
+     * The params of an object element, with lowercase names, since old pages
+     * are inconsistent about the case of names such as `FlashVars`.
+     */
     static params_of(elem) {
         let params = {};
 
         for (let param of elem.children) {
-            if (param.constructor === HTMLParamElement) {
-                params[param.name] = param.value;
+            // Check the node name rather than the constructor, as elements
+            // from other frames have that frame's constructors.
+            if (param.nodeName.toLowerCase() === "param" && param.name) {
+                params[param.name.toLowerCase()] = param.value;
             }
         }
 
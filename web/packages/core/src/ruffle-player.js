@@ -10,6 +10,11 @@ exports.FLASH_ACTIVEX_CLASSID = "clsid:D27CDB6E-AE6D-11cf-96B8-444553540000";
 
 const DIMENSION_REGEX = /^\s*(\d+(\.\d+)?(%)?)/;
 
+// How many frames of timings the profiler keeps, and how often in milliseconds
+// its overlay is updated.
+const PROFILER_FRAMES = 120;
+const PROFILER_INTERVAL = 500;
+
 exports.RufflePlayer = class RufflePlayer extends HTMLElement {
     constructor(...args) {
         let self = super(...args);
@@ -20,6 +25,23 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         self.dynamic_styles = self.shadow.getElementById("dynamic_styles");
         self.container = self.shadow.getElementById("container");
         self.play_button = self.shadow.getElementById("play_button");
+        self.loading_bar = self.shadow.getElementById("loading_bar");
+        self.context_menu = self.shadow.getElementById("context_menu");
+        self.fullscreen_button = self.shadow.getElementById(
+            "fullscreen_button"
+        );
+        self.fullscreen_button.addEventListener("click", () =>
+            self.isFullscreen ? self.exitFullscreen() : self.enterFullscreen()
+        );
+        self.fullscreen_changed = self.fullscreen_changed.bind(self);
+        self.profiler_overlay = self.shadow.getElementById("profiler");
+        self.profiler_interval = null;
+        self.last_profiled_frame = 0;
+        self.addEventListener(
+            "contextmenu",
+            self.open_context_menu.bind(self)
+        );
+        self.hide_context_menu = self.hide_context_menu.bind(self);
         if (self.play_button) {
             self.play_button.addEventListener(
                 "click",
@@ -28,6 +50,21 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         }
 
         self.instance = null;
+        self.flashvars = null;
+
+        /*
+         * Called with the command and arguments of each FSCommand the movie
+         * sends that Ruffle doesn't handle itself, like a page's
+         * `DoFSCommand` handler for the Flash plugin.
+         */
+        self.onFSCommand = null;
+
+        /*
+         * Called with each message of `trace()` and each error the movie
+         * reports to its author, and the level of the message, "trace" or
+         * "warning". Messages are logged to the console unless this is set.
+         */
+        self.onTrace = null;
 
         self.Ruffle = load_ruffle();
 
@@ -36,6 +73,13 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
 
     connectedCallback() {
         this.update_styles();
+        document.addEventListener("fullscreenchange", this.fullscreen_changed);
+        document.addEventListener(
+            "webkitfullscreenchange",
+            this.fullscreen_changed
+        );
+        // A refused request leaves the movie's display state to be undone.
+        document.addEventListener("fullscreenerror", this.fullscreen_changed);
     }
 
     static get observedAttributes() {
@@ -49,6 +93,19 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
     }
 
     disconnectedCallback() {
+        document.removeEventListener(
+            "fullscreenchange",
+            this.fullscreen_changed
+        );
+        document.removeEventListener(
+            "webkitfullscreenchange",
+            this.fullscreen_changed
+        );
+        document.removeEventListener(
+            "fullscreenerror",
+            this.fullscreen_changed
+        );
+        this.stop_profiler_overlay();
         if (this.instance) {
             this.instance.destroy();
             this.instance = null;
@@ -117,7 +174,6 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
     }
 
     async stream_swf_url(url) {
-        //TODO: Actually stream files...
         try {
             if (this.isConnected && !this.is_unused_fallback_object()) {
                 let abs_url = new URL(url, window.location.href).toString();
@@ -126,8 +182,11 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
                 let response = await fetch(abs_url);
 
                 if (response.ok) {
-                    let data = await response.arrayBuffer();
-                    await this.play_swf_data(data);
+                    try {
+                        await this.stream_response(response, abs_url);
+                    } finally {
+                        this.loading_bar.style.display = "none";
+                    }
                     console.log("Playing " + url);
                 } else {
                     console.error(
@@ -150,19 +209,409 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         }
     }
 
+    /*
+     * Plays the body of a fetch response as it downloads, showing its progress
+     * in the loading bar and dispatching a `progress` event to the page for
+     * each chunk, so that pages can show their own loading screens.
+     *
+     * The player is created as soon as the start of the movie has arrived, and
+     * each later chunk is passed on to it, so the movie can start playing its
+     * first frames while the rest of it downloads.
+     */
+    async stream_response(response, url) {
+        // Compressed responses report their compressed length, so the total is
+        // unknown once more bytes than that have been read.
+        let total = parseInt(response.headers.get("Content-Length"), 10) || 0;
+        this.loading_bar.style.display = "block";
+        this.report_progress(0, total);
+
+        if (!response.body || !response.body.getReader) {
+            // This browser can't stream responses.
+            let data = await response.arrayBuffer();
+            this.report_progress(data.byteLength, data.byteLength);
+            await this.play_swf_data(data, url);
+            return;
+        }
+
+        let Ruffle = await this.Ruffle.catch(function (e) {
+            console.error("Serious error loading Ruffle: " + e);
+            throw e;
+        });
+        let download = Ruffle.start_download(url);
+        let instance = null;
+        let reader = response.body.getReader();
+        let loaded = 0;
+        for (;;) {
+            let { done, value } = await reader.read();
+            if (done) {
+                break;
+            }
+            loaded += value.length;
+            if (instance) {
+                instance.append_data(value);
+            } else if (download.append(value)) {
+                instance = await this.create_instance((Ruffle) =>
+                    Ruffle.new_streaming(
+                        this.container,
+                        download,
+                        this.parameters()
+                    )
+                );
+                if (!instance) {
+                    reader.cancel();
+                    return;
+                }
+            }
+            if (instance && instance !== this.instance) {
+                // Another movie was loaded into this element in the meantime.
+                reader.cancel();
+                return;
+            }
+            this.report_progress(loaded, loaded <= total ? total : 0);
+        }
+
+        if (instance) {
+            instance.finish_download();
+        } else {
+            // Some movies, such as LZMA compressed ones, can only be played
+            // once the whole file has arrived.
+            download.finish();
+            await this.create_instance((Ruffle) =>
+                Ruffle.new_streaming(
+                    this.container,
+                    download,
+                    this.parameters()
+                )
+            );
+        }
+    }
+
+    report_progress(loaded, total) {
+        let progress = this.loading_bar.firstElementChild;
+        if (total > 0) {
+            this.loading_bar.classList.remove("indeterminate");
+            progress.style.width = (loaded / total) * 100 + "%";
+        } else {
+            this.loading_bar.classList.add("indeterminate");
+            progress.style.width = "";
+        }
+        this.dispatchEvent(
+            new ProgressEvent("progress", {
+                lengthComputable: total > 0,
+                loaded: loaded,
+                total: total,
+            })
+        );
+    }
+
+    /*
+     * The items of the context menu: the items of the movie, which include the
+     * ones it adds with `ContextMenu`, followed by the items of the player.
+     */
+    context_menu_items() {
+        let items = [];
+        if (this.instance) {
+            let movie_items = this.instance.prepare_context_menu();
+            movie_items.forEach((item, index) => {
+                items.push(
+                    Object.assign({}, item, {
+                        onClick: () =>
+                            this.instance.run_context_menu_callback(index),
+                    })
+                );
+            });
+        }
+        items.push({
+            caption: this.isPlaying ? "Pause" : "Resume",
+            enabled: this.instance !== null,
+            separatorBefore: items.length > 0,
+            onClick: () => (this.isPlaying ? this.pause() : this.play()),
+        });
+        items.push({
+            caption: this.isFullscreen ? "Exit fullscreen" : "Enter fullscreen",
+            enabled: true,
+            onClick: () =>
+                this.isFullscreen
+                    ? this.exitFullscreen()
+                    : this.enterFullscreen(),
+        });
+        items.push({
+            caption: this.isProfilerVisible ? "Hide profiler" : "Show profiler",
+            enabled: this.instance !== null,
+            onClick: () => this.setProfilerVisible(!this.isProfilerVisible),
+        });
+        items.push({
+            caption: "About Ruffle",
+            enabled: true,
+            separatorBefore: true,
+            onClick: () => window.open("https://ruffle.rs", "_blank"),
+        });
+        return items;
+    }
+
+    /*
+     * Shows the context menu in place of the browser's, which would otherwise
+     * cover movies that are played with the right mouse button.
+     */
+    open_context_menu(event) {
+        event.preventDefault();
+        event.stopPropagation();
+
+        this.context_menu.textContent = "";
+        for (let item of this.context_menu_items()) {
+            if (item.separatorBefore) {
+                let separator = document.createElement("li");
+                separator.className = "menu_separator";
+                separator.appendChild(document.createElement("hr"));
+                this.context_menu.appendChild(separator);
+            }
+            let element = document.createElement("li");
+            element.className = "menu_item";
+            element.textContent = item.caption;
+            if (item.checked) {
+                element.classList.add("checked");
+            }
+            if (item.enabled) {
+                element.addEventListener("click", item.onClick);
+            } else {
+                element.classList.add("disabled");
+            }
+            this.context_menu.appendChild(element);
+        }
+
+        // Show the menu at the mouse, moved back inside the player if needed.
+        this.context_menu.style.display = "block";
+        let rect = this.getBoundingClientRect();
+        let x = Math.min(
+            event.clientX - rect.left,
+            rect.width - this.context_menu.offsetWidth
+        );
+        let y = Math.min(
+            event.clientY - rect.top,
+            rect.height - this.context_menu.offsetHeight
+        );
+        this.context_menu.style.left = Math.max(x, 0) + "px";
+        this.context_menu.style.top = Math.max(y, 0) + "px";
+
+        // Any click closes the menu, after running the item that was clicked.
+        window.addEventListener("click", this.hide_context_menu, {
+            once: true,
+        });
+    }
+
+    hide_context_menu() {
+        this.context_menu.style.display = "none";
+    }
+
+    /*
+     * Whether this player fills the screen.
+     */
+    get isFullscreen() {
+        let element =
+            document.fullscreenElement || document.webkitFullscreenElement;
+        return element === this;
+    }
+
+    /*
+     * Makes this player fill the screen. Like the Flash plugin, browsers only
+     * allow this in response to a click or key press.
+     *
+     * The movie is told through `Stage.displayState`, and resized to the
+     * screen on the next frame, which sends `Stage.onResize` to movies that
+     * don't scale.
+     */
+    enterFullscreen() {
+        // The container is what the movie itself makes fill the screen.
+        if (this.container.requestFullscreen) {
+            this.container.requestFullscreen();
+        } else if (this.container.webkitRequestFullscreen) {
+            this.container.webkitRequestFullscreen();
+        }
+    }
+
+    exitFullscreen() {
+        if (document.exitFullscreen) {
+            document.exitFullscreen();
+        } else if (document.webkitExitFullscreen) {
+            document.webkitExitFullscreen();
+        }
+    }
+
+    /*
+     * Keeps the movie's `Stage.displayState` in step with the page, however
+     * fullscreen was entered or left, such as with the Escape key.
+     */
+    fullscreen_changed() {
+        if (this.instance) {
+            this.instance.set_full_screen(this.isFullscreen);
+        }
+    }
+
     play_button_clicked() {
+        this.play();
+    }
+
+    /*
+     * Starts the movie, or resumes it if it was paused.
+     */
+    play() {
         if (this.instance) {
             this.instance.play();
+            this.instance.set_paused(false);
             if (this.play_button) {
                 this.play_button.style.display = "none";
             }
         }
     }
 
-    async play_swf_data(data) {
-        if (this.isConnected && !this.is_unused_fallback_object()) {
-            console.log("Got SWF data");
+    /*
+     * Pauses the movie and its sound, keeping its state.
+     */
+    pause() {
+        if (this.instance) {
+            this.instance.set_paused(true);
+        }
+    }
+
+    /*
+     * Whether the movie is running.
+     */
+    get isPlaying() {
+        return this.instance ? this.instance.is_playing() : false;
+    }
+
+    /*
+     * The volume of the movie, from 0 to 1.
+     */
+    get volume() {
+        return this.instance ? this.instance.volume() : 1;
+    }
+
+    set volume(value) {
+        if (this.instance) {
+            this.instance.set_volume(value);
+        }
+    }
+
+    /*
+     * An object describing the movie, with its `width` and `height` in pixels,
+     * its `frameRate` and its `swfVersion`, or null if no movie is loaded.
+     */
+    get metadata() {
+        return this.instance ? this.instance.metadata() : null;
+    }
 
+    /*
+     * Defines variables on the movie's _root from a URL-encoded string, such as
+     * "name=value&other=value", as the `flashvars` embed parameter does.
+     * Variables set before the movie is loaded are defined when it starts.
+     */
+    setFlashvars(flashvars) {
+        this.flashvars = flashvars;
+        if (this.instance) {
+            this.instance.set_flash_vars(flashvars);
+        }
+    }
+
+    /*
+     * Whether the profiler overlay is shown.
+     */
+    get isProfilerVisible() {
+        return this.profiler_interval !== null;
+    }
+
+    /*
+     * Shows or hides an overlay with how long each frame takes, averaged over
+     * the last half second and broken down into ActionScript, tags,
+     * tessellation, rendering, audio and garbage collection, along with the
+     * memory in use.
+     */
+    setProfilerVisible(visible) {
+        if (visible && !this.isProfilerVisible && this.instance) {
+            this.instance.start_profiling(PROFILER_FRAMES);
+            this.last_profiled_frame = 0;
+            this.profiler_overlay.textContent = "Profiling...";
+            this.profiler_overlay.style.display = "block";
+            this.profiler_interval = setInterval(
+                this.update_profiler_overlay.bind(this),
+                PROFILER_INTERVAL
+            );
+        } else if (!visible) {
+            this.stop_profiler_overlay();
+        }
+    }
+
+    /*
+     * The timings of the last frames, oldest first, while the profiler is
+     * shown. Each has the `frame` number and the milliseconds spent on
+     * `actionScript`, `tags`, `tessellation`, `rendering`, `audio` and
+     * `garbageCollection`, and in `total`.
+     */
+    frameTimings() {
+        return this.instance ? this.instance.frame_timings() : [];
+    }
+
+    update_profiler_overlay() {
+        let timings = this.frameTimings().filter(
+            (timing) => timing.frame > this.last_profiled_frame
+        );
+        if (timings.length === 0) {
+            return;
+        }
+        this.last_profiled_frame = timings[timings.length - 1].frame;
+        let average = (name) =>
+            (
+                timings.reduce((sum, timing) => sum + timing[name], 0) /
+                timings.length
+            ).toFixed(2);
+        let memory = this.instance.gc_stats();
+        this.profiler_overlay.textContent = [
+            `Frame: ${average("total")} ms`,
+            `ActionScript: ${average("actionScript")} ms`,
+            `Tags: ${average("tags")} ms`,
+            `Tessellation: ${average("tessellation")} ms`,
+            `Rendering: ${average("rendering")} ms`,
+            `Audio: ${average("audio")} ms`,
+            `GC: ${average("garbageCollection")} ms`,
+            `Memory: ${Math.round(memory.allocatedBytes / 1024)} KiB`,
+        ].join("\n");
+    }
+
+    stop_profiler_overlay() {
+        if (this.profiler_interval !== null) {
+            clearInterval(this.profiler_interval);
+            this.profiler_interval = null;
+        }
+        if (this.instance) {
+            this.instance.stop_profiling();
+        }
+        this.profiler_overlay.style.display = "none";
+    }
+
+    /*
+     * Plays the given SWF data. `url` is the absolute URL it was loaded from,
+     * if any, which the movie sees as its `_url`.
+     */
+    async play_swf_data(data, url) {
+        console.log("Got SWF data");
+        await this.create_instance((Ruffle) =>
+            Ruffle.new(
+                this.container,
+                new Uint8Array(data),
+                this.parameters(),
+                url
+            )
+        );
+    }
+
+    /*
+     * Replaces the current Ruffle instance with the one returned by
+     * `construct`, which is passed the `Ruffle` class once it has loaded.
+     * Returns the new instance, or null if this element can't play movies.
+     */
+    async create_instance(construct) {
+        if (this.isConnected && !this.is_unused_fallback_object()) {
+            this.stop_profiler_overlay();
             if (this.instance) {
                 this.instance.destroy();
                 this.instance = null;
@@ -174,19 +623,47 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
                 throw e;
             });
 
-            this.instance = Ruffle.new(this.container, new Uint8Array(data));
+            this.instance = construct(Ruffle);
             console.log("New Ruffle instance created.");
+            this.fullscreen_button.style.display = "block";
+
+            if (this.flashvars !== null) {
+                this.instance.set_flash_vars(this.flashvars);
+            }
+            this.instance.set_fs_command_callback((command, args) => {
+                if (typeof this.onFSCommand !== "function") {
+                    return false;
+                }
+                return this.onFSCommand(command, args) !== false;
+            });
+            this.instance.set_log_callback((message, level) => {
+                if (typeof this.onTrace !== "function") {
+                    return false;
+                }
+                return this.onTrace(message, level) !== false;
+            });
 
             if (this.play_button) {
                 this.play_button.style.display = "block";
             }
+            return this.instance;
         } else {
             console.warn(
                 "Ignoring attempt to play a disconnected or suspended Ruffle element"
             );
+            return null;
         }
     }
 
+    /*
+     * The embed parameters of this element, such as `flashvars` and `quality`,
+     * as an object of name/value strings.
+     * Overridden by the polyfill elements, which read them from the page.
+     */
+    parameters() {
+        return {};
+    }
+
     /*
      * Copies attributes and children from another element to this player element.
      * Used by the polyfill elements, RuffleObject and RuffleEmbed.
@@ -242,6 +719,23 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
     }
 };
 
+/*
+ * Returns whether the given MIME type is one used for Flash content.
+ * MIME types are case-insensitive, and old pages often capitalise them.
+ */
+exports.is_flash_mimetype = function is_flash_mimetype(type) {
+    if (!type || typeof type !== "string") {
+        return false;
+    }
+    type = type.trim().toLowerCase();
+    return (
+        type === exports.FLASH_MIMETYPE ||
+        type === exports.FUTURESPLASH_MIMETYPE ||
+        type === exports.FLASH7_AND_8_MIMETYPE ||
+        type === exports.FLASH_MOVIE_MIMETYPE
+    );
+};
+
 /*
  * Returns whether the given filename ends in an "swf" extension.
  */
@@ -42,10 +42,123 @@ ruffle_shadow_tmpl.innerHTML = `
         #play_button:hover .icon {
             filter: brightness(1.3);
         }
+
+        #loading_bar {
+            position: absolute;
+            top: 50%;
+            left: 10%;
+            width: 80%;
+            height: 8px;
+            margin-top: -4px;
+            border-radius: 4px;
+            background: rgba(255, 255, 255, 0.2);
+            overflow: hidden;
+            display: none;
+        }
+
+        #loading_bar .progress {
+            width: 0;
+            height: 100%;
+            background: linear-gradient(to right, #FDA138, #FD3A40);
+        }
+
+        /* The size of the movie is unknown, so the bar just sweeps back and forth. */
+        #loading_bar.indeterminate .progress {
+            width: 25%;
+            animation: sweep 1.5s ease-in-out infinite alternate;
+        }
+
+        @keyframes sweep {
+            from { margin-left: 0; }
+            to { margin-left: 75%; }
+        }
+
+        #fullscreen_button {
+            position: absolute;
+            right: 8px;
+            bottom: 8px;
+            width: 24px;
+            height: 24px;
+            padding: 4px;
+            border-radius: 4px;
+            background: rgba(0, 0, 0, 0.5);
+            cursor: pointer;
+            opacity: 0;
+            transition: opacity 0.2s;
+            display: none;
+        }
+
+        #container:hover #fullscreen_button {
+            opacity: 0.8;
+        }
+
+        #fullscreen_button svg {
+            width: 100%;
+            height: 100%;
+        }
+
+        #profiler {
+            position: absolute;
+            left: 4px;
+            top: 4px;
+            padding: 4px 6px;
+            background: rgba(0, 0, 0, 0.7);
+            color: #fff;
+            font: 11px monospace;
+            white-space: pre;
+            pointer-events: none;
+            display: none;
+        }
+
+        #context_menu {
+            position: absolute;
+            display: none;
+            margin: 0;
+            padding: 4px 0;
+            list-style: none;
+            min-width: 160px;
+            background: #fafafa;
+            border: 1px solid #aaa;
+            box-shadow: 2px 2px 4px rgba(0, 0, 0, 0.3);
+            font: 13px sans-serif;
+            color: #222;
+            z-index: 1;
+        }
+
+        #context_menu .menu_item {
+            position: relative;
+            padding: 4px 16px 4px 24px;
+            cursor: default;
+            white-space: nowrap;
+        }
+
+        #context_menu .menu_item:not(.disabled):hover {
+            background: #ddd;
+        }
+
+        #context_menu .menu_item.disabled {
+            color: #999;
+        }
+
+        #context_menu .menu_item.checked::before {
+            content: "\\2713";
+            position: absolute;
+            left: 8px;
+        }
+
+        #context_menu .menu_separator hr {
+            margin: 4px 0;
+            border: none;
+            border-top: 1px solid #ccc;
+        }
     </style>
     <style id="dynamic_styles"></style>
 
     <div id="container">
+        <div id="loading_bar"><div class="progress"></div></div>
+        <div id="fullscreen_button" title="Fullscreen"><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><path fill="none" stroke="#FFF" stroke-width="2" d="M3 9V3h6M15 3h6v6M21 15v6h-6M9 21H3v-6"/></svg></div>
+        <div id="profiler"></div>
+        <ul id="context_menu"></ul>
         <div id="play_button"><div class="icon"><svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" preserveAspectRatio="xMidYMid" viewBox="0 0 250 250" style="width:100%;height:100%;"><defs><linearGradient id="a" gradientUnits="userSpaceOnUse" x1="125" y1="0" x2="125" y2="250" spreadMethod="pad"><stop offset="0%" stop-color="#FDA138"/><stop offset="100%" stop-color="#FD3A40"/></linearGradient><g id="b"><path fill="url(#a)" d="M250 125q0-52-37-88-36-37-88-37T37 37Q0 73 0 125t37 88q36 37 88 37t88-37q37-36 37-88M87 195V55l100 70-100 70z"/><path fill="#FFF" d="M87 55v140l100-70L87 55z"/></g></defs><use xlink:href="#b"/></svg></div></div>
     </div>
 `;
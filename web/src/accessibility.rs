@@ -0,0 +1,138 @@
+use ruffle_core::backend::accessibility::{
+    AccessibilityBackend, AccessibilityNode, AccessibilityRole,
+};
+use ruffle_web_common::JsResult;
+use std::error::Error;
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element, HtmlElement};
+
+/// Accessibility backend that mirrors the accessibility tree as ARIA elements.
+///
+/// The elements are visually hidden next to the canvas, where screen readers
+/// can find them.
+pub struct WebAccessibilityBackend {
+    document: Document,
+    container: HtmlElement,
+    nodes: Vec<AccessibilityNode>,
+}
+
+impl WebAccessibilityBackend {
+    pub fn new(document: Document, parent: &HtmlElement) -> Result<Self, Box<dyn Error>> {
+        let container: HtmlElement = document
+            .create_element("div")
+            .into_js_result()?
+            .dyn_into()
+            .map_err(|_| "Expected HtmlElement")?;
+        let style = container.style();
+        style
+            .set_property("position", "absolute")
+            .into_js_result()?;
+        style.set_property("width", "1px").into_js_result()?;
+        style.set_property("height", "1px").into_js_result()?;
+        style.set_property("overflow", "hidden").into_js_result()?;
+        style
+            .set_property("clip", "rect(0 0 0 0)")
+            .into_js_result()?;
+        parent.append_child(&container).into_js_result()?;
+
+        Ok(Self {
+            document,
+            container,
+            nodes: vec![],
+        })
+    }
+
+    /// Creates the ARIA element for an accessibility node and its children.
+    fn create_element(&self, node: &AccessibilityNode) -> Result<Element, Box<dyn Error>> {
+        let element = match node.role {
+            AccessibilityRole::Group => {
+                let element = self.document.create_element("div").into_js_result()?;
+                element.set_attribute("role", "group").into_js_result()?;
+                element
+                    .set_attribute("aria-label", &node.name)
+                    .into_js_result()?;
+                element
+            }
+            AccessibilityRole::Button => {
+                let element = self.document.create_element("button").into_js_result()?;
+                element.set_text_content(Some(&node.name));
+                element
+            }
+            AccessibilityRole::StaticText => {
+                let element = self.document.create_element("p").into_js_result()?;
+                element.set_text_content(Some(&node.name));
+                element
+            }
+            AccessibilityRole::InputText => {
+                // The movie can't receive input from here, so the field is read-only.
+                let element = self.document.create_element("input").into_js_result()?;
+                element.set_attribute("readonly", "").into_js_result()?;
+                element
+                    .set_attribute("aria-label", &node.name)
+                    .into_js_result()?;
+                element
+                    .set_attribute("value", node.value.as_deref().unwrap_or_default())
+                    .into_js_result()?;
+                element
+            }
+        };
+
+        if !node.description.is_empty() {
+            element
+                .set_attribute("aria-description", &node.description)
+                .into_js_result()?;
+        }
+        if !node.shortcut.is_empty() {
+            element
+                .set_attribute("aria-keyshortcuts", &node.shortcut)
+                .into_js_result()?;
+        }
+        for child in &node.children {
+            let child = self.create_element(child)?;
+            element.append_child(&child).into_js_result()?;
+        }
+        Ok(element)
+    }
+
+    fn replace_elements(&self) -> Result<(), Box<dyn Error>> {
+        self.container.set_inner_html("");
+        for node in &self.nodes {
+            let element = self.create_element(node)?;
+            self.container.append_child(&element).into_js_result()?;
+        }
+        Ok(())
+    }
+}
+
+impl AccessibilityBackend for WebAccessibilityBackend {
+    fn is_active(&self) -> bool {
+        // Browsers don't reveal whether a screen reader is running, so always expose the tree.
+        true
+    }
+
+    fn update_tree(&mut self, nodes: Vec<AccessibilityNode>) {
+        if same_elements(&nodes, &self.nodes) {
+            return;
+        }
+
+        self.nodes = nodes;
+        if let Err(e) = self.replace_elements() {
+            log::error!("Unable to update accessibility elements: {}", e);
+        }
+    }
+}
+
+/// Returns whether two trees create the same ARIA elements.
+///
+/// Bounds are ignored, so that moving objects don't recreate the elements every frame.
+fn same_elements(a: &[AccessibilityNode], b: &[AccessibilityNode]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.role == b.role
+                && a.name == b.name
+                && a.description == b.description
+                && a.shortcut == b.shortcut
+                && a.value == b.value
+                && same_elements(&a.children, &b.children)
+        })
+}
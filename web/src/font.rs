@@ -0,0 +1,88 @@
+use ruffle_core::backend::font::{self, FontBackend};
+use ruffle_core::swf;
+use ruffle_web_common::JsResult;
+use std::error::Error;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, Document, HtmlCanvasElement};
+
+/// The string measured to detect whether a font is installed.
+/// Wide and narrow glyphs make width differences between fonts more pronounced.
+const TEST_STRING: &str = "mmmmmmmmmmlli1WWW";
+
+/// The generic CSS families that a font is measured against.
+const BASE_FAMILIES: [&str; 3] = ["monospace", "sans-serif", "serif"];
+
+/// Font backend that detects installed fonts by measuring text on a canvas.
+///
+/// Browsers do not expose font outlines, so fonts can't be loaded as glyphs and
+/// device text is rendered with the built-in device font.
+pub struct WebFontBackend {
+    context: CanvasRenderingContext2d,
+    base_widths: Vec<f64>,
+}
+
+impl WebFontBackend {
+    pub fn new(document: &Document) -> Result<Self, Box<dyn Error>> {
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .into_js_result()?
+            .dyn_into()
+            .map_err(|_| "Expected HtmlCanvasElement")?;
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")
+            .into_js_result()?
+            .ok_or("Could not create context")?
+            .dyn_into()
+            .map_err(|_| "Expected CanvasRenderingContext2d")?;
+
+        let mut backend = Self {
+            context,
+            base_widths: vec![],
+        };
+        backend.base_widths = BASE_FAMILIES
+            .iter()
+            .map(|family| backend.measure(family))
+            .collect();
+        Ok(backend)
+    }
+
+    /// Measures the width of the test string in the given CSS font family list.
+    fn measure(&self, families: &str) -> f64 {
+        self.context.set_font(&format!("72px {}", families));
+        self.context
+            .measure_text(TEST_STRING)
+            .map(|metrics| metrics.width())
+            .unwrap_or(0.0)
+    }
+
+    /// Returns whether a font family is installed.
+    ///
+    /// The browser falls back to the generic family when a font is missing, so an
+    /// installed font is detected when its width differs from any generic family.
+    fn is_font_installed(&self, name: &str) -> bool {
+        BASE_FAMILIES
+            .iter()
+            .zip(&self.base_widths)
+            .any(|(family, base_width)| {
+                let width = self.measure(&format!("\"{}\", {}", name, family));
+                (width - base_width).abs() > std::f64::EPSILON
+            })
+    }
+}
+
+impl FontBackend for WebFontBackend {
+    fn font_names(&self) -> Vec<String> {
+        // Browsers can't enumerate fonts, so check for the fonts we map device fonts to.
+        ["_sans", "_serif", "_typewriter"]
+            .iter()
+            .filter_map(|name| font::device_font_families(name))
+            .flat_map(|families| families.iter())
+            .filter(|family| self.is_font_installed(family))
+            .map(|family| family.to_string())
+            .collect()
+    }
+
+    fn load_font(&mut self, _name: &str, _is_bold: bool, _is_italic: bool) -> Option<swf::Font> {
+        None
+    }
+}
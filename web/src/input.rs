@@ -1,8 +1,11 @@
+use js_sys::{Function, Promise, Reflect};
 use ruffle_core::backend::input::{InputBackend, MouseCursor};
 use ruffle_core::events::KeyCode;
 use ruffle_web_common::JsResult;
 use std::collections::HashSet;
-use web_sys::HtmlCanvasElement;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{HtmlCanvasElement, HtmlTextAreaElement, KeyboardEvent};
 
 /// An implementation of `InputBackend` utilizing `web_sys` bindings to input
 /// APIs
@@ -12,16 +15,30 @@ pub struct WebInputBackend {
     cursor_visible: bool,
     cursor: MouseCursor,
     last_key: KeyCode,
+
+    /// Whether the player is handling an event caused by the user.
+    /// Browsers only allow writing to the clipboard in response to user input.
+    is_handling_user_input: bool,
+
+    /// Whether the movie asked for every key, including browser shortcuts.
+    trap_all_keys: bool,
+
+    /// A hidden text area that is focused while a text field has focus, so that
+    /// the browser sends input method compositions to the player.
+    ime_input: HtmlTextAreaElement,
 }
 
 impl WebInputBackend {
-    pub fn new(canvas: &HtmlCanvasElement) -> Self {
+    pub fn new(canvas: &HtmlCanvasElement, ime_input: &HtmlTextAreaElement) -> Self {
         Self {
             keys_down: HashSet::new(),
             canvas: canvas.clone(),
+            ime_input: ime_input.clone(),
             cursor_visible: true,
             cursor: MouseCursor::Arrow,
             last_key: KeyCode::Unknown,
+            is_handling_user_input: false,
+            trap_all_keys: false,
         }
     }
 
@@ -31,6 +48,23 @@ impl WebInputBackend {
         self.keys_down.insert(code);
     }
 
+    /// Sets whether the player is handling an event caused by the user.
+    pub fn set_handling_user_input(&mut self, is_handling_user_input: bool) {
+        self.is_handling_user_input = is_handling_user_input;
+    }
+
+    /// Whether the browser should be stopped from handling a key press.
+    /// Browser shortcuts, like function keys and control key combinations, still work
+    /// unless the movie traps all keys.
+    pub fn should_prevent_default(&self, event: &KeyboardEvent) -> bool {
+        if self.trap_all_keys {
+            return true;
+        }
+        let code = event.code();
+        let is_function_key = code.starts_with('F') && code[1..].parse::<u8>().is_ok();
+        !(is_function_key || event.ctrl_key() || event.meta_key())
+    }
+
     /// Register a key release for a given code string.
     pub fn keyup(&mut self, code: String) {
         self.keys_down.remove(&code);
@@ -56,108 +90,42 @@ impl WebInputBackend {
 
 impl InputBackend for WebInputBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
-        match key {
-            KeyCode::Unknown => false,
-            KeyCode::Backspace => self.keys_down.contains("Backspace"),
-            KeyCode::Return => self.keys_down.contains("Enter"),
-            KeyCode::Shift => {
-                self.keys_down.contains("ShiftLeft") || self.keys_down.contains("ShiftRight")
-            }
-            KeyCode::Control => {
-                self.keys_down.contains("ControlLeft") || self.keys_down.contains("ControlRight")
-            }
-            KeyCode::Alt => {
-                self.keys_down.contains("AltLeft") || self.keys_down.contains("AltRight")
-            }
-            KeyCode::CapsLock => self.keys_down.contains("CapsLock"),
-            KeyCode::Escape => self.keys_down.contains("Escape"),
-            KeyCode::Space => self.keys_down.contains("Space"),
-            KeyCode::Key0 => self.keys_down.contains("Digit0"),
-            KeyCode::Key1 => self.keys_down.contains("Digit1"),
-            KeyCode::Key2 => self.keys_down.contains("Digit2"),
-            KeyCode::Key3 => self.keys_down.contains("Digit3"),
-            KeyCode::Key4 => self.keys_down.contains("Digit4"),
-            KeyCode::Key5 => self.keys_down.contains("Digit5"),
-            KeyCode::Key6 => self.keys_down.contains("Digit6"),
-            KeyCode::Key7 => self.keys_down.contains("Digit7"),
-            KeyCode::Key8 => self.keys_down.contains("Digit8"),
-            KeyCode::Key9 => self.keys_down.contains("Digit9"),
-            KeyCode::A => self.keys_down.contains("KeyA"),
-            KeyCode::B => self.keys_down.contains("KeyB"),
-            KeyCode::C => self.keys_down.contains("KeyC"),
-            KeyCode::D => self.keys_down.contains("KeyD"),
-            KeyCode::E => self.keys_down.contains("KeyE"),
-            KeyCode::F => self.keys_down.contains("KeyF"),
-            KeyCode::G => self.keys_down.contains("KeyG"),
-            KeyCode::H => self.keys_down.contains("KeyH"),
-            KeyCode::I => self.keys_down.contains("KeyI"),
-            KeyCode::J => self.keys_down.contains("KeyJ"),
-            KeyCode::K => self.keys_down.contains("KeyK"),
-            KeyCode::L => self.keys_down.contains("KeyL"),
-            KeyCode::M => self.keys_down.contains("KeyM"),
-            KeyCode::N => self.keys_down.contains("KeyN"),
-            KeyCode::O => self.keys_down.contains("KeyO"),
-            KeyCode::P => self.keys_down.contains("KeyP"),
-            KeyCode::Q => self.keys_down.contains("KeyQ"),
-            KeyCode::R => self.keys_down.contains("KeyR"),
-            KeyCode::S => self.keys_down.contains("KeyS"),
-            KeyCode::T => self.keys_down.contains("KeyT"),
-            KeyCode::U => self.keys_down.contains("KeyU"),
-            KeyCode::V => self.keys_down.contains("KeyV"),
-            KeyCode::W => self.keys_down.contains("KeyW"),
-            KeyCode::X => self.keys_down.contains("KeyX"),
-            KeyCode::Y => self.keys_down.contains("KeyY"),
-            KeyCode::Z => self.keys_down.contains("KeyZ"),
-            KeyCode::Semicolon => self.keys_down.contains("Semicolon"),
-            KeyCode::Equals => self.keys_down.contains("Equal"),
-            KeyCode::Comma => self.keys_down.contains("Comma"),
-            KeyCode::Minus => self.keys_down.contains("Minus"),
-            KeyCode::Period => self.keys_down.contains("Period"),
-            KeyCode::Slash => self.keys_down.contains("Slash"),
-            KeyCode::Grave => self.keys_down.contains("Backquote"),
-            KeyCode::LBracket => self.keys_down.contains("BracketLeft"),
-            KeyCode::Backslash => self.keys_down.contains("Backslash"),
-            KeyCode::RBracket => self.keys_down.contains("BracketRight"),
-            KeyCode::Apostrophe => self.keys_down.contains("Quote"),
-            KeyCode::Numpad0 => self.keys_down.contains("Numpad0"),
-            KeyCode::Numpad1 => self.keys_down.contains("Numpad1"),
-            KeyCode::Numpad2 => self.keys_down.contains("Numpad2"),
-            KeyCode::Numpad3 => self.keys_down.contains("Numpad3"),
-            KeyCode::Numpad4 => self.keys_down.contains("Numpad4"),
-            KeyCode::Numpad5 => self.keys_down.contains("Numpad5"),
-            KeyCode::Numpad6 => self.keys_down.contains("Numpad6"),
-            KeyCode::Numpad7 => self.keys_down.contains("Numpad7"),
-            KeyCode::Numpad8 => self.keys_down.contains("Numpad8"),
-            KeyCode::Numpad9 => self.keys_down.contains("Numpad9"),
-            KeyCode::Multiply => self.keys_down.contains("NumpadMultiply"),
-            KeyCode::Plus => self.keys_down.contains("NumpadAdd"),
-            KeyCode::NumpadMinus => self.keys_down.contains("NumpadSubtract"),
-            KeyCode::NumpadPeriod => self.keys_down.contains("NumpadDecimal"),
-            KeyCode::NumpadSlash => self.keys_down.contains("NumpadDivide"),
-            KeyCode::PgUp => self.keys_down.contains("PageUp"),
-            KeyCode::PgDown => self.keys_down.contains("PageDown"),
-            KeyCode::End => self.keys_down.contains("End"),
-            KeyCode::Home => self.keys_down.contains("Home"),
-            KeyCode::Left => self.keys_down.contains("ArrowLeft"),
-            KeyCode::Up => self.keys_down.contains("ArrowUp"),
-            KeyCode::Right => self.keys_down.contains("ArrowRight"),
-            KeyCode::Down => self.keys_down.contains("ArrowDown"),
-            KeyCode::Insert => self.keys_down.contains("Insert"),
-            KeyCode::Delete => self.keys_down.contains("Delete"),
-            KeyCode::Pause => self.keys_down.contains("Pause"),
-            KeyCode::ScrollLock => self.keys_down.contains("ScrollLock"),
-            KeyCode::F1 => self.keys_down.contains("F1"),
-            KeyCode::F2 => self.keys_down.contains("F2"),
-            KeyCode::F3 => self.keys_down.contains("F3"),
-            KeyCode::F4 => self.keys_down.contains("F4"),
-            KeyCode::F5 => self.keys_down.contains("F5"),
-            KeyCode::F6 => self.keys_down.contains("F6"),
-            KeyCode::F7 => self.keys_down.contains("F7"),
-            KeyCode::F8 => self.keys_down.contains("F8"),
-            KeyCode::F9 => self.keys_down.contains("F9"),
-            KeyCode::F10 => self.keys_down.contains("F10"),
-            KeyCode::F11 => self.keys_down.contains("F11"),
-            KeyCode::F12 => self.keys_down.contains("F12"),
+        // Some Flash key codes have more than one key, like the left and right shift keys.
+        self.keys_down
+            .iter()
+            .any(|code| web_to_ruffle_key_code(code) == Some(key))
+    }
+
+    fn set_trap_all_keys(&mut self, trap_all_keys: bool) {
+        self.trap_all_keys = trap_all_keys;
+    }
+
+    fn set_ime_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.ime_input.focus().warn_on_error();
+        } else {
+            self.ime_input.blur().warn_on_error();
+        }
+    }
+
+    fn set_full_screen(&mut self, full_screen: bool) -> bool {
+        let document = match web_sys::window().and_then(|window| window.document()) {
+            Some(document) => document,
+            None => return false,
+        };
+        if !full_screen {
+            document.exit_fullscreen();
+            return true;
+        }
+        // Browsers only allow fullscreen in response to user input, as Flash Player did.
+        if !self.is_handling_user_input {
+            log::warn!("Fullscreen can only be entered in response to user input");
+            return false;
+        }
+        // The canvas's container fills the screen, along with the player's controls.
+        match self.canvas.parent_element() {
+            Some(container) => container.request_fullscreen().is_ok(),
+            None => false,
         }
     }
 
@@ -184,17 +152,40 @@ impl InputBackend for WebInputBackend {
         self.update_mouse_cursor();
     }
 
-    fn set_clipboard_content(&mut self, _content: String) {
-        log::warn!("set clipboard not implemented");
+    fn set_clipboard_content(&mut self, content: String) {
+        if !self.is_handling_user_input {
+            log::warn!("System.setClipboard is only allowed in response to user input");
+            return;
+        }
+
+        if let Err(e) = write_clipboard_text(&content) {
+            log::warn!("Unable to set clipboard: {:?}", e);
+        }
     }
 }
 
+/// Writes text to the clipboard with the asynchronous Clipboard API.
+fn write_clipboard_text(text: &str) -> Result<(), JsValue> {
+    // `web_sys::Clipboard` is still unstable, so call `navigator.clipboard.writeText` directly.
+    let navigator = web_sys::window().ok_or("Expected window")?.navigator();
+    let clipboard = Reflect::get(&navigator, &"clipboard".into())?;
+    let write_text: Function = Reflect::get(&clipboard, &"writeText".into())?.dyn_into()?;
+    let promise: Promise = write_text.call1(&clipboard, &text.into())?.dyn_into()?;
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(promise).await {
+            log::warn!("Unable to set clipboard: {:?}", e);
+        }
+    });
+    Ok(())
+}
+
 /// Converts a Web `KeyboardEvent.code` value into a Ruffle `KeyCode`.
 /// Returns `None` if there is no matching Flash key key.
 pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
     let out = match key_code {
         "Backspace" => KeyCode::Backspace,
-        "Enter" => KeyCode::Return,
+        "Tab" => KeyCode::Tab,
+        "Enter" | "NumpadEnter" => KeyCode::Return,
         "ShiftLeft" | "ShiftRight" => KeyCode::Shift,
         "ControlLeft" | "ControlRight" => KeyCode::Control,
         "AltLeft" | "AltRight" => KeyCode::Alt,
@@ -263,6 +254,8 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
         "NumpadSubtract" => KeyCode::NumpadMinus,
         "NumpadDecimal" => KeyCode::NumpadPeriod,
         "NumpadDivide" => KeyCode::NumpadSlash,
+        "NumpadClear" => KeyCode::Clear,
+        "NumLock" => KeyCode::NumLock,
         "PageUp" => KeyCode::PgUp,
         "PageDown" => KeyCode::PgDown,
         "End" => KeyCode::End,
@@ -273,6 +266,7 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
         "ArrowDown" => KeyCode::Down,
         "Insert" => KeyCode::Insert,
         "Delete" => KeyCode::Delete,
+        "Help" => KeyCode::Help,
         "Pause" => KeyCode::Pause,
         "ScrollLock" => KeyCode::ScrollLock,
         "F1" => KeyCode::F1,
@@ -287,6 +281,9 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
         "F10" => KeyCode::F10,
         "F11" => KeyCode::F11,
         "F12" => KeyCode::F12,
+        "F13" => KeyCode::F13,
+        "F14" => KeyCode::F14,
+        "F15" => KeyCode::F15,
         _ => return None,
     };
     Some(out)
@@ -299,7 +296,7 @@ pub fn web_key_to_codepoint(key: &str) -> Option<char> {
     // Single character strings will be an actual printable char that we can use as text input.
     // All the other special values are multiple characters (e.g. "ArrowLeft").
     // It's probably better to explicitly match on all the variants.
-    if key.len() == 1 {
+    if key.chars().count() == 1 {
         key.chars().next()
     } else {
         None
@@ -1,24 +1,47 @@
 //! Ruffle web frontend.
+mod accessibility;
 mod audio;
+mod font;
 mod input;
+mod log_backend;
 mod navigator;
+mod printer;
 mod storage;
 
+use crate::accessibility::WebAccessibilityBackend;
+use crate::font::WebFontBackend;
+use crate::log_backend::WebLogBackend;
+use crate::navigator::JsNetworkObserver;
+use crate::printer::WebPrinterBackend;
 use crate::storage::LocalStorageBackend;
-use crate::{audio::WebAudioBackend, input::WebInputBackend, navigator::WebNavigatorBackend};
+use crate::{
+    audio::WebAudioBackend,
+    input::WebInputBackend,
+    navigator::{NavigatorCallbacks, WebNavigatorBackend},
+};
 use generational_arena::{Arena, Index};
 use js_sys::Uint8Array;
+use ruffle_core::backend::navigator::UrlRewriter;
 use ruffle_core::backend::render::RenderBackend;
 use ruffle_core::backend::storage::MemoryStorageBackend;
 use ruffle_core::backend::storage::StorageBackend;
-use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::events::{ImeEvent, MouseWheelDelta, TouchPhase};
+use ruffle_core::memory::GcParameters;
+use ruffle_core::parameters::{PlayerParameters, WindowMode};
+use ruffle_core::stage::StageDisplayState;
+use ruffle_core::tag_utils::{SwfDownload, SwfMovie};
 use ruffle_core::PlayerEvent;
 use ruffle_web_common::JsResult;
 use std::mem::drop;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{cell::RefCell, error::Error, num::NonZeroI32};
 use wasm_bindgen::{prelude::*, JsCast, JsValue};
-use web_sys::{Element, EventTarget, HtmlCanvasElement, HtmlElement, KeyboardEvent, PointerEvent};
+use web_sys::{
+    CompositionEvent, Element, EventTarget, HtmlCanvasElement, HtmlElement, HtmlTextAreaElement,
+    KeyboardEvent, PointerEvent, WheelEvent,
+};
 
 thread_local! {
     /// We store the actual instances of the ruffle core in a static pool.
@@ -42,10 +65,17 @@ struct RuffleInstance {
     mouse_move_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
     mouse_down_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
     mouse_up_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
+    mouse_wheel_callback: Option<Closure<dyn FnMut(WheelEvent)>>,
     window_mouse_down_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
     key_down_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     key_up_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
+    ime_input: HtmlTextAreaElement,
+    composition_update_callback: Option<Closure<dyn FnMut(CompositionEvent)>>,
+    composition_end_callback: Option<Closure<dyn FnMut(CompositionEvent)>>,
     has_focus: bool,
+    url_rewriter: Rc<RefCell<UrlRewriter>>,
+    navigator_callbacks: Rc<RefCell<NavigatorCallbacks>>,
+    download: Option<SwfDownload>,
 }
 
 /// An opaque handle to a `RuffleInstance` inside the pool.
@@ -55,10 +85,100 @@ struct RuffleInstance {
 #[derive(Clone)]
 pub struct Ruffle(Index);
 
+/// A movie that is still being downloaded, before a player has been created
+/// for it.
+///
+/// This type is exported to JS, and is passed to `Ruffle::new_streaming` once
+/// enough of the movie has arrived to start playing it.
+#[wasm_bindgen]
+pub struct MovieDownload(SwfDownload);
+
+#[wasm_bindgen]
+impl MovieDownload {
+    /// Adds the next chunk of the file. Returns whether enough of the movie
+    /// has arrived to create a player for it.
+    pub fn append(&mut self, chunk: Uint8Array) -> Result<bool, JsValue> {
+        self.0
+            .append(&chunk.to_vec())
+            .map_err(|_| "Invalid SWF file")?;
+        Ok(self.0.movie().is_some())
+    }
+
+    /// Marks the file as complete, once all of it has arrived.
+    pub fn finish(&mut self) -> Result<(), JsValue> {
+        self.0.finish().map_err(|_| "Invalid SWF file")?;
+        Ok(())
+    }
+}
+
 #[wasm_bindgen]
 impl Ruffle {
-    pub fn new(parent: HtmlElement, swf_data: Uint8Array) -> Result<Ruffle, JsValue> {
-        Ruffle::new_internal(parent, swf_data).map_err(|_| "Error creating player".into())
+    /// Creates a player in the given element. `parameters` is an object of the
+    /// embed parameters of the movie, such as `flashvars`. `swf_url` is the URL the
+    /// movie was loaded from, if any.
+    pub fn new(
+        parent: HtmlElement,
+        swf_data: Uint8Array,
+        parameters: JsValue,
+        swf_url: Option<String>,
+    ) -> Result<Ruffle, JsValue> {
+        let movie = SwfMovie::from_data(&swf_data.to_vec(), swf_url)
+            .map_err(|_| "Error creating player")?;
+        Ruffle::new_internal(parent, movie, parameters, None)
+            .map_err(|_| "Error creating player".into())
+    }
+
+    /// Starts downloading a movie from `swf_url`. Its chunks are added to the
+    /// returned `MovieDownload` as they arrive.
+    pub fn start_download(swf_url: Option<String>) -> MovieDownload {
+        MovieDownload(SwfDownload::new(swf_url))
+    }
+
+    /// Creates a player in the given element for a movie that is still being
+    /// downloaded, once `MovieDownload::append` has returned `true`.
+    ///
+    /// The rest of the file is passed to `append_data` and `finish_download`,
+    /// and the movie plays its frames as they arrive.
+    pub fn new_streaming(
+        parent: HtmlElement,
+        download: MovieDownload,
+        parameters: JsValue,
+    ) -> Result<Ruffle, JsValue> {
+        let movie = download.0.movie().ok_or("SWF file is too short")?;
+        Ruffle::new_internal(parent, (*movie).clone(), parameters, Some(download.0))
+            .map_err(|_| "Error creating player".into())
+    }
+
+    /// Adds the next chunk of the movie being downloaded.
+    pub fn append_data(&mut self, chunk: Uint8Array) -> Result<(), JsValue> {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                if let Some(download) = &mut instance.download {
+                    let movie = download
+                        .append(&chunk.to_vec())
+                        .map_err(|_| "Invalid SWF file")?;
+                    if let Some(movie) = movie {
+                        instance.core.lock().unwrap().append_movie_data(movie);
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Marks the movie being downloaded as complete, once all of it has arrived.
+    pub fn finish_download(&mut self) -> Result<(), JsValue> {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                if let Some(mut download) = instance.download.take() {
+                    let movie = download.finish().map_err(|_| "Invalid SWF file")?;
+                    instance.core.lock().unwrap().append_movie_data(movie);
+                }
+            }
+            Ok(())
+        })
     }
 
     pub fn play(&mut self) {
@@ -71,6 +191,454 @@ impl Ruffle {
         });
     }
 
+    /// Pauses or resumes the movie and its audio, keeping its state.
+    pub fn set_paused(&mut self, paused: bool) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().set_paused(paused);
+            }
+        });
+    }
+
+    /// Whether the movie is running: it was started, and isn't paused.
+    pub fn is_playing(&self) -> bool {
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            instances.get(self.0).map_or(false, |instance| {
+                let core = instance.core.lock().unwrap();
+                core.is_playing() && !core.is_paused()
+            })
+        })
+    }
+
+    /// Returns an object describing the movie, with its `width` and `height` in
+    /// pixels, its `frameRate` and its `swfVersion`.
+    pub fn metadata(&self) -> js_sys::Object {
+        let result = js_sys::Object::new();
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            if let Some(instance) = instances.get(self.0) {
+                let core = instance.core.lock().unwrap();
+                let _ = js_sys::Reflect::set(&result, &"width".into(), &core.movie_width().into());
+                let _ =
+                    js_sys::Reflect::set(&result, &"height".into(), &core.movie_height().into());
+                let _ = js_sys::Reflect::set(
+                    &result,
+                    &"frameRate".into(),
+                    &core.movie_frame_rate().into(),
+                );
+                let _ =
+                    js_sys::Reflect::set(&result, &"swfVersion".into(), &core.swf_version().into());
+            }
+        });
+        result
+    }
+
+    /// Defines variables on `_root` from a URL-encoded string, as the `flashvars`
+    /// embed parameter does.
+    pub fn set_flash_vars(&mut self, flash_vars: String) {
+        let mut parameters = PlayerParameters::new();
+        parameters.set("flashvars", &flash_vars);
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .set_flash_vars(&parameters.flash_vars);
+            }
+        });
+    }
+
+    /// Runs a single frame of the movie, and leaves it paused.
+    pub fn step_frame(&mut self) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().step_frame();
+            }
+        });
+    }
+
+    /// Runs the movie at the given frame rate, or at its own frame rate if `undefined`.
+    pub fn set_frame_rate(&mut self, frame_rate: Option<f64>) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .set_frame_rate_override(frame_rate);
+            }
+        });
+    }
+
+    /// Sets how fast the movie runs, such as 0.5 for slow motion or 2 to fast-forward.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().set_time_scale(time_scale);
+            }
+        });
+    }
+
+    /// The volume of the player, from 0 to 1.
+    pub fn volume(&self) -> f32 {
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            instances
+                .get(self.0)
+                .map_or(1.0, |instance| instance.core.lock().unwrap().volume())
+        })
+    }
+
+    /// Sets the volume of the player, from 0 to 1.
+    pub fn set_volume(&mut self, volume: f32) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().set_volume(volume);
+            }
+        });
+    }
+
+    /// Mutes or unmutes the player.
+    ///
+    /// Browsers block audio until the user interacts with the page, so an embed
+    /// can start muted and unmute on the first gesture.
+    pub fn set_muted(&mut self, muted: bool) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                let mut core = instance.core.lock().unwrap();
+                core.set_muted(muted);
+                if !muted {
+                    core.audio_mut().prime_audio();
+                }
+            }
+        });
+    }
+
+    /// Loads URLs starting with `from` from `to` instead, for movies whose servers
+    /// are gone.
+    pub fn add_url_rewrite(&mut self, from: String, to: String) {
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            if let Some(instance) = instances.get(self.0) {
+                instance.url_rewriter.borrow_mut().add_prefix(from, to);
+            }
+        });
+    }
+
+    /// Calls `callback` with the URL of each load. The callback returns the URL to
+    /// load instead, or `null` to leave the URL alone.
+    pub fn add_url_rewrite_callback(&mut self, callback: js_sys::Function) {
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            if let Some(instance) = instances.get(self.0) {
+                instance.url_rewriter.borrow_mut().add_callback(move |url| {
+                    match callback.call1(&JsValue::NULL, &url.into()) {
+                        Ok(value) => value.as_string(),
+                        Err(e) => {
+                            log::error!("URL rewrite callback failed: {:?}", e);
+                            None
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Calls `callback` with the URL and target window of each page the movie opens
+    /// with `getURL`. If the callback returns `true`, the page isn't opened.
+    pub fn set_navigation_callback(&mut self, callback: Option<js_sys::Function>) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.navigator_callbacks.borrow_mut().navigation = callback;
+            }
+        });
+    }
+
+    /// Tells the movie whether the player fills the screen, when the page enters or
+    /// leaves fullscreen, so that `Stage.displayState` follows it.
+    pub fn set_full_screen(&mut self, full_screen: bool) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .set_display_state(if full_screen {
+                        StageDisplayState::FullScreen
+                    } else {
+                        StageDisplayState::Normal
+                    });
+            }
+        });
+    }
+
+    /// Builds the context menu for a right-click on the movie, as an array of objects
+    /// with the `caption`, `enabled`, `separatorBefore` and `checked` of each item.
+    /// The page adds its own items, and calls `run_context_menu_callback` with the
+    /// index of the chosen item of this array.
+    pub fn prepare_context_menu(&mut self) -> js_sys::Array {
+        let result = js_sys::Array::new();
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                let items = instance.core.lock().unwrap().prepare_context_menu();
+                for item in items {
+                    let object = js_sys::Object::new();
+                    let _ = js_sys::Reflect::set(&object, &"caption".into(), &item.caption.into());
+                    let _ = js_sys::Reflect::set(&object, &"enabled".into(), &item.enabled.into());
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"separatorBefore".into(),
+                        &item.separator_before.into(),
+                    );
+                    let _ = js_sys::Reflect::set(&object, &"checked".into(), &item.checked.into());
+                    result.push(&object);
+                }
+            }
+        });
+        result
+    }
+
+    /// Runs the item at `index` of the menu returned by `prepare_context_menu`.
+    pub fn run_context_menu_callback(&mut self, index: usize) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .run_context_menu_callback(index);
+            }
+        });
+    }
+
+    /// Calls `callback` with the command and arguments of each FSCommand that the
+    /// player doesn't handle itself. If the callback returns `true`, it handled it.
+    pub fn set_fs_command_callback(&mut self, callback: Option<js_sys::Function>) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.navigator_callbacks.borrow_mut().fs_command = callback;
+            }
+        });
+    }
+
+    /// Calls `callback` with each message of `trace()`, and each error shown to the
+    /// author of the movie, and its level, `"trace"` or `"warning"`. If the callback
+    /// returns `true`, it handled the message and it isn't logged to the console.
+    pub fn set_log_callback(&mut self, callback: Option<js_sys::Function>) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .log_mut()
+                    .downcast_mut::<WebLogBackend>()
+                    .unwrap()
+                    .set_callback(callback);
+            }
+        });
+    }
+
+    /// Calls `callback` with an object describing each request the movie makes,
+    /// and each response it gets, for debugging.
+    pub fn add_network_observer(&mut self, callback: js_sys::Function) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance
+                    .core
+                    .lock()
+                    .unwrap()
+                    .navigator_mut()
+                    .add_network_observer(Rc::new(JsNetworkObserver::new(callback)));
+            }
+        });
+    }
+
+    /// Returns the shared objects saved by the movie, as an object mapping their
+    /// names to their data in `Uint8Array`s, so that they can be backed up.
+    pub fn export_saves(&mut self) -> js_sys::Object {
+        let result = js_sys::Object::new();
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                let saves = instance.core.lock().unwrap().export_saves();
+                for (name, data) in saves {
+                    let _ =
+                        js_sys::Reflect::set(&result, &name.into(), &Uint8Array::from(&data[..]));
+                }
+            }
+        });
+        result
+    }
+
+    /// Saves shared objects from an object like the one returned by `export_saves`,
+    /// replacing any saves with the same names. Returns whether all of them were saved.
+    pub fn import_saves(&mut self, saves: js_sys::Object) -> bool {
+        let saves: Vec<(String, Vec<u8>)> = js_sys::Object::entries(&saves)
+            .iter()
+            .filter_map(|entry| {
+                let entry = js_sys::Array::from(&entry);
+                let name = entry.get(0).as_string()?;
+                let data = entry.get(1).dyn_into::<Uint8Array>().ok()?;
+                Some((name, data.to_vec()))
+            })
+            .collect();
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            match instances.get_mut(self.0) {
+                Some(instance) => instance.core.lock().unwrap().import_saves(&saves),
+                None => false,
+            }
+        })
+    }
+
+    /// Starts recording how long each part of the player takes per frame, keeping the
+    /// timings of the last `frames` frames.
+    pub fn start_profiling(&mut self, frames: usize) {
+        let performance = match web_sys::window().and_then(|window| window.performance()) {
+            Some(performance) => performance,
+            None => return,
+        };
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().start_profiling(
+                    Box::new(move || Duration::from_secs_f64(performance.now() / 1000.0)),
+                    frames,
+                );
+            }
+        });
+    }
+
+    pub fn stop_profiling(&mut self) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                instance.core.lock().unwrap().stop_profiling();
+            }
+        });
+    }
+
+    /// Returns the timings of the last frames, oldest first, as objects with the
+    /// `frame` number and the milliseconds spent on `actionScript`, `tags`,
+    /// `tessellation`, `rendering`, `audio` and `garbageCollection`, and in `total`.
+    pub fn frame_timings(&self) -> js_sys::Array {
+        let result = js_sys::Array::new();
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            if let Some(instance) = instances.get(self.0) {
+                let ms = |duration: Duration| JsValue::from(duration.as_secs_f64() * 1000.0);
+                for timings in instance.core.lock().unwrap().frame_timings() {
+                    let object = js_sys::Object::new();
+                    let _ = js_sys::Reflect::set(&object, &"frame".into(), &timings.frame.into());
+                    let _ =
+                        js_sys::Reflect::set(&object, &"actionScript".into(), &ms(timings.avm1));
+                    let _ = js_sys::Reflect::set(&object, &"tags".into(), &ms(timings.tags));
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"tessellation".into(),
+                        &ms(timings.tessellation),
+                    );
+                    let _ =
+                        js_sys::Reflect::set(&object, &"rendering".into(), &ms(timings.rendering));
+                    let _ = js_sys::Reflect::set(&object, &"audio".into(), &ms(timings.audio));
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"garbageCollection".into(),
+                        &ms(timings.garbage_collection),
+                    );
+                    let _ = js_sys::Reflect::set(&object, &"total".into(), &ms(timings.total()));
+                    result.push(&object);
+                }
+            }
+        });
+        result
+    }
+
+    /// Returns an object describing the memory in use, with the `allocatedBytes` and
+    /// `peakAllocatedBytes` of the garbage-collected heap, and the numbers of
+    /// `displayObjects` and `avm1Objects`.
+    pub fn gc_stats(&mut self) -> js_sys::Object {
+        let result = js_sys::Object::new();
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get_mut(self.0) {
+                let stats = instance.core.lock().unwrap().gc_stats();
+                let _ = js_sys::Reflect::set(
+                    &result,
+                    &"allocatedBytes".into(),
+                    &(stats.allocated_bytes as f64).into(),
+                );
+                let _ = js_sys::Reflect::set(
+                    &result,
+                    &"peakAllocatedBytes".into(),
+                    &(stats.peak_allocated_bytes as f64).into(),
+                );
+                let _ = js_sys::Reflect::set(
+                    &result,
+                    &"displayObjects".into(),
+                    &(stats.display_objects as f64).into(),
+                );
+                let _ = js_sys::Reflect::set(
+                    &result,
+                    &"avm1Objects".into(),
+                    &(stats.avm1_objects as f64).into(),
+                );
+            }
+        });
+        result
+    }
+
+    /// Returns the features the movie used that Ruffle doesn't implement, as objects
+    /// with the `kind` and `name` of each feature, the `count` of uses and the
+    /// `firstFrame` it was used on.
+    pub fn unimplemented_features(&self) -> js_sys::Array {
+        let result = js_sys::Array::new();
+        INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            if let Some(instance) = instances.get(self.0) {
+                let core = instance.core.lock().unwrap();
+                for feature in core.unimplemented_features().iter() {
+                    let object = js_sys::Object::new();
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"kind".into(),
+                        &feature.kind.to_string().into(),
+                    );
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"name".into(),
+                        &feature.name.as_str().into(),
+                    );
+                    let _ = js_sys::Reflect::set(&object, &"count".into(), &feature.count.into());
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &"firstFrame".into(),
+                        &feature.first_frame.into(),
+                    );
+                    result.push(&object);
+                }
+            }
+        });
+        result
+    }
+
     pub fn destroy(&mut self) -> Result<(), JsValue> {
         // Remove instance from the active list.
         if let Some(mut instance) = INSTANCES.with(|instances| {
@@ -78,6 +646,7 @@ impl Ruffle {
             instances.remove(self.0)
         }) {
             instance.canvas.remove();
+            instance.ime_input.remove();
 
             // Stop all audio playing from the instance
             let mut player = instance.core.lock().unwrap();
@@ -90,7 +659,10 @@ impl Ruffle {
             instance.mouse_down_callback = None;
             instance.mouse_move_callback = None;
             instance.mouse_up_callback = None;
+            instance.mouse_wheel_callback = None;
             instance.window_mouse_down_callback = None;
+            instance.composition_update_callback = None;
+            instance.composition_end_callback = None;
 
             // Cancel the animation handler, if it's still active.
             if let Some(id) = instance.animation_handler_id {
@@ -106,27 +678,39 @@ impl Ruffle {
 }
 
 impl Ruffle {
-    fn new_internal(parent: HtmlElement, swf_data: Uint8Array) -> Result<Ruffle, Box<dyn Error>> {
+    fn new_internal(
+        parent: HtmlElement,
+        movie: SwfMovie,
+        parameters: JsValue,
+        download: Option<SwfDownload>,
+    ) -> Result<Ruffle, Box<dyn Error>> {
         console_error_panic_hook::set_once();
         let _ = console_log::init_with_level(log::Level::Trace);
 
-        let movie = {
-            let mut data = vec![0; swf_data.length() as usize];
-            swf_data.copy_to(&mut data[..]);
-            SwfMovie::from_data(&data)?
-        };
-
         let window = web_sys::window().ok_or_else(|| "Expected window")?;
         let document = window.document().ok_or("Expected document")?;
 
-        let (canvas, renderer) = create_renderer(&document)?;
+        let parameters = parse_parameters(&parameters);
+        let is_transparent = parameters.window_mode == WindowMode::Transparent;
+
+        let (canvas, renderer) = create_renderer(&document, is_transparent)?;
         parent
             .append_child(&canvas.clone().into())
             .into_js_result()?;
 
+        let ime_input = create_ime_input(&document)?;
+        parent
+            .append_child(&ime_input.clone().into())
+            .into_js_result()?;
+
         let audio = Box::new(WebAudioBackend::new()?);
-        let navigator = Box::new(WebNavigatorBackend::new());
-        let input = Box::new(WebInputBackend::new(&canvas));
+        let url_rewriter = Rc::new(RefCell::new(UrlRewriter::new()));
+        let navigator_callbacks = Rc::new(RefCell::new(NavigatorCallbacks::default()));
+        let navigator = Box::new(WebNavigatorBackend::new(
+            Rc::clone(&url_rewriter),
+            Rc::clone(&navigator_callbacks),
+        ));
+        let input = Box::new(WebInputBackend::new(&canvas, &ime_input));
 
         let current_domain = window.location().href().unwrap();
 
@@ -138,11 +722,28 @@ impl Ruffle {
             })
             .unwrap_or_else(|| Box::new(MemoryStorageBackend::default()));
 
-        let core =
-            ruffle_core::Player::new(renderer, audio, navigator, input, movie, local_storage)?;
+        let font = Box::new(WebFontBackend::new(&document)?);
+        let printer = Box::new(WebPrinterBackend::new(window.clone()));
+        let accessibility = Box::new(WebAccessibilityBackend::new(document.clone(), &parent)?);
+        let log = Box::new(WebLogBackend::new());
+
+        let core = ruffle_core::Player::new(
+            renderer,
+            audio,
+            navigator,
+            input,
+            movie,
+            local_storage,
+            font,
+            printer,
+            accessibility,
+            log,
+            GcParameters::default(),
+        )?;
         let mut core_lock = core.lock().unwrap();
         let frame_rate = core_lock.frame_rate();
         core_lock.audio_mut().set_frame_rate(frame_rate);
+        core_lock.apply_parameters(&parameters);
         drop(core_lock);
 
         // Create instance.
@@ -158,10 +759,17 @@ impl Ruffle {
             mouse_down_callback: None,
             window_mouse_down_callback: None,
             mouse_up_callback: None,
+            mouse_wheel_callback: None,
             key_down_callback: None,
             key_up_callback: None,
+            ime_input: ime_input.clone(),
+            composition_update_callback: None,
+            composition_end_callback: None,
             timestamp: None,
             has_focus: false,
+            url_rewriter,
+            navigator_callbacks,
+            download,
         };
 
         // Prevent touch-scrolling on canvas.
@@ -189,10 +797,11 @@ impl Ruffle {
                     INSTANCES.with(move |instances| {
                         let mut instances = instances.borrow_mut();
                         if let Some(instance) = instances.get_mut(index) {
-                            let event = PlayerEvent::MouseMove {
-                                x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
-                                y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
-                            };
+                            let event = pointer_to_player_event(
+                                &js_event,
+                                instance.device_pixel_ratio,
+                                TouchPhase::Move,
+                            );
                             instance.core.lock().unwrap().handle_event(event);
                             if instance.has_focus {
                                 js_event.prevent_default();
@@ -219,16 +828,22 @@ impl Ruffle {
                         let mut instances = instances.borrow_mut();
                         if let Some(instance) = instances.get_mut(index) {
                             instance.has_focus = true;
+                            // Other mouse buttons open the context menu, or aren't seen
+                            // by Flash movies.
+                            if js_event.pointer_type() == "mouse" && js_event.button() != 0 {
+                                return;
+                            }
                             if let Some(target) = js_event.current_target() {
                                 let _ = target
                                     .unchecked_ref::<Element>()
                                     .set_pointer_capture(js_event.pointer_id());
                             }
-                            let event = PlayerEvent::MouseDown {
-                                x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
-                                y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
-                            };
-                            instance.core.lock().unwrap().handle_event(event);
+                            let event = pointer_to_player_event(
+                                &js_event,
+                                instance.device_pixel_ratio,
+                                TouchPhase::Start,
+                            );
+                            handle_user_input(&instance.core, event);
                             js_event.prevent_default();
                         }
                     });
@@ -281,11 +896,20 @@ impl Ruffle {
                                     .unchecked_ref::<Element>()
                                     .release_pointer_capture(js_event.pointer_id());
                             }
-                            let event = PlayerEvent::MouseUp {
-                                x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
-                                y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
+                            if js_event.pointer_type() == "mouse" && js_event.button() != 0 {
+                                return;
+                            }
+                            let phase = if js_event.type_() == "pointercancel" {
+                                TouchPhase::Cancel
+                            } else {
+                                TouchPhase::End
                             };
-                            instance.core.lock().unwrap().handle_event(event);
+                            let event = pointer_to_player_event(
+                                &js_event,
+                                instance.device_pixel_ratio,
+                                phase,
+                            );
+                            handle_user_input(&instance.core, event);
                             if instance.has_focus {
                                 js_event.prevent_default();
                             }
@@ -300,10 +924,51 @@ impl Ruffle {
                         mouse_up_callback.as_ref().unchecked_ref(),
                     )
                     .unwrap();
+                // Touches are cancelled when the browser takes them over for a gesture.
+                canvas_events
+                    .add_event_listener_with_callback(
+                        "pointercancel",
+                        mouse_up_callback.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
                 let instance = instances.get_mut(index).unwrap();
                 instance.mouse_up_callback = Some(mouse_up_callback);
             }
 
+            // Create mouse wheel handler.
+            {
+                let mouse_wheel_callback = Closure::wrap(Box::new(move |js_event: WheelEvent| {
+                    INSTANCES.with(move |instances| {
+                        let mut instances = instances.borrow_mut();
+                        if let Some(instance) = instances.get_mut(index) {
+                            // Browsers scroll down for positive deltas, unlike Flash.
+                            let delta = -js_event.delta_y();
+                            let delta = match js_event.delta_mode() {
+                                WheelEvent::DOM_DELTA_LINE => MouseWheelDelta::Lines(delta),
+                                // Treat scrolling by a page as a single notch of the wheel.
+                                WheelEvent::DOM_DELTA_PAGE => MouseWheelDelta::Lines(delta * 3.0),
+                                _ => MouseWheelDelta::Pixels(delta),
+                            };
+                            let event = PlayerEvent::MouseWheel { delta };
+                            instance.core.lock().unwrap().handle_event(event);
+                            if instance.has_focus {
+                                js_event.prevent_default();
+                            }
+                        }
+                    });
+                })
+                    as Box<dyn FnMut(WheelEvent)>);
+                let canvas_events: &EventTarget = canvas.as_ref();
+                canvas_events
+                    .add_event_listener_with_callback(
+                        "wheel",
+                        mouse_wheel_callback.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                let instance = instances.get_mut(index).unwrap();
+                instance.mouse_wheel_callback = Some(mouse_wheel_callback);
+            }
+
             // Create click event handler.
             // {
             //     let click_callback = Closure::wrap(Box::new(move |_| {
@@ -333,7 +998,12 @@ impl Ruffle {
                 let key_down_callback = Closure::wrap(Box::new(move |js_event: KeyboardEvent| {
                     INSTANCES.with(|instances| {
                         if let Some(instance) = instances.borrow_mut().get_mut(index) {
-                            if instance.has_focus {
+                            // Key presses that are part of a composition are left to the
+                            // browser, which reports the composed text separately.
+                            if instance.has_focus
+                                && !js_event.is_composing()
+                                && js_event.key() != "Process"
+                            {
                                 let code = js_event.code();
                                 instance
                                     .core
@@ -347,22 +1017,29 @@ impl Ruffle {
                                 if let Some(codepoint) =
                                     input::web_key_to_codepoint(&js_event.key())
                                 {
-                                    instance
-                                        .core
-                                        .lock()
-                                        .unwrap()
-                                        .handle_event(PlayerEvent::TextInput { codepoint });
+                                    handle_user_input(
+                                        &instance.core,
+                                        PlayerEvent::TextInput { codepoint },
+                                    );
                                 }
 
                                 if let Some(key_code) = input::web_to_ruffle_key_code(&code) {
-                                    instance
-                                        .core
-                                        .lock()
-                                        .unwrap()
-                                        .handle_event(PlayerEvent::KeyDown { key_code });
+                                    handle_user_input(
+                                        &instance.core,
+                                        PlayerEvent::KeyDown { key_code },
+                                    );
                                 }
 
-                                js_event.prevent_default();
+                                let prevent_default = instance
+                                    .core
+                                    .lock()
+                                    .unwrap()
+                                    .input()
+                                    .downcast_ref::<WebInputBackend>()
+                                    .map_or(true, |input| input.should_prevent_default(&js_event));
+                                if prevent_default {
+                                    js_event.prevent_default();
+                                }
                             }
                         }
                     });
@@ -396,11 +1073,10 @@ impl Ruffle {
                                     .keyup(code.clone());
 
                                 if let Some(key_code) = input::web_to_ruffle_key_code(&code) {
-                                    instance
-                                        .core
-                                        .lock()
-                                        .unwrap()
-                                        .handle_event(PlayerEvent::KeyUp { key_code });
+                                    handle_user_input(
+                                        &instance.core,
+                                        PlayerEvent::KeyUp { key_code },
+                                    );
                                 }
 
                                 js_event.prevent_default();
@@ -419,6 +1095,55 @@ impl Ruffle {
                 instance.key_up_callback = Some(key_up_callback);
             }
 
+            // Create input method composition handlers.
+            {
+                let composition_update_callback =
+                    Closure::wrap(Box::new(move |js_event: CompositionEvent| {
+                        INSTANCES.with(|instances| {
+                            if let Some(instance) = instances.borrow_mut().get_mut(index) {
+                                let text = js_event.data().unwrap_or_default();
+                                handle_user_input(
+                                    &instance.core,
+                                    PlayerEvent::Ime(ImeEvent::Preedit(text)),
+                                );
+                            }
+                        });
+                    }) as Box<dyn FnMut(CompositionEvent)>);
+                let composition_end_callback =
+                    Closure::wrap(Box::new(move |js_event: CompositionEvent| {
+                        INSTANCES.with(|instances| {
+                            if let Some(instance) = instances.borrow_mut().get_mut(index) {
+                                let text = js_event.data().unwrap_or_default();
+                                let event = if text.is_empty() {
+                                    ImeEvent::Cancel
+                                } else {
+                                    ImeEvent::Commit(text)
+                                };
+                                handle_user_input(&instance.core, PlayerEvent::Ime(event));
+                                // The composed text now lives in the text field.
+                                instance.ime_input.set_value("");
+                            }
+                        });
+                    }) as Box<dyn FnMut(CompositionEvent)>);
+
+                let ime_input_events: &EventTarget = ime_input.as_ref();
+                ime_input_events
+                    .add_event_listener_with_callback(
+                        "compositionupdate",
+                        composition_update_callback.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                ime_input_events
+                    .add_event_listener_with_callback(
+                        "compositionend",
+                        composition_end_callback.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                let instance = instances.get_mut(index).unwrap();
+                instance.composition_update_callback = Some(composition_update_callback);
+                instance.composition_end_callback = Some(composition_end_callback);
+            }
+
             ruffle
         });
 
@@ -474,7 +1199,11 @@ impl Ruffle {
                     instance.canvas.set_width(viewport_width);
                     instance.canvas.set_height(viewport_height);
 
-                    core_lock.set_viewport_dimensions(viewport_width, viewport_height);
+                    core_lock.set_viewport_dimensions(
+                        viewport_width,
+                        viewport_height,
+                        instance.device_pixel_ratio,
+                    );
                     core_lock
                         .renderer_mut()
                         .set_viewport_dimensions(viewport_width, viewport_height);
@@ -502,8 +1231,55 @@ impl Ruffle {
     }
 }
 
+/// Sends an event caused by the user to the player.
+///
+/// Browsers only allow some actions, such as writing to the clipboard, while
+/// handling user input, so the input backend is told when that is the case.
+fn handle_user_input(core: &Mutex<ruffle_core::Player>, event: PlayerEvent) {
+    let mut core = core.lock().unwrap();
+    let set_user_input = |core: &mut ruffle_core::Player, is_user_input| {
+        core.input_mut()
+            .downcast_mut::<WebInputBackend>()
+            .unwrap()
+            .set_handling_user_input(is_user_input);
+    };
+    set_user_input(&mut core, true);
+    core.navigator_mut().set_handling_user_input(true);
+    core.handle_event(event);
+    set_user_input(&mut core, false);
+    core.navigator_mut().set_handling_user_input(false);
+}
+
+/// Converts a pointer event into a player event at the pointer's position.
+///
+/// Touches are sent as touch events, which the player turns into mouse events
+/// itself, while other pointers act as the mouse.
+fn pointer_to_player_event(
+    js_event: &PointerEvent,
+    device_pixel_ratio: f64,
+    phase: TouchPhase,
+) -> PlayerEvent {
+    let x = f64::from(js_event.offset_x()) * device_pixel_ratio;
+    let y = f64::from(js_event.offset_y()) * device_pixel_ratio;
+    if js_event.pointer_type() == "touch" {
+        PlayerEvent::Touch {
+            id: js_event.pointer_id() as u64,
+            phase,
+            x,
+            y,
+        }
+    } else {
+        match phase {
+            TouchPhase::Start => PlayerEvent::MouseDown { x, y },
+            TouchPhase::Move => PlayerEvent::MouseMove { x, y },
+            TouchPhase::End | TouchPhase::Cancel => PlayerEvent::MouseUp { x, y },
+        }
+    }
+}
+
 fn create_renderer(
     document: &web_sys::Document,
+    is_transparent: bool,
 ) -> Result<(HtmlCanvasElement, Box<dyn RenderBackend>), Box<dyn Error>> {
     #[cfg(not(any(feature = "canvas", feature = "webgl")))]
     std::compile_error!("You must enable one of the render backend features (e.g., webgl).");
@@ -519,7 +1295,8 @@ fn create_renderer(
             .into_js_result()?
             .dyn_into()
             .map_err(|_| "Expected HtmlCanvasElement")?;
-        if let Ok(renderer) = ruffle_render_webgl::WebGlRenderBackend::new(&canvas) {
+        if let Ok(renderer) = ruffle_render_webgl::WebGlRenderBackend::new(&canvas, is_transparent)
+        {
             return Ok((canvas, Box::new(renderer)));
         }
     }
@@ -532,10 +1309,53 @@ fn create_renderer(
             .into_js_result()?
             .dyn_into()
             .map_err(|_| "Expected HtmlCanvasElement")?;
-        if let Ok(renderer) = ruffle_render_canvas::WebCanvasRenderBackend::new(&canvas) {
+        if let Ok(renderer) =
+            ruffle_render_canvas::WebCanvasRenderBackend::new(&canvas, is_transparent)
+        {
             return Ok((canvas, Box::new(renderer)));
         }
     }
 
     Err("Unable to create renderer".into())
 }
+
+/// Reads the embed parameters given by JS, an object of name/value strings.
+/// Values that aren't strings are ignored.
+fn parse_parameters(parameters: &JsValue) -> PlayerParameters {
+    let mut player_parameters = PlayerParameters::new();
+    if parameters.is_object() {
+        for entry in js_sys::Object::entries(parameters.unchecked_ref()).iter() {
+            let entry = js_sys::Array::from(&entry);
+            if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string())
+            {
+                player_parameters.set(&name, &value);
+            }
+        }
+    }
+    player_parameters
+}
+
+/// Creates the hidden text area that receives input method compositions while a
+/// text field has focus. Browsers only show an input method editor for editable elements.
+fn create_ime_input(document: &web_sys::Document) -> Result<HtmlTextAreaElement, Box<dyn Error>> {
+    let ime_input: HtmlTextAreaElement = document
+        .create_element("textarea")
+        .into_js_result()?
+        .dyn_into()
+        .map_err(|_| "Expected HtmlTextAreaElement")?;
+    ime_input.set_tab_index(-1);
+    ime_input
+        .set_attribute("aria-hidden", "true")
+        .into_js_result()?;
+    let style = ime_input.style();
+    for (property, value) in &[
+        ("position", "absolute"),
+        ("width", "1px"),
+        ("height", "1px"),
+        ("opacity", "0"),
+        ("pointer-events", "none"),
+    ] {
+        style.set_property(property, value).into_js_result()?;
+    }
+    Ok(ime_input)
+}
@@ -0,0 +1,52 @@
+//! Log backend for web
+
+use ruffle_core::backend::log::LogBackend;
+use wasm_bindgen::JsValue;
+
+/// Log backend that passes the output of the movie to a JS function, or writes it
+/// to the browser console with the rest of the log.
+#[derive(Default)]
+pub struct WebLogBackend {
+    /// A JS function called with each message and its level, `"trace"` or
+    /// `"warning"`. It returns `true` if it handled the message.
+    callback: Option<js_sys::Function>,
+}
+
+impl WebLogBackend {
+    pub fn new() -> Self {
+        Self { callback: None }
+    }
+
+    pub fn set_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.callback = callback;
+    }
+
+    /// Lets the embedder handle a message. Returns `true` if it did.
+    fn call_callback(&self, message: &str, level: &str) -> bool {
+        let callback = match &self.callback {
+            Some(callback) => callback,
+            None => return false,
+        };
+        match callback.call2(&JsValue::NULL, &message.into(), &level.into()) {
+            Ok(handled) => handled.as_bool().unwrap_or(false),
+            Err(e) => {
+                log::error!("Log callback failed: {:?}", e);
+                false
+            }
+        }
+    }
+}
+
+impl LogBackend for WebLogBackend {
+    fn avm_trace(&mut self, message: &str) {
+        if !self.call_callback(message, "trace") {
+            log::info!(target: "avm_trace", "{}", message);
+        }
+    }
+
+    fn avm_warning(&mut self, message: &str) {
+        if !self.call_callback(message, "warning") {
+            log::warn!(target: "avm_trace", "{}", message);
+        }
+    }
+}
@@ -1,29 +1,81 @@
 //! Navigator backend for web
 
-use js_sys::{Array, ArrayBuffer, Uint8Array};
+use js_sys::{Array, ArrayBuffer, Object, Reflect, Uint8Array};
 use ruffle_core::backend::navigator::{
-    NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    NavigationMethod, NavigatorBackend, NetworkObserver, NetworkObservers, OwnedFuture,
+    RequestInfo, RequestOptions, ResponseInfo, UrlRewriter, WindowTarget,
 };
 use ruffle_core::loader::Error;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::{window, Blob, BlobPropertyBag, Performance, Request, RequestInit, Response};
 
+/// JS functions that the page sets to handle what the movie does. These are shared
+/// with the `Ruffle` handle, so that they can be changed while the movie plays.
+#[derive(Default)]
+pub struct NavigatorCallbacks {
+    /// Called with the URL and target of each `getURL` navigation. If it returns
+    /// `true`, it handled the navigation and the page isn't changed.
+    pub navigation: Option<js_sys::Function>,
+    /// Called with the command and arguments of each FSCommand that the player
+    /// doesn't handle. It returns `true` if it handled the command.
+    pub fs_command: Option<js_sys::Function>,
+}
+
 pub struct WebNavigatorBackend {
     performance: Performance,
     start_time: f64,
+
+    /// Rewrites the URLs of fetches. This is shared with the `Ruffle` handle, so
+    /// that rules can be added from JS.
+    url_rewriter: Rc<RefCell<UrlRewriter>>,
+
+    /// The functions that handle navigations and FSCommands, shared with the `Ruffle` handle.
+    callbacks: Rc<RefCell<NavigatorCallbacks>>,
+
+    /// Whether the player is handling user input. Browsers block new windows
+    /// that aren't opened in response to user input.
+    is_handling_user_input: bool,
+
+    /// Watch the requests made by the movie.
+    network_observers: NetworkObservers,
 }
 
 impl WebNavigatorBackend {
-    pub fn new() -> Self {
+    pub fn new(
+        url_rewriter: Rc<RefCell<UrlRewriter>>,
+        callbacks: Rc<RefCell<NavigatorCallbacks>>,
+    ) -> Self {
         let window = web_sys::window().expect("window()");
         let performance = window.performance().expect("window.performance()");
 
         WebNavigatorBackend {
             start_time: performance.now(),
             performance,
+            url_rewriter,
+            callbacks,
+            is_handling_user_input: false,
+            network_observers: NetworkObservers::new(),
+        }
+    }
+
+    /// Lets the embedder handle a navigation. Returns `true` if it did.
+    fn call_navigation_callback(&self, url: &str, target: &WindowTarget) -> bool {
+        let callbacks = self.callbacks.borrow();
+        let callback = match &callbacks.navigation {
+            Some(callback) => callback,
+            None => return false,
+        };
+        match callback.call2(&JsValue::NULL, &url.into(), &target.as_str().into()) {
+            Ok(handled) => handled.as_bool().unwrap_or(false),
+            Err(e) => {
+                log::error!("Navigation callback failed: {:?}", e);
+                false
+            }
         }
     }
 }
@@ -35,11 +87,23 @@ impl NavigatorBackend for WebNavigatorBackend {
         window_spec: Option<String>,
         vars_method: Option<(NavigationMethod, HashMap<String, String>)>,
     ) {
+        let target = WindowTarget::from_window_spec(window_spec.as_deref());
+        if self.call_navigation_callback(&url, &target) {
+            return;
+        }
+        if target.may_open_window() && !self.is_handling_user_input {
+            log::warn!(
+                "Opening {} in window {} outside of user input; the browser may block it",
+                url,
+                target.as_str()
+            );
+        }
+
         if let Some(window) = window() {
             //TODO: Should we return a result for failed opens? Does Flash care?
             #[allow(unused_must_use)]
-            match (vars_method, window_spec) {
-                (Some((navmethod, formvars)), window_spec) => {
+            match (vars_method, target) {
+                (Some((navmethod, formvars)), target) => {
                     let document = match window.document() {
                         Some(document) => document,
                         None => return,
@@ -61,9 +125,7 @@ impl NavigatorBackend for WebNavigatorBackend {
 
                     form.set_attribute("action", &url);
 
-                    if let Some(target) = window_spec {
-                        form.set_attribute("target", &target);
-                    }
+                    form.set_attribute("target", target.as_str());
 
                     for (k, v) in formvars.iter() {
                         let hidden = document.create_element("hidden").unwrap();
@@ -78,12 +140,14 @@ impl NavigatorBackend for WebNavigatorBackend {
                     document.body().unwrap().append_child(&form);
                     form.submit();
                 }
-                (_, Some(ref window_name)) if window_name != "" => {
-                    window.open_with_url_and_target(&url, window_name);
-                }
-                _ => {
+                (None, WindowTarget::Current) => {
                     window.location().assign(&url);
                 }
+                (None, target) => {
+                    if let Ok(None) = window.open_with_url_and_target(&url, target.as_str()) {
+                        log::warn!("The browser blocked opening {} in a new window", url);
+                    }
+                }
             };
         }
     }
@@ -94,8 +158,12 @@ impl NavigatorBackend for WebNavigatorBackend {
     }
 
     fn fetch(&self, url: &str, options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
-        let url = url.to_string();
-        Box::pin(async move {
+        let url = self.url_rewriter.borrow().rewrite(url);
+        let request_info = self.network_observers.request(&url, &options);
+        let network_observers = self.network_observers.clone();
+        let status = Rc::new(Cell::new(None));
+        let fetch_status = Rc::clone(&status);
+        let fetch = async move {
             let mut init = RequestInit::new();
 
             init.method(match options.method() {
@@ -127,6 +195,12 @@ impl NavigatorBackend for WebNavigatorBackend {
             }
 
             let request = Request::new_with_str_and_init(&url, &init).unwrap();
+            for (name, value) in options.headers() {
+                // Browsers refuse to send some headers, like `Cookie`.
+                if request.headers().set(name, value).is_err() {
+                    log::warn!("Unable to set request header {}", name);
+                }
+            }
 
             let window = web_sys::window().unwrap();
             let fetchval = JsFuture::from(window.fetch_with_request(&request)).await;
@@ -138,6 +212,10 @@ impl NavigatorBackend for WebNavigatorBackend {
             }
 
             let resp: Response = fetchval.unwrap().dyn_into().unwrap();
+            fetch_status.set(Some(resp.status()));
+            if !resp.ok() {
+                return Err(Error::HttpNotOk(resp.status()));
+            }
             let data: ArrayBuffer = JsFuture::from(resp.array_buffer().unwrap())
                 .await
                 .unwrap()
@@ -148,6 +226,12 @@ impl NavigatorBackend for WebNavigatorBackend {
             jsarray.copy_to(&mut rust_array);
 
             Ok(rust_array)
+        };
+
+        Box::pin(async move {
+            let result = fetch.await;
+            network_observers.response(&request_info, status.get(), &result);
+            result
         })
     }
 
@@ -158,4 +242,87 @@ impl NavigatorBackend for WebNavigatorBackend {
             }
         })
     }
+
+    fn set_handling_user_input(&mut self, is_handling_user_input: bool) {
+        self.is_handling_user_input = is_handling_user_input;
+    }
+
+    fn add_network_observer(&mut self, observer: Rc<dyn NetworkObserver>) {
+        self.network_observers.add(observer);
+    }
+
+    fn fs_command(&mut self, command: &str, args: &str) -> bool {
+        let callbacks = self.callbacks.borrow();
+        let callback = match &callbacks.fs_command {
+            Some(callback) => callback,
+            None => return false,
+        };
+        match callback.call2(&JsValue::NULL, &command.into(), &args.into()) {
+            Ok(handled) => handled.as_bool().unwrap_or(false),
+            Err(e) => {
+                log::error!("FSCommand callback failed: {:?}", e);
+                false
+            }
+        }
+    }
+}
+
+/// Reports requests to a JS function, which is called with an object describing
+/// each request and response.
+pub struct JsNetworkObserver {
+    callback: js_sys::Function,
+}
+
+impl JsNetworkObserver {
+    pub fn new(callback: js_sys::Function) -> Self {
+        Self { callback }
+    }
+
+    fn call(&self, fields: &[(&str, JsValue)]) {
+        let event = Object::new();
+        for (name, value) in fields {
+            let _ = Reflect::set(&event, &(*name).into(), value);
+        }
+        if let Err(e) = self.callback.call1(&JsValue::NULL, &event) {
+            log::error!("Network observer callback failed: {:?}", e);
+        }
+    }
+}
+
+impl NetworkObserver for JsNetworkObserver {
+    fn request_sent(&self, request: &RequestInfo) {
+        let method = match request.method {
+            NavigationMethod::GET => "GET",
+            NavigationMethod::POST => "POST",
+        };
+        self.call(&[
+            ("type", "request".into()),
+            ("id", (request.id as f64).into()),
+            ("url", request.url.as_str().into()),
+            ("method", method.into()),
+            ("bodySize", (request.body_size as f64).into()),
+        ]);
+    }
+
+    fn response_received(&self, response: &ResponseInfo) {
+        self.call(&[
+            ("type", "response".into()),
+            ("id", (response.id as f64).into()),
+            ("url", response.url.as_str().into()),
+            (
+                "status",
+                response
+                    .status
+                    .map_or(JsValue::NULL, |status| status.into()),
+            ),
+            ("size", (response.size as f64).into()),
+            (
+                "error",
+                response
+                    .error
+                    .as_deref()
+                    .map_or(JsValue::NULL, |error| error.into()),
+            ),
+        ]);
+    }
 }
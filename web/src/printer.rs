@@ -0,0 +1,105 @@
+use ruffle_core::backend::printer::{PrintJobSettings, PrinterBackend};
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use ruffle_web_common::JsResult;
+use std::error::Error;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, Window};
+
+/// Printer backend that prints pages with the browser's print dialog.
+///
+/// Each page is drawn to a canvas in a new window, which is then printed.
+pub struct WebPrinterBackend {
+    window: Window,
+    settings: PrintJobSettings,
+    pages: Vec<Bitmap>,
+}
+
+impl WebPrinterBackend {
+    pub fn new(window: Window) -> Self {
+        Self {
+            window,
+            settings: PrintJobSettings::default(),
+            pages: vec![],
+        }
+    }
+
+    fn print_pages(&mut self) -> Result<(), Box<dyn Error>> {
+        let print_window = self
+            .window
+            .open_with_url_and_target("", "_blank")
+            .into_js_result()?
+            .ok_or("Unable to open print window")?;
+        let document = print_window.document().ok_or("Expected document")?;
+        let body = document.body().ok_or("Expected body")?;
+
+        for page in self.pages.drain(..) {
+            let canvas: HtmlCanvasElement = document
+                .create_element("canvas")
+                .into_js_result()?
+                .dyn_into()
+                .map_err(|_| "Expected HtmlCanvasElement")?;
+            canvas.set_width(page.width);
+            canvas.set_height(page.height);
+
+            // Pages are rendered at a higher resolution than the page size in points.
+            let style = canvas.style();
+            style
+                .set_property("width", &format!("{}pt", self.settings.page_width))
+                .into_js_result()?;
+            style
+                .set_property("height", &format!("{}pt", self.settings.page_height))
+                .into_js_result()?;
+            style.set_property("break-after", "page").into_js_result()?;
+
+            let context: CanvasRenderingContext2d = canvas
+                .get_context("2d")
+                .into_js_result()?
+                .ok_or("Could not create context")?
+                .dyn_into()
+                .map_err(|_| "Expected CanvasRenderingContext2d")?;
+            let mut rgba = match page.data {
+                BitmapFormat::Rgba(data) => data,
+                BitmapFormat::Rgb(data) => data
+                    .chunks_exact(3)
+                    .flat_map(|pixel| pixel.iter().copied().chain(std::iter::once(255)))
+                    .collect(),
+            };
+            let image_data = ImageData::new_with_u8_clamped_array_and_sh(
+                Clamped(&mut rgba),
+                page.width,
+                page.height,
+            )
+            .into_js_result()?;
+            context
+                .put_image_data(&image_data, 0.0, 0.0)
+                .into_js_result()?;
+            body.append_child(&canvas).into_js_result()?;
+        }
+
+        print_window.print().into_js_result()?;
+        Ok(())
+    }
+}
+
+impl PrinterBackend for WebPrinterBackend {
+    fn start_job(&mut self) -> Option<PrintJobSettings> {
+        // Browsers don't expose the paper size, so assume the default.
+        self.pages.clear();
+        Some(self.settings.clone())
+    }
+
+    fn add_page(&mut self, page: Bitmap) {
+        self.pages.push(page);
+    }
+
+    fn send_job(&mut self) {
+        if self.pages.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.print_pages() {
+            log::error!("Unable to print: {}", e);
+        }
+        self.pages.clear();
+    }
+}
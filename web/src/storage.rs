@@ -1,31 +1,166 @@
-use ruffle_core::backend::storage::StorageBackend;
+use js_sys::{Array, Promise, Uint8Array};
+use ruffle_core::backend::storage::{decode_hex, StorageBackend};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::Storage;
 
+/// Entries larger than this are saved in IndexedDB rather than `localStorage`,
+/// which browsers limit to around 5MB per origin.
+const LOCAL_STORAGE_ENTRY_LIMIT: usize = 256 * 1024;
+
+/// Marks binary data saved as base64 in `localStorage`.
+const BASE64_PREFIX: &str = "base64:";
+
+/// Saves shared objects in `localStorage`.
+///
+/// Binary data is saved base64-encoded. Data that is too large for `localStorage`
+/// is saved in IndexedDB instead. IndexedDB can only be used asynchronously, so
+/// its entries are loaded into memory when the backend is created, and writes are
+/// sent to it in the background.
 pub struct LocalStorageBackend {
     storage: Storage,
     prefix: String,
+
+    /// The entries of this movie that are saved in IndexedDB.
+    database_entries: Rc<RefCell<HashMap<String, Vec<u8>>>>,
 }
 
 impl LocalStorageBackend {
     pub(crate) fn new(storage: Storage, prefix: String) -> Self {
-        LocalStorageBackend { storage, prefix }
+        let database_entries = Rc::new(RefCell::new(HashMap::new()));
+        {
+            let database_entries = Rc::clone(&database_entries);
+            let key_prefix = format!("{}-", prefix);
+            spawn_local(async move {
+                match JsFuture::from(storage_db_load(&key_prefix)).await {
+                    Ok(entries) => {
+                        let mut database_entries = database_entries.borrow_mut();
+                        for entry in Array::from(&entries).iter() {
+                            let entry = Array::from(&entry);
+                            if let Some(key) = entry.get(0).as_string() {
+                                let data: Uint8Array = entry.get(1).unchecked_into();
+                                // Entries saved while loading are newer.
+                                database_entries.entry(key).or_insert_with(|| data.to_vec());
+                            }
+                        }
+                    }
+                    Err(e) => log::info!("Unable to load shared objects from IndexedDB: {:?}", e),
+                }
+            });
+        }
+
+        LocalStorageBackend {
+            storage,
+            prefix,
+            database_entries,
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}-{}", self.prefix, name)
+    }
+
+    /// Saves an entry in IndexedDB, and removes it from `localStorage`.
+    fn put_database_entry(&mut self, key: String, value: &[u8]) {
+        let _ = self.storage.delete(&key);
+        spawn_database_request(storage_db_put(&key, value));
+        self.database_entries
+            .borrow_mut()
+            .insert(key, value.to_vec());
+    }
+
+    /// Removes an entry from IndexedDB, if it is saved there.
+    fn remove_database_entry(&mut self, key: &str) {
+        if self.database_entries.borrow_mut().remove(key).is_some() {
+            spawn_database_request(storage_db_delete(key));
+        }
     }
 }
 
 impl StorageBackend for LocalStorageBackend {
     fn get_string(&self, name: &str) -> Option<String> {
-        self.storage
-            .get(&format!("{}-{}", self.prefix, name))
-            .unwrap_or_default()
+        self.storage.get(&self.key(name)).unwrap_or_default()
     }
 
     fn put_string(&mut self, name: &str, value: String) -> bool {
-        self.storage
-            .set(&format!("{}-{}", self.prefix, name), &value)
-            .is_ok()
+        let key = self.key(name);
+        self.remove_database_entry(&key);
+        self.storage.set(&key, &value).is_ok()
+    }
+
+    fn get_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        let key = self.key(name);
+        if let Some(data) = self.database_entries.borrow().get(&key) {
+            return Some(data.clone());
+        }
+        let value = self.storage.get(&key).unwrap_or_default()?;
+        if value.starts_with(BASE64_PREFIX) {
+            base64::decode(&value[BASE64_PREFIX.len()..]).ok()
+        } else {
+            // Saved by older versions.
+            decode_hex(&value)
+        }
+    }
+
+    fn put_bytes(&mut self, name: &str, value: &[u8]) -> bool {
+        let key = self.key(name);
+        let encoded = format!("{}{}", BASE64_PREFIX, base64::encode(value));
+        if encoded.len() <= LOCAL_STORAGE_ENTRY_LIMIT && self.storage.set(&key, &encoded).is_ok() {
+            self.remove_database_entry(&key);
+        } else {
+            // Too large, or `localStorage` is full.
+            log::info!("Saving shared object {} in IndexedDB", name);
+            self.put_database_entry(key, value);
+        }
+        true
     }
 
     fn remove_key(&mut self, name: &str) {
-        let _ = self.storage.delete(&format!("{}-{}", self.prefix, name));
+        let key = self.key(name);
+        let _ = self.storage.delete(&key);
+        self.remove_database_entry(&key);
     }
+
+    fn get_keys(&self) -> Vec<String> {
+        let key_prefix = self.key("");
+        let mut keys: Vec<String> = self.database_entries.borrow().keys().cloned().collect();
+        for i in 0..self.storage.length().unwrap_or(0) {
+            if let Ok(Some(key)) = self.storage.key(i) {
+                keys.push(key);
+            }
+        }
+        let mut keys: Vec<String> = keys
+            .into_iter()
+            .filter(|key| key.starts_with(&key_prefix))
+            .map(|key| key[key_prefix.len()..].to_string())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+fn spawn_database_request(request: Promise) {
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(request).await {
+            log::error!("Unable to save shared object in IndexedDB: {:?}", e);
+        }
+    });
+}
+
+#[wasm_bindgen(module = "/packages/core/src/ruffle-imports.js")]
+extern "C" {
+    /// Imported JS method to load the IndexedDB entries whose keys start with
+    /// `prefix`, as an array of `[key, Uint8Array]` pairs.
+    fn storage_db_load(prefix: &str) -> Promise;
+
+    /// Imported JS method to save an entry in IndexedDB.
+    fn storage_db_put(key: &str, data: &[u8]) -> Promise;
+
+    /// Imported JS method to delete an entry from IndexedDB.
+    fn storage_db_delete(key: &str) -> Promise;
 }